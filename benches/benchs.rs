@@ -5,7 +5,7 @@ extern crate rand;
 extern crate rand_pcg;
 extern crate test;
 
-use evalexpr::build_operator_tree;
+use evalexpr::{build_operator_tree, context_map, EmptyContext, Value};
 use rand::{distributions::Uniform, seq::SliceRandom, Rng, SeedableRng};
 use rand_pcg::Pcg32;
 use std::{fmt::Write, hint::black_box};
@@ -136,3 +136,121 @@ fn bench_evaluate_large_tuple_expression(bencher: &mut Bencher) {
 
     bencher.iter(|| large_tuple_expression.eval().unwrap());
 }
+
+// Evaluates the same trees as the compiled benchmarks with the same context, for comparison.
+#[bench]
+fn bench_evaluate_uncompiled_long_expression_chains(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(0);
+    let long_expression_chain =
+        build_operator_tree(&generate_expression_chain(BENCHMARK_LEN, &mut gen)).unwrap();
+
+    bencher.iter(|| {
+        long_expression_chain
+            .eval_with_context(&EmptyContext)
+            .unwrap()
+    });
+}
+
+#[bench]
+fn bench_evaluate_uncompiled_deep_expression_trees(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(15);
+    let deep_expression_tree =
+        build_operator_tree(&generate_expression(BENCHMARK_LEN, &mut gen)).unwrap();
+
+    bencher.iter(|| {
+        deep_expression_tree
+            .eval_with_context(&EmptyContext)
+            .unwrap()
+    });
+}
+
+#[bench]
+fn bench_evaluate_uncompiled_many_small_expressions(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(33);
+    let small_expressions: Vec<_> = generate_small_expressions(BENCHMARK_LEN, &mut gen)
+        .iter()
+        .map(|expression| build_operator_tree(expression).unwrap())
+        .collect();
+
+    bencher.iter(|| {
+        for expression in &small_expressions {
+            black_box(expression.eval_with_context(&EmptyContext).unwrap());
+        }
+    });
+}
+
+#[bench]
+fn bench_evaluate_compiled_long_expression_chains(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(0);
+    let long_expression_chain =
+        build_operator_tree(&generate_expression_chain(BENCHMARK_LEN, &mut gen))
+            .unwrap()
+            .compile();
+
+    bencher.iter(|| {
+        long_expression_chain
+            .eval_with_context(&EmptyContext)
+            .unwrap()
+    });
+}
+
+#[bench]
+fn bench_evaluate_compiled_deep_expression_trees(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(15);
+    let deep_expression_tree = build_operator_tree(&generate_expression(BENCHMARK_LEN, &mut gen))
+        .unwrap()
+        .compile();
+
+    bencher.iter(|| {
+        deep_expression_tree
+            .eval_with_context(&EmptyContext)
+            .unwrap()
+    });
+}
+
+#[bench]
+fn bench_evaluate_compiled_many_small_expressions(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(33);
+    let small_expressions: Vec<_> = generate_small_expressions(BENCHMARK_LEN, &mut gen)
+        .iter()
+        .map(|expression| build_operator_tree(expression).unwrap().compile())
+        .collect();
+
+    bencher.iter(|| {
+        for expression in &small_expressions {
+            black_box(expression.eval_with_context(&EmptyContext).unwrap());
+        }
+    });
+}
+
+#[bench]
+fn bench_clone_deep_expression_trees(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(15);
+    let deep_expression_tree =
+        build_operator_tree(&generate_expression(BENCHMARK_LEN, &mut gen)).unwrap();
+
+    bencher.iter(|| black_box(deep_expression_tree.clone()));
+}
+
+#[bench]
+fn bench_clone_compiled_deep_expression_trees(bencher: &mut Bencher) {
+    let mut gen = Pcg32::seed_from_u64(15);
+    let deep_expression_tree = build_operator_tree(&generate_expression(BENCHMARK_LEN, &mut gen))
+        .unwrap()
+        .compile();
+
+    bencher.iter(|| black_box(deep_expression_tree.clone()));
+}
+
+#[bench]
+fn bench_evaluate_reads_of_large_variables(bencher: &mut Bencher) {
+    let values = Value::Array((0..BENCHMARK_LEN as i64).map(Value::from).collect());
+    let context = context_map! { "values" => values }.unwrap();
+    let expression = build_operator_tree("len(values) + values[500]").unwrap();
+
+    bencher.iter(|| {
+        for _ in 0..100 {
+            black_box(expression.eval_with_context(&context).unwrap());
+        }
+    });
+}
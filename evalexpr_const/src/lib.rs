@@ -0,0 +1,256 @@
+//! Macros that evaluate expressions of [evalexpr](https://docs.rs/evalexpr) at compile time, so static formulas are not parsed at runtime.
+//!
+//! The macros parse and evaluate expressions with evalexpr itself, so unlike the derive macro of evalexpr,
+//! they cannot be re-exported by evalexpr and are used from this crate directly.
+//! Expressions are evaluated with the features of evalexpr that are enabled for this crate,
+//! so values only evaluate the same at runtime if the same features are enabled.
+
+#![forbid(unsafe_code)]
+#![deny(missing_docs)]
+
+use evalexpr::{Node, Operator, Value};
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::{parse_macro_input, LitStr};
+
+/// Evaluates a constant expression at compile time and expands to its value as a Rust literal.
+///
+/// Integers expand to `i64` literals, floats to `f64` literals, booleans to `bool` literals, strings to `&'static str` literals
+/// and the empty value to `()`.
+/// Tuples and arrays expand to Rust tuples and arrays of their elements, so the elements of arrays need to have the same type.
+/// As the value is a literal, it can initialize constants.
+///
+/// The expression may call builtin functions and assign variables, but not read variables that it did not assign.
+/// Expressions that cannot be parsed or evaluated, and values without a literal like maps, are reported as compile errors.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr_const::eval_const;
+///
+/// const SECONDS_PER_WEEK: i64 = eval_const!("7 * 24 * 60 * 60");
+/// assert_eq!(SECONDS_PER_WEEK, 604800);
+/// assert_eq!(eval_const!("(1, max(2.5, 1), str::to_uppercase(\"a\"))"), (1, 2.5, "A"));
+/// ```
+#[proc_macro]
+pub fn eval_const(input: TokenStream) -> TokenStream {
+    let expression = parse_macro_input!(input as LitStr);
+    expand_eval_const(&expression)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Parses an expression at compile time and expands to an expression that builds its operator tree as an `evalexpr::Node`.
+///
+/// Subexpressions that only depend on constants are evaluated while expanding, like by `Node::simplify` with an empty context,
+/// so the built tree neither needs to be parsed nor simplified at runtime.
+/// Expressions that cannot be parsed, and operators with missing operands like in `1 +`, are reported as compile errors.
+/// Unlike trees built by `evalexpr::build_operator_tree`, the built tree has no source and spans.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+/// use evalexpr_const::operator_tree;
+///
+/// let tree = operator_tree!("price * (1 + 19 / 100.0)");
+/// assert_eq!(tree.to_string(), "price * 1.19");
+///
+/// let context = context_map! { "price" => 100.0 }.unwrap(); // Do proper error handling here
+/// assert_eq!(tree.eval_with_context(&context), Ok(Value::from(119.0)));
+/// ```
+#[proc_macro]
+pub fn operator_tree(input: TokenStream) -> TokenStream {
+    let expression = parse_macro_input!(input as LitStr);
+    expand_operator_tree(&expression)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_eval_const(expression: &LitStr) -> syn::Result<TokenStream2> {
+    let value = evalexpr::eval(&expression.value())
+        .map_err(|error| syn::Error::new(expression.span(), error))?;
+    value_to_literal(&value).ok_or_else(|| {
+        syn::Error::new(
+            expression.span(),
+            format!("the value `{}` cannot be written as a Rust literal", value),
+        )
+    })
+}
+
+fn expand_operator_tree(expression: &LitStr) -> syn::Result<TokenStream2> {
+    let tree = evalexpr::build_operator_tree(&expression.value())
+        .map_err(|error| syn::Error::new(expression.span(), error))?;
+    let tree = tree.simplify(&evalexpr::HashMapContext::new());
+    node_to_tokens(&tree, expression.span())
+}
+
+/// Returns the Rust literal of a value, or `None` if the value has no literal.
+fn value_to_literal(value: &Value) -> Option<TokenStream2> {
+    Some(match value {
+        Value::String(string) => string.to_token_stream(),
+        Value::Float(float) if float.is_nan() => quote!(::core::f64::NAN),
+        Value::Float(float) if float.is_infinite() && *float > 0.0 => {
+            quote!(::core::f64::INFINITY)
+        },
+        Value::Float(float) if float.is_infinite() => quote!(::core::f64::NEG_INFINITY),
+        Value::Float(float) => Literal::f64_suffixed(*float).into_token_stream(),
+        Value::Int(int) => Literal::i64_suffixed(*int).into_token_stream(),
+        Value::Boolean(boolean) => boolean.to_token_stream(),
+        Value::Tuple(elements) => {
+            let elements = elements
+                .iter()
+                .map(value_to_literal)
+                .collect::<Option<Vec<_>>>()?;
+            quote!((#(#elements,)*))
+        },
+        Value::Array(elements) => {
+            let elements = elements
+                .iter()
+                .map(value_to_literal)
+                .collect::<Option<Vec<_>>>()?;
+            quote!([#(#elements),*])
+        },
+        Value::Empty => quote!(()),
+        _ => return None,
+    })
+}
+
+/// Returns an expression that builds the given operator tree.
+fn node_to_tokens(node: &Node, span: Span) -> syn::Result<TokenStream2> {
+    // The parser accepts operators with missing operands, which only fail when they are evaluated.
+    let placeholders = node.children().iter().map(|_| Node::constant(())).collect();
+    Node::with_checked_children(node.operator().clone(), placeholders)
+        .map_err(|error| syn::Error::new(span, error))?;
+
+    let operator = operator_to_tokens(node.operator(), span)?;
+    let children = node
+        .children()
+        .iter()
+        .map(|child| node_to_tokens(child, span))
+        .collect::<syn::Result<Vec<_>>>()?;
+    Ok(quote! {
+        ::evalexpr::Node::with_checked_children(
+            ::evalexpr::Operator::#operator,
+            ::core::convert::From::from([#(#children),*]),
+        )
+        .expect("the operator tree was checked when expanding the macro")
+    })
+}
+
+/// Returns the variant of an operator, which is written after `::evalexpr::Operator::`.
+fn operator_to_tokens(operator: &Operator, span: Span) -> syn::Result<TokenStream2> {
+    use evalexpr::Operator::*;
+    Ok(match operator {
+        RootNode => quote!(RootNode),
+        Add => quote!(Add),
+        Sub => quote!(Sub),
+        Neg => quote!(Neg),
+        Pos => quote!(Pos),
+        Mul => quote!(Mul),
+        Div => quote!(Div),
+        Mod => quote!(Mod),
+        Exp => quote!(Exp),
+        Eq => quote!(Eq),
+        Neq => quote!(Neq),
+        Gt => quote!(Gt),
+        Lt => quote!(Lt),
+        Geq => quote!(Geq),
+        Leq => quote!(Leq),
+        In => quote!(In),
+        And => quote!(And),
+        Or => quote!(Or),
+        Not => quote!(Not),
+        NullCoalesce => quote!(NullCoalesce),
+        Assign => quote!(Assign),
+        AddAssign => quote!(AddAssign),
+        SubAssign => quote!(SubAssign),
+        MulAssign => quote!(MulAssign),
+        DivAssign => quote!(DivAssign),
+        ModAssign => quote!(ModAssign),
+        ExpAssign => quote!(ExpAssign),
+        AndAssign => quote!(AndAssign),
+        OrAssign => quote!(OrAssign),
+        Let => quote!(Let),
+        Tuple => quote!(Tuple),
+        Chain => quote!(Chain),
+        Array => quote!(Array),
+        Index => quote!(Index),
+        Range { inclusive } => quote!(Range { inclusive: #inclusive }),
+        Slice { inclusive } => quote!(Slice { inclusive: #inclusive }),
+        Const { value } => {
+            let value = value_to_tokens(value, span)?;
+            quote!(Const { value: #value })
+        },
+        VariableIdentifierWrite { identifier } => {
+            let identifier: &str = identifier;
+            quote!(VariableIdentifierWrite { identifier: ::core::convert::From::from(#identifier) })
+        },
+        VariableIdentifierRead { identifier } => {
+            let identifier: &str = identifier;
+            quote!(VariableIdentifierRead { identifier: ::core::convert::From::from(#identifier) })
+        },
+        FunctionIdentifier { identifier } => {
+            let identifier: &str = identifier;
+            quote!(FunctionIdentifier { identifier: ::core::convert::From::from(#identifier) })
+        },
+        Lambda { parameters } => quote! {
+            Lambda {
+                parameters: ::core::convert::From::from([
+                    #(::core::convert::From::from(#parameters)),*
+                ]),
+            }
+        },
+    })
+}
+
+/// Returns an expression that constructs the given value.
+fn value_to_tokens(value: &Value, span: Span) -> syn::Result<TokenStream2> {
+    let values_to_tokens = |values: &[Value]| {
+        values
+            .iter()
+            .map(|value| value_to_tokens(value, span))
+            .collect::<syn::Result<Vec<_>>>()
+    };
+
+    Ok(match value {
+        Value::String(string) => {
+            quote!(::evalexpr::Value::String(::core::convert::From::from(#string)))
+        },
+        Value::Float(float) => {
+            // The bits keep special values like `NaN` and `-0.0` exactly.
+            let bits = float.to_bits();
+            quote!(::evalexpr::Value::Float(::core::primitive::f64::from_bits(#bits)))
+        },
+        Value::Int(int) => quote!(::evalexpr::Value::Int(#int)),
+        Value::Boolean(boolean) => quote!(::evalexpr::Value::Boolean(#boolean)),
+        Value::Tuple(elements) => {
+            let elements = values_to_tokens(elements)?;
+            quote!(::evalexpr::Value::Tuple(::core::convert::From::from([#(#elements),*])))
+        },
+        Value::Array(elements) => {
+            let elements = values_to_tokens(elements)?;
+            quote!(::evalexpr::Value::Array(::core::convert::From::from([#(#elements),*])))
+        },
+        Value::Map(map) => {
+            let keys = map.keys();
+            let values = values_to_tokens(&map.values().cloned().collect::<Vec<_>>())?;
+            quote! {
+                ::evalexpr::Value::Map(::core::iter::FromIterator::from_iter([
+                    #((::core::convert::From::from(#keys), #values)),*
+                ]))
+            }
+        },
+        Value::Empty => quote!(::evalexpr::Value::Empty),
+        _ => {
+            return Err(syn::Error::new(
+                span,
+                format!(
+                    "the constant `{}` cannot be embedded into the operator tree",
+                    value
+                ),
+            ))
+        },
+    })
+}
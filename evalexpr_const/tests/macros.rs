@@ -0,0 +1,43 @@
+use evalexpr::*;
+use evalexpr_const::{eval_const, operator_tree};
+
+#[test]
+fn test_eval_const() {
+    const SUM: i64 = eval_const!("2 * 3 + 4");
+    assert_eq!(SUM, 10);
+    assert_eq!(eval_const!("1 / 2.0 - 1"), -0.5);
+    assert_eq!(eval_const!("(1 < 2 && !false, 1 > 2)"), (true, false));
+    assert_eq!(eval_const!("\"a\" + str::to_uppercase(\"b\\\"\")"), "aB\"");
+    assert_eq!(eval_const!("x = 2; (x, x * 1.5, ())"), (2, 3.0, ()));
+    assert_eq!(eval_const!("[1, 2, 3]"), [1, 2, 3]);
+    assert!(eval_const!("0.0 / 0").is_nan());
+}
+
+#[test]
+fn test_operator_tree() {
+    let expression = "a = 1 < b <= 3; f = |x| x * (2 + 3); if(a, [1.5, ()], f(c))";
+    let tree = operator_tree!("a = 1 < b <= 3; f = |x| x * (2 + 3); if(a, [1.5, ()], f(c))");
+    assert_eq!(
+        tree,
+        build_operator_tree(expression)
+            .unwrap()
+            .simplify(&HashMapContext::new())
+    );
+    assert_eq!(
+        tree.to_string(),
+        "a = 1 < b <= 3; f = |x| x * 5; if(a, [1.5, ()], f(c))"
+    );
+
+    let mut context = context_map! { "b" => 2, "c" => 4 }.unwrap();
+    assert_eq!(
+        tree.eval_with_context_mut(&mut context),
+        Ok(Value::Array(vec![Value::from(1.5), Value::Empty]))
+    );
+
+    let tree = operator_tree!("(1, \"x\", [true], -0.0, 0.0 / 0)[i] ?? ()");
+    assert_eq!(tree.children()[0].children().len(), 2);
+    assert_eq!(
+        tree.eval_with_context(&context_map! { "i" => 2 }.unwrap()),
+        Ok(Value::Array(vec![Value::from(true)]))
+    );
+}
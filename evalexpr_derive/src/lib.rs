@@ -0,0 +1,162 @@
+//! The derive macro for the `Context` trait of [evalexpr](https://docs.rs/evalexpr).
+//!
+//! This crate is meant to be used through the `derive` feature of evalexpr, which re-exports the macro as `evalexpr::Context`.
+
+#![forbid(unsafe_code)]
+#![deny(missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, Data, DeriveInput, Fields, Ident, LitStr, Type,
+};
+
+/// Implements `evalexpr::Context` for a struct with named fields, so that each field can be read as a variable.
+///
+/// The fields are converted into values with `Into<Value>` when they are read, so their types need to implement `Clone` and `Into<Value>`.
+/// The derived context has no functions, and builtin functions are always enabled.
+///
+/// Fields can be renamed with `#[evalexpr(rename = "identifier")]`, and hidden with `#[evalexpr(skip)]`.
+#[proc_macro_derive(Context, attributes(evalexpr))]
+pub fn derive_context(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_context(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A field that is exposed as a variable.
+struct Variable {
+    identifier: String,
+    field: Ident,
+    ty: Type,
+}
+
+fn expand_context(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(named_fields_error(&input.ident)),
+        },
+        _ => return Err(named_fields_error(&input.ident)),
+    };
+
+    let mut variables = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().expect("named fields have identifiers");
+        let mut identifier = ident.unraw().to_string();
+        let mut skip = false;
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("evalexpr"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    identifier = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"` or `skip`"))
+                }
+            })?;
+        }
+        if !skip {
+            variables.push(Variable {
+                identifier,
+                field: ident,
+                ty: field.ty.clone(),
+            });
+        }
+    }
+
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for Variable { ty, .. } in &variables {
+        where_clause.predicates.push(parse_quote!(
+            #ty: ::core::clone::Clone + ::core::convert::Into<::evalexpr::Value>
+        ));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let identifiers: Vec<_> = variables
+        .iter()
+        .map(|variable| &variable.identifier)
+        .collect();
+    let fields: Vec<_> = variables.iter().map(|variable| &variable.field).collect();
+
+    Ok(quote! {
+        impl #impl_generics ::evalexpr::Context for #name #ty_generics #where_clause {
+            /// The fields are converted into values when they are read, so they cannot be borrowed as values.
+            fn get_value(&self, _identifier: &str) -> ::core::option::Option<&::evalexpr::Value> {
+                ::core::option::Option::None
+            }
+
+            fn try_get_value(
+                &self,
+                identifier: &str,
+            ) -> ::evalexpr::EvalexprResult<
+                ::core::option::Option<::evalexpr::__private::Cow<'_, ::evalexpr::Value>>,
+            > {
+                ::core::result::Result::Ok(match identifier {
+                    #(#identifiers => ::core::option::Option::Some(
+                        ::evalexpr::__private::Cow::Owned(::core::convert::Into::into(
+                            ::core::clone::Clone::clone(&self.#fields),
+                        )),
+                    ),)*
+                    _ => ::core::option::Option::None,
+                })
+            }
+
+            fn call_function(
+                &self,
+                identifier: &str,
+                _argument: &::evalexpr::Value,
+            ) -> ::evalexpr::EvalexprResult<::evalexpr::Value> {
+                ::core::result::Result::Err(
+                    ::evalexpr::EvalexprError::function_identifier_not_found(
+                        ::core::convert::Into::into(identifier),
+                    ),
+                )
+            }
+
+            fn has_function(&self, _identifier: &str) -> bool {
+                false
+            }
+
+            fn iter_variable_identifiers(
+                &self,
+            ) -> ::evalexpr::__private::Box<dyn ::core::iter::Iterator<Item = &str> + '_> {
+                let identifiers: &'static [&'static str] = &[#(#identifiers),*];
+                ::evalexpr::__private::Box::new(identifiers.iter().copied())
+            }
+
+            fn are_builtin_functions_disabled(&self) -> bool {
+                false
+            }
+
+            fn set_builtin_functions_disabled(
+                &mut self,
+                disabled: bool,
+            ) -> ::evalexpr::EvalexprResult<()> {
+                if disabled {
+                    ::core::result::Result::Err(
+                        ::evalexpr::EvalexprError::BuiltinFunctionsCannotBeDisabled,
+                    )
+                } else {
+                    ::core::result::Result::Ok(())
+                }
+            }
+        }
+    })
+}
+
+fn named_fields_error(ident: &Ident) -> syn::Error {
+    syn::Error::new(
+        ident.span(),
+        "`Context` can only be derived for structs with named fields",
+    )
+}
@@ -1,7 +1,40 @@
+use evalexpr::{Interpreter, Value};
+use std::io::{self, BufRead, IsTerminal, Write};
+
 pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = std::env::args().skip(1).collect::<Vec<String>>().join(" ");
+    if !args.is_empty() {
+        println!("{}", evalexpr::eval(&args)?);
+        return Ok(());
+    }
+
+    // Without arguments, the lines of the standard input are evaluated with a persistent context.
+    let stdin = io::stdin();
+    let prompt = stdin.is_terminal();
+    let mut interpreter = Interpreter::new();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if prompt {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line?;
 
-    println!("{}", evalexpr::eval(&args)?);
+        if line.trim() == ":vars" {
+            for (identifier, value) in interpreter.bindings() {
+                println!("{} = {}", identifier, value);
+            }
+            continue;
+        }
+        match interpreter.eval_line(&line) {
+            Ok(Value::Empty) => {},
+            Ok(value) => println!("{}", value),
+            Err(error) => eprintln!("Error: {}", error),
+        }
+    }
 
     Ok(())
 }
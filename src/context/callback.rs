@@ -0,0 +1,126 @@
+use alloc::{borrow::Cow, string::ToString};
+use core::fmt;
+
+#[cfg(feature = "async")]
+use crate::{context::AsyncContext, function::AsyncFunction};
+use crate::{
+    context::Context,
+    error::{EvalexprError, EvalexprResult},
+    function::Function,
+    value::Value,
+};
+
+/// A context that resolves identifiers with closures, which bridges expressions to storage that is not a `Context`,
+/// like the components of an entity component system, game state, or objects behind a foreign function interface.
+///
+/// Variables are resolved by a closure that returns the value of a variable, or `None` if it does not exist.
+/// Functions are resolved by a closure that returns a reference to the function, or `None` if it does not exist.
+/// Both closures are called whenever an expression reads a variable or calls a function, so they always see the current state of the storage.
+///
+/// As the values are created on demand, `get_value` always returns `None`, and no identifiers are suggested for unknown identifiers.
+/// Builtin functions are enabled by default.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+/// use std::collections::HashMap;
+///
+/// let health = HashMap::from([("player", 80), ("boss", 350)]);
+/// let functions = HashMap::from([(
+///     "heal".to_string(),
+///     Function::new(|argument| Ok(Value::from(argument.as_int()? + 20))),
+/// )]);
+/// let context = CallbackContext::new(
+///     |identifier| health.get(identifier).map(|health| Value::from(*health)),
+///     |identifier| functions.get(identifier),
+/// );
+///
+/// assert_eq!(eval_with_context("heal(player) < boss", &context), Ok(Value::from(true)));
+/// assert_eq!(eval_with_context("max(player, 100)", &context), Ok(Value::from(100)));
+/// assert!(matches!(
+///     eval_with_context("minion", &context),
+///     Err(EvalexprError::VariableIdentifierNotFound { .. })
+/// ));
+/// ```
+#[derive(Clone)]
+pub struct CallbackContext<V, F> {
+    variables: V,
+    functions: F,
+    without_builtin_functions: bool,
+}
+
+impl<'f, V, F> CallbackContext<V, F>
+where
+    V: Fn(&str) -> Option<Value>,
+    F: Fn(&str) -> Option<&'f Function>,
+{
+    /// Constructs a `CallbackContext` that resolves variables with `variables` and functions with `functions`.
+    pub fn new(variables: V, functions: F) -> Self {
+        Self {
+            variables,
+            functions,
+            without_builtin_functions: false,
+        }
+    }
+}
+
+impl<V, F> fmt::Debug for CallbackContext<V, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackContext")
+            .field("without_builtin_functions", &self.without_builtin_functions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'f, V, F> Context for CallbackContext<V, F>
+where
+    V: Fn(&str) -> Option<Value>,
+    F: Fn(&str) -> Option<&'f Function>,
+{
+    fn get_value(&self, _identifier: &str) -> Option<&Value> {
+        None
+    }
+
+    fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+        Ok((self.variables)(identifier).map(Cow::Owned))
+    }
+
+    fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+        if let Some(function) = (self.functions)(identifier) {
+            function.call(self, argument)
+        } else {
+            Err(EvalexprError::function_identifier_not_found(
+                identifier.to_string(),
+            ))
+        }
+    }
+
+    fn has_function(&self, identifier: &str) -> bool {
+        (self.functions)(identifier).is_some()
+    }
+
+    fn is_pure_function(&self, identifier: &str) -> bool {
+        (self.functions)(identifier).is_some_and(Function::is_pure)
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.without_builtin_functions
+    }
+
+    fn set_builtin_functions_disabled(&mut self, disabled: bool) -> EvalexprResult<()> {
+        self.without_builtin_functions = disabled;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'f, V, F> AsyncContext for CallbackContext<V, F>
+where
+    V: Fn(&str) -> Option<Value>,
+    F: Fn(&str) -> Option<&'f Function>,
+{
+    fn get_async_function(&self, _identifier: &str) -> Option<&AsyncFunction> {
+        None
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::{borrow::Cow, format, string::String};
+use std::env;
+
+use crate::{
+    context::Context,
+    error::{EvalexprError, EvalexprResult},
+    value::{FloatType, IntType, Value},
+};
+
+/// A context that reads its variables from the environment variables of the process.
+///
+/// The environment is read whenever an expression reads a variable, so changes to the environment are visible to later evaluations.
+/// With a prefix, only environment variables whose name starts with the prefix are visible, and they are read without the prefix.
+///
+/// By default, the values of environment variables are converted into the types they look like:
+/// integers like `3` are read as `Value::Int`, floats like `0.5` as `Value::Float`, `true` and `false` as `Value::Boolean`,
+/// and everything else as `Value::String`.
+/// Environment variables whose value is not valid unicode cannot be read.
+///
+/// For each variable `NAME`, the variable `NAME_set` is a boolean that is true if the environment variable exists,
+/// unless an environment variable named `NAME_set` exists itself.
+///
+/// The context has no functions, and its variables cannot be assigned to.
+/// As the variables are read on demand, `get_value` always returns `None`, and no variables are suggested for unknown identifiers.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// std::env::set_var("MYTOOL_RETRIES", "5");
+/// let context = EnvContext::new().with_prefix("MYTOOL_");
+///
+/// assert_eq!(eval_with_context("RETRIES_set && RETRIES > 3", &context), Ok(Value::from(true)));
+/// assert_eq!(eval_with_context("TIMEOUT_set", &context), Ok(Value::from(false)));
+/// assert_eq!(
+///     eval_with_context("RETRIES", &context.with_type_coercion(false)),
+///     Ok(Value::from("5"))
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnvContext {
+    prefix: String,
+    type_coercion: bool,
+    without_builtin_functions: bool,
+}
+
+impl Default for EnvContext {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            type_coercion: true,
+            without_builtin_functions: false,
+        }
+    }
+}
+
+impl EnvContext {
+    /// Constructs an `EnvContext` that reads all environment variables and converts their values into the types they look like.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the context to environment variables whose name starts with the given prefix.
+    /// These are read without the prefix, e.g. the environment variable `APP_PORT` is read as `PORT` with the prefix `APP_`.
+    pub fn with_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets if the values of environment variables are converted into the types they look like.
+    /// If not, all values are read as `Value::String`.
+    pub fn with_type_coercion(mut self, type_coercion: bool) -> Self {
+        self.type_coercion = type_coercion;
+        self
+    }
+
+    /// Returns the prefix of the names of the visible environment variables.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Returns true if the values of environment variables are converted into the types they look like.
+    pub fn type_coercion(&self) -> bool {
+        self.type_coercion
+    }
+
+    /// Returns the name of the environment variable with the given identifier,
+    /// or `None` if no environment variable can have this name.
+    fn name(&self, identifier: &str) -> Option<String> {
+        if identifier.is_empty() || identifier.contains(['=', '\0']) {
+            None
+        } else {
+            Some(format!("{}{}", self.prefix, identifier))
+        }
+    }
+
+    /// Reads the environment variable with the given identifier, if it exists.
+    fn read(&self, identifier: &str) -> EvalexprResult<Option<Value>> {
+        let Some(name) = self.name(identifier) else {
+            return Ok(None);
+        };
+        match env::var(&name) {
+            Ok(value) if self.type_coercion => Ok(Some(coerce(value))),
+            Ok(value) => Ok(Some(Value::String(value))),
+            Err(env::VarError::NotPresent) => Ok(None),
+            Err(env::VarError::NotUnicode(_)) => Err(EvalexprError::CustomMessage(format!(
+                "Environment variable {} is not valid unicode",
+                name
+            ))),
+        }
+    }
+}
+
+/// Converts the value of an environment variable into the type it looks like.
+fn coerce(value: String) -> Value {
+    if let Ok(int) = value.parse::<IntType>() {
+        return Value::Int(int);
+    }
+    match value.parse::<FloatType>() {
+        // Words like `inf` or `NaN` are not read as floats.
+        Ok(float) if value.bytes().any(|byte| byte.is_ascii_digit()) => Value::Float(float),
+        _ => match value.as_str() {
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            _ => Value::String(value),
+        },
+    }
+}
+
+impl Context for EnvContext {
+    fn get_value(&self, _identifier: &str) -> Option<&Value> {
+        None
+    }
+
+    fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+        if let Some(value) = self.read(identifier)? {
+            return Ok(Some(Cow::Owned(value)));
+        }
+        match identifier.strip_suffix("_set") {
+            Some(identifier) => {
+                let is_set = self
+                    .name(identifier)
+                    .is_some_and(|name| env::var_os(name).is_some());
+                Ok(Some(Cow::Owned(Value::Boolean(is_set))))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn call_function(&self, identifier: &str, _argument: &Value) -> EvalexprResult<Value> {
+        Err(EvalexprError::function_identifier_not_found(
+            identifier.into(),
+        ))
+    }
+
+    fn has_function(&self, _identifier: &str) -> bool {
+        false
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.without_builtin_functions
+    }
+
+    fn set_builtin_functions_disabled(&mut self, disabled: bool) -> EvalexprResult<()> {
+        self.without_builtin_functions = disabled;
+        Ok(())
+    }
+}
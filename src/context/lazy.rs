@@ -0,0 +1,214 @@
+use alloc::{borrow::Cow, boxed::Box, string::String, sync::Arc};
+use core::fmt;
+use std::{collections::HashMap, sync::OnceLock};
+
+#[cfg(feature = "async")]
+use crate::{context::AsyncContext, function::AsyncFunction};
+use crate::{
+    context::{Context, ContextWithMutableFunctions, ContextWithMutableVariables},
+    error::EvalexprResult,
+    function::Function,
+    value::Value,
+    HashMapContext,
+};
+
+/// The computation of a lazy variable.
+type Computation = dyn Fn() -> EvalexprResult<Value> + Send + Sync;
+
+/// A context whose variables can be computed on demand, on top of another context.
+///
+/// A lazy variable is computed when an expression reads it for the first time,
+/// and the computed value is kept until `reset` is called, so it is computed at most once between two resets.
+/// Variables that are never read are never computed, which is useful for values that are expensive to obtain,
+/// like values read from a database or over the network.
+/// If the computation fails, reading the variable in an expression fails with the error of the computation,
+/// which is kept until the next reset as well.
+///
+/// Lazy variables shadow the variables of the inner context, and all functions are taken from the inner context.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// let lookups = Arc::new(AtomicUsize::new(0));
+/// let mut context = LazyContext::new(context_map! { "amount" => 3 }.unwrap()); // Do proper error handling here
+/// let counter = lookups.clone();
+/// context.set_lazy_value("current_price".into(), move || {
+///     counter.fetch_add(1, Ordering::SeqCst);
+///     Ok(Value::from(7))
+/// });
+///
+/// assert_eq!(eval_with_context("amount > 5 && current_price > 5", &context), Ok(Value::from(false)));
+/// assert_eq!(lookups.load(Ordering::SeqCst), 0);
+/// assert_eq!(eval_with_context("amount * current_price + current_price", &context), Ok(Value::from(28)));
+/// assert_eq!(lookups.load(Ordering::SeqCst), 1);
+///
+/// // Compute the value again the next time it is read.
+/// context.reset();
+/// assert_eq!(eval_with_context("current_price", &context), Ok(Value::from(7)));
+/// assert_eq!(lookups.load(Ordering::SeqCst), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LazyContext<C = HashMapContext> {
+    context: C,
+    lazy_values: HashMap<String, LazyValue>,
+}
+
+#[derive(Clone)]
+struct LazyValue {
+    compute: Arc<Computation>,
+    value: OnceLock<EvalexprResult<Value>>,
+}
+
+impl LazyValue {
+    fn get(&self) -> &EvalexprResult<Value> {
+        self.value.get_or_init(|| (self.compute)())
+    }
+}
+
+impl fmt::Debug for LazyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyValue")
+            .field("value", &self.value.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C: Context> LazyContext<C> {
+    /// Constructs a `LazyContext` without lazy variables on top of the given context.
+    pub fn new(context: C) -> Self {
+        Self {
+            context,
+            lazy_values: HashMap::new(),
+        }
+    }
+
+    /// Sets the variable with the given identifier to be computed by `compute` when it is first read.
+    /// This replaces a lazy variable with the same identifier, and shadows a variable of the inner context.
+    pub fn set_lazy_value<F>(&mut self, identifier: String, compute: F)
+    where
+        F: Fn() -> EvalexprResult<Value> + Send + Sync + 'static,
+    {
+        self.lazy_values.insert(
+            identifier,
+            LazyValue {
+                compute: Arc::new(compute),
+                value: OnceLock::new(),
+            },
+        );
+    }
+
+    /// Returns true if a lazy variable with the given identifier exists and was computed since the last reset.
+    pub fn is_computed(&self, identifier: &str) -> bool {
+        self.lazy_values
+            .get(identifier)
+            .is_some_and(|lazy_value| lazy_value.value.get().is_some())
+    }
+
+    /// Forgets the computed values of all lazy variables, so that they are computed again when they are next read.
+    pub fn reset(&mut self) {
+        for lazy_value in self.lazy_values.values_mut() {
+            lazy_value.value = OnceLock::new();
+        }
+    }
+
+    /// Returns the inner context.
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Returns the inner context, allowing mutation.
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Consumes this context and returns the inner context.
+    pub fn into_context(self) -> C {
+        self.context
+    }
+}
+
+impl<C: Context> Context for LazyContext<C> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        match self.lazy_values.get(identifier) {
+            Some(lazy_value) => lazy_value.get().as_ref().ok(),
+            None => self.context.get_value(identifier),
+        }
+    }
+
+    fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+        match self.lazy_values.get(identifier) {
+            Some(lazy_value) => match lazy_value.get() {
+                Ok(value) => Ok(Some(Cow::Borrowed(value))),
+                Err(error) => Err(error.clone()),
+            },
+            None => self.context.try_get_value(identifier),
+        }
+    }
+
+    fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+        self.context.call_function(identifier, argument)
+    }
+
+    fn has_function(&self, identifier: &str) -> bool {
+        self.context.has_function(identifier)
+    }
+
+    fn is_pure_function(&self, identifier: &str) -> bool {
+        self.context.is_pure_function(identifier)
+    }
+
+    fn iter_variable_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(
+            self.lazy_values
+                .keys()
+                .map(String::as_str)
+                .chain(self.context.iter_variable_identifiers()),
+        )
+    }
+
+    fn iter_function_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.context.iter_function_identifiers()
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.context.are_builtin_functions_disabled()
+    }
+
+    fn set_builtin_functions_disabled(&mut self, disabled: bool) -> EvalexprResult<()> {
+        self.context.set_builtin_functions_disabled(disabled)
+    }
+}
+
+impl<C: ContextWithMutableVariables> ContextWithMutableVariables for LazyContext<C> {
+    /// Sets the variable in the inner context, replacing a lazy variable with the same identifier.
+    fn set_value(&mut self, identifier: String, value: Value) -> EvalexprResult<()> {
+        self.lazy_values.remove(&identifier);
+        self.context.set_value(identifier, value)
+    }
+
+    /// Removes a lazy variable with the given identifier and returns its value if it was computed successfully,
+    /// or removes the variable from the inner context otherwise.
+    fn remove_value(&mut self, identifier: &str) -> EvalexprResult<Option<Value>> {
+        match self.lazy_values.remove(identifier) {
+            Some(lazy_value) => Ok(lazy_value.value.into_inner().and_then(Result::ok)),
+            None => self.context.remove_value(identifier),
+        }
+    }
+}
+
+impl<C: ContextWithMutableFunctions> ContextWithMutableFunctions for LazyContext<C> {
+    fn set_function(&mut self, identifier: String, function: Function) -> EvalexprResult<()> {
+        self.context.set_function(identifier, function)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: AsyncContext> AsyncContext for LazyContext<C> {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        self.context.get_async_function(identifier)
+    }
+}
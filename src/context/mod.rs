@@ -4,26 +4,96 @@
 //! This crate implements two basic variants, the `EmptyContext`, that returns `None` for each identifier and cannot be manipulated, and the `HashMapContext`, that stores its mappings in hash maps.
 //! The HashMapContext is type-safe and returns an error if the user tries to assign a value of a different type than before to an identifier.
 
-use std::{collections::HashMap, iter};
-
+use alloc::{borrow::Cow, boxed::Box, string::String, string::ToString, sync::Arc, vec::Vec};
+use core::iter;
+// Without the standard library, the `HashMapContext` is backed by a `BTreeMap`.
+#[cfg(not(feature = "std"))]
+use alloc::collections::{btree_map as map, BTreeMap as Map};
+#[cfg(feature = "std")]
+use std::collections::{hash_map as map, HashMap as Map};
+
+#[cfg(feature = "async")]
+use crate::function::AsyncFunction;
 use crate::{
     function::Function,
     value::{value_type::ValueType, Value},
     EvalexprError, EvalexprResult,
 };
 
+mod callback;
+#[cfg(feature = "std")]
+mod env;
+#[cfg(feature = "std")]
+mod lazy;
 mod predefined;
 
+pub use callback::CallbackContext;
+#[cfg(feature = "std")]
+pub use env::EnvContext;
+#[cfg(feature = "std")]
+pub use lazy::LazyContext;
+
 /// An immutable context.
+///
+/// When an expression calls a function, the function is resolved in the following order:
+///
+///  1. The function of the context, if `call_function` does not return `EvalexprError::FunctionIdentifierNotFound`.
+///  2. The lambda stored in the variable with the same identifier, if there is one.
+///  3. The builtin function with the same identifier, unless `are_builtin_functions_disabled` returns `true`.
+///
+/// So contexts do not need to implement builtin functions themselves, and their functions can replace builtin functions.
+/// For evaluations without any variables, `EmptyContext` provides no functions at all,
+/// and `EmptyContextWithBuiltinFunctions` provides only the builtin functions.
 pub trait Context {
     /// Returns the value that is linked to the given identifier.
     fn get_value(&self, identifier: &str) -> Option<&Value>;
 
+    /// Returns the value that is linked to the given identifier, or an error if looking it up failed.
+    ///
+    /// Expressions read variables with this method, so contexts whose lookups can fail, e.g. because they are backed by a database,
+    /// can implement it to make the evaluation fail with their error instead of `EvalexprError::VariableIdentifierNotFound`.
+    /// Contexts that do not store their variables as `Value`s can implement it to return owned values.
+    /// The default implementation borrows the value returned by `get_value` and never fails.
+    fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+        Ok(self.get_value(identifier).map(Cow::Borrowed))
+    }
+
     /// Calls the function that is linked to the given identifier with the given argument.
     /// If no function with the given identifier is found, this method returns `EvalexprError::FunctionIdentifierNotFound`.
     fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value>;
 
+    /// Returns true if a function is linked to the given identifier.
+    ///
+    /// Builtin functions that evaluate their arguments lazily, like `if`, are only used if the context does not link a function to their identifier.
+    fn has_function(&self, identifier: &str) -> bool;
+
+    /// Returns true if the function that is linked to the given identifier is pure,
+    /// meaning that it always returns the same result for the same arguments and has no side effects.
+    ///
+    /// `Node::eval_cached` only reuses the results of calls to functions of the context that are pure.
+    /// The default implementation returns `false`.
+    fn is_pure_function(&self, _identifier: &str) -> bool {
+        false
+    }
+
+    /// Returns an iterator over the identifiers of the variables in this context.
+    ///
+    /// It is used to suggest similar identifiers in `EvalexprError::VariableIdentifierNotFound`.
+    /// The default implementation returns an empty iterator, so no variables are suggested.
+    fn iter_variable_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(iter::empty())
+    }
+
+    /// Returns an iterator over the identifiers of the functions in this context.
+    ///
+    /// It is used to suggest similar identifiers in `EvalexprError::FunctionIdentifierNotFound`.
+    /// The default implementation returns an empty iterator, so only builtin functions are suggested.
+    fn iter_function_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(iter::empty())
+    }
+
     /// Checks if builtin functions are disabled.
+    /// If so, expressions can only call the functions of the context and lambdas.
     fn are_builtin_functions_disabled(&self) -> bool;
 
     /// Disables builtin functions if `disabled` is `true`, and enables them otherwise.
@@ -37,6 +107,11 @@ pub trait ContextWithMutableVariables: Context {
     fn set_value(&mut self, _identifier: String, _value: Value) -> EvalexprResult<()> {
         Err(EvalexprError::ContextNotMutable)
     }
+
+    /// Removes the variable with the given identifier, returning its value if it existed.
+    fn remove_value(&mut self, _identifier: &str) -> EvalexprResult<Option<Value>> {
+        Err(EvalexprError::ContextNotMutable)
+    }
 }
 
 /// A context that allows to assign to function identifiers.
@@ -67,6 +142,13 @@ pub trait IterateVariablesContext {
     fn iter_variable_names(&self) -> Self::VariableNameIterator<'_>;
 }
 
+/// A context that links identifiers to async functions, which are called when evaluating with `Node::eval_with_context_async`.
+#[cfg(feature = "async")]
+pub trait AsyncContext: Context {
+    /// Returns the async function that is linked to the given identifier.
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction>;
+}
+
 /*/// A context that allows to retrieve functions programmatically.
 pub trait GetFunctionContext: Context {
     /// Returns the function that is linked to the given identifier.
@@ -81,17 +163,28 @@ pub trait GetFunctionContext: Context {
 #[derive(Debug, Default)]
 pub struct EmptyContext;
 
+#[cfg(feature = "async")]
+impl AsyncContext for EmptyContext {
+    fn get_async_function(&self, _identifier: &str) -> Option<&AsyncFunction> {
+        None
+    }
+}
+
 impl Context for EmptyContext {
     fn get_value(&self, _identifier: &str) -> Option<&Value> {
         None
     }
 
     fn call_function(&self, identifier: &str, _argument: &Value) -> EvalexprResult<Value> {
-        Err(EvalexprError::FunctionIdentifierNotFound(
+        Err(EvalexprError::function_identifier_not_found(
             identifier.to_string(),
         ))
     }
 
+    fn has_function(&self, _identifier: &str) -> bool {
+        false
+    }
+
     /// Builtin functions are always disabled for `EmptyContext`.
     fn are_builtin_functions_disabled(&self) -> bool {
         true
@@ -122,20 +215,47 @@ impl IterateVariablesContext for EmptyContext {
 
 /// A context that returns `None` for each identifier.
 /// Builtin functions are enabled and cannot be disabled.
+///
+/// This is useful to evaluate expressions that may only use constants and builtin functions like `min`, `max` or `math::sqrt`.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context = EmptyContextWithBuiltinFunctions;
+/// assert_eq!(eval_with_context("max(1, math::abs(-4))", &context), Ok(Value::from(4)));
+/// assert!(matches!(
+///     eval_with_context("max(a, 4)", &context),
+///     Err(EvalexprError::VariableIdentifierNotFound { .. })
+/// ));
+/// assert_eq!(eval_with_context("max(1, 4)", &EmptyContext), Err(EvalexprError::function_identifier_not_found("max".into())));
+/// ```
 #[derive(Debug, Default)]
 pub struct EmptyContextWithBuiltinFunctions;
 
+#[cfg(feature = "async")]
+impl AsyncContext for EmptyContextWithBuiltinFunctions {
+    fn get_async_function(&self, _identifier: &str) -> Option<&AsyncFunction> {
+        None
+    }
+}
+
 impl Context for EmptyContextWithBuiltinFunctions {
     fn get_value(&self, _identifier: &str) -> Option<&Value> {
         None
     }
 
     fn call_function(&self, identifier: &str, _argument: &Value) -> EvalexprResult<Value> {
-        Err(EvalexprError::FunctionIdentifierNotFound(
+        Err(EvalexprError::function_identifier_not_found(
             identifier.to_string(),
         ))
     }
 
+    fn has_function(&self, _identifier: &str) -> bool {
+        false
+    }
+
     /// Builtin functions are always enabled for EmptyContextWithBuiltinFunctions.
     fn are_builtin_functions_disabled(&self) -> bool {
         false
@@ -165,16 +285,24 @@ impl IterateVariablesContext for EmptyContextWithBuiltinFunctions {
 }
 
 /// A context that stores its mappings in hash maps.
+/// Without the `std` feature, the mappings are stored in `BTreeMap`s instead.
 ///
 /// *Value and function mappings are stored independently, meaning that there can be a function and a value with the same identifier.*
 ///
 /// This context is type-safe, meaning that an identifier that is assigned a value of some type once cannot be assigned a value of another type.
+///
+/// With the `serde_support` feature, the context can be serialized and deserialized, but only its variables and settings are included, not its functions.
+/// Missing fields are set to their default values when deserializing.
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde_support", serde(default))]
 pub struct HashMapContext {
-    variables: HashMap<String, Value>,
+    variables: Map<String, Value>,
+    #[cfg_attr(feature = "serde_support", serde(skip))]
+    functions: Map<String, Function>,
+    #[cfg(feature = "async")]
     #[cfg_attr(feature = "serde_support", serde(skip))]
-    functions: HashMap<String, Function>,
+    async_functions: Map<String, AsyncFunction>,
 
     /// True if builtin functions are disabled.
     without_builtin_functions: bool,
@@ -185,6 +313,102 @@ impl HashMapContext {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Constructs a `HashMapContext` with no mappings, but space for at least the given amount of variables.
+    /// Without the `std` feature, the capacity is ignored.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            #[cfg(feature = "std")]
+            variables: Map::with_capacity(capacity),
+            #[cfg(not(feature = "std"))]
+            variables: {
+                let _ = capacity;
+                Map::new()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Removes all variables.
+    /// As the variables do not exist anymore, they can be assigned values of any type again.
+    pub fn clear_variables(&mut self) {
+        self.variables.clear();
+    }
+
+    /// Removes all functions, including async functions.
+    pub fn clear_functions(&mut self) {
+        self.functions.clear();
+        #[cfg(feature = "async")]
+        self.async_functions.clear();
+    }
+
+    /// Removes all variables and functions, but keeps whether builtin functions are disabled.
+    pub fn clear(&mut self) {
+        self.clear_variables();
+        self.clear_functions();
+    }
+
+    /// Returns a snapshot of the variables of this context, which can be restored with `rollback`.
+    ///
+    /// This allows to evaluate expressions with assignments speculatively, and to undo their assignments if the evaluation fails.
+    /// The snapshot contains a copy of all variables, but not the functions of the context.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let mut context = context_map! { "balance" => 100 }.unwrap(); // Do proper error handling here
+    /// let snapshot = context.snapshot();
+    /// // The transfer fails after the balance was already changed.
+    /// assert!(eval_with_context_mut("balance -= 30; fee = 5; balance / 0", &mut context).is_err());
+    /// assert_eq!(context.get_value("balance"), Some(&Value::from(70)));
+    ///
+    /// context.rollback(snapshot);
+    /// assert_eq!(context.get_value("balance"), Some(&Value::from(100)));
+    /// assert_eq!(context.get_value("fee"), None);
+    /// ```
+    pub fn snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            variables: self.variables.clone(),
+        }
+    }
+
+    /// Restores the variables of this context to the state of the given snapshot taken with `snapshot`.
+    ///
+    /// Variables that were assigned since the snapshot regain their previous values, and variables that were created since are removed.
+    /// Functions and whether builtin functions are disabled are not affected.
+    pub fn rollback(&mut self, snapshot: ContextSnapshot) {
+        self.variables = snapshot.variables;
+    }
+
+    /// Sets the async function with the given identifier to the given async function.
+    ///
+    /// Async functions are only called when evaluating with `Node::eval_with_context_async`.
+    #[cfg(feature = "async")]
+    pub fn set_async_function(
+        &mut self,
+        identifier: String,
+        function: AsyncFunction,
+    ) -> EvalexprResult<()> {
+        self.async_functions.insert(identifier, function);
+        Ok(())
+    }
+}
+
+/// A snapshot of the variables of a `HashMapContext`, taken with `HashMapContext::snapshot` and restored with `HashMapContext::rollback`.
+///
+/// A snapshot can be cloned to roll back to the same state multiple times.
+#[derive(Clone, Debug, Default)]
+pub struct ContextSnapshot {
+    variables: Map<String, Value>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncContext for HashMapContext {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        self.async_functions.get(identifier)
+    }
 }
 
 impl Context for HashMapContext {
@@ -194,14 +418,32 @@ impl Context for HashMapContext {
 
     fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
         if let Some(function) = self.functions.get(identifier) {
-            function.call(argument)
+            function.call(self, argument)
         } else {
-            Err(EvalexprError::FunctionIdentifierNotFound(
+            Err(EvalexprError::function_identifier_not_found(
                 identifier.to_string(),
             ))
         }
     }
 
+    fn has_function(&self, identifier: &str) -> bool {
+        self.functions.contains_key(identifier)
+    }
+
+    fn is_pure_function(&self, identifier: &str) -> bool {
+        self.functions
+            .get(identifier)
+            .is_some_and(Function::is_pure)
+    }
+
+    fn iter_variable_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.variables.keys().map(String::as_str))
+    }
+
+    fn iter_function_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(self.functions.keys().map(String::as_str))
+    }
+
     fn are_builtin_functions_disabled(&self) -> bool {
         self.without_builtin_functions
     }
@@ -227,6 +469,10 @@ impl ContextWithMutableVariables for HashMapContext {
         self.variables.insert(identifier, value);
         Ok(())
     }
+
+    fn remove_value(&mut self, identifier: &str) -> EvalexprResult<Option<Value>> {
+        Ok(self.variables.remove(identifier))
+    }
 }
 
 impl ContextWithMutableFunctions for HashMapContext {
@@ -237,12 +483,9 @@ impl ContextWithMutableFunctions for HashMapContext {
 }
 
 impl IterateVariablesContext for HashMapContext {
-    type VariableIterator<'a> = std::iter::Map<
-        std::collections::hash_map::Iter<'a, String, Value>,
-        fn((&String, &Value)) -> (String, Value),
-    >;
-    type VariableNameIterator<'a> =
-        std::iter::Cloned<std::collections::hash_map::Keys<'a, String, Value>>;
+    type VariableIterator<'a> =
+        core::iter::Map<map::Iter<'a, String, Value>, fn((&String, &Value)) -> (String, Value)>;
+    type VariableNameIterator<'a> = core::iter::Cloned<map::Keys<'a, String, Value>>;
 
     fn iter_variables(&self) -> Self::VariableIterator<'_> {
         self.variables
@@ -255,6 +498,282 @@ impl IterateVariablesContext for HashMapContext {
     }
 }
 
+/// Collects variables into a new `HashMapContext`.
+///
+/// Unlike with `set_value`, a variable that occurs more than once gets the last of its values, even if their types differ.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context: HashMapContext = vec![("a", 1), ("b", 2)].into_iter().collect();
+/// assert_eq!(eval_with_context("a + b", &context), Ok(Value::from(3)));
+/// ```
+impl<K: Into<String>, V: Into<Value>> iter::FromIterator<(K, V)> for HashMapContext {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut context = HashMapContext::new();
+        context.extend(iter);
+        context
+    }
+}
+
+/// Adds variables to the `HashMapContext`.
+///
+/// Unlike with `set_value`, existing variables are replaced even if the type of their new value differs.
+impl<K: Into<String>, V: Into<Value>> Extend<(K, V)> for HashMapContext {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.variables.extend(
+            iter.into_iter()
+                .map(|(identifier, value)| (identifier.into(), value.into())),
+        );
+    }
+}
+
+/// A thread-safe context that shares its mappings between its clones.
+///
+/// Cloning a `SyncHashMapContext` is cheap, as the clones share the mappings of a `HashMapContext` through an `Arc`.
+/// Assigning to a clone first copies the mappings if they are still shared, so clones never affect each other.
+/// This allows e.g. a web server to share a large context between threads, and to extend it per request.
+///
+/// Like all contexts of this crate, the `SyncHashMapContext` is `Send` and `Sync`, as functions are required to be `Send` and `Sync` as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context = SyncHashMapContext::from(context_map! { "rate" => 3 }.unwrap()); // Do proper error handling here
+/// let tree = build_operator_tree("amount * rate").unwrap(); // Do proper error handling here
+///
+/// std::thread::scope(|scope| {
+///     for amount in 1..=4 {
+///         let mut context = context.clone();
+///         let tree = &tree;
+///         scope.spawn(move || {
+///             context.set_value("amount".into(), Value::from(amount)).unwrap(); // Do proper error handling here
+///             assert_eq!(tree.eval_with_context(&context), Ok(Value::from(amount * 3)));
+///         });
+///     }
+/// });
+/// assert_eq!(context.get_value("amount"), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SyncHashMapContext {
+    context: Arc<HashMapContext>,
+}
+
+impl SyncHashMapContext {
+    /// Constructs a `SyncHashMapContext` with no mappings.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Removes all variables and functions, but keeps whether builtin functions are disabled.
+    /// Unlike assignments, this does not copy the mappings if they are shared with other clones.
+    pub fn clear(&mut self) {
+        self.context = Arc::new(HashMapContext {
+            without_builtin_functions: self.context.without_builtin_functions,
+            ..HashMapContext::new()
+        });
+    }
+
+    /// Returns a mutable reference to the mappings, copying them first if they are shared with other clones.
+    fn context_mut(&mut self) -> &mut HashMapContext {
+        Arc::make_mut(&mut self.context)
+    }
+}
+
+impl From<HashMapContext> for SyncHashMapContext {
+    fn from(context: HashMapContext) -> Self {
+        Self {
+            context: Arc::new(context),
+        }
+    }
+}
+
+impl Context for SyncHashMapContext {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.context.get_value(identifier)
+    }
+
+    fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+        self.context.call_function(identifier, argument)
+    }
+
+    fn has_function(&self, identifier: &str) -> bool {
+        self.context.has_function(identifier)
+    }
+
+    fn is_pure_function(&self, identifier: &str) -> bool {
+        self.context.is_pure_function(identifier)
+    }
+
+    fn iter_variable_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.context.iter_variable_identifiers()
+    }
+
+    fn iter_function_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.context.iter_function_identifiers()
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.context.are_builtin_functions_disabled()
+    }
+
+    fn set_builtin_functions_disabled(&mut self, disabled: bool) -> EvalexprResult<()> {
+        self.context_mut().set_builtin_functions_disabled(disabled)
+    }
+}
+
+impl ContextWithMutableVariables for SyncHashMapContext {
+    fn set_value(&mut self, identifier: String, value: Value) -> EvalexprResult<()> {
+        self.context_mut().set_value(identifier, value)
+    }
+
+    fn remove_value(&mut self, identifier: &str) -> EvalexprResult<Option<Value>> {
+        if self.context.get_value(identifier).is_none() {
+            return Ok(None);
+        }
+        self.context_mut().remove_value(identifier)
+    }
+}
+
+impl ContextWithMutableFunctions for SyncHashMapContext {
+    fn set_function(&mut self, identifier: String, function: Function) -> EvalexprResult<()> {
+        self.context_mut().set_function(identifier, function)
+    }
+}
+
+impl IterateVariablesContext for SyncHashMapContext {
+    type VariableIterator<'a> = <HashMapContext as IterateVariablesContext>::VariableIterator<'a>;
+    type VariableNameIterator<'a> =
+        <HashMapContext as IterateVariablesContext>::VariableNameIterator<'a>;
+
+    fn iter_variables(&self) -> Self::VariableIterator<'_> {
+        self.context.iter_variables()
+    }
+
+    fn iter_variable_names(&self) -> Self::VariableNameIterator<'_> {
+        self.context.iter_variable_names()
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncContext for SyncHashMapContext {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        self.context.get_async_function(identifier)
+    }
+}
+
+/// A context that consults a list of other contexts in order.
+///
+/// Each identifier is looked up in the layers from first to last, and the first layer that knows the identifier is used.
+/// This allows to put e.g. a small per-request context over a large context of global constants without copying the constants.
+/// The layers are borrowed, so they cannot be changed while the `ChainedContext` exists.
+///
+/// Builtin functions are enabled by default, independent of the builtin function settings of the layers.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let constants = context_map! { "pi" => 3.14, "answer" => 42 }.unwrap(); // Do proper error handling here
+/// let request = context_map! { "answer" => 43 }.unwrap(); // Do proper error handling here
+/// let context = ChainedContext::new().with_layer(&request).with_layer(&constants);
+///
+/// assert_eq!(eval_with_context("answer", &context), Ok(43.into()));
+/// assert_eq!(eval_with_context("pi", &context), Ok(3.14.into()));
+/// ```
+#[derive(Clone, Default)]
+pub struct ChainedContext<'a> {
+    layers: Vec<&'a dyn Context>,
+
+    /// True if builtin functions are disabled.
+    without_builtin_functions: bool,
+}
+
+impl<'a> ChainedContext<'a> {
+    /// Constructs a `ChainedContext` without any layers.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a layer that is consulted after all previously added layers.
+    pub fn with_layer(mut self, layer: &'a dyn Context) -> Self {
+        self.layers.push(layer);
+        self
+    }
+}
+
+impl Context for ChainedContext<'_> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.layers
+            .iter()
+            .find_map(|layer| layer.get_value(identifier))
+    }
+
+    fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+        for layer in &self.layers {
+            if let Some(value) = layer.try_get_value(identifier)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+        for layer in &self.layers {
+            match layer.call_function(identifier, argument) {
+                Err(EvalexprError::FunctionIdentifierNotFound { .. }) => continue,
+                result => return result,
+            }
+        }
+        Err(EvalexprError::function_identifier_not_found(
+            identifier.to_string(),
+        ))
+    }
+
+    fn has_function(&self, identifier: &str) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| layer.has_function(identifier))
+    }
+
+    fn is_pure_function(&self, identifier: &str) -> bool {
+        self.layers
+            .iter()
+            .find(|layer| layer.has_function(identifier))
+            .is_some_and(|layer| layer.is_pure_function(identifier))
+    }
+
+    fn iter_variable_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(
+            self.layers
+                .iter()
+                .flat_map(|layer| layer.iter_variable_identifiers()),
+        )
+    }
+
+    fn iter_function_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(
+            self.layers
+                .iter()
+                .flat_map(|layer| layer.iter_function_identifiers()),
+        )
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.without_builtin_functions
+    }
+
+    fn set_builtin_functions_disabled(&mut self, disabled: bool) -> EvalexprResult<()> {
+        self.without_builtin_functions = disabled;
+        Ok(())
+    }
+}
+
 /// This macro provides a convenient syntax for creating a static context.
 ///
 /// # Examples
@@ -272,16 +791,17 @@ impl IterateVariablesContext for HashMapContext {
 #[macro_export]
 macro_rules! context_map {
     // Termination (allow missing comma at the end of the argument list)
-    ( ($ctx:expr) $k:expr => Function::new($($v:tt)*) ) =>
-        { $crate::context_map!(($ctx) $k => Function::new($($v)*),) };
+    ( ($ctx:expr) $k:expr => Function::$f:ident($($v:tt)*) $(.$m:ident($($a:tt)*))* ) =>
+        { $crate::context_map!(($ctx) $k => Function::$f($($v)*) $(.$m($($a)*))*,) };
     ( ($ctx:expr) $k:expr => $v:expr ) =>
         { $crate::context_map!(($ctx) $k => $v,)  };
     // Termination
-    ( ($ctx:expr) ) => { Ok(()) };
+    ( ($ctx:expr) ) => { $crate::EvalexprResult::Ok(()) };
 
-    // The user has to specify a literal 'Function::new' in order to create a function
-    ( ($ctx:expr) $k:expr => Function::new($($v:tt)*) , $($tt:tt)*) => {{
-        $crate::ContextWithMutableFunctions::set_function($ctx, $k.into(), $crate::Function::new($($v)*))
+    // The user has to specify a literal 'Function::new', 'Function::new_with_context' or 'Function::typed' in order to create a function,
+    // optionally followed by calls of builder methods like 'with_argument_range'
+    ( ($ctx:expr) $k:expr => Function::$f:ident($($v:tt)*) $(.$m:ident($($a:tt)*))* , $($tt:tt)*) => {{
+        $crate::ContextWithMutableFunctions::set_function($ctx, $k.into(), $crate::Function::$f($($v)*) $(.$m($($a)*))*)
             .and($crate::context_map!(($ctx) $($tt)*))
     }};
     // add a value, and chain the eventual error with the ones in the next values
@@ -1,4 +1,5 @@
-use std::fmt;
+use alloc::string::String;
+use core::fmt;
 
 use crate::EvalexprError;
 
@@ -16,11 +17,54 @@ impl fmt::Display for EvalexprError {
                 "A function expected {} arguments, but got {}.",
                 expected, actual
             ),
+            WrongFunctionArgumentAmountRange { expected, actual } => {
+                if *expected.end() == usize::MAX {
+                    write!(
+                        f,
+                        "A function expected at least {} arguments",
+                        expected.start()
+                    )?;
+                } else {
+                    write!(
+                        f,
+                        "A function expected between {} and {} arguments",
+                        expected.start(),
+                        expected.end()
+                    )?;
+                }
+                write!(f, ", but got {}.", actual)
+            },
+            WrongFunctionArgumentAmountChoice { expected, actual } => {
+                write!(f, "A function expected ")?;
+                for (index, amount) in expected.iter().enumerate() {
+                    match index {
+                        0 => {},
+                        _ if index + 1 == expected.len() => write!(f, " or ")?,
+                        _ => write!(f, ", ")?,
+                    }
+                    write!(f, "{}", amount)?;
+                }
+                write!(f, " arguments, but got {}.", actual)
+            },
             ExpectedString { actual } => {
                 write!(f, "Expected a Value::String, but got {:?}.", actual)
             },
             ExpectedInt { actual } => write!(f, "Expected a Value::Int, but got {:?}.", actual),
             ExpectedFloat { actual } => write!(f, "Expected a Value::Float, but got {:?}.", actual),
+            #[cfg(feature = "decimal")]
+            ExpectedDecimal { actual } => write!(
+                f,
+                "Expected a Value::Decimal or Value::Int, but got {:?}.",
+                actual
+            ),
+            #[cfg(feature = "chrono")]
+            ExpectedDateTime { actual } => {
+                write!(f, "Expected a Value::DateTime, but got {:?}.", actual)
+            },
+            #[cfg(feature = "chrono")]
+            ExpectedDuration { actual } => {
+                write!(f, "Expected a Value::Duration, but got {:?}.", actual)
+            },
             ExpectedNumber { actual } => write!(
                 f,
                 "Expected a Value::Float or Value::Int, but got {:?}.",
@@ -43,22 +87,41 @@ impl fmt::Display for EvalexprError {
                 "Expected a Value::Tuple of len {}, but got {:?}.",
                 expected_len, actual
             ),
+            ExpectedArray { actual } => write!(f, "Expected a Value::Array, but got {:?}.", actual),
+            ExpectedMap { actual } => write!(f, "Expected a Value::Map, but got {:?}.", actual),
             ExpectedEmpty { actual } => write!(f, "Expected a Value::Empty, but got {:?}.", actual),
             AppendedToLeafNode => write!(f, "Tried to append a node to a leaf node."),
             PrecedenceViolation => write!(
                 f,
                 "Tried to append a node to another node with higher precedence."
             ),
-            VariableIdentifierNotFound(identifier) => write!(
-                f,
-                "Variable identifier is not bound to anything by context: {:?}.",
-                identifier
-            ),
-            FunctionIdentifierNotFound(identifier) => write!(
-                f,
-                "Function identifier is not bound to anything by context: {:?}.",
-                identifier
-            ),
+            VariableIdentifierNotFound {
+                identifier,
+                suggestions,
+                span,
+            } => {
+                write!(
+                    f,
+                    "Variable identifier is not bound to anything by context: {:?}",
+                    identifier
+                )?;
+                if let Some(span) = span {
+                    write!(f, " at position {}", span.start)?;
+                }
+                write!(f, ".")?;
+                write_suggestions(f, suggestions)
+            },
+            FunctionIdentifierNotFound {
+                identifier,
+                suggestions,
+            } => {
+                write!(
+                    f,
+                    "Function identifier is not bound to anything by context: {:?}.",
+                    identifier
+                )?;
+                write_suggestions(f, suggestions)
+            },
             TypeError { expected, actual } => {
                 write!(f, "Expected one of {:?}, but got {:?}.", expected, actual)
             },
@@ -67,27 +130,50 @@ impl fmt::Display for EvalexprError {
                 "The operator {:?} was called with a wrong combination of types: {:?}",
                 operator, actual
             ),
-            UnmatchedLBrace => write!(f, "Found an unmatched opening parenthesis '('."),
-            UnmatchedRBrace => write!(f, "Found an unmatched closing parenthesis ')'."),
-            MissingOperatorOutsideOfBrace { .. } => write!(
+            UnmatchedLBrace { span } => write!(
+                f,
+                "Found an unmatched opening parenthesis '(' at position {}.",
+                span.start
+            ),
+            UnmatchedRBrace { span } => write!(
+                f,
+                "Found an unmatched closing parenthesis ')' at position {}.",
+                span.start
+            ),
+            UnmatchedLBracket { span } => write!(
+                f,
+                "Found an unmatched opening bracket '[' at position {}.",
+                span.start
+            ),
+            UnmatchedRBracket { span } => write!(
+                f,
+                "Found an unmatched closing bracket ']' at position {}.",
+                span.start
+            ),
+            MissingOperatorOutsideOfBrace => write!(
                 f,
                 "Found an opening parenthesis that is preceded by something that does not take \
                  any arguments on the right, or found a closing parenthesis that is succeeded by \
                  something that does not take any arguments on the left."
             ),
-            UnmatchedPartialToken { first, second } => {
+            UnmatchedPartialToken {
+                first,
+                second,
+                span,
+            } => {
                 if let Some(second) = second {
                     write!(
                         f,
-                        "Found a partial token '{}' that should not be followed by '{}'.",
-                        first, second
+                        "Found a partial token '{}' at position {} that should not be followed by \
+                         '{}'.",
+                        first, span.start, second
                     )
                 } else {
                     write!(
                         f,
-                        "Found a partial token '{}' that should be followed by another partial \
-                         token.",
-                        first
+                        "Found a partial token '{}' at position {} that should be followed by \
+                         another partial token.",
+                        first, span.start
                     )
                 }
             },
@@ -104,14 +190,70 @@ impl fmt::Display for EvalexprError {
             DivisionError { dividend, divisor } => {
                 write!(f, "Error dividing {} / {}", dividend, divisor)
             },
+            DivisionByZero { dividend } => write!(f, "Error dividing {} by zero", dividend),
             ModulationError { dividend, divisor } => {
                 write!(f, "Error modulating {} % {}", dividend, divisor)
             },
+            ExponentiationError { base, exponent } => {
+                write!(f, "Error exponentiating {} ^ {}", base, exponent)
+            },
+            ShiftError { value, shift } => write!(f, "Error shifting {} by {} bits", value, shift),
+            IndexOutOfBounds { index, len } => write!(
+                f,
+                "Index {} is out of bounds for a value of length {}",
+                index, len
+            ),
+            InvalidRange { start, end } => write!(
+                f,
+                "The start {} of a range is greater than its end {}",
+                start, end
+            ),
+            KeyNotFound { key } => write!(f, "Key {:?} not found in map", key),
+            ColumnLengthMismatch {
+                identifier,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "The column {:?} has {} rows, but the first column has {} rows.",
+                identifier, actual, expected
+            ),
+            ConversionError { value, target } => {
+                write!(
+                    f,
+                    "Cannot convert {} into a value of type {:?}",
+                    value, target
+                )
+            },
+            NotJsonRepresentable { value } => {
+                write!(
+                    f,
+                    "The value {} cannot be represented as a JSON value",
+                    value
+                )
+            },
             InvalidRegex { regex, message } => write!(
                 f,
                 "Regular expression {:?} is invalid: {:?}",
                 regex, message
             ),
+            InvalidFormatString {
+                format_string,
+                message,
+            } => write!(
+                f,
+                "Format string {:?} is invalid: {}",
+                format_string, message
+            ),
+            InvalidDateTime { datetime, message } => {
+                write!(f, "Date and time {:?} is invalid: {}", datetime, message)
+            },
+            InvalidDuration { duration, message } => {
+                write!(f, "Duration {:?} is invalid: {}", duration, message)
+            },
+            InvalidBinaryFormat { message } => {
+                write!(f, "Invalid binary operator tree: {}", message)
+            },
             ContextNotMutable => write!(f, "Cannot manipulate context"),
             BuiltinFunctionsCannotBeEnabled => {
                 write!(f, "This context does not allow enabling builtin functions")
@@ -119,8 +261,88 @@ impl fmt::Display for EvalexprError {
             BuiltinFunctionsCannotBeDisabled => {
                 write!(f, "This context does not allow disabling builtin functions")
             },
+            RecursionLimitExceeded { max_depth } => write!(
+                f,
+                "The expression is nested deeper than the maximum depth of {}.",
+                max_depth
+            ),
+            BudgetExceeded {
+                max_node_evaluations,
+            } => write!(
+                f,
+                "The evaluation exceeded the budget of {} node evaluations.",
+                max_node_evaluations
+            ),
+            ForbiddenOperator { operator } => write!(
+                f,
+                "The operator {:?} is forbidden by the evaluation policy.",
+                operator
+            ),
+            ForbiddenFunction { identifier } => write!(
+                f,
+                "The function {:?} is forbidden by the evaluation policy.",
+                identifier
+            ),
+            TupleLengthLimitExceeded {
+                max_tuple_length,
+                actual,
+            } => write!(
+                f,
+                "A tuple with {} elements exceeds the maximum length of {}.",
+                actual, max_tuple_length
+            ),
+            StringLengthLimitExceeded {
+                max_string_length,
+                actual,
+            } => write!(
+                f,
+                "A string of length {} exceeds the maximum length of {}.",
+                actual, max_string_length
+            ),
+            UnterminatedComment { span } => write!(
+                f,
+                "Found an unterminated block comment starting at position {}.",
+                span.start
+            ),
+            IntLiteralOutOfRange { literal, span } => write!(
+                f,
+                "The integer literal {} at position {} does not fit into an integer.",
+                literal, span.start
+            ),
+            UnterminatedTemplateExpression { span } => write!(
+                f,
+                "Found an unterminated template expression starting at position {}.",
+                span.start
+            ),
+            TemplateExpressionFailed { span, error } => write!(
+                f,
+                "The template expression at position {} failed: {}",
+                span.start, error
+            ),
             IllegalEscapeSequence(string) => write!(f, "Illegal escape sequence: {}", string),
             CustomMessage(message) => write!(f, "Error: {}", message),
+            #[cfg(feature = "std")]
+            FunctionError { name, source } if name.is_empty() => {
+                write!(f, "A function failed: {}", source)
+            },
+            #[cfg(feature = "std")]
+            FunctionError { name, source } => write!(f, "Function {:?} failed: {}", name, source),
+        }
+    }
+}
+
+fn write_suggestions(f: &mut fmt::Formatter, suggestions: &[String]) -> Result<(), fmt::Error> {
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+    write!(f, " Did you mean ")?;
+    for (index, suggestion) in suggestions.iter().enumerate() {
+        match index {
+            0 => {},
+            _ if index + 1 == suggestions.len() => write!(f, " or ")?,
+            _ => write!(f, ", ")?,
         }
+        write!(f, "{:?}", suggestion)?;
     }
+    write!(f, "?")
 }
@@ -5,13 +5,27 @@
 //! The module also contains some helper functions starting with `expect_` that check for a condition and return `Err(_)` if the condition is not fulfilled.
 //! They are meant as shortcuts to not write the same error checking code everywhere.
 
-use crate::{token::PartialToken, value::value_type::ValueType};
+use crate::{
+    token::{PartialToken, Span},
+    value::{value_type::ValueType, IntType},
+};
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
+use core::fmt;
+use core::ops::RangeInclusive;
 
 use crate::{operator::Operator, value::Value};
 
 // Exclude error display code from test coverage, as the code does not make sense to test.
 #[cfg(not(tarpaulin_include))]
 mod display;
+mod recovery;
+mod suggestions;
+
+pub(crate) use recovery::ErrorRecovery;
+use suggestions::suggest_identifiers;
 
 /// Errors used in this crate.
 #[derive(Debug, Clone, PartialEq)]
@@ -33,6 +47,22 @@ pub enum EvalexprError {
         actual: usize,
     },
 
+    /// A function was called with an amount of arguments outside of the range it accepts.
+    WrongFunctionArgumentAmountRange {
+        /// The range of accepted amounts of arguments.
+        expected: RangeInclusive<usize>,
+        /// The actual amount of arguments.
+        actual: usize,
+    },
+
+    /// A function was called with an amount of arguments that is not one of the amounts it accepts.
+    WrongFunctionArgumentAmountChoice {
+        /// The accepted amounts of arguments.
+        expected: Vec<usize>,
+        /// The actual amount of arguments.
+        actual: usize,
+    },
+
     /// A string value was expected.
     ExpectedString {
         /// The actual value.
@@ -51,15 +81,36 @@ pub enum EvalexprError {
         actual: Value,
     },
 
+    /// A decimal or integer value was expected.
+    #[cfg(feature = "decimal")]
+    ExpectedDecimal {
+        /// The actual value.
+        actual: Value,
+    },
+
+    /// A datetime value was expected.
+    #[cfg(feature = "chrono")]
+    ExpectedDateTime {
+        /// The actual value.
+        actual: Value,
+    },
+
+    /// A duration value was expected.
+    #[cfg(feature = "chrono")]
+    ExpectedDuration {
+        /// The actual value.
+        actual: Value,
+    },
+
     /// A numeric value was expected.
-    /// Numeric values are the variants `Value::Int` and `Value::Float`.
+    /// Numeric values are the variants `Value::Int` and `Value::Float`, as well as `Value::BigInt` and `Value::Decimal` with the respective features.
     ExpectedNumber {
         /// The actual value.
         actual: Value,
     },
 
     /// A numeric or string value was expected.
-    /// Numeric values are the variants `Value::Int` and `Value::Float`.
+    /// Numeric values are the variants `Value::Int` and `Value::Float`, as well as `Value::BigInt` and `Value::Decimal` with the respective features.
     ExpectedNumberOrString {
         /// The actual value.
         actual: Value,
@@ -85,6 +136,18 @@ pub enum EvalexprError {
         actual: Value,
     },
 
+    /// An array value was expected.
+    ExpectedArray {
+        /// The actual value.
+        actual: Value,
+    },
+
+    /// A map value was expected.
+    ExpectedMap {
+        /// The actual value.
+        actual: Value,
+    },
+
     /// An empty value was expected.
     ExpectedEmpty {
         /// The actual value.
@@ -101,10 +164,22 @@ pub enum EvalexprError {
     PrecedenceViolation,
 
     /// A `VariableIdentifier` operation did not find its value in the context.
-    VariableIdentifierNotFound(String),
+    VariableIdentifierNotFound {
+        /// The identifier of the variable.
+        identifier: String,
+        /// Identifiers of variables in the context that are most similar to the identifier.
+        suggestions: Vec<String>,
+        /// The part of the expression string the variable was read in, if the node reading it was parsed from a string.
+        span: Option<Span>,
+    },
 
     /// A `FunctionIdentifier` operation did not find its value in the context.
-    FunctionIdentifierNotFound(String),
+    FunctionIdentifierNotFound {
+        /// The identifier of the function.
+        identifier: String,
+        /// Identifiers of functions in the context or builtin functions that are most similar to the identifier.
+        suggestions: Vec<String>,
+    },
 
     /// A value has the wrong type.
     /// Only use this if there is no other error that describes the expected and provided types in more detail.
@@ -124,10 +199,28 @@ pub enum EvalexprError {
     },
 
     /// An opening brace without a matching closing brace was found.
-    UnmatchedLBrace,
+    UnmatchedLBrace {
+        /// The position of the unmatched opening brace in the expression string.
+        span: Span,
+    },
 
     /// A closing brace without a matching opening brace was found.
-    UnmatchedRBrace,
+    UnmatchedRBrace {
+        /// The position of the unmatched closing brace in the expression string.
+        span: Span,
+    },
+
+    /// An opening bracket without a matching closing bracket was found.
+    UnmatchedLBracket {
+        /// The position of the unmatched opening bracket in the expression string.
+        span: Span,
+    },
+
+    /// A closing bracket without a matching opening bracket was found.
+    UnmatchedRBracket {
+        /// The position of the unmatched closing bracket in the expression string.
+        span: Span,
+    },
 
     /// Left of an opening brace or right of a closing brace is a token that does not expect the brace next to it.
     /// For example, writing `4(5)` would yield this error, as the `4` does not have any operands.
@@ -141,6 +234,8 @@ pub enum EvalexprError {
         first: PartialToken,
         /// The token that follows the unmatched partial token and that cannot be matched to the partial token, or `None`, if `first` is the last partial token in the stream.
         second: Option<PartialToken>,
+        /// The position of the unmatched partial token in the expression string.
+        span: Span,
     },
 
     /// An addition operation performed by Rust failed.
@@ -181,6 +276,13 @@ pub enum EvalexprError {
         divisor: Value,
     },
 
+    /// An integer or decimal number was divided by zero, or the remainder of such a division was requested.
+    /// Dividing floats by zero results in infinity or `NaN` instead.
+    DivisionByZero {
+        /// The number that was divided by zero.
+        dividend: Value,
+    },
+
     /// A modulation operation performed by Rust failed.
     ModulationError {
         /// The first argument of the modulation.
@@ -189,6 +291,68 @@ pub enum EvalexprError {
         divisor: Value,
     },
 
+    /// An exponentiation of integers performed by Rust failed, because the result does not fit into an `IntType`.
+    ExponentiationError {
+        /// The base of the exponentiation.
+        base: Value,
+        /// The exponent of the exponentiation.
+        exponent: Value,
+    },
+
+    /// A bit shift performed by Rust failed, because the amount of bits to shift by is negative or too large.
+    ShiftError {
+        /// The value that was shifted.
+        value: Value,
+        /// The amount of bits to shift by.
+        shift: Value,
+    },
+
+    /// An index was outside of the bounds of the indexed value.
+    IndexOutOfBounds {
+        /// The index that was out of bounds.
+        index: IntType,
+        /// The length of the indexed value.
+        len: usize,
+    },
+
+    /// The start of a range of indices is greater than its end.
+    InvalidRange {
+        /// The start of the range.
+        start: IntType,
+        /// The end of the range.
+        end: IntType,
+    },
+
+    /// A map does not contain the key it was indexed with.
+    KeyNotFound {
+        /// The key that was not found.
+        key: String,
+    },
+
+    /// A value cannot be converted into the requested type, like the string `"abc"` into an integer.
+    ConversionError {
+        /// The value that could not be converted.
+        value: Value,
+        /// The type the value should have been converted into.
+        target: ValueType,
+    },
+
+    /// A value cannot be represented as a JSON value, like a non-finite float.
+    NotJsonRepresentable {
+        /// The value that cannot be represented.
+        value: Value,
+    },
+
+    /// A column for the vectorized evaluation has a different length than the first column.
+    ColumnLengthMismatch {
+        /// The identifier of the variable bound to the column.
+        identifier: String,
+        /// The length of the first column.
+        expected: usize,
+        /// The length of the column.
+        actual: usize,
+    },
+
     /// A regular expression could not be parsed
     InvalidRegex {
         /// The invalid regular expression
@@ -197,9 +361,108 @@ pub enum EvalexprError {
         message: String,
     },
 
+    /// A format string passed to a builtin function like `format` is invalid or does not match its arguments.
+    InvalidFormatString {
+        /// The invalid format string.
+        format_string: String,
+        /// A description of the problem.
+        message: String,
+    },
+
+    /// A string could not be parsed as a date and time.
+    InvalidDateTime {
+        /// The string that could not be parsed.
+        datetime: String,
+        /// A description of the problem.
+        message: String,
+    },
+
+    /// A string passed to the builtin function `duration` could not be parsed as a duration.
+    InvalidDuration {
+        /// The string that could not be parsed.
+        duration: String,
+        /// A description of the problem.
+        message: String,
+    },
+
+    /// Bytes passed to `Node::from_binary` are not an operator tree in the binary format of this build.
+    InvalidBinaryFormat {
+        /// A description of the problem.
+        message: String,
+    },
+
     /// A modification was attempted on a `Context` that does not allow modifications.
     ContextNotMutable,
 
+    /// The operator tree of an expression is nested deeper than allowed by the `ParserConfig`.
+    RecursionLimitExceeded {
+        /// The maximum allowed depth of the operator tree.
+        max_depth: usize,
+    },
+
+    /// The evaluation of an expression needed more node evaluations than allowed by the `EvalOptions`.
+    BudgetExceeded {
+        /// The maximum allowed amount of node evaluations.
+        max_node_evaluations: usize,
+    },
+
+    /// An expression uses an operator that is forbidden by the `EvaluationPolicy`.
+    ForbiddenOperator {
+        /// The forbidden operator.
+        operator: Operator,
+    },
+
+    /// An expression calls a function that is forbidden by the `EvaluationPolicy`.
+    ForbiddenFunction {
+        /// The identifier of the forbidden function.
+        identifier: String,
+    },
+
+    /// A tuple or array has more elements than allowed by the `EvaluationPolicy`.
+    TupleLengthLimitExceeded {
+        /// The maximum allowed amount of elements.
+        max_tuple_length: usize,
+        /// The actual amount of elements.
+        actual: usize,
+    },
+
+    /// A string is longer than allowed by the `EvaluationPolicy`.
+    StringLengthLimitExceeded {
+        /// The maximum allowed length in bytes.
+        max_string_length: usize,
+        /// The actual length in bytes.
+        actual: usize,
+    },
+
+    /// A block comment `/* ... */` was opened but not closed.
+    UnterminatedComment {
+        /// The position of the unterminated comment in the expression string, reaching until the end of the string.
+        span: Span,
+    },
+
+    /// An integer literal does not fit into an `IntType`.
+    /// With the `bigint` feature, such literals are parsed as `Value::BigInt` instead.
+    IntLiteralOutOfRange {
+        /// The integer literal as written in the expression string.
+        literal: String,
+        /// The position of the integer literal in the expression string.
+        span: Span,
+    },
+
+    /// An expression in a template was opened, but its closing delimiter was not found.
+    UnterminatedTemplateExpression {
+        /// The position of the unterminated expression in the template, from its opening delimiter until the end of the template.
+        span: Span,
+    },
+
+    /// An expression in a template could not be parsed or evaluated.
+    TemplateExpressionFailed {
+        /// The position of the expression in the template, without its delimiters.
+        span: Span,
+        /// The error of the expression, with its positions relative to the template.
+        error: Box<EvalexprError>,
+    },
+
     /// An escape sequence within a string literal is illegal.
     IllegalEscapeSequence(String),
 
@@ -211,6 +474,51 @@ pub enum EvalexprError {
 
     /// A custom error explained by its message.
     CustomMessage(String),
+
+    /// A function failed with an application error.
+    /// Such errors are created with `EvalexprError::function_error`.
+    #[cfg(feature = "std")]
+    FunctionError {
+        /// The identifier of the function that returned the error.
+        name: String,
+        /// The error returned by the function.
+        source: FunctionErrorSource,
+    },
+}
+
+/// An application error returned by a function, see `EvalexprError::FunctionError`.
+///
+/// The error is reference counted, so that `EvalexprError` can be cloned.
+/// Two sources are equal if they refer to the same error.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct FunctionErrorSource(Arc<dyn std::error::Error + Send + Sync>);
+
+#[cfg(feature = "std")]
+impl FunctionErrorSource {
+    /// Returns the error returned by the function.
+    pub fn error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self.0.as_ref()
+    }
+
+    /// Returns the error returned by the function if it has the type `E`.
+    pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq for FunctionErrorSource {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for FunctionErrorSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
 }
 
 impl EvalexprError {
@@ -222,6 +530,119 @@ impl EvalexprError {
         EvalexprError::WrongFunctionArgumentAmount { actual, expected }
     }
 
+    /// Constructs `EvalexprError::FunctionError` from an application error returned by a function.
+    ///
+    /// The identifier of the function is filled in when the error is returned from the call of the function.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! {
+    ///     "parse" => Function::new(|argument| {
+    ///         let int: i64 = argument.as_string()?.parse().map_err(EvalexprError::function_error)?;
+    ///         Ok(Value::from(int))
+    ///     }),
+    /// }
+    /// .unwrap(); // Do proper error handling here
+    /// assert_eq!(eval_with_context("parse(\"42\")", &context), Ok(Value::from(42)));
+    ///
+    /// match eval_with_context("parse(\"x\")", &context) {
+    ///     Err(EvalexprError::FunctionError { name, source }) => {
+    ///         assert_eq!(name, "parse");
+    ///         assert!(source.downcast_ref::<std::num::ParseIntError>().is_some());
+    ///     },
+    ///     result => panic!("unexpected result {:?}", result),
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn function_error<E: std::error::Error + Send + Sync + 'static>(error: E) -> Self {
+        EvalexprError::FunctionError {
+            name: String::new(),
+            source: FunctionErrorSource(Arc::new(error)),
+        }
+    }
+
+    /// Fills in the identifier of the function that returned this error, if this is a `FunctionError` without one.
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    pub(crate) fn returned_from_function(self, identifier: &str) -> Self {
+        match self {
+            #[cfg(feature = "std")]
+            EvalexprError::FunctionError { name, source } if name.is_empty() => {
+                EvalexprError::FunctionError {
+                    name: identifier.into(),
+                    source,
+                }
+            },
+            error => error,
+        }
+    }
+
+    pub(crate) fn wrong_function_argument_amount_range(
+        actual: usize,
+        expected: RangeInclusive<usize>,
+    ) -> Self {
+        EvalexprError::WrongFunctionArgumentAmountRange { actual, expected }
+    }
+
+    pub(crate) fn wrong_function_argument_amount_choice(
+        actual: usize,
+        expected: Vec<usize>,
+    ) -> Self {
+        EvalexprError::WrongFunctionArgumentAmountChoice { actual, expected }
+    }
+
+    /// Constructs `EvalexprError::VariableIdentifierNotFound` without suggestions and without a span.
+    pub fn variable_identifier_not_found(identifier: String) -> Self {
+        EvalexprError::VariableIdentifierNotFound {
+            identifier,
+            suggestions: Vec::new(),
+            span: None,
+        }
+    }
+
+    /// Constructs `EvalexprError::FunctionIdentifierNotFound` without suggestions.
+    pub fn function_identifier_not_found(identifier: String) -> Self {
+        EvalexprError::FunctionIdentifierNotFound {
+            identifier,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Adds suggestions from the given candidates to `EvalexprError::VariableIdentifierNotFound` and `EvalexprError::FunctionIdentifierNotFound`.
+    pub(crate) fn with_suggestions<'a>(
+        mut self,
+        candidates: impl Iterator<Item = &'a str>,
+    ) -> Self {
+        if let EvalexprError::VariableIdentifierNotFound {
+            identifier,
+            suggestions,
+            ..
+        }
+        | EvalexprError::FunctionIdentifierNotFound {
+            identifier,
+            suggestions,
+        } = &mut self
+        {
+            *suggestions = suggest_identifiers(identifier, candidates);
+        }
+        self
+    }
+
+    /// Sets the span of `EvalexprError::VariableIdentifierNotFound` to the given span, unless it already has one.
+    /// So when the error passes the nodes that contain the failing node, it keeps the span of the innermost node.
+    pub(crate) fn with_span(mut self, span: Option<Span>) -> Self {
+        if let EvalexprError::VariableIdentifierNotFound {
+            span: error_span @ None,
+            ..
+        } = &mut self
+        {
+            *error_span = span;
+        }
+        self
+    }
+
     /// Constructs `EvalexprError::TypeError{actual, expected}`.
     pub fn type_error(actual: Value, expected: Vec<ValueType>) -> Self {
         EvalexprError::TypeError { actual, expected }
@@ -247,6 +668,24 @@ impl EvalexprError {
         EvalexprError::ExpectedFloat { actual }
     }
 
+    /// Constructs `EvalexprError::ExpectedDecimal{actual}`.
+    #[cfg(feature = "decimal")]
+    pub fn expected_decimal(actual: Value) -> Self {
+        EvalexprError::ExpectedDecimal { actual }
+    }
+
+    /// Constructs `EvalexprError::ExpectedDateTime{actual}`.
+    #[cfg(feature = "chrono")]
+    pub fn expected_datetime(actual: Value) -> Self {
+        EvalexprError::ExpectedDateTime { actual }
+    }
+
+    /// Constructs `EvalexprError::ExpectedDuration{actual}`.
+    #[cfg(feature = "chrono")]
+    pub fn expected_duration(actual: Value) -> Self {
+        EvalexprError::ExpectedDuration { actual }
+    }
+
     /// Constructs `EvalexprError::ExpectedNumber{actual}`.
     pub fn expected_number(actual: Value) -> Self {
         EvalexprError::ExpectedNumber { actual }
@@ -275,6 +714,16 @@ impl EvalexprError {
         }
     }
 
+    /// Constructs `EvalexprError::ExpectedArray{actual}`.
+    pub fn expected_array(actual: Value) -> Self {
+        EvalexprError::ExpectedArray { actual }
+    }
+
+    /// Constructs `EvalexprError::ExpectedMap{actual}`.
+    pub fn expected_map(actual: Value) -> Self {
+        EvalexprError::ExpectedMap { actual }
+    }
+
     /// Constructs `EvalexprError::ExpectedEmpty{actual}`.
     pub fn expected_empty(actual: Value) -> Self {
         EvalexprError::ExpectedEmpty { actual }
@@ -282,12 +731,26 @@ impl EvalexprError {
 
     /// Constructs an error that expresses that the type of `expected` was expected, but `actual` was found.
     pub(crate) fn expected_type(expected: &Value, actual: Value) -> Self {
-        match ValueType::from(expected) {
+        Self::expected_value_type(ValueType::from(expected), actual)
+    }
+
+    /// Constructs an error that expresses that a value of the `expected` type was expected, but `actual` was found.
+    pub(crate) fn expected_value_type(expected: ValueType, actual: Value) -> Self {
+        match expected {
             ValueType::String => Self::expected_string(actual),
             ValueType::Int => Self::expected_int(actual),
             ValueType::Float => Self::expected_float(actual),
+            #[cfg(feature = "decimal")]
+            ValueType::Decimal => Self::expected_decimal(actual),
+            #[cfg(feature = "chrono")]
+            ValueType::DateTime => Self::expected_datetime(actual),
+            #[cfg(feature = "chrono")]
+            ValueType::Duration => Self::expected_duration(actual),
             ValueType::Boolean => Self::expected_boolean(actual),
             ValueType::Tuple => Self::expected_tuple(actual),
+            ValueType::Array => Self::expected_array(actual),
+            ValueType::Map => Self::expected_map(actual),
+            ValueType::Lambda => Self::type_error(actual, vec![ValueType::Lambda]),
             ValueType::Empty => Self::expected_empty(actual),
         }
     }
@@ -295,8 +758,65 @@ impl EvalexprError {
     pub(crate) fn unmatched_partial_token(
         first: PartialToken,
         second: Option<PartialToken>,
+        span: Span,
     ) -> Self {
-        EvalexprError::UnmatchedPartialToken { first, second }
+        EvalexprError::UnmatchedPartialToken {
+            first,
+            second,
+            span,
+        }
+    }
+
+    pub(crate) fn recursion_limit_exceeded(max_depth: usize) -> Self {
+        EvalexprError::RecursionLimitExceeded { max_depth }
+    }
+
+    pub(crate) fn budget_exceeded(max_node_evaluations: usize) -> Self {
+        EvalexprError::BudgetExceeded {
+            max_node_evaluations,
+        }
+    }
+
+    pub(crate) fn forbidden_operator(operator: Operator) -> Self {
+        EvalexprError::ForbiddenOperator { operator }
+    }
+
+    pub(crate) fn forbidden_function(identifier: String) -> Self {
+        EvalexprError::ForbiddenFunction { identifier }
+    }
+
+    pub(crate) fn tuple_length_limit_exceeded(max_tuple_length: usize, actual: usize) -> Self {
+        EvalexprError::TupleLengthLimitExceeded {
+            max_tuple_length,
+            actual,
+        }
+    }
+
+    pub(crate) fn string_length_limit_exceeded(max_string_length: usize, actual: usize) -> Self {
+        EvalexprError::StringLengthLimitExceeded {
+            max_string_length,
+            actual,
+        }
+    }
+
+    pub(crate) fn unterminated_comment(span: Span) -> Self {
+        EvalexprError::UnterminatedComment { span }
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    pub(crate) fn int_literal_out_of_range(literal: String, span: Span) -> Self {
+        EvalexprError::IntLiteralOutOfRange { literal, span }
+    }
+
+    pub(crate) fn unterminated_template_expression(span: Span) -> Self {
+        EvalexprError::UnterminatedTemplateExpression { span }
+    }
+
+    pub(crate) fn template_expression_failed(span: Span, error: EvalexprError) -> Self {
+        EvalexprError::TemplateExpressionFailed {
+            span,
+            error: Box::new(error),
+        }
     }
 
     pub(crate) fn addition_error(augend: Value, addend: Value) -> Self {
@@ -325,14 +845,83 @@ impl EvalexprError {
         EvalexprError::DivisionError { dividend, divisor }
     }
 
+    pub(crate) fn division_by_zero(dividend: Value) -> Self {
+        EvalexprError::DivisionByZero { dividend }
+    }
+
     pub(crate) fn modulation_error(dividend: Value, divisor: Value) -> Self {
         EvalexprError::ModulationError { dividend, divisor }
     }
 
+    pub(crate) fn exponentiation_error(base: Value, exponent: Value) -> Self {
+        EvalexprError::ExponentiationError { base, exponent }
+    }
+
+    pub(crate) fn shift_error(value: Value, shift: Value) -> Self {
+        EvalexprError::ShiftError { value, shift }
+    }
+
+    /// Constructs `EvalexprError::IndexOutOfBounds{index, len}`.
+    pub fn index_out_of_bounds(index: IntType, len: usize) -> Self {
+        EvalexprError::IndexOutOfBounds { index, len }
+    }
+
+    /// Constructs `EvalexprError::InvalidRange{start, end}`.
+    pub fn invalid_range(start: IntType, end: IntType) -> Self {
+        EvalexprError::InvalidRange { start, end }
+    }
+
+    /// Constructs `EvalexprError::KeyNotFound{key}`.
+    pub fn key_not_found(key: String) -> Self {
+        EvalexprError::KeyNotFound { key }
+    }
+
+    /// Constructs `EvalexprError::ConversionError{value, target}`.
+    pub fn conversion_error(value: Value, target: ValueType) -> Self {
+        EvalexprError::ConversionError { value, target }
+    }
+
+    /// Constructs `EvalexprError::NotJsonRepresentable{value}`.
+    pub fn not_json_representable(value: Value) -> Self {
+        EvalexprError::NotJsonRepresentable { value }
+    }
+
+    pub(crate) fn column_length_mismatch(identifier: &str, expected: usize, actual: usize) -> Self {
+        EvalexprError::ColumnLengthMismatch {
+            identifier: identifier.into(),
+            expected,
+            actual,
+        }
+    }
+
     /// Constructs `EvalexprError::InvalidRegex(regex)`
     pub fn invalid_regex(regex: String, message: String) -> Self {
         EvalexprError::InvalidRegex { regex, message }
     }
+
+    /// Constructs `EvalexprError::InvalidFormatString{format_string, message}`.
+    pub(crate) fn invalid_format_string(format_string: String, message: String) -> Self {
+        EvalexprError::InvalidFormatString {
+            format_string,
+            message,
+        }
+    }
+
+    /// Constructs `EvalexprError::InvalidDateTime{datetime, message}`.
+    pub(crate) fn invalid_datetime(datetime: String, message: String) -> Self {
+        EvalexprError::InvalidDateTime { datetime, message }
+    }
+
+    /// Constructs `EvalexprError::InvalidDuration{duration, message}`.
+    pub(crate) fn invalid_duration(duration: String, message: String) -> Self {
+        EvalexprError::InvalidDuration { duration, message }
+    }
+
+    /// Constructs `EvalexprError::InvalidBinaryFormat{message}`.
+    #[cfg(feature = "bincode")]
+    pub(crate) fn invalid_binary_format(message: String) -> Self {
+        EvalexprError::InvalidBinaryFormat { message }
+    }
 }
 
 /// Returns `Ok(())` if the actual and expected parameters are equal, and `Err(Error::WrongOperatorArgumentAmount)` otherwise.
@@ -360,15 +949,37 @@ pub fn expect_function_argument_amount(actual: usize, expected: usize) -> Evalex
     }
 }
 
+/// Returns `Ok(())` if the given value has the expected type, and an error like `Err(Error::ExpectedInt)` otherwise.
+pub fn expect_value_type(actual: &Value, expected: ValueType) -> EvalexprResult<()> {
+    if ValueType::from(actual) == expected {
+        Ok(())
+    } else {
+        Err(EvalexprError::expected_value_type(expected, actual.clone()))
+    }
+}
+
 /// Returns `Ok(())` if the given value is a string or a numeric
 pub fn expect_number_or_string(actual: &Value) -> EvalexprResult<()> {
     match actual {
         Value::String(_) | Value::Float(_) | Value::Int(_) => Ok(()),
+        #[cfg(feature = "bigint")]
+        Value::BigInt(_) => Ok(()),
+        #[cfg(feature = "decimal")]
+        Value::Decimal(_) => Ok(()),
         _ => Err(EvalexprError::expected_number_or_string(actual.clone())),
     }
 }
 
-impl std::error::Error for EvalexprError {}
+#[cfg(feature = "std")]
+impl std::error::Error for EvalexprError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EvalexprError::FunctionError { source, .. } => Some(source.0.as_ref()),
+            EvalexprError::TemplateExpressionFailed { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 /// Standard result type used by this crate.
 pub type EvalexprResult<T> = Result<T, EvalexprError>;
@@ -376,6 +987,7 @@ pub type EvalexprResult<T> = Result<T, EvalexprError>;
 #[cfg(test)]
 mod tests {
     use crate::{EvalexprError, Value, ValueType};
+    use alloc::{string::ToString, vec};
 
     /// Tests whose only use is to bring test coverage of trivial lines up, like trivial constructors.
     #[test]
@@ -0,0 +1,47 @@
+use alloc::vec::Vec;
+
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    token::Span,
+};
+
+/// Decides whether parsing fails at the first error, or records the errors and skips past them.
+#[derive(Debug, Default)]
+pub(crate) struct ErrorRecovery {
+    errors: Option<Vec<(EvalexprError, Span)>>,
+}
+
+impl ErrorRecovery {
+    /// Creates an error recovery that makes parsing fail at the first error.
+    pub(crate) fn fail_fast() -> Self {
+        Self { errors: None }
+    }
+
+    /// Creates an error recovery that records all errors of parsing.
+    pub(crate) fn recovering() -> Self {
+        Self {
+            errors: Some(Vec::new()),
+        }
+    }
+
+    /// Returns true if errors are recorded instead of failing.
+    pub(crate) fn is_recovering(&self) -> bool {
+        self.errors.is_some()
+    }
+
+    /// Records the error at the given position and returns `Ok` if recovering, and returns the error otherwise.
+    pub(crate) fn recover(&mut self, error: EvalexprError, span: Span) -> EvalexprResult<()> {
+        match &mut self.errors {
+            Some(errors) => {
+                errors.push((error, span));
+                Ok(())
+            },
+            None => Err(error),
+        }
+    }
+
+    /// Returns the recorded errors in the order they occurred.
+    pub(crate) fn into_errors(self) -> Vec<(EvalexprError, Span)> {
+        self.errors.unwrap_or_default()
+    }
+}
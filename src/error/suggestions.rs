@@ -0,0 +1,101 @@
+use alloc::{string::String, vec, vec::Vec};
+
+/// The maximum amount of suggestions for an unknown identifier.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the candidates that are most similar to the given unknown identifier, in alphabetical order.
+///
+/// A candidate is similar if it differs from the identifier, but can be reached with at most a third of the length of the identifier in edits, rounded,
+/// where an edit inserts, removes or replaces a character, or swaps two adjacent characters.
+/// So no candidates are suggested for identifiers with a single character.
+pub(crate) fn suggest_identifiers<'a>(
+    identifier: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Vec<String> {
+    let identifier: Vec<char> = identifier.chars().collect();
+    let max_distance = (identifier.len() + 1) / 3;
+
+    let mut suggestions: Vec<(usize, &str)> = candidates
+        .filter_map(|candidate| {
+            let distance = edit_distance(&identifier, &candidate.chars().collect::<Vec<_>>());
+            (distance > 0 && distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    suggestions.sort_unstable();
+    suggestions.dedup();
+    let min_distance = suggestions.first().map(|(distance, _)| *distance);
+    suggestions
+        .into_iter()
+        .take_while(|(distance, _)| Some(*distance) == min_distance)
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.into())
+        .collect()
+}
+
+/// Returns the optimal string alignment distance between `a` and `b`,
+/// which is the Levenshtein distance that additionally counts swapping two adjacent characters as a single edit.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // The distances between prefixes of `a` and the prefixes of `b`, for the current and the two previous prefixes of `a`.
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let substitution_cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + substitution_cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        before_previous.clone_from(&previous);
+        previous.clone_from(&current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, suggest_identifiers};
+    use alloc::vec::Vec;
+
+    fn distance(a: &str, b: &str) -> usize {
+        edit_distance(
+            &a.chars().collect::<Vec<_>>(),
+            &b.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("abc", ""), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("lenght", "length"), 1);
+        assert_eq!(distance("ca", "abc"), 3);
+        assert_eq!(distance("äöü", "aöü"), 1);
+    }
+
+    #[test]
+    fn test_suggest_identifiers() {
+        let candidates = ["length", "height", "width", "len", "lengths"];
+        assert_eq!(
+            suggest_identifiers("lenght", candidates.iter().copied()),
+            ["length"]
+        );
+        assert_eq!(
+            suggest_identifiers("xy", ["y", "x", "xy", "yx", "xyz", "abc"].iter().copied()),
+            ["x", "xyz", "y"]
+        );
+        assert!(suggest_identifiers("x", ["y", "xy"].iter().copied()).is_empty());
+        assert_eq!(
+            suggest_identifiers("heigth", candidates.iter().copied()),
+            ["height"]
+        );
+        assert!(suggest_identifiers("foo", candidates.iter().copied()).is_empty());
+    }
+}
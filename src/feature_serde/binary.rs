@@ -0,0 +1,127 @@
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    feature_serde::structured,
+    Node,
+};
+use alloc::{format, string::ToString, vec::Vec};
+use bincode::Options;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The bytes every operator tree in the binary format starts with.
+const MAGIC: [u8; 4] = *b"EVXB";
+
+/// The version of the binary format, which is increased whenever the encoding of operator trees changes.
+const VERSION: u8 = 1;
+
+/// The features that add variants to `Value`, which change the encoding of constants.
+const FEATURES: &[(&str, bool)] = &[
+    ("bigint", cfg!(feature = "bigint")),
+    ("decimal", cfg!(feature = "decimal")),
+    ("chrono", cfg!(feature = "chrono")),
+];
+
+/// The length of the header in front of the encoded operator tree.
+const HEADER_LENGTH: usize = MAGIC.len() + 2;
+
+fn feature_flags() -> u8 {
+    FEATURES
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, enabled))| *enabled)
+        .fold(0, |flags, (index, _)| flags | 1 << index)
+}
+
+fn feature_names(flags: u8) -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| flags & 1 << index != 0)
+        .map(|(_, (name, _))| *name)
+        .collect()
+}
+
+struct BinaryNode<'a>(&'a Node);
+
+impl Serialize for BinaryNode<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        structured::serialize(self.0, serializer)
+    }
+}
+
+struct OwnedBinaryNode(Node);
+
+impl<'de> Deserialize<'de> for OwnedBinaryNode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        structured::deserialize(deserializer).map(OwnedBinaryNode)
+    }
+}
+
+impl Node {
+    /// Serializes the operator tree rooted at this node into a compact binary format,
+    /// which can be stored and turned back into an operator tree with `Node::from_binary` without parsing the expression again.
+    ///
+    /// The format is the `structured` format encoded with [bincode](https://docs.rs/bincode) using its default options,
+    /// behind a header that holds the version of the format and the features that change the encoding of constants.
+    /// Operator trees can only be deserialized by builds of this crate that support the same version and have the same features enabled,
+    /// so a cache of serialized trees should be rebuilt from the expressions if deserializing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("a * 2 + max(b, 3)").unwrap(); // Do proper error handling here
+    /// let bytes = tree.to_binary().unwrap(); // Do proper error handling here
+    /// let deserialized = Node::from_binary(&bytes).unwrap(); // Do proper error handling here
+    ///
+    /// assert_eq!(deserialized, tree);
+    /// assert!(Node::from_binary(&bytes[..bytes.len() - 1]).is_err());
+    /// ```
+    pub fn to_binary(&self) -> EvalexprResult<Vec<u8>> {
+        let mut bytes = Vec::from(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(feature_flags());
+        bincode::options()
+            .serialize_into(&mut bytes, &BinaryNode(self))
+            .map_err(|error| EvalexprError::invalid_binary_format(error.to_string()))?;
+        Ok(bytes)
+    }
+
+    /// Deserializes an operator tree from the binary format written by `Node::to_binary`.
+    ///
+    /// Fails with `EvalexprError::InvalidBinaryFormat` if the bytes were written by a different version of the format
+    /// or a build with different features, or if they do not encode a valid operator tree.
+    pub fn from_binary(bytes: &[u8]) -> EvalexprResult<Node> {
+        if bytes.len() < HEADER_LENGTH || bytes[..MAGIC.len()] != MAGIC {
+            return Err(EvalexprError::invalid_binary_format(
+                "the bytes do not start with the header of the binary format".to_string(),
+            ));
+        }
+
+        let (version, flags) = (bytes[MAGIC.len()], bytes[MAGIC.len() + 1]);
+        if version != VERSION {
+            return Err(EvalexprError::invalid_binary_format(format!(
+                "the version of the format is {}, but only version {} is supported",
+                version, VERSION
+            )));
+        }
+        if flags != feature_flags() {
+            return Err(EvalexprError::invalid_binary_format(format!(
+                "the operator tree was serialized with the features {:?}, but the features {:?} are enabled",
+                feature_names(flags),
+                feature_names(feature_flags())
+            )));
+        }
+
+        bincode::options()
+            .deserialize::<OwnedBinaryNode>(&bytes[HEADER_LENGTH..])
+            .map(|node| node.0)
+            .map_err(|error| EvalexprError::invalid_binary_format(error.to_string()))
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{interface::build_operator_tree, Node};
-use serde::{de, Deserialize, Deserializer};
-use std::fmt;
+use core::fmt;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "bincode")]
+mod binary;
+pub mod structured;
 
 impl<'de> Deserialize<'de> for Node {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -33,3 +37,12 @@ impl<'de> de::Visitor<'de> for NodeVisitor {
         }
     }
 }
+
+impl Serialize for Node {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
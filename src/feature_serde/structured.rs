@@ -0,0 +1,106 @@
+//! A structured serialization format for operator trees.
+//!
+//! By default, `Node` is serialized as its expression string.
+//! The functions in this module instead serialize each node as a structure with the fields `operator` and `children`,
+//! which represents the operator tree exactly and can be inspected and generated without parsing expressions.
+//! They are meant to be used with the `#[serde(with = "evalexpr::structured")]` attribute.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_support")] {
+//! extern crate ron;
+//! use evalexpr::*;
+//! use serde_derive::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Rule {
+//!     #[serde(with = "evalexpr::structured")]
+//!     condition: Node,
+//! }
+//!
+//! let rule = Rule { condition: Node::not(Node::variable("a")) };
+//! let serialized = ron::ser::to_string(&rule).unwrap(); // Do proper error handling here
+//! assert_eq!(
+//!     serialized,
+//!     "(condition:(operator:Not,children:[(operator:VariableIdentifierRead(identifier:\"a\"),children:[])]))"
+//! );
+//! let deserialized: Rule = ron::de::from_str(&serialized).unwrap(); // Do proper error handling here
+//! assert_eq!(deserialized.condition, rule.condition);
+//! # }
+//! ```
+
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    operator::Operator,
+    Node,
+};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes the operator tree rooted at the given node in the structured format.
+pub fn serialize<S>(node: &Node, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    StructuredNode(node).serialize(serializer)
+}
+
+/// Deserializes an operator tree from the structured format.
+///
+/// Fails if an operator has a different amount of children than it requires.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Node, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    CheckedNode::deserialize(deserializer).map(|node| node.0)
+}
+
+struct StructuredNode<'a>(&'a Node);
+
+impl Serialize for StructuredNode<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Node", 2)?;
+        state.serialize_field("operator", self.0.operator())?;
+        state.serialize_field("children", &StructuredChildren(self.0.children()))?;
+        state.end()
+    }
+}
+
+struct StructuredChildren<'a>(&'a [Node]);
+
+impl Serialize for StructuredChildren<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.0.iter().map(StructuredNode))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Node")]
+struct OwnedStructuredNode {
+    operator: Operator,
+    #[serde(default)]
+    children: Vec<CheckedNode>,
+}
+
+/// A node whose operator was checked to have the right amount of children while deserializing it.
+#[derive(Deserialize)]
+#[serde(try_from = "OwnedStructuredNode")]
+struct CheckedNode(Node);
+
+impl TryFrom<OwnedStructuredNode> for CheckedNode {
+    type Error = EvalexprError;
+
+    fn try_from(node: OwnedStructuredNode) -> EvalexprResult<Self> {
+        // The children are collected in place, as `CheckedNode` has the same layout as `Node`.
+        let children = node.children.into_iter().map(|child| child.0).collect();
+        Node::with_checked_children(node.operator, children).map(CheckedNode)
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    value::{MapType, Value},
+    ContextWithMutableVariables, HashMapContext,
+};
+use core::convert::TryFrom;
+use serde_json::{Map, Number};
+
+/// Converts a JSON value into a value.
+///
+/// JSON arrays become `Value::Array`s, objects become `Value::Map`s and `null` becomes `Value::Empty`.
+/// Numbers become `Value::Int`s if they fit into an `IntType`, and `Value::Float`s otherwise.
+/// With the `bigint` feature, integers that do not fit into an `IntType` become `Value::BigInt`s instead.
+impl From<serde_json::Value> for Value {
+    fn from(json: serde_json::Value) -> Self {
+        match json {
+            serde_json::Value::Null => Value::Empty,
+            serde_json::Value::Bool(boolean) => Value::Boolean(boolean),
+            serde_json::Value::Number(number) => {
+                if let Some(int) = number.as_i64() {
+                    Value::Int(int)
+                } else {
+                    #[cfg(feature = "bigint")]
+                    if let Some(int) = number.as_u64() {
+                        return Value::from(crate::value::BigIntType::from(int));
+                    }
+                    // Numbers that are not integers can always be represented as floats.
+                    Value::Float(number.as_f64().unwrap_or(f64::NAN))
+                }
+            },
+            serde_json::Value::String(string) => Value::String(string),
+            serde_json::Value::Array(array) => {
+                Value::Array(array.into_iter().map(Value::from).collect())
+            },
+            serde_json::Value::Object(object) => Value::Map(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Converts a value into a JSON value.
+///
+/// Tuples and arrays become JSON arrays, maps become objects and `Value::Empty` becomes `null`.
+/// Decimal numbers become the nearest float, and dates and durations become strings as they are displayed.
+///
+/// Fails with `EvalexprError::NotJsonRepresentable` if the value contains a float that is not finite,
+/// or a big integer that fits into neither an `i64` nor a `u64`.
+impl TryFrom<Value> for serde_json::Value {
+    type Error = EvalexprError;
+
+    fn try_from(value: Value) -> EvalexprResult<Self> {
+        match value {
+            Value::String(string) => Ok(serde_json::Value::String(string)),
+            Value::Float(float) => Number::from_f64(float)
+                .map(serde_json::Value::Number)
+                .ok_or_else(|| EvalexprError::not_json_representable(value)),
+            Value::Int(int) => Ok(serde_json::Value::Number(int.into())),
+            #[cfg(feature = "bigint")]
+            Value::BigInt(int) => {
+                use num_traits::ToPrimitive;
+                match (int.to_i64(), int.to_u64()) {
+                    (Some(int), _) => Ok(serde_json::Value::Number(int.into())),
+                    (None, Some(int)) => Ok(serde_json::Value::Number(int.into())),
+                    (None, None) => Err(EvalexprError::not_json_representable(Value::BigInt(int))),
+                }
+            },
+            #[cfg(feature = "decimal")]
+            Value::Decimal(decimal) => {
+                use rust_decimal::prelude::ToPrimitive;
+                decimal
+                    .to_f64()
+                    .and_then(Number::from_f64)
+                    .map(serde_json::Value::Number)
+                    .ok_or_else(|| EvalexprError::not_json_representable(value))
+            },
+            #[cfg(feature = "chrono")]
+            Value::DateTime(_) | Value::Duration(_) => {
+                Ok(serde_json::Value::String(value.to_string()))
+            },
+            Value::Boolean(boolean) => Ok(serde_json::Value::Bool(boolean)),
+            Value::Tuple(values) | Value::Array(values) => values
+                .into_iter()
+                .map(serde_json::Value::try_from)
+                .collect::<EvalexprResult<_>>()
+                .map(serde_json::Value::Array),
+            Value::Map(map) => map
+                .into_iter()
+                .map(|(key, value)| Ok((key, serde_json::Value::try_from(value)?)))
+                .collect::<EvalexprResult<Map<_, _>>>()
+                .map(serde_json::Value::Object),
+            Value::Empty => Ok(serde_json::Value::Null),
+            Value::Lambda(_) => Err(EvalexprError::not_json_representable(value)),
+        }
+    }
+}
+
+impl HashMapContext {
+    /// Constructs a `HashMapContext` with a variable for each field of the given JSON object.
+    ///
+    /// The fields are converted with `Value::from`, so nested objects become `Value::Map`s whose fields can be accessed with dot paths.
+    /// Fails with `EvalexprError::ExpectedMap` if the given JSON value is not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "serde_json")] {
+    /// use evalexpr::*;
+    ///
+    /// let json = serde_json::json!({ "price": 2.5, "item": { "count": 3 } });
+    /// let context = HashMapContext::from_json(json).unwrap(); // Do proper error handling here
+    /// assert_eq!(eval_with_context("price * item.count", &context), Ok(Value::from(7.5)));
+    /// # }
+    /// ```
+    pub fn from_json(json: serde_json::Value) -> EvalexprResult<Self> {
+        let variables: MapType = match Value::from(json) {
+            Value::Map(map) => map,
+            value => return Err(EvalexprError::expected_map(value)),
+        };
+
+        let mut context = HashMapContext::new();
+        for (identifier, value) in variables {
+            context.set_value(identifier, value)?;
+        }
+        Ok(context)
+    }
+}
@@ -0,0 +1,57 @@
+use alloc::{boxed::Box, sync::Arc};
+use core::{fmt, future::Future, pin::Pin};
+
+use crate::{error::EvalexprResult, value::Value};
+
+/// The future returned by calling an `AsyncFunction`.
+pub type AsyncFunctionFuture = Pin<Box<dyn Future<Output = EvalexprResult<Value>> + Send>>;
+
+/// A user-defined function that is evaluated asynchronously, like a function that queries a database or an HTTP service.
+/// Async functions can be used in expressions by storing them in an `AsyncContext` and evaluating with `Node::eval_with_context_async`.
+///
+/// Unlike `Function`, an async function receives its argument by value, such that the returned future does not borrow from the expression.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let mut context = HashMapContext::new();
+/// context.set_async_function("fetch_price".into(), AsyncFunction::new(|argument| async move {
+///     // Query a database here
+///     Ok(Value::from(argument.as_int()? * 10))
+/// })).unwrap(); // Do proper error handling here
+///
+/// let tree = build_operator_tree("fetch_price(3) + 1").unwrap(); // Do proper error handling here
+/// let future = tree.eval_with_context_async(&context); // Await this future with an async runtime
+/// ```
+#[derive(Clone)]
+pub struct AsyncFunction {
+    function: Arc<dyn Fn(Value) -> AsyncFunctionFuture + Send + Sync>,
+}
+
+impl AsyncFunction {
+    /// Creates a user-defined async function from a closure that returns a future.
+    ///
+    /// The `function` is reference counted for storage, and the futures it returns are boxed.
+    pub fn new<F, Fut>(function: F) -> Self
+    where
+        F: Fn(Value) -> Fut,
+        F: Send + Sync + 'static,
+        Fut: Future<Output = EvalexprResult<Value>> + Send + 'static,
+    {
+        Self {
+            function: Arc::new(move |argument| Box::pin(function(argument)) as _),
+        }
+    }
+
+    pub(crate) fn call(&self, argument: Value) -> AsyncFunctionFuture {
+        (self.function)(argument)
+    }
+}
+
+impl fmt::Debug for AsyncFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "AsyncFunction {{ [...] }}")
+    }
+}
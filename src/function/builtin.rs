@@ -1,11 +1,33 @@
+#[cfg(feature = "rand")]
+use rand::{seq::SliceRandom, Rng};
 #[cfg(feature = "regex_support")]
 use regex::Regex;
 
 use crate::{
-    value::{FloatType, IntType},
-    EvalexprError, Function, Value, ValueType,
+    function::time,
+    operator::{compare_numbers, values_equal, Operator},
+    value::{FloatType, IntType, TupleType},
+    EmptyContext, EvalexprError, Function, Lambda, Value, ValueType,
 };
-use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
+    convert::TryFrom,
+    ops::{BitAnd, BitOr, BitXor, Not},
+};
+// Unused if the standard library is linked anyways, as in tests, because the inherent float methods take precedence.
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(feature = "bigint")]
+use num_traits::{Signed, Zero};
+#[cfg(feature = "decimal")]
+use rust_decimal::RoundingStrategy;
 
 macro_rules! simple_math {
     ($func:ident) => {
@@ -23,12 +45,33 @@ macro_rules! simple_math {
     };
 }
 
+macro_rules! rounding_math {
+    ($func:ident, $decimal_strategy:ident) => {
+        Some(Function::new(|argument| match argument {
+            Value::Int(int) => Ok(Value::Int(*int)),
+            #[cfg(feature = "bigint")]
+            Value::BigInt(int) => Ok(Value::BigInt(int.clone())),
+            #[cfg(feature = "decimal")]
+            Value::Decimal(decimal) => Ok(Value::Decimal(
+                decimal.round_dp_with_strategy(0, RoundingStrategy::$decimal_strategy),
+            )),
+            _ => Ok(Value::Float(argument.as_number()?.$func())),
+        }))
+    };
+}
+
 fn float_is(func: fn(FloatType) -> bool) -> Option<Function> {
     Some(Function::new(move |argument| {
         Ok(func(argument.as_number()?).into())
     }))
 }
 
+fn value_type_is(value_type: ValueType) -> Option<Function> {
+    Some(Function::new(move |argument| {
+        Ok((ValueType::from(argument) == value_type).into())
+    }))
+}
+
 macro_rules! int_function {
     ($func:ident) => {
         Some(Function::new(|argument| {
@@ -45,6 +88,534 @@ macro_rules! int_function {
     };
 }
 
+/// Shifts the first integer of the tuple by the amount of bits given by the second integer.
+/// Fails if the amount is negative or not smaller than the amount of bits of an `IntType`.
+fn shift(
+    argument: &Value,
+    checked_shift: fn(IntType, u32) -> Option<IntType>,
+) -> Result<Value, EvalexprError> {
+    let tuple = argument.as_fixed_len_tuple(2)?;
+    let (value, bits) = (tuple[0].as_int()?, tuple[1].as_int()?);
+    u32::try_from(bits)
+        .ok()
+        .and_then(|bits| checked_shift(value, bits))
+        .map(Value::Int)
+        .ok_or_else(|| EvalexprError::shift_error(tuple[0].clone(), tuple[1].clone()))
+}
+
+/// Applies the euclidean remainder function, or the euclidean division function if `remainder` is false, to the two numbers of the tuple.
+/// Integers and decimals are combined exactly, failing on a zero divisor or an overflow, and all other numbers as floats.
+fn euclid(argument: &Value, remainder: bool) -> Result<Value, EvalexprError> {
+    let tuple = argument.as_fixed_len_tuple(2)?;
+    let error = || {
+        if remainder {
+            EvalexprError::modulation_error(tuple[0].clone(), tuple[1].clone())
+        } else {
+            EvalexprError::division_error(tuple[0].clone(), tuple[1].clone())
+        }
+    };
+    match (&tuple[0], &tuple[1]) {
+        (Value::Int(_), Value::Int(0)) => Err(EvalexprError::division_by_zero(tuple[0].clone())),
+        (Value::Int(a), Value::Int(b)) => if remainder {
+            a.checked_rem_euclid(*b)
+        } else {
+            a.checked_div_euclid(*b)
+        }
+        .map(Value::Int)
+        .ok_or_else(error),
+        #[cfg(feature = "bigint")]
+        (Value::Int(_) | Value::BigInt(_), Value::Int(_) | Value::BigInt(_)) => {
+            let (a, b) = (tuple[0].as_bigint()?, tuple[1].as_bigint()?);
+            if b.is_zero() {
+                return Err(EvalexprError::division_by_zero(tuple[0].clone()));
+            }
+            let mut rem = &a % &b;
+            if rem.is_negative() {
+                rem += b.abs();
+            }
+            Ok(if remainder { rem } else { (a - rem) / b }.into())
+        },
+        #[cfg(feature = "decimal")]
+        (Value::Decimal(_), Value::Decimal(_) | Value::Int(_))
+        | (Value::Int(_), Value::Decimal(_)) => {
+            let (a, b) = (tuple[0].as_decimal()?, tuple[1].as_decimal()?);
+            if b.is_zero() {
+                return Err(EvalexprError::division_by_zero(tuple[0].clone()));
+            }
+            let mut rem = a.checked_rem(b).ok_or_else(error)?;
+            if rem.is_sign_negative() && !rem.is_zero() {
+                rem = rem.checked_add(b.abs()).ok_or_else(error)?;
+            }
+            if remainder {
+                Ok(Value::Decimal(rem))
+            } else {
+                // The difference is a multiple of the divisor, so the quotient is integral up to rounding errors.
+                a.checked_sub(rem)
+                    .and_then(|difference| difference.checked_div(b))
+                    .map(|quotient| Value::Decimal(quotient.round()))
+                    .ok_or_else(error)
+            }
+        },
+        (a, b) => {
+            let (a, b) = (a.as_number()?, b.as_number()?);
+            Ok(Value::Float(if remainder {
+                float_rem_euclid(a, b)
+            } else {
+                float_div_euclid(a, b)
+            }))
+        },
+    }
+}
+
+/// The euclidean remainder of two floats, which is never negative unless it is NaN.
+fn float_rem_euclid(a: FloatType, b: FloatType) -> FloatType {
+    let remainder = a % b;
+    if remainder < 0.0 {
+        remainder + b.abs()
+    } else {
+        remainder
+    }
+}
+
+/// The euclidean quotient of two floats, rounded such that `float_rem_euclid` is the matching remainder.
+fn float_div_euclid(a: FloatType, b: FloatType) -> FloatType {
+    let quotient = (a / b).trunc();
+    if a % b < 0.0 {
+        if b > 0.0 {
+            quotient - 1.0
+        } else {
+            quotient + 1.0
+        }
+    } else {
+        quotient
+    }
+}
+
+/// The tolerance of `approx_eq` if none is given.
+const DEFAULT_APPROX_EQ_EPSILON: FloatType = 1e-9;
+
+/// Returns true if the two numbers of the tuple differ by at most the epsilon given as optional third element.
+/// The epsilon is absolute for numbers up to a magnitude of one, and relative to the larger magnitude above that.
+fn approx_eq(argument: &Value) -> Result<Value, EvalexprError> {
+    let arguments = argument.as_tuple()?;
+    let epsilon = match arguments.len() {
+        2 => DEFAULT_APPROX_EQ_EPSILON,
+        3 => arguments[2].as_number()?,
+        len => {
+            return Err(EvalexprError::wrong_function_argument_amount_choice(
+                len,
+                vec![2, 3],
+            ))
+        },
+    };
+    let (a, b) = (arguments[0].as_number()?, arguments[1].as_number()?);
+    let scale = a.abs().max(b.abs()).max(1.0);
+    Ok(Value::Boolean(a == b || (a - b).abs() <= epsilon * scale))
+}
+
+/// Returns the smallest of the given numbers, or the largest if `max` is true.
+/// If a float is equal to another number, the float is returned.
+fn min_or_max(arguments: TupleType, max: bool) -> Result<Value, EvalexprError> {
+    let mut result: Option<Value> = None;
+
+    for argument in arguments {
+        if !argument.is_number() {
+            return Err(EvalexprError::expected_number(argument));
+        }
+        if matches!(argument, Value::Float(float) if float.is_nan()) {
+            continue;
+        }
+
+        let is_better = match &result {
+            None => true,
+            Some(result) => match compare_numbers(&argument, result) {
+                Some(Ordering::Equal) => argument.is_float() && !result.is_float(),
+                Some(ordering) => (ordering == Ordering::Greater) == max,
+                None => false,
+            },
+        };
+        if is_better {
+            result = Some(argument);
+        }
+    }
+
+    Ok(result.unwrap_or(Value::Float(if max { -1.0 / 0.0 } else { 1.0 / 0.0 })))
+}
+
+/// Returns the values that an aggregation function aggregates over.
+/// These are the elements of a tuple or array, no values for the empty value, and the value itself otherwise.
+fn aggregated_values(argument: &Value) -> Vec<Value> {
+    match argument {
+        Value::Tuple(values) | Value::Array(values) => values.clone(),
+        Value::Empty => Vec::new(),
+        value => vec![value.clone()],
+    }
+}
+
+/// Combines the given numbers with the given binary operator, starting with `initial`.
+fn fold_numbers(
+    argument: &Value,
+    initial: Value,
+    operator: Operator,
+) -> Result<Value, EvalexprError> {
+    aggregated_values(argument)
+        .into_iter()
+        .try_fold(initial, |result, value| {
+            if !value.is_number() {
+                return Err(EvalexprError::expected_number(value));
+            }
+            operator.eval(&[result, value], &EmptyContext)
+        })
+}
+
+/// Converts a float into an integer by truncating it towards zero.
+/// Returns `None` if the float is not finite, or does not fit into an integer.
+fn float_to_int(float: FloatType) -> Option<Value> {
+    let float = float.trunc();
+    #[cfg(feature = "bigint")]
+    {
+        num_traits::FromPrimitive::from_f64(float).map(|int: crate::BigIntType| int.into())
+    }
+    #[cfg(not(feature = "bigint"))]
+    {
+        // The upper bound is exclusive, because `IntType::MAX` is rounded up when converted into a float.
+        if float >= IntType::MIN as FloatType && float < IntType::MAX as FloatType {
+            Some(Value::Int(float as IntType))
+        } else {
+            None
+        }
+    }
+}
+
+/// Converts a value into an integer, as documented for the builtin function `int`.
+fn convert_to_int(value: &Value) -> Result<Value, EvalexprError> {
+    let converted = match value {
+        Value::Int(_) => Some(value.clone()),
+        #[cfg(feature = "bigint")]
+        Value::BigInt(_) => Some(value.clone()),
+        #[cfg(feature = "decimal")]
+        Value::Decimal(decimal) => IntType::try_from(decimal.trunc()).ok().map(Value::Int),
+        Value::Float(float) => float_to_int(*float),
+        Value::Boolean(boolean) => Some(Value::Int(IntType::from(*boolean))),
+        Value::String(string) => {
+            let string = string.trim();
+            if let Ok(int) = string.parse::<IntType>() {
+                Some(Value::Int(int))
+            } else {
+                #[cfg(feature = "bigint")]
+                let int = string.parse::<crate::BigIntType>().ok().map(Value::from);
+                #[cfg(not(feature = "bigint"))]
+                let int = None;
+                int.or_else(|| string.parse::<FloatType>().ok().and_then(float_to_int))
+            }
+        },
+        _ => None,
+    };
+    converted.ok_or_else(|| EvalexprError::conversion_error(value.clone(), ValueType::Int))
+}
+
+/// Converts a value into a float, as documented for the builtin function `float`.
+fn convert_to_float(value: &Value) -> Result<Value, EvalexprError> {
+    let converted = match value {
+        Value::Boolean(boolean) => Some(if *boolean { 1.0 } else { 0.0 }),
+        Value::String(string) => string.trim().parse::<FloatType>().ok(),
+        value => value.as_number().ok(),
+    };
+    converted
+        .map(Value::Float)
+        .ok_or_else(|| EvalexprError::conversion_error(value.clone(), ValueType::Float))
+}
+
+/// Converts a value into a boolean, as documented for the builtin function `bool`.
+fn convert_to_bool(value: &Value) -> Result<Value, EvalexprError> {
+    let converted = match value {
+        Value::Boolean(boolean) => Some(*boolean),
+        Value::String(string) => match string.trim() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        value => value.as_number().ok().map(|number| number != 0.0),
+    };
+    converted
+        .map(Value::Boolean)
+        .ok_or_else(|| EvalexprError::conversion_error(value.clone(), ValueType::Boolean))
+}
+
+/// Returns the elements of the tuple or array passed to a higher-order builtin function like `map`,
+/// together with the lambda it is called with.
+/// The empty value `()` is treated as an empty tuple.
+fn elements_and_lambda(arguments: &[Value]) -> Result<(&[Value], &Lambda), EvalexprError> {
+    let elements = match &arguments[0] {
+        Value::Tuple(elements) | Value::Array(elements) => elements.as_slice(),
+        Value::Empty => &[],
+        value => {
+            return Err(EvalexprError::type_error(
+                value.clone(),
+                vec![ValueType::Tuple, ValueType::Array],
+            ))
+        },
+    };
+    match &arguments[1] {
+        Value::Lambda(lambda) => Ok((elements, lambda)),
+        value => Err(EvalexprError::type_error(
+            value.clone(),
+            vec![ValueType::Lambda],
+        )),
+    }
+}
+
+/// Returns an array if `collection` is an array, and a tuple otherwise.
+fn same_collection_type(collection: &Value, elements: TupleType) -> Value {
+    if collection.is_array() {
+        Value::Array(elements)
+    } else {
+        Value::Tuple(elements)
+    }
+}
+
+/// Parses a date and time in RFC 3339 format, or in the given format, as documented for the builtin function `datetime::parse`.
+#[cfg(feature = "chrono")]
+fn parse_datetime(string: &str, format: Option<&str>) -> Result<Value, EvalexprError> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+    let datetime = match format {
+        None => DateTime::parse_from_rfc3339(string).map(|datetime| datetime.with_timezone(&Utc)),
+        Some(format) => DateTime::parse_from_str(string, format)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .or_else(|error| {
+                // Dates and times without a timezone are in UTC, and dates without a time at midnight.
+                NaiveDateTime::parse_from_str(string, format)
+                    .map(|datetime| datetime.and_utc())
+                    .or_else(|_| {
+                        NaiveDate::parse_from_str(string, format)
+                            .map(|date| date.and_time(NaiveTime::MIN).and_utc())
+                    })
+                    .map_err(|_| error)
+            }),
+    };
+    datetime
+        .map(Value::DateTime)
+        .map_err(|error| EvalexprError::invalid_datetime(string.into(), error.to_string()))
+}
+
+/// Formats a date and time, as documented for the builtin function `datetime::format`.
+#[cfg(feature = "chrono")]
+fn format_datetime(datetime: &crate::DateTimeType, format: &str) -> Result<Value, EvalexprError> {
+    use core::fmt::Write;
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", datetime.format(format)).map_err(|_| {
+        EvalexprError::invalid_format_string(format.into(), "invalid format specifier".into())
+    })?;
+    Ok(Value::String(formatted))
+}
+
+/// Returns a random float between 0 and 1, as documented for the builtin function `random`.
+#[cfg(feature = "rand")]
+fn random<R: Rng + ?Sized>(
+    argument: &Value,
+    random_generator: &mut R,
+) -> Result<Value, EvalexprError> {
+    argument.as_empty()?;
+    Ok(Value::Float(random_generator.gen()))
+}
+
+/// Returns a random integer between the two given integers, as documented for the builtin function `random_int`.
+#[cfg(feature = "rand")]
+fn random_int<R: Rng + ?Sized>(
+    argument: &Value,
+    random_generator: &mut R,
+) -> Result<Value, EvalexprError> {
+    let arguments = argument.as_fixed_len_tuple(2)?;
+    let (a, b) = (arguments[0].as_int()?, arguments[1].as_int()?);
+    Ok(Value::Int(random_generator.gen_range(a.min(b)..=a.max(b))))
+}
+
+/// Returns one of the given values chosen at random, as documented for the builtin function `random_choice`.
+#[cfg(feature = "rand")]
+fn random_choice<R: Rng + ?Sized>(
+    argument: &Value,
+    random_generator: &mut R,
+) -> Result<Value, EvalexprError> {
+    let choices = match argument {
+        Value::Tuple(values) | Value::Array(values) => values.as_slice(),
+        Value::Empty => &[],
+        argument => core::slice::from_ref(argument),
+    };
+    choices
+        .choose(random_generator)
+        .cloned()
+        .ok_or_else(|| EvalexprError::wrong_function_argument_amount_range(0, 1..=usize::MAX))
+}
+
+/// Calls the builtin function with the given identifier with the given random number generator,
+/// or returns `None` if it is not a function that produces random values.
+#[cfg(feature = "rand")]
+pub(crate) fn call_random_function<R: Rng + ?Sized>(
+    identifier: &str,
+    argument: &Value,
+    random_generator: &mut R,
+) -> Option<Result<Value, EvalexprError>> {
+    match identifier {
+        "random" => Some(random(argument, random_generator)),
+        "random_int" => Some(random_int(argument, random_generator)),
+        "random_choice" => Some(random_choice(argument, random_generator)),
+        _ => None,
+    }
+}
+
+/// Formats the arguments into the template, as documented for the builtin function `format`.
+fn format_string(template: &str, arguments: &[Value]) -> Result<Value, EvalexprError> {
+    let error = |message: String| EvalexprError::invalid_format_string(template.into(), message);
+    let mut result = String::with_capacity(template.len());
+    let mut used = vec![false; arguments.len()];
+    let mut next_index = 0;
+    let mut characters = template.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '{' if characters.next_if_eq(&'{').is_some() => result.push('{'),
+            '}' if characters.next_if_eq(&'}').is_some() => result.push('}'),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match characters.next() {
+                        Some('}') => break,
+                        Some(character) => placeholder.push(character),
+                        None => return Err(error("unclosed `{`".into())),
+                    }
+                }
+                let index = if placeholder.is_empty() {
+                    next_index += 1;
+                    next_index - 1
+                } else {
+                    placeholder
+                        .parse()
+                        .map_err(|_| error(format!("invalid placeholder `{{{}}}`", placeholder)))?
+                };
+                match arguments.get(index) {
+                    Some(Value::String(string)) => result.push_str(string),
+                    Some(value) => result.push_str(&value.to_string()),
+                    None => return Err(error(format!("argument {} is missing", index))),
+                }
+                used[index] = true;
+            },
+            '}' => return Err(error("unmatched `}`".into())),
+            character => result.push(character),
+        }
+    }
+
+    match used.iter().position(|used| !used) {
+        Some(index) => Err(error(format!("argument {} is not used", index))),
+        None => Ok(Value::String(result)),
+    }
+}
+
+/// The identifiers of the builtin functions that may return different results for the same arguments.
+pub(crate) const IMPURE_BUILTIN_FUNCTION_IDENTIFIERS: &[&str] =
+    &["random", "random_int", "random_choice", "now"];
+
+/// The identifiers of all builtin functions, including the ones of disabled features.
+pub(crate) const BUILTIN_FUNCTION_IDENTIFIERS: &[&str] = &[
+    "math::ln",
+    "math::log",
+    "math::log2",
+    "math::log10",
+    "math::exp",
+    "math::exp2",
+    "math::pow",
+    "math::cos",
+    "math::acos",
+    "math::cosh",
+    "math::acosh",
+    "math::sin",
+    "math::asin",
+    "math::sinh",
+    "math::asinh",
+    "math::tan",
+    "math::atan",
+    "math::tanh",
+    "math::atanh",
+    "math::atan2",
+    "math::sqrt",
+    "math::cbrt",
+    "math::hypot",
+    "floor",
+    "round",
+    "ceil",
+    "math::is_nan",
+    "math::is_finite",
+    "math::is_infinite",
+    "math::is_normal",
+    "math::abs",
+    "math::rem_euclid",
+    "math::div_euclid",
+    "approx_eq",
+    "typeof",
+    "is_string",
+    "is_int",
+    "is_float",
+    "is_decimal",
+    "is_datetime",
+    "is_duration",
+    "is_number",
+    "is_boolean",
+    "is_tuple",
+    "is_array",
+    "is_map",
+    "is_lambda",
+    "is_empty",
+    "min",
+    "max",
+    "sum",
+    "product",
+    "avg",
+    "count",
+    "int",
+    "float",
+    "bool",
+    "str",
+    "if",
+    "contains",
+    "contains_any",
+    "len",
+    "map",
+    "filter",
+    "reduce",
+    "any",
+    "all",
+    "str::regex_matches",
+    "str::regex_replace",
+    "str::to_lowercase",
+    "str::to_uppercase",
+    "str::trim",
+    "str::len",
+    "str::contains",
+    "str::starts_with",
+    "str::substring",
+    "str::from",
+    "format",
+    "random",
+    "random_int",
+    "random_choice",
+    "duration",
+    "timestamp",
+    "now",
+    "datetime::parse",
+    "datetime::format",
+    "datetime::timestamp",
+    "datetime::from_timestamp",
+    "duration::seconds",
+    "duration::as_seconds",
+    "bitand",
+    "bitor",
+    "bitxor",
+    "bitnot",
+    "shl",
+    "shr",
+];
+
 pub fn builtin_function(identifier: &str) -> Option<Function> {
     match identifier {
         // Log
@@ -79,9 +650,9 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
         // Hypotenuse
         "math::hypot" => simple_math!(hypot, 2),
         // Rounding
-        "floor" => simple_math!(floor),
-        "round" => simple_math!(round),
-        "ceil" => simple_math!(ceil),
+        "floor" => rounding_math!(floor, ToNegativeInfinity),
+        "round" => rounding_math!(round, MidpointAwayFromZero),
+        "ceil" => rounding_math!(ceil, ToPositiveInfinity),
         // Float special values
         "math::is_nan" => float_is(FloatType::is_nan),
         "math::is_finite" => float_is(FloatType::is_finite),
@@ -90,65 +661,100 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
         // Absolute
         "math::abs" => Some(Function::new(|argument| match argument {
             Value::Float(num) => Ok(Value::Float(num.abs())),
-            Value::Int(num) => Ok(Value::Int(num.abs())),
+            #[cfg(not(feature = "bigint"))]
+            Value::Int(num) => num
+                .checked_abs()
+                .map(Value::Int)
+                .ok_or_else(|| EvalexprError::negation_error(argument.clone())),
+            #[cfg(feature = "bigint")]
+            Value::Int(_) | Value::BigInt(_) => Ok(argument.as_bigint()?.abs().into()),
+            #[cfg(feature = "decimal")]
+            Value::Decimal(num) => Ok(Value::Decimal(num.abs())),
             _ => Err(EvalexprError::expected_number(argument.clone())),
         })),
+        // Euclidean division
+        "math::rem_euclid" => Some(Function::new(|argument| euclid(argument, true))),
+        "math::div_euclid" => Some(Function::new(|argument| euclid(argument, false))),
+        // Approximate equality
+        "approx_eq" => Some(Function::new(approx_eq)),
         // Other
         "typeof" => Some(Function::new(move |argument| {
             Ok(match argument {
                 Value::String(_) => "string",
                 Value::Float(_) => "float",
                 Value::Int(_) => "int",
+                #[cfg(feature = "bigint")]
+                Value::BigInt(_) => "int",
+                #[cfg(feature = "decimal")]
+                Value::Decimal(_) => "decimal",
+                #[cfg(feature = "chrono")]
+                Value::DateTime(_) => "datetime",
+                #[cfg(feature = "chrono")]
+                Value::Duration(_) => "duration",
                 Value::Boolean(_) => "boolean",
                 Value::Tuple(_) => "tuple",
+                Value::Array(_) => "array",
+                Value::Map(_) => "map",
+                Value::Lambda(_) => "lambda",
                 Value::Empty => "empty",
             }
             .into())
         })),
+        "is_string" => value_type_is(ValueType::String),
+        "is_int" => value_type_is(ValueType::Int),
+        "is_float" => value_type_is(ValueType::Float),
+        #[cfg(feature = "decimal")]
+        "is_decimal" => value_type_is(ValueType::Decimal),
+        #[cfg(feature = "chrono")]
+        "is_datetime" => value_type_is(ValueType::DateTime),
+        #[cfg(feature = "chrono")]
+        "is_duration" => value_type_is(ValueType::Duration),
+        "is_number" => Some(Function::new(|argument| Ok(argument.is_number().into()))),
+        "is_boolean" => value_type_is(ValueType::Boolean),
+        "is_tuple" => value_type_is(ValueType::Tuple),
+        "is_array" => value_type_is(ValueType::Array),
+        "is_map" => value_type_is(ValueType::Map),
+        "is_lambda" => value_type_is(ValueType::Lambda),
+        "is_empty" => value_type_is(ValueType::Empty),
         "min" => Some(Function::new(|argument| {
-            let arguments = argument.as_tuple()?;
-            let mut min_int = IntType::max_value();
-            let mut min_float: FloatType = 1.0 / 0.0;
-            debug_assert!(min_float.is_infinite());
-
-            for argument in arguments {
-                if let Value::Float(float) = argument {
-                    min_float = min_float.min(float);
-                } else if let Value::Int(int) = argument {
-                    min_int = min_int.min(int);
-                } else {
-                    return Err(EvalexprError::expected_number(argument));
-                }
-            }
-
-            if (min_int as FloatType) < min_float {
-                Ok(Value::Int(min_int))
-            } else {
-                Ok(Value::Float(min_float))
-            }
+            min_or_max(argument.as_tuple()?, false)
         })),
         "max" => Some(Function::new(|argument| {
-            let arguments = argument.as_tuple()?;
-            let mut max_int = IntType::min_value();
-            let mut max_float: FloatType = -1.0 / 0.0;
-            debug_assert!(max_float.is_infinite());
-
-            for argument in arguments {
-                if let Value::Float(float) = argument {
-                    max_float = max_float.max(float);
-                } else if let Value::Int(int) = argument {
-                    max_int = max_int.max(int);
-                } else {
-                    return Err(EvalexprError::expected_number(argument));
-                }
+            min_or_max(argument.as_tuple()?, true)
+        })),
+        "sum" => Some(Function::new(|argument| {
+            fold_numbers(argument, Value::Int(0), Operator::Add)
+        })),
+        "product" => Some(Function::new(|argument| {
+            fold_numbers(argument, Value::Int(1), Operator::Mul)
+        })),
+        "avg" => Some(Function::new(|argument| {
+            let count = aggregated_values(argument).len();
+            if count == 0 {
+                return Err(EvalexprError::wrong_function_argument_amount_range(
+                    0,
+                    1..=usize::MAX,
+                ));
             }
-
-            if (max_int as FloatType) > max_float {
-                Ok(Value::Int(max_int))
-            } else {
-                Ok(Value::Float(max_float))
+            match fold_numbers(argument, Value::Int(0), Operator::Add)? {
+                #[cfg(feature = "decimal")]
+                sum @ Value::Decimal(_) => {
+                    Operator::Div.eval(&[sum, Value::Int(count as IntType)], &EmptyContext)
+                },
+                sum => Ok(Value::Float(sum.as_number()? / count as FloatType)),
             }
         })),
+        "count" => Some(Function::new(|argument| {
+            Ok(Value::Int(aggregated_values(argument).len() as IntType))
+        })),
+        // Conversions
+        "int" => Some(Function::new(convert_to_int)),
+        "float" => Some(Function::new(convert_to_float)),
+        "bool" => Some(Function::new(convert_to_bool)),
+        "str" => Some(Function::new(|argument| match argument {
+            Value::String(_) => Ok(argument.clone()),
+            argument => Ok(Value::String(argument.to_string())),
+        })),
         "if" => Some(Function::new(|argument| {
             let mut arguments = argument.as_fixed_len_tuple(3)?;
             let result_index = if arguments[0].as_boolean()? { 1 } else { 2 };
@@ -157,8 +763,8 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
         "contains" => Some(Function::new(move |argument| {
             let arguments = argument.as_fixed_len_tuple(2)?;
             if let (Value::Tuple(a), b) = (&arguments[0].clone(), &arguments[1].clone()) {
-                if let Value::String(_) | Value::Int(_) | Value::Float(_) | Value::Boolean(_) = b {
-                    Ok(a.contains(b).into())
+                if b.is_string() || b.is_number() || b.is_boolean() {
+                    Ok(a.iter().any(|element| values_equal(element, b)).into())
                 } else {
                     Err(EvalexprError::type_error(
                         b.clone(),
@@ -185,7 +791,7 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
                         | Value::Float(_)
                         | Value::Boolean(_) = value
                         {
-                            if a.contains(value) {
+                            if a.iter().any(|element| values_equal(element, value)) {
                                 contains = true;
                             }
                         } else {
@@ -209,21 +815,73 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
             }
         })),
         "len" => Some(Function::new(|argument| {
-            if let Ok(subject) = argument.as_string() {
+            if let Value::String(subject) = argument {
                 Ok(Value::from(subject.len() as IntType))
-            } else if let Ok(subject) = argument.as_tuple() {
+            } else if let Value::Tuple(subject) | Value::Array(subject) = argument {
+                Ok(Value::from(subject.len() as IntType))
+            } else if let Value::Map(subject) = argument {
                 Ok(Value::from(subject.len() as IntType))
             } else {
                 Err(EvalexprError::type_error(
                     argument.clone(),
-                    vec![ValueType::String, ValueType::Tuple],
+                    vec![
+                        ValueType::String,
+                        ValueType::Tuple,
+                        ValueType::Array,
+                        ValueType::Map,
+                    ],
                 ))
             }
         })),
+        // Higher-order functions
+        "map" => Some(Function::new_with_context(|context, argument| {
+            let arguments = argument.as_fixed_len_tuple(2)?;
+            let (elements, lambda) = elements_and_lambda(&arguments)?;
+            let mapped = elements
+                .iter()
+                .map(|element| lambda.call(context, element))
+                .collect::<Result<_, _>>()?;
+            Ok(same_collection_type(&arguments[0], mapped))
+        })),
+        "filter" => Some(Function::new_with_context(|context, argument| {
+            let arguments = argument.as_fixed_len_tuple(2)?;
+            let (elements, lambda) = elements_and_lambda(&arguments)?;
+            let mut filtered = Vec::new();
+            for element in elements {
+                if lambda.call(context, element)?.as_boolean()? {
+                    filtered.push(element.clone());
+                }
+            }
+            Ok(same_collection_type(&arguments[0], filtered))
+        })),
+        "reduce" => Some(Function::new_with_context(|context, argument| {
+            let mut arguments = argument.as_fixed_len_tuple(3)?;
+            let initial_value = arguments.pop().unwrap_or(Value::Empty);
+            let (elements, lambda) = elements_and_lambda(&arguments)?;
+            elements
+                .iter()
+                .try_fold(initial_value, |accumulator, element| {
+                    lambda.call(context, &Value::Tuple(vec![accumulator, element.clone()]))
+                })
+        })),
+        "any" | "all" => {
+            let is_all = identifier == "all";
+            Some(Function::new_with_context(move |context, argument| {
+                let arguments = argument.as_fixed_len_tuple(2)?;
+                let (elements, lambda) = elements_and_lambda(&arguments)?;
+                for element in elements {
+                    // Stop at the first element that decides the result.
+                    if lambda.call(context, element)?.as_boolean()? != is_all {
+                        return Ok(Value::Boolean(!is_all));
+                    }
+                }
+                Ok(Value::Boolean(is_all))
+            }))
+        },
         // String functions
         #[cfg(feature = "regex_support")]
         "str::regex_matches" => Some(Function::new(|argument| {
-            let arguments = argument.as_tuple()?;
+            let arguments = argument.as_fixed_len_tuple(2)?;
 
             let subject = arguments[0].as_string()?;
             let re_str = arguments[1].as_string()?;
@@ -237,7 +895,7 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
         })),
         #[cfg(feature = "regex_support")]
         "str::regex_replace" => Some(Function::new(|argument| {
-            let arguments = argument.as_tuple()?;
+            let arguments = argument.as_fixed_len_tuple(3)?;
 
             let subject = arguments[0].as_string()?;
             let re_str = arguments[1].as_string()?;
@@ -264,21 +922,130 @@ pub fn builtin_function(identifier: &str) -> Option<Function> {
             let subject = argument.as_string()?;
             Ok(Value::from(subject.trim()))
         })),
+        "str::len" => Some(Function::new(|argument| {
+            let subject = argument.as_string()?;
+            Ok(Value::from(subject.chars().count() as IntType))
+        })),
+        "str::contains" => Some(Function::new(|argument| {
+            let arguments = argument.as_fixed_len_tuple(2)?;
+            let (subject, pattern) = (arguments[0].as_string()?, arguments[1].as_string()?);
+            Ok(Value::Boolean(subject.contains(&pattern)))
+        })),
+        "str::starts_with" => Some(Function::new(|argument| {
+            let arguments = argument.as_fixed_len_tuple(2)?;
+            let (subject, prefix) = (arguments[0].as_string()?, arguments[1].as_string()?);
+            Ok(Value::Boolean(subject.starts_with(&prefix)))
+        })),
+        "str::substring" => Some(Function::new(|argument| {
+            let arguments = argument.as_fixed_len_tuple(3)?;
+            let subject = arguments[0].as_string()?;
+            let (start, end) = (arguments[1].as_int()?, arguments[2].as_int()?);
+            let len = subject.chars().count();
+            let to_index = |index: IntType| {
+                usize::try_from(index)
+                    .ok()
+                    .filter(|&index| index <= len)
+                    .ok_or_else(|| EvalexprError::index_out_of_bounds(index, len))
+            };
+            if start > end {
+                return Err(EvalexprError::invalid_range(start, end));
+            }
+            let (start, end) = (to_index(start)?, to_index(end)?);
+            Ok(Value::String(
+                subject.chars().skip(start).take(end - start).collect(),
+            ))
+        })),
         "str::from" => Some(Function::new(|argument| {
             Ok(Value::String(argument.to_string()))
         })),
+        "format" => Some(Function::new(|argument| {
+            let arguments = match argument {
+                Value::Tuple(arguments) => arguments.as_slice(),
+                argument => core::slice::from_ref(argument),
+            };
+            match arguments.split_first() {
+                Some((template, arguments)) => format_string(&template.as_string()?, arguments),
+                None => Err(EvalexprError::wrong_function_argument_amount_range(
+                    0,
+                    1..=usize::MAX,
+                )),
+            }
+        })),
         #[cfg(feature = "rand")]
         "random" => Some(Function::new(|argument| {
+            random(argument, &mut rand::thread_rng())
+        })),
+        #[cfg(feature = "rand")]
+        "random_int" => Some(Function::new(|argument| {
+            random_int(argument, &mut rand::thread_rng())
+        })),
+        #[cfg(feature = "rand")]
+        "random_choice" => Some(Function::new(|argument| {
+            random_choice(argument, &mut rand::thread_rng())
+        })),
+        // Dates and times
+        "duration" => Some(Function::new(|argument| {
+            time::duration(&argument.as_string()?)
+        })),
+        "timestamp" => Some(Function::new(|argument| {
+            time::timestamp(&argument.as_string()?)
+        })),
+        #[cfg(feature = "chrono")]
+        "now" => Some(Function::new(|argument| {
             argument.as_empty()?;
-            Ok(Value::Float(rand::random()))
+            Ok(Value::DateTime(chrono::Utc::now()))
+        })),
+        #[cfg(feature = "chrono")]
+        "datetime::parse" => Some(Function::new(|argument| match argument {
+            Value::Tuple(_) => {
+                let arguments = argument.as_fixed_len_tuple(2)?;
+                parse_datetime(&arguments[0].as_string()?, Some(&arguments[1].as_string()?))
+            },
+            argument => parse_datetime(&argument.as_string()?, None),
+        })),
+        #[cfg(feature = "chrono")]
+        "datetime::format" => Some(Function::new(|argument| {
+            let arguments = argument.as_fixed_len_tuple(2)?;
+            format_datetime(&arguments[0].as_datetime()?, &arguments[1].as_string()?)
+        })),
+        #[cfg(feature = "chrono")]
+        "datetime::timestamp" => Some(Function::new(|argument| {
+            Ok(Value::Int(argument.as_datetime()?.timestamp()))
+        })),
+        #[cfg(feature = "chrono")]
+        "datetime::from_timestamp" => Some(Function::new(|argument| {
+            crate::value::duration_from_seconds(argument)?
+                .and_then(|duration| crate::DateTimeType::default().checked_add_signed(duration))
+                .map(Value::DateTime)
+                .ok_or_else(|| {
+                    EvalexprError::conversion_error(argument.clone(), ValueType::DateTime)
+                })
+        })),
+        #[cfg(feature = "chrono")]
+        "duration::seconds" => Some(Function::new(|argument| {
+            crate::value::duration_from_seconds(argument)?
+                .map(Value::Duration)
+                .ok_or_else(|| {
+                    EvalexprError::conversion_error(argument.clone(), ValueType::Duration)
+                })
+        })),
+        #[cfg(feature = "chrono")]
+        "duration::as_seconds" => Some(Function::new(|argument| {
+            Ok(Value::Float(crate::value::duration_as_seconds(
+                &argument.as_duration()?,
+            )))
         })),
         // Bitwise operators
         "bitand" => int_function!(bitand, 2),
         "bitor" => int_function!(bitor, 2),
         "bitxor" => int_function!(bitxor, 2),
         "bitnot" => int_function!(not),
-        "shl" => int_function!(shl, 2),
-        "shr" => int_function!(shr, 2),
+        "shl" => Some(Function::new(|argument| {
+            shift(argument, IntType::checked_shl)
+        })),
+        "shr" => Some(Function::new(|argument| {
+            shift(argument, IntType::checked_shr)
+        })),
         _ => None,
     }
 }
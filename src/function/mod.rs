@@ -1,13 +1,24 @@
-use std::fmt;
+use alloc::{boxed::Box, vec::Vec};
+use core::{fmt, ops::RangeInclusive, slice};
 
-use crate::{error::EvalexprResult, value::Value};
+use crate::{
+    context::Context,
+    error::{expect_function_argument_amount, expect_value_type, EvalexprError, EvalexprResult},
+    value::{value_type::ValueType, Value},
+};
 
+#[cfg(feature = "async")]
+mod asynchronous;
 pub(crate) mod builtin;
+pub(crate) mod time;
+
+#[cfg(feature = "async")]
+pub use asynchronous::{AsyncFunction, AsyncFunctionFuture};
 
 /// A helper trait to enable cloning through `Fn` trait objects.
 trait ClonableFn
 where
-    Self: Fn(&Value) -> EvalexprResult<Value>,
+    Self: Fn(&dyn Context, &Value) -> EvalexprResult<Value>,
     Self: Send + Sync + 'static,
 {
     fn dyn_clone(&self) -> Box<dyn ClonableFn>;
@@ -15,7 +26,7 @@ where
 
 impl<F> ClonableFn for F
 where
-    F: Fn(&Value) -> EvalexprResult<Value>,
+    F: Fn(&dyn Context, &Value) -> EvalexprResult<Value>,
     F: Send + Sync + 'static,
     F: Clone,
 {
@@ -40,12 +51,24 @@ where
 /// ```
 pub struct Function {
     function: Box<dyn ClonableFn>,
+    argument_amount: ArgumentAmount,
+    is_pure: bool,
+}
+
+/// The amounts of arguments a function accepts.
+#[derive(Clone, Debug)]
+enum ArgumentAmount {
+    Any,
+    Range(RangeInclusive<usize>),
+    Choice(Vec<usize>),
 }
 
 impl Clone for Function {
     fn clone(&self) -> Self {
         Self {
             function: self.function.dyn_clone(),
+            argument_amount: self.argument_amount.clone(),
+            is_pure: self.is_pure,
         }
     }
 }
@@ -59,14 +82,210 @@ impl Function {
         F: Fn(&Value) -> EvalexprResult<Value>,
         F: Send + Sync + 'static,
         F: Clone,
+    {
+        Self::new_with_context(move |_, argument| function(argument))
+    }
+
+    /// Creates a user-defined function that can read from the context it is called from.
+    ///
+    /// The function receives the context that links it to its identifier, for example the `HashMapContext` it was stored in.
+    /// For builtin functions, this is the context the expression is evaluated with.
+    ///
+    /// The `function` is boxed for storage.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! {
+    ///     "a" => 3,
+    ///     "lookup" => Function::new_with_context(|context, argument| {
+    ///         let identifier = argument.as_string()?;
+    ///         context
+    ///             .get_value(&identifier)
+    ///             .cloned()
+    ///             .ok_or(EvalexprError::variable_identifier_not_found(identifier))
+    ///     }),
+    /// }
+    /// .unwrap(); // Do proper error handling here
+    /// assert_eq!(eval_with_context("lookup(\"a\") + 1", &context), Ok(Value::from(4)));
+    /// ```
+    pub fn new_with_context<F>(function: F) -> Self
+    where
+        F: Fn(&dyn Context, &Value) -> EvalexprResult<Value>,
+        F: Send + Sync + 'static,
+        F: Clone,
     {
         Self {
             function: Box::new(function) as _,
+            argument_amount: ArgumentAmount::Any,
+            is_pure: false,
         }
     }
 
-    pub(crate) fn call(&self, argument: &Value) -> EvalexprResult<Value> {
-        (self.function)(argument)
+    /// Creates a user-defined function with a typed signature.
+    ///
+    /// The function is called with as many arguments as there are `types`, and each argument must have the type at the same position.
+    /// Otherwise, the call fails with `EvalexprError::WrongFunctionArgumentAmount` or an error like `EvalexprError::ExpectedInt`
+    /// before the function is invoked.
+    /// Arguments are counted like for `Function::with_argument_range`, and the amount is checked before the types.
+    /// Only a function with a single `ValueType::Tuple` or `ValueType::Empty` parameter receives a tuple or empty argument as its only argument.
+    ///
+    /// The `function` is boxed for storage.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! {
+    ///     "repeat" => Function::typed(&[ValueType::String, ValueType::Int], |arguments| {
+    ///         let string = arguments[0].as_string()?;
+    ///         let count = arguments[1].as_int()?;
+    ///         Ok(Value::from(string.repeat(count.max(0) as usize)))
+    ///     }),
+    /// }
+    /// .unwrap(); // Do proper error handling here
+    /// assert_eq!(eval_with_context("repeat(\"ab\", 3)", &context), Ok(Value::from("ababab")));
+    /// assert_eq!(
+    ///     eval_with_context("repeat(\"ab\", 3.0)", &context),
+    ///     Err(EvalexprError::expected_int(Value::from(3.0)))
+    /// );
+    /// ```
+    pub fn typed<F>(types: &[ValueType], function: F) -> Self
+    where
+        F: Fn(&[Value]) -> EvalexprResult<Value>,
+        F: Send + Sync + 'static,
+        F: Clone,
+    {
+        let types = types.to_vec();
+        Self::new(move |argument| {
+            let arguments = match (types.as_slice(), argument) {
+                ([ValueType::Tuple], Value::Tuple(_)) | ([ValueType::Empty], Value::Empty) => {
+                    slice::from_ref(argument)
+                },
+                (_, Value::Empty) => &[],
+                (_, Value::Tuple(tuple)) => tuple.as_slice(),
+                (_, argument) => slice::from_ref(argument),
+            };
+            expect_function_argument_amount(arguments.len(), types.len())?;
+            for (argument, expected) in arguments.iter().zip(&types) {
+                expect_value_type(argument, *expected)?;
+            }
+            function(arguments)
+        })
+    }
+
+    /// Restricts this function to be called with at least `min` and at most `max` arguments.
+    /// Use `usize::MAX` as `max` to only require a minimum amount of arguments.
+    ///
+    /// Arguments are counted like functions receive them: a tuple argument counts as its elements,
+    /// an empty argument as no arguments, and any other value as a single argument.
+    /// Calling the function with a different amount of arguments fails with `EvalexprError::WrongFunctionArgumentAmountRange`
+    /// before the function is invoked.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! {
+    ///     "round_to" => Function::new(|argument| match argument {
+    ///         Value::Tuple(arguments) => Ok(Value::from(
+    ///             (arguments[0].as_number()? * 10f64.powf(arguments[1].as_number()?)).round(),
+    ///         )),
+    ///         argument => Ok(Value::from(argument.as_number()?.round())),
+    ///     })
+    ///     .with_argument_range(1, 2),
+    /// }
+    /// .unwrap(); // Do proper error handling here
+    /// assert_eq!(eval_with_context("round_to(2.5)", &context), Ok(Value::from(3.0)));
+    /// assert_eq!(
+    ///     eval_with_context("round_to(1, 2, 3)", &context),
+    ///     Err(EvalexprError::WrongFunctionArgumentAmountRange { expected: 1..=2, actual: 3 })
+    /// );
+    /// ```
+    pub fn with_argument_range(mut self, min: usize, max: usize) -> Self {
+        self.argument_amount = ArgumentAmount::Range(min..=max);
+        self
+    }
+
+    /// Restricts this function to be called with one of the given amounts of arguments.
+    ///
+    /// Arguments are counted like in `Function::with_argument_range`.
+    /// Calling the function with a different amount of arguments fails with `EvalexprError::WrongFunctionArgumentAmountChoice`
+    /// before the function is invoked.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! {
+    ///     "first" => Function::new(|argument| match argument {
+    ///         Value::Tuple(arguments) => Ok(arguments[0].clone()),
+    ///         argument => Ok(argument.clone()),
+    ///     })
+    ///     .with_argument_counts(&[1, 3]),
+    /// }
+    /// .unwrap(); // Do proper error handling here
+    /// assert_eq!(eval_with_context("first(1, 2, 3)", &context), Ok(Value::from(1)));
+    /// assert_eq!(
+    ///     eval_with_context("first(1, 2)", &context),
+    ///     Err(EvalexprError::WrongFunctionArgumentAmountChoice { expected: vec![1, 3], actual: 2 })
+    /// );
+    /// ```
+    pub fn with_argument_counts(mut self, counts: &[usize]) -> Self {
+        self.argument_amount = ArgumentAmount::Choice(counts.to_vec());
+        self
+    }
+
+    /// Marks this function as pure, meaning that it always returns the same result for the same arguments and has no side effects.
+    ///
+    /// `Node::eval_cached` only reuses the results of calls to functions of the context that are pure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! {
+    ///     "square" => Function::new(|argument| Ok(Value::from(argument.as_int()? * argument.as_int()?))).pure(),
+    /// }
+    /// .unwrap(); // Do proper error handling here
+    /// assert!(context.is_pure_function("square"));
+    /// ```
+    pub fn pure(mut self) -> Self {
+        self.is_pure = true;
+        self
+    }
+
+    /// Returns true if this function was marked as pure with `Function::pure`.
+    pub fn is_pure(&self) -> bool {
+        self.is_pure
+    }
+
+    pub(crate) fn call(&self, context: &dyn Context, argument: &Value) -> EvalexprResult<Value> {
+        self.check_argument_amount(argument)?;
+        (self.function)(context, argument)
+    }
+
+    fn check_argument_amount(&self, argument: &Value) -> EvalexprResult<()> {
+        let actual = match argument {
+            Value::Empty => 0,
+            Value::Tuple(tuple) => tuple.len(),
+            _ => 1,
+        };
+        match &self.argument_amount {
+            ArgumentAmount::Range(range) if !range.contains(&actual) => Err(
+                EvalexprError::wrong_function_argument_amount_range(actual, range.clone()),
+            ),
+            ArgumentAmount::Choice(counts) if !counts.contains(&actual) => Err(
+                EvalexprError::wrong_function_argument_amount_choice(actual, counts.clone()),
+            ),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -78,6 +297,7 @@ impl fmt::Debug for Function {
 
 /// A trait to ensure a type is `Send` and `Sync`.
 /// If implemented for a type, the crate will not compile if the type is not `Send` and `Sync`.
+#[allow(dead_code)]
 trait IsSendAndSync: Send + Sync {}
 
 impl IsSendAndSync for Function {}
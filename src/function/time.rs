@@ -0,0 +1,265 @@
+//! The builtin functions `duration` and `timestamp`, which parse durations and dates from strings.
+
+use alloc::format;
+use core::convert::TryFrom;
+
+#[cfg(not(feature = "chrono"))]
+use crate::value::FloatType;
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    value::{IntType, Value},
+};
+
+const NANOSECONDS_PER_SECOND: i128 = 1_000_000_000;
+
+/// The names of the units of durations, with their length in nanoseconds.
+const DURATION_UNITS: &[(&[&str], i128)] = &[
+    (&["ns", "nsec", "nanosecond", "nanoseconds"], 1),
+    (&["us", "µs", "usec", "microsecond", "microseconds"], 1_000),
+    (&["ms", "msec", "millisecond", "milliseconds"], 1_000_000),
+    (
+        &["s", "sec", "secs", "second", "seconds"],
+        NANOSECONDS_PER_SECOND,
+    ),
+    (
+        &["m", "min", "mins", "minute", "minutes"],
+        60 * NANOSECONDS_PER_SECOND,
+    ),
+    (
+        &["h", "hr", "hrs", "hour", "hours"],
+        3_600 * NANOSECONDS_PER_SECOND,
+    ),
+    (&["d", "day", "days"], 86_400 * NANOSECONDS_PER_SECOND),
+    (&["w", "week", "weeks"], 604_800 * NANOSECONDS_PER_SECOND),
+];
+
+/// Evaluates the builtin function `duration`.
+///
+/// Returns a `Value::Duration` with the `chrono` feature, and otherwise the amount of seconds as integer if it is whole, or as float.
+pub(crate) fn duration(string: &str) -> EvalexprResult<Value> {
+    let nanoseconds = parse_duration(string)?;
+    let out_of_range = || EvalexprError::invalid_duration(string.into(), "out of range".into());
+    let seconds = IntType::try_from(nanoseconds.div_euclid(NANOSECONDS_PER_SECOND))
+        .map_err(|_| out_of_range())?;
+    let subsecond_nanoseconds = nanoseconds.rem_euclid(NANOSECONDS_PER_SECOND) as i64;
+
+    #[cfg(feature = "chrono")]
+    {
+        crate::DurationType::try_seconds(seconds)
+            .and_then(|duration| {
+                duration.checked_add(&crate::DurationType::nanoseconds(subsecond_nanoseconds))
+            })
+            .map(Value::Duration)
+            .ok_or_else(out_of_range)
+    }
+    #[cfg(not(feature = "chrono"))]
+    Ok(match subsecond_nanoseconds {
+        0 => Value::Int(seconds),
+        _ => Value::Float(nanoseconds as FloatType / NANOSECONDS_PER_SECOND as FloatType),
+    })
+}
+
+/// Evaluates the builtin function `timestamp`.
+///
+/// Returns a `Value::DateTime` with the `chrono` feature, and otherwise the seconds since the Unix epoch as integer if they are whole, or as float.
+pub(crate) fn timestamp(string: &str) -> EvalexprResult<Value> {
+    let (seconds, nanoseconds) = parse_timestamp(string)?;
+
+    #[cfg(feature = "chrono")]
+    {
+        chrono::DateTime::from_timestamp(seconds, nanoseconds)
+            .map(Value::DateTime)
+            .ok_or_else(|| EvalexprError::invalid_datetime(string.into(), "out of range".into()))
+    }
+    #[cfg(not(feature = "chrono"))]
+    Ok(match nanoseconds {
+        0 => Value::Int(seconds),
+        _ => Value::Float(seconds as FloatType + FloatType::from(nanoseconds) / 1e9),
+    })
+}
+
+/// Parses a duration like `1h 30m` or `-2.5s` into nanoseconds, as documented for the builtin function `duration`.
+fn parse_duration(string: &str) -> EvalexprResult<i128> {
+    let error = |message: &str| EvalexprError::invalid_duration(string.into(), message.into());
+
+    let trimmed = string.trim();
+    let (is_negative, mut rest) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, trimmed),
+    };
+    if rest.is_empty() {
+        return Err(error("expected an amount followed by a unit"));
+    }
+
+    let mut nanoseconds: i128 = 0;
+    while !rest.is_empty() {
+        let amount_length = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (amount, tail) = rest.split_at(amount_length);
+        let tail = tail.trim_start();
+        let unit_length = tail
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_length);
+
+        if amount.is_empty() {
+            return Err(error(&format!("expected an amount before `{}`", rest)));
+        }
+        let unit_nanoseconds = match DURATION_UNITS
+            .iter()
+            .find(|(names, _)| names.contains(&unit))
+        {
+            Some((_, unit_nanoseconds)) => *unit_nanoseconds,
+            None if unit.is_empty() => {
+                return Err(error(&format!("expected a unit after `{}`", amount)))
+            },
+            None => return Err(error(&format!("unknown unit `{}`", unit))),
+        };
+
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        if fraction.contains('.') {
+            return Err(error(&format!("invalid amount `{}`", amount)));
+        }
+        // Digits beyond the precision of nanoseconds are ignored.
+        let fraction = &fraction[..fraction.len().min(18)];
+        let parse = |digits: &str| match digits {
+            "" => Some(0),
+            digits => digits.parse::<i128>().ok(),
+        };
+        let (Some(whole), Some(fraction_digits)) = (parse(whole), parse(fraction)) else {
+            return Err(error("out of range"));
+        };
+        let part = whole
+            .checked_mul(unit_nanoseconds)
+            .and_then(|part| {
+                part.checked_add(
+                    fraction_digits * unit_nanoseconds / 10i128.pow(fraction.len() as u32),
+                )
+            })
+            .and_then(|part| nanoseconds.checked_add(part));
+        nanoseconds = part.ok_or_else(|| error("out of range"))?;
+
+        rest = tail.trim_start();
+    }
+
+    Ok(if is_negative {
+        -nanoseconds
+    } else {
+        nanoseconds
+    })
+}
+
+/// Parses a date and time in RFC 3339 format like `2024-01-01T12:00:00Z`, as documented for the builtin function `timestamp`.
+///
+/// Returns the whole seconds since the Unix epoch and the nanoseconds within the second.
+fn parse_timestamp(string: &str) -> EvalexprResult<(IntType, u32)> {
+    let error = |message: &str| EvalexprError::invalid_datetime(string.into(), message.into());
+    let format_error = || error("expected a date and time like `2024-01-01T12:00:00Z`");
+
+    let bytes = string.as_bytes();
+    let number = |start: usize, length: usize| -> Option<IntType> {
+        let digits = bytes.get(start..start + length)?;
+        digits.iter().try_fold(0, |number, digit| match digit {
+            b'0'..=b'9' => Some(number * 10 + IntType::from(digit - b'0')),
+            _ => None,
+        })
+    };
+    let separator = |index: usize, separators: &[u8]| {
+        bytes
+            .get(index)
+            .is_some_and(|byte| separators.contains(byte))
+    };
+
+    let (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) = (
+        number(0, 4),
+        number(5, 2),
+        number(8, 2),
+        number(11, 2),
+        number(14, 2),
+        number(17, 2),
+    ) else {
+        return Err(format_error());
+    };
+    if !(separator(4, b"-")
+        && separator(7, b"-")
+        && separator(10, b"Tt ")
+        && separator(13, b":")
+        && separator(16, b":"))
+    {
+        return Err(format_error());
+    }
+
+    let mut index = 19;
+    let mut nanoseconds = 0;
+    if separator(index, b".") {
+        index += 1;
+        let digits = bytes[index..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return Err(format_error());
+        }
+        for (position, digit) in bytes[index..index + digits].iter().take(9).enumerate() {
+            nanoseconds += u32::from(digit - b'0') * 10u32.pow(8 - position as u32);
+        }
+        index += digits;
+    }
+
+    let offset = match bytes.get(index) {
+        Some(b'Z' | b'z') if index + 1 == bytes.len() => 0,
+        Some(sign @ (b'+' | b'-')) if index + 6 == bytes.len() && separator(index + 3, b":") => {
+            let (Some(offset_hours), Some(offset_minutes)) =
+                (number(index + 1, 2), number(index + 4, 2))
+            else {
+                return Err(format_error());
+            };
+            if offset_hours > 23 || offset_minutes > 59 {
+                return Err(error("the timezone offset is out of range"));
+            }
+            let offset = offset_hours * 3_600 + offset_minutes * 60;
+            if *sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        },
+        _ => return Err(format_error()),
+    };
+
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return Err(error("the date does not exist"));
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(error("the time does not exist"));
+    }
+
+    let seconds =
+        days_since_epoch(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second - offset;
+    Ok((seconds, nanoseconds))
+}
+
+fn is_leap_year(year: IntType) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: IntType, month: IntType) -> IntType {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days from 1970-01-01 to the given date in the proleptic Gregorian calendar.
+fn days_since_epoch(year: IntType, month: IntType, day: IntType) -> IntType {
+    // Count years from March, so that the leap day is the last day of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
@@ -1,7 +1,12 @@
 use crate::{
-    token, tree, value::TupleType, Context, ContextWithMutableVariables, EmptyType, EvalexprError,
-    EvalexprResult, FloatType, HashMapContext, IntType, Node, Value, EMPTY_VALUE,
+    error::ErrorRecovery,
+    token::{self, Span, SpannedToken},
+    tree::{self, Completion},
+    value::TupleType,
+    Context, ContextWithMutableVariables, EmptyType, EvalObserver, EvalOptions, EvalexprError,
+    EvalexprResult, FloatType, HashMapContext, IntType, Node, ParserConfig, Value, EMPTY_VALUE,
 };
+use alloc::{string::String, vec::Vec};
 
 /// Evaluate the given expression string.
 ///
@@ -34,7 +39,20 @@ pub fn eval(string: &str) -> EvalexprResult<Value> {
 ///
 /// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
 pub fn eval_with_context<C: Context>(string: &str, context: &C) -> EvalexprResult<Value> {
-    tree::tokens_to_operator_tree(token::tokenize(string)?)?.eval_with_context(context)
+    build_operator_tree(string)?.eval_with_context(context)
+}
+
+/// Evaluate the given expression string with the given context, notifying the observer about the evaluation of each node.
+///
+/// See the documentation of `EvalObserver` for an example.
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn eval_with_context_and_observer<C: Context, O: EvalObserver + Send>(
+    string: &str,
+    context: &C,
+    observer: &mut O,
+) -> EvalexprResult<Value> {
+    build_operator_tree(string)?.eval_with_context_and_observer(context, observer)
 }
 
 /// Evaluate the given expression string with the given mutable context.
@@ -56,7 +74,49 @@ pub fn eval_with_context_mut<C: ContextWithMutableVariables>(
     string: &str,
     context: &mut C,
 ) -> EvalexprResult<Value> {
-    tree::tokens_to_operator_tree(token::tokenize(string)?)?.eval_with_context_mut(context)
+    build_operator_tree(string)?.eval_with_context_mut(context)
+}
+
+/// Evaluate the given expression string with the given context and evaluation options.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let options = EvalOptions::new().with_max_node_evaluations(Some(100));
+/// let context = HashMapContext::new();
+/// assert_eq!(eval_with_context_and_options("1 + 2 + 3", &context, &options), Ok(Value::from(6)));
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn eval_with_context_and_options<C: Context>(
+    string: &str,
+    context: &C,
+    options: &EvalOptions,
+) -> EvalexprResult<Value> {
+    build_operator_tree(string)?.eval_with_context_and_options(context, options)
+}
+
+/// Evaluate the given expression string with the given mutable context and evaluation options.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let options = EvalOptions::new().with_max_node_evaluations(Some(100));
+/// let mut context = HashMapContext::new();
+/// assert_eq!(eval_with_context_mut_and_options("a = 3; a * 2", &mut context, &options), Ok(Value::from(6)));
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn eval_with_context_mut_and_options<C: ContextWithMutableVariables>(
+    string: &str,
+    context: &mut C,
+    options: &EvalOptions,
+) -> EvalexprResult<Value> {
+    build_operator_tree(string)?.eval_with_context_mut_and_options(context, options)
 }
 
 /// Build the operator tree for the given expression string.
@@ -84,7 +144,177 @@ pub fn eval_with_context_mut<C: ContextWithMutableVariables>(
 ///
 /// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
 pub fn build_operator_tree(string: &str) -> EvalexprResult<Node> {
-    tree::tokens_to_operator_tree(token::tokenize(string)?)
+    build_operator_tree_with_config(string, &ParserConfig::default())
+}
+
+/// Build the operator tree for the given expression string with the given parser configuration.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let config = ParserConfig::new().with_max_depth(3);
+/// assert!(build_operator_tree_with_config("1 + 2", &config).is_ok());
+/// assert_eq!(
+///     build_operator_tree_with_config("1 + 2 * 3", &config),
+///     Err(EvalexprError::RecursionLimitExceeded { max_depth: 3 })
+/// );
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn build_operator_tree_with_config(
+    string: &str,
+    config: &ParserConfig,
+) -> EvalexprResult<Node> {
+    let tokens = token::tokenize(string, config)?;
+    let tree =
+        tree::tokens_to_operator_tree(string, tokens, config, &mut ErrorRecovery::fail_fast())?;
+    if let Some(policy) = config.policy() {
+        policy.check_tree(&tree)?;
+    }
+    Ok(tree)
+}
+
+/// Split the given expression string into tokens, returning the kind of each token and the part of the string it was read from.
+///
+/// The tokens are the same the parser builds the operator tree from, so they can be used to highlight an expression consistently with how it is evaluated.
+/// Whitespace and comments are not returned as tokens.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let tokens = tokenize("max(a, 2)").unwrap(); // Do proper error handling here
+/// assert_eq!(
+///     tokens,
+///     vec![
+///         SpannedToken { kind: TokenKind::Identifier, span: Span::new(0, 3) },
+///         SpannedToken { kind: TokenKind::Parenthesis, span: Span::new(3, 4) },
+///         SpannedToken { kind: TokenKind::Identifier, span: Span::new(4, 5) },
+///         SpannedToken { kind: TokenKind::Operator, span: Span::new(5, 6) },
+///         SpannedToken { kind: TokenKind::Number, span: Span::new(7, 8) },
+///         SpannedToken { kind: TokenKind::Parenthesis, span: Span::new(8, 9) },
+///     ]
+/// );
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn tokenize(string: &str) -> EvalexprResult<Vec<SpannedToken>> {
+    tokenize_with_config(string, &ParserConfig::default())
+}
+
+/// Split the given expression string into tokens with the given parser configuration.
+///
+/// The configuration determines for example whether keyword operators like `and` are operators or identifiers.
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn tokenize_with_config(
+    string: &str,
+    config: &ParserConfig,
+) -> EvalexprResult<Vec<SpannedToken>> {
+    Ok(token::tokenize(string, config)?
+        .into_iter()
+        .map(|(token, span)| SpannedToken {
+            kind: token.kind(),
+            span,
+        })
+        .collect())
+}
+
+/// Build the operator tree for the given expression string with the given parser configuration,
+/// skipping past errors instead of failing at the first one.
+///
+/// Returns the operator tree built from the parts of the expression that could be parsed,
+/// together with all errors and the positions in the expression string where they occurred, ordered by their positions.
+/// Tokens that cannot be parsed are skipped, and braces and brackets that are not closed are closed at the end of the expression.
+/// A violation of the policy of the configuration is reported at the position of the whole expression.
+///
+/// If the list of errors is empty, the operator tree is the same as the one built by `build_operator_tree_with_config`.
+/// Otherwise, it should only be used for tooling like syntax highlighting or autocompletion, and not be evaluated.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let (tree, errors) = build_operator_tree_with_recovery("a + ? + (b", &ParserConfig::new());
+/// assert_eq!(
+///     errors,
+///     vec![
+///         (
+///             EvalexprError::UnmatchedPartialToken {
+///                 first: PartialToken::QuestionMark,
+///                 second: Some(PartialToken::Whitespace),
+///                 span: Span::new(4, 5),
+///             },
+///             Span::new(4, 5),
+///         ),
+///         (EvalexprError::UnmatchedLBrace { span: Span::new(8, 9) }, Span::new(8, 9)),
+///     ]
+/// );
+/// assert_eq!(tree.iter_variable_identifiers().collect::<Vec<_>>(), ["a", "b"]);
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn build_operator_tree_with_recovery(
+    string: &str,
+    config: &ParserConfig,
+) -> (Node, Vec<(EvalexprError, Span)>) {
+    let mut recovery = ErrorRecovery::recovering();
+    // When recovering, errors are recorded instead of being returned.
+    let tokens = token::tokenize_with_recovery(string, config, &mut recovery).unwrap_or_default();
+    let tree = tree::tokens_to_operator_tree(string, tokens, config, &mut recovery)
+        .unwrap_or_else(|_| Node::root_node());
+    if let Some(Err(error)) = config.policy().map(|policy| policy.check_tree(&tree)) {
+        recovery
+            .recover(error, Span::new(0, string.len()))
+            .unwrap_or_default();
+    }
+    let mut errors = recovery.into_errors();
+    errors.sort_by_key(|(_, span)| span.start);
+    (tree, errors)
+}
+
+/// Returns the candidates to complete the given expression string with at the given byte offset `cursor`.
+///
+/// The parser determines whether a value or an operator is expected at the cursor.
+/// If a value is expected, the candidates are the variables and functions of the context, the builtin functions unless they are disabled,
+/// keywords and the operators that start a value.
+/// If an operator is expected, the candidates are the binary operators, the assignment operators after an identifier,
+/// and the bracket that closes the innermost open group.
+/// Only candidates starting with the identifier or keyword directly before the cursor are returned,
+/// and their span covers this part of the expression.
+///
+/// The candidates are sorted by their kind and text.
+/// No candidates are returned if the cursor is within a string literal or a comment, or if it is not at a character boundary of the string.
+///
+/// Variables and functions are only suggested if the context lists them with `Context::iter_variable_identifiers` and `Context::iter_function_identifiers`.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context = context_map! {
+///     "width" => 3,
+///     "weight" => 70,
+///     "height" => 180,
+/// }
+/// .unwrap(); // Do proper error handling here
+///
+/// let expression = "height * we";
+/// let completions = complete(expression, expression.len(), &context);
+/// let texts: Vec<_> = completions.iter().map(|completion| completion.text.as_str()).collect();
+/// assert_eq!(texts, ["weight"]);
+/// assert_eq!(completions[0].kind, CompletionKind::Variable);
+/// assert_eq!(completions[0].span, Span::new(9, 11));
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn complete<C: Context>(string: &str, cursor: usize, context: &C) -> Vec<Completion> {
+    tree::completions(string, cursor, context)
 }
 
 /// Evaluate the given expression string into a string.
@@ -0,0 +1,81 @@
+use crate::{
+    context::{ContextWithMutableVariables, HashMapContext, IterateVariablesContext},
+    error::EvalexprResult,
+    interface::build_operator_tree,
+    value::Value,
+};
+use alloc::{string::String, vec::Vec};
+
+/// Evaluates successive lines of input with a context that persists between them, like the console of a debugger or a calculator.
+///
+/// Each line is an expression that is evaluated with the context of the interpreter,
+/// so variables assigned by a line can be read by the following lines.
+/// Functions are defined by assigning lambdas like `double = |x| x * 2`, which are stored as variables as well.
+/// A line that fails to parse or evaluate returns an error, but the assignments it made before failing are kept.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let mut interpreter = Interpreter::new();
+/// assert_eq!(interpreter.eval_line("rate = 3"), Ok(Value::Empty));
+/// assert_eq!(interpreter.eval_line("cost = |hours| hours * rate"), Ok(Value::Empty));
+/// assert_eq!(interpreter.eval_line("cost(4)"), Ok(Value::from(12)));
+/// assert!(interpreter.eval_line("cost(").is_err());
+///
+/// let bindings = interpreter.bindings();
+/// assert_eq!(bindings[0].0, "cost");
+/// assert_eq!(bindings[1], ("rate".to_string(), Value::from(3)));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Interpreter<C = HashMapContext> {
+    context: C,
+}
+
+impl Interpreter {
+    /// Constructs an interpreter with an empty `HashMapContext`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C> Interpreter<C> {
+    /// Constructs an interpreter that evaluates lines with the given context, for example to provide custom functions.
+    pub fn with_context(context: C) -> Self {
+        Self { context }
+    }
+
+    /// Returns the context of the interpreter.
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Returns the context of the interpreter mutably, for example to set variables from outside of the input.
+    pub fn context_mut(&mut self) -> &mut C {
+        &mut self.context
+    }
+
+    /// Returns the context of the interpreter, consuming the interpreter.
+    pub fn into_context(self) -> C {
+        self.context
+    }
+}
+
+impl<C: ContextWithMutableVariables> Interpreter<C> {
+    /// Evaluates a line of input with the context of the interpreter, and returns its value.
+    ///
+    /// Empty lines and lines that only contain comments evaluate to `Value::Empty`.
+    pub fn eval_line(&mut self, line: &str) -> EvalexprResult<Value> {
+        build_operator_tree(line)?.eval_with_context_mut(&mut self.context)
+    }
+}
+
+impl<C: IterateVariablesContext> Interpreter<C> {
+    /// Returns the variables of the context together with their values, sorted by their identifiers.
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        let mut bindings: Vec<_> = self.context.iter_variables().collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+}
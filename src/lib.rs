@@ -110,6 +110,20 @@
 //! evalexpr 2 + 3 # outputs `5` to stdout.
 //! ```
 //!
+//! Without arguments, it evaluates each line of the standard input with a context that persists between the lines,
+//! and `:vars` lists the assigned variables.
+//! Such consoles can be built with the `Interpreter` type, which evaluates successive lines with a persistent context.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let mut interpreter = Interpreter::new();
+//! interpreter.eval_line("total = 0").unwrap(); // Do proper error handling here
+//! interpreter.eval_line("total += 5").unwrap(); // Do proper error handling here
+//! assert_eq!(interpreter.eval_line("total * 2"), Ok(Value::from(10)));
+//! assert_eq!(interpreter.bindings(), vec![("total".to_string(), Value::from(5))]);
+//! ```
+//!
 //! ## Features
 //!
 //! ### Operators
@@ -129,14 +143,18 @@
 //! | % | 100 | Modulo (integer if both arguments are integers, otherwise float) |
 //! | + | 95 | Sum or String Concatenation |
 //! | - | 95 | Difference |
+//! | .. | 85 | Range of integers, excluding the end |
+//! | ..= | 85 | Range of integers, including the end |
 //! | < | 80 | Lower than |
 //! | \> | 80 | Greater than |
 //! | <= | 80 | Lower than or equal |
 //! | \>= | 80 | Greater than or equal |
 //! | == | 80 | Equal |
 //! | != | 80 | Not equal |
-//! | && | 75 | Logical and |
-//! | &#124;&#124; | 70 | Logical or |
+//! | in | 80 | Membership in a tuple, array, string or the keys of a map |
+//! | && | 75 | Logical and, evaluates the right operand only if the left one is true |
+//! | &#124;&#124; | 70 | Logical or, evaluates the right operand only if the left one is false |
+//! | ?? | 60 | Null-coalescing, evaluates to the right operand if the left one is empty or an unknown variable |
 //! | = | 50 | Assignment |
 //! | += | 50 | Sum-Assignment or String-Concatenation-Assignment |
 //! | -= | 50 | Difference-Assignment |
@@ -146,20 +164,28 @@
 //! | ^= | 50 | Exponentiation-Assignment |
 //! | &&= | 50 | Logical-And-Assignment |
 //! | &#124;&#124;= | 50 | Logical-Or-Assignment |
+//! | let = | 50 | [Let-Binding](#let-bindings) |
 //! | , | 40 | Aggregation |
 //! | ; | 0 | Expression Chaining |
 //!
+//! Chains of binary operators with the same precedence are evaluated left-to-right, so `8 - 4 - 2` is evaluated like `(8 - 4) - 2`.
+//! The exceptions are the right-associative exponentiation and assignment, so `2 ^ 3 ^ 2` is evaluated like `2 ^ (3 ^ 2)`, and `a = b = 1` like `a = (b = 1)`,
+//! as well as chained comparisons, which are explained below.
+//! For expressions that rely on the left-associative exponentiation of earlier versions, it can be restored with `ParserConfig::with_left_associative_exponentiation`.
+//!
 //! Supported unary operators:
 //!
 //! | Operator | Precedence | Description |
 //! |----------|------------|-------------|
 //! | - | 110 | Negation |
+//! | + | 110 | Unary plus, evaluates to its operand if it is a number |
 //! | ! | 110 | Logical not |
 //!
 //! Operators that take numbers as arguments can either take integers or floating point numbers.
 //! If one of the arguments is a floating point number, all others are converted to floating point numbers as well, and the resulting value is a floating point number as well.
 //! Otherwise, the result is an integer.
-//! An exception to this is the exponentiation operator that always returns a floating point number.
+//! An exception to this is the exponentiation operator that always returns a floating point number,
+//! unless integer exponentiation is enabled with `EvalOptions::with_integer_exponentiation`.
 //! Example:
 //!
 //! ```rust
@@ -170,6 +196,76 @@
 //! assert_eq!(eval("2^2"), Ok(Value::from(4.0)));
 //! ```
 //!
+//! The logical operators `&&` and `||` short-circuit, so an expression like `x != 0 && 10 / x > 2` does not divide by zero.
+//!
+//! The membership operator `in` checks if a tuple or array contains a value, if a string contains a substring, or if a map contains a key.
+//! It has the same precedence as the comparison operators, so `x + 1 in (2, 3) && y` is evaluated like `((x + 1) in (2, 3)) && y`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! assert_eq!(eval("2 in (1, 2, 3)"), Ok(Value::from(true)));
+//! assert_eq!(eval("\"sub\" in \"substring\""), Ok(Value::from(true)));
+//! assert_eq!(eval("1 + 3 in [1, 2, 3]"), Ok(Value::from(false)));
+//! ```
+//!
+//! The equality operators `==` and `!=` compare numbers by their value after converting them into a common type like the other operators, so `1 == 1.0` is true.
+//! Tuples and arrays are equal if they have the same length and all their elements are equal, and maps are equal if they have the same keys with equal values.
+//! Strings, booleans and all other values are equal if they are identical.
+//! Comparing values of mismatched types with `==` or `!=` never fails: a string is never equal to a number, and a tuple is never equal to an array.
+//! The ordering comparisons `<`, `>`, `<=` and `>=` however only accept two numbers or two strings, and fail with an `EvalexprError` otherwise.
+//! The operator `in` and the builtin function `contains` use the same equality to find elements.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! assert_eq!(eval("(1, 2.0) == (1.0, 2)"), Ok(Value::from(true)));
+//! assert_eq!(eval("[1, (2, \"a\")] == [1, (2, \"b\")]"), Ok(Value::from(false)));
+//! assert_eq!(eval("\"1\" == 1"), Ok(Value::from(false)));
+//! assert_eq!(eval("(1, 2) != [1, 2]"), Ok(Value::from(true)));
+//! assert!(eval("\"1\" < 2").is_err());
+//! ```
+//!
+//! The null-coalescing operator `??` provides defaults: `a ?? 0` evaluates to `0` if `a` is not set in the context or if it is empty, and to `a` otherwise.
+//! Only the error of reading a variable that does not exist in the left operand is suppressed, and the right operand is only evaluated if it is needed.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "timeout" => 30, "retries" => () }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context("timeout ?? 10", &context), Ok(Value::from(30)));
+//! assert_eq!(eval_with_context("retries ?? 3", &context), Ok(Value::from(3)));
+//! assert_eq!(eval_with_context("delay ?? 0", &context), Ok(Value::from(0)));
+//! ```
+//!
+//! For rule authors that are not used to the symbolic operators, `ParserConfig::with_keyword_operators` enables the keywords `and`, `or` and `not` as aliases of `&&`, `||` and `!`,
+//! and `ParserConfig::with_angle_bracket_neq` enables `<>` as an alias of `!=`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let config = ParserConfig::new().with_keyword_operators(true).with_angle_bracket_neq(true);
+//! let tree = build_operator_tree_with_config("x <> 0 and not (x > 10)", &config).unwrap(); // Do proper error handling here
+//! let context = context_map!{"x" => 5}.unwrap(); // Do proper error handling here
+//! assert_eq!(tree.eval_boolean_with_context(&context), Ok(true));
+//! ```
+//!
+//! #### Chained Comparisons
+//!
+//! The comparison operators `<`, `>`, `<=` and `>=` can be chained like in mathematical notation.
+//! An expression like `a < b <= c` is evaluated like `a < b && b <= c`, except that `b` is evaluated only once.
+//! The evaluation stops at the first comparison that is false.
+//! Parentheses and the operators `==` and `!=` end a chain.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map!{"x" => 5}.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_boolean_with_context("1 < x < 10", &context), Ok(true));
+//! assert_eq!(eval_boolean_with_context("1 < x <= 3", &context), Ok(false));
+//! assert_eq!(eval_boolean_with_context("1 < x < 10 == true", &context), Ok(true));
+//! ```
+//!
 //! #### The Aggregation Operator
 //!
 //! The aggregation operator aggregates a set of values into a tuple.
@@ -255,6 +351,64 @@
 //! assert_eq!(healing_script.eval_int_with_context_mut(&mut context), Ok(5));
 //! ```
 //!
+//! #### Let-Bindings
+//!
+//! A chained expression of the form `let name = value` binds a local variable that is visible to the following expressions of the chain.
+//! Unlike assignments, let-bindings do not modify the context, so they can be used with immutable contexts as well.
+//! Let-bound variables shadow variables of the context and can be reassigned within the chain, but not to a value of another type.
+//! Without a following assignment, `let` is an ordinary identifier.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "a" => 1, "b" => 5, "c" => 4 }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context("let d = b*b - 4*a*c; d > 0", &context), Ok(Value::from(true)));
+//! assert_eq!(eval_with_context("let square = |x| x * x; square(b)", &context), Ok(Value::from(25)));
+//! assert_eq!(context.get_value("d"), None);
+//! ```
+//!
+//! #### Arrays, Maps and Indexing
+//!
+//! Unlike the aggregation operator, square brackets construct arrays that keep their elements as they are, even if these are tuples or arrays themselves.
+//! An expression in square brackets directly after a value indexes into that value, with a precedence of 190.
+//! Tuples and arrays are indexed with integers starting at zero, strings are indexed by their characters in the same way,
+//! and maps are indexed with string keys.
+//! Maps have no literal, but can be stored in a context.
+//! A range like `1..4` evaluates to the tuple of integers from the start up to, but not including the end, here `(1, 2, 3)`,
+//! and an inclusive range like `1..=4` includes the end as well.
+//! Ranges are created eagerly, so a policy that limits the length of tuples also limits the length of ranges, see [evaluation options](#evaluation-options).
+//! A range directly in square brackets selects a slice of a tuple, array or string instead: `t[1..3]` contains the elements at the indices 1 and 2,
+//! and `t[1..=3]` includes the element at index 3 as well.
+//! An index that is out of bounds results in an `EvalexprError::IndexOutOfBounds`, and a missing key in an `EvalexprError::KeyNotFound`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! assert_eq!(eval("[[1, 2], 3][0]"), Ok(Value::Array(vec![1.into(), 2.into()])));
+//! assert_eq!(eval("a = [1, 2, 3]; a[1] + a[2]"), Ok(5.into()));
+//! assert_eq!(eval("t = (1, 2, 3, 4); t[0] + len(t[1..3])"), Ok(3.into()));
+//! assert_eq!(eval("\"evalexpr\"[0..=3]"), Ok("eval".into()));
+//! assert_eq!(eval("sum(1..=10)"), Ok(55.into()));
+//! assert_eq!(eval("x = 7; x in 1..10"), Ok(true.into()));
+//!
+//! let mut map = MapType::new();
+//! map.insert("answer".into(), 42.into());
+//! let context = context_map! { "map" => Value::from(map) }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context("map[\"answer\"]", &context), Ok(42.into()));
+//! ```
+//!
+//! #### Comments
+//!
+//! Expressions can contain line comments starting with `//` and block comments enclosed in `/*` and `*/`.
+//! Comments are treated like whitespace, and a block comment that is not closed results in an `EvalexprError::UnterminatedComment`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! assert_eq!(eval("a = 3; /* the length */ a * 2 // doubled"), Ok(6.into()));
+//! assert_eq!(eval("1 + /* 2 */ 3"), Ok(4.into()));
+//! ```
+//!
 //! ### Contexts
 //!
 //! An expression evaluator that just evaluates expressions would be useful already, but this crate can do more.
@@ -269,7 +423,7 @@
 //!
 //! assert_eq!(eval("a = 5;"), Ok(Value::from(())));
 //! // The context is not preserved between eval calls
-//! assert_eq!(eval("a"), Err(EvalexprError::VariableIdentifierNotFound("a".to_string())));
+//! assert!(matches!(eval("a"), Err(EvalexprError::VariableIdentifierNotFound { .. })));
 //!
 //! let mut context = HashMapContext::new();
 //! assert_eq!(eval_with_context_mut("a = 5;", &mut context), Ok(Value::from(())));
@@ -291,6 +445,20 @@
 //! Also, the `HashMapContext` is type safe.
 //! This means that assigning to `a` again with a different type yields an error.
 //! Type unsafe contexts may be implemented if requested.
+//!
+//! If an expression reads a variable or calls a function that does not exist, the error suggests similar identifiers of the context and builtin functions.
+//! Custom contexts can take part in this by implementing `Context::iter_variable_identifiers` and `Context::iter_function_identifiers`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "length" => 3 }.unwrap(); // Do proper error handling here
+//! let error = eval_with_context("lenght * 2", &context).unwrap_err();
+//! assert_eq!(
+//!     error.to_string(),
+//!     "Variable identifier is not bound to anything by context: \"lenght\" at position 0. Did you mean \"length\"?"
+//! );
+//! ```
 //! For reading `a`, it is enough to pass an immutable reference.
 //!
 //! Contexts can also be manipulated in code.
@@ -311,6 +479,34 @@
 //! assert_eq!(context.get_value("b"), Some(&Value::from(1.0)));
 //! ```
 //!
+//! The variables of a `HashMapContext` can be inspected with the methods of `IterateVariablesContext`,
+//! and removed with `remove_value` or `clear_variables`, which is useful for long-running sessions:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let mut context = HashMapContext::new();
+//! eval_with_context_mut("a = 10; b = 1.0", &mut context).unwrap(); // Do proper error handling here
+//! assert_eq!(context.remove_value("a"), Ok(Some(Value::from(10))));
+//! assert_eq!(context.iter_variable_names().collect::<Vec<_>>(), vec!["b".to_string()]);
+//! context.clear_variables();
+//! assert_eq!(context.iter_variables().count(), 0);
+//! ```
+//!
+//! To undo the assignments of an expression that failed halfway, `snapshot` saves the variables of a `HashMapContext`,
+//! and `rollback` restores them:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let mut context = context_map! { "stock" => 3 }.unwrap(); // Do proper error handling here
+//! let snapshot = context.snapshot();
+//! if eval_with_context_mut("stock -= 5; stock >= 0 || unknown_function()", &mut context).is_err() {
+//!     context.rollback(snapshot);
+//! }
+//! assert_eq!(context.get_value("stock"), Some(&Value::from(3)));
+//! ```
+//!
 //! Contexts are also required for user-defined functions.
 //! Those can be passed one by one with the `set_function` method, but it might be more convenient to use the `context_map!` macro instead:
 //!
@@ -325,6 +521,74 @@
 //!
 //! For more information about user-defined functions, refer to the respective [section](#user-defined-functions).
 //!
+//! A `HashMapContext` can also be collected from an iterator over pairs of identifiers and values, or extended with one:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let mut context: HashMapContext = vec![("width", 3), ("height", 4)].into_iter().collect();
+//! context.extend(vec![("depth", 5)]);
+//! assert_eq!(eval_with_context("width * height * depth", &context), Ok(Value::from(60)));
+//! ```
+//!
+//! Several contexts can be layered with a `ChainedContext`, which looks up each identifier in its layers in order.
+//! This way, a large context of constants can be shared between evaluations without copying it:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let constants = context_map! { "tax_rate" => 0.2 }.unwrap(); // Do proper error handling here
+//! let request = context_map! { "price" => 10.0 }.unwrap(); // Do proper error handling here
+//! let context = ChainedContext::new().with_layer(&request).with_layer(&constants);
+//! assert_eq!(eval_with_context("price * (1 + tax_rate)", &context), Ok(12.0.into()));
+//! ```
+//!
+//! Contexts, operator trees and compiled expressions are `Send` and `Sync`, so they can be shared between threads.
+//! The `SyncHashMapContext` shares its mappings between its clones through an `Arc`, and copies them only when a clone is assigned to.
+//!
+//! Variables that are expensive to obtain can be set as lazy variables of a `LazyContext`, which computes them only if an expression reads them,
+//! and at most once until it is reset:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let mut context = LazyContext::new(context_map! { "budget" => 10 }.unwrap()); // Do proper error handling here
+//! context.set_lazy_value("current_price".into(), || Ok(Value::from(12)));
+//! assert_eq!(eval_with_context("budget > 100 && current_price < budget", &context), Ok(Value::from(false)));
+//! assert!(!context.is_computed("current_price"));
+//! assert_eq!(eval_with_context("current_price < budget", &context), Ok(Value::from(false)));
+//! assert!(context.is_computed("current_price"));
+//! ```
+//!
+//! If the computation of a lazy variable fails, reading the variable fails with the error of the computation.
+//! Custom contexts whose lookups can fail, e.g. because they are backed by a database, can report their errors the same way
+//! by implementing `Context::try_get_value`, which expressions use to read variables.
+//!
+//! The `EnvContext` reads variables from the environment variables of the process, which is useful for command line tools.
+//! For each variable `NAME`, it also provides the boolean `NAME_set` to check if the environment variable exists:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! std::env::set_var("DEPLOY_REPLICAS", "3");
+//! let context = EnvContext::new().with_prefix("DEPLOY_");
+//! assert_eq!(eval_with_context("REGION_set || REPLICAS > 2", &context), Ok(Value::from(true)));
+//! ```
+//!
+//! To read variables from storage that is not a context without implementing `Context`,
+//! the `CallbackContext` resolves variables and functions with closures:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let speeds = [("car", 120), ("bike", 25)];
+//! let context = CallbackContext::new(
+//!     |identifier| speeds.iter().find(|(name, _)| *name == identifier).map(|(_, speed)| Value::from(*speed)),
+//!     |_| None,
+//! );
+//! assert_eq!(eval_with_context("car / bike", &context), Ok(Value::from(4)));
+//! ```
+//!
 //! ### Builtin Functions
 //!
 //! This crate offers a set of builtin functions (see below for a full list).
@@ -335,25 +599,64 @@
 //! let mut context = HashMapContext::new();
 //! assert_eq!(eval_with_context("max(1,3)",&context),Ok(Value::from(3)));
 //! context.set_builtin_functions_disabled(true).unwrap(); // Do proper error handling here
-//! assert_eq!(eval_with_context("max(1,3)",&context),Err(EvalexprError::FunctionIdentifierNotFound(String::from("max"))));
+//! assert_eq!(eval_with_context("max(1,3)",&context),Err(EvalexprError::function_identifier_not_found(String::from("max"))));
 //! ```
 //!
 //! Not all contexts support enabling or disabling builtin functions.
 //! Specifically the `EmptyContext` has builtin functions disabled by default, and they cannot be enabled.
 //! Symmetrically, the `EmptyContextWithBuiltinFunctions` has builtin functions enabled by default, and they cannot be disabled.
+//! It is the context to use for expressions that may call builtin functions, but not read any variables.
+//!
+//! Functions defined in the context take precedence over builtin functions with the same identifier,
+//! so builtin functions can be replaced with custom implementations:
+//!
+//! ```rust
+//! use evalexpr::*;
+//! let context = context_map!{
+//!     "max" => Function::new(|_| Ok(Value::from("custom"))),
+//! }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context("max(1,3)",&context),Ok(Value::from("custom")));
+//! assert_eq!(eval_with_context("min(1,3)",&context),Ok(Value::from(1)));
+//! ```
 //!
 //! | Identifier           | Argument Amount | Argument Types                | Description |
 //! |----------------------|-----------------|-------------------------------|-------------|
 //! | `min`                | >= 1            | Numeric                       | Returns the minimum of the arguments |
 //! | `max`                | >= 1            | Numeric                       | Returns the maximum of the arguments |
-//! | `len`                | 1               | String/Tuple                  | Returns the character length of a string, or the amount of elements in a tuple (not recursively) |
-//! | `floor`              | 1               | Numeric                       | Returns the largest integer less than or equal to a number |
-//! | `round`              | 1               | Numeric                       | Returns the nearest integer to a number. Rounds half-way cases away from 0.0 |
-//! | `ceil`               | 1               | Numeric                       | Returns the smallest integer greater than or equal to a number |
-//! | `if`                 | 3               | Boolean, Any, Any             | If the first argument is true, returns the second argument, otherwise, returns the third  |
+//! | `len`                | 1               | String/Tuple/Array/Map        | Returns the character length of a string, or the amount of elements in a tuple, array or map (not recursively) |
+//! | `sum`                | >= 0            | Numeric                       | Returns the sum of the arguments, or 0 if there are none |
+//! | `product`            | >= 0            | Numeric                       | Returns the product of the arguments, or 1 if there are none |
+//! | `avg`                | >= 1            | Numeric                       | Returns the arithmetic mean of the arguments as a float |
+//! | `count`              | >= 0            | Any                           | Returns the amount of arguments |
+//! | `floor`              | 1               | Numeric                       | Returns the largest integer less than or equal to a number, returning an integer if the argument was an integer, and a float otherwise |
+//! | `round`              | 1               | Numeric                       | Returns the nearest integer to a number. Rounds half-way cases away from 0.0, returning an integer if the argument was an integer, and a float otherwise |
+//! | `ceil`               | 1               | Numeric                       | Returns the smallest integer greater than or equal to a number, returning an integer if the argument was an integer, and a float otherwise |
+//! | `int`                | 1               | Any                           | Converts the argument into an integer, see below |
+//! | `float`              | 1               | Any                           | Converts the argument into a float, see below |
+//! | `bool`               | 1               | Any                           | Converts the argument into a boolean, see below |
+//! | `str`                | 1               | Any                           | Converts the argument into a string. Strings are returned unchanged, and other values are formatted like their `Display` implementation |
+//! | `if`                 | 3               | Boolean, Any, Any             | If the first argument is true, returns the second argument, otherwise, returns the third. Only the returned argument is evaluated |
 //! | `contains`           | 2               | Tuple, any non-tuple          | Returns true if second argument exists in first tuple argument. |
 //! | `contains_any`       | 2               | Tuple, Tuple of any non-tuple | Returns true if one of the values in the second tuple argument exists in first tuple argument. |
-//! | `typeof`             | 1               | Any                           | returns "string", "float", "int", "boolean", "tuple", or "empty" depending on the type of the argument  |
+//! | `map`                | 2               | Tuple/Array, Lambda           | Returns the results of calling the lambda with each element, see [lambdas](#lambdas) |
+//! | `filter`             | 2               | Tuple/Array, Lambda           | Returns the elements for which the lambda returns true |
+//! | `reduce`             | 3               | Tuple/Array, Lambda, Any      | Combines the elements into one value by calling the lambda with the value combined so far and each element, starting with the third argument |
+//! | `any`                | 2               | Tuple/Array, Lambda           | Returns true if the lambda returns true for at least one element |
+//! | `all`                | 2               | Tuple/Array, Lambda           | Returns true if the lambda returns true for all elements |
+//! | `typeof`             | 1               | Any                           | returns "string", "float", "int", "decimal", "datetime", "duration", "boolean", "tuple", "array", "map", "lambda", or "empty" depending on the type of the argument  |
+//! | `is_string`          | 1               | Any                           | Returns true if the argument is a string |
+//! | `is_int`             | 1               | Any                           | Returns true if the argument is an integer |
+//! | `is_float`           | 1               | Any                           | Returns true if the argument is a float |
+//! | `is_decimal`         | 1               | Any                           | Returns true if the argument is a decimal. Requires the `decimal` feature flag |
+//! | `is_datetime`        | 1               | Any                           | Returns true if the argument is a date and time. Requires the `chrono` feature flag |
+//! | `is_duration`        | 1               | Any                           | Returns true if the argument is a duration. Requires the `chrono` feature flag |
+//! | `is_number`          | 1               | Any                           | Returns true if the argument is an integer, float or decimal |
+//! | `is_boolean`         | 1               | Any                           | Returns true if the argument is a boolean |
+//! | `is_tuple`           | 1               | Any                           | Returns true if the argument is a tuple. Note that multiple arguments form a tuple |
+//! | `is_array`           | 1               | Any                           | Returns true if the argument is an array |
+//! | `is_map`             | 1               | Any                           | Returns true if the argument is a map |
+//! | `is_lambda`          | 1               | Any                           | Returns true if the argument is a lambda |
+//! | `is_empty`           | 1               | Any                           | Returns true if the argument is the empty value |
 //! | `math::is_nan`       | 1               | Numeric                       | Returns true if the argument is the floating-point value NaN, false if it is another floating-point value, and throws an error if it is not a number  |
 //! | `math::is_finite`    | 1               | Numeric                       | Returns true if the argument is a finite floating-point number, false otherwise  |
 //! | `math::is_infinite`  | 1               | Numeric                       | Returns true if the argument is an infinite floating-point number, false otherwise  |
@@ -382,12 +685,20 @@
 //! | `math::cbrt`         | 1               | Numeric                       | Returns the cube root of a number |
 //! | `math::hypot`        | 2               | Numeric                       | Calculates the length of the hypotenuse of a right-angle triangle given legs of length given by the two arguments |
 //! | `math::abs`          | 1               | Numeric                       | Returns the absolute value of a number, returning an integer if the argument was an integer, and a float otherwise |
+//! | `math::rem_euclid`   | 2               | Numeric, Numeric              | Returns the euclidean remainder of the first number divided by the second, which is never negative |
+//! | `math::div_euclid`   | 2               | Numeric, Numeric              | Returns the euclidean quotient of the first number divided by the second, rounded such that `math::rem_euclid` is the matching remainder |
+//! | `approx_eq`          | 2, 3            | Numeric, Numeric, Numeric     | Returns true if the two numbers are equal up to the tolerance given as third argument, or `1e-9` by default, see below |
 //! | `str::regex_matches` | 2               | String, String                | Returns true if the first argument matches the regex in the second argument (Requires `regex_support` feature flag) |
 //! | `str::regex_replace` | 3               | String, String, String        | Returns the first argument with all matches of the regex in the second argument replaced by the third argument (Requires `regex_support` feature flag) |
 //! | `str::to_lowercase`  | 1               | String                        | Returns the lower-case version of the string |
 //! | `str::to_uppercase`  | 1               | String                        | Returns the upper-case version of the string |
 //! | `str::trim`          | 1               | String                        | Strips whitespace from the start and the end of the string |
+//! | `str::len`           | 1               | String                        | Returns the character length of the string |
+//! | `str::contains`      | 2               | String, String                | Returns true if the first argument contains the second argument |
+//! | `str::starts_with`   | 2               | String, String                | Returns true if the first argument starts with the second argument |
+//! | `str::substring`     | 3               | String, Int, Int              | Returns the characters of the string from the start index (inclusive) to the end index (exclusive) |
 //! | `str::from`          | >= 0            | Any                           | Returns passed value as string |
+//! | `format`             | >= 1            | String, Any                   | Replaces the placeholders in the format string with the other arguments, see below |
 //! | `bitand`             | 2               | Int                           | Computes the bitwise and of the given integers |
 //! | `bitor`              | 2               | Int                           | Computes the bitwise or of the given integers |
 //! | `bitxor`             | 2               | Int                           | Computes the bitwise xor of the given integers |
@@ -395,33 +706,126 @@
 //! | `shl`                | 2               | Int                           | Computes the given integer bitwise shifted left by the other given integer |
 //! | `shr`                | 2               | Int                           | Computes the given integer bitwise shifted right by the other given integer |
 //! | `random`             | 0               | Empty                         | Return a random float between 0 and 1. Requires the `rand` feature flag. |
+//! | `random_int`         | 2               | Int                           | Return a random integer between the given integers, both inclusive. Requires the `rand` feature flag. |
+//! | `random_choice`      | >= 1            | Any                           | Return one of the given values, or one element of the given array, chosen at random. Requires the `rand` feature flag. |
+//! | `duration`           | 1               | String                        | Parses a duration like `"1h 30m"`, see below |
+//! | `timestamp`          | 1               | String                        | Parses a date and time in RFC 3339 format like `"2024-01-01T12:00:00Z"`, see below |
+//! | `now`                | 0               | Empty                         | Returns the current date and time. Requires the `chrono` feature flag |
+//! | `datetime::parse`    | 1, 2            | String, String                | Parses a date and time in RFC 3339 format, or in the format given as second argument. Requires the `chrono` feature flag |
+//! | `datetime::format`   | 2               | DateTime, String              | Formats a date and time in the given format. Requires the `chrono` feature flag |
+//! | `datetime::timestamp` | 1              | DateTime                      | Returns the seconds since the Unix epoch as integer. Requires the `chrono` feature flag |
+//! | `datetime::from_timestamp` | 1         | Number                        | Returns the date and time the given amount of seconds after the Unix epoch. Requires the `chrono` feature flag |
+//! | `duration::seconds`  | 1               | Number                        | Returns a duration of the given amount of seconds. Requires the `chrono` feature flag |
+//! | `duration::as_seconds` | 1             | Duration                      | Returns the amount of seconds of a duration as float. Requires the `chrono` feature flag |
 //!
 //! The `min` and `max` functions can deal with a mixture of integer and floating point arguments.
 //! If the maximum or minimum is an integer, then an integer is returned.
 //! Otherwise, a float is returned.
 //!
+//! The aggregation functions `sum`, `product`, `avg` and `count` take their arguments either directly like `sum(1, 2, 3)`, or as a single tuple or array like `sum(scores)`.
+//! A single value that is not a tuple or array counts as one argument, and the empty value as none.
+//! `sum` and `product` follow the same conversion rules as the operators `+` and `*`, and `avg` returns a float unless its arguments contain a decimal.
+//!
+//! The conversion functions `int`, `float` and `bool` convert their argument as follows, and return an `EvalexprError::ConversionError` if that is not possible:
+//!
+//! * `int` truncates floats towards zero, converts `true` into 1 and `false` into 0, and parses strings as integers, or as floats that are truncated.
+//!   Floats that are not finite or do not fit into an integer cannot be converted.
+//! * `float` converts numbers into the nearest float, `true` into 1.0 and `false` into 0.0, and parses strings as floats.
+//! * `bool` converts numbers into `true` if they are not zero, and parses the strings `"true"` and `"false"`.
+//!
+//! Surrounding whitespace is ignored when parsing strings, and tuples, arrays, maps and the empty value cannot be converted.
+//!
+//! The other numeric functions follow the same conversion rules as the [operators](#operators).
+//! The rounding functions, `math::abs`, `math::rem_euclid` and `math::div_euclid` return integer arguments as integers, while all other `math::` functions always return floats.
+//!
+//! The operator `%` takes the sign of the dividend like in Rust, so `-7 % 3` is `-1`, while `math::rem_euclid(-7, 3)` is `2`.
+//! This makes `math::rem_euclid` the right choice for wrapping angles or times of day, as in `math::rem_euclid(angle, 360.0)`.
+//! With integer arguments, both functions fail with `EvalexprError::DivisionByZero` if the divisor is zero, while float arguments result in NaN like `%` does.
+//!
+//! As floats cannot represent most decimal fractions exactly, `0.1 + 0.2 == 0.3` is false.
+//! The `approx_eq` function compares numbers with a tolerance instead, so `approx_eq(0.1 + 0.2, 0.3)` is true.
+//! The tolerance is absolute for numbers with a magnitude of at most one, and relative to the larger magnitude otherwise, so `approx_eq(a, b, 0.01)` allows a difference of 0.01 between small numbers and of one percent between large ones.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! assert_eq!(eval("0.1 + 0.2 == 0.3"), Ok(Value::from(false)));
+//! assert_eq!(eval("approx_eq(0.1 + 0.2, 0.3)"), Ok(Value::from(true)));
+//! assert_eq!(eval("approx_eq(0.5, 0.505, 0.01)"), Ok(Value::from(true)));
+//! assert_eq!(eval("approx_eq(1000, 1005, 0.01)"), Ok(Value::from(true)));
+//! assert_eq!(eval("approx_eq(1000, 1020, 0.01)"), Ok(Value::from(false)));
+//! ```
+//!
 //! The regex functions require the feature flag `regex_support`.
 //!
+//! The `format` function replaces each `{}` in its first argument with the next of the other arguments, and `{n}` with the argument at index `n`, counting from zero.
+//! Strings are inserted without quotes, and all other values as they are displayed.
+//! `{{` and `}}` produce literal braces.
+//! Each argument has to be used, otherwise an `EvalexprError::InvalidFormatString` is returned.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "n" => 3, "price" => 4.5 }.unwrap(); // Do proper error handling here
+//! assert_eq!(
+//!     eval_with_context(r#"format("{} items cost {}, {0} is {{n}}", n, price * n)"#, &context),
+//!     Ok(Value::from("3 items cost 13.5, 3 is {n}"))
+//! );
+//! ```
+//!
+//! The `if` function evaluates its arguments lazily, so the branch that is not taken is never evaluated:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! assert_eq!(eval("x = 0; if(x != 0, 10 / x, 0)"), Ok(Value::from(0)));
+//! ```
+//!
+//! `duration` takes a sequence of amounts with units, like `"1h 30m"` or `"-2.5s"`, where a leading minus negates the whole duration.
+//! The units are `w`, `d`, `h`, `m`, `s`, `ms`, `us` and `ns`, which can also be written like `weeks`, `days`, `hours`, `min`, `sec` and so on.
+//! `timestamp` takes a date and time in RFC 3339 format, like `"2024-01-01T12:00:00Z"` or `"2024-01-01 14:00:00.5+02:00"`.
+//! Without the `chrono` feature, they return the amount of seconds of the duration and the seconds since the Unix epoch, as integer if it is whole and as float otherwise.
+//! With the `chrono` feature, they return a `Value::Duration` and a `Value::DateTime`, see [Dates and Times](#dates-and-times).
+//!
+//! ```rust
+//! # #[cfg(not(feature = "chrono"))] {
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "age" => 600, "created_at" => 1_704_067_200 }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context(r#"age > duration("5m 30s")"#, &context), Ok(Value::from(true)));
+//! assert_eq!(
+//!     eval_with_context(r#"created_at < timestamp("2024-01-01T00:00:01Z")"#, &context),
+//!     Ok(Value::from(true))
+//! );
+//! # }
+//! ```
+//!
 //! ### Values
 //!
 //! Operators take values as arguments and produce values as results.
-//! Values can be booleans, integer or floating point numbers, strings, tuples or the empty type.
+//! Values can be booleans, integer or floating point numbers, strings, tuples, arrays, maps or the empty type.
 //! Values are denoted as displayed in the following table.
 //!
 //! | Value type | Example |
 //! |------------|---------|
-//! | `Value::String` | `"abc"`, `""`, `"a\"b\\c"` |
+//! | `Value::String` | `"abc"`, `""`, `"a\"b\\c"`, `'say "hi"'`, `"line\n\u{1F600}"` |
 //! | `Value::Boolean` | `true`, `false` |
-//! | `Value::Int` | `3`, `-9`, `0`, `135412`, `0xfe02`, `-0x1e` |
+//! | `Value::Int` | `3`, `-9`, `0`, `135412`, `1_000_000`, `0xfe02`, `-0x1e`, `0o17`, `0b1010` |
 //! | `Value::Float` | `3.`, `.35`, `1.00`, `0.5`, `123.554`, `23e4`, `-2e-3`, `3.54e+2` |
 //! | `Value::Tuple` | `(3, 55.0, false, ())`, `(1, 2)` |
+//! | `Value::Array` | `[]`, `[1, (2, 3), [4]]` |
+//! | `Value::Map` | no literal |
 //! | `Value::Empty` | `()` |
 //!
 //! Integers are internally represented as `i64`, and floating point numbers are represented as `f64`.
-//! Tuples are represented as `Vec<Value>` and empty values are not stored, but represented by Rust's unit type `()` where necessary.
+//! Integer literals can be written in decimal, hexadecimal (`0x`), octal (`0o`) or binary (`0b`) notation, and their digits can be separated by underscores.
+//! An integer literal that does not fit into an `i64` results in an `EvalexprError::IntLiteralOutOfRange`.
+//! String literals are enclosed in double quotes `"` or single quotes `'`.
+//! They support the escape sequences `\"`, `\'`, `\\`, `\n`, `\r`, `\t`, `\0` and unicode escapes like `\u{1F600}`, and any other escape sequence results in an `EvalexprError::IllegalEscapeSequence`.
+//! Tuples and arrays are represented as `Vec<Value>`, maps as `BTreeMap<String, Value>`, and empty values are not stored, but represented by Rust's unit type `()` where necessary.
 //!
 //! There exist type aliases for some of the types.
-//! They include `IntType`, `FloatType`, `TupleType` and `EmptyType`.
+//! They include `IntType`, `FloatType`, `TupleType`, `ArrayType`, `MapType` and `EmptyType`.
 //!
 //! Values can be constructed either directly or using the `From` trait.
 //! They can be decomposed using the `Value::as_[type]` methods.
@@ -468,23 +872,43 @@
 //! | `123` | no | Expression is interpreted as `Value::Int` |
 //! | `true` | no | Expression is interpreted as `Value::Bool` |
 //! | `.34` | no | Expression is interpreted as `Value::Float` |
+//! | `a.b` | yes | Expression is interpreted as a dot path, see below |
 //!
 //! Variables have a precedence of 200.
 //!
+//! A variable containing dots that is not found in the context is resolved as a path through nested maps.
+//! For example, `user.address.city` reads the field `city` of the map in the field `address` of the map `user`.
+//! Identifiers containing dots that are stored in the context directly, like `"user.address"`, are preferred over this.
+//! Assigning to a dot path creates a variable with that identifier, and does not change the nested map.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let mut user = MapType::new();
+//! user.insert("name".into(), "Ada".into());
+//! let context = context_map! { "user" => Value::from(user) }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context("user.name", &context), Ok("Ada".into()));
+//! ```
+//!
 //! ### User-Defined Functions
 //!
 //! This crate allows to define arbitrary functions to be used in parsed expressions.
 //! A function is defined as a `Function` instance, wrapping an `fn(&Value) -> EvalexprResult<Value>`.
 //! The definition needs to be included in the [`Context`](#contexts) that is used for evaluation.
-//! As of now, functions cannot be defined within the expression, but that might change in the future.
+//! Functions can also be defined within the expression as [lambdas](#lambdas).
 //!
 //! The function gets passed what ever value is directly behind it, be it a tuple or a single values.
 //! If there is no value behind a function, it is interpreted as a variable instead.
 //! More specifically, a function needs to be followed by either an opening brace `(`, another literal, or a value.
 //! While not including special support for multi-valued functions, they can be realized by requiring a single tuple argument.
 //!
+//! The amount of arguments a function accepts can be restricted with `Function::with_argument_range` and `Function::with_argument_counts`.
+//! Calls with a different amount of arguments then fail before the function is invoked.
+//!
 //! Be aware that functions need to verify the types of values that are passed to them.
 //! The `error` module contains some shortcuts for verification, and error types for passing a wrong value type.
+//! Alternatively, functions created with `Function::typed` declare the types of their arguments, which are then verified before the function is invoked.
+//! Application errors of a function can be returned with `EvalexprError::function_error`, which keeps the original error as the source of the resulting `EvalexprError::FunctionError`.
 //! Also, most numeric functions need to distinguish between being called with integers or floating point numbers, and act accordingly.
 //!
 //! Here are some examples and counter-examples on expressions that are interpreted as function calls:
@@ -501,6 +925,601 @@
 //!
 //! Functions have a precedence of 190.
 //!
+//! Functions created with `Function::new_with_context` additionally get read access to the context that contains them.
+//! This allows e.g. to look up variables by a name that is computed in the expression:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! {
+//!     "price_apple" => 3,
+//!     "price" => Function::new_with_context(|context, argument| {
+//!         let identifier = format!("price_{}", argument.as_string()?);
+//!         context.get_value(&identifier).cloned().ok_or(EvalexprError::variable_identifier_not_found(identifier))
+//!     }),
+//! }.unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context("price(\"apple\") * 2", &context), Ok(Value::from(6)));
+//! ```
+//!
+//! ### Lambdas
+//!
+//! Functions can be defined within an expression as lambdas like `|x, y| x * y`, which evaluate to a `Value::Lambda`.
+//! A variable that contains a lambda can be called like a function, so lambdas can be used to name recurring computations, for example in rule engines.
+//! Lambdas take precedence over builtin functions with the same identifier, but functions of the context take precedence over lambdas.
+//! A lambda without parameters is written as `|| body`.
+//!
+//! A lambda needs to be called with as many arguments as it has parameters.
+//! Only identifiers can be called, so calling a lambda expression directly like `(|x| x + 1)(2)` is not supported.
+//!
+//! The body of a lambda extends up to the next `,` or `;` that is not enclosed in parentheses.
+//! It can read variables of the context, but cannot assign to them.
+//! Variables are looked up when the lambda is called and not when it is defined, so `a = 1; f = |x| x + a; a = 2; f(0)` evaluates to `2`.
+//! Lambdas can be passed to other lambdas, capture the arguments of the lambdas they are defined in, and call themselves recursively
+//! up to a depth of `Lambda::MAX_CALL_DEPTH` nested calls.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let mut context = HashMapContext::new();
+//! assert_eq!(eval_with_context_mut("double = |x| x * 2; double(21)", &mut context), Ok(Value::from(42)));
+//! assert_eq!(eval_with_context_mut("twice = |f, x| f(f(x)); twice(double, 3)", &mut context), Ok(Value::from(12)));
+//! assert_eq!(
+//!     eval_with_context_mut("fib = |n| if(n < 2, n, fib(n - 1) + fib(n - 2)); fib(10)", &mut context),
+//!     Ok(Value::from(55))
+//! );
+//! ```
+//!
+//! The builtin functions `map`, `filter`, `reduce`, `any` and `all` call a lambda with each element of a tuple or array.
+//! `map` and `filter` return a collection of the same kind, and `any` and `all` stop at the first element that decides their result.
+//! Elements that are tuples are passed to the lambda as multiple arguments, just like a tuple argument of a function call.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! assert_eq!(eval("map((1, 2, 3), |x| x * 2)"), Ok(Value::from(vec![2.into(), 4.into(), 6.into()])));
+//! assert_eq!(eval("filter([1, 2, 3, 4], |x| x % 2 == 0)"), Ok(Value::Array(vec![2.into(), 4.into()])));
+//! assert_eq!(eval("reduce((1, 2, 3), |sum, x| sum + x, 0)"), Ok(Value::from(6)));
+//! assert_eq!(eval("all((\"a\", \"ab\"), |s| str::starts_with(s, \"a\"))"), Ok(Value::from(true)));
+//! assert_eq!(eval("any(((1, 2), (3, 1)), |a, b| a > b)"), Ok(Value::from(true)));
+//! ```
+//!
+//! ### Nesting Depth
+//!
+//! Parsing and evaluating expressions works recursively, so very deeply nested expressions could overflow the stack.
+//! To be safe to use with untrusted input, the parser rejects expressions that are nested deeper than `ParserConfig::DEFAULT_MAX_DEPTH` levels with an `EvalexprError::RecursionLimitExceeded`.
+//! Parentheses, brackets, function calls and unary operators are nesting, while chains of left-associative operators like `1 + 2 + 3` are not limited by this.
+//! The depth of the whole operator tree can be limited with an `EvaluationPolicy`.
+//! A different limit can be set with a custom `ParserConfig`:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let deeply_nested = format!("{}1{}", "(".repeat(1000), ")".repeat(1000));
+//! assert_eq!(
+//!     eval(&deeply_nested),
+//!     Err(EvalexprError::RecursionLimitExceeded { max_depth: ParserConfig::DEFAULT_MAX_DEPTH })
+//! );
+//!
+//! let config = ParserConfig::new().with_max_depth(3);
+//! assert!(build_operator_tree_with_config("1 + 2", &config).is_ok());
+//! assert!(build_operator_tree_with_config("1 + 2 * 3", &config).is_err());
+//! ```
+//!
+//! ### Evaluation Options
+//!
+//! Evaluation can be customised with `EvalOptions` that are passed to `eval_with_context_and_options` and related functions.
+//! For example, to bound the time spent on evaluating untrusted expressions, the amount of evaluated nodes of the operator tree can be limited.
+//! Exceeding the limit aborts the evaluation with an `EvalexprError::BudgetExceeded`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let options = EvalOptions::new().with_max_node_evaluations(Some(100));
+//! let mut context = HashMapContext::new();
+//! assert_eq!(eval_with_context_mut_and_options("a = 2; a * a", &mut context, &options), Ok(Value::from(4)));
+//!
+//! let long_sum = format!("0{}", " + 1".repeat(100));
+//! assert_eq!(
+//!     eval_with_context_mut_and_options(&long_sum, &mut context, &options),
+//!     Err(EvalexprError::BudgetExceeded { max_node_evaluations: 100 })
+//! );
+//! ```
+//!
+//! Dividing an integer or decimal number by zero with `/` or `%` fails with `EvalexprError::DivisionByZero` by default.
+//! For a more tolerant evaluation, as in spreadsheets, the options can make such divisions result in `Value::Empty`,
+//! or perform them with floats, resulting in infinity or `NaN`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = HashMapContext::new();
+//! let options = EvalOptions::new().with_division_by_zero(DivisionByZeroMode::Empty);
+//! assert_eq!(eval_with_context_and_options("(4 / 2, 4 / 0)", &context, &options), Ok(Value::from(vec![Value::from(2), Value::Empty])));
+//! assert_eq!(eval("4 / 0"), Err(EvalexprError::DivisionByZero { dividend: Value::from(4) }));
+//! ```
+//!
+//! Integers are converted into floats implicitly when they are combined with floats, like in `1 + 2.5`.
+//! With strict number types, arithmetic operators and ordering comparisons instead fail with `EvalexprError::WrongTypeCombination`
+//! if their operands are numbers of different types, and numbers have to be converted explicitly with `int` or `float`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = HashMapContext::new();
+//! let options = EvalOptions::new().with_strict_number_types(true);
+//! assert!(eval_with_context_and_options("1 + 2.5", &context, &options).is_err());
+//! assert_eq!(eval_with_context_and_options("float(1) + 2.5", &context, &options), Ok(Value::from(3.5)));
+//! ```
+//!
+//! With integer exponentiation, raising an integer to a non-negative integer power results in an integer,
+//! so that the results of expressions like `2 ^ 10` can be read with `as_int`.
+//! Results that do not fit into an `IntType` fail with `EvalexprError::ExponentiationError` then.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = HashMapContext::new();
+//! let options = EvalOptions::new().with_integer_exponentiation(true);
+//! assert_eq!(eval_with_context_and_options("2 ^ 10", &context, &options), Ok(Value::from(1024)));
+//! assert_eq!(eval_with_context_and_options("2 ^ -1", &context, &options), Ok(Value::from(0.5)));
+//! assert_eq!(eval("2 ^ 10"), Ok(Value::from(1024.0)));
+//! ```
+//!
+//! To evaluate expressions on partially filled data, lenient mode makes variables that do not exist evaluate to `Value::Empty`,
+//! and ordering comparisons with an empty operand false, instead of failing.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "age" => 42 }.unwrap(); // Do proper error handling here
+//! let options = EvalOptions::new().with_lenient(true);
+//! assert_eq!(eval_with_context_and_options("age > 30 && income > 1000", &context, &options), Ok(Value::from(false)));
+//! assert_eq!(
+//!     eval_with_context_and_options("if(age > 30, 1, 0) + if(income > 1000, 1, 0)", &context, &options),
+//!     Ok(Value::from(1))
+//! );
+//! assert!(eval_with_context("income > 1000", &context).is_err());
+//! ```
+//!
+//! To accept untrusted expressions safely, an `EvaluationPolicy` can forbid operators and functions,
+//! and limit the length of tuples and strings as well as the depth of the operator tree.
+//! The policy is enforced at parse time if it is set in the `ParserConfig`, and at evaluation time if it is set in the `EvalOptions`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let policy = EvaluationPolicy::new().with_forbidden_operator(Operator::Assign).with_max_tuple_length(Some(3));
+//! let config = ParserConfig::new().with_policy(Some(policy.clone()));
+//! assert_eq!(
+//!     build_operator_tree_with_config("a = 1", &config),
+//!     Err(EvalexprError::ForbiddenOperator { operator: Operator::Assign })
+//! );
+//!
+//! let options = EvalOptions::new().with_policy(Some(policy));
+//! let context = context_map! { "t" => Value::from((1..=4).map(Value::from).collect::<Vec<_>>()) }.unwrap(); // Do proper error handling here
+//! assert_eq!(
+//!     eval_with_context_and_options("len(t)", &context, &options),
+//!     Err(EvalexprError::TupleLengthLimitExceeded { max_tuple_length: 3, actual: 4 })
+//! );
+//! ```
+//!
+//! With the `rand` feature, the random builtin functions can be made deterministic by setting a seed with `EvalOptions::with_random_seed`.
+//! Each evaluation then starts with a random number generator seeded with it, so evaluating the same expression yields the same values.
+//!
+//! ### Tracing evaluations
+//!
+//! An `EvalObserver` passed to `eval_with_context_and_observer` is notified when the evaluation enters and leaves each node,
+//! together with the values of the operands and the result of each node.
+//! This records an audit trail of an evaluation, for example to explain why a rule of a rule engine fired.
+//! See the documentation of `EvalObserver` for an example.
+//!
+//! For end users, `Node::eval_with_explanation` returns the result together with an `Explanation` of the evaluation,
+//! which displays the values of the variables and subexpressions in a single line:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "price" => 12.0, "qty" => 3 }.unwrap(); // Do proper error handling here
+//! let tree = build_operator_tree("price * qty > 30").unwrap(); // Do proper error handling here
+//! let (result, explanation) = tree.eval_with_explanation(&context);
+//! assert_eq!(result, Ok(Value::from(true)));
+//! assert_eq!(explanation.to_string(), "price (12) * qty (3) = 36 > 30 → true");
+//! ```
+//!
+//! A `Profiler` is an observer that records how often each node was evaluated and how long its evaluations took,
+//! summed over any number of evaluations, to find the expensive parts of large expressions.
+//! See the documentation of `Profiler` for an example.
+//!
+//! ### Rendering templates
+//!
+//! `eval_template` renders a text with embedded expressions, like a notification or a label that is configured by users.
+//! Each expression in braces is evaluated with the given context and replaced by its value, where strings are inserted without quotes.
+//! `{{` and `}}` write a literal brace, and `eval_template_with_delimiters` renders templates with other delimiters.
+//! If an expression fails, the error is an `EvalexprError::TemplateExpressionFailed` that holds the position of the expression in the template,
+//! and the positions in the error of the expression are relative to the template as well.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! { "name" => "Bob", "price" => 2.5, "qty" => 4 }.unwrap(); // Do proper error handling here
+//! assert_eq!(
+//!     eval_template("Hello {name}, you owe {price * qty} EUR", &context),
+//!     Ok("Hello Bob, you owe 10 EUR".to_string())
+//! );
+//! assert_eq!(
+//!     eval_template_with_delimiters("Total: <%= str::to_uppercase(\"eur\") %>", "<%=", "%>", &context),
+//!     Ok("Total: EUR".to_string())
+//! );
+//!
+//! match eval_template("Hello {name}, you owe {price * (qty} EUR", &context) {
+//!     Err(EvalexprError::TemplateExpressionFailed { span, .. }) => assert_eq!(span, Span::new(23, 35)),
+//!     _ => unreachable!(),
+//! }
+//! ```
+//!
+//! ### Building expressions
+//!
+//! Operator trees can also be constructed directly, without formatting an expression string and parsing it.
+//! `Node::constant`, `Node::variable` and `Node::call` create values, variable reads and function calls,
+//! and functions like `Node::add`, `Node::and` or `Node::index` combine them with operators.
+//! Any other operator can be applied with `Node::operation`, which checks that the operator receives the right amount of operands.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let tree = Node::add(
+//!     Node::variable("x"),
+//!     Node::call("max", vec![Node::constant(1), Node::constant(2)]),
+//! );
+//! assert_eq!(tree.to_string(), "x + max(1, 2)");
+//!
+//! let comparison = Node::operation(Operator::Geq, vec![tree, Node::constant(5)]).unwrap(); // Do proper error handling here
+//! let context = context_map! { "x" => 3 }.unwrap(); // Do proper error handling here
+//! assert_eq!(comparison.eval_with_context(&context), Ok(Value::from(true)));
+//! assert_eq!(
+//!     Node::operation(Operator::Not, vec![]),
+//!     Err(EvalexprError::WrongOperatorArgumentAmount { expected: 1, actual: 0 })
+//! );
+//! ```
+//!
+//! ### Walking expressions
+//!
+//! The operator and children of each node are available with `Node::operator` and `Node::children`.
+//! To analyze a whole expression, for example to reject certain operators or to estimate the cost of evaluating it,
+//! `Node::walk` visits all nodes of the tree with a `Visitor`.
+//! A visitor is notified when entering and leaving each node, and can skip the children of a node.
+//! Closures that take a node and return if its children should be visited are visitors as well.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let tree = build_operator_tree("sum(a, b) * 2 + str::len(s)").unwrap(); // Do proper error handling here
+//! let mut functions = Vec::new();
+//! tree.walk(&mut |node: &Node| {
+//!     if let Operator::FunctionIdentifier { identifier } = node.operator() {
+//!         functions.push(identifier.to_string());
+//!     }
+//!     true
+//! });
+//! assert_eq!(functions, ["sum", "str::len"]);
+//! ```
+//!
+//! Nodes parsed from a string remember where they came from.
+//! `Node::span` returns the part of the expression string covered by the subexpression of a node, and `Node::source_of` called on the root of the tree returns its text,
+//! for example to point at the part of a formula that failed to evaluate.
+//! Evaluating a parsed tree reports a variable that does not exist with the span of the node reading it in `EvalexprError::VariableIdentifierNotFound`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let tree = build_operator_tree("1 + 4 / (x - x)").unwrap(); // Do proper error handling here
+//! let division = &tree.children()[0].children()[1];
+//! assert_eq!(division.span(), Some(Span::new(4, 15)));
+//! assert_eq!(tree.source_of(&division.children()[1]), Some("(x - x)"));
+//! ```
+//!
+//! ### Highlighting expressions
+//!
+//! `tokenize` splits an expression into the tokens the parser reads, each with its kind and the part of the expression string it covers.
+//! This allows an editor to highlight formulas consistently with how they are evaluated.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let expression = "price * 2 // twice";
+//! let tokens = tokenize(expression).unwrap(); // Do proper error handling here
+//! let highlighted: Vec<_> = tokens
+//!     .iter()
+//!     .map(|token| (token.kind, &expression[token.span.start..token.span.end]))
+//!     .collect();
+//! assert_eq!(highlighted, [
+//!     (TokenKind::Identifier, "price"),
+//!     (TokenKind::Operator, "*"),
+//!     (TokenKind::Number, "2"),
+//! ]);
+//! ```
+//!
+//! ### Recovering from parse errors
+//!
+//! `build_operator_tree_with_recovery` does not stop at the first syntax error, but skips past it and reports all errors of an expression together with their positions.
+//! This allows an editor to underline every problem of a formula while the user is typing it.
+//! The returned operator tree contains the parts of the expression that could be parsed, and should not be evaluated if there were errors.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let (_, errors) = build_operator_tree_with_recovery("1 2 + (3", &ParserConfig::new());
+//! let positions: Vec<_> = errors.iter().map(|(_, span)| (span.start, span.end)).collect();
+//! assert_eq!(positions, [(2, 3), (6, 7)]);
+//! ```
+//!
+//! ### Completing expressions
+//!
+//! `complete` returns the candidates to complete an expression with at a cursor position, for example to build autocompletion for a formula editor.
+//! Depending on whether the parser expects a value or an operator at the cursor, the candidates are the variables, functions and keywords,
+//! or the operators and closing brackets that are valid there.
+//! Only candidates starting with the identifier typed before the cursor are returned.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let context = context_map! {
+//!     "price" => 12,
+//!     "pieces" => 3,
+//! }
+//! .unwrap(); // Do proper error handling here
+//!
+//! let completions = complete("pri", 3, &context);
+//! assert_eq!(completions[0].text, "price");
+//! assert_eq!(completions[0].kind, CompletionKind::Variable);
+//! assert_eq!(completions[0].span, Span::new(0, 3));
+//!
+//! let completions = complete("(price + 1", 10, &context);
+//! assert!(completions.iter().any(|completion| completion.text == ")"));
+//! ```
+//!
+//! ### Validating expressions
+//!
+//! `Node::validate` checks that an expression only uses the given variables and functions, besides the builtin functions and the variables it defines itself.
+//! It returns an error for each unknown identifier at once, so that an editor can show all problems of a formula instead of failing at the first unknown variable during evaluation.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let tree = build_operator_tree("wieght * 2 + hieght + bmi(wieght)").unwrap(); // Do proper error handling here
+//! let unknown: Vec<String> = tree
+//!     .validate(&["weight", "height"], &["bmi"])
+//!     .unwrap_err()
+//!     .iter()
+//!     .map(ToString::to_string)
+//!     .collect();
+//! assert_eq!(unknown, [
+//!     "Variable identifier is not bound to anything by context: \"wieght\" at position 0. Did you mean \"weight\"?",
+//!     "Variable identifier is not bound to anything by context: \"hieght\" at position 13. Did you mean \"height\"?",
+//! ]);
+//! ```
+//!
+//! ### Inferring types
+//!
+//! `Node::infer_return_type` determines the type of the value an expression evaluates to without evaluating it,
+//! for example to check that a formula entered by a user yields a boolean before saving it.
+//! The types of variables and the return types of user-defined functions are declared with `TypeHints`, or taken from the current values of a context with `TypeHints::from_context`.
+//! If the type depends on values that are not known before evaluation, or if an operator does not accept the types of its arguments, `None` is returned.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let hints = TypeHints::new()
+//!     .with_variable_type("temperature", ValueType::Float)
+//!     .with_variable_type("unit", ValueType::String);
+//! let infer = |formula| build_operator_tree(formula).unwrap().infer_return_type(&hints); // Do proper error handling here
+//!
+//! assert_eq!(infer("temperature > 30 && unit == \"C\""), Some(ValueType::Boolean));
+//! assert_eq!(infer("round(temperature * 1.8 + 32)"), Some(ValueType::Float));
+//! assert_eq!(infer("unit * 2"), None);
+//! ```
+//!
+//! ### Displaying expressions
+//!
+//! The `Display` implementation of `Node` writes the parsed expression back as a string.
+//! The string is a canonical form of the expression, so it may differ from the original string in whitespace, parentheses and the notation of function calls.
+//! But parsing it again results in an expression that evaluates the same way.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let tree = build_operator_tree("(1+2)*  max(3, ((4)))").unwrap();
+//! assert_eq!(tree.to_string(), "(1 + 2) * max(3, 4)");
+//! let reparsed = build_operator_tree(&tree.to_string()).unwrap();
+//! assert_eq!(reparsed.eval(), tree.eval());
+//! ```
+//!
+//! To see how an expression was parsed, `Node::to_dot` returns the operator tree as a graph in the DOT language of [Graphviz](https://graphviz.org/).
+//!
+//! ### Simplifying expressions
+//!
+//! An expression that is evaluated many times can be simplified beforehand with `Node::simplify`.
+//! This evaluates all subexpressions that only consist of constants, and replaces variables by their values in the given context.
+//! Function calls and variables that are assigned to in the expression are kept as they are.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let constants = context_map! { "rate" => 0.5 }.unwrap(); // Do proper error handling here
+//! let tree = build_operator_tree("price * (1 + rate * 2)").unwrap(); // Do proper error handling here
+//! let simplified = tree.simplify(&constants);
+//! assert_eq!(simplified.to_string(), "price * 2.0");
+//! ```
+//!
+//! Single variables can be replaced by a value with `Node::substitute_variable`, and renamed with `Node::rename_variable`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let tree = build_operator_tree("price * (1 + rate)").unwrap(); // Do proper error handling here
+//! let pinned = tree.substitute_variable("rate", Value::from(0.5)).rename_variable("price", "cost");
+//! assert_eq!(pinned.to_string(), "cost * (1 + 0.5)");
+//! ```
+//!
+//! ### Compiling expressions
+//!
+//! For even faster repeated evaluation, an operator tree can be compiled into a `CompiledExpression` with `Node::compile`.
+//! A compiled expression is a flat sequence of instructions that is evaluated with a stack instead of walking the tree,
+//! and it evaluates to the same results as the tree it was compiled from.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let compiled = build_operator_tree("x * x + 1").unwrap().compile(); // Do proper error handling here
+//! let mut context = HashMapContext::new();
+//! for x in 0..10 {
+//!     context.set_value("x".into(), x.into()).unwrap(); // Do proper error handling here
+//!     assert_eq!(compiled.eval_with_context(&context), Ok(Value::from(x * x + 1)));
+//! }
+//! ```
+//!
+//! To evaluate an expression for many rows of data at once, variables can be bound to columns of values with `CompiledExpression::eval_columns`.
+//! The expression is then evaluated once per row, without setting the variables in a context for each row:
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let xs = [1, 2, 3];
+//! let ys = [0.5, 1.5, 2.5];
+//! let compiled = build_operator_tree("x * y").unwrap().compile(); // Do proper error handling here
+//! assert_eq!(
+//!     compiled.eval_columns(&[("x", Column::Int(&xs)), ("y", Column::Float(&ys))], &EmptyContext),
+//!     Ok(vec![Value::from(0.5), Value::from(3.0), Value::from(7.5)])
+//! );
+//! ```
+//!
+//! ### Caching subexpressions
+//!
+//! When a large expression is evaluated repeatedly and only a few of its variables change between evaluations,
+//! `Node::eval_cached` can skip the subexpressions that do not depend on the changed variables.
+//! Their values are looked up in an `EvalCache`, which is filled by previous evaluations.
+//! Subexpressions that call impure builtin functions like `now` or `random`, lambdas, or functions of the context are evaluated every time,
+//! unless the functions of the context are marked as pure with `Function::pure`.
+//!
+//! ```rust
+//! use evalexpr::*;
+//!
+//! let tree = build_operator_tree("math::sqrt(a * a + b * b) + c").unwrap(); // Do proper error handling here
+//! let mut context = context_map! { "a" => 3.0, "b" => 4.0, "c" => 0.0 }.unwrap(); // Do proper error handling here
+//! let mut cache = EvalCache::new();
+//! for c in 0..3 {
+//!     context.set_value("c".into(), Value::from(c as f64)).unwrap(); // Do proper error handling here
+//!     assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::from(5.0 + c as f64)));
+//! }
+//! ```
+//!
+//! An `IncrementalEvaluator` combines an expression with its context and a cache.
+//! It keeps the result of the expression until a variable that the expression depends on is changed with `IncrementalEvaluator::set_value`, unless the expression calls impure functions.
+//!
+//! ### Big Integers
+//!
+//! By default, integer arithmetic that overflows `IntType` results in an error.
+//! With the `bigint` feature, such integers are represented as `Value::BigInt` instead, which can hold integers of arbitrary size.
+//! The same applies to integer literals that do not fit into `IntType`.
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = {version = "12", features = ["bigint"]}
+//! ```
+//!
+//! Integers are only represented as `Value::BigInt` if they do not fit into `IntType`, so results that fit are `Value::Int`s again.
+//! The arithmetic and comparison operators as well as the builtin functions `min`, `max`, `floor`, `round`, `ceil` and `math::abs` support big integers.
+//! Operators and functions that work with floats convert big integers into the nearest float.
+//!
+//! ```rust
+//! # #[cfg(feature = "bigint")] {
+//! use evalexpr::*;
+//!
+//! assert_eq!(
+//!     eval("9223372036854775807 + 1"),
+//!     Ok(Value::BigInt("9223372036854775808".parse().unwrap()))
+//! );
+//! assert_eq!(eval("9223372036854775808 - 1"), Ok(Value::Int(9223372036854775807)));
+//! # }
+//! ```
+//!
+//! ### Decimal Numbers
+//!
+//! Floats cannot represent most decimal fractions exactly, so for example `0.1 + 0.2 == 0.3` is false.
+//! For calculations that need exact decimal fractions, like prices, the `decimal` feature adds `Value::Decimal`.
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = {version = "12", features = ["decimal"]}
+//! ```
+//!
+//! Decimals can be passed in through the context, and `ParserConfig::with_decimal_literals` makes the parser read literals like `0.1` as decimals instead of floats.
+//! Arithmetic with decimals and integers results in decimals, while arithmetic with decimals and floats results in floats.
+//! The builtin functions `min`, `max`, `floor`, `round`, `ceil` and `math::abs` keep decimals as decimals, and the other math functions convert them into floats.
+//!
+//! ```rust
+//! # #[cfg(feature = "decimal")] {
+//! use evalexpr::*;
+//!
+//! let config = ParserConfig::new().with_decimal_literals(true);
+//! let tree = build_operator_tree_with_config("0.1 + 0.2", &config).unwrap();
+//! assert_eq!(tree.eval(), Ok(Value::Decimal("0.3".parse().unwrap())));
+//! # }
+//! ```
+//!
+//! ### Dates and Times
+//!
+//! The `chrono` feature adds `Value::DateTime` for points in time in UTC and `Value::Duration` for spans of time, based on the [chrono](https://docs.rs/chrono) crate.
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = {version = "12", features = ["chrono"]}
+//! ```
+//!
+//! Dates and durations can be passed in through the context, or created with the builtin functions listed above, like `timestamp` and `duration`.
+//! Subtracting two dates results in a duration, and durations can be added to and subtracted from dates and from each other.
+//! Durations can be multiplied with and divided by integers, and dividing two durations results in a float.
+//! Dates can be compared with dates and durations with durations.
+//! Dates are displayed in RFC 3339 format like `2024-01-01T12:00:00Z`, and durations with units like `1h 30m`.
+//!
+//! `datetime::parse` and `datetime::format` take formats like `%Y-%m-%d %H:%M` as described in the documentation of [`chrono::format::strftime`](https://docs.rs/chrono/latest/chrono/format/strftime/index.html).
+//! Parsed dates without a timezone are in UTC, and dates without a time are at midnight.
+//!
+//! ```rust
+//! # #[cfg(feature = "chrono")] {
+//! use evalexpr::*;
+//!
+//! let context = context_map! {
+//!     "created_at" => Value::DateTime("2024-01-01T08:00:00Z".parse().unwrap()),
+//! }.unwrap(); // Do proper error handling here
+//!
+//! assert_eq!(
+//!     eval_with_context(r#"now() - created_at > duration("2h")"#, &context),
+//!     Ok(Value::from(true))
+//! );
+//! assert_eq!(
+//!     eval_string_with_context(r#"datetime::format(created_at + duration("1h 30m"), "%H:%M")"#, &context),
+//!     Ok("09:30".into())
+//! );
+//! assert_eq!(
+//!     eval_with_context(r#"str(datetime::parse("2024-01-02", "%Y-%m-%d") - created_at)"#, &context),
+//!     Ok(Value::from("16h"))
+//! );
+//! # }
+//! ```
+//!
+//! ### `no_std`
+//!
+//! The crate can be used without the standard library, as long as an allocator is available.
+//! To do so, disable the default `std` feature and enable the `libm` feature, which provides the floating point math functions:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = {version = "12", default-features = false, features = ["libm"]}
+//! ```
+//!
+//! Without the `std` feature, `HashMapContext` stores its variables and functions in `BTreeMap`s, `EvalexprError` does not implement `std::error::Error` and has no variant `FunctionError`, and the `regex_support` feature is not available.
+//!
 //! ### [Serde](https://serde.rs)
 //!
 //! To use this crate with serde, the `serde_support` feature flag has to be set.
@@ -537,8 +1556,132 @@
 //!
 //! With `serde`, expressions can be integrated into arbitrarily complex data.
 //!
-//! The crate also implements `Serialize` and `Deserialize` for the `HashMapContext`,
-//! but note that only the variables get (de)serialized, not the functions.
+//! `Node` also implements `serde::ser::Serialize`, which writes the expression string produced by its `Display` implementation.
+//!
+//! Alternatively, the module `structured` serializes operator trees as nested structures of operators and their children.
+//! This format represents the operator tree exactly, including constants without a literal like maps, and can be used with `#[serde(with = "evalexpr::structured")]`.
+//!
+//! With the `bincode` feature, `Node::to_binary` encodes an operator tree in this format with [bincode](https://docs.rs/bincode),
+//! behind a header with the version of the encoding, and `Node::from_binary` decodes it without parsing the expression again.
+//! This allows to cache many parsed expressions on disk and to load them faster than parsing them.
+//!
+//! The crate also implements `Serialize` and `Deserialize` for `Value` and the `HashMapContext`,
+//! so the variables of an evaluation can be stored and loaded together with its expressions.
+//! Note that only the variables and the setting to disable builtin functions of a `HashMapContext` get (de)serialized, not the functions.
+//! Fields missing when deserializing a `HashMapContext` are set to their default values.
+//!
+//! ### JSON
+//!
+//! With the `serde_json` feature, values can be converted from and into `serde_json::Value`s.
+//! JSON arrays become `Value::Array`s, objects become `Value::Map`s and `null` becomes `Value::Empty`.
+//! `HashMapContext::from_json` creates a context with a variable for each field of a JSON object.
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = {version = "12", features = ["serde_json"]}
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_json")] {
+//! use evalexpr::*;
+//! use std::convert::TryFrom;
+//!
+//! let payload = serde_json::json!({ "order": { "items": [2, 3] }, "discount": 0.5 });
+//! let context = HashMapContext::from_json(payload).unwrap(); // Do proper error handling here
+//! assert_eq!(eval_with_context("sum(order.items) * discount", &context), Ok(Value::from(2.5)));
+//!
+//! let result = eval("(1, \"a\", ())").unwrap(); // Do proper error handling here
+//! assert_eq!(serde_json::Value::try_from(result), Ok(serde_json::json!([1, "a", null])));
+//! # }
+//! ```
+//!
+//! ### WebAssembly
+//!
+//! With the `wasm` feature, the module `wasm` provides bindings to evaluate expressions from JavaScript with [wasm-bindgen](https://docs.rs/wasm-bindgen),
+//! so web frontends can validate and preview expressions with the same semantics as a Rust backend.
+//! The function `evaluate` evaluates an expression with the variables of a JSON object,
+//! and `compile` parses an expression once into a handle that can be evaluated many times and lists the variables the expression reads.
+//!
+//! ### Deriving Contexts
+//!
+//! With the `derive` feature, the derive macro `Context` implements the `Context` trait for structs with named fields,
+//! so that their fields can be read as variables.
+//! The fields are converted into values with `Into<Value>` when they are read.
+//! Fields can be renamed with `#[evalexpr(rename = "identifier")]` and hidden with `#[evalexpr(skip)]`.
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = {version = "12", features = ["derive"]}
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "derive")] {
+//! use evalexpr::*;
+//!
+//! #[derive(Context)]
+//! struct Inputs {
+//!     price: f64,
+//!     #[evalexpr(rename = "qty")]
+//!     quantity: i64,
+//!     #[evalexpr(skip)]
+//!     _secret: String,
+//! }
+//!
+//! let inputs = Inputs { price: 2.5, quantity: 4, _secret: "hidden".into() };
+//! assert_eq!(eval_with_context("price * qty", &inputs), Ok(Value::from(10.0)));
+//! assert!(eval_with_context("_secret", &inputs).is_err());
+//! # }
+//! ```
+//!
+//! ### Compile-Time Evaluation
+//!
+//! The companion crate `evalexpr_const` evaluates static expressions at compile time.
+//! Its macro `eval_const!` expands to the value of a constant expression as a Rust literal,
+//! and `operator_tree!` expands to code that builds the operator tree of an expression with its constant subexpressions already evaluated.
+//! As the macros use this crate to evaluate expressions, they cannot be re-exported by it.
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = "12"
+//! evalexpr_const = "12"
+//! ```
+//!
+//! ```rust
+//! use evalexpr::*;
+//! use evalexpr_const::{eval_const, operator_tree};
+//!
+//! const SECONDS_PER_DAY: i64 = eval_const!("24 * 60 * 60");
+//! assert_eq!(SECONDS_PER_DAY, 86400);
+//!
+//! let tree = operator_tree!("x * (60 * 60)");
+//! assert_eq!(tree.to_string(), "x * 3600");
+//! ```
+//!
+//! ### Async Functions
+//!
+//! With the `async` feature, expressions can call async functions, like functions that query a database or an HTTP service.
+//! An `AsyncFunction` wraps a closure that returns a future, and is stored in a context that implements `AsyncContext`, like the `HashMapContext`.
+//! Async functions are called when evaluating with `Node::eval_with_context_async`, which returns a future that can be awaited with any async runtime.
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = {version = "12", features = ["async"]}
+//! ```
+//!
+//! ```rust
+//! # #[cfg(feature = "async")] {
+//! use evalexpr::*;
+//!
+//! let mut context = HashMapContext::new();
+//! context.set_async_function("fetch_stock".into(), AsyncFunction::new(|argument| async move {
+//!     // Query a database here
+//!     Ok(Value::from(argument.as_string()?.len() as IntType))
+//! })).unwrap(); // Do proper error handling here
+//!
+//! let tree = build_operator_tree("fetch_stock(\"apple\") > 3").unwrap(); // Do proper error handling here
+//! let future = tree.eval_with_context_async(&context); // Await this future with an async runtime
+//! # }
+//! ```
 //!
 //! ## License
 //!
@@ -548,6 +1691,14 @@
 
 #![deny(missing_docs)]
 #![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("Either the `std` or the `libm` feature is required for floating point math.");
+
+extern crate alloc;
+#[cfg(any(feature = "libm", feature = "bigint"))]
+extern crate num_traits;
 
 #[cfg(feature = "regex_support")]
 extern crate regex;
@@ -558,30 +1709,78 @@ extern crate serde;
 #[cfg(feature = "serde_support")]
 #[macro_use]
 extern crate serde_derive;
+#[cfg(feature = "derive")]
+extern crate evalexpr_derive;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
 
+#[cfg(feature = "std")]
+pub use crate::context::{EnvContext, LazyContext};
+#[cfg(feature = "std")]
+pub use crate::error::FunctionErrorSource;
+#[cfg(feature = "serde_support")]
+pub use crate::feature_serde::structured;
+#[cfg(feature = "std")]
+pub use crate::tree::{NodeProfile, Profiler};
+#[cfg(feature = "bigint")]
+pub use crate::value::BigIntType;
+#[cfg(feature = "decimal")]
+pub use crate::value::DecimalType;
+#[cfg(feature = "chrono")]
+pub use crate::value::{DateTimeType, DurationType};
+#[cfg(feature = "async")]
+pub use crate::{
+    context::AsyncContext,
+    function::{AsyncFunction, AsyncFunctionFuture},
+};
 pub use crate::{
     context::{
-        Context, ContextWithMutableFunctions, ContextWithMutableVariables, EmptyContext,
-        EmptyContextWithBuiltinFunctions, HashMapContext, IterateVariablesContext,
+        CallbackContext, ChainedContext, Context, ContextSnapshot, ContextWithMutableFunctions,
+        ContextWithMutableVariables, EmptyContext, EmptyContextWithBuiltinFunctions,
+        HashMapContext, IterateVariablesContext, SyncHashMapContext,
     },
     error::{EvalexprError, EvalexprResult},
     function::Function,
     interface::*,
+    interpreter::Interpreter,
     operator::Operator,
-    token::PartialToken,
-    tree::Node,
-    value::{value_type::ValueType, EmptyType, FloatType, IntType, TupleType, Value, EMPTY_VALUE},
+    template::{eval_template, eval_template_with_delimiters},
+    token::{PartialToken, Span, SpannedToken, TokenKind},
+    tree::{
+        Column, CompiledExpression, Completion, CompletionKind, DivisionByZeroMode, EvalCache,
+        EvalObserver, EvalOptions, EvaluationPolicy, Explanation, IncrementalEvaluator, Lambda,
+        Node, ParserConfig, TypeHints, Visitor,
+    },
+    value::{
+        value_type::ValueType, ArrayType, EmptyType, FloatType, IntType, MapType, TupleType, Value,
+        EMPTY_VALUE,
+    },
 };
+#[cfg(feature = "derive")]
+pub use evalexpr_derive::Context;
 
 mod context;
 pub mod error;
 #[cfg(feature = "serde_support")]
 mod feature_serde;
+#[cfg(feature = "serde_json")]
+mod feature_serde_json;
 mod function;
 mod interface;
+mod interpreter;
 mod operator;
+mod template;
 mod token;
 mod tree;
 mod value;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Exports
+
+/// Items used by the code generated by the derive macro `Context`.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{borrow::Cow, boxed::Box};
+}
@@ -1,6 +1,6 @@
 #![cfg(not(tarpaulin_include))]
 
-use std::fmt::{Display, Error, Formatter};
+use core::fmt::{Display, Error, Formatter};
 
 use crate::operator::*;
 
@@ -12,6 +12,7 @@ impl Display for Operator {
             Add => write!(f, "+"),
             Sub => write!(f, "-"),
             Neg => write!(f, "-"),
+            Pos => write!(f, "+"),
             Mul => write!(f, "*"),
             Div => write!(f, "/"),
             Mod => write!(f, "%"),
@@ -23,9 +24,11 @@ impl Display for Operator {
             Lt => write!(f, "<"),
             Geq => write!(f, ">="),
             Leq => write!(f, "<="),
+            In => write!(f, "in"),
             And => write!(f, "&&"),
             Or => write!(f, "||"),
             Not => write!(f, "!"),
+            NullCoalesce => write!(f, "??"),
 
             Assign => write!(f, " = "),
             AddAssign => write!(f, " += "),
@@ -36,15 +39,23 @@ impl Display for Operator {
             ExpAssign => write!(f, " ^= "),
             AndAssign => write!(f, " &&= "),
             OrAssign => write!(f, " ||= "),
+            Let => write!(f, " = "),
 
             Tuple => write!(f, ", "),
             Chain => write!(f, "; "),
 
+            Array | Index => write!(f, "[]"),
+            Range { inclusive: false } => write!(f, ".."),
+            Range { inclusive: true } => write!(f, "..="),
+            Slice { inclusive: false } => write!(f, "[..]"),
+            Slice { inclusive: true } => write!(f, "[..=]"),
+
             Const { value } => write!(f, "{}", value),
             VariableIdentifierWrite { identifier } | VariableIdentifierRead { identifier } => {
                 write!(f, "{}", identifier)
             },
             FunctionIdentifier { identifier } => write!(f, "{}", identifier),
+            Lambda { parameters } => write!(f, "|{}|", parameters.join(", ")),
         }
     }
 }
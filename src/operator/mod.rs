@@ -1,11 +1,36 @@
-use crate::function::builtin::builtin_function;
+#[cfg(feature = "rand")]
+use crate::function::builtin::call_random_function;
+use crate::function::builtin::{builtin_function, BUILTIN_FUNCTION_IDENTIFIERS};
+use alloc::{borrow::Cow, string::String, sync::Arc, vec, vec::Vec};
+use core::{
+    cmp::Ordering,
+    convert::TryFrom,
+    hash::{Hash, Hasher},
+    mem,
+};
+// Unused if the standard library is linked anyways, as in tests, because the inherent float methods take precedence.
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(feature = "bigint")]
+use num_traits::Zero;
 
-use crate::{context::Context, error::*, value::Value, ContextWithMutableVariables};
+use crate::{
+    context::Context,
+    error::*,
+    tree::EvalState,
+    value::{value_type::ValueType, IntType, Value},
+    ContextWithMutableVariables,
+};
 
 mod display;
 
 /// An enum that represents operators in the operator tree.
-#[derive(Debug, PartialEq, Clone)]
+///
+/// Operators are equal if they are structurally identical.
+/// Constants are compared by the representation of their values, so all constant `NaN`s are equal, but `0.0` and `-0.0` are not.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
 pub enum Operator {
     /// A root node in the operator tree.
     /// The whole expression is stored under a root node, as well as each subexpression surrounded by parentheses.
@@ -17,6 +42,8 @@ pub enum Operator {
     Sub,
     /// A unary negation operator.
     Neg,
+    /// A unary plus operator, which evaluates to its operand if it is a number.
+    Pos,
     /// A binary multiplication operator.
     Mul,
     /// A binary division operator.
@@ -38,12 +65,18 @@ pub enum Operator {
     Geq,
     /// A binary lower-than-or-equal comparator.
     Leq,
+    /// A binary membership operator like `x in (1, 2, 3)` or `"sub" in "substring"`.
+    In,
     /// A binary logical and operator.
     And,
     /// A binary logical or operator.
     Or,
     /// A binary logical not operator.
     Not,
+    /// A binary null-coalescing operator like `a ?? 0`.
+    /// It evaluates to its right operand if the left operand is empty or reads a variable that does not exist, and to the left operand otherwise.
+    /// The right operand is only evaluated if it is the result.
+    NullCoalesce,
 
     /// A binary assignment operator.
     Assign,
@@ -63,12 +96,33 @@ pub enum Operator {
     AndAssign,
     /// A binary or-assign operator.
     OrAssign,
+    /// A binary let-binding operator like `let x = 1`.
+    /// Its arguments are the bound variable and its value, which are available to the following expressions of the enclosing chain.
+    Let,
 
     /// An n-ary tuple constructor.
     Tuple,
     /// An n-ary subexpression chain.
     Chain,
 
+    /// An n-ary array constructor.
+    Array,
+    /// A binary indexing operator.
+    /// The first argument is a tuple, array, string or map, and the second one the index or key.
+    Index,
+    /// A binary range operator like `1..3`, or `1..=3` if it is inclusive.
+    /// It evaluates to a tuple of the integers in the range, unless it is directly enclosed in the brackets of a slice.
+    Range {
+        /// True if the range includes its end.
+        inclusive: bool,
+    },
+    /// A ternary slicing operator like `t[1..3]`, or `t[1..=3]` if it is inclusive.
+    /// The first argument is a tuple, array or string, and the other two the start and end of the slice.
+    Slice {
+        /// True if the slice includes the element at its end.
+        inclusive: bool,
+    },
+
     /// A constant value.
     Const {
         /** The value of the constant. */
@@ -77,37 +131,90 @@ pub enum Operator {
     /// A write to a variable identifier.
     VariableIdentifierWrite {
         /// The identifier of the variable.
-        identifier: String,
+        identifier: Arc<str>,
     },
     /// A read from a variable identifier.
     VariableIdentifierRead {
         /// The identifier of the variable.
-        identifier: String,
+        identifier: Arc<str>,
     },
     /// A function identifier.
     FunctionIdentifier {
         /// The identifier of the function.
-        identifier: String,
+        identifier: Arc<str>,
+    },
+    /// A lambda expression like `|x, y| x * y`.
+    /// Its only argument is the body of the lambda, which is not evaluated when the lambda expression is evaluated.
+    Lambda {
+        /// The identifiers of the parameters of the lambda.
+        parameters: Vec<String>,
     },
 }
 
+impl PartialEq for Operator {
+    fn eq(&self, other: &Self) -> bool {
+        use crate::operator::Operator::*;
+        match (self, other) {
+            (Const { value: a }, Const { value: b }) => a.is_identical_to(b),
+            (
+                VariableIdentifierWrite { identifier: a },
+                VariableIdentifierWrite { identifier: b },
+            )
+            | (
+                VariableIdentifierRead { identifier: a },
+                VariableIdentifierRead { identifier: b },
+            )
+            | (FunctionIdentifier { identifier: a }, FunctionIdentifier { identifier: b }) => {
+                a == b
+            },
+            (Lambda { parameters: a }, Lambda { parameters: b }) => a == b,
+            (Range { inclusive: a }, Range { inclusive: b })
+            | (Slice { inclusive: a }, Slice { inclusive: b }) => a == b,
+            // The remaining operators with the same discriminant do not carry any data.
+            _ => mem::discriminant(self) == mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for Operator {}
+
+impl Hash for Operator {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use crate::operator::Operator::*;
+        mem::discriminant(self).hash(state);
+        match self {
+            Const { value } => value.hash_identity(state),
+            VariableIdentifierWrite { identifier }
+            | VariableIdentifierRead { identifier }
+            | FunctionIdentifier { identifier } => identifier.hash(state),
+            Lambda { parameters } => parameters.hash(state),
+            Range { inclusive } | Slice { inclusive } => inclusive.hash(state),
+            _ => {},
+        }
+    }
+}
+
 impl Operator {
     pub(crate) fn value(value: Value) -> Self {
         Operator::Const { value }
     }
 
-    pub(crate) fn variable_identifier_write(identifier: String) -> Self {
+    pub(crate) fn variable_identifier_write(identifier: Arc<str>) -> Self {
         Operator::VariableIdentifierWrite { identifier }
     }
 
-    pub(crate) fn variable_identifier_read(identifier: String) -> Self {
+    pub(crate) fn variable_identifier_read(identifier: Arc<str>) -> Self {
         Operator::VariableIdentifierRead { identifier }
     }
 
-    pub(crate) fn function_identifier(identifier: String) -> Self {
+    pub(crate) fn function_identifier(identifier: Arc<str>) -> Self {
         Operator::FunctionIdentifier { identifier }
     }
 
+    pub(crate) fn lambda(parameters: Vec<String>) -> Self {
+        Operator::Lambda { parameters }
+    }
+
     /// Returns the precedence of the operator.
     /// A high precedence means that the operator has priority to be deeper in the tree.
     pub(crate) const fn precedence(&self) -> i32 {
@@ -116,21 +223,28 @@ impl Operator {
             RootNode => 200,
 
             Add | Sub => 95,
-            Neg => 110,
+            Neg | Pos => 110,
             Mul | Div | Mod => 100,
             Exp => 120,
 
-            Eq | Neq | Gt | Lt | Geq | Leq => 80,
+            Range { .. } => 85,
+            Eq | Neq | Gt | Lt | Geq | Leq | In => 80,
             And => 75,
             Or => 70,
+            NullCoalesce => 60,
             Not => 110,
 
             Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
-            | AndAssign | OrAssign => 50,
+            | AndAssign | OrAssign | Let => 50,
+            // The body of a lambda extends over everything but tuples and chains.
+            Lambda { .. } => 45,
 
             Tuple => 40,
             Chain => 0,
 
+            Array => 200,
+            Index | Slice { .. } => 190,
+
             Const { .. } => 200,
             VariableIdentifierWrite { .. } | VariableIdentifierRead { .. } => 200,
             FunctionIdentifier { .. } => 190,
@@ -140,9 +254,11 @@ impl Operator {
     /// Returns true if chains of operators with the same precedence as this one should be evaluated left-to-right,
     /// and false if they should be evaluated right-to-left.
     /// Left-to-right chaining has priority if operators with different order but same precedence are chained.
+    ///
+    /// Exponentiation is right-to-left, unless `ParserConfig::with_left_associative_exponentiation` is enabled.
     pub(crate) const fn is_left_to_right(&self) -> bool {
         use crate::operator::Operator::*;
-        !matches!(self, Assign | FunctionIdentifier { .. })
+        !matches!(self, Exp | Assign | Let | FunctionIdentifier { .. })
     }
 
     /// Returns true if chains of this operator should be flattened into one operator with many arguments.
@@ -161,11 +277,37 @@ impl Operator {
     pub(crate) const fn max_argument_amount(&self) -> Option<usize> {
         use crate::operator::Operator::*;
         match self {
-            Add | Sub | Mul | Div | Mod | Exp | Eq | Neq | Gt | Lt | Geq | Leq | And | Or
-            | Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
-            | AndAssign | OrAssign => Some(2),
-            Tuple | Chain => None,
-            Not | Neg | RootNode => Some(1),
+            Add
+            | Sub
+            | Mul
+            | Div
+            | Mod
+            | Exp
+            | Eq
+            | Neq
+            | Gt
+            | Lt
+            | Geq
+            | Leq
+            | In
+            | And
+            | Or
+            | NullCoalesce
+            | Assign
+            | AddAssign
+            | SubAssign
+            | MulAssign
+            | DivAssign
+            | ModAssign
+            | ExpAssign
+            | AndAssign
+            | OrAssign
+            | Let
+            | Index
+            | Range { .. } => Some(2),
+            Slice { .. } => Some(3),
+            Tuple | Chain | Array => None,
+            Not | Neg | Pos | RootNode | Lambda { .. } => Some(1),
             Const { .. } => Some(0),
             VariableIdentifierWrite { .. } | VariableIdentifierRead { .. } => Some(0),
             FunctionIdentifier { .. } => Some(1),
@@ -177,6 +319,12 @@ impl Operator {
         self.max_argument_amount() == Some(1) && *self != Operator::RootNode
     }
 
+    /// Returns true if this operator compares the order of its arguments, i.e. if it can be part of a comparison chain like `a < b <= c`.
+    pub(crate) const fn is_ordering_comparison(&self) -> bool {
+        use crate::operator::Operator::*;
+        matches!(self, Lt | Leq | Gt | Geq)
+    }
+
     /// Evaluates the operator with the given arguments and context.
     pub(crate) fn eval<C: Context>(
         &self,
@@ -184,6 +332,19 @@ impl Operator {
         context: &C,
     ) -> EvalexprResult<Value> {
         use crate::operator::Operator::*;
+        #[cfg(feature = "bigint")]
+        if let Some(result) = self.eval_bigint(arguments) {
+            return result;
+        }
+        #[cfg(feature = "decimal")]
+        if let Some(result) = self.eval_decimal(arguments) {
+            return result;
+        }
+        #[cfg(feature = "chrono")]
+        if let Some(result) = self.eval_chrono(arguments) {
+            return result;
+        }
+
         match self {
             RootNode => {
                 if let Some(first) = arguments.first() {
@@ -219,7 +380,7 @@ impl Operator {
                     Err(EvalexprError::wrong_type_combination(
                         self.clone(),
                         vec![
-                            arguments.get(0).unwrap().into(),
+                            arguments.first().unwrap().into(),
                             arguments.get(1).unwrap().into(),
                         ],
                     ))
@@ -261,6 +422,15 @@ impl Operator {
                     Ok(Value::Float(-arguments[0].as_number()?))
                 }
             },
+            Pos => {
+                expect_operator_argument_amount(arguments.len(), 1)?;
+
+                if arguments[0].is_number() {
+                    Ok(arguments[0].clone())
+                } else {
+                    Err(EvalexprError::expected_number(arguments[0].clone()))
+                }
+            },
             Mul => {
                 expect_operator_argument_amount(arguments.len(), 2)?;
                 arguments[0].as_number()?;
@@ -288,8 +458,9 @@ impl Operator {
                 arguments[1].as_number()?;
 
                 if let (Ok(a), Ok(b)) = (arguments[0].as_int(), arguments[1].as_int()) {
-                    let result = a.checked_div(b);
-                    if let Some(result) = result {
+                    if b == 0 {
+                        Err(EvalexprError::division_by_zero(arguments[0].clone()))
+                    } else if let Some(result) = a.checked_div(b) {
                         Ok(Value::Int(result))
                     } else {
                         Err(EvalexprError::division_error(
@@ -309,8 +480,9 @@ impl Operator {
                 arguments[1].as_number()?;
 
                 if let (Ok(a), Ok(b)) = (arguments[0].as_int(), arguments[1].as_int()) {
-                    let result = a.checked_rem(b);
-                    if let Some(result) = result {
+                    if b == 0 {
+                        Err(EvalexprError::division_by_zero(arguments[0].clone()))
+                    } else if let Some(result) = a.checked_rem(b) {
                         Ok(Value::Int(result))
                     } else {
                         Err(EvalexprError::modulation_error(
@@ -336,12 +508,17 @@ impl Operator {
             Eq => {
                 expect_operator_argument_amount(arguments.len(), 2)?;
 
-                Ok(Value::Boolean(arguments[0] == arguments[1]))
+                Ok(Value::Boolean(values_equal(&arguments[0], &arguments[1])))
             },
             Neq => {
                 expect_operator_argument_amount(arguments.len(), 2)?;
 
-                Ok(Value::Boolean(arguments[0] != arguments[1]))
+                Ok(Value::Boolean(!values_equal(&arguments[0], &arguments[1])))
+            },
+            In => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+
+                contains(&arguments[1], &arguments[0]).map(Value::Boolean)
             },
             Gt => {
                 expect_operator_argument_amount(arguments.len(), 2)?;
@@ -417,6 +594,15 @@ impl Operator {
 
                 Ok(Value::Boolean(a || b))
             },
+            NullCoalesce => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+
+                if arguments[0].is_empty() {
+                    Ok(arguments[1].clone())
+                } else {
+                    Ok(arguments[0].clone())
+                }
+            },
             Not => {
                 expect_operator_argument_amount(arguments.len(), 1)?;
                 let a = arguments[0].as_boolean()?;
@@ -425,6 +611,12 @@ impl Operator {
             },
             Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
             | AndAssign | OrAssign => Err(EvalexprError::ContextNotMutable),
+            // The variable is bound when the enclosing chain is evaluated.
+            Let => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+
+                Ok(Value::Empty)
+            },
             Tuple => Ok(Value::Tuple(arguments.into())),
             Chain => {
                 if arguments.is_empty() {
@@ -433,6 +625,28 @@ impl Operator {
 
                 Ok(arguments.last().cloned().unwrap_or(Value::Empty))
             },
+            Array => Ok(Value::Array(arguments.into())),
+            Index => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+
+                index(&arguments[0], &arguments[1])
+            },
+            Range { inclusive } => {
+                expect_operator_argument_amount(arguments.len(), 2)?;
+                let start = arguments[0].as_int()?;
+                let end = arguments[1].as_int()?;
+
+                Ok(Value::Tuple(if *inclusive {
+                    (start..=end).map(Value::Int).collect()
+                } else {
+                    (start..end).map(Value::Int).collect()
+                }))
+            },
+            Slice { inclusive } => {
+                expect_operator_argument_amount(arguments.len(), 3)?;
+
+                slice(&arguments[0], &arguments[1], &arguments[2], *inclusive)
+            },
             Const { value } => {
                 expect_operator_argument_amount(arguments.len(), 0)?;
 
@@ -441,39 +655,198 @@ impl Operator {
             VariableIdentifierWrite { identifier } => {
                 expect_operator_argument_amount(arguments.len(), 0)?;
 
-                Ok(identifier.clone().into())
+                Ok(identifier.as_ref().into())
             },
             VariableIdentifierRead { identifier } => {
                 expect_operator_argument_amount(arguments.len(), 0)?;
 
-                if let Some(value) = context.get_value(identifier).cloned() {
-                    Ok(value)
-                } else {
-                    Err(EvalexprError::VariableIdentifierNotFound(
-                        identifier.clone(),
-                    ))
-                }
+                read_variable(context, identifier).map(Cow::into_owned)
             },
             FunctionIdentifier { identifier } => {
                 expect_operator_argument_amount(arguments.len(), 1)?;
-                let arguments = &arguments[0];
 
-                match context.call_function(identifier, arguments) {
-                    Err(EvalexprError::FunctionIdentifierNotFound(_))
-                        if !context.are_builtin_functions_disabled() =>
-                    {
-                        if let Some(builtin_function) = builtin_function(identifier) {
-                            builtin_function.call(arguments)
-                        } else {
-                            Err(EvalexprError::FunctionIdentifierNotFound(
-                                identifier.clone(),
-                            ))
-                        }
-                    },
-                    result => result,
+                call_function(context, identifier, &arguments[0], None)
+            },
+            // The body of a lambda is not evaluated, so lambda expressions are evaluated by their node.
+            Lambda { .. } => unreachable!("lambda expressions are evaluated without their body"),
+        }
+    }
+
+    /// Evaluates integer arithmetic and comparisons with arbitrary precision.
+    ///
+    /// Returns `None` if not all arguments are integers, or if they are all `Value::Int`s and the operation does not overflow `IntType`.
+    /// In these cases, the operator is evaluated as usual.
+    #[cfg(feature = "bigint")]
+    fn eval_bigint(&self, arguments: &[Value]) -> Option<EvalexprResult<Value>> {
+        use crate::operator::Operator::*;
+        let overflows = match arguments {
+            [Value::Int(a), Value::Int(b)] => match self {
+                Add => a.checked_add(*b).is_none(),
+                Sub => a.checked_sub(*b).is_none(),
+                Mul => a.checked_mul(*b).is_none(),
+                Div => a.checked_div(*b).is_none(),
+                Mod => a.checked_rem(*b).is_none(),
+                _ => false,
+            },
+            [Value::Int(a)] => *self == Neg && a.checked_neg().is_none(),
+            _ => true,
+        };
+        if !overflows {
+            return None;
+        }
+
+        let integers = arguments
+            .iter()
+            .map(Value::as_bigint)
+            .collect::<EvalexprResult<Vec<_>>>()
+            .ok()?;
+        Some(match (self, integers.as_slice()) {
+            (Add, [a, b]) => Ok((a + b).into()),
+            (Sub, [a, b]) => Ok((a - b).into()),
+            (Mul, [a, b]) => Ok((a * b).into()),
+            (Div, [a, b]) => {
+                if b.is_zero() {
+                    Err(EvalexprError::division_by_zero(arguments[0].clone()))
+                } else {
+                    Ok((a / b).into())
+                }
+            },
+            (Mod, [a, b]) => {
+                if b.is_zero() {
+                    Err(EvalexprError::division_by_zero(arguments[0].clone()))
+                } else {
+                    Ok((a % b).into())
                 }
             },
+            (Neg, [a]) => Ok((-a).into()),
+            (Gt, [a, b]) => Ok(Value::Boolean(a > b)),
+            (Lt, [a, b]) => Ok(Value::Boolean(a < b)),
+            (Geq, [a, b]) => Ok(Value::Boolean(a >= b)),
+            (Leq, [a, b]) => Ok(Value::Boolean(a <= b)),
+            _ => return None,
+        })
+    }
+
+    /// Evaluates decimal arithmetic and comparisons.
+    ///
+    /// Returns `None` if none of the arguments is a `Value::Decimal`, or if not all arguments are decimals or integers.
+    /// In these cases, the operator is evaluated as usual.
+    #[cfg(feature = "decimal")]
+    fn eval_decimal(&self, arguments: &[Value]) -> Option<EvalexprResult<Value>> {
+        use crate::operator::Operator::*;
+        if !arguments.iter().any(Value::is_decimal) {
+            return None;
         }
+
+        let decimals = arguments
+            .iter()
+            .map(Value::as_decimal)
+            .collect::<EvalexprResult<Vec<_>>>()
+            .ok()?;
+        Some(match (self, decimals.as_slice()) {
+            (Add, [a, b]) => a.checked_add(*b).map(Value::Decimal).ok_or_else(|| {
+                EvalexprError::addition_error(arguments[0].clone(), arguments[1].clone())
+            }),
+            (Sub, [a, b]) => a.checked_sub(*b).map(Value::Decimal).ok_or_else(|| {
+                EvalexprError::subtraction_error(arguments[0].clone(), arguments[1].clone())
+            }),
+            (Mul, [a, b]) => a.checked_mul(*b).map(Value::Decimal).ok_or_else(|| {
+                EvalexprError::multiplication_error(arguments[0].clone(), arguments[1].clone())
+            }),
+            (Div, [_, b]) if b.is_zero() => {
+                Err(EvalexprError::division_by_zero(arguments[0].clone()))
+            },
+            (Div, [a, b]) => a.checked_div(*b).map(Value::Decimal).ok_or_else(|| {
+                EvalexprError::division_error(arguments[0].clone(), arguments[1].clone())
+            }),
+            (Mod, [_, b]) if b.is_zero() => {
+                Err(EvalexprError::division_by_zero(arguments[0].clone()))
+            },
+            (Mod, [a, b]) => a.checked_rem(*b).map(Value::Decimal).ok_or_else(|| {
+                EvalexprError::modulation_error(arguments[0].clone(), arguments[1].clone())
+            }),
+            (Neg, [a]) => Ok(Value::Decimal(-*a)),
+            (Gt, [a, b]) => Ok(Value::Boolean(a > b)),
+            (Lt, [a, b]) => Ok(Value::Boolean(a < b)),
+            (Geq, [a, b]) => Ok(Value::Boolean(a >= b)),
+            (Leq, [a, b]) => Ok(Value::Boolean(a <= b)),
+            _ => return None,
+        })
+    }
+
+    /// Evaluates arithmetic and comparisons of dates and durations.
+    ///
+    /// Returns `None` if the operator is not defined for the types of the arguments, in which case it is evaluated as usual.
+    #[cfg(feature = "chrono")]
+    fn eval_chrono(&self, arguments: &[Value]) -> Option<EvalexprResult<Value>> {
+        use crate::operator::Operator::*;
+        if !arguments
+            .iter()
+            .any(|argument| argument.is_datetime() || argument.is_duration())
+        {
+            return None;
+        }
+
+        let addition_error =
+            || EvalexprError::addition_error(arguments[0].clone(), arguments[1].clone());
+        let subtraction_error =
+            || EvalexprError::subtraction_error(arguments[0].clone(), arguments[1].clone());
+        let multiplication_error =
+            || EvalexprError::multiplication_error(arguments[0].clone(), arguments[1].clone());
+        let division_error =
+            || EvalexprError::division_error(arguments[0].clone(), arguments[1].clone());
+        Some(match (self, arguments) {
+            (Add, [Value::DateTime(a), Value::Duration(b)])
+            | (Add, [Value::Duration(b), Value::DateTime(a)]) => a
+                .checked_add_signed(*b)
+                .map(Value::DateTime)
+                .ok_or_else(addition_error),
+            (Sub, [Value::DateTime(a), Value::Duration(b)]) => a
+                .checked_sub_signed(*b)
+                .map(Value::DateTime)
+                .ok_or_else(subtraction_error),
+            (Sub, [Value::DateTime(a), Value::DateTime(b)]) => {
+                Ok(Value::Duration(a.signed_duration_since(*b)))
+            },
+            (Add, [Value::Duration(a), Value::Duration(b)]) => a
+                .checked_add(b)
+                .map(Value::Duration)
+                .ok_or_else(addition_error),
+            (Sub, [Value::Duration(a), Value::Duration(b)]) => a
+                .checked_sub(b)
+                .map(Value::Duration)
+                .ok_or_else(subtraction_error),
+            (Mul, [Value::Duration(a), Value::Int(b)])
+            | (Mul, [Value::Int(b), Value::Duration(a)]) => i32::try_from(*b)
+                .ok()
+                .and_then(|b| a.checked_mul(b))
+                .map(Value::Duration)
+                .ok_or_else(multiplication_error),
+            (Div, [Value::Duration(_), Value::Int(0)]) => {
+                Err(EvalexprError::division_by_zero(arguments[0].clone()))
+            },
+            (Div, [Value::Duration(a), Value::Int(b)]) => i32::try_from(*b)
+                .ok()
+                .and_then(|b| a.checked_div(b))
+                .map(Value::Duration)
+                .ok_or_else(division_error),
+            (Div, [Value::Duration(_), Value::Duration(b)]) if b.is_zero() => {
+                Err(EvalexprError::division_by_zero(arguments[0].clone()))
+            },
+            (Div, [Value::Duration(a), Value::Duration(b)]) => Ok(Value::Float(
+                crate::value::duration_as_seconds(a) / crate::value::duration_as_seconds(b),
+            )),
+            (Neg, [Value::Duration(a)]) => Ok(Value::Duration(-*a)),
+            (Gt, [Value::DateTime(a), Value::DateTime(b)]) => Ok(Value::Boolean(a > b)),
+            (Lt, [Value::DateTime(a), Value::DateTime(b)]) => Ok(Value::Boolean(a < b)),
+            (Geq, [Value::DateTime(a), Value::DateTime(b)]) => Ok(Value::Boolean(a >= b)),
+            (Leq, [Value::DateTime(a), Value::DateTime(b)]) => Ok(Value::Boolean(a <= b)),
+            (Gt, [Value::Duration(a), Value::Duration(b)]) => Ok(Value::Boolean(a > b)),
+            (Lt, [Value::Duration(a), Value::Duration(b)]) => Ok(Value::Boolean(a < b)),
+            (Geq, [Value::Duration(a), Value::Duration(b)]) => Ok(Value::Boolean(a >= b)),
+            (Leq, [Value::Duration(a), Value::Duration(b)]) => Ok(Value::Boolean(a <= b)),
+            _ => return None,
+        })
     }
 
     /// Evaluates the operator with the given arguments and mutable context.
@@ -496,10 +869,7 @@ impl Operator {
                 expect_operator_argument_amount(arguments.len(), 2)?;
 
                 let target = arguments[0].as_string()?;
-                let left_value = Operator::VariableIdentifierRead {
-                    identifier: target.clone(),
-                }
-                .eval(&Vec::new(), context)?;
+                let left_value = read_variable(&*context, &target)?.into_owned();
                 let arguments = vec![left_value, arguments[1].clone()];
 
                 let result = match self {
@@ -524,3 +894,275 @@ impl Operator {
         }
     }
 }
+
+/// Returns the identifiers that can be called as a function with the context,
+/// which are its functions, its variables containing lambdas and the enabled builtin functions.
+fn callable_identifiers<C: Context>(context: &C) -> impl Iterator<Item = &str> {
+    let builtin_functions_disabled = context.are_builtin_functions_disabled();
+    context
+        .iter_function_identifiers()
+        .chain(
+            context
+                .iter_variable_identifiers()
+                .filter(move |identifier| {
+                    matches!(context.get_value(identifier), Some(Value::Lambda(_)))
+                }),
+        )
+        .chain(
+            BUILTIN_FUNCTION_IDENTIFIERS
+                .iter()
+                .copied()
+                .filter(move |identifier| {
+                    !builtin_functions_disabled && builtin_function(identifier).is_some()
+                }),
+        )
+}
+
+/// Reads the value of the variable with the given identifier from the context, without cloning it if the context stores it.
+///
+/// Fails, if the variable is not found or looking it up fails.
+pub(crate) fn read_variable<'a, C: Context>(
+    context: &'a C,
+    identifier: &str,
+) -> EvalexprResult<Cow<'a, Value>> {
+    if let Some(value) = resolve_variable(context, identifier)? {
+        Ok(value)
+    } else {
+        Err(
+            EvalexprError::variable_identifier_not_found(identifier.into())
+                .with_suggestions(context.iter_variable_identifiers()),
+        )
+    }
+}
+
+/// Calls the function with the given identifier with the given argument.
+///
+/// If the state of an evaluation is given, builtin functions use its options.
+#[cfg_attr(not(feature = "rand"), allow(unused_variables))]
+pub(crate) fn call_function<C: Context>(
+    context: &C,
+    identifier: &str,
+    argument: &Value,
+    state: Option<&mut EvalState>,
+) -> EvalexprResult<Value> {
+    match context.call_function(identifier, argument) {
+        Err(EvalexprError::FunctionIdentifierNotFound { .. }) => {
+            // Variables containing lambdas take precedence over builtin functions.
+            if let Some(Value::Lambda(lambda)) = resolve_variable(context, identifier)?.as_deref() {
+                lambda.call(context, argument)
+            } else if let Some(builtin_function) =
+                builtin_function(identifier).filter(|_| !context.are_builtin_functions_disabled())
+            {
+                #[cfg(feature = "rand")]
+                if let Some(random_generator) = state.and_then(EvalState::random_generator) {
+                    if let Some(result) =
+                        call_random_function(identifier, argument, random_generator)
+                    {
+                        return result.map_err(|error| error.returned_from_function(identifier));
+                    }
+                }
+                builtin_function.call(context, argument)
+            } else {
+                Err(
+                    EvalexprError::function_identifier_not_found(identifier.into())
+                        .with_suggestions(callable_identifiers(context)),
+                )
+            }
+        },
+        result => result,
+    }
+    .map_err(|error| error.returned_from_function(identifier))
+}
+
+/// Returns the element of a tuple or array at the given index, the character of a string at the given index,
+/// or the value of a map at the given key.
+pub(crate) fn index(container: &Value, index: &Value) -> EvalexprResult<Value> {
+    match container {
+        Value::Tuple(values) | Value::Array(values) => {
+            let index = index.as_int()?;
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| values.get(index))
+                .cloned()
+                .ok_or_else(|| EvalexprError::index_out_of_bounds(index, values.len()))
+        },
+        Value::String(string) => {
+            let index = index.as_int()?;
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| string.chars().nth(index))
+                .map(|c| Value::String(c.into()))
+                .ok_or_else(|| EvalexprError::index_out_of_bounds(index, string.chars().count()))
+        },
+        Value::Map(map) => {
+            let key = index.as_string()?;
+            if let Some(value) = map.get(&key) {
+                Ok(value.clone())
+            } else {
+                Err(EvalexprError::key_not_found(key))
+            }
+        },
+        value => Err(EvalexprError::type_error(
+            value.clone(),
+            vec![
+                ValueType::Tuple,
+                ValueType::Array,
+                ValueType::String,
+                ValueType::Map,
+            ],
+        )),
+    }
+}
+
+/// Returns the amount of integers from `start` up to `end`, including `end` if the range is `inclusive`.
+pub(crate) fn range_length(start: IntType, end: IntType, inclusive: bool) -> usize {
+    let length = i128::from(end) - i128::from(start) + i128::from(inclusive);
+    usize::try_from(length.max(0)).unwrap_or(usize::MAX)
+}
+
+/// Compares two numbers after converting them into a common type, like the comparison operators do.
+pub(crate) fn compare_numbers(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        #[cfg(feature = "bigint")]
+        (Value::BigInt(_), Value::Int(_) | Value::BigInt(_))
+        | (Value::Int(_), Value::BigInt(_)) => Some(a.as_bigint().ok()?.cmp(&b.as_bigint().ok()?)),
+        #[cfg(feature = "decimal")]
+        (Value::Decimal(_), Value::Int(_) | Value::Decimal(_))
+        | (Value::Int(_), Value::Decimal(_)) => {
+            Some(a.as_decimal().ok()?.cmp(&b.as_decimal().ok()?))
+        },
+        _ => a.as_number().ok()?.partial_cmp(&b.as_number().ok()?),
+    }
+}
+
+/// Returns true if the values are equal as defined by the operator `==`.
+///
+/// Numbers are equal if they are equal after converting them into a common type, so `1 == 1.0`.
+/// Tuples and arrays are equal if they have the same length and their elements are equal,
+/// and maps are equal if they have the same keys and the values of each key are equal.
+/// Values of other types are equal if they have the same type and are identical, and values of different types are never equal.
+pub(crate) fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (a, b) if a.is_number() && b.is_number() => compare_numbers(a, b) == Some(Ordering::Equal),
+        (Value::Tuple(a), Value::Tuple(b)) | (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b))
+        },
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|((a_key, a), (b_key, b))| a_key == b_key && values_equal(a, b))
+        },
+        (a, b) => a == b,
+    }
+}
+
+/// Returns true if the tuple or array contains an element equal to the value as defined by [`values_equal`],
+/// if the string contains the value as substring, or if the map contains the value as key.
+pub(crate) fn contains(container: &Value, value: &Value) -> EvalexprResult<bool> {
+    match container {
+        Value::Tuple(values) | Value::Array(values) => {
+            Ok(values.iter().any(|element| values_equal(element, value)))
+        },
+        // The empty tuple `()` is the empty value.
+        Value::Empty => Ok(false),
+        Value::String(string) => Ok(string.contains(&value.as_string()?)),
+        Value::Map(map) => Ok(map.contains_key(&value.as_string()?)),
+        container => Err(EvalexprError::type_error(
+            container.clone(),
+            vec![
+                ValueType::Tuple,
+                ValueType::Array,
+                ValueType::String,
+                ValueType::Map,
+            ],
+        )),
+    }
+}
+
+/// Returns the elements of a tuple or array, or the characters of a string, from the index `start` up to the index `end`.
+/// The element at `end` is only included if the slice is `inclusive`.
+///
+/// Fails if one of the indices is outside of the bounds of the container.
+/// If `end` is before `start`, the slice is empty.
+pub(crate) fn slice(
+    container: &Value,
+    start: &Value,
+    end: &Value,
+    inclusive: bool,
+) -> EvalexprResult<Value> {
+    let (start, end) = (start.as_int()?, end.as_int()?);
+    let bounds = |len: usize| -> EvalexprResult<(usize, usize)> {
+        let to_bound = |index: IntType, offset: IntType| {
+            index
+                .checked_add(offset)
+                .and_then(|bound| usize::try_from(bound).ok())
+                .filter(|bound| *bound <= len)
+                .ok_or_else(|| EvalexprError::index_out_of_bounds(index, len))
+        };
+        let start = to_bound(start, 0)?;
+        let end = to_bound(end, IntType::from(inclusive))?;
+        Ok((start, end.max(start)))
+    };
+
+    match container {
+        Value::Tuple(values) => {
+            let (start, end) = bounds(values.len())?;
+            Ok(Value::Tuple(values[start..end].to_vec()))
+        },
+        Value::Array(values) => {
+            let (start, end) = bounds(values.len())?;
+            Ok(Value::Array(values[start..end].to_vec()))
+        },
+        Value::String(string) => {
+            let (start, end) = bounds(string.chars().count())?;
+            Ok(Value::String(
+                string.chars().skip(start).take(end - start).collect(),
+            ))
+        },
+        value => Err(EvalexprError::type_error(
+            value.clone(),
+            vec![ValueType::Tuple, ValueType::Array, ValueType::String],
+        )),
+    }
+}
+
+/// Looks up the value of a variable identifier in the context.
+///
+/// If the context does not contain the identifier itself, it is treated as a dot-separated path like `object.field.subfield`.
+/// The longest prefix of the path that is a variable in the context is looked up first,
+/// and the remaining fields are looked up in nested `Value::Map`s.
+///
+/// Fails, if looking up a variable in the context fails.
+pub(crate) fn resolve_variable<'a, C: Context>(
+    context: &'a C,
+    identifier: &str,
+) -> EvalexprResult<Option<Cow<'a, Value>>> {
+    if let Some(value) = context.try_get_value(identifier)? {
+        return Ok(Some(value));
+    }
+
+    let mut prefix_end = identifier.len();
+    while let Some(dot) = identifier[..prefix_end].rfind('.') {
+        if let Some(value) = context.try_get_value(&identifier[..dot])? {
+            let fields = &identifier[dot + 1..];
+            return Ok(match value {
+                Cow::Borrowed(value) => resolve_fields(value, fields).map(Cow::Borrowed),
+                Cow::Owned(value) => resolve_fields(&value, fields).cloned().map(Cow::Owned),
+            });
+        }
+        prefix_end = dot;
+    }
+    Ok(None)
+}
+
+/// Looks up a dot-separated path of fields in nested `Value::Map`s.
+fn resolve_fields<'a>(value: &'a Value, fields: &str) -> Option<&'a Value> {
+    fields
+        .split('.')
+        .try_fold(value, |value, field| match value {
+            Value::Map(map) => map.get(field),
+            _ => None,
+        })
+}
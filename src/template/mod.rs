@@ -0,0 +1,170 @@
+use crate::{
+    context::Context,
+    error::{EvalexprError, EvalexprResult},
+    interface::eval_with_context,
+    token::Span,
+    value::Value,
+};
+use alloc::string::{String, ToString};
+
+/// Renders a template by replacing each expression in braces with its value, evaluated with the given context.
+///
+/// Strings are inserted without quotes, and all other values as written by their `Display` implementation.
+/// Like in the builtin `format` function, `{{` and `}}` write a literal brace.
+/// See `eval_template_with_delimiters` for the details and for templates with other delimiters.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context = context_map! { "name" => "Bob", "price" => 2.5, "qty" => 4 }.unwrap(); // Do proper error handling here
+/// assert_eq!(
+///     eval_template("Hello {name}, you owe {price * qty} EUR", &context),
+///     Ok("Hello Bob, you owe 10 EUR".to_string())
+/// );
+/// assert_eq!(eval_template("{{name}}", &context), Ok("{name}".to_string()));
+/// ```
+///
+/// *See the [crate doc](index.html) for more examples and explanations of the expression format.*
+pub fn eval_template<C: Context>(template: &str, context: &C) -> EvalexprResult<String> {
+    eval_template_with_delimiters(template, "{", "}", context)
+}
+
+/// Renders a template by replacing each expression between the given delimiters with its value, evaluated with the given context.
+///
+/// An expression ends at the first closing delimiter that is not within a string literal of the expression.
+/// Outside of expressions, a doubled opening or closing delimiter writes the delimiter once,
+/// and a single closing delimiter is written as it is.
+///
+/// Fails with `EvalexprError::UnterminatedTemplateExpression` if an expression is not closed,
+/// and with `EvalexprError::TemplateExpressionFailed` if an expression cannot be parsed or evaluated.
+/// The positions in both errors, including those in the error of the expression, are byte offsets into the template.
+///
+/// # Panics
+///
+/// Panics if one of the delimiters is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context = context_map! { "total" => 12 }.unwrap(); // Do proper error handling here
+/// assert_eq!(
+///     eval_template_with_delimiters("{\"total\": ${total * 2}}", "${", "}", &context),
+///     Ok("{\"total\": 24}".to_string())
+/// );
+/// ```
+pub fn eval_template_with_delimiters<C: Context>(
+    template: &str,
+    opening: &str,
+    closing: &str,
+    context: &C,
+) -> EvalexprResult<String> {
+    assert!(
+        !opening.is_empty() && !closing.is_empty(),
+        "the delimiters of a template must not be empty"
+    );
+
+    let mut result = String::new();
+    let mut position = 0;
+    while let Some(character) = template[position..].chars().next() {
+        let rest = &template[position..];
+        if let Some(after_opening) = rest.strip_prefix(opening) {
+            if after_opening.starts_with(opening) {
+                result.push_str(opening);
+                position += 2 * opening.len();
+                continue;
+            }
+
+            let start = position + opening.len();
+            let end = find_closing_delimiter(&template[start..], closing)
+                .map(|length| start + length)
+                .ok_or_else(|| {
+                    EvalexprError::unterminated_template_expression(Span::new(
+                        position,
+                        template.len(),
+                    ))
+                })?;
+            match eval_with_context(&template[start..end], context) {
+                Ok(Value::String(string)) => result.push_str(&string),
+                Ok(value) => result.push_str(&value.to_string()),
+                Err(error) => {
+                    return Err(EvalexprError::template_expression_failed(
+                        Span::new(start, end),
+                        offset_spans(error, start),
+                    ))
+                },
+            }
+            position = end + closing.len();
+        } else if rest
+            .strip_prefix(closing)
+            .is_some_and(|after_closing| after_closing.starts_with(closing))
+        {
+            result.push_str(closing);
+            position += 2 * closing.len();
+        } else {
+            result.push(character);
+            position += character.len_utf8();
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns the byte offset of the first closing delimiter in the given expression that is not within a string literal.
+fn find_closing_delimiter(expression: &str, closing: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (offset, character) in expression.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if character == '\\' => escaped = true,
+            Some(opening_quote) if character == opening_quote => quote = None,
+            Some(_) => {},
+            None if expression[offset..].starts_with(closing) => return Some(offset),
+            None if character == '"' || character == '\'' => quote = Some(character),
+            None => {},
+        }
+    }
+    None
+}
+
+/// Moves the positions in an error of an expression by the offset of the expression in the template.
+fn offset_spans(error: EvalexprError, offset: usize) -> EvalexprError {
+    let offset_span = |span: Span| Span::new(span.start + offset, span.end + offset);
+    match error {
+        EvalexprError::UnmatchedLBrace { span } => EvalexprError::UnmatchedLBrace {
+            span: offset_span(span),
+        },
+        EvalexprError::UnmatchedRBrace { span } => EvalexprError::UnmatchedRBrace {
+            span: offset_span(span),
+        },
+        EvalexprError::UnmatchedLBracket { span } => EvalexprError::UnmatchedLBracket {
+            span: offset_span(span),
+        },
+        EvalexprError::UnmatchedRBracket { span } => EvalexprError::UnmatchedRBracket {
+            span: offset_span(span),
+        },
+        EvalexprError::UnmatchedPartialToken {
+            first,
+            second,
+            span,
+        } => EvalexprError::UnmatchedPartialToken {
+            first,
+            second,
+            span: offset_span(span),
+        },
+        EvalexprError::UnterminatedComment { span } => EvalexprError::UnterminatedComment {
+            span: offset_span(span),
+        },
+        EvalexprError::IntLiteralOutOfRange { literal, span } => {
+            EvalexprError::IntLiteralOutOfRange {
+                literal,
+                span: offset_span(span),
+            }
+        },
+        error => error,
+    }
+}
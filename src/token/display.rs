@@ -1,8 +1,8 @@
-use std::fmt;
+use core::fmt;
 
 use crate::token::{PartialToken, Token};
 
-impl fmt::Display for Token {
+impl fmt::Display for Token<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         use self::Token::*;
         match self {
@@ -20,13 +20,17 @@ impl fmt::Display for Token {
             Lt => write!(f, "<"),
             Geq => write!(f, ">="),
             Leq => write!(f, "<="),
+            In => write!(f, "in"),
             And => write!(f, "&&"),
             Or => write!(f, "||"),
             Not => write!(f, "!"),
+            NullCoalesce => write!(f, "??"),
 
             // Precedence
             LBrace => write!(f, "("),
             RBrace => write!(f, ")"),
+            LBracket => write!(f, "["),
+            RBracket => write!(f, "]"),
 
             // Assignment
             Assign => write!(f, "="),
@@ -42,11 +46,19 @@ impl fmt::Display for Token {
             // Special
             Comma => write!(f, ","),
             Semicolon => write!(f, ";"),
+            DotDot => write!(f, ".."),
+            DotDotEq => write!(f, "..="),
+            Lambda(parameters) => write!(f, "|{}|", parameters.join(", ")),
+            Let => write!(f, "let"),
 
             // Values => write!(f, ""), Variables and Functions
             Identifier(identifier) => identifier.fmt(f),
             Float(float) => float.fmt(f),
             Int(int) => int.fmt(f),
+            #[cfg(feature = "bigint")]
+            BigInt(int) => int.fmt(f),
+            #[cfg(feature = "decimal")]
+            Decimal(decimal) => decimal.fmt(f),
             Boolean(boolean) => boolean.fmt(f),
             String(string) => fmt::Debug::fmt(string, f),
         }
@@ -72,6 +84,7 @@ impl fmt::Display for PartialToken {
             Lt => write!(f, "<"),
             Ampersand => write!(f, "&"),
             VerticalBar => write!(f, "|"),
+            QuestionMark => write!(f, "?"),
         }
     }
 }
@@ -1,12 +1,26 @@
+#[cfg(feature = "bigint")]
+use crate::value::BigIntType;
+#[cfg(feature = "decimal")]
+use crate::value::DecimalType;
 use crate::{
-    error::{EvalexprError, EvalexprResult},
+    error::{ErrorRecovery, EvalexprError, EvalexprResult},
+    tree::ParserConfig,
     value::{FloatType, IntType},
 };
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{iter::Peekable, str::CharIndices};
 
 mod display;
 
+/// A token of an expression string.
+/// Identifiers and string literals without escape sequences borrow their text from the expression string.
 #[derive(Clone, PartialEq, Debug)]
-pub enum Token {
+pub enum Token<'a> {
     // Arithmetic
     Plus,
     Minus,
@@ -22,13 +36,17 @@ pub enum Token {
     Lt,
     Geq,
     Leq,
+    In,
     And,
     Or,
     Not,
+    NullCoalesce,
 
     // Precedence
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
 
     // Assignment
     Assign,
@@ -44,20 +62,80 @@ pub enum Token {
     // Special
     Comma,
     Semicolon,
+    /// The range operator `..`.
+    DotDot,
+    /// The inclusive range operator `..=`.
+    DotDotEq,
+    /// The parameter list of a lambda, like `|x, y|`.
+    Lambda(Vec<String>),
+    /// The keyword of a let-binding like `let x = 1`.
+    Let,
 
     // Values, Variables and Functions
-    Identifier(String),
+    Identifier(Cow<'a, str>),
     Float(FloatType),
     Int(IntType),
+    #[cfg(feature = "bigint")]
+    BigInt(BigIntType),
+    #[cfg(feature = "decimal")]
+    Decimal(DecimalType),
     Boolean(bool),
-    String(String),
+    String(Cow<'a, str>),
+}
+
+/// A range of bytes in an expression string.
+///
+/// Spans are used to point to the part of an expression that caused an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The byte offset of the first character of the span.
+    pub start: usize,
+    /// The byte offset directly after the last character of the span.
+    pub end: usize,
+}
+
+impl Span {
+    /// Creates a new span covering the bytes from `start` up to, but not including, `end`.
+    pub const fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// The kind of a token, which determines how it is highlighted in an editor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// An integer, float or decimal literal.
+    Number,
+    /// A boolean literal `true` or `false`.
+    Boolean,
+    /// A string literal, including its quotes.
+    String,
+    /// The identifier of a variable or a function.
+    Identifier,
+    /// The keyword `let` of a let-binding.
+    Keyword,
+    /// An operator like `+`, `&&` or `in`, including assignments, commas and semicolons.
+    Operator,
+    /// A parenthesis or a bracket.
+    Parenthesis,
+    /// The parameter list of a lambda, like `|x, y|`.
+    LambdaParameters,
+}
+
+/// A token of an expression string together with the part of the string it was read from, as returned by `tokenize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpannedToken {
+    /// The kind of the token.
+    pub kind: TokenKind,
+    /// The part of the expression string the token was read from.
+    pub span: Span,
 }
 
 /// A partial token is an input character whose meaning depends on the characters around it.
 #[derive(Clone, Debug, PartialEq)]
 pub enum PartialToken {
     /// A partial token that unambiguously maps to a single token.
-    Token(Token),
+    Token(Token<'static>),
     /// A partial token that is a literal.
     Literal(String),
     /// A plus character '+'.
@@ -86,11 +164,43 @@ pub enum PartialToken {
     Ampersand,
     /// A vertical bar character '|'.
     VerticalBar,
+    /// A question mark character '?'.
+    QuestionMark,
+}
+
+/// A partial token that borrows its text from the tokenized string.
+/// It is only converted into a `PartialToken` for error messages.
+#[derive(Clone, Debug, PartialEq)]
+enum RawToken<'a> {
+    /// A partial token that unambiguously maps to a single token.
+    Token(Token<'a>),
+    /// A run of characters that are neither whitespace nor part of an operator.
+    Literal(&'a str),
+    /// A partial token that carries no data, which is never `PartialToken::Token` or `PartialToken::Literal`.
+    Symbol(PartialToken),
+}
+
+impl RawToken<'_> {
+    fn symbol(&self) -> Option<&PartialToken> {
+        match self {
+            RawToken::Symbol(symbol) => Some(symbol),
+            _ => None,
+        }
+    }
+
+    fn to_partial_token(&self) -> PartialToken {
+        match self {
+            RawToken::Token(token) => PartialToken::Token(token.clone().into_owned()),
+            RawToken::Literal(literal) => PartialToken::Literal(literal.to_string()),
+            RawToken::Symbol(symbol) => symbol.clone(),
+        }
+    }
 }
 
-// Make this a const fn as soon as is_whitespace and to_string get stable (issue #57563)
-fn char_to_partial_token(c: char) -> PartialToken {
-    match c {
+/// Returns the partial token of a character that is not part of a literal, or `None` if it is part of a literal.
+// Make this a const fn as soon as is_whitespace gets stable (issue #57563)
+fn char_to_partial_token(c: char) -> Option<PartialToken> {
+    Some(match c {
         '+' => PartialToken::Plus,
         '-' => PartialToken::Minus,
         '*' => PartialToken::Star,
@@ -100,6 +210,8 @@ fn char_to_partial_token(c: char) -> PartialToken {
 
         '(' => PartialToken::Token(Token::LBrace),
         ')' => PartialToken::Token(Token::RBrace),
+        '[' => PartialToken::Token(Token::LBracket),
+        ']' => PartialToken::Token(Token::RBracket),
 
         ',' => PartialToken::Token(Token::Comma),
         ';' => PartialToken::Token(Token::Semicolon),
@@ -110,18 +222,14 @@ fn char_to_partial_token(c: char) -> PartialToken {
         '<' => PartialToken::Lt,
         '&' => PartialToken::Ampersand,
         '|' => PartialToken::VerticalBar,
+        '?' => PartialToken::QuestionMark,
 
-        c => {
-            if c.is_whitespace() {
-                PartialToken::Whitespace
-            } else {
-                PartialToken::Literal(c.to_string())
-            }
-        },
-    }
+        c if c.is_whitespace() => PartialToken::Whitespace,
+        _ => return None,
+    })
 }
 
-impl Token {
+impl Token<'_> {
     #[cfg(not(tarpaulin_include))]
     pub(crate) const fn is_leftsided_value(&self) -> bool {
         match self {
@@ -138,15 +246,24 @@ impl Token {
             Token::Lt => false,
             Token::Geq => false,
             Token::Leq => false,
+            Token::In => false,
             Token::And => false,
             Token::Or => false,
             Token::Not => false,
+            Token::NullCoalesce => false,
 
             Token::LBrace => true,
             Token::RBrace => false,
+            // A bracket after an identifier indexes a variable instead of calling a function.
+            Token::LBracket => false,
+            Token::RBracket => false,
 
             Token::Comma => false,
             Token::Semicolon => false,
+            Token::DotDot => false,
+            Token::DotDotEq => false,
+            Token::Lambda(_) => false,
+            Token::Let => false,
 
             Token::Assign => false,
             Token::PlusAssign => false,
@@ -161,6 +278,10 @@ impl Token {
             Token::Identifier(_) => true,
             Token::Float(_) => true,
             Token::Int(_) => true,
+            #[cfg(feature = "bigint")]
+            Token::BigInt(_) => true,
+            #[cfg(feature = "decimal")]
+            Token::Decimal(_) => true,
             Token::Boolean(_) => true,
             Token::String(_) => true,
         }
@@ -182,15 +303,23 @@ impl Token {
             Token::Lt => false,
             Token::Geq => false,
             Token::Leq => false,
+            Token::In => false,
             Token::And => false,
             Token::Or => false,
             Token::Not => false,
+            Token::NullCoalesce => false,
 
             Token::LBrace => false,
             Token::RBrace => true,
+            Token::LBracket => false,
+            Token::RBracket => true,
 
             Token::Comma => false,
             Token::Semicolon => false,
+            Token::DotDot => false,
+            Token::DotDotEq => false,
+            Token::Lambda(_) => false,
+            Token::Let => false,
 
             Token::Assign => false,
             Token::PlusAssign => false,
@@ -205,6 +334,10 @@ impl Token {
             Token::Identifier(_) => true,
             Token::Float(_) => true,
             Token::Int(_) => true,
+            #[cfg(feature = "bigint")]
+            Token::BigInt(_) => true,
+            #[cfg(feature = "decimal")]
+            Token::Decimal(_) => true,
             Token::Boolean(_) => true,
             Token::String(_) => true,
         }
@@ -226,84 +359,335 @@ impl Token {
                 | OrAssign
         )
     }
+
+    /// Returns the kind of this token.
+    pub(crate) const fn kind(&self) -> TokenKind {
+        match self {
+            Token::LBrace | Token::RBrace | Token::LBracket | Token::RBracket => {
+                TokenKind::Parenthesis
+            },
+            Token::Lambda(_) => TokenKind::LambdaParameters,
+            Token::Let => TokenKind::Keyword,
+            Token::Identifier(_) => TokenKind::Identifier,
+            Token::Float(_) | Token::Int(_) => TokenKind::Number,
+            #[cfg(feature = "bigint")]
+            Token::BigInt(_) => TokenKind::Number,
+            #[cfg(feature = "decimal")]
+            Token::Decimal(_) => TokenKind::Number,
+            Token::Boolean(_) => TokenKind::Boolean,
+            Token::String(_) => TokenKind::String,
+            _ => TokenKind::Operator,
+        }
+    }
+
+    /// Converts this token into a token that does not borrow from the expression string.
+    pub(crate) fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::Identifier(identifier) => Token::Identifier(Cow::Owned(identifier.into_owned())),
+            Token::String(string) => Token::String(Cow::Owned(string.into_owned())),
+            Token::Plus => Token::Plus,
+            Token::Minus => Token::Minus,
+            Token::Star => Token::Star,
+            Token::Slash => Token::Slash,
+            Token::Percent => Token::Percent,
+            Token::Hat => Token::Hat,
+            Token::Eq => Token::Eq,
+            Token::Neq => Token::Neq,
+            Token::Gt => Token::Gt,
+            Token::Lt => Token::Lt,
+            Token::Geq => Token::Geq,
+            Token::Leq => Token::Leq,
+            Token::In => Token::In,
+            Token::And => Token::And,
+            Token::Or => Token::Or,
+            Token::Not => Token::Not,
+            Token::NullCoalesce => Token::NullCoalesce,
+            Token::LBrace => Token::LBrace,
+            Token::RBrace => Token::RBrace,
+            Token::LBracket => Token::LBracket,
+            Token::RBracket => Token::RBracket,
+            Token::Assign => Token::Assign,
+            Token::PlusAssign => Token::PlusAssign,
+            Token::MinusAssign => Token::MinusAssign,
+            Token::StarAssign => Token::StarAssign,
+            Token::SlashAssign => Token::SlashAssign,
+            Token::PercentAssign => Token::PercentAssign,
+            Token::HatAssign => Token::HatAssign,
+            Token::AndAssign => Token::AndAssign,
+            Token::OrAssign => Token::OrAssign,
+            Token::Comma => Token::Comma,
+            Token::Semicolon => Token::Semicolon,
+            Token::DotDot => Token::DotDot,
+            Token::DotDotEq => Token::DotDotEq,
+            Token::Lambda(parameters) => Token::Lambda(parameters),
+            Token::Let => Token::Let,
+            Token::Float(float) => Token::Float(float),
+            Token::Int(int) => Token::Int(int),
+            #[cfg(feature = "bigint")]
+            Token::BigInt(int) => Token::BigInt(int),
+            #[cfg(feature = "decimal")]
+            Token::Decimal(decimal) => Token::Decimal(decimal),
+            Token::Boolean(boolean) => Token::Boolean(boolean),
+        }
+    }
 }
 
 /// Parses an escape sequence within a string literal.
 fn parse_escape_sequence<Iter: Iterator<Item = char>>(iter: &mut Iter) -> EvalexprResult<char> {
     match iter.next() {
         Some('"') => Ok('"'),
+        Some('\'') => Ok('\''),
         Some('\\') => Ok('\\'),
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('0') => Ok('\0'),
+        Some('u') => parse_unicode_escape_sequence(iter),
         Some(c) => Err(EvalexprError::IllegalEscapeSequence(format!("\\{}", c))),
         None => Err(EvalexprError::IllegalEscapeSequence("\\".to_string())),
     }
 }
 
-/// Parses a string value from the given character iterator.
+/// Parses the remainder of a unicode escape sequence `\u{...}` with one to six hexadecimal digits.
+fn parse_unicode_escape_sequence<Iter: Iterator<Item = char>>(
+    iter: &mut Iter,
+) -> EvalexprResult<char> {
+    let mut sequence = String::from("\\u");
+    let mut digits = String::new();
+
+    if let Some(c) = iter.next() {
+        sequence.push(c);
+        if c == '{' {
+            for c in iter.by_ref() {
+                sequence.push(c);
+                match c {
+                    '}' if (1..=6).contains(&digits.len()) => {
+                        if let Some(c) = u32::from_str_radix(&digits, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                        {
+                            return Ok(c);
+                        }
+                        break;
+                    },
+                    c if c.is_ascii_hexdigit() => digits.push(c),
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    Err(EvalexprError::IllegalEscapeSequence(sequence))
+}
+
+/// Parses a string literal whose opening quote is at the byte offset `start` of `string`,
+/// advancing the character iterator over `string` past the closing quote.
 ///
-/// The first character from the iterator is interpreted as first character of the string.
-/// The string is terminated by a double quote `"`.
-/// Occurrences of `"` within the string can be escaped with `\`.
+/// The string is terminated by the given quote character, which is either `"` or `'`.
+/// Occurrences of quotes within the string can be escaped with `\`, as well as `\n`, `\r`, `\t`, `\0` and unicode code points like `\u{1F600}`.
 /// The backslash needs to be escaped with another backslash `\`.
-fn parse_string_literal<Iter: Iterator<Item = char>>(
-    mut iter: &mut Iter,
-) -> EvalexprResult<PartialToken> {
-    let mut result = String::new();
+/// String literals without escape sequences are borrowed from `string`.
+fn parse_string_literal<'a>(
+    string: &'a str,
+    start: usize,
+    iter: &mut Peekable<CharIndices<'a>>,
+    quote: char,
+) -> EvalexprResult<Token<'a>> {
+    let content_start = start + quote.len_utf8();
+    let content = &string[content_start..];
+    if let Some(length) = content
+        .find([quote, '\\'])
+        .filter(|length| content[*length..].starts_with(quote))
+    {
+        while iter
+            .next_if(|(index, _)| *index <= content_start + length)
+            .is_some()
+        {}
+        return Ok(Token::String(Cow::Borrowed(&content[..length])));
+    }
 
+    let mut iter = iter.map(|(_, c)| c);
+    let mut result = String::new();
     while let Some(c) = iter.next() {
         match c {
-            '"' => break,
+            c if c == quote => break,
             '\\' => result.push(parse_escape_sequence(&mut iter)?),
             c => result.push(c),
         }
     }
 
-    Ok(PartialToken::Token(Token::String(result)))
+    Ok(Token::String(Cow::Owned(result)))
 }
 
-/// Converts a string to a vector of partial tokens.
-fn str_to_partial_tokens(string: &str) -> EvalexprResult<Vec<PartialToken>> {
-    let mut result = Vec::new();
-    let mut iter = string.chars().peekable();
-
-    while let Some(c) = iter.next() {
-        if c == '"' {
-            result.push(parse_string_literal(&mut iter)?);
-        } else {
-            let partial_token = char_to_partial_token(c);
-
-            let if_let_successful =
-                if let (Some(PartialToken::Literal(last)), PartialToken::Literal(literal)) =
-                    (result.last_mut(), &partial_token)
-                {
-                    last.push_str(literal);
-                    true
-                } else {
-                    false
-                };
+/// Converts a string to a vector of partial tokens, together with the span each partial token covers in the string.
+/// Malformed string literals and unterminated comments are skipped if the `recovery` records errors.
+fn str_to_partial_tokens<'a>(
+    string: &'a str,
+    recovery: &mut ErrorRecovery,
+) -> EvalexprResult<Vec<(RawToken<'a>, Span)>> {
+    let mut result: Vec<(RawToken, Span)> = Vec::new();
+    let mut iter = string.char_indices().peekable();
 
-            if !if_let_successful {
-                result.push(partial_token);
+    while let Some((start, c)) = iter.next() {
+        if c == '"' || c == '\'' {
+            match parse_string_literal(string, start, &mut iter, c) {
+                Ok(token) => {
+                    let end = iter.peek().map_or(string.len(), |(index, _)| *index);
+                    result.push((RawToken::Token(token), Span::new(start, end)));
+                },
+                Err(error) => {
+                    // Skip the rest of the string literal
+                    while iter.next_if(|(_, next)| *next != c).is_some() {}
+                    iter.next();
+                    let end = iter.peek().map_or(string.len(), |(index, _)| *index);
+                    recovery.recover(error, Span::new(start, end))?;
+                },
             }
+        } else if c == '/' && matches!(iter.peek(), Some((_, '/'))) {
+            // Line comments end before the next newline
+            while iter.next_if(|(_, c)| *c != '\n').is_some() {}
+            let end = iter.peek().map_or(string.len(), |(index, _)| *index);
+            // Comments separate the tokens around them like whitespace
+            result.push((
+                RawToken::Symbol(PartialToken::Whitespace),
+                Span::new(start, end),
+            ));
+        } else if c == '/' && matches!(iter.peek(), Some((_, '*'))) {
+            iter.next();
+            let mut last_was_star = false;
+            let end = loop {
+                match iter.next() {
+                    Some((index, '/')) if last_was_star => break index + 1,
+                    Some((_, c)) => last_was_star = c == '*',
+                    None => {
+                        let span = Span::new(start, string.len());
+                        recovery.recover(EvalexprError::unterminated_comment(span), span)?;
+                        break string.len();
+                    },
+                }
+            };
+            result.push((
+                RawToken::Symbol(PartialToken::Whitespace),
+                Span::new(start, end),
+            ));
+        } else if c == '.' && matches!(iter.peek(), Some((_, '.'))) {
+            // Two dots separate the literals around them, like in `1..3`
+            iter.next();
+            let token = if iter.next_if(|(_, c)| *c == '=').is_some() {
+                Token::DotDotEq
+            } else {
+                Token::DotDot
+            };
+            let end = iter.peek().map_or(string.len(), |(index, _)| *index);
+            result.push((RawToken::Token(token), Span::new(start, end)));
+        } else {
+            let end = start + c.len_utf8();
+            let partial_token = match char_to_partial_token(c) {
+                Some(PartialToken::Token(token)) => RawToken::Token(token),
+                Some(partial_token) => RawToken::Symbol(partial_token),
+                None => {
+                    // Consecutive literal characters form a single literal
+                    if let Some((RawToken::Literal(last), last_span)) = result.last_mut() {
+                        if last_span.end == start {
+                            last_span.end = end;
+                            *last = &string[last_span.start..end];
+                            continue;
+                        }
+                    }
+                    RawToken::Literal(&string[start..end])
+                },
+            };
+            result.push((partial_token, Span::new(start, end)));
         }
     }
     Ok(result)
 }
 
 /// Resolves all partial tokens by converting them to complex tokens.
-fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> EvalexprResult<Vec<Token>> {
+/// The span of each complex token covers the spans of all partial tokens it was created from.
+/// Partial tokens that cannot be resolved are skipped if the `recovery` records errors.
+fn partial_tokens_to_tokens<'a>(
+    mut tokens: &[(RawToken<'a>, Span)],
+    config: &ParserConfig,
+    recovery: &mut ErrorRecovery,
+) -> EvalexprResult<Vec<(Token<'a>, Span)>> {
     let mut result = Vec::new();
     while !tokens.is_empty() {
-        let first = tokens[0].clone();
-        let second = tokens.get(1).cloned();
-        let third = tokens.get(2).cloned();
+        let (first, first_span) = &tokens[0];
+        let first_span = *first_span;
+        let raw_second = tokens.get(1).map(|(token, _)| token);
+        let second = raw_second.and_then(RawToken::symbol);
+        let raw_third = tokens.get(2).map(|(token, _)| token);
+        let third = raw_third.and_then(RawToken::symbol);
         let mut cutoff = 2;
+        // Partial tokens that cannot be combined with the following one are reported with it.
+        let unmatched = |symbol: &PartialToken| {
+            EvalexprError::unmatched_partial_token(
+                symbol.clone(),
+                raw_second.map(RawToken::to_partial_token),
+                first_span,
+            )
+        };
 
-        result.extend(
-            match first {
-                PartialToken::Token(token) => {
-                    cutoff = 1;
+        let token = match first {
+            RawToken::Token(token) => {
+                cutoff = 1;
+                Some(token.clone())
+            },
+            RawToken::Literal(literal) => {
+                cutoff = 1;
+                if let Some(number) = parse_int_literal(literal) {
+                    match number {
+                        Ok(number) => Some(Token::Int(number)),
+                        #[cfg(feature = "bigint")]
+                        Err(_) => parse_bigint_literal(literal).map(Token::BigInt),
+                        #[cfg(not(feature = "bigint"))]
+                        Err(_) => {
+                            recovery.recover(
+                                EvalexprError::int_literal_out_of_range(
+                                    literal.to_string(),
+                                    first_span,
+                                ),
+                                first_span,
+                            )?;
+                            None
+                        },
+                    }
+                } else if let Some(token) = parse_float_literal(literal, config) {
                     Some(token)
-                },
+                } else if let Ok(boolean) = literal.parse::<bool>() {
+                    Some(Token::Boolean(boolean))
+                } else if *literal == "in" {
+                    Some(Token::In)
+                } else if let Some(token) = parse_keyword_operator(literal, config) {
+                    Some(token)
+                } else if *literal == "let" && is_let_binding(&tokens[1..]) {
+                    Some(Token::Let)
+                } else {
+                    // If there are two tokens following this one, check if the next one is
+                    // a plus or a minus. If so, then attempt to parse all three tokens as a
+                    // scientific notation number of the form `<coefficient>e{+,-}<exponent>`,
+                    // for example [Literal("10e"), Minus, Literal("3")] => "1e-3".parse().
+                    match (second, raw_third) {
+                        (
+                            Some(sign @ (PartialToken::Minus | PartialToken::Plus)),
+                            Some(RawToken::Literal(exponent)),
+                        ) => {
+                            if let Some(token) = parse_float_literal(
+                                &format!("{}{}{}", literal, sign, exponent),
+                                config,
+                            ) {
+                                cutoff = 3;
+                                Some(token)
+                            } else {
+                                Some(Token::Identifier(Cow::Borrowed(literal)))
+                            }
+                        },
+                        _ => Some(Token::Identifier(Cow::Borrowed(literal))),
+                    }
+                }
+            },
+            RawToken::Symbol(symbol) => match symbol {
                 PartialToken::Plus => match second {
                     Some(PartialToken::Eq) => Some(Token::PlusAssign),
                     _ => {
@@ -346,37 +730,6 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> EvalexprResult<Vec<T
                         Some(Token::Hat)
                     },
                 },
-                PartialToken::Literal(literal) => {
-                    cutoff = 1;
-                    if let Ok(number) = parse_dec_or_hex(&literal) {
-                        Some(Token::Int(number))
-                    } else if let Ok(number) = literal.parse::<FloatType>() {
-                        Some(Token::Float(number))
-                    } else if let Ok(boolean) = literal.parse::<bool>() {
-                        Some(Token::Boolean(boolean))
-                    } else {
-                        // If there are two tokens following this one, check if the next one is
-                        // a plus or a minus. If so, then attempt to parse all three tokens as a
-                        // scientific notation number of the form `<coefficient>e{+,-}<exponent>`,
-                        // for example [Literal("10e"), Minus, Literal("3")] => "1e-3".parse().
-                        match (second, third) {
-                            (Some(second), Some(third))
-                                if second == PartialToken::Minus
-                                    || second == PartialToken::Plus =>
-                            {
-                                if let Ok(number) =
-                                    format!("{}{}{}", literal, second, third).parse::<FloatType>()
-                                {
-                                    cutoff = 3;
-                                    Some(Token::Float(number))
-                                } else {
-                                    Some(Token::Identifier(literal.to_string()))
-                                }
-                            },
-                            _ => Some(Token::Identifier(literal.to_string())),
-                        }
-                    }
-                },
                 PartialToken::Whitespace => {
                     cutoff = 1;
                     None
@@ -404,6 +757,7 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> EvalexprResult<Vec<T
                 },
                 PartialToken::Lt => match second {
                     Some(PartialToken::Eq) => Some(Token::Leq),
+                    Some(PartialToken::Gt) if config.angle_bracket_neq() => Some(Token::Neq),
                     _ => {
                         cutoff = 1;
                         Some(Token::Lt)
@@ -417,7 +771,19 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> EvalexprResult<Vec<T
                         },
                         _ => Some(Token::And),
                     },
-                    _ => return Err(EvalexprError::unmatched_partial_token(first, second)),
+                    _ => {
+                        cutoff = 1;
+                        recovery.recover(unmatched(symbol), first_span)?;
+                        None
+                    },
+                },
+                PartialToken::QuestionMark => match second {
+                    Some(PartialToken::QuestionMark) => Some(Token::NullCoalesce),
+                    _ => {
+                        cutoff = 1;
+                        recovery.recover(unmatched(symbol), first_span)?;
+                        None
+                    },
                 },
                 PartialToken::VerticalBar => match second {
                     Some(PartialToken::VerticalBar) => match third {
@@ -427,44 +793,196 @@ fn partial_tokens_to_tokens(mut tokens: &[PartialToken]) -> EvalexprResult<Vec<T
                         },
                         _ => Some(Token::Or),
                     },
-                    _ => return Err(EvalexprError::unmatched_partial_token(first, second)),
+                    _ => match parse_lambda_parameters(&tokens[1..]) {
+                        Some((parameters, length)) => {
+                            cutoff = length + 1;
+                            Some(Token::Lambda(parameters))
+                        },
+                        None => {
+                            cutoff = 1;
+                            recovery.recover(unmatched(symbol), first_span)?;
+                            None
+                        },
+                    },
                 },
-            }
-            .into_iter(),
-        );
+                PartialToken::Token(_) | PartialToken::Literal(_) => {
+                    unreachable!("symbols are neither tokens nor literals")
+                },
+            },
+        };
 
+        if let Some(token) = token {
+            let end = tokens[cutoff - 1].1.end;
+            result.push((token, Span::new(first_span.start, end)));
+        }
         tokens = &tokens[cutoff..];
     }
     Ok(result)
 }
 
-pub(crate) fn tokenize(string: &str) -> EvalexprResult<Vec<Token>> {
-    partial_tokens_to_tokens(&str_to_partial_tokens(string)?)
+/// Converts a string to a vector of tokens, together with the span each token covers in the string.
+pub(crate) fn tokenize<'a>(
+    string: &'a str,
+    config: &ParserConfig,
+) -> EvalexprResult<Vec<(Token<'a>, Span)>> {
+    tokenize_with_recovery(string, config, &mut ErrorRecovery::fail_fast())
+}
+
+/// Converts a string to a vector of tokens like `tokenize`, skipping the parts of the string that cannot be tokenized
+/// if the `recovery` records errors.
+pub(crate) fn tokenize_with_recovery<'a>(
+    string: &'a str,
+    config: &ParserConfig,
+    recovery: &mut ErrorRecovery,
+) -> EvalexprResult<Vec<(Token<'a>, Span)>> {
+    let partial_tokens = str_to_partial_tokens(string, recovery)?;
+    partial_tokens_to_tokens(&partial_tokens, config, recovery)
+}
+
+/// Parses the parameters of a lambda like `|x, y|` from the partial tokens following its opening vertical bar.
+///
+/// Returns the identifiers of the parameters and the amount of partial tokens up to and including the closing vertical bar,
+/// or `None` if the partial tokens do not form a parameter list.
+/// A lambda without parameters is written as `||`, which is tokenized as `Token::Or`.
+fn parse_lambda_parameters(tokens: &[(RawToken, Span)]) -> Option<(Vec<String>, usize)> {
+    let mut parameters = Vec::new();
+    let mut expects_parameter = true;
+    for (index, (token, _)) in tokens.iter().enumerate() {
+        match token {
+            RawToken::Symbol(PartialToken::Whitespace) => {},
+            RawToken::Literal(literal)
+                if expects_parameter
+                    && !literal.starts_with(|c: char| c.is_ascii_digit())
+                    && literal.parse::<bool>().is_err() =>
+            {
+                parameters.push(literal.to_string());
+                expects_parameter = false;
+            },
+            RawToken::Token(Token::Comma) if !expects_parameter => expects_parameter = true,
+            RawToken::Symbol(PartialToken::VerticalBar)
+                if !expects_parameter || parameters.is_empty() =>
+            {
+                return Some((parameters, index + 1));
+            },
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Returns true if the partial tokens following a `let` literal are an identifier followed by an assignment, like in `let x = 1`.
+/// Otherwise, `let` is an ordinary identifier.
+fn is_let_binding(tokens: &[(RawToken, Span)]) -> bool {
+    let mut tokens = tokens.iter().map(|(token, _)| token).peekable();
+    let mut next_non_whitespace = || {
+        while tokens
+            .next_if(|token| token.symbol() == Some(&PartialToken::Whitespace))
+            .is_some()
+        {}
+        tokens.next()
+    };
+    match (next_non_whitespace(), next_non_whitespace()) {
+        (Some(RawToken::Literal(identifier)), Some(RawToken::Symbol(PartialToken::Eq))) => {
+            !identifier.starts_with(|c: char| c.is_ascii_digit())
+                && identifier.parse::<bool>().is_err()
+                // Two equal signs are a comparison
+                && tokens.next().and_then(RawToken::symbol) != Some(&PartialToken::Eq)
+        },
+        _ => false,
+    }
+}
+
+/// Parses the keywords `and`, `or` and `not` as logical operators if enabled in the `config`.
+fn parse_keyword_operator(literal: &str, config: &ParserConfig) -> Option<Token<'static>> {
+    if !config.keyword_operators() {
+        return None;
+    }
+
+    match literal {
+        "and" => Some(Token::And),
+        "or" => Some(Token::Or),
+        "not" => Some(Token::Not),
+        _ => None,
+    }
+}
+
+/// Parses a float literal, or a decimal literal if enabled in the `config`.
+/// Returns `None` if the literal is not a float literal.
+#[cfg_attr(not(feature = "decimal"), allow(unused_variables))]
+fn parse_float_literal(literal: &str, config: &ParserConfig) -> Option<Token<'static>> {
+    #[cfg(feature = "decimal")]
+    if config.decimal_literals() {
+        let decimal =
+            DecimalType::from_str_exact(literal).or_else(|_| DecimalType::from_scientific(literal));
+        if let Ok(decimal) = decimal {
+            return Some(Token::Decimal(decimal));
+        }
+    }
+
+    literal.parse::<FloatType>().ok().map(Token::Float)
 }
 
-fn parse_dec_or_hex(literal: &str) -> Result<IntType, std::num::ParseIntError> {
-    if let Some(literal) = literal.strip_prefix("0x") {
-        IntType::from_str_radix(literal, 16)
+/// Parses an integer literal in decimal, hexadecimal (`0x`), octal (`0o`) or binary (`0b`) notation.
+/// The digits may be separated by underscores, e.g. `1_000_000`.
+///
+/// Returns `None` if the literal is not an integer literal,
+/// and `Some(Err(_))` if it is an integer literal whose value does not fit into an `IntType`.
+fn parse_int_literal(literal: &str) -> Option<Result<IntType, core::num::ParseIntError>> {
+    let (digits, radix) = int_literal_digits(literal)?;
+    Some(IntType::from_str_radix(&digits, radix))
+}
+
+/// Parses an integer literal like `parse_int_literal`, but without any limit on its size.
+#[cfg(feature = "bigint")]
+fn parse_bigint_literal(literal: &str) -> Option<BigIntType> {
+    let (digits, radix) = int_literal_digits(literal)?;
+    BigIntType::parse_bytes(digits.as_bytes(), radix)
+}
+
+/// Returns the digits of an integer literal without prefix and separators, together with their radix.
+/// Returns `None` if the literal is not an integer literal.
+fn int_literal_digits(literal: &str) -> Option<(String, u32)> {
+    let (digits, radix) = if let Some(digits) = literal.strip_prefix("0x") {
+        (digits, 16)
+    } else if let Some(digits) = literal.strip_prefix("0o") {
+        (digits, 8)
+    } else if let Some(digits) = literal.strip_prefix("0b") {
+        (digits, 2)
     } else {
-        IntType::from_str_radix(literal, 10)
+        (literal, 10)
+    };
+
+    // Literals must start with a digit, such that e.g. `_1` stays an identifier.
+    if !literal.starts_with(|c: char| c.is_ascii_digit())
+        || !digits.chars().any(|c| c.is_digit(radix))
+        || !digits.chars().all(|c| c == '_' || c.is_digit(radix))
+    {
+        return None;
     }
+
+    Some((digits.chars().filter(|c| *c != '_').collect(), radix))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::token::{char_to_partial_token, tokenize, Token};
-    use std::fmt::Write;
+    use crate::{
+        token::{char_to_partial_token, tokenize, Span, Token},
+        tree::ParserConfig,
+    };
+    use alloc::{borrow::Cow, format, string::String, vec, vec::Vec};
+    use core::fmt::Write;
 
     #[test]
     fn test_partial_token_display() {
         let chars = vec![
-            '+', '-', '*', '/', '%', '^', '(', ')', ',', ';', '=', '!', '>', '<', '&', '|', ' ',
+            '+', '-', '*', '/', '%', '^', '(', ')', '[', ']', ',', ';', '=', '!', '>', '<', '&',
+            '|', '?', ' ',
         ];
 
         for char in chars {
             assert_eq!(
                 format!("{}", char),
-                format!("{}", char_to_partial_token(char))
+                format!("{}", char_to_partial_token(char).unwrap())
             );
         }
     }
@@ -472,11 +990,11 @@ mod tests {
     #[test]
     fn test_token_display() {
         let token_string =
-            "+ - * / % ^ == != > < >= <= && || ! ( ) = += -= *= /= %= ^= &&= ||= , ; ";
-        let tokens = tokenize(token_string).unwrap();
+            "+ - * / % ^ == != > < >= <= in && || ! ?? ( ) = += -= *= /= %= ^= &&= ||= , ; .. ..= ";
+        let tokens = tokenize(token_string, &ParserConfig::new()).unwrap();
         let mut result_string = String::new();
 
-        for token in tokens {
+        for (token, _) in tokens {
             write!(result_string, "{} ", token).unwrap();
         }
 
@@ -485,14 +1003,42 @@ mod tests {
 
     #[test]
     fn assignment_lhs_is_identifier() {
-        let tokens = tokenize("a = 1").unwrap();
+        let tokens = tokenize("a = 1", &ParserConfig::new()).unwrap();
         assert_eq!(
             tokens.as_slice(),
             [
-                Token::Identifier("a".to_string()),
-                Token::Assign,
-                Token::Int(1)
+                (Token::Identifier("a".into()), Span::new(0, 1)),
+                (Token::Assign, Span::new(2, 3)),
+                (Token::Int(1), Span::new(4, 5))
             ]
         );
     }
+
+    #[test]
+    fn token_spans() {
+        let tokens = tokenize("ä1+=\"x\\\"\"  1e-3 &&=", &ParserConfig::new()).unwrap();
+        let spans: Vec<_> = tokens.iter().map(|(_, span)| *span).collect();
+        assert_eq!(
+            spans,
+            [
+                Span::new(0, 3),
+                Span::new(3, 5),
+                Span::new(5, 10),
+                Span::new(12, 16),
+                Span::new(17, 20)
+            ]
+        );
+    }
+
+    #[test]
+    fn borrowed_tokens() {
+        let tokens = tokenize("abc + \"def\" + \"g\\\"h\"", &ParserConfig::new()).unwrap();
+        assert!(matches!(
+            tokens[0].0,
+            Token::Identifier(Cow::Borrowed("abc"))
+        ));
+        assert!(matches!(tokens[2].0, Token::String(Cow::Borrowed("def"))));
+        assert_eq!(tokens[4].0, Token::String("g\"h".into()));
+        assert!(matches!(tokens[4].0, Token::String(Cow::Owned(_))));
+    }
 }
@@ -0,0 +1,175 @@
+use alloc::boxed::Box;
+use core::{future::Future, iter::once, pin::Pin};
+
+use crate::{
+    context::AsyncContext,
+    error::EvalexprResult,
+    operator::{call_function, Operator},
+    value::Value,
+    EvalOptions, Node,
+};
+
+use super::{is_absent, options::EvalState, scope::Scope, Operands};
+
+type EvalFuture<'a> = Pin<Box<dyn Future<Output = EvalexprResult<Value>> + Send + 'a>>;
+
+impl Node {
+    /// Evaluates the operator tree rooted at this node with the given context, awaiting calls of async functions.
+    ///
+    /// Async functions of the context take precedence over its other functions with the same identifier.
+    /// Subexpressions that do not call async functions are evaluated like with `eval_with_context`.
+    /// Async functions cannot be called from the body of a lambda.
+    ///
+    /// Fails, if one of the operators in the expression tree fails.
+    /// See the documentation of `AsyncFunction` for an example.
+    pub async fn eval_with_context_async<C: AsyncContext + Sync>(
+        &self,
+        context: &C,
+    ) -> EvalexprResult<Value> {
+        self.eval_with_context_and_options_async(context, &EvalOptions::default())
+            .await
+    }
+
+    /// Evaluates the operator tree rooted at this node with the given context and evaluation options, awaiting calls of async functions.
+    ///
+    /// Fails, if one of the operators in the expression tree fails, or if a limit set in the options is exceeded.
+    pub async fn eval_with_context_and_options_async<C: AsyncContext + Sync>(
+        &self,
+        context: &C,
+        options: &EvalOptions,
+    ) -> EvalexprResult<Value> {
+        self.eval_with_state_async(context, &mut EvalState::new(options))
+            .await
+    }
+
+    /// Evaluates this node like `eval_with_state`, but awaits the async functions called within it.
+    // The future is boxed, as it is recursive.
+    fn eval_with_state_async<'a, 'options, C: AsyncContext + Sync>(
+        &'a self,
+        context: &'a C,
+        state: &'a mut EvalState<'options>,
+    ) -> EvalFuture<'a> {
+        Box::pin(async move {
+            if !self.calls_async_function(context) {
+                return self.eval_with_state(context, state);
+            }
+            state.enter_node(self)?;
+            let result = self.eval_operator_with_state_async(context, state).await;
+            state.leave_node(self, result)
+        })
+    }
+
+    /// Evaluates the operator of this node like `eval_operator_with_state`, but awaits the async functions called within it.
+    fn eval_operator_with_state_async<'a, 'options, C: AsyncContext + Sync>(
+        &'a self,
+        context: &'a C,
+        state: &'a mut EvalState<'options>,
+    ) -> EvalFuture<'a> {
+        Box::pin(async move {
+            state.count_node_evaluation()?;
+
+            if let Some([condition, if_true, if_false]) = self.lazy_if_arguments(context) {
+                return if condition
+                    .eval_with_state_async(context, state)
+                    .await?
+                    .as_boolean()?
+                {
+                    if_true.eval_with_state_async(context, state).await
+                } else {
+                    if_false.eval_with_state_async(context, state).await
+                };
+            }
+            if let Some((operands, comparisons)) = self.comparison_chain() {
+                let mut left = operands[0].eval_with_state_async(context, state).await?;
+                for (operand, comparison) in operands[1..].iter().zip(comparisons) {
+                    let right = operand.eval_with_state_async(context, state).await?;
+                    match Self::eval_chained_comparison(comparison, left, right, state.options())? {
+                        Some(right) => left = right,
+                        None => return Ok(Value::Boolean(false)),
+                    }
+                }
+                return Ok(Value::Boolean(true));
+            }
+            if let Operator::Lambda { parameters } = self.operator() {
+                return self.eval_lambda(parameters);
+            }
+            if self.is_let_chain() {
+                let mut scope = Scope::new(context as &(dyn AsyncContext + Sync));
+                let mut result = Value::Empty;
+                for child in self.children() {
+                    result = match child.let_binding() {
+                        Some((identifier, value)) => {
+                            let value = value.eval_with_state_async(&scope, state).await?;
+                            scope.bind(identifier.into(), value);
+                            Value::Empty
+                        },
+                        None => child.eval_with_state_async(&scope, state).await?,
+                    };
+                }
+                return Ok(result);
+            }
+            if let (Operator::FunctionIdentifier { identifier }, [argument]) =
+                (self.operator(), self.children())
+            {
+                if let Some(function) = context.get_async_function(identifier) {
+                    let argument = argument.eval_with_state_async(context, state).await?;
+                    return function
+                        .call(argument)
+                        .await
+                        .map_err(|error| error.returned_from_function(identifier));
+                }
+            }
+            if let (Some(lambda), [argument]) = (self.called_lambda(context), self.children()) {
+                let argument = argument.eval_with_state_async(context, state).await?;
+                return lambda.call_with_state(context, &argument, state);
+            }
+            if let (Operator::FunctionIdentifier { identifier }, [argument]) =
+                (self.operator(), self.children())
+            {
+                let argument = argument.eval_with_state_async(context, state).await?;
+                return call_function(context, identifier, &argument, Some(state));
+            }
+
+            if self.is_null_coalescing() {
+                let left = self.children[0].eval_with_state_async(context, state).await;
+                if !is_absent(&left) {
+                    return left;
+                }
+                return self.children[1].eval_with_state_async(context, state).await;
+            }
+
+            if let Some(short_circuit_value) = self.short_circuit_value() {
+                let left = self.children[0]
+                    .eval_with_state_async(context, state)
+                    .await?;
+                if left.as_boolean()? == short_circuit_value {
+                    return Ok(Value::Boolean(short_circuit_value));
+                }
+                let right = self.children[1]
+                    .eval_with_state_async(context, state)
+                    .await?;
+                return self.operator().eval(&[left, right], context);
+            }
+
+            let mut arguments = Operands::new();
+            for child in self.children() {
+                arguments.push(child.eval_with_state_async(context, state).await?);
+            }
+            state.apply_operator(self.operator(), &arguments, |arguments| {
+                self.operator().eval(arguments, context)
+            })
+        })
+    }
+
+    /// Returns true if this node or one of its descendants calls an async function of the context.
+    fn calls_async_function<C: AsyncContext>(&self, context: &C) -> bool {
+        once(self)
+            .chain(self.iter())
+            .any(|node| match node.operator() {
+                Operator::FunctionIdentifier { identifier } => {
+                    context.get_async_function(identifier).is_some()
+                },
+                _ => false,
+            })
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    operator::Operator,
+    value::Value,
+    Node,
+};
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
+use core::mem;
+
+/// Functions to construct operator trees programmatically, without formatting and parsing an expression string.
+///
+/// The constructed trees can be evaluated, displayed and compiled like the trees created by `build_operator_tree`.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let tree = Node::add(
+///     Node::mul(Node::variable("x"), Node::constant(2)),
+///     Node::call("max", vec![Node::variable("y"), Node::constant(3)]),
+/// );
+/// assert_eq!(tree.to_string(), "x * 2 + max(y, 3)");
+///
+/// let context = context_map! { "x" => 4, "y" => 1 }.unwrap(); // Do proper error handling here
+/// assert_eq!(tree.eval_with_context(&context), Ok(Value::from(11)));
+/// ```
+// The functions named like operator trait methods take both operands as arguments, so they are called as `Node::add(left, right)`.
+#[allow(clippy::should_implement_trait)]
+impl Node {
+    /// Creates a node with the given operator and children.
+    ///
+    /// Fails with `EvalexprError::WrongOperatorArgumentAmount` if the operator requires a different amount of children.
+    /// Root nodes may have zero or one child, and tuples, chains and arrays any amount of children.
+    ///
+    /// Ordering comparisons like `a < b < c` are evaluated as chains by the parser.
+    /// If an ordering comparison is given as the left operand of another ordering comparison, it is therefore enclosed in a root node,
+    /// such that the result of the inner comparison is compared as in `(a < b) < c`.
+    pub fn operation(operator: Operator, mut children: Vec<Node>) -> EvalexprResult<Node> {
+        if operator.is_ordering_comparison() {
+            if let Some(left) = children.first_mut() {
+                if left.operator().is_ordering_comparison() {
+                    let comparison = mem::replace(left, Node::root_node());
+                    left.children.push(comparison);
+                }
+            }
+        }
+
+        Node::with_checked_children(operator, children)
+    }
+
+    /// Creates a node with the given operator and children, without changing the structure of the children.
+    ///
+    /// Unlike `Node::operation`, an ordering comparison given as the left operand of another ordering comparison is not enclosed in a root node,
+    /// so the two comparisons form a chain like `a < b < c`, as in trees built by the parser.
+    /// Fails with `EvalexprError::WrongOperatorArgumentAmount` if the operator requires a different amount of children.
+    pub fn with_checked_children(operator: Operator, children: Vec<Node>) -> EvalexprResult<Node> {
+        match operator.max_argument_amount() {
+            Some(1) if operator == Operator::RootNode && children.is_empty() => {},
+            Some(expected) if children.len() != expected => {
+                return Err(EvalexprError::wrong_operator_argument_amount(
+                    children.len(),
+                    expected,
+                ));
+            },
+            _ => {},
+        }
+
+        Ok(Node::with_children(operator, children))
+    }
+
+    /// Creates a node that evaluates to the given constant value.
+    pub fn constant<V: Into<Value>>(value: V) -> Node {
+        Node::new(Operator::value(value.into()))
+    }
+
+    /// Creates a node that reads the variable with the given identifier.
+    pub fn variable<S: Into<Arc<str>>>(identifier: S) -> Node {
+        Node::new(Operator::variable_identifier_read(identifier.into()))
+    }
+
+    /// Creates a node that calls the function with the given identifier.
+    ///
+    /// Like in expression strings, a function called with more than one argument receives the arguments as a tuple,
+    /// and a function called without arguments receives an empty value.
+    pub fn call<S: Into<Arc<str>>>(identifier: S, mut arguments: Vec<Node>) -> Node {
+        let argument = match arguments.len() {
+            0 => Node::root_node(),
+            1 => arguments.remove(0),
+            _ => Node::tuple(arguments),
+        };
+        Node::with_children(
+            Operator::function_identifier(identifier.into()),
+            vec![argument],
+        )
+    }
+
+    /// Creates a node that assigns the value of the given node to the variable with the given identifier.
+    pub fn assign<S: Into<Arc<str>>>(identifier: S, value: Node) -> Node {
+        let variable = Node::new(Operator::variable_identifier_write(identifier.into()));
+        Node::with_children(Operator::Assign, vec![variable, value])
+    }
+
+    /// Creates an addition node.
+    pub fn add(left: Node, right: Node) -> Node {
+        Node::with_children(Operator::Add, vec![left, right])
+    }
+
+    /// Creates a subtraction node.
+    pub fn sub(left: Node, right: Node) -> Node {
+        Node::with_children(Operator::Sub, vec![left, right])
+    }
+
+    /// Creates a multiplication node.
+    pub fn mul(left: Node, right: Node) -> Node {
+        Node::with_children(Operator::Mul, vec![left, right])
+    }
+
+    /// Creates a division node.
+    pub fn div(left: Node, right: Node) -> Node {
+        Node::with_children(Operator::Div, vec![left, right])
+    }
+
+    /// Creates a modulo node.
+    pub fn modulo(left: Node, right: Node) -> Node {
+        Node::with_children(Operator::Mod, vec![left, right])
+    }
+
+    /// Creates an exponentiation node.
+    pub fn exp(base: Node, exponent: Node) -> Node {
+        Node::with_children(Operator::Exp, vec![base, exponent])
+    }
+
+    /// Creates a negation node.
+    pub fn neg(operand: Node) -> Node {
+        Node::with_children(Operator::Neg, vec![operand])
+    }
+
+    /// Creates a logical and node.
+    pub fn and(left: Node, right: Node) -> Node {
+        Node::with_children(Operator::And, vec![left, right])
+    }
+
+    /// Creates a logical or node.
+    pub fn or(left: Node, right: Node) -> Node {
+        Node::with_children(Operator::Or, vec![left, right])
+    }
+
+    /// Creates a logical not node.
+    pub fn not(operand: Node) -> Node {
+        Node::with_children(Operator::Not, vec![operand])
+    }
+
+    /// Creates a tuple node with the given elements.
+    pub fn tuple(elements: Vec<Node>) -> Node {
+        Node::with_children(Operator::Tuple, elements)
+    }
+
+    /// Creates an array node with the given elements.
+    pub fn array(elements: Vec<Node>) -> Node {
+        Node::with_children(Operator::Array, elements)
+    }
+
+    /// Creates a node that indexes into the given array or map.
+    pub fn index(collection: Node, index: Node) -> Node {
+        Node::with_children(Operator::Index, vec![collection, index])
+    }
+
+    /// Creates a lambda expression with the given parameters and body, like `|x, y| x * y`.
+    pub fn lambda(parameters: Vec<String>, body: Node) -> Node {
+        Node::with_children(Operator::lambda(parameters), vec![body])
+    }
+
+    /// Creates a chain node that evaluates the given expressions in order and results in the value of the last one.
+    pub fn chain(expressions: Vec<Node>) -> Node {
+        Node::with_children(Operator::Chain, expressions)
+    }
+
+    /// Creates a node with children whose amount is known to be correct for the operator.
+    fn with_children(operator: Operator, children: Vec<Node>) -> Node {
+        let mut node = Node::new(operator);
+        node.children = children;
+        node
+    }
+}
@@ -0,0 +1,351 @@
+use alloc::{borrow::Cow, collections::BTreeMap, sync::Arc, vec::Vec};
+use core::{
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+use crate::{
+    error::EvalexprResult,
+    function::builtin::IMPURE_BUILTIN_FUNCTION_IDENTIFIERS,
+    operator::{resolve_variable, Operator},
+    tree::options::EvalState,
+    value::Value,
+    Context, EvalOptions, Node,
+};
+
+/// A cache of the values of subexpressions for the memoized evaluation with `Node::eval_cached`.
+///
+/// A subexpression is identified by its structure, and its value is reused while the values of the variables it reads stay the same.
+/// So identical subexpressions share their cached value, even across different operator trees.
+/// For each subexpression, only the value of its most recent evaluation is kept.
+/// Cached values are looked up by hashes, but only reused if the structure and the variables of the subexpression are compared equal.
+///
+/// To decide which cached values can be reused, all variables read by an expression are resolved before it is evaluated,
+/// including those that are only read in branches that are not taken.
+/// So all lazy variables of a `LazyContext` that the expression reads are computed.
+///
+/// Only the values of subexpressions that call pure functions are cached.
+/// The builtin functions are pure, except for `random`, `random_int`, `random_choice` and `now`,
+/// and functions of the context are pure if they were marked with `Function::pure`.
+/// Calls of lambdas are never cached.
+/// The cache should be cleared if the pure functions of the context change.
+#[derive(Clone, Debug, Default)]
+pub struct EvalCache {
+    entries: BTreeMap<u64, CacheEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct CacheEntry {
+    key: CacheKey,
+    snapshot: Arc<Snapshot>,
+    value: Value,
+}
+
+/// Identifies the value of a subexpression by the hash of its structure and the hash of the values of the variables it reads,
+/// and locates the subexpression in the snapshot of its tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CacheKey {
+    structure: u64,
+    inputs: u64,
+    /// The nodes of the subexpression in `Snapshot::nodes`.
+    nodes: Range<usize>,
+    /// The inputs of the subexpression in `Snapshot::inputs`.
+    inputs_range: Range<usize>,
+}
+
+/// The structure of an evaluated operator tree and the inputs it read, against which the subexpressions of cached values are compared.
+#[derive(Debug, Default)]
+struct Snapshot {
+    /// The operators of the nodes in pre-order with their amounts of children, which determine each subtree uniquely.
+    nodes: Vec<(Operator, usize)>,
+    /// The inputs read by the nodes in pre-order.
+    inputs: Vec<Input>,
+}
+
+/// A value that a node depends on besides its structure.
+#[derive(Debug)]
+enum Input {
+    /// The value of a read variable, or `None` if the variable does not exist.
+    Variable(Option<Value>),
+    /// True if the builtin functions are disabled in the context of a function call.
+    BuiltinFunctionsDisabled(bool),
+}
+
+impl Input {
+    fn is_identical_to(&self, other: &Input) -> bool {
+        match (self, other) {
+            (Input::Variable(Some(a)), Input::Variable(Some(b))) => a.is_identical_to(b),
+            (Input::Variable(None), Input::Variable(None)) => true,
+            (Input::BuiltinFunctionsDisabled(a), Input::BuiltinFunctionsDisabled(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Snapshot {
+    /// Returns true if the subexpression with the given key in this snapshot is identical to the one with the other key in the other snapshot,
+    /// and read identical inputs.
+    fn is_identical_to(&self, key: &CacheKey, other: &Snapshot, other_key: &CacheKey) -> bool {
+        let nodes = &self.nodes[key.nodes.clone()];
+        let other_nodes = &other.nodes[other_key.nodes.clone()];
+        let inputs = &self.inputs[key.inputs_range.clone()];
+        let other_inputs = &other.inputs[other_key.inputs_range.clone()];
+        nodes == other_nodes
+            && inputs.len() == other_inputs.len()
+            && inputs
+                .iter()
+                .zip(other_inputs)
+                .all(|(input, other_input)| input.is_identical_to(other_input))
+    }
+}
+
+/// The keys of the nodes of an operator tree whose values can be cached, indexed by the addresses of the nodes,
+/// and the snapshot of the tree that the keys refer to.
+pub(crate) struct CacheKeys {
+    keys: BTreeMap<usize, CacheKey>,
+    snapshot: Arc<Snapshot>,
+}
+
+impl CacheKeys {
+    /// Returns the key of the given node, if its value can be cached.
+    pub(crate) fn get(&self, node: &Node) -> Option<&CacheKey> {
+        self.keys.get(&(node as *const Node as usize))
+    }
+}
+
+impl EvalCache {
+    /// Creates a new empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the amount of subexpressions whose value is cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no value is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all cached values.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn get(&self, key: &CacheKey, snapshot: &Snapshot) -> Option<&Value> {
+        self.entries
+            .get(&key.structure)
+            .filter(|entry| {
+                entry.key.inputs == key.inputs
+                    && entry.snapshot.is_identical_to(&entry.key, snapshot, key)
+            })
+            .map(|entry| &entry.value)
+    }
+
+    fn insert(&mut self, key: &CacheKey, snapshot: &Arc<Snapshot>, value: Value) {
+        self.entries.insert(
+            key.structure,
+            CacheEntry {
+                key: key.clone(),
+                snapshot: snapshot.clone(),
+                value,
+            },
+        );
+    }
+}
+
+/// The 64 bit FNV-1a hash function, which is available without `std`.
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl Node {
+    /// Evaluates the operator tree rooted at this node with the given context,
+    /// reusing the values of subexpressions cached by previous evaluations.
+    ///
+    /// Subexpressions whose variables still have the same values as when they were cached are not evaluated again,
+    /// which speeds up the repeated evaluation of large expressions where only a few variables change.
+    /// The values of evaluated subexpressions are stored in the cache, unless they call impure functions, see `EvalCache`.
+    ///
+    /// Fails, if one of the operators in the expression tree fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("sum_of_squares(a, b) + c").unwrap();
+    /// let mut context = context_map! {
+    ///     "a" => 3,
+    ///     "b" => 4,
+    ///     "c" => 0,
+    ///     "sum_of_squares" => Function::new(|argument| {
+    ///         let arguments = argument.as_fixed_len_tuple(2)?;
+    ///         let (a, b) = (arguments[0].as_int()?, arguments[1].as_int()?);
+    ///         Ok(Value::Int(a * a + b * b))
+    ///     }).pure(),
+    /// }.unwrap();
+    /// let mut cache = EvalCache::new();
+    ///
+    /// assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(25)));
+    /// context.set_value("c".into(), 1.into()).unwrap();
+    /// // The value of `sum_of_squares(a, b)` is taken from the cache.
+    /// assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(26)));
+    /// ```
+    pub fn eval_cached<C: Context>(
+        &self,
+        context: &C,
+        cache: &mut EvalCache,
+    ) -> EvalexprResult<Value> {
+        self.eval_cached_with_purity(context, cache).0
+    }
+
+    /// Evaluates like `eval_cached`, and additionally returns true if the value only depends on the values of the variables,
+    /// because the expression only calls pure functions.
+    pub(crate) fn eval_cached_with_purity<C: Context>(
+        &self,
+        context: &C,
+        cache: &mut EvalCache,
+    ) -> (EvalexprResult<Value>, bool) {
+        let mut keys = BTreeMap::new();
+        let mut snapshot = Snapshot::default();
+        let is_pure = self
+            .collect_cache_keys(context, true, &mut keys, &mut snapshot)
+            .is_some();
+        let keys = CacheKeys {
+            keys,
+            snapshot: Arc::new(snapshot),
+        };
+        let options = EvalOptions::default();
+        let result =
+            self.eval_with_state(context, &mut EvalState::with_cache(&options, cache, keys));
+        (result, is_pure)
+    }
+
+    /// Returns the cache key of this node, or `None` if its value depends on more than the values of the variables it reads.
+    /// If `record` is true, the keys of this node and its descendants whose values are worth caching are added to `keys`,
+    /// indexed by the addresses of the nodes.
+    /// The structure of this node and its inputs are added to the snapshot.
+    fn collect_cache_keys<C: Context>(
+        &self,
+        context: &C,
+        record: bool,
+        keys: &mut BTreeMap<usize, CacheKey>,
+        snapshot: &mut Snapshot,
+    ) -> Option<CacheKey> {
+        let mut structure = FnvHasher::default();
+        let mut inputs = FnvHasher::default();
+        let nodes_start = snapshot.nodes.len();
+        let inputs_start = snapshot.inputs.len();
+        self.operator().hash(&mut structure);
+        snapshot
+            .nodes
+            .push((self.operator().clone(), self.children().len()));
+        let mut is_pure = match self.operator() {
+            Operator::VariableIdentifierRead { identifier } => {
+                match resolve_variable(context, identifier) {
+                    Ok(value) => {
+                        value.is_some().hash(&mut inputs);
+                        if let Some(value) = &value {
+                            value.hash_identity(&mut inputs);
+                        }
+                        snapshot
+                            .inputs
+                            .push(Input::Variable(value.map(Cow::into_owned)));
+                        true
+                    },
+                    Err(_) => false,
+                }
+            },
+            Operator::FunctionIdentifier { identifier } => {
+                let builtin_functions_disabled = context.are_builtin_functions_disabled();
+                builtin_functions_disabled.hash(&mut inputs);
+                snapshot
+                    .inputs
+                    .push(Input::BuiltinFunctionsDisabled(builtin_functions_disabled));
+                is_pure_function_call(context, identifier)
+            },
+            _ => true,
+        };
+
+        // The children of let chains and lambdas are evaluated with additional variables,
+        // so their values cannot be cached, but they still determine the value of this node.
+        let record_children =
+            record && !self.is_let_chain() && !matches!(self.operator(), Operator::Lambda { .. });
+        for child in self.children() {
+            match child.collect_cache_keys(context, record_children, keys, snapshot) {
+                Some(key) => {
+                    key.structure.hash(&mut structure);
+                    key.inputs.hash(&mut inputs);
+                },
+                None => is_pure = false,
+            }
+        }
+
+        let key = CacheKey {
+            structure: structure.finish(),
+            inputs: inputs.finish(),
+            nodes: nodes_start..snapshot.nodes.len(),
+            inputs_range: inputs_start..snapshot.inputs.len(),
+        };
+        if is_pure && record && self.is_cacheable() {
+            keys.insert(self as *const Node as usize, key.clone());
+        }
+        is_pure.then_some(key)
+    }
+
+    /// Returns true if caching the value of this node can save work.
+    fn is_cacheable(&self) -> bool {
+        !self.children().is_empty() && !matches!(self.operator(), Operator::RootNode)
+    }
+
+    /// Evaluates this node with `evaluate`, unless its value is found in the cache of the given state.
+    pub(crate) fn eval_with_cache(
+        &self,
+        key: CacheKey,
+        state: &mut EvalState,
+        evaluate: impl FnOnce(&mut EvalState) -> EvalexprResult<Value>,
+    ) -> EvalexprResult<Value> {
+        if let Some(value) = state
+            .cache()
+            .and_then(|(cache, keys)| cache.get(&key, &keys.snapshot).cloned())
+        {
+            return Ok(value);
+        }
+
+        let value = evaluate(state)?;
+        if let Some((cache, keys)) = state.cache() {
+            cache.insert(&key, &keys.snapshot, value.clone());
+        }
+        Ok(value)
+    }
+}
+
+/// Returns true if calling the function with the given identifier always returns the same result for the same argument.
+fn is_pure_function_call<C: Context>(context: &C, identifier: &str) -> bool {
+    if context.has_function(identifier) {
+        context.is_pure_function(identifier)
+    } else {
+        // Lambdas are called with the variables of the context, so only builtin functions are left.
+        matches!(resolve_variable(context, identifier), Ok(None))
+            && !IMPURE_BUILTIN_FUNCTION_IDENTIFIERS.contains(&identifier)
+    }
+}
@@ -0,0 +1,207 @@
+use alloc::{borrow::Cow, boxed::Box, vec::Vec};
+
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    value::{FloatType, IntType, Value},
+    CompiledExpression, Context, Node,
+};
+
+/// A column of values that a variable is bound to for the vectorized evaluation with `CompiledExpression::eval_columns`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Column<'a> {
+    /// A column of integers.
+    Int(&'a [IntType]),
+    /// A column of floats.
+    Float(&'a [FloatType]),
+    /// A column of booleans.
+    Boolean(&'a [bool]),
+    /// A column of arbitrary values.
+    Value(&'a [Value]),
+}
+
+impl Column<'_> {
+    /// Returns the amount of rows in this column.
+    pub fn len(&self) -> usize {
+        match self {
+            Column::Int(column) => column.len(),
+            Column::Float(column) => column.len(),
+            Column::Boolean(column) => column.len(),
+            Column::Value(column) => column.len(),
+        }
+    }
+
+    /// Returns true if this column has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, row: usize) -> Value {
+        match self {
+            Column::Int(column) => Value::Int(column[row]),
+            Column::Float(column) => Value::Float(column[row]),
+            Column::Boolean(column) => Value::Boolean(column[row]),
+            Column::Value(column) => column[row].clone(),
+        }
+    }
+}
+
+impl<'a> From<&'a [IntType]> for Column<'a> {
+    fn from(column: &'a [IntType]) -> Self {
+        Column::Int(column)
+    }
+}
+
+impl<'a> From<&'a [FloatType]> for Column<'a> {
+    fn from(column: &'a [FloatType]) -> Self {
+        Column::Float(column)
+    }
+}
+
+impl<'a> From<&'a [bool]> for Column<'a> {
+    fn from(column: &'a [bool]) -> Self {
+        Column::Boolean(column)
+    }
+}
+
+impl<'a> From<&'a [Value]> for Column<'a> {
+    fn from(column: &'a [Value]) -> Self {
+        Column::Value(column)
+    }
+}
+
+/// The context in which one row of the columns is evaluated.
+/// The variables bound to columns shadow the variables of the wrapped context.
+struct RowContext<'a, C> {
+    identifiers: Vec<&'a str>,
+    values: Vec<Value>,
+    context: &'a C,
+}
+
+impl<C: Context> Context for RowContext<'_, C> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        match self
+            .identifiers
+            .iter()
+            .position(|bound| *bound == identifier)
+        {
+            Some(index) => Some(&self.values[index]),
+            None => self.context.get_value(identifier),
+        }
+    }
+
+    fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+        match self
+            .identifiers
+            .iter()
+            .position(|bound| *bound == identifier)
+        {
+            Some(index) => Ok(Some(Cow::Borrowed(&self.values[index]))),
+            None => self.context.try_get_value(identifier),
+        }
+    }
+
+    fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+        self.context.call_function(identifier, argument)
+    }
+
+    fn has_function(&self, identifier: &str) -> bool {
+        self.context.has_function(identifier)
+    }
+
+    fn is_pure_function(&self, identifier: &str) -> bool {
+        self.context.is_pure_function(identifier)
+    }
+
+    fn iter_variable_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(
+            self.identifiers
+                .iter()
+                .copied()
+                .chain(self.context.iter_variable_identifiers()),
+        )
+    }
+
+    fn iter_function_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        self.context.iter_function_identifiers()
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.context.are_builtin_functions_disabled()
+    }
+
+    fn set_builtin_functions_disabled(&mut self, _disabled: bool) -> EvalexprResult<()> {
+        Err(EvalexprError::ContextNotMutable)
+    }
+}
+
+impl CompiledExpression {
+    /// Evaluates the compiled expression once for each row of the given columns, and returns the results in order.
+    ///
+    /// Each column is bound to the variable with the given identifier, which has the value of the column in the current row.
+    /// The columns shadow the variables of the context, which is used for all other variables and functions.
+    /// Compared to evaluating the expression for each row with a context, this avoids to compile the expression
+    /// and to build a context for each row.
+    ///
+    /// Fails with `EvalexprError::ColumnLengthMismatch` if the columns have different lengths,
+    /// or if the evaluation of one of the rows fails.
+    /// Without columns, there are no rows, so the result is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let prices = [10.0, 20.0, 30.0];
+    /// let amounts = [1, 2, 3];
+    /// let context = context_map! { "discount" => 0.5 }.unwrap(); // Do proper error handling here
+    /// let compiled = build_operator_tree("price * amount * discount").unwrap().compile(); // Do proper error handling here
+    /// assert_eq!(
+    ///     compiled.eval_columns(&[("price", Column::Float(&prices)), ("amount", Column::Int(&amounts))], &context),
+    ///     Ok(vec![Value::from(5.0), Value::from(20.0), Value::from(45.0)])
+    /// );
+    /// ```
+    pub fn eval_columns<C: Context>(
+        &self,
+        columns: &[(&str, Column)],
+        context: &C,
+    ) -> EvalexprResult<Vec<Value>> {
+        let rows = columns.first().map_or(0, |(_, column)| column.len());
+        for (identifier, column) in columns {
+            if column.len() != rows {
+                return Err(EvalexprError::column_length_mismatch(
+                    identifier,
+                    rows,
+                    column.len(),
+                ));
+            }
+        }
+
+        let mut row_context = RowContext {
+            identifiers: columns.iter().map(|(identifier, _)| *identifier).collect(),
+            values: columns.iter().map(|_| Value::Empty).collect(),
+            context,
+        };
+        let mut stack = Vec::new();
+        let mut results = Vec::with_capacity(rows);
+        for row in 0..rows {
+            for (value, (_, column)) in row_context.values.iter_mut().zip(columns) {
+                *value = column.get(row);
+            }
+            results.push(self.eval_with_stack(&row_context, &mut stack)?);
+        }
+        Ok(results)
+    }
+}
+
+impl Node {
+    /// Evaluates the operator tree rooted at this node once for each row of the given columns.
+    ///
+    /// The tree is compiled once before the evaluation, see `CompiledExpression::eval_columns` for details.
+    pub fn eval_columns<C: Context>(
+        &self,
+        columns: &[(&str, Column)],
+        context: &C,
+    ) -> EvalexprResult<Vec<Value>> {
+        self.compile().eval_columns(columns, context)
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{
+    error::EvalexprResult, operator::Operator, token::Span, value::Value, Context, EmptyContext,
+    Lambda, Node,
+};
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use super::is_builtin_if_used;
+
+/// An operator tree compiled into a flat sequence of instructions for a stack machine.
+///
+/// Evaluating a compiled expression does not need to walk the tree recursively or to allocate a vector of arguments for each node,
+/// which makes it faster than evaluating the `Node` it was compiled from if the expression is evaluated many times.
+/// It is created with `Node::compile`, and evaluates to the same results as `Node::eval_with_context`.
+/// The instructions are shared between clones, so cloning a compiled expression is cheap.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let compiled = build_operator_tree("a * 2 + max(a, 3)").unwrap().compile(); // Do proper error handling here
+/// let mut context = HashMapContext::new();
+/// for a in 0..5 {
+///     context.set_value("a".into(), a.into()).unwrap(); // Do proper error handling here
+///     assert_eq!(compiled.eval_with_context(&context), Ok(Value::from(a * 2 + a.max(3))));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledExpression {
+    instructions: Arc<[Instruction]>,
+    /// True if the expression contains calls of the function `if`, which are evaluated lazily if they call the builtin `if`.
+    contains_if: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Instruction {
+    /// Pushes a constant value.
+    Push(Value),
+    /// Pops the given amount of arguments, applies the operator to them and pushes the result.
+    /// The span of the node of the operator is added to the errors of the operator, like when evaluating the node.
+    Apply {
+        operator: Operator,
+        arguments: usize,
+        span: Option<Span>,
+    },
+    /// Replaces the boolean on top of the stack by the given value and jumps to the target if they are equal.
+    /// Otherwise the boolean is kept as the left operand of a logical operator.
+    ShortCircuit { value: bool, target: usize },
+    /// Pops the two topmost operands and compares them with the operator.
+    /// If the comparison is true, the right operand is pushed as the left operand of the next comparison in the chain.
+    /// Otherwise, `false` is pushed and the rest of the chain is skipped by jumping to the target.
+    Compare { operator: Operator, target: usize },
+    /// Replaces the value on top of the stack by the given value.
+    Replace(Value),
+    /// Pops the condition of a call of `if` and jumps to the target if it is false.
+    /// Does nothing if `if` is not the builtin function, so all arguments of the call are evaluated.
+    IfCondition { target: usize },
+    /// Jumps to the target after the first branch of a call of `if`, unless `if` is not the builtin function.
+    IfThen { target: usize },
+    /// Calls the function `if` with all three arguments if it is not the builtin function.
+    IfElse,
+    /// Evaluates the node with the context and pushes the result.
+    /// Chains with let-bindings are evaluated like this, as the bound variables are not part of the context.
+    Eval(Node),
+}
+
+impl Node {
+    /// Compiles the operator tree rooted at this node into a `CompiledExpression`.
+    ///
+    /// See the documentation of `CompiledExpression` for more details.
+    pub fn compile(&self) -> CompiledExpression {
+        let mut instructions = Vec::new();
+        self.compile_into(&mut instructions);
+        let contains_if = instructions
+            .iter()
+            .any(|instruction| matches!(instruction, Instruction::IfElse));
+        CompiledExpression {
+            instructions: instructions.into(),
+            contains_if,
+        }
+    }
+
+    fn compile_into(&self, instructions: &mut Vec<Instruction>) {
+        if let Some([condition, if_true, if_false]) = self.if_arguments() {
+            condition.compile_into(instructions);
+            let condition_index = instructions.len();
+            instructions.push(Instruction::IfCondition { target: 0 });
+            if_true.compile_into(instructions);
+            let then_index = instructions.len();
+            instructions.push(Instruction::IfThen { target: 0 });
+            instructions[condition_index] = Instruction::IfCondition {
+                target: instructions.len(),
+            };
+            if_false.compile_into(instructions);
+            instructions.push(Instruction::IfElse);
+            instructions[then_index] = Instruction::IfThen {
+                target: instructions.len(),
+            };
+        } else if let Some((operands, comparisons)) = self.comparison_chain() {
+            operands[0].compile_into(instructions);
+            let mut compare_indices = Vec::new();
+            for (operand, comparison) in operands[1..].iter().zip(comparisons) {
+                operand.compile_into(instructions);
+                compare_indices.push(instructions.len());
+                instructions.push(Instruction::Compare {
+                    operator: comparison.clone(),
+                    target: 0,
+                });
+            }
+            instructions.push(Instruction::Replace(Value::Boolean(true)));
+            let end = instructions.len();
+            for index in compare_indices {
+                if let Instruction::Compare { target, .. } = &mut instructions[index] {
+                    *target = end;
+                }
+            }
+        } else if let Some(short_circuit_value) = self.short_circuit_value() {
+            self.children[0].compile_into(instructions);
+            let short_circuit_index = instructions.len();
+            instructions.push(Instruction::ShortCircuit {
+                value: short_circuit_value,
+                target: 0,
+            });
+            self.children[1].compile_into(instructions);
+            instructions.push(Instruction::Apply {
+                operator: self.operator.clone(),
+                arguments: 2,
+                span: self.span(),
+            });
+            instructions[short_circuit_index] = Instruction::ShortCircuit {
+                value: short_circuit_value,
+                target: instructions.len(),
+            };
+        } else {
+            match (self.operator(), self.children.as_slice()) {
+                (Operator::Const { value }, []) => {
+                    instructions.push(Instruction::Push(value.clone()))
+                },
+                (Operator::RootNode, []) => instructions.push(Instruction::Push(Value::Empty)),
+                (Operator::Chain, _) if self.is_let_chain() => {
+                    instructions.push(Instruction::Eval(self.clone()))
+                },
+                // Suppressing errors of the left operand needs the tree.
+                (Operator::NullCoalesce, [_, _]) => {
+                    instructions.push(Instruction::Eval(self.clone()))
+                },
+                (Operator::Lambda { parameters }, [body]) => instructions.push(Instruction::Push(
+                    Lambda::new(parameters.clone(), body.clone()).into(),
+                )),
+                // Lambdas without a body fail like when evaluating the tree.
+                (Operator::Lambda { .. }, _) => instructions.push(Instruction::Eval(self.clone())),
+                // Root nodes with one child evaluate to the value of their child.
+                (Operator::RootNode, [child]) => child.compile_into(instructions),
+                (operator, children) => {
+                    for child in children {
+                        child.compile_into(instructions);
+                    }
+                    instructions.push(Instruction::Apply {
+                        operator: operator.clone(),
+                        arguments: children.len(),
+                        span: self.span(),
+                    });
+                },
+            }
+        }
+    }
+}
+
+impl CompiledExpression {
+    /// Evaluates the compiled expression with the given context.
+    ///
+    /// Fails, if one of the operators in the expression fails.
+    pub fn eval_with_context<C: Context>(&self, context: &C) -> EvalexprResult<Value> {
+        self.eval_with_stack(context, &mut Vec::new())
+    }
+
+    /// Evaluates the compiled expression using the given stack, which allows to reuse its allocation for repeated evaluations.
+    pub(crate) fn eval_with_stack<C: Context>(
+        &self,
+        context: &C,
+        stack: &mut Vec<Value>,
+    ) -> EvalexprResult<Value> {
+        let lazy_if = self.contains_if && is_builtin_if_used(context);
+        stack.clear();
+        let mut index = 0;
+
+        while let Some(instruction) = self.instructions.get(index) {
+            index += 1;
+            match instruction {
+                Instruction::Push(value) => stack.push(value.clone()),
+                Instruction::Apply {
+                    operator,
+                    arguments,
+                    span,
+                } => {
+                    let arguments_start = stack.len() - arguments;
+                    let result = operator
+                        .eval(&stack[arguments_start..], context)
+                        .map_err(|error| error.with_span(*span))?;
+                    stack.truncate(arguments_start);
+                    stack.push(result);
+                },
+                Instruction::ShortCircuit { value, target } => {
+                    let left = stack.last_mut().expect("the left operand is on the stack");
+                    if left.as_boolean()? == *value {
+                        *left = Value::Boolean(*value);
+                        index = *target;
+                    }
+                },
+                Instruction::Compare { operator, target } => {
+                    let right = stack.pop().expect("the right operand is on the stack");
+                    let left = stack.pop().expect("the left operand is on the stack");
+                    let arguments = [left, right];
+                    // Comparisons do not access the context.
+                    if operator.eval(&arguments, &EmptyContext)?.as_boolean()? {
+                        let [_, right] = arguments;
+                        stack.push(right);
+                    } else {
+                        stack.push(Value::Boolean(false));
+                        index = *target;
+                    }
+                },
+                Instruction::Replace(value) => {
+                    if let Some(top) = stack.last_mut() {
+                        *top = value.clone();
+                    }
+                },
+                Instruction::IfCondition { target } => {
+                    if lazy_if {
+                        let condition = stack.pop().expect("the condition is on the stack");
+                        if !condition.as_boolean()? {
+                            index = *target;
+                        }
+                    }
+                },
+                Instruction::IfThen { target } => {
+                    if lazy_if {
+                        index = *target;
+                    }
+                },
+                Instruction::IfElse => {
+                    if !lazy_if {
+                        let arguments_start = stack.len() - 3;
+                        let arguments = vec![Value::Tuple(stack.split_off(arguments_start))];
+                        let function = Operator::FunctionIdentifier {
+                            identifier: "if".into(),
+                        };
+                        stack.push(function.eval(&arguments, context)?);
+                    }
+                },
+                Instruction::Eval(node) => stack.push(node.eval_with_context(context)?),
+            }
+        }
+
+        Ok(stack.pop().unwrap_or(Value::Empty))
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{
+    error::ErrorRecovery,
+    function::builtin::BUILTIN_FUNCTION_IDENTIFIERS,
+    token::{self, Span, Token},
+    tree::{Group, ParserConfig, ParserState},
+    Context,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The operators that can follow a value.
+const BINARY_OPERATORS: &[&str] = &[
+    "+", "-", "*", "/", "%", "^", "==", "!=", ">", "<", ">=", "<=", "&&", "||", "??", "in", "..",
+    "..=", ",", ";",
+];
+
+/// The operators that can follow an identifier that is assigned to.
+const ASSIGNMENT_OPERATORS: &[&str] = &["=", "+=", "-=", "*=", "/=", "%=", "^=", "&&=", "||="];
+
+/// The operators that can start a value.
+const PREFIX_OPERATORS: &[&str] = &["-", "!", "(", "["];
+
+/// The keywords that can start a value.
+const VALUE_KEYWORDS: &[&str] = &["true", "false", "let"];
+
+/// The kind of a completion candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompletionKind {
+    /// A variable of the context.
+    Variable,
+    /// A function of the context or a builtin function.
+    Function,
+    /// A keyword like `true` or `let`.
+    Keyword,
+    /// An operator or a bracket.
+    Operator,
+}
+
+/// A candidate to complete an expression at the cursor position with, as returned by `complete`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Completion {
+    /// The text to insert.
+    pub text: String,
+    /// The kind of the inserted text.
+    pub kind: CompletionKind,
+    /// The part of the expression string that is replaced by the text.
+    /// It covers the part of an identifier or keyword that was typed before the cursor, or is empty at the cursor.
+    pub span: Span,
+}
+
+/// Returns the completion candidates for the expression `string` at the byte offset `cursor`.
+pub(crate) fn completions<C: Context + ?Sized>(
+    string: &str,
+    cursor: usize,
+    context: &C,
+) -> Vec<Completion> {
+    let Some(before_cursor) = string.get(..cursor) else {
+        return Vec::new();
+    };
+    if is_within_literal_or_comment(before_cursor) {
+        return Vec::new();
+    }
+
+    let config = ParserConfig::new();
+    let mut recovery = ErrorRecovery::recovering();
+    // When recovering, errors are recorded instead of being returned.
+    let mut tokens =
+        token::tokenize_with_recovery(before_cursor, &config, &mut recovery).unwrap_or_default();
+
+    // A word directly before the cursor is the prefix of the completed identifier or keyword.
+    let mut span = Span::new(cursor, cursor);
+    if let Some((_, last_span)) = tokens.last() {
+        let text = &before_cursor[last_span.start..last_span.end];
+        if last_span.end == cursor && text.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            span = *last_span;
+            tokens.pop();
+        }
+    }
+    let prefix = &before_cursor[span.start..];
+
+    let mut state = ParserState::new();
+    for (index, (token, token_span)) in tokens.iter().enumerate() {
+        let next = tokens.get(index + 1).map(|(token, _)| token);
+        let snapshot = state.clone();
+        if state
+            .parse_token(token, *token_span, next, &config)
+            .is_err()
+        {
+            state = snapshot;
+            state.skip_closing_token(token);
+        }
+    }
+
+    let mut candidates: Vec<(CompletionKind, &str)> = Vec::new();
+    if state.last_token_is_rightsided_value {
+        candidates.extend(
+            BINARY_OPERATORS
+                .iter()
+                .map(|operator| (CompletionKind::Operator, *operator)),
+        );
+        if let Some((Token::Identifier(_), _)) = tokens.last() {
+            candidates.extend(
+                ASSIGNMENT_OPERATORS
+                    .iter()
+                    .chain(&["(", "["])
+                    .map(|operator| (CompletionKind::Operator, *operator)),
+            );
+        }
+        if let Some((group, _)) = state.open_groups.last() {
+            let closing = match group {
+                Group::Parentheses => ")",
+                Group::Array | Group::Index => "]",
+            };
+            candidates.push((CompletionKind::Operator, closing));
+        }
+    } else {
+        candidates.extend(
+            context
+                .iter_variable_identifiers()
+                .map(|identifier| (CompletionKind::Variable, identifier)),
+        );
+        candidates.extend(
+            context
+                .iter_function_identifiers()
+                .map(|identifier| (CompletionKind::Function, identifier)),
+        );
+        if !context.are_builtin_functions_disabled() {
+            candidates.extend(
+                BUILTIN_FUNCTION_IDENTIFIERS
+                    .iter()
+                    .map(|identifier| (CompletionKind::Function, *identifier)),
+            );
+        }
+        candidates.extend(
+            VALUE_KEYWORDS
+                .iter()
+                .map(|keyword| (CompletionKind::Keyword, *keyword)),
+        );
+        candidates.extend(
+            PREFIX_OPERATORS
+                .iter()
+                .map(|operator| (CompletionKind::Operator, *operator)),
+        );
+    }
+
+    candidates.retain(|(_, text)| text.starts_with(prefix));
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+        .into_iter()
+        .map(|(kind, text)| Completion {
+            text: text.to_string(),
+            kind,
+            span,
+        })
+        .collect()
+}
+
+/// Returns true if the given beginning of an expression ends within a string literal or a comment.
+fn is_within_literal_or_comment(string: &str) -> bool {
+    let mut iter = string.chars().peekable();
+    while let Some(c) = iter.next() {
+        match c {
+            '"' | '\'' => loop {
+                match iter.next() {
+                    Some('\\') => {
+                        iter.next();
+                    },
+                    Some(next) if next == c => break,
+                    Some(_) => {},
+                    None => return true,
+                }
+            },
+            '/' if iter.next_if_eq(&'/').is_some() => {
+                // Line comments end at the next newline
+                let Some(_) = iter.find(|next| *next == '\n') else {
+                    return true;
+                };
+            },
+            '/' if iter.next_if_eq(&'*').is_some() => {
+                let mut last_was_star = false;
+                loop {
+                    match iter.next() {
+                        Some('/') if last_was_star => break,
+                        Some(next) => last_was_star = next == '*',
+                        None => return true,
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    false
+}
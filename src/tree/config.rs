@@ -0,0 +1,182 @@
+use super::EvaluationPolicy;
+
+/// Configuration of the parser used by `build_operator_tree_with_config`.
+///
+/// The default configuration is used by `build_operator_tree` and all `eval` functions that take an expression string.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let config = ParserConfig::new().with_max_depth(5);
+/// assert!(build_operator_tree_with_config("((1))", &config).is_ok());
+/// assert_eq!(
+///     build_operator_tree_with_config("((((1))))", &config),
+///     Err(EvalexprError::RecursionLimitExceeded { max_depth: 5 })
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParserConfig {
+    max_depth: usize,
+    keyword_operators: bool,
+    angle_bracket_neq: bool,
+    left_associative_exponentiation: bool,
+    #[cfg(feature = "decimal")]
+    decimal_literals: bool,
+    policy: Option<EvaluationPolicy>,
+}
+
+impl ParserConfig {
+    /// The maximum nesting depth of parsed expressions in the default configuration.
+    pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+    /// Creates the default configuration.
+    pub fn new() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            keyword_operators: false,
+            angle_bracket_neq: false,
+            left_associative_exponentiation: false,
+            #[cfg(feature = "decimal")]
+            decimal_literals: false,
+            policy: None,
+        }
+    }
+
+    /// Sets the maximum syntactic nesting depth of parsed expressions.
+    ///
+    /// The whole expression is on the first level, and every pair of parentheses or brackets, function call and unary operator adds one level.
+    /// Operands that are nested to the right, like the right operand of an operator with a higher precedence or of a right-associative operator like `^` or `=`, add one level each as well.
+    /// Chains of left-associative operators like `1 + 2 + 3` do not increase the depth, no matter how long they are.
+    /// Parsing an expression that is nested deeper fails with `EvalexprError::RecursionLimitExceeded`.
+    ///
+    /// Parsing works recursively, so the limit protects against stack overflows caused by deeply nested untrusted input.
+    /// Operator trees built with the builder functions of `Node` or with `Node::from_binary` are not checked.
+    /// To limit the depth of the whole operator tree, which evaluation recurses into, use `EvaluationPolicy::with_max_depth`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the maximum nesting depth of parsed expressions.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets whether the keywords `and`, `or` and `not` are parsed as the logical operators `&&`, `||` and `!`.
+    /// By default, they are parsed as identifiers.
+    ///
+    /// If enabled, variables and functions cannot be named `and`, `or` or `not` anymore.
+    /// The keywords are case-sensitive, so e.g. `AND` is still an identifier.
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let config = ParserConfig::new().with_keyword_operators(true);
+    /// let tree = build_operator_tree_with_config("not (1 > 2 and 3 > 4) or false", &config).unwrap();
+    /// assert_eq!(tree.eval(), Ok(Value::from(true)));
+    /// assert_eq!(tree.to_string(), "!(1 > 2 && 3 > 4) || false");
+    /// ```
+    pub fn with_keyword_operators(mut self, keyword_operators: bool) -> Self {
+        self.keyword_operators = keyword_operators;
+        self
+    }
+
+    /// Returns whether the keywords `and`, `or` and `not` are parsed as logical operators.
+    pub fn keyword_operators(&self) -> bool {
+        self.keyword_operators
+    }
+
+    /// Sets whether `<>` is parsed as the not-equal operator `!=`.
+    /// By default, `<>` is parsed as the two separate operators `<` and `>`.
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let config = ParserConfig::new().with_angle_bracket_neq(true);
+    /// let tree = build_operator_tree_with_config("1 <> 2", &config).unwrap();
+    /// assert_eq!(tree.eval(), Ok(Value::from(true)));
+    /// ```
+    pub fn with_angle_bracket_neq(mut self, angle_bracket_neq: bool) -> Self {
+        self.angle_bracket_neq = angle_bracket_neq;
+        self
+    }
+
+    /// Returns whether `<>` is parsed as the not-equal operator.
+    pub fn angle_bracket_neq(&self) -> bool {
+        self.angle_bracket_neq
+    }
+
+    /// Sets whether chains of the exponentiation operator `^` are parsed left-to-right.
+    /// By default, they are parsed right-to-left like in mathematical notation, so `2 ^ 3 ^ 2` is parsed as `2 ^ (3 ^ 2)`.
+    ///
+    /// Before exponentiation was right-associative, `2 ^ 3 ^ 2` was parsed as `(2 ^ 3) ^ 2`.
+    /// Enabling this restores that behavior for expressions that rely on it.
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// assert_eq!(eval("2 ^ 3 ^ 2"), Ok(Value::from(512.0)));
+    /// let config = ParserConfig::new().with_left_associative_exponentiation(true);
+    /// let tree = build_operator_tree_with_config("2 ^ 3 ^ 2", &config).unwrap();
+    /// assert_eq!(tree.eval(), Ok(Value::from(64.0)));
+    /// assert_eq!(tree.to_string(), "(2 ^ 3) ^ 2");
+    /// ```
+    pub fn with_left_associative_exponentiation(
+        mut self,
+        left_associative_exponentiation: bool,
+    ) -> Self {
+        self.left_associative_exponentiation = left_associative_exponentiation;
+        self
+    }
+
+    /// Returns whether chains of the exponentiation operator `^` are parsed left-to-right.
+    pub fn left_associative_exponentiation(&self) -> bool {
+        self.left_associative_exponentiation
+    }
+
+    /// Sets whether literals with a decimal point or an exponent are parsed as `Value::Decimal` instead of `Value::Float`.
+    /// By default, they are parsed as floats.
+    ///
+    /// Literals that cannot be represented as a decimal, like `1e100`, are still parsed as floats.
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let config = ParserConfig::new().with_decimal_literals(true);
+    /// let tree = build_operator_tree_with_config("0.1 + 0.2 == 0.3", &config).unwrap();
+    /// assert_eq!(tree.eval(), Ok(Value::from(true)));
+    /// ```
+    #[cfg(feature = "decimal")]
+    pub fn with_decimal_literals(mut self, decimal_literals: bool) -> Self {
+        self.decimal_literals = decimal_literals;
+        self
+    }
+
+    /// Returns whether literals with a decimal point or an exponent are parsed as `Value::Decimal`.
+    #[cfg(feature = "decimal")]
+    pub fn decimal_literals(&self) -> bool {
+        self.decimal_literals
+    }
+
+    /// Sets an `EvaluationPolicy` that parsed operator trees must comply with, or removes it if `None` is given.
+    /// By default, there is no policy.
+    ///
+    /// Parsing an expression that does not comply with the policy fails with the error of the violated restriction.
+    pub fn with_policy(mut self, policy: Option<EvaluationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the `EvaluationPolicy` that parsed operator trees must comply with, if any.
+    pub fn policy(&self) -> Option<&EvaluationPolicy> {
+        self.policy.as_ref()
+    }
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,12 +1,437 @@
-use crate::Node;
-use std::fmt::{Display, Error, Formatter};
+use crate::{
+    operator::Operator,
+    value::{IntType, Value},
+    Node,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+};
+use core::fmt::{Display, Error, Formatter};
 
+/// Displays the node as an expression string that can be parsed again with `build_operator_tree`.
+///
+/// The output is a canonical form of the expression: parentheses are only written where the precedence of the operators requires them,
+/// and function arguments are always enclosed in parentheses.
 impl Display for Node {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        self.operator.fmt(f)?;
-        for child in self.children() {
-            write!(f, " {}", child)?;
+        write_node(self.unwrap_root_nodes(), f)
+    }
+}
+
+impl Node {
+    /// Returns a representation of this operator tree in the DOT language of [Graphviz](https://graphviz.org/).
+    ///
+    /// Each node of the tree is labelled with its operator, and edges point from each node to its children in order.
+    /// Root nodes, which group subexpressions like parentheses do, are labelled with `()`.
+    /// Rendering the graph, for example with `dot -Tsvg`, shows how an expression was parsed, which helps to debug the precedence of operators.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("1 + 2 * 3").unwrap(); // Do proper error handling here
+    /// assert_eq!(
+    ///     tree.to_dot(),
+    ///     "digraph {
+    ///     n0 [label=\"()\"];
+    ///     n0 -> n1;
+    ///     n1 [label=\"+\"];
+    ///     n1 -> n2;
+    ///     n1 -> n3;
+    ///     n2 [label=\"1\"];
+    ///     n3 [label=\"*\"];
+    ///     n3 -> n4;
+    ///     n3 -> n5;
+    ///     n4 [label=\"2\"];
+    ///     n5 [label=\"3\"];
+    /// }
+    /// "
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        // Iterate instead of recursing, as deep trees can overflow the stack.
+        let mut stack = vec![(self, 0)];
+        let mut next_id = 1;
+        while let Some((node, id)) = stack.pop() {
+            dot.push_str(&format!(
+                "    n{} [label=\"{}\"];\n",
+                id,
+                escape_dot_label(&dot_label(node.operator()))
+            ));
+            let child_ids = next_id..next_id + node.children().len();
+            for child_id in child_ids.clone() {
+                dot.push_str(&format!("    n{} -> n{};\n", id, child_id));
+            }
+            next_id = child_ids.end;
+            stack.extend(node.children().iter().zip(child_ids).rev());
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Skips over root nodes with a single child, as their only purpose is to group their child.
+    fn unwrap_root_nodes(&self) -> &Node {
+        let mut node = self;
+        while node.operator() == &Operator::RootNode && node.children().len() == 1 {
+            node = &node.children()[0];
+        }
+        node
+    }
+
+    /// Returns the precedence that determines if the displayed node needs to be enclosed in parentheses.
+    fn display_precedence(&self) -> i32 {
+        match self.operator() {
+            // Negative numbers are displayed with a leading minus, so they behave like a negation.
+            Operator::Const {
+                value: Value::Int(int),
+            } if *int < 0 => Operator::Neg.precedence(),
+            #[cfg(feature = "bigint")]
+            Operator::Const {
+                value: Value::BigInt(int),
+            } if int.sign() == num_bigint::Sign::Minus => Operator::Neg.precedence(),
+            #[cfg(feature = "decimal")]
+            Operator::Const {
+                value: Value::Decimal(decimal),
+            } if decimal.is_sign_negative() => Operator::Neg.precedence(),
+            Operator::Const {
+                value: Value::Float(float),
+            } if float.is_sign_negative() || !float.is_finite() => Operator::Neg.precedence(),
+            // Tuples with less than two elements are displayed as slices.
+            Operator::Tuple if self.children().len() < 2 => {
+                Operator::Slice { inclusive: false }.precedence()
+            },
+            operator => operator.precedence(),
+        }
+    }
+}
+
+/// Returns the label of a node with the given operator in the DOT representation of a tree.
+fn dot_label(operator: &Operator) -> String {
+    match operator {
+        Operator::RootNode => "()".to_string(),
+        Operator::Let => "let".to_string(),
+        Operator::Array => "array".to_string(),
+        operator => operator.to_string().trim().to_string(),
+    }
+}
+
+/// Escapes the characters of a label that have a special meaning in quoted strings of the DOT language.
+fn escape_dot_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
         }
-        Ok(())
+    }
+    escaped
+}
+
+fn write_node(node: &Node, f: &mut Formatter) -> Result<(), Error> {
+    use crate::operator::Operator::*;
+    let operator = node.operator();
+    let children = node.children();
+
+    match operator {
+        RootNode => {
+            if children.is_empty() {
+                write!(f, "()")
+            } else {
+                // Root nodes with more than one child are invalid, but display them in a readable way anyways.
+                write!(f, "(")?;
+                write_separated(children, ", ", f)?;
+                write!(f, ")")
+            }
+        },
+        Const { value } => write_value(value, f),
+        VariableIdentifierWrite { identifier } | VariableIdentifierRead { identifier } => {
+            write!(f, "{}", identifier)
+        },
+        FunctionIdentifier { identifier } => {
+            write!(f, "{}(", identifier)?;
+            if let Some(argument) = children.first() {
+                let argument = argument.unwrap_root_nodes();
+                if argument.operator() != &RootNode || !argument.children().is_empty() {
+                    write_node(argument, f)?;
+                }
+            }
+            write!(f, ")")
+        },
+        Tuple if children.len() < 2 => write_short_tuple(
+            children.first().map(Node::unwrap_root_nodes),
+            f,
+            |child, f| write_child(child, child.display_precedence() <= Tuple.precedence(), f),
+        ),
+        Tuple | Chain => {
+            let separator = operator.to_string();
+            let separator = separator.trim();
+            for (index, child) in children.iter().enumerate() {
+                if index > 0 {
+                    write!(f, "{}", separator)?;
+                }
+
+                let child = child.unwrap_root_nodes();
+                if operator == &Chain
+                    && child.operator() == &RootNode
+                    && child.children().is_empty()
+                {
+                    // Empty statements are created by trailing semicolons.
+                    continue;
+                }
+                if index > 0 {
+                    write!(f, " ")?;
+                }
+                write_child(
+                    child,
+                    child.display_precedence() <= operator.precedence(),
+                    f,
+                )?;
+            }
+            Ok(())
+        },
+        Lambda { .. } => {
+            write!(f, "{} ", operator)?;
+            if let Some(body) = children.first() {
+                let body = body.unwrap_root_nodes();
+                write_child(body, body.display_precedence() < operator.precedence(), f)?;
+            }
+            Ok(())
+        },
+        Array => {
+            write!(f, "[")?;
+            for (index, child) in children.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                // Tuples and chains inside of an array literal would be parsed as multiple elements.
+                let child = child.unwrap_root_nodes();
+                write_child(child, child.display_precedence() <= Tuple.precedence(), f)?;
+            }
+            write!(f, "]")
+        },
+        Index => {
+            if let Some(collection) = children.first() {
+                let collection = collection.unwrap_root_nodes();
+                write_child(
+                    collection,
+                    collection.display_precedence() < operator.precedence(),
+                    f,
+                )?;
+            }
+            write!(f, "[")?;
+            if let Some(index) = children.get(1) {
+                let index = index.unwrap_root_nodes();
+                // A range directly inside of the brackets would be parsed as a slice.
+                write_child(index, matches!(index.operator(), Range { .. }), f)?;
+            }
+            write!(f, "]")
+        },
+        Range { .. } => write_range(children, operator, f),
+        Slice { inclusive } => {
+            if let Some(collection) = children.first() {
+                let collection = collection.unwrap_root_nodes();
+                write_child(
+                    collection,
+                    collection.display_precedence() < operator.precedence(),
+                    f,
+                )?;
+            }
+            write!(f, "[")?;
+            let range = Range {
+                inclusive: *inclusive,
+            };
+            write_range(children.get(1..).unwrap_or_default(), &range, f)?;
+            write!(f, "]")
+        },
+        Neg | Pos | Not => {
+            write!(f, "{}", operator)?;
+            if let Some(child) = children.first() {
+                let child = child.unwrap_root_nodes();
+                // A unary operator binds to the operand that directly follows it,
+                // so everything but values, function calls, arrays, indexing and other unary operators needs parentheses.
+                // Lambdas need parentheses as well, as their body would extend over the operators following the unary operator.
+                let needs_parentheses = !(child.operator().is_leaf()
+                    || (child.operator().is_unary() && !matches!(child.operator(), Lambda { .. }))
+                    || matches!(child.operator(), RootNode | Array | Index | Slice { .. }));
+                write_child(child, needs_parentheses, f)?;
+            }
+            Ok(())
+        },
+        _ => {
+            let precedence = operator.precedence();
+            let spaced = operator.to_string();
+            let symbol = spaced.trim();
+
+            if *operator == Let {
+                write!(f, "let ")?;
+            }
+            if let Some(left) = children.first() {
+                let is_grouped = left.operator() == &RootNode;
+                let left = left.unwrap_root_nodes();
+                let left_precedence = left.display_precedence();
+                write_child(
+                    left,
+                    left_precedence < precedence
+                        || (left_precedence == precedence && !operator.is_left_to_right())
+                        // Without parentheses, the comparisons would be parsed as a comparison chain.
+                        || (is_grouped
+                            && operator.is_ordering_comparison()
+                            && left.operator().is_ordering_comparison()),
+                    f,
+                )?;
+            }
+            write!(f, " {} ", symbol)?;
+            if let Some(right) = children.get(1) {
+                let right = right.unwrap_root_nodes();
+                let right_precedence = right.display_precedence();
+                // Assigned lambdas extend to the end of the assignment anyways.
+                let is_assigned_lambda = operator.precedence() == Assign.precedence()
+                    && matches!(right.operator(), Lambda { .. });
+                write_child(
+                    right,
+                    !is_assigned_lambda
+                        && (right_precedence < precedence
+                            || (right_precedence == precedence && operator.is_left_to_right())),
+                    f,
+                )?;
+            }
+            Ok(())
+        },
+    }
+}
+
+/// Writes the bounds of a range, or of a slice, separated by the given range operator.
+fn write_range(bounds: &[Node], range: &Operator, f: &mut Formatter) -> Result<(), Error> {
+    for (index, bound) in bounds.iter().enumerate() {
+        if index > 0 {
+            write!(f, "{}", range)?;
+        }
+        // Ranges are neither left- nor right-associative, so bounds of the same precedence need parentheses on both sides.
+        let bound = bound.unwrap_root_nodes();
+        write_child(bound, bound.display_precedence() <= range.precedence(), f)?;
+    }
+    Ok(())
+}
+
+fn write_child(child: &Node, parenthesize: bool, f: &mut Formatter) -> Result<(), Error> {
+    if parenthesize {
+        write!(f, "(")?;
+        write_node(child, f)?;
+        write!(f, ")")
+    } else {
+        write_node(child, f)
+    }
+}
+
+fn write_separated(children: &[Node], separator: &str, f: &mut Formatter) -> Result<(), Error> {
+    for (index, child) in children.iter().enumerate() {
+        if index > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write_node(child.unwrap_root_nodes(), f)?;
+    }
+    Ok(())
+}
+
+/// Writes a tuple with at most one element.
+///
+/// There is no literal for such tuples, as `(x)` is parsed as `x`, `()` as the empty value and `(x,)` as a tuple of `x` and the empty value.
+/// So they are written as a slice of a tuple with two elements, like `(x, ())[0..1]`.
+fn write_short_tuple<T>(
+    element: Option<&T>,
+    f: &mut Formatter,
+    write_element: impl Fn(&T, &mut Formatter) -> Result<(), Error>,
+) -> Result<(), Error> {
+    write!(f, "(")?;
+    match element {
+        Some(element) => {
+            write_element(element, f)?;
+            write!(f, ", ())[0..1]")
+        },
+        None => write!(f, "(), ())[0..0]"),
+    }
+}
+
+/// Writes a value such that the tokenizer parses it as the same value again.
+fn write_value(value: &Value, f: &mut Formatter) -> Result<(), Error> {
+    match value {
+        Value::String(string) => {
+            write!(f, "\"")?;
+            for c in string.chars() {
+                if c == '"' || c == '\\' {
+                    write!(f, "\\")?;
+                }
+                write!(f, "{}", c)?;
+            }
+            write!(f, "\"")
+        },
+        Value::Float(float) => {
+            if float.is_nan() {
+                write!(f, "(0.0 / 0.0)")
+            } else if float.is_infinite() {
+                write!(f, "({}1.0 / 0.0)", if *float < 0.0 { "-" } else { "" })
+            } else {
+                // The debug representation always contains a decimal point or an exponent,
+                // so the tokenizer does not mistake it for an integer.
+                write!(f, "{:?}", float)
+            }
+        },
+        Value::Tuple(tuple) if tuple.len() < 2 => write_short_tuple(tuple.first(), f, write_value),
+        Value::Tuple(tuple) => {
+            write!(f, "(")?;
+            for (index, value) in tuple.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write_value(value, f)?;
+            }
+            write!(f, ")")
+        },
+        Value::Array(array) => {
+            write!(f, "[")?;
+            for (index, value) in array.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write_value(value, f)?;
+            }
+            write!(f, "]")
+        },
+        // There is no literal for maps, so they are written in a readable way that cannot be parsed again.
+        Value::Map(map) => {
+            write!(f, "{{")?;
+            for (index, (key, value)) in map.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write_value(&Value::String(key.clone()), f)?;
+                write!(f, ": ")?;
+                write_value(value, f)?;
+            }
+            write!(f, "}}")
+        },
+        Value::Lambda(lambda) => write!(f, "({})", lambda),
+        // The literal of the smallest integer would overflow before it is negated.
+        Value::Int(IntType::MIN) => write!(f, "({} - 1)", IntType::MIN + 1),
+        Value::Int(_) | Value::Boolean(_) | Value::Empty => write!(f, "{}", value),
+        #[cfg(feature = "bigint")]
+        Value::BigInt(_) => write!(f, "{}", value),
+        // Decimals are parsed again as decimals only if decimal literals are enabled in the `ParserConfig`.
+        // The decimal point keeps them from being parsed as integers.
+        #[cfg(feature = "decimal")]
+        Value::Decimal(decimal) if decimal.scale() == 0 => write!(f, "{}.0", decimal),
+        #[cfg(feature = "decimal")]
+        Value::Decimal(_) => write!(f, "{}", value),
+        // There are no literals for dates and durations, so they are written as calls of the functions that parse them.
+        #[cfg(feature = "chrono")]
+        Value::DateTime(_) => write!(f, "timestamp(\"{}\")", value),
+        #[cfg(feature = "chrono")]
+        Value::Duration(_) => write!(f, "duration(\"{}\")", value),
     }
 }
@@ -0,0 +1,266 @@
+use crate::{
+    context::Context,
+    error::{EvalexprError, EvalexprResult},
+    operator::Operator,
+    value::Value,
+    EvalObserver, Node,
+};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{self, Display, Formatter};
+
+/// A breakdown of how an expression was evaluated, as returned by `Node::eval_with_explanation`.
+///
+/// An explanation is a tree that mirrors the evaluated part of the operator tree.
+/// Each step holds the expression of a node, the value it evaluated to, and the explanations of its operands.
+/// Parentheses and other nodes that only group subexpressions are left out.
+///
+/// The `Display` implementation writes the explanation in a single line for end users,
+/// showing the values of variables in parentheses and the values of subexpressions after an `=`,
+/// like `price (12) * qty (3) = 36 > 30 → true`.
+/// Operands that were not evaluated, like the right operand of `&&` if the left operand is false, are written as `…`,
+/// and values that could not be evaluated as `error`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Explanation {
+    operator: Operator,
+    expression: String,
+    value: Option<Value>,
+    operands: Vec<Explanation>,
+    /// The operators written between the operands, if the operator is an infix operator or a chain of comparisons.
+    infix_operators: Vec<Operator>,
+}
+
+impl Explanation {
+    fn new(node: &Node) -> Self {
+        use crate::operator::Operator::*;
+        let infix_operators = match (node.comparison_chain(), node.operator()) {
+            (Some((_, comparisons)), _) => comparisons.into_iter().cloned().collect(),
+            (
+                None,
+                operator @ (Add
+                | Sub
+                | Mul
+                | Div
+                | Mod
+                | Exp
+                | Eq
+                | Neq
+                | Gt
+                | Lt
+                | Geq
+                | Leq
+                | In
+                | And
+                | Or
+                | NullCoalesce
+                | Range { .. }),
+            ) => vec![operator.clone()],
+            _ => Vec::new(),
+        };
+
+        Self {
+            operator: node.operator().clone(),
+            expression: node.to_string(),
+            value: None,
+            operands: Vec::new(),
+            infix_operators,
+        }
+    }
+
+    /// Returns the operator of the explained node.
+    pub fn operator(&self) -> &Operator {
+        &self.operator
+    }
+
+    /// Returns the explained expression, as displayed by the `Display` implementation of `Node`.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns the value the expression evaluated to, or `None` if its evaluation failed.
+    pub fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+
+    /// Returns the explanations of the operands of the expression that were evaluated, in the order of their evaluation.
+    pub fn operands(&self) -> &[Explanation] {
+        &self.operands
+    }
+
+    /// Returns true if the explanation consists of more than a variable or a constant.
+    fn is_compound(&self) -> bool {
+        !matches!(
+            self.operator,
+            Operator::Const { .. } | Operator::VariableIdentifierRead { .. }
+        )
+    }
+
+    /// Writes the explanation, followed by the value of the expression if it is compound and `with_value` is true.
+    fn write(&self, f: &mut Formatter, with_value: bool) -> fmt::Result {
+        use crate::operator::Operator::*;
+        match (&self.operator, self.operands.as_slice()) {
+            (Const { .. }, _) => return self.write_value(f),
+            (VariableIdentifierRead { identifier }, _) => {
+                write!(f, "{} (", identifier)?;
+                self.write_value(f)?;
+                return write!(f, ")");
+            },
+            (_, [first, ..])
+                if !self.infix_operators.is_empty()
+                    && self.operands.len() <= self.infix_operators.len() + 1 =>
+            {
+                first.write_operand(f, true)?;
+                for (index, operator) in self.infix_operators.iter().enumerate() {
+                    write!(f, " {} ", operator)?;
+                    match self.operands.get(index + 1) {
+                        Some(operand) => operand.write_operand(f, false)?,
+                        None => write!(f, "…")?,
+                    }
+                }
+            },
+            (Neg | Pos | Not, [operand]) => {
+                write!(f, "{}", self.operator)?;
+                operand.write_operand(f, false)?;
+            },
+            (FunctionIdentifier { identifier }, [argument, ..]) => {
+                write!(f, "{}(", identifier)?;
+                match (&argument.operator, argument.operands.as_slice()) {
+                    (Tuple, arguments) => {
+                        for (index, argument) in arguments.iter().enumerate() {
+                            if index > 0 {
+                                write!(f, ", ")?;
+                            }
+                            argument.write(f, true)?;
+                        }
+                    },
+                    _ => argument.write(f, true)?,
+                }
+                write!(f, ")")?;
+            },
+            _ => write!(f, "{}", self.expression)?,
+        }
+
+        if with_value {
+            write!(f, " = ")?;
+            self.write_value(f)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the explanation of an operand, enclosing it in parentheses if it is compound and not the first operand.
+    fn write_operand(&self, f: &mut Formatter, is_first: bool) -> fmt::Result {
+        if self.is_compound() && !is_first {
+            write!(f, "(")?;
+            self.write(f, true)?;
+            write!(f, ")")
+        } else {
+            self.write(f, true)
+        }
+    }
+
+    fn write_value(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}", value),
+            None => write!(f, "error"),
+        }
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write(f, false)?;
+        if self.is_compound() {
+            write!(f, " → ")?;
+            self.write_value(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// An observer that builds the explanation of an evaluation.
+#[derive(Default)]
+struct ExplanationBuilder {
+    /// The explanations of the nodes that are being evaluated, together with the addresses of their operand nodes.
+    stack: Vec<(Explanation, Vec<usize>)>,
+    explanation: Option<Explanation>,
+}
+
+impl EvalObserver for ExplanationBuilder {
+    fn enter_node(&mut self, node: &Node) {
+        // The operands of a chain of comparisons are the operands of the nested comparisons.
+        let operands = match node.comparison_chain() {
+            Some((operands, _)) => operands.into_iter().map(address).collect(),
+            None => node.children().iter().map(address).collect(),
+        };
+        self.stack.push((Explanation::new(node), operands));
+    }
+
+    fn exit_node(
+        &mut self,
+        node: &Node,
+        _operands: &[Value],
+        result: Result<&Value, &EvalexprError>,
+    ) {
+        let Some((mut explanation, _)) = self.stack.pop() else {
+            return;
+        };
+        explanation.value = result.ok().cloned();
+        // Nodes that only group their child are replaced by the explanation of their child.
+        if explanation.operator == Operator::RootNode && explanation.operands.len() == 1 {
+            explanation = explanation.operands.remove(0);
+        }
+
+        match self.stack.last_mut() {
+            // Nodes evaluated within a node that are not its children, like the body of a called lambda, are no operands.
+            Some((parent, operands)) => {
+                if operands.contains(&address(node)) {
+                    parent.operands.push(explanation);
+                }
+            },
+            None => self.explanation = Some(explanation),
+        }
+    }
+}
+
+fn address(node: &Node) -> usize {
+    node as *const Node as usize
+}
+
+impl Node {
+    /// Evaluates the operator tree rooted at this node with the given context, and explains how the result came about.
+    ///
+    /// Returns the result of the evaluation together with an `Explanation` of the evaluated nodes,
+    /// which can be displayed to end users to show why a condition evaluated the way it did.
+    /// If the evaluation fails, the explanation covers the nodes evaluated up to the failure.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! { "price" => 12.5, "qty" => 3, "vip" => false }.unwrap(); // Do proper error handling here
+    /// let tree = build_operator_tree("price * qty > 30 && !vip").unwrap(); // Do proper error handling here
+    ///
+    /// let (result, explanation) = tree.eval_with_explanation(&context);
+    /// assert_eq!(result, Ok(Value::from(true)));
+    /// assert_eq!(
+    ///     explanation.to_string(),
+    ///     "price (12.5) * qty (3) = 37.5 > 30 = true && (!vip (false) = true) → true"
+    /// );
+    /// assert_eq!(explanation.operands()[0].expression(), "price * qty > 30");
+    /// ```
+    pub fn eval_with_explanation<C: Context>(
+        &self,
+        context: &C,
+    ) -> (EvalexprResult<Value>, Explanation) {
+        let mut builder = ExplanationBuilder::default();
+        let result = self.eval_with_context_and_observer(context, &mut builder);
+        let explanation = builder
+            .explanation
+            .unwrap_or_else(|| Explanation::new(self));
+        (result, explanation)
+    }
+}
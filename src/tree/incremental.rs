@@ -0,0 +1,114 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    error::EvalexprResult, tree::is_dependency, value::Value, ContextWithMutableVariables,
+    EvalCache, HashMapContext, Node,
+};
+
+/// An expression together with a context, which re-evaluates only the parts of the expression that are affected by changed variables.
+///
+/// Setting a variable that the expression does not depend on keeps its result.
+/// Otherwise, only the subexpressions that depend on changed variables are evaluated again,
+/// while the values of all other subexpressions are taken from an `EvalCache`.
+///
+/// Like with `Node::eval_cached`, only the values of subexpressions that call pure functions are cached.
+/// If the expression calls impure functions like `now` or `random`, its result is not kept,
+/// so each call to `eval` evaluates the impure parts of the expression again.
+/// The expression cannot assign to variables.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let tree = build_operator_tree("math::sqrt(a * a + b * b) * scale").unwrap(); // Do proper error handling here
+/// let context = context_map! { "a" => 3.0, "b" => 4.0, "scale" => 1.0 }.unwrap(); // Do proper error handling here
+/// let mut evaluator = IncrementalEvaluator::new(tree, context);
+///
+/// assert_eq!(evaluator.eval(), Ok(Value::from(5.0)));
+/// // Only the multiplication with the new scale is evaluated again.
+/// evaluator.set_value("scale".into(), Value::from(2.0)).unwrap(); // Do proper error handling here
+/// assert_eq!(evaluator.eval(), Ok(Value::from(10.0)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct IncrementalEvaluator<C = HashMapContext> {
+    tree: Node,
+    dependencies: Vec<String>,
+    context: C,
+    cache: EvalCache,
+    result: Option<EvalexprResult<Value>>,
+}
+
+impl<C: ContextWithMutableVariables> IncrementalEvaluator<C> {
+    /// Creates a new evaluator for the given operator tree and context.
+    pub fn new(tree: Node, context: C) -> Self {
+        let dependencies = tree.dependencies().into_iter().map(String::from).collect();
+        Self {
+            tree,
+            dependencies,
+            context,
+            cache: EvalCache::new(),
+            result: None,
+        }
+    }
+
+    /// Returns the operator tree of this evaluator.
+    pub fn tree(&self) -> &Node {
+        &self.tree
+    }
+
+    /// Returns the context of this evaluator.
+    pub fn context(&self) -> &C {
+        &self.context
+    }
+
+    /// Returns the context of this evaluator, allowing mutation.
+    ///
+    /// As the changes made to the context are not tracked, the expression is re-evaluated on the next call to `eval`,
+    /// but the values of subexpressions whose variables did not change are still taken from the cache.
+    pub fn context_mut(&mut self) -> &mut C {
+        self.result = None;
+        &mut self.context
+    }
+
+    /// Consumes this evaluator and returns its context.
+    pub fn into_context(self) -> C {
+        self.context
+    }
+
+    /// Sets the variable with the given identifier in the context to the given value.
+    ///
+    /// If the expression depends on the variable and the value changed, the expression is re-evaluated on the next call to `eval`.
+    pub fn set_value(&mut self, identifier: String, value: Value) -> EvalexprResult<()> {
+        let is_affected = self
+            .dependencies
+            .iter()
+            .any(|dependency| is_dependency(dependency, &identifier))
+            && !matches!(self.context.get_value(&identifier), Some(old_value) if old_value.is_identical_to(&value));
+        self.context.set_value(identifier, value)?;
+        if is_affected {
+            self.result = None;
+        }
+        Ok(())
+    }
+
+    /// Returns the result of evaluating the expression with the current values of the variables,
+    /// evaluating only the subexpressions that are affected by changes since the previous call.
+    pub fn eval(&mut self) -> EvalexprResult<Value> {
+        let Self {
+            tree,
+            context,
+            cache,
+            result,
+            ..
+        } = self;
+        if let Some(result) = result {
+            return result.clone();
+        }
+        let (new_result, is_pure) = tree.eval_cached_with_purity(context, cache);
+        if is_pure {
+            *result = Some(new_result.clone());
+        }
+        new_result
+    }
+}
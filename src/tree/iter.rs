@@ -1,5 +1,6 @@
 use crate::{operator::Operator, Node};
-use std::slice::{Iter, IterMut};
+use alloc::{vec, vec::Vec};
+use core::slice::{Iter, IterMut};
 
 /// An iterator that traverses an operator tree in pre-order.
 pub struct NodeIter<'a> {
@@ -81,7 +82,101 @@ impl<'a> Iterator for OperatorIterMut<'a> {
     }
 }
 
+/// A visitor of the nodes of an operator tree, used with `Node::walk`.
+///
+/// Closures of type `FnMut(&Node) -> bool` are visitors that are called when entering each node.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// /// Counts the function calls outside of calls of `if`.
+/// struct CallCounter(usize);
+///
+/// impl Visitor for CallCounter {
+///     fn enter(&mut self, node: &Node) -> bool {
+///         match node.operator() {
+///             Operator::FunctionIdentifier { identifier } if &**identifier == "if" => false,
+///             Operator::FunctionIdentifier { .. } => {
+///                 self.0 += 1;
+///                 true
+///             },
+///             _ => true,
+///         }
+///     }
+/// }
+///
+/// let tree = build_operator_tree("f(g(1)) + if(true, h(), 2)").unwrap(); // Do proper error handling here
+/// let mut counter = CallCounter(0);
+/// tree.walk(&mut counter);
+/// assert_eq!(counter.0, 2);
+/// ```
+pub trait Visitor {
+    /// Called when a node is visited, before its children are visited.
+    /// If this returns false, the children of the node are skipped.
+    fn enter(&mut self, node: &Node) -> bool {
+        let _ = node;
+        true
+    }
+
+    /// Called after a node and its children were visited.
+    /// This is also called if the children were skipped.
+    fn leave(&mut self, node: &Node) {
+        let _ = node;
+    }
+}
+
+impl<F: FnMut(&Node) -> bool> Visitor for F {
+    fn enter(&mut self, node: &Node) -> bool {
+        self(node)
+    }
+}
+
 impl Node {
+    /// Visits this node and all nodes below it in depth-first order with the given visitor.
+    ///
+    /// See the documentation of `Visitor` for more details.
+    /// The tree is traversed without recursion, so deep trees do not overflow the stack.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("a * (b + 2) / a").unwrap(); // Do proper error handling here
+    /// let mut divisions = 0;
+    /// tree.walk(&mut |node: &Node| {
+    ///     if node.operator() == &Operator::Div {
+    ///         divisions += 1;
+    ///     }
+    ///     true
+    /// });
+    /// assert_eq!(divisions, 1);
+    /// ```
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        let mut stack: Vec<(&Node, Iter<Node>)> = Vec::new();
+        if visitor.enter(self) {
+            stack.push((self, self.children.iter()));
+        } else {
+            visitor.leave(self);
+        }
+
+        while let Some((_, children)) = stack.last_mut() {
+            if let Some(child) = children.next() {
+                if visitor.enter(child) {
+                    stack.push((child, child.children.iter()));
+                } else {
+                    visitor.leave(child);
+                }
+            } else {
+                // Can not fail because we just borrowed the last element.
+                let (node, _) = stack.pop().unwrap();
+                visitor.leave(node);
+            }
+        }
+    }
+
     /// Returns an iterator over all nodes in this tree.
     pub fn iter(&self) -> impl Iterator<Item = &Node> {
         NodeIter::new(self)
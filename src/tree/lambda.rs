@@ -0,0 +1,154 @@
+use crate::{
+    error::{expect_function_argument_amount, EvalexprError, EvalexprResult},
+    operator::{resolve_variable, Operator},
+    value::Value,
+    Context, EvalOptions, Node,
+};
+use alloc::{borrow::Cow, boxed::Box, string::String, vec, vec::Vec};
+use core::fmt::{Display, Error, Formatter};
+
+use super::{options::EvalState, scope::Scope};
+
+/// A function that is defined inside of an expression, like `|x, y| x * y`.
+///
+/// Evaluating a lambda expression results in a `Value::Lambda`.
+/// A variable that contains a lambda can be called like a function, unless the context links a function to the same identifier.
+/// Lambdas take precedence over builtin functions with the same identifier.
+///
+/// Arguments are counted like functions receive them: a tuple argument counts as its elements, an empty argument as no arguments,
+/// and any other value as a single argument.
+/// Calling a lambda with an amount of arguments that differs from its amount of parameters fails with `EvalexprError::WrongFunctionArgumentAmount`.
+/// Lambdas without parameters are written as `|| body`.
+/// The body of a lambda extends as far to the right as possible, up to the next `,` or `;` that is not enclosed in parentheses.
+///
+/// When a lambda is called, its body is evaluated with the context of the call, in which the parameters are bound to the arguments.
+/// The body can read variables of the context, but cannot assign to them.
+/// Variables are looked up when the lambda is called and not when it is defined,
+/// so after `a = 1; f = |x| x + a; a = 2`, the call `f(0)` evaluates to `2`.
+/// Lambdas that are defined in the body of another lambda capture the arguments of the outer lambda.
+///
+/// Only identifiers can be called, so a lambda expression needs to be stored in a variable or passed as an argument to be called.
+/// Calling it directly like `(|x| x + 1)(2)` is not supported.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let mut context = HashMapContext::new();
+/// assert_eq!(eval_with_context_mut("f = |x| x * 2; f(21)", &mut context), Ok(Value::from(42)));
+/// assert_eq!(eval_with_context("f(f(1))", &context), Ok(Value::from(4)));
+///
+/// assert_eq!(
+///     eval_with_context_mut("add = |a| |b| a + b; add_two = add(2); add_two(3)", &mut context),
+///     Ok(Value::from(5))
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct Lambda {
+    parameters: Vec<String>,
+    body: Node,
+}
+
+impl Lambda {
+    /// The maximum amount of nested lambda calls.
+    /// Calling lambdas recursively any deeper fails with `EvalexprError::RecursionLimitExceeded`.
+    pub const MAX_CALL_DEPTH: usize = 64;
+
+    /// Creates a lambda with the given parameters and body.
+    pub fn new(parameters: Vec<String>, body: Node) -> Self {
+        Self { parameters, body }
+    }
+
+    /// Returns the identifiers of the parameters of this lambda.
+    pub fn parameters(&self) -> &[String] {
+        &self.parameters
+    }
+
+    /// Returns the body of this lambda.
+    pub fn body(&self) -> &Node {
+        &self.body
+    }
+
+    /// Calls this lambda with the given argument, evaluating its body with the given context.
+    ///
+    /// Fails with `EvalexprError::WrongFunctionArgumentAmount` if the amount of arguments differs from the amount of parameters,
+    /// or if the evaluation of the body fails.
+    pub fn call(&self, context: &dyn Context, argument: &Value) -> EvalexprResult<Value> {
+        self.call_with_state(
+            context,
+            argument,
+            &mut EvalState::new(&EvalOptions::default()),
+        )
+    }
+
+    pub(crate) fn call_with_state(
+        &self,
+        context: &dyn Context,
+        argument: &Value,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        let arguments = match argument {
+            Value::Empty => Vec::new(),
+            Value::Tuple(tuple) => tuple.clone(),
+            argument => vec![argument.clone()],
+        };
+        expect_function_argument_amount(arguments.len(), self.parameters.len())?;
+
+        // Replacing the parameters in the body makes lambdas defined in the body capture the arguments.
+        let mut body = self.body.clone();
+        for (parameter, argument) in self.parameters.iter().zip(&arguments) {
+            body.substitute_variable_in_place(parameter, argument);
+        }
+        // Binding the parameters allows to call arguments that are lambdas themselves.
+        let mut scope = Scope::new(context);
+        for (parameter, argument) in self.parameters.iter().zip(arguments) {
+            scope.bind(parameter.clone(), argument);
+        }
+        state.nested_lambda_call(|state| body.eval_with_state(&scope, state))
+    }
+}
+
+/// Displays the lambda as a lambda expression that can be parsed again with `build_operator_tree`.
+impl Display for Lambda {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let mut node = Node::new(Operator::lambda(self.parameters.clone()));
+        node.children.push(self.body.clone());
+        write!(f, "{}", node)
+    }
+}
+
+impl From<Lambda> for Value {
+    fn from(lambda: Lambda) -> Self {
+        Value::Lambda(Box::new(lambda))
+    }
+}
+
+impl Node {
+    /// Evaluates a lambda expression with the given parameters to a `Value::Lambda`, without evaluating its body.
+    pub(crate) fn eval_lambda(&self, parameters: &[String]) -> EvalexprResult<Value> {
+        match self.children() {
+            [body] => Ok(Lambda::new(parameters.to_vec(), body.clone()).into()),
+            children => Err(EvalexprError::wrong_operator_argument_amount(
+                children.len(),
+                1,
+            )),
+        }
+    }
+
+    /// Returns the lambda called by this node, if it calls a variable that contains a lambda.
+    /// Like in `Operator::eval`, functions of the context take precedence over lambdas.
+    pub(crate) fn called_lambda<'a, C: Context>(&self, context: &'a C) -> Option<&'a Lambda> {
+        match self.operator() {
+            Operator::FunctionIdentifier { identifier } if !context.has_function(identifier) => {
+                // Lambdas that the context does not store are called without this shortcut.
+                match resolve_variable(context, identifier) {
+                    Ok(Some(Cow::Borrowed(Value::Lambda(lambda)))) => Some(lambda),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+}
@@ -1,20 +1,63 @@
 use crate::{
-    token::Token,
+    token::{Span, Token},
     value::{TupleType, EMPTY_VALUE},
-    Context, ContextWithMutableVariables, EmptyType, FloatType, HashMapContext, IntType,
+    Context, ContextWithMutableVariables, EmptyContext, EmptyType, FloatType, HashMapContext,
+    IntType,
 };
 
 use crate::{
-    error::{EvalexprError, EvalexprResult},
+    error::{ErrorRecovery, EvalexprError, EvalexprResult},
+    function::builtin::BUILTIN_FUNCTION_IDENTIFIERS,
     operator::*,
     value::Value,
 };
-use std::mem;
+use alloc::{borrow::Cow, collections::BTreeSet, string::String, sync::Arc, vec, vec::Vec};
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::once,
+    mem,
+};
 
-// Exclude display module from coverage, as it prints not well-defined prefix notation.
-#[cfg(not(tarpaulin_include))]
+#[cfg(feature = "async")]
+mod asynchronous;
+mod builder;
+mod cache;
+mod columns;
+mod compiled;
+mod completion;
+mod config;
 mod display;
+mod explanation;
+mod incremental;
 mod iter;
+mod lambda;
+mod observer;
+mod options;
+mod policy;
+#[cfg(feature = "std")]
+mod profiler;
+mod scope;
+mod types;
+
+pub use cache::EvalCache;
+pub use columns::Column;
+pub use compiled::CompiledExpression;
+pub(crate) use completion::completions;
+pub use completion::{Completion, CompletionKind};
+pub use config::ParserConfig;
+pub use explanation::Explanation;
+pub use incremental::IncrementalEvaluator;
+pub use iter::Visitor;
+pub use lambda::Lambda;
+pub use observer::EvalObserver;
+pub(crate) use options::EvalState;
+pub use options::{DivisionByZeroMode, EvalOptions};
+pub use policy::EvaluationPolicy;
+#[cfg(feature = "std")]
+pub use profiler::{NodeProfile, Profiler};
+use scope::Scope;
+pub use types::TypeHints;
 
 /// A node in the operator tree.
 /// The operator tree is created by the crate-level `build_operator_tree` method.
@@ -33,10 +76,17 @@ mod iter;
 /// assert_eq!(node.eval_with_context(&context), Ok(Value::from(3)));
 /// ```
 ///
-#[derive(Debug, PartialEq, Clone)]
+/// Nodes are equal if their trees are structurally identical, see the documentation of `Operator` for how constants are compared.
+/// Equal trees also have the same hash, so they can be used as keys in hash maps, for example to cache the results of expressions.
+#[derive(Clone)]
 pub struct Node {
     operator: Operator,
     children: Vec<Node>,
+    /// The part of the expression string this node was parsed from.
+    span: Option<Span>,
+    /// The expression string this tree was parsed from, which is only stored at the root of a parsed tree.
+    /// A thin pointer keeps the nodes small.
+    source: Option<Arc<String>>,
 }
 
 impl Node {
@@ -44,13 +94,70 @@ impl Node {
         Self {
             children: Vec::new(),
             operator,
+            span: None,
+            source: None,
         }
     }
 
-    fn root_node() -> Self {
+    pub(crate) fn root_node() -> Self {
         Self::new(Operator::RootNode)
     }
 
+    /// Converts the root node of an array literal into an array node.
+    /// The elements of a tuple directly inside the brackets become the elements of the array.
+    fn into_array(mut self) -> Self {
+        let mut array = Self::new(Operator::Array);
+        array.children = match self.children.pop() {
+            Some(mut child) if child.operator() == &Operator::Tuple => {
+                mem::take(&mut child.children)
+            },
+            Some(child) => vec![child],
+            None => Vec::new(),
+        };
+        array
+    }
+
+    /// Extends the span of each node in this tree to cover the spans of its children,
+    /// so that the span of a node covers the whole subexpression it was parsed from.
+    fn extend_spans(&mut self) {
+        for child in &mut self.children {
+            child.extend_spans();
+            if let Some(child_span) = child.span {
+                self.span = Some(match self.span {
+                    Some(span) => Span::new(
+                        span.start.min(child_span.start),
+                        span.end.max(child_span.end),
+                    ),
+                    None => child_span,
+                });
+            }
+        }
+    }
+
+    fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Converts all index nodes whose brackets directly contain a range, like `t[1..3]`, into slice nodes.
+    fn convert_slices(&mut self) {
+        for child in &mut self.children {
+            child.convert_slices();
+        }
+        if self.operator != Operator::Index || self.children.len() != 2 {
+            return;
+        }
+        let key = &mut self.children[1];
+        if key.operator == Operator::RootNode && key.children.len() == 1 {
+            if let Operator::Range { inclusive } = key.children[0].operator {
+                let mut range = key.children.pop().unwrap();
+                self.operator = Operator::Slice { inclusive };
+                self.children.pop();
+                self.children.append(&mut range.children);
+            }
+        }
+    }
+
     /// Returns an iterator over all identifiers in this expression.
     /// Each occurrence of an identifier is returned separately.
     ///
@@ -71,7 +178,7 @@ impl Node {
         self.iter().filter_map(|node| match node.operator() {
             Operator::VariableIdentifierWrite { identifier }
             | Operator::VariableIdentifierRead { identifier }
-            | Operator::FunctionIdentifier { identifier } => Some(identifier.as_str()),
+            | Operator::FunctionIdentifier { identifier } => Some(identifier.as_ref()),
             _ => None,
         })
     }
@@ -87,7 +194,7 @@ impl Node {
     /// let mut tree = build_operator_tree("a + b + c * f()").unwrap(); // Do proper error handling here
     ///
     /// for identifier in tree.iter_identifiers_mut() {
-    ///     *identifier = String::from("x");
+    ///     *identifier = "x".into();
     /// }
     ///
     /// let mut iter = tree.iter_identifiers();
@@ -98,7 +205,7 @@ impl Node {
     /// assert_eq!(iter.next(), Some("x"));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter_identifiers_mut(&mut self) -> impl Iterator<Item = &mut String> {
+    pub fn iter_identifiers_mut(&mut self) -> impl Iterator<Item = &mut Arc<str>> {
         self.iter_operators_mut()
             .filter_map(|operator| match operator {
                 Operator::VariableIdentifierWrite { identifier }
@@ -126,7 +233,7 @@ impl Node {
     pub fn iter_variable_identifiers(&self) -> impl Iterator<Item = &str> {
         self.iter().filter_map(|node| match node.operator() {
             Operator::VariableIdentifierWrite { identifier }
-            | Operator::VariableIdentifierRead { identifier } => Some(identifier.as_str()),
+            | Operator::VariableIdentifierRead { identifier } => Some(identifier.as_ref()),
             _ => None,
         })
     }
@@ -142,7 +249,7 @@ impl Node {
     /// let mut tree = build_operator_tree("a + b + c * f()").unwrap(); // Do proper error handling here
     ///
     /// for identifier in tree.iter_variable_identifiers_mut() {
-    ///     *identifier = String::from("x");
+    ///     *identifier = "x".into();
     /// }
     ///
     /// let mut iter = tree.iter_identifiers();
@@ -153,7 +260,7 @@ impl Node {
     /// assert_eq!(iter.next(), Some("f"));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter_variable_identifiers_mut(&mut self) -> impl Iterator<Item = &mut String> {
+    pub fn iter_variable_identifiers_mut(&mut self) -> impl Iterator<Item = &mut Arc<str>> {
         self.iter_operators_mut()
             .filter_map(|operator| match operator {
                 Operator::VariableIdentifierWrite { identifier }
@@ -179,7 +286,7 @@ impl Node {
     /// ```
     pub fn iter_read_variable_identifiers(&self) -> impl Iterator<Item = &str> {
         self.iter().filter_map(|node| match node.operator() {
-            Operator::VariableIdentifierRead { identifier } => Some(identifier.as_str()),
+            Operator::VariableIdentifierRead { identifier } => Some(identifier.as_ref()),
             _ => None,
         })
     }
@@ -195,7 +302,7 @@ impl Node {
     /// let mut tree = build_operator_tree("d = a + f(b + c)").unwrap(); // Do proper error handling here
     ///
     /// for identifier in tree.iter_read_variable_identifiers_mut() {
-    ///     *identifier = String::from("x");
+    ///     *identifier = "x".into();
     /// }
     ///
     /// let mut iter = tree.iter_identifiers();
@@ -207,7 +314,7 @@ impl Node {
     /// assert_eq!(iter.next(), Some("x"));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter_read_variable_identifiers_mut(&mut self) -> impl Iterator<Item = &mut String> {
+    pub fn iter_read_variable_identifiers_mut(&mut self) -> impl Iterator<Item = &mut Arc<str>> {
         self.iter_operators_mut()
             .filter_map(|operator| match operator {
                 Operator::VariableIdentifierRead { identifier } => Some(identifier),
@@ -230,7 +337,7 @@ impl Node {
     /// ```
     pub fn iter_write_variable_identifiers(&self) -> impl Iterator<Item = &str> {
         self.iter().filter_map(|node| match node.operator() {
-            Operator::VariableIdentifierWrite { identifier } => Some(identifier.as_str()),
+            Operator::VariableIdentifierWrite { identifier } => Some(identifier.as_ref()),
             _ => None,
         })
     }
@@ -246,7 +353,7 @@ impl Node {
     /// let mut tree = build_operator_tree("d = a + f(b + c)").unwrap(); // Do proper error handling here
     ///
     /// for identifier in tree.iter_write_variable_identifiers_mut() {
-    ///     *identifier = String::from("x");
+    ///     *identifier = "x".into();
     /// }
     ///
     /// let mut iter = tree.iter_identifiers();
@@ -258,7 +365,7 @@ impl Node {
     /// assert_eq!(iter.next(), Some("c"));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter_write_variable_identifiers_mut(&mut self) -> impl Iterator<Item = &mut String> {
+    pub fn iter_write_variable_identifiers_mut(&mut self) -> impl Iterator<Item = &mut Arc<str>> {
         self.iter_operators_mut()
             .filter_map(|operator| match operator {
                 Operator::VariableIdentifierWrite { identifier } => Some(identifier),
@@ -281,7 +388,7 @@ impl Node {
     /// ```
     pub fn iter_function_identifiers(&self) -> impl Iterator<Item = &str> {
         self.iter().filter_map(|node| match node.operator() {
-            Operator::FunctionIdentifier { identifier } => Some(identifier.as_str()),
+            Operator::FunctionIdentifier { identifier } => Some(identifier.as_ref()),
             _ => None,
         })
     }
@@ -297,7 +404,7 @@ impl Node {
     /// let mut tree = build_operator_tree("d = a + f(b + c)").unwrap(); // Do proper error handling here
     ///
     /// for identifier in tree.iter_function_identifiers_mut() {
-    ///     *identifier = String::from("x");
+    ///     *identifier = "x".into();
     /// }
     ///
     /// let mut iter = tree.iter_identifiers();
@@ -309,7 +416,7 @@ impl Node {
     /// assert_eq!(iter.next(), Some("c"));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter_function_identifiers_mut(&mut self) -> impl Iterator<Item = &mut String> {
+    pub fn iter_function_identifiers_mut(&mut self) -> impl Iterator<Item = &mut Arc<str>> {
         self.iter_operators_mut()
             .filter_map(|operator| match operator {
                 Operator::FunctionIdentifier { identifier } => Some(identifier),
@@ -317,15 +424,198 @@ impl Node {
             })
     }
 
+    /// Returns the set of variable identifiers whose values in the context can affect the result of this expression.
+    ///
+    /// Unlike `iter_read_variable_identifiers`, this omits reads of parameters of lambdas
+    /// and of let-bound variables, as their values are not taken from the context.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("let y = x * 2; f(|z| z + y + a, b)").unwrap(); // Do proper error handling here
+    /// let dependencies: Vec<_> = tree.dependencies().into_iter().collect();
+    /// assert_eq!(dependencies, vec!["a", "b", "x"]);
+    /// ```
+    pub fn dependencies(&self) -> BTreeSet<&str> {
+        let mut dependencies = BTreeSet::new();
+        let mut stack = vec![(self, Vec::new())];
+        while let Some((node, bound_identifiers)) = stack.pop() {
+            match node.operator() {
+                Operator::VariableIdentifierRead { identifier } => {
+                    if !bound_identifiers.contains(&identifier.as_ref()) {
+                        dependencies.insert(identifier.as_ref());
+                    }
+                },
+                Operator::Lambda { parameters } => {
+                    let mut bound_identifiers = bound_identifiers;
+                    bound_identifiers.extend(parameters.iter().map(String::as_str));
+                    stack.extend(
+                        node.children()
+                            .iter()
+                            .map(|child| (child, bound_identifiers.clone())),
+                    );
+                },
+                Operator::Chain => {
+                    // Let-bound variables are visible to the expressions following their binding.
+                    let mut bound_identifiers = bound_identifiers;
+                    for child in node.children() {
+                        stack.push((child, bound_identifiers.clone()));
+                        if let Some((identifier, _)) = child.let_binding() {
+                            bound_identifiers.push(identifier);
+                        }
+                    }
+                },
+                _ => stack.extend(
+                    node.children()
+                        .iter()
+                        .map(|child| (child, bound_identifiers.clone())),
+                ),
+            }
+        }
+        dependencies
+    }
+
+    /// Returns true if the value of the variable with the given identifier in the context can affect the result of this expression.
+    ///
+    /// A variable is also depended on if a field of it is read through a dot path like `identifier.field`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("let double = |a| a * 2; point.x + double(b)").unwrap(); // Do proper error handling here
+    /// assert!(tree.depends_on("point"));
+    /// assert!(tree.depends_on("point.x"));
+    /// assert!(tree.depends_on("b"));
+    /// assert!(!tree.depends_on("a"));
+    /// ```
+    pub fn depends_on(&self, identifier: &str) -> bool {
+        self.dependencies()
+            .into_iter()
+            .any(|dependency| is_dependency(dependency, identifier))
+    }
+
+    /// Checks that this expression only uses the given variables and functions, and returns errors for all other identifiers at once.
+    ///
+    /// A variable read through a dot path like `identifier.field` is allowed if the path or one of its prefixes is allowed.
+    /// Builtin functions, variables that are assigned to in the expression, parameters of lambdas and let-bound variables are always allowed,
+    /// and lambdas stored in allowed variables can be called as functions.
+    /// To forbid builtin functions, use an `EvaluationPolicy`.
+    ///
+    /// Each unknown identifier results in one `EvalexprError::VariableIdentifierNotFound` or `EvalexprError::FunctionIdentifierNotFound`
+    /// with suggestions from the allowed identifiers, in the order of their first occurrence in the expression.
+    /// Errors for variables carry the span of the first occurrence of the variable, if the tree was parsed from a string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("prise * qty + tax(prise) + sqrt(qty)").unwrap(); // Do proper error handling here
+    /// let errors = tree.validate(&["price", "qty"], &["tax"]).unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[0].to_string(), "Variable identifier is not bound to anything by context: \"prise\" at position 0. Did you mean \"price\"?");
+    /// assert_eq!(
+    ///     errors[1],
+    ///     EvalexprError::FunctionIdentifierNotFound { identifier: "sqrt".into(), suggestions: vec![] }
+    /// );
+    ///
+    /// let tree = build_operator_tree("let total = price * qty; total + tax(total)").unwrap(); // Do proper error handling here
+    /// assert_eq!(tree.validate(&["price", "qty"], &["tax"]), Ok(()));
+    /// ```
+    pub fn validate(
+        &self,
+        allowed_variables: &[&str],
+        allowed_functions: &[&str],
+    ) -> Result<(), Vec<EvalexprError>> {
+        let dependencies = self.dependencies();
+        // Assigned variables are stored in the context, while bound ones are only visible in their scope,
+        // which `dependencies` already accounts for when they are read as variables.
+        let mut assigned_identifiers = BTreeSet::new();
+        let mut bound_identifiers = BTreeSet::new();
+        for node in self.iter() {
+            match (node.operator(), node.children().first().map(Node::operator)) {
+                (Operator::Lambda { parameters }, _) => {
+                    bound_identifiers.extend(parameters.iter().map(String::as_str))
+                },
+                (Operator::Let, Some(Operator::VariableIdentifierWrite { identifier })) => {
+                    bound_identifiers.insert(identifier.as_ref());
+                },
+                (_, Some(Operator::VariableIdentifierWrite { identifier })) => {
+                    assigned_identifiers.insert(identifier.as_ref());
+                },
+                _ => {},
+            }
+        }
+        let is_allowed_variable = |identifier: &str| {
+            assigned_identifiers.contains(identifier)
+                || allowed_variables
+                    .iter()
+                    .any(|allowed| is_dependency(identifier, allowed))
+        };
+
+        let mut reported = BTreeSet::new();
+        let mut errors = Vec::new();
+        for node in self.iter() {
+            match node.operator() {
+                Operator::VariableIdentifierRead { identifier }
+                    if dependencies.contains(identifier.as_ref())
+                        && !is_allowed_variable(identifier)
+                        && reported.insert(identifier.as_ref()) =>
+                {
+                    errors.push(
+                        EvalexprError::variable_identifier_not_found(identifier.as_ref().into())
+                            .with_suggestions(allowed_variables.iter().copied())
+                            .with_span(node.span()),
+                    );
+                },
+                Operator::FunctionIdentifier { identifier }
+                    if !allowed_functions.contains(&identifier.as_ref())
+                        && !BUILTIN_FUNCTION_IDENTIFIERS.contains(&identifier.as_ref())
+                        && !bound_identifiers.contains(identifier.as_ref())
+                        && !is_allowed_variable(identifier)
+                        && reported.insert(identifier.as_ref()) =>
+                {
+                    errors.push(
+                        EvalexprError::function_identifier_not_found(identifier.as_ref().into())
+                            .with_suggestions(
+                                allowed_functions
+                                    .iter()
+                                    .chain(BUILTIN_FUNCTION_IDENTIFIERS)
+                                    .copied(),
+                            ),
+                    );
+                },
+                _ => {},
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Evaluates the operator tree rooted at this node with the given context.
     ///
     /// Fails, if one of the operators in the expression tree fails.
     pub fn eval_with_context<C: Context>(&self, context: &C) -> EvalexprResult<Value> {
-        let mut arguments = Vec::new();
-        for child in self.children() {
-            arguments.push(child.eval_with_context(context)?);
-        }
-        self.operator().eval(&arguments, context)
+        self.eval_with_context_and_options(context, &EvalOptions::default())
+    }
+
+    /// Evaluates the operator tree rooted at this node with the given context and evaluation options.
+    ///
+    /// Fails, if one of the operators in the expression tree fails, or if a limit set in the options is exceeded.
+    pub fn eval_with_context_and_options<C: Context>(
+        &self,
+        context: &C,
+        options: &EvalOptions,
+    ) -> EvalexprResult<Value> {
+        self.eval_with_state(context, &mut EvalState::new(options))
     }
 
     /// Evaluates the operator tree rooted at this node with the given mutable context.
@@ -335,11 +625,342 @@ impl Node {
         &self,
         context: &mut C,
     ) -> EvalexprResult<Value> {
-        let mut arguments = Vec::new();
+        self.eval_with_context_mut_and_options(context, &EvalOptions::default())
+    }
+
+    /// Evaluates the operator tree rooted at this node with the given mutable context and evaluation options.
+    ///
+    /// Fails, if one of the operators in the expression tree fails, or if a limit set in the options is exceeded.
+    pub fn eval_with_context_mut_and_options<C: ContextWithMutableVariables>(
+        &self,
+        context: &mut C,
+        options: &EvalOptions,
+    ) -> EvalexprResult<Value> {
+        self.eval_with_state_mut(context, &mut EvalState::new(options))
+    }
+
+    fn eval_with_state<C: Context>(
+        &self,
+        context: &C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        if state.is_traced() {
+            self.eval_node::<true, C>(context, state)
+        } else {
+            self.eval_node::<false, C>(context, state)
+        }
+    }
+
+    /// Evaluates this node, entering and leaving it in the state only if the evaluation is traced.
+    /// Untraced evaluations skip the bookkeeping for caches, observers and policies, which they do not need.
+    fn eval_node<const TRACED: bool, C: Context>(
+        &self,
+        context: &C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        if !TRACED {
+            return self
+                .eval_operator_with_state::<TRACED, C>(context, state)
+                .map_err(|error| error.with_span(self.span()));
+        }
+
+        state.enter_node(self)?;
+        let result = if let Some(key) = state.cache_key(self) {
+            self.eval_with_cache(key, state, |state| {
+                self.eval_operator_with_state::<TRACED, C>(context, state)
+            })
+        } else {
+            self.eval_operator_with_state::<TRACED, C>(context, state)
+        };
+        state.leave_node(self, result)
+    }
+
+    fn eval_operator_with_state<const TRACED: bool, C: Context>(
+        &self,
+        context: &C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        state.count_node_evaluation()?;
+        if self.has_special_evaluation() {
+            self.eval_special_operator_with_state::<TRACED, C>(context, state)
+        } else {
+            self.apply_operator_to_children::<TRACED, C>(context, state)
+        }
+    }
+
+    /// Evaluates an operator that does not simply apply to the values of all its children, like lazy or short-circuiting operators.
+    fn eval_special_operator_with_state<const TRACED: bool, C: Context>(
+        &self,
+        context: &C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        if let Some([condition, if_true, if_false]) = self.lazy_if_arguments(context) {
+            return if condition
+                .eval_node::<TRACED, _>(context, state)?
+                .as_boolean()?
+            {
+                if_true.eval_node::<TRACED, _>(context, state)
+            } else {
+                if_false.eval_node::<TRACED, _>(context, state)
+            };
+        }
+        if let Some((operands, comparisons)) = self.comparison_chain() {
+            let options = state.options();
+            return Self::eval_comparison_chain(&operands, &comparisons, options, |operand| {
+                operand.eval_node::<TRACED, _>(context, state)
+            });
+        }
+        if let Operator::Lambda { parameters } = self.operator() {
+            return self.eval_lambda(parameters);
+        }
+        if self.is_let_chain() {
+            let mut scope = Scope::new(context as &dyn Context);
+            let mut result = Value::Empty;
+            for child in self.children() {
+                result = match child.let_binding() {
+                    Some((identifier, value)) => {
+                        let value = value.eval_node::<TRACED, _>(&scope, state)?;
+                        scope.bind(identifier.into(), value);
+                        Value::Empty
+                    },
+                    None => child.eval_node::<TRACED, _>(&scope, state)?,
+                };
+            }
+            return Ok(result);
+        }
+        if let (Some(lambda), [argument]) = (self.called_lambda(context), self.children()) {
+            let argument = argument.eval_borrowed_with_state::<TRACED, _>(context, state)?;
+            return lambda.call_with_state(context, &argument, state);
+        }
+
+        if self.is_null_coalescing() {
+            let left = self.children[0].eval_node::<TRACED, _>(context, state);
+            if !is_absent(&left) {
+                return left;
+            }
+            return self.children[1].eval_node::<TRACED, _>(context, state);
+        }
+
+        if let Some(short_circuit_value) = self.short_circuit_value() {
+            let left = self.children[0].eval_node::<TRACED, _>(context, state)?;
+            if left.as_boolean()? == short_circuit_value {
+                return Ok(Value::Boolean(short_circuit_value));
+            }
+            let right = self.children[1].eval_node::<TRACED, _>(context, state)?;
+            return self.operator().eval(&[left, right], context);
+        }
+
+        // Function arguments and indexed containers that are read from variables are borrowed instead of cloned.
+        match (self.operator(), self.children()) {
+            (Operator::FunctionIdentifier { identifier }, [argument]) => {
+                let argument = argument.eval_borrowed_with_state::<TRACED, _>(context, state)?;
+                return call_function(context, identifier, &argument, Some(state));
+            },
+            (Operator::Index, [container, key]) => {
+                let container = container.eval_borrowed_with_state::<TRACED, _>(context, state)?;
+                let key = key.eval_node::<TRACED, _>(context, state)?;
+                return index(&container, &key);
+            },
+            _ => {},
+        }
+
+        self.apply_operator_to_children::<TRACED, C>(context, state)
+    }
+
+    /// Applies the operator of this node to the values of all its children.
+    fn apply_operator_to_children<const TRACED: bool, C: Context>(
+        &self,
+        context: &C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        let mut arguments = Operands::new();
+        for child in self.children() {
+            arguments.push(child.eval_node::<TRACED, _>(context, state)?);
+        }
+        state.apply_operator(self.operator(), &arguments, |arguments| {
+            self.operator().eval(arguments, context)
+        })
+    }
+
+    /// Evaluates this node like `eval_node`, but borrows the value of a variable read from the context instead of cloning it.
+    fn eval_borrowed_with_state<'a, const TRACED: bool, C: Context>(
+        &'a self,
+        context: &'a C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Cow<'a, Value>> {
+        if !self.is_variable_read() {
+            return self.eval_node::<TRACED, _>(context, state).map(Cow::Owned);
+        }
+
+        if TRACED {
+            state.enter_node(self)?;
+        }
+        let result =
+            state
+                .count_node_evaluation()
+                .and_then(|()| match (self.operator(), self.children()) {
+                    (Operator::VariableIdentifierRead { identifier }, _) => {
+                        match read_variable(context, identifier) {
+                            Err(error) if state.options().ignores_error(&error) => {
+                                Ok(Cow::Owned(Value::Empty))
+                            },
+                            result => result,
+                        }
+                    },
+                    (_, [child]) => child.eval_borrowed_with_state::<TRACED, _>(context, state),
+                    _ => unreachable!("only variable reads are evaluated borrowed"),
+                });
+        if TRACED {
+            state.leave_node(self, result)
+        } else {
+            result.map_err(|error| error.with_span(self.span()))
+        }
+    }
+
+    /// Returns true if this node reads a variable, possibly wrapped in root nodes with a single child.
+    fn is_variable_read(&self) -> bool {
+        match (self.operator(), self.children()) {
+            (Operator::VariableIdentifierRead { .. }, _) => true,
+            (Operator::RootNode, [child]) => child.is_variable_read(),
+            _ => false,
+        }
+    }
+
+    fn eval_with_state_mut<C: ContextWithMutableVariables>(
+        &self,
+        context: &mut C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        if state.is_traced() {
+            self.eval_node_mut::<true, C>(context, state)
+        } else {
+            self.eval_node_mut::<false, C>(context, state)
+        }
+    }
+
+    /// Evaluates this node like `eval_node`, but with a mutable context.
+    fn eval_node_mut<const TRACED: bool, C: ContextWithMutableVariables>(
+        &self,
+        context: &mut C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        if !TRACED {
+            return self
+                .eval_operator_with_state_mut::<TRACED, C>(context, state)
+                .map_err(|error| error.with_span(self.span()));
+        }
+
+        state.enter_node(self)?;
+        let result = self.eval_operator_with_state_mut::<TRACED, C>(context, state);
+        state.leave_node(self, result)
+    }
+
+    fn eval_operator_with_state_mut<const TRACED: bool, C: ContextWithMutableVariables>(
+        &self,
+        context: &mut C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        state.count_node_evaluation()?;
+        if self.has_special_evaluation() {
+            self.eval_special_operator_with_state_mut::<TRACED, C>(context, state)
+        } else {
+            self.apply_operator_to_children_mut::<TRACED, C>(context, state)
+        }
+    }
+
+    /// Evaluates an operator that does not simply apply to the values of all its children, like lazy or short-circuiting operators.
+    fn eval_special_operator_with_state_mut<const TRACED: bool, C: ContextWithMutableVariables>(
+        &self,
+        context: &mut C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        if let Some([condition, if_true, if_false]) = self.lazy_if_arguments(context) {
+            return if condition
+                .eval_node_mut::<TRACED, _>(context, state)?
+                .as_boolean()?
+            {
+                if_true.eval_node_mut::<TRACED, _>(context, state)
+            } else {
+                if_false.eval_node_mut::<TRACED, _>(context, state)
+            };
+        }
+        if let Some((operands, comparisons)) = self.comparison_chain() {
+            let options = state.options();
+            return Self::eval_comparison_chain(&operands, &comparisons, options, |operand| {
+                operand.eval_node_mut::<TRACED, _>(context, state)
+            });
+        }
+        if let Operator::Lambda { parameters } = self.operator() {
+            return self.eval_lambda(parameters);
+        }
+        if self.is_let_chain() {
+            // Assignments to variables that are not bound by the chain still reach the context.
+            let mut scope = Scope::new(context as &mut dyn ContextWithMutableVariables);
+            let mut result = Value::Empty;
+            for child in self.children() {
+                result = match child.let_binding() {
+                    Some((identifier, value)) => {
+                        let value = value.eval_node_mut::<TRACED, _>(&mut scope, state)?;
+                        scope.bind(identifier.into(), value);
+                        Value::Empty
+                    },
+                    None => child.eval_node_mut::<TRACED, _>(&mut scope, state)?,
+                };
+            }
+            return Ok(result);
+        }
+        if let (Some(lambda), [argument]) = (self.called_lambda(context).cloned(), self.children())
+        {
+            let argument = argument.eval_node_mut::<TRACED, _>(context, state)?;
+            // The body of a lambda cannot assign to variables.
+            return lambda.call_with_state(context, &argument, state);
+        }
+
+        if self.is_null_coalescing() {
+            let left = self.children[0].eval_node_mut::<TRACED, _>(context, state);
+            if !is_absent(&left) {
+                return left;
+            }
+            return self.children[1].eval_node_mut::<TRACED, _>(context, state);
+        }
+
+        if let Some(short_circuit_value) = self.short_circuit_value() {
+            let left = self.children[0].eval_node_mut::<TRACED, _>(context, state)?;
+            if left.as_boolean()? == short_circuit_value {
+                return Ok(Value::Boolean(short_circuit_value));
+            }
+            let right = self.children[1].eval_node_mut::<TRACED, _>(context, state)?;
+            return self.operator().eval_mut(&[left, right], context);
+        }
+
+        if let (Operator::FunctionIdentifier { identifier }, [argument]) =
+            (self.operator(), self.children())
+        {
+            if argument.is_variable_read() {
+                let context = &*context;
+                let argument = argument.eval_borrowed_with_state::<TRACED, _>(context, state)?;
+                return call_function(context, identifier, &argument, Some(state));
+            }
+            let argument = argument.eval_node_mut::<TRACED, _>(context, state)?;
+            return call_function(&*context, identifier, &argument, Some(state));
+        }
+
+        self.apply_operator_to_children_mut::<TRACED, C>(context, state)
+    }
+
+    /// Applies the operator of this node to the values of all its children.
+    fn apply_operator_to_children_mut<const TRACED: bool, C: ContextWithMutableVariables>(
+        &self,
+        context: &mut C,
+        state: &mut EvalState,
+    ) -> EvalexprResult<Value> {
+        let mut arguments = Operands::new();
         for child in self.children() {
-            arguments.push(child.eval_with_context_mut(context)?);
+            arguments.push(child.eval_node_mut::<TRACED, _>(context, state)?);
         }
-        self.operator().eval_mut(&arguments, context)
+        state.apply_operator(self.operator(), &arguments, |arguments| {
+            self.operator().eval_mut(arguments, context)
+        })
     }
 
     /// Evaluates the operator tree rooted at this node.
@@ -578,6 +1199,211 @@ impl Node {
         self.eval_empty_with_context_mut(&mut HashMapContext::new())
     }
 
+    /// Returns a simplified copy of the operator tree rooted at this node.
+    ///
+    /// Variables that are known to the given context are replaced by their values, and subexpressions that only depend on constants are evaluated.
+    /// Evaluating the simplified tree gives the same result as evaluating the original tree with a context that contains the same variables as the given one.
+    ///
+    /// Function calls are never evaluated, and variables that are assigned to anywhere in the expression are never replaced.
+    /// Subexpressions whose evaluation fails are kept, so the error occurs when the simplified tree is evaluated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let context = context_map! { "y" => 4 }.unwrap(); // Do proper error handling here
+    /// let tree = build_operator_tree("2 * 3 + x * y").unwrap(); // Do proper error handling here
+    /// assert_eq!(tree.simplify(&context).to_string(), "6 + x * 4");
+    /// ```
+    pub fn simplify<C: Context>(&self, context: &C) -> Node {
+        let written_identifiers: Vec<&str> = self.iter_write_variable_identifiers().collect();
+        self.simplify_with(context, &written_identifiers, false)
+    }
+
+    /// Returns a copy of this tree in which all reads of the variable with the given identifier are replaced by the given value.
+    ///
+    /// Only identifiers that are equal to the given one are replaced, so reading a field of a map variable through a dot path is kept.
+    /// Assignments to the variable are kept as well, but reads after them still result in the given value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("x * y + x").unwrap(); // Do proper error handling here
+    /// let substituted = tree.substitute_variable("x", Value::from(3));
+    /// assert_eq!(substituted.to_string(), "3 * y + 3");
+    /// ```
+    pub fn substitute_variable(&self, identifier: &str, value: Value) -> Node {
+        let mut node = self.clone();
+        node.substitute_variable_in_place(identifier, &value);
+        node
+    }
+
+    /// Replaces all reads of the variable with the given identifier in this tree by the given value,
+    /// except for reads of parameters of lambdas and of let-bound variables with the same identifier.
+    fn substitute_variable_in_place(&mut self, identifier: &str, value: &Value) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match &node.operator {
+                Operator::VariableIdentifierRead { identifier: read } if **read == *identifier => {
+                    node.operator = Operator::value(value.clone());
+                },
+                Operator::Lambda { parameters }
+                    if parameters.iter().any(|parameter| parameter == identifier) => {},
+                Operator::Chain => {
+                    // The expressions after a let-binding of the identifier read the bound variable.
+                    for child in node.children.iter_mut() {
+                        let binds_identifier =
+                            matches!(child.let_binding(), Some((bound, _)) if bound == identifier);
+                        stack.push(child);
+                        if binds_identifier {
+                            break;
+                        }
+                    }
+                },
+                _ => stack.extend(node.children.iter_mut()),
+            }
+        }
+    }
+
+    /// Returns a copy of this tree in which all reads of and assignments to the variable with the given identifier
+    /// use the new identifier instead.
+    ///
+    /// Functions with the given identifier are not renamed,
+    /// and neither are parameters of lambdas and let-bound variables with the given identifier, nor the reads of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("a = a + 1; a(a)").unwrap(); // Do proper error handling here
+    /// let renamed = tree.rename_variable("a", "b");
+    /// assert_eq!(renamed.to_string(), "b = b + 1; a(b)");
+    /// ```
+    pub fn rename_variable(&self, old_identifier: &str, new_identifier: &str) -> Node {
+        let mut node = self.clone();
+        node.rename_variable_in_place(old_identifier, new_identifier);
+        node
+    }
+
+    /// Renames all reads of and assignments to the variable with the given identifier in this tree,
+    /// except for those within the scope of parameters of lambdas and of let-bound variables with the same identifier.
+    fn rename_variable_in_place(&mut self, old_identifier: &str, new_identifier: &str) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match &mut node.operator {
+                Operator::VariableIdentifierRead { identifier }
+                | Operator::VariableIdentifierWrite { identifier }
+                    if **identifier == *old_identifier =>
+                {
+                    *identifier = new_identifier.into();
+                },
+                Operator::Lambda { parameters }
+                    if parameters
+                        .iter()
+                        .any(|parameter| parameter == old_identifier) => {},
+                Operator::Chain => {
+                    // The value of a let-binding of the identifier still reads the outer variable, unlike the expressions after it.
+                    for child in node.children.iter_mut() {
+                        if matches!(child.let_binding(), Some((bound, _)) if bound == old_identifier)
+                        {
+                            stack.extend(child.let_binding_value_mut());
+                            break;
+                        }
+                        stack.push(child);
+                    }
+                },
+                _ => stack.extend(node.children.iter_mut()),
+            }
+        }
+    }
+
+    fn simplify_with<C: Context>(
+        &self,
+        context: &C,
+        written_identifiers: &[&str],
+        is_comparison_chain_operand: bool,
+    ) -> Node {
+        // Parameters of lambdas are not replaced in their body.
+        let mut shadowed_identifiers;
+        let written_identifiers = match self.operator() {
+            Operator::Lambda { parameters } => {
+                shadowed_identifiers = written_identifiers.to_vec();
+                shadowed_identifiers.extend(parameters.iter().map(String::as_str));
+                &shadowed_identifiers
+            },
+            _ => written_identifiers,
+        };
+        let is_ordering_comparison = self.operator().is_ordering_comparison();
+        let children = self
+            .children
+            .iter()
+            .enumerate()
+            .map(|(index, child)| {
+                child.simplify_with(
+                    context,
+                    written_identifiers,
+                    is_ordering_comparison && index == 0,
+                )
+            })
+            .collect();
+        let node = Node {
+            operator: self.operator.clone(),
+            children,
+            span: self.span,
+            source: self.source.clone(),
+        };
+
+        let value = match node.operator() {
+            Operator::VariableIdentifierRead { identifier }
+                if !written_identifiers.contains(&identifier.as_ref()) =>
+            {
+                // Variables whose lookup fails are kept, so the evaluation reports the error.
+                resolve_variable(context, identifier)
+                    .ok()
+                    .flatten()
+                    .map(Cow::into_owned)
+            },
+            Operator::VariableIdentifierRead { .. }
+            | Operator::VariableIdentifierWrite { .. }
+            | Operator::FunctionIdentifier { .. }
+            | Operator::Assign
+            | Operator::AddAssign
+            | Operator::SubAssign
+            | Operator::MulAssign
+            | Operator::DivAssign
+            | Operator::ModAssign
+            | Operator::ExpAssign
+            | Operator::AndAssign
+            | Operator::OrAssign
+            | Operator::Let => None,
+            // A comparison that is part of a chain is evaluated together with the whole chain.
+            _ if is_ordering_comparison && is_comparison_chain_operand => None,
+            _ => {
+                let operands = match node.comparison_chain() {
+                    Some((operands, _)) => operands,
+                    None => node.children.iter().collect(),
+                };
+                if operands
+                    .iter()
+                    .all(|operand| matches!(operand.operator(), Operator::Const { .. }))
+                {
+                    node.eval_with_context(&EmptyContext).ok()
+                } else {
+                    None
+                }
+            },
+        };
+
+        match value {
+            Some(value) => Node::new(Operator::value(value)),
+            None => node,
+        }
+    }
+
     /// Returns the children of this node as a slice.
     pub fn children(&self) -> &[Node] {
         &self.children
@@ -588,6 +1414,57 @@ impl Node {
         &self.operator
     }
 
+    /// Returns the part of the expression string this node was parsed from, covering the whole subexpression of this node.
+    ///
+    /// Returns `None` if the node was not parsed from a string, for example if it was built with the builder methods like `Node::add`.
+    /// The span is not updated when the tree is modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("1 + (2 * x)").unwrap(); // Do proper error handling here
+    /// let add = &tree.children()[0];
+    /// assert_eq!(add.span(), Some(Span::new(0, 11)));
+    /// assert_eq!(add.children()[1].span(), Some(Span::new(4, 11)));
+    /// assert_eq!(Node::variable("x").span(), None);
+    /// ```
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Returns the expression string this tree was parsed from.
+    ///
+    /// The expression string is only stored at the root of a tree returned by `build_operator_tree` and related functions,
+    /// so this returns `None` for all other nodes.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref().map(String::as_str)
+    }
+
+    /// Returns the text of the subexpression the given node of this tree was parsed from.
+    ///
+    /// Returns `None` if this node is not the root of a parsed tree, see `Node::source`,
+    /// or if the given node was not parsed from a string, see `Node::span`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let tree = build_operator_tree("a * max(b, 2) + 1").unwrap(); // Do proper error handling here
+    /// let max = tree
+    ///     .iter()
+    ///     .find(|node| matches!(node.operator(), Operator::FunctionIdentifier { .. }))
+    ///     .unwrap();
+    /// assert_eq!(tree.source_of(max), Some("max(b, 2)"));
+    /// assert_eq!(max.source_of(max), None);
+    /// ```
+    pub fn source_of(&self, node: &Node) -> Option<&str> {
+        let span = node.span?;
+        self.source()?.get(span.start..span.end)
+    }
+
     /// Returns a mutable reference to the vector containing the children of this node.
     ///
     /// WARNING: Writing to this might have unexpected results, as some operators require certain amounts and types of arguments.
@@ -602,6 +1479,127 @@ impl Node {
         &mut self.operator
     }
 
+    /// If this node is a call of the builtin `if` function with three arguments, returns the argument nodes.
+    /// The builtin `if` evaluates only the argument it returns, so the caller needs to evaluate it specially.
+    fn lazy_if_arguments<C: Context>(&self, context: &C) -> Option<&[Node]> {
+        self.if_arguments().filter(|_| is_builtin_if_used(context))
+    }
+
+    /// If this node is a call of a function named `if` with three arguments, returns the argument nodes.
+    fn if_arguments(&self) -> Option<&[Node]> {
+        match self.operator() {
+            Operator::FunctionIdentifier { identifier } if &**identifier == "if" => {},
+            _ => return None,
+        }
+
+        let mut argument = self.children.first()?;
+        while argument.operator() == &Operator::RootNode && argument.children.len() == 1 {
+            argument = &argument.children[0];
+        }
+
+        if argument.operator() == &Operator::Tuple && argument.children.len() == 3 {
+            Some(argument.children())
+        } else {
+            None
+        }
+    }
+
+    /// If this node is a logical operator that does not need to evaluate its right operand if the left operand has a certain value,
+    /// returns that value, which is then also the result of the operator.
+    fn short_circuit_value(&self) -> Option<bool> {
+        match self.operator() {
+            Operator::And if self.children.len() == 2 => Some(false),
+            Operator::Or if self.children.len() == 2 => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this node may need to be evaluated differently than by applying its operator to the values of all its children.
+    fn has_special_evaluation(&self) -> bool {
+        matches!(
+            self.operator(),
+            Operator::FunctionIdentifier { .. }
+                | Operator::Lambda { .. }
+                | Operator::Chain
+                | Operator::NullCoalesce
+                | Operator::And
+                | Operator::Or
+                | Operator::Index
+        ) || self.operator().is_ordering_comparison()
+    }
+
+    /// Returns true if this node is a null-coalescing operator with both operands,
+    /// which only evaluates its right operand if the left operand is absent.
+    fn is_null_coalescing(&self) -> bool {
+        matches!(self.operator(), Operator::NullCoalesce) && self.children.len() == 2
+    }
+
+    /// If this node is a chain of ordering comparisons like `a < b <= c`, returns the operands and the comparison operators of the chain.
+    /// Chains of ordering comparisons are evaluated like `a < b && b <= c`, but each operand is evaluated at most once.
+    fn comparison_chain(&self) -> Option<(Vec<&Node>, Vec<&Operator>)> {
+        fn is_ordering_comparison(node: &Node) -> bool {
+            node.operator().is_ordering_comparison() && node.children.len() == 2
+        }
+
+        if !is_ordering_comparison(self) || !is_ordering_comparison(&self.children[0]) {
+            return None;
+        }
+
+        let mut operands = Vec::new();
+        let mut comparisons = Vec::new();
+        let mut node = self;
+        while is_ordering_comparison(node) {
+            operands.push(&node.children[1]);
+            comparisons.push(node.operator());
+            node = &node.children[0];
+        }
+        operands.push(node);
+
+        operands.reverse();
+        comparisons.reverse();
+        Some((operands, comparisons))
+    }
+
+    fn eval_comparison_chain<F: FnMut(&Node) -> EvalexprResult<Value>>(
+        operands: &[&Node],
+        comparisons: &[&Operator],
+        options: &EvalOptions,
+        mut eval_operand: F,
+    ) -> EvalexprResult<Value> {
+        let mut left = eval_operand(operands[0])?;
+        for (operand, comparison) in operands[1..].iter().zip(comparisons) {
+            let right = eval_operand(operand)?;
+            match Self::eval_chained_comparison(comparison, left, right, options)? {
+                Some(right) => left = right,
+                None => return Ok(Value::Boolean(false)),
+            }
+        }
+        Ok(Value::Boolean(true))
+    }
+
+    /// Evaluates one comparison of a comparison chain.
+    /// Returns the right operand as the left operand of the next comparison if the comparison is true, and `None` otherwise.
+    fn eval_chained_comparison(
+        comparison: &Operator,
+        left: Value,
+        right: Value,
+        options: &EvalOptions,
+    ) -> EvalexprResult<Option<Value>> {
+        // Comparisons do not access the context.
+        let arguments = [left, right];
+        options.check_number_types(comparison, &arguments)?;
+        let result = match options.eval_by_options(comparison, &arguments) {
+            Some(result) => result?,
+            None => comparison.eval(&arguments, &EmptyContext)?,
+        };
+        if result.as_boolean()? {
+            let [_, right] = arguments;
+            Ok(Some(right))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn has_enough_children(&self) -> bool {
         Some(self.children().len()) == self.operator().max_argument_amount()
     }
@@ -614,7 +1612,32 @@ impl Node {
         }
     }
 
-    fn insert_back_prioritized(&mut self, node: Node, is_root_node: bool) -> EvalexprResult<()> {
+    /// Returns the amount of nodes on the longest path from this node to a leaf, including this node and the leaf.
+    pub(crate) fn depth(&self) -> usize {
+        // Iterate instead of recursing, as this is used to check trees that may be too deep for recursion.
+        let mut max_depth = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((node, depth)) = stack.pop() {
+            max_depth = max_depth.max(depth);
+            stack.extend(node.children.iter().map(|child| (child, depth + 1)));
+        }
+        max_depth
+    }
+
+    /// Inserts the node into the operator tree rooted at this node.
+    /// Fails if the insertion reaches deeper than the maximum depth of the config, counting this node as level `depth`.
+    fn insert_back_prioritized(
+        &mut self,
+        node: Node,
+        is_root_node: bool,
+        depth: usize,
+        config: &ParserConfig,
+    ) -> EvalexprResult<()> {
+        let max_depth = config.max_depth();
+        if depth >= max_depth {
+            return Err(EvalexprError::recursion_limit_exceeded(max_depth));
+        }
+
         // println!(
         //     "Inserting {:?} into {:?}, is_root_node = {is_root_node}",
         //     node.operator(),
@@ -623,7 +1646,7 @@ impl Node {
         // println!("Self is {:?}", self);
         if self.operator().precedence() < node.operator().precedence() || node.operator().is_unary() || is_root_node
             // Right-to-left chaining
-            || (self.operator().precedence() == node.operator().precedence() && !self.operator().is_left_to_right() && !node.operator().is_left_to_right())
+            || (self.operator().precedence() == node.operator().precedence() && is_right_to_left(self.operator(), config) && is_right_to_left(node.operator(), config))
         {
             if self.operator().is_leaf() {
                 Err(EvalexprError::AppendedToLeafNode)
@@ -635,17 +1658,19 @@ impl Node {
                     < node.operator().precedence() || node.operator().is_unary()
                     // Right-to-left chaining
                     || (last_child_operator.precedence()
-                    == node.operator().precedence() && !last_child_operator.is_left_to_right() && !node.operator().is_left_to_right())
+                    == node.operator().precedence() && is_right_to_left(last_child_operator, config) && is_right_to_left(node.operator(), config))
                 {
                     // println!(
                     //     "Recursing into {:?}",
                     //     self.children.last().unwrap().operator()
                     // );
                     // Unwrap cannot fail because is_leaf being false and has_enough_children being true implies that the operator wants and has at least one child
-                    self.children
-                        .last_mut()
-                        .unwrap()
-                        .insert_back_prioritized(node, false)
+                    self.children.last_mut().unwrap().insert_back_prioritized(
+                        node,
+                        false,
+                        depth + 1,
+                        config,
+                    )
                 } else {
                     // println!("Rotating");
                     if node.operator().is_leaf() {
@@ -695,10 +1720,94 @@ impl Node {
     }
 }
 
+/// Returns true if calls of the function `if` call the builtin `if` function in the given context.
+fn is_builtin_if_used<C: Context>(context: &C) -> bool {
+    !context.are_builtin_functions_disabled() && !context.has_function("if")
+}
+
+/// Returns true if the given result of the left operand of a null-coalescing operator is absent,
+/// i.e. if it is empty or if it failed because a variable does not exist.
+fn is_absent(left: &EvalexprResult<Value>) -> bool {
+    matches!(
+        left,
+        Ok(Value::Empty) | Err(EvalexprError::VariableIdentifierNotFound { .. })
+    )
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare iteratively, as recursively comparing deep trees can overflow the stack.
+        // The pre-order sequences of operators and amounts of children determine the trees uniquely.
+        let mut nodes = once(self).chain(self.iter());
+        let mut other_nodes = once(other).chain(other.iter());
+        loop {
+            match (nodes.next(), other_nodes.next()) {
+                (Some(node), Some(other_node)) => {
+                    if node.operator != other_node.operator
+                        || node.children.len() != other_node.children.len()
+                    {
+                        return false;
+                    }
+                },
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+}
+
+impl Eq for Node {}
+
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The span and source are left out, so that equal trees parsed from different strings look the same.
+        f.debug_struct("Node")
+            .field("operator", &self.operator)
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for node in once(self).chain(self.iter()) {
+            node.operator.hash(state);
+            node.children.len().hash(state);
+        }
+    }
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        // Drop the children iteratively, as recursively dropping deep trees can overflow the stack.
+        let mut stack = mem::take(&mut self.children);
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.children);
+        }
+    }
+}
+
+/// Returns true if chains of the given operator are parsed right-to-left with the given config.
+fn is_right_to_left(operator: &Operator, config: &ParserConfig) -> bool {
+    match operator {
+        Operator::Exp => !config.left_associative_exponentiation(),
+        operator => !operator.is_left_to_right(),
+    }
+}
+
+/// Returns true if reading the variable identifier `dependency` depends on the variable with the given identifier,
+/// either directly or by reading one of its fields through a dot path.
+pub(crate) fn is_dependency(dependency: &str, identifier: &str) -> bool {
+    dependency
+        .strip_prefix(identifier)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
 fn collapse_root_stack_to(
     root_stack: &mut Vec<Node>,
     mut root: Node,
     collapse_goal: &Node,
+    span: Span,
 ) -> EvalexprResult<Node> {
     loop {
         if let Some(mut potential_higher_root) = root_stack.pop() {
@@ -713,20 +1822,20 @@ fn collapse_root_stack_to(
             }
         } else {
             // This is the only way the topmost root node could have been removed
-            return Err(EvalexprError::UnmatchedRBrace);
+            return Err(EvalexprError::UnmatchedRBrace { span });
         }
     }
 
     Ok(root)
 }
 
-fn collapse_all_sequences(root_stack: &mut Vec<Node>) -> EvalexprResult<()> {
+fn collapse_all_sequences(root_stack: &mut Vec<Node>, span: Span) -> EvalexprResult<()> {
     // println!("Collapsing all sequences");
     // println!("Initial root stack is: {:?}", root_stack);
     let mut root = if let Some(root) = root_stack.pop() {
         root
     } else {
-        return Err(EvalexprError::UnmatchedRBrace);
+        return Err(EvalexprError::UnmatchedRBrace { span });
     };
 
     loop {
@@ -757,7 +1866,7 @@ fn collapse_all_sequences(root_stack: &mut Vec<Node>) -> EvalexprResult<()> {
             }
         } else {
             // This is the only way the topmost root node could have been removed
-            return Err(EvalexprError::UnmatchedRBrace);
+            return Err(EvalexprError::UnmatchedRBrace { span });
         }
     }
 
@@ -765,18 +1874,81 @@ fn collapse_all_sequences(root_stack: &mut Vec<Node>) -> EvalexprResult<()> {
     Ok(())
 }
 
-pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Node> {
-    let mut root_stack = vec![Node::root_node()];
-    let mut last_token_is_rightsided_value = false;
-    let mut token_iter = tokens.iter().peekable();
+/// The kinds of brackets that open a new root node while parsing.
+#[derive(Clone, Copy, PartialEq)]
+enum Group {
+    /// Parentheses group a subexpression.
+    Parentheses,
+    /// Brackets that do not follow a value construct an array.
+    Array,
+    /// Brackets that follow a value index into it.
+    Index,
+}
+
+/// The values of the operands of a node while it is evaluated.
+/// Most operators have at most two operands, which are stored inline with the `smallvec` feature.
+#[cfg(feature = "smallvec")]
+type Operands = smallvec::SmallVec<[Value; 2]>;
+#[cfg(not(feature = "smallvec"))]
+type Operands = Vec<Value>;
+
+/// The state of the parser between two tokens.
+#[derive(Clone)]
+struct ParserState {
+    root_stack: Vec<Node>,
+    /// The kinds and spans of the opening braces and brackets that were not closed yet.
+    open_groups: Vec<(Group, Span)>,
+    last_token_is_rightsided_value: bool,
+    /// The span of the keyword of a let-binding whose assignment was not parsed yet.
+    /// The tokenizer only emits a let token if it is followed by an identifier and an assignment.
+    let_binding: Option<Span>,
+    /// The identifiers parsed so far, so that all occurrences of an identifier share one allocation.
+    identifiers: BTreeSet<Arc<str>>,
+}
+
+impl ParserState {
+    fn new() -> Self {
+        Self {
+            root_stack: vec![Node::root_node()],
+            open_groups: Vec::new(),
+            last_token_is_rightsided_value: false,
+            let_binding: None,
+            identifiers: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the shared allocation of the given identifier.
+    fn intern(&mut self, identifier: &str) -> Arc<str> {
+        if let Some(interned) = self.identifiers.get(identifier) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = identifier.into();
+        self.identifiers.insert(interned.clone());
+        interned
+    }
 
-    while let Some(token) = token_iter.next().cloned() {
-        let next = token_iter.peek().cloned();
+    /// Parses the given token, which is followed by the token `next`.
+    fn parse_token(
+        &mut self,
+        token: &Token,
+        span: Span,
+        next: Option<&Token>,
+        config: &ParserConfig,
+    ) -> EvalexprResult<()> {
+        let max_depth = config.max_depth();
+        // An index node is inserted before its brackets open a new root node.
+        let mut opened_index = false;
 
         let node = match token.clone() {
-            Token::Plus => Some(Node::new(Operator::Add)),
+            Token::Plus => {
+                if self.last_token_is_rightsided_value {
+                    Some(Node::new(Operator::Add))
+                } else {
+                    Some(Node::new(Operator::Pos))
+                }
+            },
             Token::Minus => {
-                if last_token_is_rightsided_value {
+                if self.last_token_is_rightsided_value {
                     Some(Node::new(Operator::Sub))
                 } else {
                     Some(Node::new(Operator::Neg))
@@ -793,23 +1965,80 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Node
             Token::Lt => Some(Node::new(Operator::Lt)),
             Token::Geq => Some(Node::new(Operator::Geq)),
             Token::Leq => Some(Node::new(Operator::Leq)),
+            Token::In => Some(Node::new(Operator::In)),
             Token::And => Some(Node::new(Operator::And)),
-            Token::Or => Some(Node::new(Operator::Or)),
+            Token::Or => {
+                if self.last_token_is_rightsided_value {
+                    Some(Node::new(Operator::Or))
+                } else {
+                    // A lambda without parameters
+                    Some(Node::new(Operator::lambda(Vec::new())))
+                }
+            },
             Token::Not => Some(Node::new(Operator::Not)),
+            Token::NullCoalesce => Some(Node::new(Operator::NullCoalesce)),
 
             Token::LBrace => {
-                root_stack.push(Node::root_node());
+                // Every element of the root stack is nested into the one below it.
+                if self.root_stack.len() >= max_depth {
+                    return Err(EvalexprError::recursion_limit_exceeded(max_depth));
+                }
+                self.root_stack.push(Node::root_node());
+                self.open_groups.push((Group::Parentheses, span));
                 None
             },
             Token::RBrace => {
-                if root_stack.len() <= 1 {
-                    return Err(EvalexprError::UnmatchedRBrace);
+                if self.root_stack.len() <= 1
+                    || !matches!(self.open_groups.last(), Some((Group::Parentheses, _)))
+                {
+                    return Err(EvalexprError::UnmatchedRBrace { span });
                 } else {
-                    collapse_all_sequences(&mut root_stack)?;
-                    root_stack.pop()
+                    collapse_all_sequences(&mut self.root_stack, span)?;
+                    let group_span = self.close_group(span);
+                    self.root_stack.pop().map(|root| root.with_span(group_span))
+                }
+            },
+            Token::LBracket => {
+                if self.root_stack.len() >= max_depth {
+                    return Err(EvalexprError::recursion_limit_exceeded(max_depth));
+                }
+                if self.last_token_is_rightsided_value {
+                    opened_index = true;
+                    self.open_groups.push((Group::Index, span));
+                    Some(Node::new(Operator::Index))
+                } else {
+                    self.root_stack.push(Node::root_node());
+                    self.open_groups.push((Group::Array, span));
+                    None
+                }
+            },
+            Token::RBracket => {
+                let group = match self.open_groups.last() {
+                    Some((group, _))
+                        if *group != Group::Parentheses && self.root_stack.len() > 1 =>
+                    {
+                        *group
+                    },
+                    _ => return Err(EvalexprError::UnmatchedRBracket { span }),
+                };
+                collapse_all_sequences(&mut self.root_stack, span)?;
+                let group_span = self.close_group(span);
+                let root = self.root_stack.pop();
+                if group == Group::Array {
+                    root.map(|root| root.into_array().with_span(group_span))
+                } else {
+                    root.map(|root| root.with_span(group_span))
                 }
             },
 
+            Token::Let => {
+                self.let_binding = Some(span);
+                None
+            },
+            Token::Assign if self.let_binding.is_some() => {
+                let let_span = self.let_binding.take().unwrap_or(span);
+                Some(Node::new(Operator::Let).with_span(Span::new(let_span.start, span.end)))
+            },
             Token::Assign => Some(Node::new(Operator::Assign)),
             Token::PlusAssign => Some(Node::new(Operator::AddAssign)),
             Token::MinusAssign => Some(Node::new(Operator::SubAssign)),
@@ -822,8 +2051,12 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Node
 
             Token::Comma => Some(Node::new(Operator::Tuple)),
             Token::Semicolon => Some(Node::new(Operator::Chain)),
+            Token::DotDot => Some(Node::new(Operator::Range { inclusive: false })),
+            Token::DotDotEq => Some(Node::new(Operator::Range { inclusive: true })),
+            Token::Lambda(parameters) => Some(Node::new(Operator::lambda(parameters))),
 
             Token::Identifier(identifier) => {
+                let identifier = self.intern(&identifier);
                 let mut result = Some(Node::new(Operator::variable_identifier_read(
                     identifier.clone(),
                 )));
@@ -840,27 +2073,35 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Node
             },
             Token::Float(float) => Some(Node::new(Operator::value(Value::Float(float)))),
             Token::Int(int) => Some(Node::new(Operator::value(Value::Int(int)))),
+            #[cfg(feature = "bigint")]
+            Token::BigInt(int) => Some(Node::new(Operator::value(Value::BigInt(int)))),
+            #[cfg(feature = "decimal")]
+            Token::Decimal(decimal) => Some(Node::new(Operator::value(Value::Decimal(decimal)))),
             Token::Boolean(boolean) => Some(Node::new(Operator::value(Value::Boolean(boolean)))),
-            Token::String(string) => Some(Node::new(Operator::value(Value::String(string)))),
+            Token::String(string) => Some(Node::new(Operator::value(Value::String(
+                string.into_owned(),
+            )))),
         };
 
         if let Some(mut node) = node {
-            // Need to pop and then repush here, because Rust 1.33.0 cannot release the mutable borrow of root_stack before the end of this complete if-statement
-            if let Some(mut root) = root_stack.pop() {
+            // Nodes closing a group already cover the whole group.
+            node.span.get_or_insert(span);
+            // Need to pop and then repush here, because Rust 1.33.0 cannot release the mutable borrow of self.root_stack before the end of this complete if-statement
+            if let Some(mut root) = self.root_stack.pop() {
                 if node.operator().is_sequence() {
                     // println!("Found a sequence operator");
-                    // println!("Stack before sequence operation: {:?}, {:?}", root_stack, root);
+                    // println!("Stack before sequence operation: {:?}, {:?}", self.root_stack, root);
                     // If root.operator() and node.operator() are of the same variant, ...
                     if mem::discriminant(root.operator()) == mem::discriminant(node.operator()) {
                         // ... we create a new root node for the next expression in the sequence
                         root.children.push(Node::root_node());
-                        root_stack.push(root);
+                        self.root_stack.push(root);
                     } else if root.operator() == &Operator::RootNode {
                         // If the current root is an actual root node, we start a new sequence
                         node.children.push(root);
                         node.children.push(Node::root_node());
-                        root_stack.push(Node::root_node());
-                        root_stack.push(node);
+                        self.root_stack.push(Node::root_node());
+                        self.root_stack.push(node);
                     } else {
                         // Otherwise, we combine the sequences based on their precedences
                         // TODO I'm not sure about this <, as I have no example for different sequence operators with the same precedence
@@ -869,49 +2110,146 @@ pub(crate) fn tokens_to_operator_tree(tokens: Vec<Token>) -> EvalexprResult<Node
                             if let Some(last_root_child) = root.children.pop() {
                                 node.children.push(last_root_child);
                                 node.children.push(Node::root_node());
-                                root_stack.push(root);
-                                root_stack.push(node);
+                                self.root_stack.push(root);
+                                self.root_stack.push(node);
                             } else {
                                 // Once a sequence has been pushed on top of the stack, it also gets a child
                                 unreachable!()
                             }
                         } else {
                             // If the new sequence doesn't have a higher precedence, then all sequences with a higher precedence are collapsed below this one
-                            root = collapse_root_stack_to(&mut root_stack, root, &node)?;
+                            root = collapse_root_stack_to(&mut self.root_stack, root, &node, span)?;
                             node.children.push(root);
-                            root_stack.push(node);
+                            self.root_stack.push(node);
                         }
                     }
-                // println!("Stack after sequence operation: {:?}", root_stack);
+                // println!("Stack after sequence operation: {:?}", self.root_stack);
                 } else if root.operator().is_sequence() {
                     if let Some(mut last_root_child) = root.children.pop() {
-                        last_root_child.insert_back_prioritized(node, true)?;
+                        last_root_child.insert_back_prioritized(
+                            node,
+                            true,
+                            self.root_stack.len() + 1,
+                            config,
+                        )?;
                         root.children.push(last_root_child);
-                        root_stack.push(root);
+                        self.root_stack.push(root);
                     } else {
                         // Once a sequence has been pushed on top of the stack, it also gets a child
                         unreachable!()
                     }
                 } else {
-                    root.insert_back_prioritized(node, true)?;
-                    root_stack.push(root);
+                    // The popped root is nested into the remaining elements of the root stack.
+                    root.insert_back_prioritized(node, true, self.root_stack.len() + 1, config)?;
+                    self.root_stack.push(root);
                 }
             } else {
-                return Err(EvalexprError::UnmatchedRBrace);
+                return Err(EvalexprError::UnmatchedRBrace { span });
+            }
+        }
+
+        if opened_index {
+            self.root_stack.push(Node::root_node());
+        }
+
+        self.last_token_is_rightsided_value = token.is_rightsided_value();
+        Ok(())
+    }
+
+    /// Removes the innermost group of braces or brackets, returning the span from its opening token to the given span of its closing token.
+    fn close_group(&mut self, span: Span) -> Span {
+        match self.open_groups.pop() {
+            Some((_, open_span)) => Span::new(open_span.start, span.end),
+            None => span,
+        }
+    }
+
+    /// Discards the innermost group of braces or brackets together with its contents, if it is the group the given closing token closes.
+    /// This skips a group whose closing token could not be parsed.
+    fn skip_closing_token(&mut self, token: &Token) {
+        let closes_group = matches!(
+            (token, self.open_groups.last()),
+            (Token::RBrace, Some((Group::Parentheses, _)))
+                | (Token::RBracket, Some((Group::Array | Group::Index, _)))
+        );
+        if closes_group {
+            self.open_groups.pop();
+            if self.root_stack.len() > 1 {
+                self.root_stack.pop();
             }
         }
+    }
 
-        last_token_is_rightsided_value = token.is_rightsided_value();
+    /// Terminates all sequences and returns the parsed operator tree.
+    fn finish(mut self, last_span: Span) -> EvalexprResult<Node> {
+        // In the end, all sequences are implicitly terminated
+        collapse_all_sequences(&mut self.root_stack, last_span)?;
+
+        if self.root_stack.len() > 1 {
+            match self.open_groups.last().copied() {
+                Some((Group::Parentheses, span)) => Err(EvalexprError::UnmatchedLBrace { span }),
+                Some((_, span)) => Err(EvalexprError::UnmatchedLBracket { span }),
+                None => Err(EvalexprError::UnmatchedLBrace { span: last_span }),
+            }
+        } else if let Some(mut root) = self.root_stack.pop() {
+            root.extend_spans();
+            root.convert_slices();
+            Ok(root)
+        } else {
+            Err(EvalexprError::UnmatchedRBrace { span: last_span })
+        }
     }
+}
 
-    // In the end, all sequences are implicitly terminated
-    collapse_all_sequences(&mut root_stack)?;
+/// Parses the tokens of the expression string `source` into an operator tree.
+/// Tokens that cannot be parsed are skipped and unclosed braces and brackets are closed at the end if the `recovery` records errors.
+pub(crate) fn tokens_to_operator_tree(
+    source: &str,
+    tokens: Vec<(Token, Span)>,
+    config: &ParserConfig,
+    recovery: &mut ErrorRecovery,
+) -> EvalexprResult<Node> {
+    let mut state = ParserState::new();
+    let mut last_span = Span::new(0, 0);
+
+    for (index, (token, span)) in tokens.iter().enumerate() {
+        let next = tokens.get(index + 1).map(|(token, _)| token);
+        last_span = *span;
+        // When recovering, a token that cannot be parsed is skipped by restoring the state from before it.
+        let snapshot = recovery.is_recovering().then(|| state.clone());
+        if let Err(error) = state.parse_token(token, *span, next, config) {
+            recovery.recover(error, *span)?;
+            if let Some(snapshot) = snapshot {
+                state = snapshot;
+                state.skip_closing_token(token);
+            }
+        }
+    }
 
-    if root_stack.len() > 1 {
-        Err(EvalexprError::UnmatchedLBrace)
-    } else if let Some(root) = root_stack.pop() {
-        Ok(root)
-    } else {
-        Err(EvalexprError::UnmatchedRBrace)
+    if recovery.is_recovering() {
+        while let Some((group, span)) = state.open_groups.last().copied() {
+            let (error, closing_token) = match group {
+                Group::Parentheses => (EvalexprError::UnmatchedLBrace { span }, Token::RBrace),
+                Group::Array | Group::Index => {
+                    (EvalexprError::UnmatchedLBracket { span }, Token::RBracket)
+                },
+            };
+            recovery.recover(error, span)?;
+            let snapshot = state.clone();
+            if state
+                .parse_token(&closing_token, last_span, None, config)
+                .is_err()
+            {
+                state = snapshot;
+                state.skip_closing_token(&closing_token);
+            }
+        }
     }
+
+    let mut root = state.finish(last_span).or_else(|error| {
+        recovery.recover(error, last_span)?;
+        Ok(Node::root_node())
+    })?;
+    root.source = Some(Arc::new(source.into()));
+    Ok(root)
 }
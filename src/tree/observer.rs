@@ -0,0 +1,74 @@
+use crate::{
+    context::Context,
+    error::{EvalexprError, EvalexprResult},
+    value::Value,
+    EvalOptions, Node,
+};
+
+use super::EvalState;
+
+/// An observer that is notified when the evaluation of an operator tree enters and leaves each node.
+///
+/// The notifications form an audit trail of an evaluation, explaining for example why a rule of a rule engine fired.
+/// An observer is passed to `Node::eval_with_context_and_observer` or the crate-level `eval_with_context_and_observer`.
+///
+/// The evaluation enters a node before evaluating its children, and leaves it after it was evaluated, so the notifications of the children are nested between those of their parent.
+/// Children that are not evaluated, like the right operand of `&&` if the left operand is false, are not reported.
+/// Both methods do nothing by default.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// #[derive(Default)]
+/// struct AuditTrail(Vec<String>);
+///
+/// impl EvalObserver for AuditTrail {
+///     fn exit_node(&mut self, node: &Node, operands: &[Value], result: Result<&Value, &EvalexprError>) {
+///         if let (Operator::Gt, Ok(result)) = (node.operator(), result) {
+///             self.0.push(format!("{} > {} is {}", operands[0], operands[1], result));
+///         }
+///     }
+/// }
+///
+/// let context = context_map! { "age" => 70, "income" => 1200 }.unwrap(); // Do proper error handling here
+/// let mut trail = AuditTrail::default();
+/// assert_eq!(
+///     eval_with_context_and_observer("age > 65 || income > 5000", &context, &mut trail),
+///     Ok(Value::from(true))
+/// );
+/// assert_eq!(trail.0, ["70 > 65 is true"]);
+/// ```
+pub trait EvalObserver {
+    /// Called before the given node is evaluated.
+    fn enter_node(&mut self, _node: &Node) {}
+
+    /// Called after the given node was evaluated, with the values of the children of the node that were evaluated, in the order of their evaluation,
+    /// and the value the node resulted in or the error its evaluation failed with.
+    ///
+    /// The operands are the values of the arguments of operators and function calls.
+    /// A node whose evaluation failed may have fewer operands than children.
+    fn exit_node(
+        &mut self,
+        _node: &Node,
+        _operands: &[Value],
+        _result: Result<&Value, &EvalexprError>,
+    ) {
+    }
+}
+
+impl Node {
+    /// Evaluates the operator tree rooted at this node with the given context, notifying the given observer about the evaluation of each node.
+    ///
+    /// Fails, if one of the operators in the expression tree fails.
+    /// See the documentation of `EvalObserver` for an example.
+    pub fn eval_with_context_and_observer<C: Context, O: EvalObserver + Send>(
+        &self,
+        context: &C,
+        observer: &mut O,
+    ) -> EvalexprResult<Value> {
+        let options = EvalOptions::default();
+        self.eval_with_state(context, &mut EvalState::with_observer(&options, observer))
+    }
+}
@@ -0,0 +1,519 @@
+use crate::{
+    operator::Operator,
+    value::{value_type::ValueType, IntType, Value},
+    EvalexprError, EvalexprResult,
+};
+use alloc::{vec, vec::Vec};
+use core::{borrow::Borrow, convert::TryFrom, ops::Range};
+
+use super::{
+    cache::{CacheKey, CacheKeys},
+    EvalCache, EvalObserver, EvaluationPolicy, Lambda, Node,
+};
+
+/// Options for evaluating an operator tree with `Node::eval_with_context_and_options` and related functions.
+///
+/// The default options are used by all `eval` functions that do not take options.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let options = EvalOptions::new().with_max_node_evaluations(Some(5));
+/// let context = HashMapContext::new();
+/// assert_eq!(eval_with_context_and_options("1 + 2", &context, &options), Ok(Value::from(3)));
+/// assert_eq!(
+///     eval_with_context_and_options("1 + 2 + 3", &context, &options),
+///     Err(EvalexprError::BudgetExceeded { max_node_evaluations: 5 })
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvalOptions {
+    max_node_evaluations: Option<usize>,
+    division_by_zero: DivisionByZeroMode,
+    strict_number_types: bool,
+    lenient: bool,
+    integer_exponentiation: bool,
+    policy: Option<EvaluationPolicy>,
+    #[cfg(feature = "rand")]
+    random_seed: Option<u64>,
+}
+
+/// Determines the result of dividing an integer or decimal number by zero with the operators `/` and `%`.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context = HashMapContext::new();
+/// let options = EvalOptions::new().with_division_by_zero(DivisionByZeroMode::Empty);
+/// assert_eq!(eval_with_context_and_options("1 / 0", &context, &options), Ok(Value::Empty));
+/// let options = EvalOptions::new().with_division_by_zero(DivisionByZeroMode::Float);
+/// assert_eq!(eval_with_context_and_options("-1 / 0", &context, &options), Ok(Value::from(f64::NEG_INFINITY)));
+/// assert_eq!(
+///     eval_with_context("1 / 0", &context),
+///     Err(EvalexprError::DivisionByZero { dividend: Value::from(1) })
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DivisionByZeroMode {
+    /// The division fails with `EvalexprError::DivisionByZero`.
+    #[default]
+    Error,
+    /// The division results in `Value::Empty`.
+    Empty,
+    /// The division is performed with floats, resulting in infinity or `NaN`.
+    Float,
+}
+
+impl EvalOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum amount of nodes that may be evaluated, or removes the limit if `None` is given.
+    /// By default, there is no limit.
+    ///
+    /// Every node of the operator tree counts once each time it is evaluated, including the root node of the tree.
+    /// If the limit is reached, the evaluation is aborted with `EvalexprError::BudgetExceeded`.
+    /// This allows to bound the time spent on evaluating untrusted expressions.
+    pub fn with_max_node_evaluations(mut self, max_node_evaluations: Option<usize>) -> Self {
+        self.max_node_evaluations = max_node_evaluations;
+        self
+    }
+
+    /// Sets the result of dividing an integer or decimal number by zero.
+    /// By default, such divisions fail with `EvalexprError::DivisionByZero`.
+    ///
+    /// This applies to the operators `/` and `%`, but not to the assignment operators `/=` and `%=`,
+    /// as the type of a variable cannot be changed by an assignment.
+    pub fn with_division_by_zero(mut self, division_by_zero: DivisionByZeroMode) -> Self {
+        self.division_by_zero = division_by_zero;
+        self
+    }
+
+    /// Sets if the arithmetic and ordering comparison operators require their operands to be numbers of the same type.
+    /// By default, this is false, and integers are converted into floats when they are combined with floats.
+    ///
+    /// In strict mode, combining numbers of different types, like `1 + 2.5` or `2.0 > 1`, fails with `EvalexprError::WrongTypeCombination`.
+    /// Numbers can still be converted explicitly with the builtin functions `int` and `float`.
+    pub fn with_strict_number_types(mut self, strict_number_types: bool) -> Self {
+        self.strict_number_types = strict_number_types;
+        self
+    }
+
+    /// Sets if the evaluation tolerates missing values, e.g. to evaluate expressions on partially filled data sets.
+    /// By default, this is false.
+    ///
+    /// In lenient mode, reading a variable that does not exist results in `Value::Empty` instead of failing with `EvalexprError::VariableIdentifierNotFound`,
+    /// and the ordering comparisons `<`, `>`, `<=` and `>=` are false if one of their operands is empty, instead of failing with a type error.
+    /// Comparing an empty value with `==` and `!=` is allowed anyway, and `a ?? 0` gives a default for a missing variable in both modes.
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets if the exponentiation of two integers with a non-negative exponent results in an integer.
+    /// By default, this is false, and the exponentiation operator always results in a float.
+    ///
+    /// If enabled, `2 ^ 10` results in `Value::Int(1024)`, and results that do not fit into an `IntType` fail with `EvalexprError::ExponentiationError`.
+    /// Negative exponents and floats still result in floats.
+    /// This applies to the operator `^`, but not to the assignment operator `^=`.
+    pub fn with_integer_exponentiation(mut self, integer_exponentiation: bool) -> Self {
+        self.integer_exponentiation = integer_exponentiation;
+        self
+    }
+
+    /// Sets an `EvaluationPolicy` that is enforced during the evaluation, or removes it if `None` is given.
+    /// By default, there is no policy.
+    ///
+    /// Each node is checked before it is evaluated, and each value it results in is checked after its evaluation.
+    /// If the policy is violated, the evaluation is aborted with the error of the violated restriction.
+    pub fn with_policy(mut self, policy: Option<EvaluationPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets the seed of the random number generator used by the builtin functions `random`, `random_int` and `random_choice`,
+    /// or uses a randomly seeded generator if `None` is given, which is the default.
+    ///
+    /// Each evaluation starts with a new generator seeded with the given seed,
+    /// so evaluating the same expression with the same options produces the same random numbers.
+    /// This is meant for reproducible results, like in tests, and the numbers are not suitable for cryptographic purposes.
+    #[cfg(feature = "rand")]
+    pub fn with_random_seed(mut self, random_seed: Option<u64>) -> Self {
+        self.random_seed = random_seed;
+        self
+    }
+
+    /// Returns the maximum amount of nodes that may be evaluated, or `None` if there is no limit.
+    pub fn max_node_evaluations(&self) -> Option<usize> {
+        self.max_node_evaluations
+    }
+
+    /// Returns the result of dividing an integer or decimal number by zero.
+    pub fn division_by_zero(&self) -> DivisionByZeroMode {
+        self.division_by_zero
+    }
+
+    /// Returns true if the arithmetic and ordering comparison operators require their operands to be numbers of the same type.
+    pub fn strict_number_types(&self) -> bool {
+        self.strict_number_types
+    }
+
+    /// Returns true if the evaluation tolerates missing values.
+    pub fn lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Returns true if the exponentiation of two integers with a non-negative exponent results in an integer.
+    pub fn integer_exponentiation(&self) -> bool {
+        self.integer_exponentiation
+    }
+
+    /// Returns the `EvaluationPolicy` that is enforced during the evaluation, if any.
+    pub fn policy(&self) -> Option<&EvaluationPolicy> {
+        self.policy.as_ref()
+    }
+
+    /// Returns the seed of the random number generator, or `None` if it is seeded randomly.
+    #[cfg(feature = "rand")]
+    pub fn random_seed(&self) -> Option<u64> {
+        self.random_seed
+    }
+
+    /// Checks the evaluated arguments of an operator before the operator is evaluated with them.
+    ///
+    /// Fails if the arguments violate the strict number types, or if they would create a range that exceeds the maximum tuple length of the policy.
+    pub(crate) fn check_arguments(
+        &self,
+        operator: &Operator,
+        arguments: &[Value],
+    ) -> EvalexprResult<()> {
+        self.check_number_types(operator, arguments)?;
+        match (self.policy(), operator) {
+            (Some(policy), Operator::Range { inclusive }) => {
+                policy.check_range_length(arguments, *inclusive)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluates the operator with the given arguments if the options change its result, and returns `None` otherwise.
+    ///
+    /// This is the case for ordering comparisons with an empty operand in lenient mode, which are false,
+    /// and for exponentiations of integers with integer exponentiation.
+    pub(crate) fn eval_by_options(
+        &self,
+        operator: &Operator,
+        arguments: &[Value],
+    ) -> Option<EvalexprResult<Value>> {
+        use crate::operator::Operator::*;
+        match (operator, arguments) {
+            (Gt | Lt | Geq | Leq, _) if self.lenient && arguments.iter().any(Value::is_empty) => {
+                Some(Ok(Value::Boolean(false)))
+            },
+            (Exp, [Value::Int(base), Value::Int(exponent)])
+                if self.integer_exponentiation && *exponent >= 0 =>
+            {
+                Some(
+                    checked_pow(*base, *exponent)
+                        .map(Value::Int)
+                        .ok_or_else(|| {
+                            EvalexprError::exponentiation_error(
+                                arguments[0].clone(),
+                                arguments[1].clone(),
+                            )
+                        }),
+                )
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns true if these options change how some operator is applied to its arguments, or how its errors are handled.
+    fn changes_operators(&self) -> bool {
+        self.division_by_zero != DivisionByZeroMode::Error
+            || self.strict_number_types
+            || self.lenient
+            || self.integer_exponentiation
+            || self.policy.is_some()
+    }
+
+    /// Returns true if the given error is ignored in lenient mode, because it reports reading a variable that does not exist.
+    pub(crate) fn ignores_error(&self, error: &EvalexprError) -> bool {
+        self.lenient && matches!(error, EvalexprError::VariableIdentifierNotFound { .. })
+    }
+
+    /// Fails if strict number types are enabled and the operator is applied to numbers of different types.
+    pub(crate) fn check_number_types(
+        &self,
+        operator: &Operator,
+        arguments: &[Value],
+    ) -> EvalexprResult<()> {
+        use crate::operator::Operator::*;
+        if !self.strict_number_types
+            || !matches!(
+                operator,
+                Add | Sub | Mul | Div | Mod | Exp | Gt | Lt | Geq | Leq
+            )
+        {
+            return Ok(());
+        }
+
+        match arguments {
+            [a, b]
+                if a.is_number() && b.is_number() && ValueType::from(a) != ValueType::from(b) =>
+            {
+                Err(EvalexprError::wrong_type_combination(
+                    operator.clone(),
+                    vec![a.into(), b.into()],
+                ))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the range of the addresses of the given nodes, to recognize them without borrowing them.
+fn address_range(nodes: &[Node]) -> Range<usize> {
+    let addresses = nodes.as_ptr_range();
+    addresses.start as usize..addresses.end as usize
+}
+
+/// Raises the integer to the given non-negative power, or returns `None` if the result does not fit into an `IntType`.
+fn checked_pow(base: IntType, exponent: IntType) -> Option<IntType> {
+    match u32::try_from(exponent) {
+        Ok(exponent) => base.checked_pow(exponent),
+        // For larger exponents, only the powers of 0, 1 and -1 fit.
+        Err(_) => match base {
+            0 | 1 => Some(base),
+            -1 => Some(if exponent % 2 == 0 { 1 } else { -1 }),
+            _ => None,
+        },
+    }
+}
+
+/// The state of a single evaluation of an operator tree.
+pub(crate) struct EvalState<'options> {
+    options: &'options EvalOptions,
+    node_evaluations: usize,
+    lambda_call_depth: usize,
+    depth: usize,
+    cache: Option<(&'options mut EvalCache, CacheKeys)>,
+    observer: Option<&'options mut (dyn EvalObserver + Send)>,
+    /// For each node entered while observing, the addresses of its children and the values of those that were evaluated.
+    observed_operands: Vec<(Range<usize>, Vec<Value>)>,
+    #[cfg(feature = "rand")]
+    random_generator: Option<rand::rngs::StdRng>,
+}
+
+impl<'options> EvalState<'options> {
+    pub(crate) fn new(options: &'options EvalOptions) -> Self {
+        Self {
+            options,
+            node_evaluations: 0,
+            lambda_call_depth: 0,
+            depth: 0,
+            cache: None,
+            observer: None,
+            observed_operands: Vec::new(),
+            #[cfg(feature = "rand")]
+            random_generator: options
+                .random_seed
+                .map(<rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64),
+        }
+    }
+
+    /// Creates the state of an evaluation that caches the values of the nodes with the given keys in the given cache.
+    pub(crate) fn with_cache(
+        options: &'options EvalOptions,
+        cache: &'options mut EvalCache,
+        cache_keys: CacheKeys,
+    ) -> Self {
+        Self {
+            cache: Some((cache, cache_keys)),
+            ..Self::new(options)
+        }
+    }
+
+    /// Creates the state of an evaluation that notifies the given observer about the evaluation of each node.
+    pub(crate) fn with_observer(
+        options: &'options EvalOptions,
+        observer: &'options mut (dyn EvalObserver + Send),
+    ) -> Self {
+        Self {
+            observer: Some(observer),
+            ..Self::new(options)
+        }
+    }
+
+    /// Returns true if this evaluation has a cache, an observer or an `EvaluationPolicy`,
+    /// which need to enter and leave each evaluated node.
+    pub(crate) fn is_traced(&self) -> bool {
+        self.cache.is_some() || self.observer.is_some() || self.options.policy().is_some()
+    }
+
+    /// Returns the cache of this evaluation and the keys of the evaluated tree, if it has a cache.
+    pub(crate) fn cache(&mut self) -> Option<(&mut EvalCache, &CacheKeys)> {
+        self.cache
+            .as_mut()
+            .map(|(cache, cache_keys)| (&mut **cache, &*cache_keys))
+    }
+
+    /// Returns the key under which the value of the given node is cached, if this evaluation has a cache and the value can be cached.
+    pub(crate) fn cache_key(&self, node: &Node) -> Option<CacheKey> {
+        self.cache
+            .as_ref()
+            .and_then(|(_, cache_keys)| cache_keys.get(node))
+            .cloned()
+    }
+
+    /// Returns the seeded random number generator of this evaluation, if the options contain a seed.
+    #[cfg(feature = "rand")]
+    pub(crate) fn random_generator(&mut self) -> Option<&mut rand::rngs::StdRng> {
+        self.random_generator.as_mut()
+    }
+
+    /// Returns the options of this evaluation.
+    pub(crate) fn options(&self) -> &'options EvalOptions {
+        self.options
+    }
+
+    /// Applies the operator to its evaluated arguments with `apply`, checking the arguments and handling the result as configured by the options.
+    pub(crate) fn apply_operator<F: FnOnce(&[Value]) -> EvalexprResult<Value>>(
+        &self,
+        operator: &Operator,
+        arguments: &[Value],
+        apply: F,
+    ) -> EvalexprResult<Value> {
+        if !self.options.changes_operators() {
+            return apply(arguments);
+        }
+
+        self.options.check_arguments(operator, arguments)?;
+        match self.options.eval_by_options(operator, arguments) {
+            Some(result) => result,
+            None => self.handle_errors(operator, apply(arguments)),
+        }
+    }
+
+    /// Replaces the error of dividing by zero with the given operator as configured by the `DivisionByZeroMode` of the options,
+    /// and the error of reading a variable that does not exist with `Value::Empty` in lenient mode.
+    fn handle_errors(
+        &self,
+        operator: &Operator,
+        result: EvalexprResult<Value>,
+    ) -> EvalexprResult<Value> {
+        match (result, self.options.division_by_zero) {
+            (Err(EvalexprError::DivisionByZero { dividend }), mode)
+                if mode != DivisionByZeroMode::Error
+                    && matches!(operator, Operator::Div | Operator::Mod) =>
+            {
+                if mode == DivisionByZeroMode::Empty {
+                    Ok(Value::Empty)
+                } else if operator == &Operator::Div {
+                    Ok(Value::Float(dividend.as_number()? / 0.0))
+                } else {
+                    Ok(Value::Float(dividend.as_number()? % 0.0))
+                }
+            },
+            (Err(error), _)
+                if matches!(operator, Operator::VariableIdentifierRead { .. })
+                    && self.options.ignores_error(&error) =>
+            {
+                Ok(Value::Empty)
+            },
+            (result, _) => result,
+        }
+    }
+
+    /// Counts the evaluation of a node, failing if the budget of node evaluations is exhausted.
+    pub(crate) fn count_node_evaluation(&mut self) -> EvalexprResult<()> {
+        self.node_evaluations += 1;
+        match self.options.max_node_evaluations {
+            Some(max_node_evaluations) if self.node_evaluations > max_node_evaluations => {
+                Err(EvalexprError::budget_exceeded(max_node_evaluations))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Enters the evaluation of the given node, failing if the node violates the `EvaluationPolicy` of the options.
+    ///
+    /// Each call must be followed by a call to `leave_node` with the result of evaluating the node.
+    pub(crate) fn enter_node(&mut self, node: &Node) -> EvalexprResult<()> {
+        if let Some(policy) = self.options.policy() {
+            policy.check_operator(node.operator())?;
+            if let Some(max_depth) = policy.max_depth() {
+                if self.depth >= max_depth {
+                    return Err(EvalexprError::recursion_limit_exceeded(max_depth));
+                }
+            }
+        }
+        self.depth += 1;
+        if let Some(observer) = &mut self.observer {
+            observer.enter_node(node);
+            self.observed_operands
+                .push((address_range(node.children()), Vec::new()));
+        }
+        Ok(())
+    }
+
+    /// Leaves the evaluation of a node entered with `enter_node`, failing if its result violates the `EvaluationPolicy` of the options.
+    pub(crate) fn leave_node<V: Borrow<Value>>(
+        &mut self,
+        node: &Node,
+        result: EvalexprResult<V>,
+    ) -> EvalexprResult<V> {
+        self.depth -= 1;
+        let result = result
+            .map_err(|error| error.with_span(node.span()))
+            .and_then(|value| {
+                if let Some(policy) = self.options.policy() {
+                    policy.check_value(value.borrow())?;
+                }
+                Ok(value)
+            });
+        if self.observer.is_some() {
+            self.observe_exit(node, result.as_ref().map(Borrow::borrow));
+        }
+        result
+    }
+
+    /// Notifies the observer that the given node was evaluated, and records its value as operand of its parent.
+    fn observe_exit(&mut self, node: &Node, result: Result<&Value, &EvalexprError>) {
+        let (_, operands) = self.observed_operands.pop().unwrap_or_default();
+        if let Some(observer) = &mut self.observer {
+            observer.exit_node(node, &operands, result);
+        }
+        // Nodes evaluated within a node that are not its children, like the body of a called lambda, are no operands.
+        if let (Ok(value), Some((children, parent_operands))) =
+            (result, self.observed_operands.last_mut())
+        {
+            if children.contains(&(node as *const Node as usize)) {
+                parent_operands.push(value.clone());
+            }
+        }
+    }
+
+    /// Evaluates a call of a lambda, failing if more than `Lambda::MAX_CALL_DEPTH` lambda calls are nested.
+    pub(crate) fn nested_lambda_call<F: FnOnce(&mut Self) -> EvalexprResult<Value>>(
+        &mut self,
+        call: F,
+    ) -> EvalexprResult<Value> {
+        if self.lambda_call_depth >= Lambda::MAX_CALL_DEPTH {
+            return Err(EvalexprError::recursion_limit_exceeded(
+                Lambda::MAX_CALL_DEPTH,
+            ));
+        }
+
+        self.lambda_call_depth += 1;
+        let result = call(self);
+        self.lambda_call_depth -= 1;
+        result
+    }
+}
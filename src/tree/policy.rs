@@ -0,0 +1,218 @@
+use alloc::{string::String, vec::Vec};
+use core::{iter::once, mem};
+
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    operator::{range_length, Operator},
+    value::Value,
+    Node,
+};
+
+/// Restrictions for untrusted expressions, which are enforced when parsing and when evaluating them.
+///
+/// A policy is enforced at parse time if it is set in the `ParserConfig` used by `build_operator_tree_with_config`,
+/// and at evaluation time if it is set in the `EvalOptions` used to evaluate an operator tree.
+/// Checking at evaluation time also covers operator trees that were not parsed with the policy,
+/// as well as the bodies of lambdas and the values returned by functions and read from variables.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let policy = EvaluationPolicy::new()
+///     .with_forbidden_operator(Operator::Exp)
+///     .with_forbidden_function("str::from")
+///     .with_max_string_length(Some(8));
+/// let config = ParserConfig::new().with_policy(Some(policy.clone()));
+///
+/// assert!(build_operator_tree_with_config("2 * 3", &config).is_ok());
+/// assert_eq!(
+///     build_operator_tree_with_config("2 ^ 3", &config),
+///     Err(EvalexprError::ForbiddenOperator { operator: Operator::Exp })
+/// );
+/// assert_eq!(
+///     build_operator_tree_with_config("str::from(1)", &config),
+///     Err(EvalexprError::ForbiddenFunction { identifier: "str::from".into() })
+/// );
+///
+/// let options = EvalOptions::new().with_policy(Some(policy));
+/// assert_eq!(
+///     eval_with_context_and_options(r#""abcde" + "fghij""#, &HashMapContext::new(), &options),
+///     Err(EvalexprError::StringLengthLimitExceeded { max_string_length: 8, actual: 10 })
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EvaluationPolicy {
+    forbidden_operators: Vec<Operator>,
+    forbidden_functions: Vec<String>,
+    max_tuple_length: Option<usize>,
+    max_string_length: Option<usize>,
+    max_depth: Option<usize>,
+}
+
+impl EvaluationPolicy {
+    /// Creates a policy without any restrictions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forbids the given operator, so that expressions using it fail with `EvalexprError::ForbiddenOperator`.
+    ///
+    /// Operators are compared by their kind, ignoring the values and identifiers they contain.
+    /// For example, forbidding `Operator::Assign` forbids all assignments with `=`,
+    /// and forbidding `Operator::Lambda { parameters: Vec::new() }` forbids all lambdas.
+    pub fn with_forbidden_operator(mut self, operator: Operator) -> Self {
+        self.forbidden_operators.push(operator);
+        self
+    }
+
+    /// Forbids calling the function with the given identifier, so that expressions calling it fail with `EvalexprError::ForbiddenFunction`.
+    /// This applies to builtin functions, functions of the context and lambdas alike.
+    pub fn with_forbidden_function<S: Into<String>>(mut self, identifier: S) -> Self {
+        self.forbidden_functions.push(identifier.into());
+        self
+    }
+
+    /// Sets the maximum amount of elements of tuples and arrays, or removes the limit if `None` is given.
+    /// By default, there is no limit.
+    ///
+    /// Longer tuples and arrays result in `EvalexprError::TupleLengthLimitExceeded`.
+    pub fn with_max_tuple_length(mut self, max_tuple_length: Option<usize>) -> Self {
+        self.max_tuple_length = max_tuple_length;
+        self
+    }
+
+    /// Sets the maximum length of strings in bytes, or removes the limit if `None` is given.
+    /// By default, there is no limit.
+    ///
+    /// Longer strings result in `EvalexprError::StringLengthLimitExceeded`.
+    pub fn with_max_string_length(mut self, max_string_length: Option<usize>) -> Self {
+        self.max_string_length = max_string_length;
+        self
+    }
+
+    /// Sets the maximum depth of the operator tree, or removes the limit if `None` is given.
+    /// By default, there is no limit.
+    ///
+    /// The depth of an operator tree is the amount of nodes on the longest path from its root to a leaf, including the root and the leaf.
+    /// Unlike the maximum depth of the `ParserConfig`, this also counts the operands of chains of left-associative operators like `1 + 2 + 3`.
+    /// At evaluation time, the bodies of called lambdas count as nested into the call,
+    /// so this also limits the depth of recursive lambda calls.
+    /// Deeper expressions result in `EvalexprError::RecursionLimitExceeded`.
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the forbidden operators.
+    pub fn forbidden_operators(&self) -> &[Operator] {
+        &self.forbidden_operators
+    }
+
+    /// Returns the identifiers of the forbidden functions.
+    pub fn forbidden_functions(&self) -> &[String] {
+        &self.forbidden_functions
+    }
+
+    /// Returns the maximum amount of elements of tuples and arrays, or `None` if there is no limit.
+    pub fn max_tuple_length(&self) -> Option<usize> {
+        self.max_tuple_length
+    }
+
+    /// Returns the maximum length of strings in bytes, or `None` if there is no limit.
+    pub fn max_string_length(&self) -> Option<usize> {
+        self.max_string_length
+    }
+
+    /// Returns the maximum depth of the operator tree, or `None` if there is no limit.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Checks that the given operator tree complies with this policy.
+    ///
+    /// Fails, if the tree contains a forbidden operator or function call, a value or tuple that exceeds a length limit,
+    /// or if it is nested too deeply.
+    pub fn check_tree(&self, tree: &Node) -> EvalexprResult<()> {
+        if let Some(max_depth) = self.max_depth {
+            if tree.depth() > max_depth {
+                return Err(EvalexprError::recursion_limit_exceeded(max_depth));
+            }
+        }
+        for node in once(tree).chain(tree.iter()) {
+            self.check_operator(node.operator())?;
+            match node.operator() {
+                Operator::Const { value } => self.check_value(value)?,
+                Operator::Tuple => self.check_tuple_length(node.children().len())?,
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails if the given operator is forbidden or calls a forbidden function.
+    pub(crate) fn check_operator(&self, operator: &Operator) -> EvalexprResult<()> {
+        if let Operator::FunctionIdentifier { identifier } = operator {
+            if self
+                .forbidden_functions
+                .iter()
+                .any(|forbidden| forbidden.as_str() == identifier.as_ref())
+            {
+                return Err(EvalexprError::forbidden_function(
+                    identifier.as_ref().into(),
+                ));
+            }
+        }
+        if self
+            .forbidden_operators
+            .iter()
+            .any(|forbidden| mem::discriminant(forbidden) == mem::discriminant(operator))
+        {
+            return Err(EvalexprError::forbidden_operator(operator.clone()));
+        }
+        Ok(())
+    }
+
+    /// Fails if the given value or one of the values contained in it exceeds a length limit.
+    pub(crate) fn check_value(&self, value: &Value) -> EvalexprResult<()> {
+        match value {
+            Value::String(string) => match self.max_string_length {
+                Some(max_string_length) if string.len() > max_string_length => Err(
+                    EvalexprError::string_length_limit_exceeded(max_string_length, string.len()),
+                ),
+                _ => Ok(()),
+            },
+            Value::Tuple(values) | Value::Array(values) => {
+                self.check_tuple_length(values.len())?;
+                values.iter().try_for_each(|value| self.check_value(value))
+            },
+            Value::Map(map) => map.values().try_for_each(|value| self.check_value(value)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if the range with the given arguments would contain more integers than the maximum tuple length,
+    /// such that long ranges fail before they are created.
+    pub(crate) fn check_range_length(
+        &self,
+        arguments: &[Value],
+        inclusive: bool,
+    ) -> EvalexprResult<()> {
+        match arguments {
+            [Value::Int(start), Value::Int(end)] => {
+                self.check_tuple_length(range_length(*start, *end, inclusive))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn check_tuple_length(&self, length: usize) -> EvalexprResult<()> {
+        match self.max_tuple_length {
+            Some(max_tuple_length) if length > max_tuple_length => Err(
+                EvalexprError::tuple_length_limit_exceeded(max_tuple_length, length),
+            ),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    iter::once,
+    time::{Duration, Instant},
+};
+
+use crate::{error::EvalexprError, value::Value, EvalObserver, Node};
+
+/// An observer that records how often each node of an operator tree was evaluated and how long its evaluations took.
+///
+/// A profiler is passed to `Node::eval_with_context_and_observer` for any number of evaluations, and collects the statistics of all of them.
+/// Afterwards, `Profiler::profile` returns the statistics of the nodes of the evaluated tree, with the most expensive subtrees first.
+/// This finds the expensive parts of large generated expressions, like calls of costly custom functions.
+///
+/// The time of a node includes the time of its children.
+/// Nodes are identified by their address, so the tree must not be moved or modified between the profiled evaluations.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let context = context_map! { "x" => 3 }.unwrap(); // Do proper error handling here
+/// let tree = build_operator_tree("x > 1 && max(x, 5) < 10").unwrap(); // Do proper error handling here
+///
+/// let mut profiler = Profiler::new();
+/// for _ in 0..100 {
+///     tree.eval_with_context_and_observer(&context, &mut profiler).unwrap(); // Do proper error handling here
+/// }
+///
+/// let profile = profiler.profile(&tree);
+/// assert_eq!(profile[0].node, &tree);
+/// assert_eq!(profile[0].evaluations, 100);
+/// let call = profile
+///     .iter()
+///     .find(|entry| entry.node.to_string() == "max(x, 5)")
+///     .unwrap();
+/// assert_eq!(call.evaluations, 100);
+/// assert!(call.total_time <= profile[0].total_time);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    statistics: HashMap<usize, (u64, Duration)>,
+    /// The start times of the nodes that are being evaluated.
+    started: Vec<Instant>,
+}
+
+/// The statistics of a node recorded by a `Profiler`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeProfile<'tree> {
+    /// The profiled node.
+    pub node: &'tree Node,
+    /// How often the node was evaluated.
+    pub evaluations: u64,
+    /// The cumulative time of all evaluations of the node, including the time of its children.
+    pub total_time: Duration,
+}
+
+impl NodeProfile<'_> {
+    /// Returns the average time of an evaluation of the node.
+    pub fn average_time(&self) -> Duration {
+        // Only evaluated nodes are profiled, so the evaluations are never zero.
+        self.total_time.div_f64(self.evaluations as f64)
+    }
+}
+
+impl Profiler {
+    /// Constructs a profiler without recorded statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the statistics of the nodes of the given tree that were evaluated, including the root,
+    /// sorted by their total time in descending order.
+    ///
+    /// Nodes that were evaluated equally long are ordered as in a pre-order traversal of the tree.
+    pub fn profile<'tree>(&self, tree: &'tree Node) -> Vec<NodeProfile<'tree>> {
+        let mut profile: Vec<_> = once(tree)
+            .chain(tree.iter())
+            .filter_map(|node| {
+                let (evaluations, total_time) = *self.statistics.get(&address(node))?;
+                Some(NodeProfile {
+                    node,
+                    evaluations,
+                    total_time,
+                })
+            })
+            .collect();
+        profile.sort_by_key(|entry| Reverse(entry.total_time));
+        profile
+    }
+
+    /// Removes all recorded statistics.
+    pub fn clear(&mut self) {
+        self.statistics.clear();
+        self.started.clear();
+    }
+}
+
+impl EvalObserver for Profiler {
+    fn enter_node(&mut self, _node: &Node) {
+        self.started.push(Instant::now());
+    }
+
+    fn exit_node(
+        &mut self,
+        node: &Node,
+        _operands: &[Value],
+        _result: Result<&Value, &EvalexprError>,
+    ) {
+        let Some(started) = self.started.pop() else {
+            return;
+        };
+        let (evaluations, total_time) = self.statistics.entry(address(node)).or_default();
+        *evaluations += 1;
+        *total_time += started.elapsed();
+    }
+}
+
+fn address(node: &Node) -> usize {
+    node as *const Node as usize
+}
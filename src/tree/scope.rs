@@ -0,0 +1,183 @@
+#[cfg(feature = "async")]
+use crate::{context::AsyncContext, function::AsyncFunction};
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    operator::Operator,
+    value::{value_type::ValueType, Value},
+    Context, ContextWithMutableVariables, Node,
+};
+use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use core::ops::{Deref, DerefMut};
+
+/// A context that binds variables for a part of an expression, like the parameters of a lambda or variables defined with `let`.
+///
+/// The bound variables shadow the variables and functions of the wrapped context, and assignments to them do not reach the wrapped context.
+/// The wrapped context is referenced as a trait object, such that nested scopes have the same type.
+pub(crate) struct Scope<C> {
+    variables: Vec<(String, Value)>,
+    context: C,
+}
+
+impl<C> Scope<C> {
+    pub(crate) fn new(context: C) -> Self {
+        Self {
+            variables: Vec::new(),
+            context,
+        }
+    }
+
+    /// Binds the variable with the given identifier to the given value, shadowing previous bindings of the identifier.
+    pub(crate) fn bind(&mut self, identifier: String, value: Value) {
+        match self.variable_mut(&identifier) {
+            Some(variable) => *variable = value,
+            None => self.variables.push((identifier, value)),
+        }
+    }
+
+    fn variable_mut(&mut self, identifier: &str) -> Option<&mut Value> {
+        self.variables
+            .iter_mut()
+            .find(|(bound, _)| bound == identifier)
+            .map(|(_, value)| value)
+    }
+
+    fn is_bound(&self, identifier: &str) -> bool {
+        self.variables.iter().any(|(bound, _)| bound == identifier)
+    }
+}
+
+impl<C: Deref> Context for Scope<C>
+where
+    C::Target: Context,
+{
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        match self.variables.iter().find(|(bound, _)| bound == identifier) {
+            Some((_, value)) => Some(value),
+            None => self.context.get_value(identifier),
+        }
+    }
+
+    fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+        match self.variables.iter().find(|(bound, _)| bound == identifier) {
+            Some((_, value)) => Ok(Some(Cow::Borrowed(value))),
+            None => self.context.try_get_value(identifier),
+        }
+    }
+
+    fn call_function(&self, identifier: &str, argument: &Value) -> EvalexprResult<Value> {
+        // Bound variables shadow the functions of the wrapped context, so bound lambdas can be called.
+        if self.is_bound(identifier) {
+            Err(EvalexprError::function_identifier_not_found(
+                identifier.into(),
+            ))
+        } else {
+            self.context.call_function(identifier, argument)
+        }
+    }
+
+    fn has_function(&self, identifier: &str) -> bool {
+        !self.is_bound(identifier) && self.context.has_function(identifier)
+    }
+
+    fn is_pure_function(&self, identifier: &str) -> bool {
+        !self.is_bound(identifier) && self.context.is_pure_function(identifier)
+    }
+
+    fn iter_variable_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(
+            self.variables
+                .iter()
+                .map(|(bound, _)| bound.as_str())
+                .chain(self.context.iter_variable_identifiers()),
+        )
+    }
+
+    fn iter_function_identifiers(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+        Box::new(
+            self.context
+                .iter_function_identifiers()
+                .filter(move |identifier| !self.is_bound(identifier)),
+        )
+    }
+
+    fn are_builtin_functions_disabled(&self) -> bool {
+        self.context.are_builtin_functions_disabled()
+    }
+
+    fn set_builtin_functions_disabled(&mut self, _disabled: bool) -> EvalexprResult<()> {
+        Err(EvalexprError::ContextNotMutable)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: Deref> AsyncContext for Scope<C>
+where
+    C::Target: AsyncContext,
+{
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        // Like functions, async functions are shadowed by bound variables.
+        if self.is_bound(identifier) {
+            None
+        } else {
+            self.context.get_async_function(identifier)
+        }
+    }
+}
+
+impl<C: DerefMut> ContextWithMutableVariables for Scope<C>
+where
+    C::Target: ContextWithMutableVariables,
+{
+    fn set_value(&mut self, identifier: String, value: Value) -> EvalexprResult<()> {
+        // Like in a `HashMapContext`, assignments cannot change the type of a variable.
+        match self.variable_mut(&identifier) {
+            Some(variable) if ValueType::from(&*variable) == ValueType::from(&value) => {
+                *variable = value;
+                Ok(())
+            },
+            Some(variable) => Err(EvalexprError::expected_type(variable, value)),
+            None => self.context.set_value(identifier, value),
+        }
+    }
+}
+
+impl Node {
+    /// Returns the identifier and the value of the let-binding at this node, if it is one.
+    /// The parser encloses each expression of a chain in a root node, which is skipped.
+    pub(crate) fn let_binding(&self) -> Option<(&str, &Node)> {
+        let node = match (self.operator(), self.children()) {
+            (Operator::RootNode, [child]) => child,
+            _ => self,
+        };
+        match (node.operator(), node.children()) {
+            (Operator::Let, [variable, value]) => match variable.operator() {
+                Operator::VariableIdentifierWrite { identifier } => Some((identifier, value)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the node of the value of the let-binding at this node, if it is one.
+    pub(crate) fn let_binding_value_mut(&mut self) -> Option<&mut Node> {
+        let node = if self.operator() == &Operator::RootNode && self.children.len() == 1 {
+            &mut self.children[0]
+        } else {
+            self
+        };
+        match (&node.operator, node.children.as_mut_slice()) {
+            (Operator::Let, [_, value]) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this node is a chain that contains let-bindings.
+    /// The variables bound by them are only visible to the following expressions of the chain.
+    pub(crate) fn is_let_chain(&self) -> bool {
+        *self.operator() == Operator::Chain
+            && self
+                .children()
+                .iter()
+                .any(|child| child.let_binding().is_some())
+    }
+}
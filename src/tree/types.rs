@@ -0,0 +1,396 @@
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+use crate::{
+    context::IterateVariablesContext, function::builtin::BUILTIN_FUNCTION_IDENTIFIERS,
+    operator::Operator, value::value_type::ValueType, Node,
+};
+
+/// The types of variables and the return types of functions, used by `Node::infer_return_type`.
+///
+/// Builtin functions do not need hints, as their return types are known, but a hint for a function overrides them.
+///
+/// # Examples
+///
+/// ```rust
+/// use evalexpr::*;
+///
+/// let hints = TypeHints::new()
+///     .with_variable_type("price", ValueType::Float)
+///     .with_function_return_type("discount", ValueType::Float);
+///
+/// let tree = build_operator_tree("price * (1 - discount(price)) > 100").unwrap(); // Do proper error handling here
+/// assert_eq!(tree.infer_return_type(&hints), Some(ValueType::Boolean));
+/// let tree = build_operator_tree("price * (1 - discount(price))").unwrap(); // Do proper error handling here
+/// assert_eq!(tree.infer_return_type(&hints), Some(ValueType::Float));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeHints {
+    variables: BTreeMap<String, ValueType>,
+    functions: BTreeMap<String, ValueType>,
+}
+
+impl TypeHints {
+    /// Creates type hints without any variables or functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates type hints with the types of the current values of all variables of the context.
+    pub fn from_context<C: IterateVariablesContext>(context: &C) -> Self {
+        Self {
+            variables: context
+                .iter_variables()
+                .map(|(identifier, value)| (identifier, ValueType::from(&value)))
+                .collect(),
+            functions: BTreeMap::new(),
+        }
+    }
+
+    /// Declares that the variable with the given identifier has the given type.
+    pub fn with_variable_type(
+        mut self,
+        identifier: impl Into<String>,
+        value_type: ValueType,
+    ) -> Self {
+        self.variables.insert(identifier.into(), value_type);
+        self
+    }
+
+    /// Declares that the function with the given identifier returns values of the given type.
+    pub fn with_function_return_type(
+        mut self,
+        identifier: impl Into<String>,
+        value_type: ValueType,
+    ) -> Self {
+        self.functions.insert(identifier.into(), value_type);
+        self
+    }
+
+    /// Returns the declared type of the variable with the given identifier.
+    pub fn variable_type(&self, identifier: &str) -> Option<ValueType> {
+        self.variables.get(identifier).copied()
+    }
+
+    /// Returns the declared return type of the function with the given identifier.
+    pub fn function_return_type(&self, identifier: &str) -> Option<ValueType> {
+        self.functions.get(identifier).copied()
+    }
+}
+
+impl Node {
+    /// Infers the type of the value this operator tree evaluates to, without evaluating it.
+    ///
+    /// The types of the values are propagated through the operators and the calls of builtin functions as well as of the functions given in the `context_hints`.
+    /// Variables have the types given in the `context_hints`, or the types of the values assigned to them earlier in the expression.
+    /// The inference assumes the default `EvalOptions`.
+    ///
+    /// Returns `None` if the type cannot be determined without evaluating the operator tree,
+    /// for example because it depends on a variable without a type hint or on the elements of a collection,
+    /// or if the operator is known to fail for the types of its arguments.
+    /// As the inference does not know the values, evaluating the operator tree can still fail even if a type is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use evalexpr::*;
+    ///
+    /// let hints = TypeHints::new()
+    ///     .with_variable_type("age", ValueType::Int)
+    ///     .with_variable_type("name", ValueType::String);
+    ///
+    /// let tree = build_operator_tree("age >= 18 && str::len(name) > 0").unwrap(); // Do proper error handling here
+    /// assert_eq!(tree.infer_return_type(&hints), Some(ValueType::Boolean));
+    /// let tree = build_operator_tree("age / 2 + 1").unwrap(); // Do proper error handling here
+    /// assert_eq!(tree.infer_return_type(&hints), Some(ValueType::Int));
+    /// let tree = build_operator_tree("age + 0.5").unwrap(); // Do proper error handling here
+    /// assert_eq!(tree.infer_return_type(&hints), Some(ValueType::Float));
+    /// let tree = build_operator_tree("name && true").unwrap(); // Do proper error handling here
+    /// assert_eq!(tree.infer_return_type(&hints), None);
+    /// let tree = build_operator_tree("unknown + 1").unwrap(); // Do proper error handling here
+    /// assert_eq!(tree.infer_return_type(&hints), None);
+    /// ```
+    pub fn infer_return_type(&self, context_hints: &TypeHints) -> Option<ValueType> {
+        self.infer_type(&mut context_hints.clone())
+    }
+
+    /// Infers the type of this node, recording the types of variables assigned by it in `hints`.
+    fn infer_type(&self, hints: &mut TypeHints) -> Option<ValueType> {
+        use crate::operator::Operator::*;
+        let children = self.children();
+        match self.operator() {
+            RootNode => match children.first() {
+                Some(child) => child.infer_type(hints),
+                None => Some(ValueType::Empty),
+            },
+            Add | Sub | Mul | Div | Mod | Exp => {
+                let (a, b) = infer_pair(children, hints)?;
+                arithmetic_type(self.operator(), a?, b?)
+            },
+            Neg | Pos => {
+                let operand = children.first()?.infer_type(hints)?;
+                match operand {
+                    ValueType::Int | ValueType::Float => Some(operand),
+                    #[cfg(feature = "decimal")]
+                    ValueType::Decimal => Some(operand),
+                    #[cfg(feature = "chrono")]
+                    ValueType::Duration if *self.operator() == Neg => Some(operand),
+                    _ => None,
+                }
+            },
+            Eq | Neq | In => {
+                infer_pair(children, hints)?;
+                Some(ValueType::Boolean)
+            },
+            Gt | Lt | Geq | Leq => {
+                let operands = match self.comparison_chain() {
+                    Some((operands, _)) => operands,
+                    None => children.iter().collect(),
+                };
+                let operand_types: Vec<_> = operands
+                    .iter()
+                    .map(|operand| operand.infer_type(hints))
+                    .collect();
+                let is_ordered = operand_types.windows(2).all(|pair| match pair {
+                    [Some(a), Some(b)] => is_ordered(*a, *b),
+                    _ => true,
+                });
+                is_ordered.then_some(ValueType::Boolean)
+            },
+            And | Or | Not => {
+                let mut result = Some(ValueType::Boolean);
+                for child in children {
+                    if !matches!(child.infer_type(hints), Some(ValueType::Boolean) | None) {
+                        result = None;
+                    }
+                }
+                result
+            },
+            NullCoalesce => match infer_pair(children, hints)? {
+                (Some(ValueType::Empty), b) => b,
+                (a, _) => a,
+            },
+            Assign | AddAssign | SubAssign | MulAssign | DivAssign | ModAssign | ExpAssign
+            | AndAssign | OrAssign | Let => {
+                let identifier = match children.first()?.operator() {
+                    VariableIdentifierWrite { identifier } => identifier,
+                    _ => return None,
+                };
+                let value = children.get(1)?.infer_type(hints);
+                let operator = match self.operator() {
+                    AddAssign => Add,
+                    SubAssign => Sub,
+                    MulAssign => Mul,
+                    DivAssign => Div,
+                    ModAssign => Mod,
+                    ExpAssign => Exp,
+                    _ => Assign,
+                };
+                let assigned = match self.operator() {
+                    Assign | Let => value,
+                    AndAssign | OrAssign => Some(ValueType::Boolean),
+                    _ => hints
+                        .variable_type(identifier)
+                        .zip(value)
+                        .and_then(|(a, b)| arithmetic_type(&operator, a, b)),
+                };
+                match assigned {
+                    Some(value_type) => hints
+                        .variables
+                        .insert(identifier.as_ref().into(), value_type),
+                    None => hints.variables.remove(identifier.as_ref()),
+                };
+                Some(ValueType::Empty)
+            },
+            Tuple | Range { .. } => {
+                children.iter().for_each(|child| {
+                    child.infer_type(hints);
+                });
+                Some(ValueType::Tuple)
+            },
+            Array => {
+                children.iter().for_each(|child| {
+                    child.infer_type(hints);
+                });
+                Some(ValueType::Array)
+            },
+            Chain => children
+                .iter()
+                .map(|child| child.infer_type(hints))
+                .last()
+                .unwrap_or(Some(ValueType::Empty)),
+            Index => match infer_pair(children, hints)?.0 {
+                Some(ValueType::String) => Some(ValueType::String),
+                _ => None,
+            },
+            Slice { .. } => {
+                let container = children.first()?.infer_type(hints);
+                children.iter().skip(1).for_each(|child| {
+                    child.infer_type(hints);
+                });
+                match container? {
+                    container @ (ValueType::String | ValueType::Tuple | ValueType::Array) => {
+                        Some(container)
+                    },
+                    _ => None,
+                }
+            },
+            Const { value } => Some(ValueType::from(value)),
+            VariableIdentifierRead { identifier } => hints.variable_type(identifier),
+            VariableIdentifierWrite { .. } => None,
+            FunctionIdentifier { identifier } => {
+                // Parenthesized arguments are wrapped in root nodes.
+                let mut argument = children.first();
+                while let Some(node) = argument.filter(|node| *node.operator() == RootNode) {
+                    argument = node.children().first();
+                }
+                let arguments: Vec<_> = match argument {
+                    Some(argument) if *argument.operator() == Tuple => argument
+                        .children()
+                        .iter()
+                        .map(|argument| argument.infer_type(hints))
+                        .collect(),
+                    Some(argument) => vec![argument.infer_type(hints)],
+                    None => Vec::new(),
+                };
+                if let Some(return_type) = hints.function_return_type(identifier) {
+                    Some(return_type)
+                } else if BUILTIN_FUNCTION_IDENTIFIERS.contains(&identifier.as_ref()) {
+                    builtin_return_type(identifier, &arguments)
+                } else {
+                    None
+                }
+            },
+            Lambda { .. } => Some(ValueType::Lambda),
+        }
+    }
+}
+
+/// Infers the types of the two children of a binary operator, or returns `None` if there are not two children.
+fn infer_pair(
+    children: &[Node],
+    hints: &mut TypeHints,
+) -> Option<(Option<ValueType>, Option<ValueType>)> {
+    match children {
+        [a, b] => Some((a.infer_type(hints), b.infer_type(hints))),
+        _ => None,
+    }
+}
+
+/// Returns the result type of the arithmetic operator for arguments of the given types,
+/// or `None` if the operator is not defined for them.
+fn arithmetic_type(operator: &Operator, a: ValueType, b: ValueType) -> Option<ValueType> {
+    use crate::operator::Operator::*;
+    use ValueType::*;
+    match (operator, a, b) {
+        (Exp, Int | Float, Int | Float) => Some(Float),
+        (Add, String, String) => Some(String),
+        (Add | Sub | Mul | Div | Mod, Int, Int) => Some(Int),
+        (Add | Sub | Mul | Div | Mod, Int | Float, Int | Float) => Some(Float),
+        #[cfg(feature = "decimal")]
+        (Exp, Decimal, Int | Float | Decimal) | (Exp, Int | Float, Decimal) => Some(Float),
+        #[cfg(feature = "decimal")]
+        (Add | Sub | Mul | Div | Mod, Decimal, Int | Decimal)
+        | (Add | Sub | Mul | Div | Mod, Int, Decimal) => Some(Decimal),
+        #[cfg(feature = "decimal")]
+        (Add | Sub | Mul | Div | Mod, Decimal, Float)
+        | (Add | Sub | Mul | Div | Mod, Float, Decimal) => Some(Float),
+        #[cfg(feature = "chrono")]
+        (Add, DateTime, Duration) | (Add, Duration, DateTime) | (Sub, DateTime, Duration) => {
+            Some(DateTime)
+        },
+        #[cfg(feature = "chrono")]
+        (Sub, DateTime, DateTime)
+        | (Add | Sub, Duration, Duration)
+        | (Mul, Duration, Int)
+        | (Mul, Int, Duration)
+        | (Div, Duration, Int) => Some(Duration),
+        #[cfg(feature = "chrono")]
+        (Div, Duration, Duration) => Some(Float),
+        _ => None,
+    }
+}
+
+/// Returns true if values of the given types can be compared with `<`, `>`, `<=` and `>=`.
+fn is_ordered(a: ValueType, b: ValueType) -> bool {
+    let is_number = |value_type| matches!(value_type, ValueType::Int | ValueType::Float);
+    #[cfg(feature = "decimal")]
+    let is_number = |value_type| is_number(value_type) || value_type == ValueType::Decimal;
+    #[cfg(feature = "chrono")]
+    if matches!(
+        (a, b),
+        (ValueType::DateTime, ValueType::DateTime) | (ValueType::Duration, ValueType::Duration)
+    ) {
+        return true;
+    }
+    (is_number(a) && is_number(b)) || (a == ValueType::String && b == ValueType::String)
+}
+
+/// Returns the return type of the builtin function with the given identifier for arguments of the given types,
+/// or `None` if it depends on the values of the arguments.
+fn builtin_return_type(identifier: &str, arguments: &[Option<ValueType>]) -> Option<ValueType> {
+    use ValueType::*;
+    let all_are = |value_type: ValueType| {
+        arguments
+            .iter()
+            .all(|argument| *argument == Some(value_type))
+    };
+    let fold = |operator: Operator| {
+        arguments.iter().try_fold(Int, |result, argument| {
+            arithmetic_type(&operator, result, (*argument)?)
+        })
+    };
+    match identifier {
+        "math::is_nan" | "math::is_finite" | "math::is_infinite" | "math::is_normal"
+        | "approx_eq" | "contains" | "contains_any" | "any" | "all" | "bool"
+        | "str::regex_matches" | "str::contains" | "str::starts_with" => Some(Boolean),
+        identifier if identifier.starts_with("is_") => Some(Boolean),
+        "floor" | "round" | "ceil" | "math::abs" => match arguments {
+            [Some(value_type @ (Int | Float))] => Some(*value_type),
+            #[cfg(feature = "decimal")]
+            [Some(Decimal)] => Some(Decimal),
+            _ => None,
+        },
+        "math::rem_euclid" | "math::div_euclid" => match arguments {
+            [Some(Int), Some(Int)] => Some(Int),
+            [Some(_), Some(_)] => Some(Float),
+            _ => None,
+        },
+        identifier if identifier.starts_with("math::") => Some(Float),
+        "min" | "max" if !arguments.is_empty() && all_are(Int) => Some(Int),
+        "min" | "max" if !arguments.is_empty() && all_are(Float) => Some(Float),
+        "sum" if arguments.len() > 1 => fold(Operator::Add),
+        "product" if arguments.len() > 1 => fold(Operator::Mul),
+        "avg"
+            if cfg!(not(feature = "decimal"))
+                || arguments
+                    .iter()
+                    .all(|argument| matches!(argument, Some(Int | Float))) =>
+        {
+            Some(Float)
+        },
+        "str::len" | "len" | "count" | "int" | "random_int" | "bitand" | "bitor" | "bitxor"
+        | "bitnot" | "shl" | "shr" => Some(Int),
+        "float" | "random" => Some(Float),
+        "typeof" | "str" | "format" => Some(String),
+        identifier if identifier.starts_with("str::") => Some(String),
+        "if" => match arguments {
+            [_, Some(a), Some(b)] if a == b => Some(*a),
+            _ => None,
+        },
+        "map" | "filter" => match arguments.first() {
+            Some(Some(collection @ (Tuple | Array))) => Some(*collection),
+            _ => None,
+        },
+        #[cfg(feature = "chrono")]
+        "timestamp" | "now" | "datetime::parse" | "datetime::from_timestamp" => Some(DateTime),
+        #[cfg(feature = "chrono")]
+        "duration" | "duration::seconds" => Some(Duration),
+        #[cfg(feature = "chrono")]
+        "datetime::format" => Some(String),
+        #[cfg(feature = "chrono")]
+        "datetime::timestamp" => Some(Int),
+        #[cfg(feature = "chrono")]
+        "duration::as_seconds" => Some(Float),
+        _ => None,
+    }
+}
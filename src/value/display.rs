@@ -1,5 +1,7 @@
-use std::fmt::{Display, Error, Formatter};
+use core::fmt::{Display, Error, Formatter};
 
+#[cfg(feature = "chrono")]
+use crate::value::DurationType;
 use crate::Value;
 
 impl Display for Value {
@@ -8,6 +10,18 @@ impl Display for Value {
             Value::String(string) => write!(f, "\"{}\"", string),
             Value::Float(float) => write!(f, "{}", float),
             Value::Int(int) => write!(f, "{}", int),
+            #[cfg(feature = "bigint")]
+            Value::BigInt(int) => write!(f, "{}", int),
+            #[cfg(feature = "decimal")]
+            Value::Decimal(decimal) => write!(f, "{}", decimal),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(datetime) => write!(
+                f,
+                "{}",
+                datetime.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            ),
+            #[cfg(feature = "chrono")]
+            Value::Duration(duration) => write_duration(duration, f),
             Value::Boolean(boolean) => write!(f, "{}", boolean),
             Value::Tuple(tuple) => {
                 write!(f, "(")?;
@@ -22,7 +36,58 @@ impl Display for Value {
                 }
                 write!(f, ")")
             },
+            Value::Array(array) => {
+                write!(f, "[")?;
+                for (index, value) in array.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt(f)?;
+                }
+                write!(f, "]")
+            },
+            Value::Map(map) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in map.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}: ", key)?;
+                    value.fmt(f)?;
+                }
+                write!(f, "}}")
+            },
+            Value::Lambda(lambda) => write!(f, "{}", lambda),
             Value::Empty => write!(f, "()"),
         }
     }
 }
+
+/// Writes a duration with units like `1d 2h 30m 1s 500ms`, omitting the units that are zero.
+#[cfg(feature = "chrono")]
+fn write_duration(duration: &DurationType, f: &mut Formatter) -> Result<(), Error> {
+    if duration.is_zero() {
+        return write!(f, "0s");
+    }
+    if *duration < DurationType::zero() {
+        write!(f, "-")?;
+    }
+    let seconds = duration.num_seconds().abs();
+    let nanoseconds = i64::from(duration.subsec_nanos().abs());
+    let parts = [
+        (seconds / 86_400, "d"),
+        (seconds / 3_600 % 24, "h"),
+        (seconds / 60 % 60, "m"),
+        (seconds % 60, "s"),
+        (nanoseconds / 1_000_000, "ms"),
+        (nanoseconds / 1_000 % 1_000, "us"),
+        (nanoseconds % 1_000, "ns"),
+    ];
+    for (index, (amount, unit)) in parts.iter().filter(|(amount, _)| *amount != 0).enumerate() {
+        if index > 0 {
+            write!(f, " ")?;
+        }
+        write!(f, "{}{}", amount, unit)?;
+    }
+    Ok(())
+}
@@ -1,5 +1,20 @@
-use crate::error::{EvalexprError, EvalexprResult};
-use std::convert::TryFrom;
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    tree::Lambda,
+};
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    convert::TryFrom,
+    hash::{Hash, Hasher},
+    mem,
+};
+#[cfg(feature = "bigint")]
+use num_traits::ToPrimitive;
 
 mod display;
 pub mod value_type;
@@ -7,12 +22,34 @@ pub mod value_type;
 /// The type used to represent integers in `Value::Int`.
 pub type IntType = i64;
 
+/// The type used to represent integers that do not fit into `IntType` in `Value::BigInt`.
+#[cfg(feature = "bigint")]
+pub type BigIntType = num_bigint::BigInt;
+
+/// The type used to represent decimal numbers in `Value::Decimal`.
+#[cfg(feature = "decimal")]
+pub type DecimalType = rust_decimal::Decimal;
+
+/// The type used to represent points in time in `Value::DateTime`.
+#[cfg(feature = "chrono")]
+pub type DateTimeType = chrono::DateTime<chrono::Utc>;
+
+/// The type used to represent spans of time in `Value::Duration`.
+#[cfg(feature = "chrono")]
+pub type DurationType = chrono::Duration;
+
 /// The type used to represent floats in `Value::Float`.
 pub type FloatType = f64;
 
 /// The type used to represent tuples in `Value::Tuple`.
 pub type TupleType = Vec<Value>;
 
+/// The type used to represent arrays in `Value::Array`.
+pub type ArrayType = Vec<Value>;
+
+/// The type used to represent maps in `Value::Map`.
+pub type MapType = BTreeMap<String, Value>;
+
 /// The type used to represent empty values in `Value::Empty`.
 pub type EmptyType = ();
 
@@ -21,8 +58,11 @@ pub const EMPTY_VALUE: () = ();
 
 /// The value type used by the parser.
 /// Values can be of different subtypes that are the variants of this enum.
+///
+/// Some variants only exist if the respective feature is enabled, so matches on values need a wildcard arm.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+#[non_exhaustive]
 pub enum Value {
     /// A string value.
     String(String),
@@ -30,10 +70,35 @@ pub enum Value {
     Float(FloatType),
     /// An integer value.
     Int(IntType),
+    /// An integer value that does not fit into `IntType`.
+    ///
+    /// Integer literals and the results of integer arithmetic are only represented as big integers if they do not fit into `IntType`.
+    /// Use `Value::from` to construct big integers, as it applies the same rule.
+    #[cfg(feature = "bigint")]
+    BigInt(BigIntType),
+    /// A decimal number value.
+    ///
+    /// Unlike floats, decimal numbers represent decimal fractions like `0.1` exactly.
+    #[cfg(feature = "decimal")]
+    Decimal(DecimalType),
+    /// A point in time in UTC.
+    #[cfg(feature = "chrono")]
+    DateTime(DateTimeType),
+    /// A span of time, which can be negative.
+    #[cfg(feature = "chrono")]
+    Duration(DurationType),
     /// A boolean value.
     Boolean(bool),
     /// A tuple value.
     Tuple(TupleType),
+    /// An array value.
+    ///
+    /// Unlike tuples, arrays are never flattened into the sequence they are part of, so they can be nested and passed around as a whole.
+    Array(ArrayType),
+    /// A map from strings to values.
+    Map(MapType),
+    /// A lambda like `|x| x * 2`, which can be called like a function if it is stored in a variable.
+    Lambda(Box<Lambda>),
     /// An empty value.
     Empty,
 }
@@ -48,14 +113,45 @@ impl Value {
         matches!(self, Value::Int(_))
     }
 
+    /// Returns true if `self` is a `Value::BigInt`.
+    #[cfg(feature = "bigint")]
+    pub fn is_bigint(&self) -> bool {
+        matches!(self, Value::BigInt(_))
+    }
+
+    /// Returns true if `self` is a `Value::Decimal`.
+    #[cfg(feature = "decimal")]
+    pub fn is_decimal(&self) -> bool {
+        matches!(self, Value::Decimal(_))
+    }
+
+    /// Returns true if `self` is a `Value::DateTime`.
+    #[cfg(feature = "chrono")]
+    pub fn is_datetime(&self) -> bool {
+        matches!(self, Value::DateTime(_))
+    }
+
+    /// Returns true if `self` is a `Value::Duration`.
+    #[cfg(feature = "chrono")]
+    pub fn is_duration(&self) -> bool {
+        matches!(self, Value::Duration(_))
+    }
+
     /// Returns true if `self` is a `Value::Float`.
     pub fn is_float(&self) -> bool {
         matches!(self, Value::Float(_))
     }
 
-    /// Returns true if `self` is a `Value::Int` or `Value::Float`.
+    /// Returns true if `self` is a `Value::Int`, `Value::BigInt`, `Value::Decimal` or `Value::Float`.
     pub fn is_number(&self) -> bool {
-        matches!(self, Value::Int(_) | Value::Float(_))
+        match self {
+            Value::Int(_) | Value::Float(_) => true,
+            #[cfg(feature = "bigint")]
+            Value::BigInt(_) => true,
+            #[cfg(feature = "decimal")]
+            Value::Decimal(_) => true,
+            _ => false,
+        }
     }
 
     /// Returns true if `self` is a `Value::Boolean`.
@@ -68,6 +164,21 @@ impl Value {
         matches!(self, Value::Tuple(_))
     }
 
+    /// Returns true if `self` is a `Value::Array`.
+    pub fn is_array(&self) -> bool {
+        matches!(self, Value::Array(_))
+    }
+
+    /// Returns true if `self` is a `Value::Map`.
+    pub fn is_map(&self) -> bool {
+        matches!(self, Value::Map(_))
+    }
+
+    /// Returns true if `self` is a `Value::Lambda`.
+    pub fn is_lambda(&self) -> bool {
+        matches!(self, Value::Lambda(_))
+    }
+
     /// Returns true if `self` is a `Value::Empty`.
     pub fn is_empty(&self) -> bool {
         matches!(self, Value::Empty)
@@ -89,6 +200,45 @@ impl Value {
         }
     }
 
+    /// Clones the value stored in `self` as `BigIntType`, or returns `Err` if `self` is not a `Value::Int` or `Value::BigInt`.
+    #[cfg(feature = "bigint")]
+    pub fn as_bigint(&self) -> EvalexprResult<BigIntType> {
+        match self {
+            Value::Int(i) => Ok(BigIntType::from(*i)),
+            Value::BigInt(i) => Ok(i.clone()),
+            value => Err(EvalexprError::expected_int(value.clone())),
+        }
+    }
+
+    /// Clones the value stored in `self` as `DecimalType`, or returns `Err` if `self` is not a `Value::Decimal` or `Value::Int`.
+    /// Note that this method silently converts `IntType` to `DecimalType`, if `self` is a `Value::Int`.
+    #[cfg(feature = "decimal")]
+    pub fn as_decimal(&self) -> EvalexprResult<DecimalType> {
+        match self {
+            Value::Decimal(d) => Ok(*d),
+            Value::Int(i) => Ok(DecimalType::from(*i)),
+            value => Err(EvalexprError::expected_decimal(value.clone())),
+        }
+    }
+
+    /// Returns the value stored in `self` as `DateTimeType`, or returns `Err` if `self` is not a `Value::DateTime`.
+    #[cfg(feature = "chrono")]
+    pub fn as_datetime(&self) -> EvalexprResult<DateTimeType> {
+        match self {
+            Value::DateTime(datetime) => Ok(*datetime),
+            value => Err(EvalexprError::expected_datetime(value.clone())),
+        }
+    }
+
+    /// Returns the value stored in `self` as `DurationType`, or returns `Err` if `self` is not a `Value::Duration`.
+    #[cfg(feature = "chrono")]
+    pub fn as_duration(&self) -> EvalexprResult<DurationType> {
+        match self {
+            Value::Duration(duration) => Ok(*duration),
+            value => Err(EvalexprError::expected_duration(value.clone())),
+        }
+    }
+
     /// Clones the value stored in  `self` as `FloatType`, or returns `Err` if `self` is not a `Value::Float`.
     pub fn as_float(&self) -> EvalexprResult<FloatType> {
         match self {
@@ -99,10 +249,17 @@ impl Value {
 
     /// Clones the value stored in  `self` as `FloatType`, or returns `Err` if `self` is not a `Value::Float` or `Value::Int`.
     /// Note that this method silently converts `IntType` to `FloatType`, if `self` is a `Value::Int`.
+    /// Likewise, a `Value::BigInt` or `Value::Decimal` is converted to the nearest `FloatType`.
     pub fn as_number(&self) -> EvalexprResult<FloatType> {
         match self {
             Value::Float(f) => Ok(*f),
             Value::Int(i) => Ok(*i as FloatType),
+            #[cfg(feature = "bigint")]
+            Value::BigInt(i) => Ok(i.to_f64().unwrap_or(FloatType::NAN)),
+            #[cfg(feature = "decimal")]
+            Value::Decimal(d) => {
+                Ok(rust_decimal::prelude::ToPrimitive::to_f64(d).unwrap_or(FloatType::NAN))
+            },
             value => Err(EvalexprError::expected_number(value.clone())),
         }
     }
@@ -137,6 +294,22 @@ impl Value {
         }
     }
 
+    /// Clones the value stored in `self` as `ArrayType`, or returns `Err` if `self` is not a `Value::Array`.
+    pub fn as_array(&self) -> EvalexprResult<ArrayType> {
+        match self {
+            Value::Array(array) => Ok(array.clone()),
+            value => Err(EvalexprError::expected_array(value.clone())),
+        }
+    }
+
+    /// Clones the value stored in `self` as `MapType`, or returns `Err` if `self` is not a `Value::Map`.
+    pub fn as_map(&self) -> EvalexprResult<MapType> {
+        match self {
+            Value::Map(map) => Ok(map.clone()),
+            value => Err(EvalexprError::expected_map(value.clone())),
+        }
+    }
+
     /// Returns `()`, or returns`Err` if `self` is not a `Value::Tuple`.
     pub fn as_empty(&self) -> EvalexprResult<()> {
         match self {
@@ -144,6 +317,94 @@ impl Value {
             value => Err(EvalexprError::expected_empty(value.clone())),
         }
     }
+
+    /// Returns true if `self` and `other` are the same value, as opposed to values that are only considered equal.
+    ///
+    /// Unlike `==`, this compares floats and decimals by their representation,
+    /// so all `NaN`s are identical, but `0.0` and `-0.0` as well as `1.5` and `1.50` are not identical.
+    pub(crate) fn is_identical_to(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) => float_identity(*a) == float_identity(*b),
+            #[cfg(feature = "decimal")]
+            (Value::Decimal(a), Value::Decimal(b)) => a.serialize() == b.serialize(),
+            (Value::Tuple(a), Value::Tuple(b)) | (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_identical_to(b))
+            },
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((a_key, a), (b_key, b))| a_key == b_key && a.is_identical_to(b))
+            },
+            (a, b) => a == b,
+        }
+    }
+
+    /// Feeds the value into the given hasher, such that identical values according to `Value::is_identical_to` have the same hash.
+    pub(crate) fn hash_identity<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Value::String(string) => string.hash(state),
+            Value::Float(float) => float_identity(*float).hash(state),
+            Value::Int(int) => int.hash(state),
+            #[cfg(feature = "bigint")]
+            Value::BigInt(int) => int.hash(state),
+            #[cfg(feature = "decimal")]
+            Value::Decimal(decimal) => decimal.serialize().hash(state),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(datetime) => datetime.hash(state),
+            #[cfg(feature = "chrono")]
+            Value::Duration(duration) => duration.hash(state),
+            Value::Boolean(boolean) => boolean.hash(state),
+            Value::Tuple(values) | Value::Array(values) => {
+                values.len().hash(state);
+                for value in values {
+                    value.hash_identity(state);
+                }
+            },
+            Value::Map(map) => {
+                map.len().hash(state);
+                for (key, value) in map {
+                    key.hash(state);
+                    value.hash_identity(state);
+                }
+            },
+            Value::Lambda(lambda) => lambda.hash(state),
+            Value::Empty => {},
+        }
+    }
+}
+
+/// Returns the bit representation of the float, with all `NaN`s represented the same.
+fn float_identity(float: FloatType) -> u64 {
+    if float.is_nan() {
+        FloatType::NAN.to_bits()
+    } else {
+        float.to_bits()
+    }
+}
+
+/// Returns the duration in seconds, including the fractional part.
+#[cfg(feature = "chrono")]
+pub(crate) fn duration_as_seconds(duration: &DurationType) -> FloatType {
+    duration.num_seconds() as FloatType + FloatType::from(duration.subsec_nanos()) / 1e9
+}
+
+/// Converts an amount of seconds into a duration, as documented for the builtin function `duration::seconds`.
+/// Returns `None` if the amount is not finite or out of the range of `DurationType`.
+#[cfg(feature = "chrono")]
+pub(crate) fn duration_from_seconds(seconds: &Value) -> EvalexprResult<Option<DurationType>> {
+    if let Value::Int(seconds) = seconds {
+        return Ok(DurationType::try_seconds(*seconds));
+    }
+    let seconds = seconds.as_number()?;
+    let whole_seconds = seconds.trunc();
+    if whole_seconds.is_nan() || whole_seconds.abs() >= IntType::MAX as FloatType {
+        return Ok(None);
+    }
+    let nanoseconds = ((seconds - whole_seconds) * 1e9).round() as i64;
+    Ok(DurationType::try_seconds(whole_seconds as IntType)
+        .and_then(|duration| duration.checked_add(&DurationType::nanoseconds(nanoseconds))))
 }
 
 impl From<String> for Value {
@@ -170,6 +431,38 @@ impl From<IntType> for Value {
     }
 }
 
+/// Converts the big integer into a `Value::Int` if it fits into `IntType`, and into a `Value::BigInt` otherwise.
+#[cfg(feature = "bigint")]
+impl From<BigIntType> for Value {
+    fn from(int: BigIntType) -> Self {
+        match IntType::try_from(&int) {
+            Ok(int) => Value::Int(int),
+            Err(_) => Value::BigInt(int),
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<DecimalType> for Value {
+    fn from(decimal: DecimalType) -> Self {
+        Value::Decimal(decimal)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DateTimeType> for Value {
+    fn from(datetime: DateTimeType) -> Self {
+        Value::DateTime(datetime)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<DurationType> for Value {
+    fn from(duration: DurationType) -> Self {
+        Value::Duration(duration)
+    }
+}
+
 impl From<bool> for Value {
     fn from(boolean: bool) -> Self {
         Value::Boolean(boolean)
@@ -182,6 +475,12 @@ impl From<TupleType> for Value {
     }
 }
 
+impl From<MapType> for Value {
+    fn from(map: MapType) -> Self {
+        Value::Map(map)
+    }
+}
+
 impl From<Value> for EvalexprResult<Value> {
     fn from(value: Value) -> Self {
         Ok(value)
@@ -230,6 +529,58 @@ impl TryFrom<Value> for IntType {
     }
 }
 
+#[cfg(feature = "bigint")]
+impl TryFrom<Value> for BigIntType {
+    type Error = EvalexprError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Int(value) => Ok(value.into()),
+            Value::BigInt(value) => Ok(value),
+            _ => Err(EvalexprError::ExpectedInt { actual: value }),
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl TryFrom<Value> for DecimalType {
+    type Error = EvalexprError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Decimal(value) = value {
+            Ok(value)
+        } else {
+            Err(EvalexprError::ExpectedDecimal { actual: value })
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for DateTimeType {
+    type Error = EvalexprError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::DateTime(value) = value {
+            Ok(value)
+        } else {
+            Err(EvalexprError::ExpectedDateTime { actual: value })
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Value> for DurationType {
+    type Error = EvalexprError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Duration(value) = value {
+            Ok(value)
+        } else {
+            Err(EvalexprError::ExpectedDuration { actual: value })
+        }
+    }
+}
+
 impl TryFrom<Value> for bool {
     type Error = EvalexprError;
 
@@ -254,6 +605,18 @@ impl TryFrom<Value> for TupleType {
     }
 }
 
+impl TryFrom<Value> for MapType {
+    type Error = EvalexprError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        if let Value::Map(value) = value {
+            Ok(value)
+        } else {
+            Err(EvalexprError::ExpectedMap { actual: value })
+        }
+    }
+}
+
 impl TryFrom<Value> for () {
     type Error = EvalexprError;
 
@@ -269,6 +632,7 @@ impl TryFrom<Value> for () {
 #[cfg(test)]
 mod tests {
     use crate::value::{TupleType, Value};
+    use alloc::string::String;
 
     #[test]
     fn test_value_conversions() {
@@ -7,12 +7,27 @@ pub enum ValueType {
     String,
     /// The `Value::Float` type.
     Float,
-    /// The `Value::Int` type.
+    /// The `Value::Int` type, which also covers `Value::BigInt` with the `bigint` feature.
     Int,
+    /// The `Value::Decimal` type.
+    #[cfg(feature = "decimal")]
+    Decimal,
+    /// The `Value::DateTime` type.
+    #[cfg(feature = "chrono")]
+    DateTime,
+    /// The `Value::Duration` type.
+    #[cfg(feature = "chrono")]
+    Duration,
     /// The `Value::Boolean` type.
     Boolean,
     /// The `Value::Tuple` type.
     Tuple,
+    /// The `Value::Array` type.
+    Array,
+    /// The `Value::Map` type.
+    Map,
+    /// The `Value::Lambda` type.
+    Lambda,
     /// The `Value::Empty` type.
     Empty,
 }
@@ -23,8 +38,19 @@ impl From<&Value> for ValueType {
             Value::String(_) => ValueType::String,
             Value::Float(_) => ValueType::Float,
             Value::Int(_) => ValueType::Int,
+            #[cfg(feature = "bigint")]
+            Value::BigInt(_) => ValueType::Int,
+            #[cfg(feature = "decimal")]
+            Value::Decimal(_) => ValueType::Decimal,
+            #[cfg(feature = "chrono")]
+            Value::DateTime(_) => ValueType::DateTime,
+            #[cfg(feature = "chrono")]
+            Value::Duration(_) => ValueType::Duration,
             Value::Boolean(_) => ValueType::Boolean,
             Value::Tuple(_) => ValueType::Tuple,
+            Value::Array(_) => ValueType::Array,
+            Value::Map(_) => ValueType::Map,
+            Value::Lambda(_) => ValueType::Lambda,
             Value::Empty => ValueType::Empty,
         }
     }
@@ -0,0 +1,99 @@
+//! Bindings to evaluate expressions from JavaScript with [wasm-bindgen](https://docs.rs/wasm-bindgen).
+//!
+//! When this crate is compiled to WebAssembly with the `wasm` feature, the functions and types of this module are exported to JavaScript,
+//! so web frontends can validate and preview expressions with the same semantics as a Rust backend.
+//! Variables are passed as a JSON object, and results are returned as JSON, with the conversions of the `serde_json` feature.
+//! Errors are thrown as JavaScript `Error`s with the message of the `EvalexprError`.
+//!
+//! Bindings of dependencies are included in a WebAssembly module if it uses the dependency.
+//! To build a module with [wasm-pack](https://rustwasm.github.io/wasm-pack/), create a crate with `crate-type = ["cdylib"]`
+//! that depends on this crate with the `wasm` feature and re-exports the bindings with `pub use evalexpr::wasm::*;`.
+//! Note that wasm-bindgen requires a newer Rust version than this crate.
+//!
+//! ```js
+//! import { evaluate, compile } from "evalexpr";
+//!
+//! evaluate("price * qty", '{ "price": 2.5, "qty": 4 }'); // "10.0"
+//!
+//! const expression = compile("price * qty > limit");
+//! expression.variables(); // ["limit", "price", "qty"]
+//! expression.evaluate('{ "price": 2.5, "qty": 4, "limit": 5 }'); // "true"
+//! ```
+
+use crate::{
+    error::{EvalexprError, EvalexprResult},
+    value::Value,
+    HashMapContext, Node,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::convert::TryFrom;
+use wasm_bindgen::prelude::*;
+
+/// Evaluates an expression with the variables of a JSON object, and returns the result as JSON.
+///
+/// Throws an error if the context is not a JSON object, if the expression cannot be parsed or evaluated,
+/// or if the result cannot be represented as JSON.
+#[wasm_bindgen]
+pub fn evaluate(expression: &str, context_json: &str) -> Result<String, JsError> {
+    let result =
+        crate::build_operator_tree(expression).and_then(|tree| evaluate_tree(&tree, context_json));
+    result.map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Parses an expression into an `Expression` that can be evaluated many times without parsing it again.
+///
+/// Throws an error if the expression cannot be parsed.
+#[wasm_bindgen]
+pub fn compile(expression: &str) -> Result<Expression, JsError> {
+    crate::build_operator_tree(expression)
+        .map(|tree| Expression { tree })
+        .map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// A parsed expression, as returned by `compile`.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Expression {
+    tree: Node,
+}
+
+#[wasm_bindgen]
+impl Expression {
+    /// Evaluates the expression with the variables of a JSON object, and returns the result as JSON.
+    ///
+    /// Throws an error if the context is not a JSON object, if the expression cannot be evaluated,
+    /// or if the result cannot be represented as JSON.
+    pub fn evaluate(&self, context_json: &str) -> Result<String, JsError> {
+        evaluate_tree(&self.tree, context_json).map_err(|error| JsError::new(&error.to_string()))
+    }
+
+    /// Returns the sorted identifiers of the variables that the expression reads, without duplicates.
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables: Vec<String> = self
+            .tree
+            .iter_read_variable_identifiers()
+            .map(String::from)
+            .collect();
+        variables.sort_unstable();
+        variables.dedup();
+        variables
+    }
+
+    /// Returns the expression as formatted by the `Display` implementation of `Node`.
+    #[wasm_bindgen(js_name = toString)]
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.tree.to_string()
+    }
+}
+
+fn evaluate_tree(tree: &Node, context_json: &str) -> EvalexprResult<String> {
+    let json = serde_json::from_str(context_json)
+        .map_err(|error| EvalexprError::CustomMessage(error.to_string()))?;
+    let mut context = HashMapContext::from_json(json)?;
+    let result: Value = tree.eval_with_context_mut(&mut context)?;
+    Ok(serde_json::Value::try_from(result)?.to_string())
+}
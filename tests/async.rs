@@ -0,0 +1,184 @@
+#![cfg(feature = "async")]
+
+use evalexpr::*;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+/// Polls the future until it is ready, as the futures in these tests do not wait for external events.
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// A future that is pending once before it is ready, like a future waiting for a response.
+struct Delayed {
+    value: Value,
+    is_pending: bool,
+}
+
+impl Future for Delayed {
+    type Output = EvalexprResult<Value>;
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        if self.is_pending {
+            self.is_pending = false;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(self.value.clone()))
+        }
+    }
+}
+
+fn eval_async(expression: &str, context: &HashMapContext) -> EvalexprResult<Value> {
+    block_on(build_operator_tree(expression)?.eval_with_context_async(context))
+}
+
+fn assert_send<T: Send>(value: T) -> T {
+    value
+}
+
+#[test]
+fn test_async_functions() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut context = context_map! {
+        "a" => 2,
+        "double" => Function::new(|argument| Ok(Value::from(argument.as_int()? * 2))),
+    }
+    .unwrap();
+    let counter = calls.clone();
+    context
+        .set_async_function(
+            "fetch".into(),
+            AsyncFunction::new(move |argument| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async move { Ok(Value::from(argument.as_int()? + 100)) }
+            }),
+        )
+        .unwrap();
+    context
+        .set_async_function(
+            "sum".into(),
+            AsyncFunction::new(|argument| async move {
+                let mut sum = 0;
+                for value in argument.as_tuple()? {
+                    sum += value.as_int()?;
+                }
+                Ok(Value::from(sum))
+            }),
+        )
+        .unwrap();
+    context
+        .set_async_function(
+            "fail".into(),
+            AsyncFunction::new(|_| async { Err(EvalexprError::CustomMessage("offline".into())) }),
+        )
+        .unwrap();
+
+    assert_eq!(eval_async("fetch(1)", &context), Ok(Value::from(101)));
+    assert_eq!(
+        eval_async("double(fetch(a)) + sum(fetch(1), 2, a)", &context),
+        Ok(Value::from(309))
+    );
+    assert_eq!(eval_async("1 + double(a)", &context), Ok(Value::from(5)));
+    assert_eq!(
+        eval_async("fetch(1) < fetch(2) < 50", &context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(
+        eval_async("let x = fetch(1); x * 2", &context),
+        Ok(Value::from(202))
+    );
+    assert_eq!(
+        eval_async("let fetch = |x| x; fetch(1)", &context),
+        Ok(Value::from(1))
+    );
+    assert_eq!(
+        eval_async("fail(1)", &context),
+        Err(EvalexprError::CustomMessage("offline".into()))
+    );
+    // Async functions are not called in the synchronous evaluation.
+    assert_eq!(
+        eval_with_context("fetch(1)", &context),
+        Err(EvalexprError::function_identifier_not_found("fetch".into()))
+    );
+
+    // Short-circuiting operators and `if` only call the async functions whose result is needed.
+    calls.store(0, Ordering::SeqCst);
+    assert_eq!(
+        eval_async("if(a > 1, fetch(1), fetch(2)) + fetch(3)", &context),
+        Ok(Value::from(204))
+    );
+    assert_eq!(
+        eval_async("false && fetch(1) == 101", &context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // The futures can be sent to other threads.
+    let tree = build_operator_tree("fetch(a) * 2").unwrap();
+    let future = assert_send(tree.eval_with_context_async(&context));
+    assert_eq!(block_on(future), Ok(Value::from(204)));
+}
+
+#[test]
+fn test_pending_async_functions() {
+    let mut context = HashMapContext::new();
+    context
+        .set_async_function(
+            "delayed".into(),
+            AsyncFunction::new(|value| Delayed {
+                value,
+                is_pending: true,
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        eval_async("delayed(1) + delayed(2) * delayed(3)", &context),
+        Ok(Value::from(7))
+    );
+}
+
+#[test]
+fn test_async_evaluation_options() {
+    let mut context = HashMapContext::new();
+    context
+        .set_async_function(
+            "id".into(),
+            AsyncFunction::new(|argument| async { Ok(argument) }),
+        )
+        .unwrap();
+    let tree = build_operator_tree("id(1) + id(2) + id(3)").unwrap();
+    assert_eq!(
+        block_on(tree.eval_with_context_and_options_async(
+            &context,
+            &EvalOptions::new().with_max_node_evaluations(Some(3))
+        )),
+        Err(EvalexprError::BudgetExceeded {
+            max_node_evaluations: 3
+        })
+    );
+    assert_eq!(
+        block_on(tree.eval_with_context_async(&EmptyContext)),
+        Err(EvalexprError::function_identifier_not_found("id".into()))
+    );
+}
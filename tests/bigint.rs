@@ -0,0 +1,195 @@
+#![cfg(feature = "bigint")]
+
+use evalexpr::*;
+
+fn bigint(digits: &str) -> Value {
+    Value::BigInt(digits.parse::<BigIntType>().unwrap())
+}
+
+#[test]
+fn test_bigint_literals() {
+    assert_eq!(
+        eval("9223372036854775808"),
+        Ok(bigint("9223372036854775808"))
+    );
+    assert_eq!(
+        eval("-0x1_0000_0000_0000_0000"),
+        Ok(bigint("-18446744073709551616"))
+    );
+    assert_eq!(eval("-9223372036854775808"), Ok(Value::Int(IntType::MIN)));
+    assert_eq!(
+        build_operator_tree("-99999999999999999999")
+            .unwrap()
+            .to_string(),
+        "-99999999999999999999"
+    );
+}
+
+#[test]
+fn test_bigint_arithmetic() {
+    assert_eq!(
+        eval("9223372036854775807 + 1"),
+        Ok(bigint("9223372036854775808"))
+    );
+    assert_eq!(
+        eval("-9223372036854775807 - 2"),
+        Ok(bigint("-9223372036854775809"))
+    );
+    assert_eq!(
+        eval("4294967296 * 4294967296 * 4294967296"),
+        Ok(bigint("79228162514264337593543950336"))
+    );
+    assert_eq!(
+        eval("-(-9223372036854775807 - 1)"),
+        Ok(bigint("9223372036854775808"))
+    );
+    assert_eq!(
+        eval("(-9223372036854775807 - 1) / -1"),
+        Ok(bigint("9223372036854775808"))
+    );
+    assert_eq!(eval("(-9223372036854775807 - 1) % -1"), Ok(Value::Int(0)));
+
+    // Results that fit into an integer are integers again.
+    assert_eq!(
+        eval("9223372036854775808 - 1"),
+        Ok(Value::Int(IntType::MAX))
+    );
+    assert_eq!(
+        eval("100000000000000000000 / 100000000000000000000"),
+        Ok(Value::Int(1))
+    );
+    assert_eq!(eval("100000000000000000000 % 7"), Ok(Value::Int(2)));
+    assert_eq!(eval("100000000000000000000 / 0.5"), Ok(Value::Float(2e20)));
+    assert!(matches!(
+        eval("100000000000000000000 / 0"),
+        Err(EvalexprError::DivisionByZero { .. })
+    ));
+    assert!(matches!(
+        eval("100000000000000000000 % 0"),
+        Err(EvalexprError::DivisionByZero { .. })
+    ));
+    // Overflowing divisions result in big integers.
+    assert_eq!(
+        eval(&format!("(-{} - 1) / -1", IntType::MAX)),
+        Ok(Value::from(BigIntType::from(IntType::MAX) + 1))
+    );
+
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_with_context_mut("a = 9223372036854775807; a += a; a", &mut context),
+        Ok(bigint("18446744073709551614"))
+    );
+}
+
+#[test]
+fn test_bigint_comparisons() {
+    assert_eq!(eval("100000000000000000000 > 1"), Ok(Value::Boolean(true)));
+    assert_eq!(
+        eval("-100000000000000000000 < -100000000000000000000 + 1"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        eval("100000000000000000000 >= 1e21"),
+        Ok(Value::Boolean(false))
+    );
+    assert_eq!(
+        eval("100000000000000000000 == 100000000000000000000"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        eval("(100000000000000000000 - 99999999999999999999, 2) == (1, 2)"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        eval("1 < 100000000000000000000 <= 1e20"),
+        Ok(Value::Boolean(true))
+    );
+}
+
+#[test]
+fn test_bigint_builtin_functions() {
+    assert_eq!(
+        eval("min(3, 100000000000000000000, 2.5)"),
+        Ok(Value::Float(2.5))
+    );
+    assert_eq!(
+        eval("min(3, -100000000000000000000, -2.5)"),
+        Ok(bigint("-100000000000000000000"))
+    );
+    assert_eq!(
+        eval("max(3, 100000000000000000000, 2.5)"),
+        Ok(bigint("100000000000000000000"))
+    );
+    assert_eq!(
+        eval("max(100000000000000000000, 200000000000000000000)"),
+        Ok(bigint("200000000000000000000"))
+    );
+    assert_eq!(eval("max(3, -100000000000000000000)"), Ok(Value::Int(3)));
+    assert_eq!(
+        eval("math::abs(-9223372036854775807 - 1)"),
+        Ok(bigint("9223372036854775808"))
+    );
+    assert_eq!(
+        eval("floor(100000000000000000000)"),
+        Ok(bigint("100000000000000000000"))
+    );
+    assert_eq!(
+        eval("typeof(100000000000000000000)"),
+        Ok(Value::from("int"))
+    );
+    assert_eq!(eval("is_int(100000000000000000000)"), Ok(Value::from(true)));
+    assert_eq!(eval_number("100000000000000000000 + 0.0"), Ok(1e20));
+    assert_eq!(eval("int(1e20)"), Ok(bigint("100000000000000000000")));
+    assert_eq!(
+        eval("int(\"-100000000000000000000\")"),
+        Ok(bigint("-100000000000000000000"))
+    );
+    assert_eq!(
+        eval("math::rem_euclid(99999999999999999999999, 7)"),
+        Ok(Value::Int(4))
+    );
+    assert_eq!(
+        eval("math::rem_euclid(-99999999999999999999999, -7)"),
+        Ok(Value::Int(3))
+    );
+    assert_eq!(
+        eval("math::div_euclid(-99999999999999999999999, 7)"),
+        Ok(bigint("-14285714285714285714286"))
+    );
+    assert_eq!(
+        eval("math::div_euclid(7, 99999999999999999999999)"),
+        Ok(Value::Int(0))
+    );
+    assert_eq!(
+        eval("math::rem_euclid(99999999999999999999999, 0)"),
+        Err(EvalexprError::DivisionByZero {
+            dividend: bigint("99999999999999999999999")
+        })
+    );
+}
+
+#[test]
+fn test_bigint_conversions() {
+    assert_eq!(Value::from(BigIntType::from(5)), Value::Int(5));
+    assert_eq!(
+        Value::from(BigIntType::from(IntType::MAX) + 1),
+        bigint("9223372036854775808")
+    );
+    assert_eq!(
+        bigint("9223372036854775808").as_bigint(),
+        Ok(BigIntType::from(IntType::MAX) + 1)
+    );
+    assert_eq!(Value::Int(5).as_bigint(), Ok(BigIntType::from(5)));
+    assert_eq!(
+        bigint("9223372036854775808").as_int(),
+        Err(EvalexprError::expected_int(bigint("9223372036854775808")))
+    );
+    assert_eq!(
+        bigint("9223372036854775808").as_number(),
+        Ok(9223372036854775808.0)
+    );
+    assert_eq!(
+        ValueType::from(&bigint("9223372036854775808")),
+        ValueType::Int
+    );
+}
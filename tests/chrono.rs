@@ -0,0 +1,242 @@
+#![cfg(feature = "chrono")]
+
+use evalexpr::*;
+
+fn datetime(string: &str) -> Value {
+    Value::DateTime(string.parse::<DateTimeType>().unwrap())
+}
+
+fn duration(seconds: i64) -> Value {
+    Value::Duration(DurationType::seconds(seconds))
+}
+
+#[test]
+fn test_datetime_arithmetic() {
+    let context = context_map! {
+        "start" => datetime("2024-01-01T08:00:00Z"),
+        "end" => datetime("2024-01-02T10:30:00Z"),
+        "hour" => duration(3600),
+    }
+    .unwrap();
+
+    assert_eq!(
+        eval_with_context("end - start", &context),
+        Ok(duration(26 * 3600 + 1800))
+    );
+    assert_eq!(
+        eval_with_context("start + hour", &context),
+        Ok(datetime("2024-01-01T09:00:00Z"))
+    );
+    assert_eq!(
+        eval_with_context("hour + start", &context),
+        Ok(datetime("2024-01-01T09:00:00Z"))
+    );
+    assert_eq!(
+        eval_with_context("start - hour", &context),
+        Ok(datetime("2024-01-01T07:00:00Z"))
+    );
+    assert_eq!(
+        eval_with_context("hour * 3 - hour / 2", &context),
+        Ok(duration(9000))
+    );
+    assert_eq!(eval_with_context("3 * hour", &context), Ok(duration(10800)));
+    assert_eq!(eval_with_context("-hour", &context), Ok(duration(-3600)));
+    assert_eq!(
+        eval_with_context("(end - start) / hour", &context),
+        Ok(Value::Float(26.5))
+    );
+    assert_eq!(
+        eval_with_context(
+            "start < end && end - start > hour && start == start",
+            &context
+        ),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("start >= end || hour <= -hour", &context),
+        Ok(Value::from(false))
+    );
+
+    assert_eq!(
+        eval_with_context("hour / 0", &context),
+        Err(EvalexprError::DivisionByZero {
+            dividend: duration(3600)
+        })
+    );
+    assert_eq!(
+        eval_with_context("start + start", &context),
+        Err(EvalexprError::expected_number_or_string(datetime(
+            "2024-01-01T08:00:00Z"
+        )))
+    );
+    assert!(eval_with_context("start > hour", &context).is_err());
+    assert!(eval_with_context("hour * 1.5", &context).is_err());
+
+    // Assignment operators use the same arithmetic.
+    let mut context = context;
+    assert_eq!(
+        eval_with_context_mut("start += hour * 2; start", &mut context),
+        Ok(datetime("2024-01-01T10:00:00Z"))
+    );
+}
+
+#[test]
+fn test_now_is_not_cached() {
+    let tree = build_operator_tree("now() + duration::seconds(a)").unwrap();
+    let mut cache = EvalCache::new();
+    let context = context_map! { "a" => 1 }.unwrap();
+    let first = tree.eval_cached(&context, &mut cache).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let second = tree.eval_cached(&context, &mut cache).unwrap();
+    assert!(second.as_datetime().unwrap() > first.as_datetime().unwrap());
+
+    let mut evaluator = IncrementalEvaluator::new(tree, context);
+    let first = evaluator.eval().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let second = evaluator.eval().unwrap();
+    assert!(second.as_datetime().unwrap() > first.as_datetime().unwrap());
+}
+
+#[test]
+fn test_datetime_builtin_functions() {
+    assert_eq!(
+        eval(r#"now() > datetime::parse("2024-01-01T00:00:00Z")"#),
+        Ok(Value::from(true))
+    );
+
+    assert_eq!(
+        eval(r#"datetime::parse("2024-03-01T12:30:00+02:00")"#),
+        Ok(datetime("2024-03-01T10:30:00Z"))
+    );
+    assert_eq!(
+        eval(r#"datetime::parse("01.03.2024 12:30", "%d.%m.%Y %H:%M")"#),
+        Ok(datetime("2024-03-01T12:30:00Z"))
+    );
+    assert_eq!(
+        eval(r#"datetime::parse("2024-03-01", "%Y-%m-%d")"#),
+        Ok(datetime("2024-03-01T00:00:00Z"))
+    );
+    assert!(matches!(
+        eval(r#"datetime::parse("yesterday")"#),
+        Err(EvalexprError::InvalidDateTime { datetime, .. }) if datetime == "yesterday"
+    ));
+    assert_eq!(
+        eval(r#"datetime::format(datetime::parse("2024-03-01T12:30:00Z"), "%Y/%m/%d %H:%M")"#),
+        Ok(Value::from("2024/03/01 12:30"))
+    );
+    assert!(matches!(
+        eval(r#"datetime::format(now(), "%Q")"#),
+        Err(EvalexprError::InvalidFormatString { .. })
+    ));
+
+    assert_eq!(
+        eval(r#"datetime::timestamp(datetime::parse("2024-01-01T00:00:00Z"))"#),
+        Ok(Value::Int(1_704_067_200))
+    );
+    assert_eq!(
+        eval("datetime::from_timestamp(1704067200)"),
+        Ok(datetime("2024-01-01T00:00:00Z"))
+    );
+    assert_eq!(
+        eval("datetime::from_timestamp(1.5)"),
+        Ok(datetime("1970-01-01T00:00:01.500Z"))
+    );
+    assert_eq!(
+        eval("datetime::from_timestamp(1e30)"),
+        Err(EvalexprError::conversion_error(
+            Value::Float(1e30),
+            ValueType::DateTime
+        ))
+    );
+
+    assert_eq!(eval("duration::seconds(90)"), Ok(duration(90)));
+    assert_eq!(
+        eval("duration::seconds(-0.25)"),
+        Ok(Value::Duration(DurationType::milliseconds(-250)))
+    );
+    assert_eq!(
+        eval("duration::as_seconds(duration::seconds(1.5))"),
+        Ok(Value::Float(1.5))
+    );
+    assert!(matches!(
+        eval("duration::seconds(0.0 / 0.0)"),
+        Err(EvalexprError::ConversionError {
+            target: ValueType::Duration,
+            ..
+        })
+    ));
+    assert_eq!(
+        eval("typeof(now()) + typeof(duration::seconds(1))"),
+        Ok(Value::from("datetimeduration"))
+    );
+    assert_eq!(
+        eval("is_datetime(now()) && is_duration(duration::seconds(1)) && !is_datetime(1)"),
+        Ok(Value::from(true))
+    );
+}
+
+#[test]
+fn test_datetime_display() {
+    assert_eq!(
+        datetime("2024-01-01T08:00:00Z").to_string(),
+        "2024-01-01T08:00:00Z"
+    );
+    assert_eq!(
+        datetime("2024-01-01T08:00:00.25+01:00").to_string(),
+        "2024-01-01T07:00:00.250Z"
+    );
+    assert_eq!(duration(0).to_string(), "0s");
+    assert_eq!(duration(90061).to_string(), "1d 1h 1m 1s");
+    assert_eq!(duration(-5400).to_string(), "-1h 30m");
+    assert_eq!(
+        Value::Duration(DurationType::nanoseconds(1_500_001)).to_string(),
+        "1ms 500us 1ns"
+    );
+
+    // Displayed operator trees can be parsed again.
+    let context = context_map! {
+        "start" => datetime("2024-01-01T08:00:00Z"),
+        "step" => Value::Duration(DurationType::milliseconds(-1500)),
+    }
+    .unwrap();
+    let tree = build_operator_tree("start + step * 2 > start")
+        .unwrap()
+        .simplify(&context);
+    assert_eq!(tree.to_string(), "false");
+    let tree = build_operator_tree("(start, step)")
+        .unwrap()
+        .simplify(&context);
+    assert_eq!(
+        tree.to_string(),
+        r#"(timestamp("2024-01-01T08:00:00Z"), duration("-1s 500ms"))"#
+    );
+    assert_eq!(
+        build_operator_tree(&tree.to_string()).unwrap().eval(),
+        Ok(Value::Tuple(vec![
+            datetime("2024-01-01T08:00:00Z"),
+            Value::Duration(DurationType::milliseconds(-1500))
+        ]))
+    );
+}
+
+#[test]
+fn test_duration_and_timestamp_builtin_functions() {
+    assert_eq!(eval(r#"duration("5m 30s")"#), Ok(duration(330)));
+    assert_eq!(
+        eval(r#"duration("-1.5 seconds 2ns")"#),
+        Ok(Value::Duration(-DurationType::nanoseconds(1_500_000_002)))
+    );
+    assert_eq!(
+        eval(r#"timestamp("2024-02-29 14:30:00.5+02:00")"#),
+        Ok(datetime("2024-02-29T12:30:00.5Z"))
+    );
+
+    let context = context_map! { "created_at" => datetime("2024-01-01T08:00:00Z") }.unwrap();
+    assert_eq!(
+        eval_with_context(
+            r#"timestamp("2024-01-01T10:00:00Z") - created_at > duration("1h 59m")"#,
+            &context
+        ),
+        Ok(Value::from(true))
+    );
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "decimal")]
+
+use evalexpr::*;
+
+fn decimal(digits: &str) -> Value {
+    Value::Decimal(digits.parse::<DecimalType>().unwrap())
+}
+
+fn eval_decimal(string: &str) -> EvalexprResult<Value> {
+    build_operator_tree_with_config(string, &ParserConfig::new().with_decimal_literals(true))?
+        .eval()
+}
+
+#[test]
+fn test_decimal_literals() {
+    assert_eq!(eval_decimal("0.1"), Ok(decimal("0.1")));
+    assert_eq!(eval_decimal("-2.50"), Ok(decimal("-2.50")));
+    assert_eq!(eval_decimal("1e-3"), Ok(decimal("0.001")));
+    assert_eq!(eval_decimal("2.5e2"), Ok(decimal("250")));
+    assert_eq!(eval_decimal("3"), Ok(Value::Int(3)));
+    assert_eq!(eval_decimal("1e100"), Ok(Value::Float(1e100)));
+
+    // Without the option, literals are floats.
+    assert_eq!(eval("0.1"), Ok(Value::Float(0.1)));
+}
+
+#[test]
+fn test_decimal_arithmetic() {
+    assert_eq!(eval_decimal("0.1 + 0.2 == 0.3"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("0.1 + 0.2 == 0.3"), Ok(Value::Boolean(false)));
+    assert_eq!(eval_decimal("19.99 * 3"), Ok(decimal("59.97")));
+    assert_eq!(eval_decimal("10 - 0.01"), Ok(decimal("9.99")));
+    assert_eq!(eval_decimal("1.5 / 2"), Ok(decimal("0.75")));
+    assert_eq!(eval_decimal("10.5 % 3"), Ok(decimal("1.5")));
+    assert_eq!(eval_decimal("-(1.5)"), Ok(decimal("-1.5")));
+    assert!(matches!(
+        eval_decimal("1.5 / 0"),
+        Err(EvalexprError::DivisionByZero { .. })
+    ));
+    assert!(matches!(
+        eval_decimal("1.5 % 0.0"),
+        Err(EvalexprError::DivisionByZero { .. })
+    ));
+    assert!(matches!(
+        eval_decimal("7922816251426433759354395033.5 * 100"),
+        Err(EvalexprError::MultiplicationError { .. })
+    ));
+
+    // Mixing decimals with floats results in floats.
+    let context = context_map! {
+        "price" => Value::Decimal("4.20".parse().unwrap()),
+        "factor" => 0.5,
+    }
+    .unwrap();
+    assert_eq!(
+        eval_with_context("price * 2", &context),
+        Ok(decimal("8.40"))
+    );
+    assert_eq!(
+        eval_with_context("price * factor", &context),
+        Ok(Value::Float(2.1))
+    );
+    assert_eq!(
+        eval_with_context("price ^ 2", &context),
+        Ok(Value::Float(4.2f64.powf(2.0)))
+    );
+}
+
+#[test]
+fn test_decimal_comparisons() {
+    assert_eq!(eval_decimal("0.3 > 0.29"), Ok(Value::Boolean(true)));
+    assert_eq!(eval_decimal("2.0 <= 2"), Ok(Value::Boolean(true)));
+    assert_eq!(eval_decimal("1.50 == 1.5"), Ok(Value::Boolean(true)));
+    assert_eq!(eval_decimal("2.0 == 2"), Ok(Value::Boolean(true)));
+    assert_eq!(
+        eval_decimal("(2.0, 1) != (2, 1.00)"),
+        Ok(Value::Boolean(false))
+    );
+    assert_eq!(eval_decimal("0 < 0.5 < 1"), Ok(Value::Boolean(true)));
+}
+
+#[test]
+fn test_decimal_builtin_functions() {
+    assert_eq!(eval_decimal("floor(-2.5)"), Ok(decimal("-3")));
+    assert_eq!(eval_decimal("round(2.5)"), Ok(decimal("3")));
+    assert_eq!(eval_decimal("ceil(2.1)"), Ok(decimal("3")));
+    assert_eq!(eval_decimal("math::abs(-2.5)"), Ok(decimal("2.5")));
+    assert_eq!(eval_decimal("min(3, 2.5, 2.75)"), Ok(decimal("2.5")));
+    assert_eq!(eval_decimal("max(3, 2.5, 2.75)"), Ok(Value::Int(3)));
+    assert_eq!(eval_decimal("typeof(2.5)"), Ok(Value::from("decimal")));
+    assert_eq!(eval_decimal("is_decimal(2.5)"), Ok(Value::from(true)));
+    assert_eq!(eval_decimal("is_number(2.5)"), Ok(Value::from(true)));
+    assert_eq!(eval_decimal("is_float(2.5)"), Ok(Value::from(false)));
+    assert_eq!(eval_decimal("math::sqrt(6.25)"), Ok(Value::Float(2.5)));
+    assert_eq!(eval_decimal("int(-2.5)"), Ok(Value::Int(-2)));
+    assert_eq!(eval_decimal("float(2.5)"), Ok(Value::Float(2.5)));
+    assert_eq!(eval_decimal("str(2.50)"), Ok(Value::from("2.50")));
+    assert_eq!(
+        eval_decimal("math::rem_euclid(-7.5, 2)"),
+        Ok(decimal("0.5"))
+    );
+    assert_eq!(
+        eval_decimal("math::rem_euclid(0.3, -0.1)"),
+        Ok(decimal("0.0"))
+    );
+    assert_eq!(eval_decimal("math::div_euclid(-7.5, 2)"), Ok(decimal("-4")));
+    assert_eq!(
+        eval_decimal("math::div_euclid(7, -0.5)"),
+        Ok(decimal("-14"))
+    );
+    assert_eq!(
+        eval_decimal("math::rem_euclid(2.5, 0)"),
+        Err(EvalexprError::DivisionByZero {
+            dividend: decimal("2.5")
+        })
+    );
+}
+
+#[test]
+fn test_decimal_conversions() {
+    assert_eq!(decimal("1.5").as_decimal(), Ok("1.5".parse().unwrap()));
+    assert_eq!(Value::Int(2).as_decimal(), Ok(DecimalType::from(2)));
+    assert_eq!(
+        Value::Float(1.5).as_decimal(),
+        Err(EvalexprError::expected_decimal(Value::Float(1.5)))
+    );
+    assert_eq!(decimal("1.5").as_number(), Ok(1.5));
+    assert_eq!(ValueType::from(&decimal("1.5")), ValueType::Decimal);
+
+    let config = ParserConfig::new().with_decimal_literals(true);
+    let tree = build_operator_tree_with_config("2.0 * -0.5", &config).unwrap();
+    assert_eq!(tree.to_string(), "2.0 * -0.5");
+    let tree = build_operator_tree_with_config(&tree.to_string(), &config).unwrap();
+    assert_eq!(tree.eval(), Ok(decimal("-1.00")));
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "derive")]
+
+use evalexpr::*;
+
+#[derive(Context)]
+struct Inputs {
+    price: f64,
+    qty: i64,
+    name: String,
+    #[evalexpr(rename = "is_active")]
+    active: bool,
+    #[evalexpr(skip)]
+    #[allow(dead_code)]
+    internal: Vec<u8>,
+    r#type: &'static str,
+}
+
+#[derive(Context)]
+struct Generic<T> {
+    value: T,
+}
+
+#[derive(Context)]
+struct Nothing {}
+
+fn inputs() -> Inputs {
+    Inputs {
+        price: 2.5,
+        qty: 4,
+        name: "apple".into(),
+        active: true,
+        internal: vec![1, 2, 3],
+        r#type: "fruit",
+    }
+}
+
+#[test]
+fn test_derived_context_variables() {
+    let inputs = inputs();
+    assert_eq!(
+        eval_with_context("price * qty", &inputs),
+        Ok(Value::from(10.0))
+    );
+    assert_eq!(
+        eval_with_context("name + \" \" + type", &inputs),
+        Ok(Value::from("apple fruit"))
+    );
+    assert_eq!(
+        eval_with_context("is_active && qty > 3", &inputs),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("str::len(name)", &inputs),
+        Ok(Value::from(5))
+    );
+    assert_eq!(
+        eval_with_context("internal", &inputs),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "internal".into(),
+            suggestions: Vec::new(),
+            span: Some(Span::new(0, 8)),
+        })
+    );
+    assert_eq!(
+        eval_with_context("prise", &inputs),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "prise".into(),
+            suggestions: vec!["price".into()],
+            span: Some(Span::new(0, 5)),
+        })
+    );
+    assert!(eval_with_context("active", &inputs).is_err());
+    assert_eq!(inputs.get_value("price"), None);
+
+    assert_eq!(
+        eval_with_context("value + 1", &Generic { value: 41 }),
+        Ok(Value::from(42))
+    );
+    assert_eq!(
+        eval_with_context("value", &Generic { value: "text" }),
+        Ok(Value::from("text"))
+    );
+}
+
+#[test]
+fn test_derived_context_functions() {
+    let mut inputs = inputs();
+    assert_eq!(
+        eval_with_context("max(price, qty)", &inputs),
+        Ok(Value::from(4))
+    );
+    assert_eq!(
+        eval_with_context("f(1)", &Nothing {}),
+        Err(EvalexprError::function_identifier_not_found("f".into()))
+    );
+    assert_eq!(
+        inputs.set_builtin_functions_disabled(true),
+        Err(EvalexprError::BuiltinFunctionsCannotBeDisabled)
+    );
+    assert_eq!(inputs.set_builtin_functions_disabled(false), Ok(()));
+    let mut identifiers: Vec<_> = inputs.iter_variable_identifiers().collect();
+    identifiers.sort_unstable();
+    assert_eq!(
+        identifiers,
+        vec!["is_active", "name", "price", "qty", "type"]
+    );
+    assert_eq!(Nothing {}.iter_variable_identifiers().count(), 0);
+}
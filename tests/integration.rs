@@ -3,6 +3,15 @@
 use evalexpr::{error::*, *};
 use std::convert::TryFrom;
 
+/// Returns the error of reading the variable with the given identifier at the given span, if it does not exist.
+fn variable_not_found_at(identifier: &str, span: Span) -> EvalexprError {
+    EvalexprError::VariableIdentifierNotFound {
+        identifier: identifier.into(),
+        suggestions: Vec::new(),
+        span: Some(span),
+    }
+}
+
 #[test]
 fn test_unary_examples() {
     assert_eq!(eval("3"), Ok(Value::Int(3)));
@@ -11,9 +20,7 @@ fn test_unary_examples() {
     assert_eq!(eval("false"), Ok(Value::Boolean(false)));
     assert_eq!(
         eval("blub"),
-        Err(EvalexprError::VariableIdentifierNotFound(
-            "blub".to_string()
-        ))
+        Err(variable_not_found_at("blub", Span::new(0, 4)))
     );
     assert_eq!(eval("-3"), Ok(Value::Int(-3)));
     assert_eq!(eval("-3.6"), Ok(Value::Float(-3.6)));
@@ -84,6 +91,11 @@ fn test_mod_examples() {
     assert_eq!(eval("1 % 4"), Ok(Value::Int(1)));
     assert_eq!(eval("6 % 4"), Ok(Value::Int(2)));
     assert_eq!(eval("1 % 4 + 2"), Ok(Value::Int(3)));
+    assert_eq!(eval("-7 % 3"), Ok(Value::Int(-1)));
+    assert_eq!(eval("7 % -3"), Ok(Value::Int(1)));
+    assert_eq!(eval("5.5 % 2"), Ok(Value::Float(1.5)));
+    assert_eq!(eval("-5.5 % 2.0"), Ok(Value::Float(-1.5)));
+    assert_eq!(eval("7 % 2.5"), Ok(Value::Float(2.0)));
 }
 
 #[test]
@@ -372,6 +384,13 @@ fn test_builtin_functions() {
     assert_eq!(eval("round(1.9)"), Ok(Value::Float(2.0)));
     assert_eq!(eval("ceil(1.1)"), Ok(Value::Float(2.0)));
     assert_eq!(eval("ceil(1.9)"), Ok(Value::Float(2.0)));
+    assert_eq!(eval("floor(3)"), Ok(Value::Int(3)));
+    assert_eq!(eval("round(-3)"), Ok(Value::Int(-3)));
+    assert_eq!(eval("ceil(3)"), Ok(Value::Int(3)));
+    assert_eq!(
+        eval("floor(\"a\")"),
+        Err(EvalexprError::expected_number(Value::from("a")))
+    );
     assert_eq!(eval("math::is_nan(1.0/0.0)"), Ok(Value::Boolean(false)));
     assert_eq!(eval("math::is_nan(0.0/0.0)"), Ok(Value::Boolean(true)));
     assert_eq!(eval("math::is_finite(1.0/0.0)"), Ok(Value::Boolean(false)));
@@ -389,6 +408,79 @@ fn test_builtin_functions() {
     assert_eq!(eval("math::abs(-15.4)"), Ok(Value::Float(15.4)));
     assert_eq!(eval("math::abs(15)"), Ok(Value::Int(15)));
     assert_eq!(eval("math::abs(-15)"), Ok(Value::Int(15)));
+    // Euclidean division
+    assert_eq!(eval("math::rem_euclid(7, 3)"), Ok(Value::Int(1)));
+    assert_eq!(eval("math::rem_euclid(-7, 3)"), Ok(Value::Int(2)));
+    assert_eq!(eval("math::rem_euclid(-7, -3)"), Ok(Value::Int(2)));
+    assert_eq!(eval("math::div_euclid(-7, 3)"), Ok(Value::Int(-3)));
+    assert_eq!(eval("math::div_euclid(-7, -3)"), Ok(Value::Int(3)));
+    assert_eq!(
+        eval("math::rem_euclid(-90.5, 360)"),
+        Ok(Value::Float(269.5))
+    );
+    assert_eq!(eval("math::div_euclid(-5.5, 2)"), Ok(Value::Float(-3.0)));
+    assert_eq!(eval("math::div_euclid(5.5, -2.0)"), Ok(Value::Float(-2.0)));
+    assert_eq!(
+        eval("math::rem_euclid(7, 0)"),
+        Err(EvalexprError::DivisionByZero {
+            dividend: Value::Int(7)
+        })
+    );
+    assert_eq!(
+        eval(&format!("math::div_euclid({} - 1, -1)", IntType::MIN + 1)),
+        Err(EvalexprError::DivisionError {
+            dividend: Value::Int(IntType::MIN),
+            divisor: Value::Int(-1)
+        })
+    );
+    assert!(eval("math::rem_euclid(7.0, 0.0)")
+        .unwrap()
+        .as_float()
+        .unwrap()
+        .is_nan());
+    assert_eq!(
+        eval("math::rem_euclid(\"7\", 3)"),
+        Err(EvalexprError::expected_number(Value::from("7")))
+    );
+    assert!(eval("math::rem_euclid(7)").is_err());
+    // Approximate equality
+    assert_eq!(eval("approx_eq(0.1 + 0.2, 0.3)"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("approx_eq(0.1, 0.2)"), Ok(Value::Boolean(false)));
+    assert_eq!(eval("approx_eq(3, 3.0)"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("approx_eq(1e-12, 0)"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("approx_eq(1e12, 1e12 + 1)"), Ok(Value::Boolean(true)));
+    assert_eq!(
+        eval("approx_eq(1e12, 1e12 + 10000)"),
+        Ok(Value::Boolean(false))
+    );
+    assert_eq!(
+        eval("approx_eq(0.5, 0.505, 0.01)"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        eval("approx_eq(0.5, 0.52, 0.01)"),
+        Ok(Value::Boolean(false))
+    );
+    assert_eq!(eval("approx_eq(200, 201, 0.01)"), Ok(Value::Boolean(true)));
+    assert_eq!(
+        eval("approx_eq(1.0 / 0.0, 1.0 / 0.0)"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        eval("approx_eq(0.0 / 0.0, 0.0 / 0.0)"),
+        Ok(Value::Boolean(false))
+    );
+    assert_eq!(
+        eval("approx_eq(1, 2, 3, 4)"),
+        Err(EvalexprError::WrongFunctionArgumentAmountChoice {
+            actual: 4,
+            expected: vec![2, 3]
+        })
+    );
+    assert_eq!(
+        eval("approx_eq(1, \"1\")"),
+        Err(EvalexprError::expected_number(Value::from("1")))
+    );
     // Other
     assert_eq!(eval("typeof(4.0, 3)"), Ok(Value::String("tuple".into())));
     assert_eq!(eval("typeof(4.0)"), Ok(Value::String("float".into())));
@@ -396,6 +488,26 @@ fn test_builtin_functions() {
     assert_eq!(eval("typeof(\"\")"), Ok(Value::String("string".into())));
     assert_eq!(eval("typeof(true)"), Ok(Value::String("boolean".into())));
     assert_eq!(eval("typeof()"), Ok(Value::String("empty".into())));
+    assert_eq!(eval("is_int(4)"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("is_int(4.0)"), Ok(Value::Boolean(false)));
+    assert_eq!(eval("is_float(4.0)"), Ok(Value::Boolean(true)));
+    assert_eq!(
+        eval("is_number(4) && is_number(4.0)"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(eval("is_number(\"4\")"), Ok(Value::Boolean(false)));
+    assert_eq!(eval("is_string(\"\")"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("is_boolean(1 < 2)"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("is_tuple(1, 2)"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("is_tuple([1, 2])"), Ok(Value::Boolean(false)));
+    assert_eq!(eval("is_array([1, 2])"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("is_map([])"), Ok(Value::Boolean(false)));
+    assert_eq!(eval("is_empty()"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("is_empty(0)"), Ok(Value::Boolean(false)));
+    assert_eq!(
+        eval("x = \"3\"; if(is_int(x), x, int(x)) + 1"),
+        Ok(Value::Int(4))
+    );
     assert_eq!(eval("min(4.0, 3)"), Ok(Value::Int(3)));
     assert_eq!(eval("max(4.0, 3)"), Ok(Value::Float(4.0)));
     assert_eq!(eval("len(\"foobar\")"), Ok(Value::Int(6)));
@@ -500,6 +612,44 @@ fn test_builtin_functions() {
         eval("str::trim(\"  foo  bar \")"),
         Ok(Value::from("foo  bar"))
     );
+    assert_eq!(eval("str::len(\"\")"), Ok(Value::Int(0)));
+    assert_eq!(eval("str::len(\"föö\")"), Ok(Value::Int(3)));
+    assert_eq!(
+        eval("str::contains(\"foobar\", \"oba\")"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        eval("str::contains(\"foobar\", \"baz\")"),
+        Ok(Value::Boolean(false))
+    );
+    assert_eq!(
+        eval("str::starts_with(\"foobar\", \"foo\")"),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        eval("str::starts_with(\"foobar\", \"bar\")"),
+        Ok(Value::Boolean(false))
+    );
+    assert_eq!(
+        eval("str::substring(\"föobar\", 1, 4)"),
+        Ok(Value::from("öob"))
+    );
+    assert_eq!(
+        eval("str::substring(\"foobar\", 6, 6)"),
+        Ok(Value::from(""))
+    );
+    assert_eq!(
+        eval("str::substring(\"foobar\", 2, 7)"),
+        Err(EvalexprError::index_out_of_bounds(7, 6))
+    );
+    assert_eq!(
+        eval("str::substring(\"foobar\", -1, 2)"),
+        Err(EvalexprError::index_out_of_bounds(-1, 6))
+    );
+    assert_eq!(
+        eval("str::substring(\"foobar\", 3, 2)"),
+        Err(EvalexprError::invalid_range(3, 2))
+    );
     assert_eq!(
         eval("str::from(\"a\")"),
         Ok(Value::String(String::from("\"a\"")))
@@ -564,33 +714,54 @@ fn test_errors() {
 
 #[test]
 fn test_no_panic() {
-    assert!(eval(&format!(
-        "{} + {}",
-        IntType::max_value(),
-        IntType::max_value()
-    ))
-    .is_err());
-    assert!(eval(&format!(
-        "-{} - {}",
-        IntType::max_value(),
-        IntType::max_value()
-    ))
-    .is_err());
-    assert!(eval(&format!("-(-{} - 1)", IntType::max_value())).is_err());
-    assert!(eval(&format!(
-        "{} * {}",
-        IntType::max_value(),
-        IntType::max_value()
-    ))
-    .is_err());
-    assert!(eval(&format!("{} / {}", IntType::max_value(), 0)).is_err());
-    assert!(eval(&format!("{} % {}", IntType::max_value(), 0)).is_err());
-    assert!(eval(&format!(
-        "{} ^ {}",
-        IntType::max_value(),
-        IntType::max_value()
-    ))
-    .is_ok());
+    // With the `bigint` feature, overflowing integers become big integers instead.
+    #[cfg(not(feature = "bigint"))]
+    {
+        assert!(eval(&format!("{} + {}", IntType::MAX, IntType::MAX)).is_err());
+        assert!(eval(&format!("-{} - {}", IntType::MAX, IntType::MAX)).is_err());
+        assert!(eval(&format!("-(-{} - 1)", IntType::MAX)).is_err());
+        assert!(eval(&format!("{} * {}", IntType::MAX, IntType::MAX)).is_err());
+        assert!(eval(&format!("math::abs(-{} - 1)", IntType::MAX)).is_err());
+        assert_eq!(
+            eval(&format!("{} + 1", IntType::MAX)),
+            Err(EvalexprError::AdditionError {
+                augend: Value::from(IntType::MAX),
+                addend: Value::from(1)
+            })
+        );
+        assert_eq!(
+            eval(&format!("(-{} - 1) / -1", IntType::MAX)),
+            Err(EvalexprError::DivisionError {
+                dividend: Value::from(IntType::MIN),
+                divisor: Value::from(-1)
+            })
+        );
+        assert_eq!(
+            eval(&format!("(-{} - 1) % -1", IntType::MAX)),
+            Err(EvalexprError::ModulationError {
+                dividend: Value::from(IntType::MIN),
+                divisor: Value::from(-1)
+            })
+        );
+    }
+    assert_eq!(
+        eval("shl(1, 64)"),
+        Err(EvalexprError::ShiftError {
+            value: Value::from(1),
+            shift: Value::from(64)
+        })
+    );
+    assert_eq!(
+        eval("shr(1, -1)"),
+        Err(EvalexprError::ShiftError {
+            value: Value::from(1),
+            shift: Value::from(-1)
+        })
+    );
+    assert_eq!(eval("shl(1, 63)"), Ok(Value::from(IntType::MIN)));
+    assert!(eval(&format!("{} / {}", IntType::MAX, 0)).is_err());
+    assert!(eval(&format!("{} % {}", IntType::MAX, 0)).is_err());
+    assert!(eval(&format!("{} ^ {}", IntType::MAX, IntType::MAX)).is_ok());
     assert!(eval("if").is_err());
     assert!(eval("if()").is_err());
     assert!(eval("if(true, 1)").is_err());
@@ -614,8 +785,8 @@ fn test_shortcut_functions() {
         })
     );
     assert_eq!(
-        eval_string("3..3"),
-        Err(EvalexprError::VariableIdentifierNotFound("3..3".to_owned()))
+        eval_string("3.3.3"),
+        Err(variable_not_found_at("3.3.3", Span::new(0, 5)))
     );
     assert_eq!(
         eval_string_with_context("string", &context),
@@ -628,8 +799,8 @@ fn test_shortcut_functions() {
         })
     );
     assert_eq!(
-        eval_string_with_context("3..3", &context),
-        Err(EvalexprError::VariableIdentifierNotFound("3..3".to_owned()))
+        eval_string_with_context("3.3.3", &context),
+        Err(variable_not_found_at("3.3.3", Span::new(0, 5)))
     );
     assert_eq!(
         eval_string_with_context_mut("string", &mut context),
@@ -642,8 +813,8 @@ fn test_shortcut_functions() {
         })
     );
     assert_eq!(
-        eval_string_with_context_mut("3..3", &mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("3..3".to_owned()))
+        eval_string_with_context_mut("3.3.3", &mut context),
+        Err(variable_not_found_at("3.3.3", Span::new(0, 5)))
     );
 
     assert_eq!(eval_float("3.3"), Ok(3.3));
@@ -655,7 +826,9 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_float("asd()"),
-        Err(EvalexprError::FunctionIdentifierNotFound("asd".to_owned()))
+        Err(EvalexprError::function_identifier_not_found(
+            "asd".to_owned()
+        ))
     );
     assert_eq!(eval_float_with_context("3.3", &context), Ok(3.3));
     assert_eq!(
@@ -666,7 +839,9 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_float_with_context("asd)", &context),
-        Err(EvalexprError::UnmatchedRBrace)
+        Err(EvalexprError::UnmatchedRBrace {
+            span: Span::new(3, 4)
+        })
     );
     assert_eq!(eval_float_with_context_mut("3.3", &mut context), Ok(3.3));
     assert_eq!(
@@ -677,7 +852,9 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_float_with_context_mut("asd(", &mut context),
-        Err(EvalexprError::UnmatchedLBrace)
+        Err(EvalexprError::UnmatchedLBrace {
+            span: Span::new(3, 4)
+        })
     );
 
     assert_eq!(eval_int("3"), Ok(3));
@@ -689,7 +866,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_int("(,);."),
-        Err(EvalexprError::VariableIdentifierNotFound(".".to_owned()))
+        Err(variable_not_found_at(".", Span::new(4, 5)))
     );
     assert_eq!(eval_int_with_context("3", &context), Ok(3));
     assert_eq!(
@@ -700,7 +877,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_int_with_context("(,);.", &context),
-        Err(EvalexprError::VariableIdentifierNotFound(".".to_owned()))
+        Err(variable_not_found_at(".", Span::new(4, 5)))
     );
     assert_eq!(eval_int_with_context_mut("3", &mut context), Ok(3));
     assert_eq!(
@@ -711,7 +888,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_int_with_context_mut("(,);.", &mut context),
-        Err(EvalexprError::VariableIdentifierNotFound(".".to_owned()))
+        Err(variable_not_found_at(".", Span::new(4, 5)))
     );
 
     assert_eq!(eval_number("3"), Ok(3.0));
@@ -723,7 +900,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_number("abc"),
-        Err(EvalexprError::VariableIdentifierNotFound("abc".to_owned()))
+        Err(variable_not_found_at("abc", Span::new(0, 3)))
     );
     assert_eq!(eval_number_with_context("3.5", &context), Ok(3.5));
     assert_eq!(eval_number_with_context("3", &context), Ok(3.0));
@@ -735,7 +912,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_number_with_context("abc", &context),
-        Err(EvalexprError::VariableIdentifierNotFound("abc".to_owned()))
+        Err(variable_not_found_at("abc", Span::new(0, 3)))
     );
     assert_eq!(eval_number_with_context_mut("3.5", &mut context), Ok(3.5));
     assert_eq!(eval_number_with_context_mut("3", &mut context), Ok(3.0));
@@ -747,7 +924,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_number_with_context_mut("abc", &mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("abc".to_owned()))
+        Err(variable_not_found_at("abc", Span::new(0, 3)))
     );
 
     assert_eq!(eval_boolean("true"), Ok(true));
@@ -759,9 +936,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_boolean("trueee"),
-        Err(EvalexprError::VariableIdentifierNotFound(
-            "trueee".to_owned()
-        ))
+        Err(variable_not_found_at("trueee", Span::new(0, 6)))
     );
     assert_eq!(eval_boolean_with_context("true", &context), Ok(true));
     assert_eq!(
@@ -772,9 +947,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_boolean_with_context("trueee", &context),
-        Err(EvalexprError::VariableIdentifierNotFound(
-            "trueee".to_owned()
-        ))
+        Err(variable_not_found_at("trueee", Span::new(0, 6)))
     );
     assert_eq!(
         eval_boolean_with_context_mut("true", &mut context),
@@ -788,9 +961,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_boolean_with_context_mut("trueee", &mut context),
-        Err(EvalexprError::VariableIdentifierNotFound(
-            "trueee".to_owned()
-        ))
+        Err(variable_not_found_at("trueee", Span::new(0, 6)))
     );
 
     assert_eq!(eval_tuple("3,3"), Ok(vec![Value::Int(3), Value::Int(3)]));
@@ -802,7 +973,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_tuple("3a3"),
-        Err(EvalexprError::VariableIdentifierNotFound("3a3".to_owned()))
+        Err(variable_not_found_at("3a3", Span::new(0, 3)))
     );
     assert_eq!(
         eval_tuple_with_context("3,3", &context),
@@ -816,7 +987,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_tuple_with_context("3a3", &context),
-        Err(EvalexprError::VariableIdentifierNotFound("3a3".to_owned()))
+        Err(variable_not_found_at("3a3", Span::new(0, 3)))
     );
     assert_eq!(
         eval_tuple_with_context_mut("3,3", &mut context),
@@ -830,7 +1001,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_tuple_with_context_mut("3a3", &mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("3a3".to_owned()))
+        Err(variable_not_found_at("3a3", Span::new(0, 3)))
     );
 
     assert_eq!(eval_empty(""), Ok(EMPTY_VALUE));
@@ -843,7 +1014,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_empty("xaq"),
-        Err(EvalexprError::VariableIdentifierNotFound("xaq".to_owned()))
+        Err(variable_not_found_at("xaq", Span::new(0, 3)))
     );
     assert_eq!(eval_empty_with_context("", &context), Ok(EMPTY_VALUE));
     assert_eq!(eval_empty_with_context("()", &context), Ok(EMPTY_VALUE));
@@ -855,7 +1026,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_empty_with_context("xaq", &context),
-        Err(EvalexprError::VariableIdentifierNotFound("xaq".to_owned()))
+        Err(variable_not_found_at("xaq", Span::new(0, 3)))
     );
     assert_eq!(
         eval_empty_with_context_mut("", &mut context),
@@ -873,7 +1044,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         eval_empty_with_context_mut("xaq", &mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("xaq".to_owned()))
+        Err(variable_not_found_at("xaq", Span::new(0, 3)))
     );
 
     // With detour via build_operator_tree
@@ -889,8 +1060,8 @@ fn test_shortcut_functions() {
         })
     );
     assert_eq!(
-        build_operator_tree("3..3").unwrap().eval_string(),
-        Err(EvalexprError::VariableIdentifierNotFound("3..3".to_owned()))
+        build_operator_tree("3.3.3").unwrap().eval_string(),
+        Err(variable_not_found_at("3.3.3", Span::new(0, 5)))
     );
     assert_eq!(
         build_operator_tree("string")
@@ -907,10 +1078,10 @@ fn test_shortcut_functions() {
         })
     );
     assert_eq!(
-        build_operator_tree("3..3")
+        build_operator_tree("3.3.3")
             .unwrap()
             .eval_string_with_context(&context),
-        Err(EvalexprError::VariableIdentifierNotFound("3..3".to_owned()))
+        Err(variable_not_found_at("3.3.3", Span::new(0, 5)))
     );
     assert_eq!(
         build_operator_tree("string")
@@ -927,10 +1098,10 @@ fn test_shortcut_functions() {
         })
     );
     assert_eq!(
-        build_operator_tree("3..3")
+        build_operator_tree("3.3.3")
             .unwrap()
             .eval_string_with_context_mut(&mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("3..3".to_owned()))
+        Err(variable_not_found_at("3.3.3", Span::new(0, 5)))
     );
 
     assert_eq!(build_operator_tree("3.3").unwrap().eval_float(), Ok(3.3));
@@ -942,7 +1113,9 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         build_operator_tree("asd()").unwrap().eval_float(),
-        Err(EvalexprError::FunctionIdentifierNotFound("asd".to_owned()))
+        Err(EvalexprError::function_identifier_not_found(
+            "asd".to_owned()
+        ))
     );
     assert_eq!(
         build_operator_tree("3.3")
@@ -962,7 +1135,7 @@ fn test_shortcut_functions() {
         build_operator_tree("asd")
             .unwrap()
             .eval_float_with_context(&context),
-        Err(EvalexprError::VariableIdentifierNotFound("asd".to_owned()))
+        Err(variable_not_found_at("asd", Span::new(0, 3)))
     );
     assert_eq!(
         build_operator_tree("3.3")
@@ -982,7 +1155,7 @@ fn test_shortcut_functions() {
         build_operator_tree("asd")
             .unwrap()
             .eval_float_with_context_mut(&mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("asd".to_owned()))
+        Err(variable_not_found_at("asd", Span::new(0, 3)))
     );
 
     assert_eq!(build_operator_tree("3").unwrap().eval_int(), Ok(3));
@@ -994,7 +1167,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         build_operator_tree("(,);.").unwrap().eval_int(),
-        Err(EvalexprError::VariableIdentifierNotFound(".".to_owned()))
+        Err(variable_not_found_at(".", Span::new(4, 5)))
     );
     assert_eq!(
         build_operator_tree("3")
@@ -1014,7 +1187,7 @@ fn test_shortcut_functions() {
         build_operator_tree("(,);.")
             .unwrap()
             .eval_int_with_context(&context),
-        Err(EvalexprError::VariableIdentifierNotFound(".".to_owned()))
+        Err(variable_not_found_at(".", Span::new(4, 5)))
     );
     assert_eq!(
         build_operator_tree("3")
@@ -1034,7 +1207,7 @@ fn test_shortcut_functions() {
         build_operator_tree("(,);.")
             .unwrap()
             .eval_int_with_context_mut(&mut context),
-        Err(EvalexprError::VariableIdentifierNotFound(".".to_owned()))
+        Err(variable_not_found_at(".", Span::new(4, 5)))
     );
 
     assert_eq!(build_operator_tree("3").unwrap().eval_number(), Ok(3.0));
@@ -1046,7 +1219,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         build_operator_tree("abc").unwrap().eval_number(),
-        Err(EvalexprError::VariableIdentifierNotFound("abc".to_owned()))
+        Err(variable_not_found_at("abc", Span::new(0, 3)))
     );
     assert_eq!(
         build_operator_tree("3")
@@ -1066,7 +1239,7 @@ fn test_shortcut_functions() {
         build_operator_tree("abc")
             .unwrap()
             .eval_number_with_context(&context),
-        Err(EvalexprError::VariableIdentifierNotFound("abc".to_owned()))
+        Err(variable_not_found_at("abc", Span::new(0, 3)))
     );
     assert_eq!(
         build_operator_tree("3")
@@ -1086,7 +1259,7 @@ fn test_shortcut_functions() {
         build_operator_tree("abc")
             .unwrap()
             .eval_number_with_context_mut(&mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("abc".to_owned()))
+        Err(variable_not_found_at("abc", Span::new(0, 3)))
     );
 
     assert_eq!(
@@ -1101,9 +1274,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         build_operator_tree("trueee").unwrap().eval_boolean(),
-        Err(EvalexprError::VariableIdentifierNotFound(
-            "trueee".to_owned()
-        ))
+        Err(variable_not_found_at("trueee", Span::new(0, 6)))
     );
     assert_eq!(
         build_operator_tree("true")
@@ -1123,9 +1294,7 @@ fn test_shortcut_functions() {
         build_operator_tree("trueee")
             .unwrap()
             .eval_boolean_with_context(&context),
-        Err(EvalexprError::VariableIdentifierNotFound(
-            "trueee".to_owned()
-        ))
+        Err(variable_not_found_at("trueee", Span::new(0, 6)))
     );
     assert_eq!(
         build_operator_tree("true")
@@ -1145,9 +1314,7 @@ fn test_shortcut_functions() {
         build_operator_tree("trueee")
             .unwrap()
             .eval_boolean_with_context_mut(&mut context),
-        Err(EvalexprError::VariableIdentifierNotFound(
-            "trueee".to_owned()
-        ))
+        Err(variable_not_found_at("trueee", Span::new(0, 6)))
     );
 
     assert_eq!(
@@ -1162,7 +1329,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         build_operator_tree("3a3").unwrap().eval_tuple(),
-        Err(EvalexprError::VariableIdentifierNotFound("3a3".to_owned()))
+        Err(variable_not_found_at("3a3", Span::new(0, 3)))
     );
     assert_eq!(
         build_operator_tree("3,3")
@@ -1182,7 +1349,7 @@ fn test_shortcut_functions() {
         build_operator_tree("3a3")
             .unwrap()
             .eval_tuple_with_context(&context),
-        Err(EvalexprError::VariableIdentifierNotFound("3a3".to_owned()))
+        Err(variable_not_found_at("3a3", Span::new(0, 3)))
     );
     assert_eq!(
         build_operator_tree("3,3")
@@ -1202,7 +1369,7 @@ fn test_shortcut_functions() {
         build_operator_tree("3a3")
             .unwrap()
             .eval_tuple_with_context_mut(&mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("3a3".to_owned()))
+        Err(variable_not_found_at("3a3", Span::new(0, 3)))
     );
 
     assert_eq!(
@@ -1221,7 +1388,7 @@ fn test_shortcut_functions() {
     );
     assert_eq!(
         build_operator_tree("xaq").unwrap().eval_empty(),
-        Err(EvalexprError::VariableIdentifierNotFound("xaq".to_owned()))
+        Err(variable_not_found_at("xaq", Span::new(0, 3)))
     );
     assert_eq!(
         build_operator_tree("")
@@ -1247,7 +1414,7 @@ fn test_shortcut_functions() {
         build_operator_tree("xaq")
             .unwrap()
             .eval_empty_with_context(&context),
-        Err(EvalexprError::VariableIdentifierNotFound("xaq".to_owned()))
+        Err(variable_not_found_at("xaq", Span::new(0, 3)))
     );
     assert_eq!(
         build_operator_tree("")
@@ -1273,7 +1440,7 @@ fn test_shortcut_functions() {
         build_operator_tree("xaq")
             .unwrap()
             .eval_empty_with_context_mut(&mut context),
-        Err(EvalexprError::VariableIdentifierNotFound("xaq".to_owned()))
+        Err(variable_not_found_at("xaq", Span::new(0, 3)))
     );
 }
 
@@ -1320,6 +1487,22 @@ fn test_assignment() {
         Ok(EMPTY_VALUE)
     );
     assert_eq!(eval_empty_with_context("b", &context), Ok(EMPTY_VALUE));
+
+    assert_eq!(
+        eval_int_with_context_mut("c = 5; c * 2", &mut context),
+        Ok(10)
+    );
+    assert_eq!(context.get_value("c"), Some(&Value::from(5)));
+    assert_eq!(
+        eval_with_context("c = 6", &context),
+        Err(EvalexprError::ContextNotMutable)
+    );
+    assert_eq!(
+        build_operator_tree("c = c + 1; c")
+            .unwrap()
+            .eval_int_with_context_mut(&mut context),
+        Ok(6)
+    );
 }
 
 #[test]
@@ -1329,6 +1512,14 @@ fn test_expression_chaining() {
         eval_int_with_context_mut("a = 5; a = a + 2; a", &mut context),
         Ok(7)
     );
+    assert_eq!(eval("1; 2; 3"), Ok(Value::from(3)));
+    assert_eq!(eval("1; 2; 3;"), Ok(Value::Empty));
+    assert_eq!(eval("1; 2; 3;;"), Ok(Value::Empty));
+    assert_eq!(eval("(1; 2) + 3"), Ok(Value::from(5)));
+
+    let chain = build_operator_tree("a = a * 2; b = a + 1; b").unwrap();
+    assert_eq!(chain.eval_int_with_context_mut(&mut context), Ok(15));
+    assert_eq!(chain.eval_int_with_context_mut(&mut context), Ok(29));
 }
 
 #[test]
@@ -1362,6 +1553,40 @@ fn test_string_escaping() {
         eval("\"\\\"str\\\\ing\\\"\""),
         Ok(Value::from("\"str\\ing\""))
     );
+    assert_eq!(eval(r#""a\nb\tc\r\0""#), Ok(Value::from("a\nb\tc\r\0")));
+    assert_eq!(
+        eval(r#""\u{41}\u{1F600}\u{00e9}""#),
+        Ok(Value::from("A😀é"))
+    );
+    assert_eq!(eval(r#""it's" + "\'""#), Ok(Value::from("it's'")));
+
+    // Single-quoted strings
+    assert_eq!(eval("'string'"), Ok(Value::from("string")));
+    assert_eq!(eval(r#"'say "hi"'"#), Ok(Value::from("say \"hi\"")));
+    assert_eq!(eval(r#"'it\'s'"#), Ok(Value::from("it's")));
+    assert_eq!(eval("'a' + \"b\" == \"ab\""), Ok(Value::from(true)));
+
+    for sequence in [
+        r"\x",
+        r"\u41",
+        r"\u{}",
+        r"\u{1234567}",
+        r"\u{d800}",
+        r"\u{4g}",
+    ] {
+        assert!(
+            matches!(
+                eval(&format!("\"{}\"", sequence)),
+                Err(EvalexprError::IllegalEscapeSequence(_))
+            ),
+            "{}",
+            sequence
+        );
+    }
+    assert_eq!(
+        eval(r#""\u{41""#),
+        Err(EvalexprError::IllegalEscapeSequence(r#"\u{41""#.into()))
+    );
 }
 
 #[test]
@@ -1524,11 +1749,13 @@ fn test_empty_context() {
     assert_eq!(context.get_value("abc"), None);
     assert_eq!(
         context.call_function("abc", &Value::Empty),
-        Err(EvalexprError::FunctionIdentifierNotFound("abc".to_owned()))
+        Err(EvalexprError::function_identifier_not_found(
+            "abc".to_owned()
+        ))
     );
     assert_eq!(
         eval_with_context("max(1,3)", &context),
-        Err(EvalexprError::FunctionIdentifierNotFound(String::from(
+        Err(EvalexprError::function_identifier_not_found(String::from(
             "max"
         )))
     );
@@ -1545,7 +1772,9 @@ fn test_empty_context_with_builtin_functions() {
     assert_eq!(context.get_value("abc"), None);
     assert_eq!(
         context.call_function("abc", &Value::Empty),
-        Err(EvalexprError::FunctionIdentifierNotFound("abc".to_owned()))
+        Err(EvalexprError::function_identifier_not_found(
+            "abc".to_owned()
+        ))
     );
     assert_eq!(eval_with_context("max(1,3)", &context), Ok(Value::Int(3)));
     assert_eq!(context.set_builtin_functions_disabled(false), Ok(()));
@@ -1641,6 +1870,67 @@ fn test_hashmap_context_type_safety() {
     );
 }
 
+#[test]
+fn test_chained_context() {
+    let constants = context_map! {
+        "pi" => 3,
+        "limit" => 100,
+        "double" => Function::new(|argument| Ok(Value::from(argument.as_int()? * 2))),
+        "inc" => Function::new(|argument| Ok(Value::from(argument.as_int()? + 1))),
+    }
+    .unwrap();
+    let request = context_map! {
+        "limit" => 10,
+        "inc" => Function::new(|argument| Ok(Value::from(argument.as_int()? + 10))),
+    }
+    .unwrap();
+    let mut context = ChainedContext::new()
+        .with_layer(&request)
+        .with_layer(&constants);
+
+    assert_eq!(
+        eval_with_context("limit + pi", &context),
+        Ok(Value::from(13))
+    );
+    assert_eq!(
+        eval_with_context("double(inc(1))", &context),
+        Ok(Value::from(22))
+    );
+    assert_eq!(
+        eval_with_context("missing", &context),
+        Err(variable_not_found_at("missing", Span::new(0, 7)))
+    );
+    assert_eq!(
+        eval_with_context("missing()", &context),
+        Err(EvalexprError::function_identifier_not_found(
+            "missing".into()
+        ))
+    );
+    assert!(context.has_function("double"));
+    assert!(!context.has_function("len"));
+
+    // Builtin functions are handled by the chained context itself.
+    assert_eq!(
+        eval_with_context("len(\"abc\")", &context),
+        Ok(Value::from(3))
+    );
+    context.set_builtin_functions_disabled(true).unwrap();
+    assert_eq!(
+        eval_with_context("len(\"abc\")", &context),
+        Err(EvalexprError::function_identifier_not_found("len".into()))
+    );
+
+    // Layers that do not know an identifier are skipped.
+    let context = ChainedContext::new()
+        .with_layer(&EmptyContext)
+        .with_layer(&constants);
+    assert_eq!(eval_with_context("limit", &context), Ok(Value::from(100)));
+    assert_eq!(
+        eval_with_context("limit = 1", &ChainedContext::new()),
+        Err(EvalexprError::ContextNotMutable)
+    );
+}
+
 #[test]
 fn test_hashmap_context_clone_debug() {
     let mut context = HashMapContext::new();
@@ -1729,7 +2019,8 @@ fn test_error_constructors() {
         eval("&"),
         Err(EvalexprError::UnmatchedPartialToken {
             first: PartialToken::Ampersand,
-            second: None
+            second: None,
+            span: Span::new(0, 1)
         })
     );
 
@@ -1768,6 +2059,58 @@ fn test_iterators() {
     let mut iter = tree.iter_function_identifiers();
     assert_eq!(iter.next(), Some("fun"));
     assert_eq!(iter.next(), None);
+
+    // Discover the bindings a script needs before evaluating it.
+    let tree = build_operator_tree("x = f(a, g(b)); y = x * a; h y").unwrap();
+    let mut variables: Vec<_> = tree.iter_read_variable_identifiers().collect();
+    variables.sort_unstable();
+    variables.dedup();
+    assert_eq!(variables, ["a", "b", "x", "y"]);
+    let functions: Vec<_> = tree.iter_function_identifiers().collect();
+    assert_eq!(functions, ["f", "g", "h"]);
+    let writes: Vec<_> = tree.iter_write_variable_identifiers().collect();
+    assert_eq!(writes, ["x", "y"]);
+
+    // Operators can be inspected as well.
+    let constants: Vec<_> = build_operator_tree("1 + a * 2.5")
+        .unwrap()
+        .iter()
+        .filter_map(|node| match node.operator() {
+            Operator::Const { value } => Some(value.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(constants, [Value::from(1), Value::from(2.5)]);
+}
+
+#[test]
+fn test_identifier_interning() {
+    let mut tree = build_operator_tree("x = x + 1; f(x, y) * f(y)").unwrap();
+    let identifiers = |tree: &Node, name: &str| -> Vec<std::sync::Arc<str>> {
+        tree.iter()
+            .filter_map(|node| match node.operator() {
+                Operator::VariableIdentifierWrite { identifier }
+                | Operator::VariableIdentifierRead { identifier }
+                | Operator::FunctionIdentifier { identifier } => Some(identifier.clone()),
+                _ => None,
+            })
+            .filter(|identifier| &**identifier == name)
+            .collect()
+    };
+
+    // All occurrences of an identifier share one allocation, regardless of their role.
+    for name in ["x", "y", "f"] {
+        let occurrences = identifiers(&tree, name);
+        assert!(occurrences.len() >= 2);
+        assert!(occurrences
+            .windows(2)
+            .all(|pair| std::sync::Arc::ptr_eq(&pair[0], &pair[1])));
+    }
+
+    // Renaming an occurrence does not affect the others.
+    *tree.iter_read_variable_identifiers_mut().next().unwrap() = "z".into();
+    assert_eq!(tree.to_string(), "x = z + 1; f(x, y) * f(y)");
+    assert_eq!(identifiers(&tree, "x").len(), 2);
 }
 
 #[test]
@@ -2241,7 +2584,7 @@ fn assignment_lhs_is_identifier() {
                 Operator::Const {
                     value: Value::Int(1)
                 }
-            ] if value == "a"
+            ] if &**value == "a"
         ),
         "actual: {:#?}",
         operators
@@ -2263,6 +2606,171 @@ fn test_variable_assignment_and_iteration() {
     let mut variables: Vec<_> = context.iter_variable_names().collect();
     variables.sort_unstable();
     assert_eq!(variables, vec!["a".to_string(), "b".to_string()],);
+
+    assert_eq!(context.remove_value("a"), Ok(Some(5.into())));
+    assert_eq!(context.remove_value("a"), Ok(None));
+    assert_eq!(
+        context.iter_variable_names().collect::<Vec<_>>(),
+        vec!["b".to_string()]
+    );
+    // Removed variables can be assigned values of another type.
+    eval_with_context_mut("a = true", &mut context).unwrap();
+    assert_eq!(context.get_value("a"), Some(&true.into()));
+
+    context
+        .set_function("f".into(), Function::new(|_| Ok(Value::Empty)))
+        .unwrap();
+    context.set_builtin_functions_disabled(true).unwrap();
+    context.clear_variables();
+    assert_eq!(context.iter_variables().count(), 0);
+    assert!(context.has_function("f"));
+    context.clear();
+    assert!(!context.has_function("f"));
+    assert!(context.are_builtin_functions_disabled());
+
+    let mut shared = SyncHashMapContext::from(context_map! { "x" => 1, "y" => 2 }.unwrap());
+    let clone = shared.clone();
+    assert_eq!(shared.remove_value("x"), Ok(Some(1.into())));
+    assert_eq!(shared.remove_value("z"), Ok(None));
+    assert_eq!(clone.get_value("x"), Some(&1.into()));
+    shared.clear();
+    assert_eq!(shared.get_value("y"), None);
+    assert_eq!(clone.get_value("y"), Some(&2.into()));
+}
+
+#[test]
+fn test_context_snapshot_and_rollback() {
+    let mut context = context_map! { "a" => 1, "b" => "text" }.unwrap();
+    context
+        .set_function("f".into(), Function::new(|_| Ok(Value::from(2))))
+        .unwrap();
+    let snapshot = context.snapshot();
+
+    assert!(eval_with_context_mut("a += 1; c = 3; b = b + \"!\"; a / 0", &mut context).is_err());
+    context.remove_value("b").unwrap();
+    assert_eq!(context.get_value("a"), Some(&Value::from(2)));
+    assert_eq!(context.get_value("c"), Some(&Value::from(3)));
+    assert_eq!(context.get_value("b"), None);
+
+    context.rollback(snapshot.clone());
+    assert_eq!(context.get_value("a"), Some(&Value::from(1)));
+    assert_eq!(context.get_value("b"), Some(&Value::from("text")));
+    assert_eq!(context.get_value("c"), None);
+    // Functions are not part of the snapshot.
+    assert!(context.has_function("f"));
+
+    // Snapshots can be nested and restored multiple times.
+    eval_with_context_mut("a = 10", &mut context).unwrap();
+    let inner = context.snapshot();
+    eval_with_context_mut("a = 20", &mut context).unwrap();
+    context.rollback(inner);
+    assert_eq!(context.get_value("a"), Some(&Value::from(10)));
+    context.rollback(snapshot.clone());
+    assert_eq!(context.get_value("a"), Some(&Value::from(1)));
+    eval_with_context_mut("a = 30", &mut context).unwrap();
+    context.rollback(snapshot);
+    assert_eq!(context.get_value("a"), Some(&Value::from(1)));
+}
+
+#[test]
+fn test_exponentiation_associativity() {
+    assert_eq!(eval("2 ^ 3 ^ 2"), Ok(Value::from(512.0)));
+    assert_eq!(eval("(2 ^ 3) ^ 2"), Ok(Value::from(64.0)));
+    assert_eq!(eval("2 ^ 1 ^ 3 ^ 2"), Ok(Value::from(2.0)));
+    assert_eq!(eval("2 * 2 ^ 3 ^ 2 * 2"), Ok(Value::from(2048.0)));
+    assert_eq!(eval("-2 ^ 2 ^ 3"), Ok(Value::from(-256.0)));
+    assert_eq!(eval("2 ^ -1 ^ 2"), Ok(Value::from(0.5)));
+    assert_eq!(
+        build_operator_tree("2 ^ (3 ^ 2)").unwrap().to_string(),
+        "2 ^ 3 ^ 2"
+    );
+    // Other operators are still left-associative.
+    assert_eq!(eval("8 - 4 - 2"), Ok(Value::from(2)));
+    assert_eq!(eval("64 / 4 / 2"), Ok(Value::from(8)));
+
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_with_context_mut("a = 2.0; a ^= 3 ^ 2; a", &mut context),
+        Ok(Value::from(512.0))
+    );
+
+    let config = ParserConfig::new().with_left_associative_exponentiation(true);
+    assert!(!ParserConfig::new().left_associative_exponentiation());
+    assert!(config.left_associative_exponentiation());
+    let tree = build_operator_tree_with_config("2 ^ 3 ^ 2 * 2", &config).unwrap();
+    assert_eq!(tree.eval(), Ok(Value::from(128.0)));
+    assert_eq!(tree.to_string(), "(2 ^ 3) ^ 2 * 2");
+    assert_eq!(
+        build_operator_tree_with_config("2 ^ (3 ^ 2)", &config)
+            .unwrap()
+            .eval(),
+        Ok(Value::from(512.0))
+    );
+}
+
+#[test]
+fn test_unary_operators() {
+    let context = context_map! { "x" => 3, "a" => true, "f" => 0.5 }.unwrap();
+    assert_eq!(eval("+5"), Ok(Value::from(5)));
+    assert_eq!(eval("+-5"), Ok(Value::from(-5)));
+    assert_eq!(eval("-+5"), Ok(Value::from(-5)));
+    assert_eq!(eval("2 * +3 - +1"), Ok(Value::from(5)));
+    assert_eq!(eval("2++3"), Ok(Value::from(5)));
+    assert_eq!(eval("(+2, +(1 + 1))"), eval("(2, 2)"));
+    assert_eq!(eval_with_context("+f", &context), Ok(Value::from(0.5)));
+    assert_eq!(eval_with_context("- -x", &context), Ok(Value::from(3)));
+    assert_eq!(eval_with_context("-(-(x))", &context), Ok(Value::from(3)));
+    assert_eq!(
+        eval_with_context("!(!(a))", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("! ! !a", &context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(eval_with_context("+-+-x", &context), Ok(Value::from(3)));
+    // Unary operators bind weaker than exponentiation.
+    assert_eq!(
+        eval_with_context("- -x ^ 2", &context),
+        Ok(Value::from(9.0))
+    );
+    assert_eq!(eval_with_context("+x ^ 2", &context), Ok(Value::from(9.0)));
+
+    // Deeply stacked unary operators.
+    let expression = format!("{}x", "- ".repeat(100));
+    assert_eq!(eval_with_context(&expression, &context), Ok(Value::from(3)));
+    let expression = format!("{}x", "-+".repeat(51));
+    assert_eq!(
+        eval_with_context(&expression, &context),
+        Ok(Value::from(-3))
+    );
+    let expression = format!("{}a", "!".repeat(101));
+    assert_eq!(
+        eval_with_context(&expression, &context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(
+        build_operator_tree(&format!("{}x", "-".repeat(1000))),
+        Err(EvalexprError::RecursionLimitExceeded {
+            max_depth: ParserConfig::DEFAULT_MAX_DEPTH
+        })
+    );
+
+    assert_eq!(
+        eval("+\"text\""),
+        Err(EvalexprError::expected_number(Value::from("text")))
+    );
+    assert_eq!(
+        eval_with_context("+a", &context),
+        Err(EvalexprError::expected_number(Value::from(true)))
+    );
+    assert_eq!(
+        eval("+"),
+        Err(EvalexprError::WrongOperatorArgumentAmount {
+            expected: 1,
+            actual: 0
+        })
+    );
 }
 
 #[test]
@@ -2286,12 +2794,39 @@ fn test_builtin_functions_context() {
     // Builtin functions are disabled and using them returns an error.
     assert_eq!(
         eval_with_context("max(1,3)", &context),
-        Err(EvalexprError::FunctionIdentifierNotFound(String::from(
+        Err(EvalexprError::function_identifier_not_found(String::from(
             "max"
         )))
     );
 }
 
+#[test]
+fn test_shadowed_builtin_functions() {
+    let mut context = context_map! {
+        "max" => Function::new(|argument| {
+            let arguments = argument.as_fixed_len_tuple(2)?;
+            Ok(Value::from(arguments[0].as_int()? * arguments[1].as_int()?))
+        }),
+        "str::len" => Function::new(|_| Ok(Value::from(0))),
+    }
+    .unwrap();
+    // Functions in the context are preferred over builtin functions with the same identifier.
+    assert_eq!(eval_int_with_context("max(2, 3)", &context), Ok(6));
+    assert_eq!(eval_int_with_context("str::len \"abc\"", &context), Ok(0));
+    // Builtin functions that are not shadowed are still available.
+    assert_eq!(eval_int_with_context("min(2, 3)", &context), Ok(2));
+
+    // Disabling builtin functions does not affect functions in the context.
+    context.set_builtin_functions_disabled(true).unwrap();
+    assert_eq!(eval_int_with_context("max(2, 3)", &context), Ok(6));
+    assert_eq!(
+        eval_int_with_context("min(2, 3)", &context),
+        Err(EvalexprError::function_identifier_not_found(
+            "min".to_string()
+        ))
+    );
+}
+
 #[test]
 fn test_hex() {
     assert_eq!(eval("0x3"), Ok(Value::Int(3)));
@@ -2301,6 +2836,4548 @@ fn test_hex() {
         eval("0x"),
         // The "VariableIdentifierNotFound" error is what evalexpr currently returns,
         // but ideally it would return more specific errors for "illegal" literals.
-        Err(EvalexprError::VariableIdentifierNotFound("0x".into()))
+        Err(variable_not_found_at("0x", Span::new(0, 2)))
+    );
+}
+
+#[test]
+fn test_int_literals() {
+    assert_eq!(eval("0o17"), Ok(Value::Int(15)));
+    assert_eq!(eval("0b1010"), Ok(Value::Int(10)));
+    assert_eq!(eval("-0b1"), Ok(Value::Int(-1)));
+    assert_eq!(eval("1_000_000"), Ok(Value::Int(1_000_000)));
+    assert_eq!(eval("0xFF_FF + 0b_1"), Ok(Value::Int(65536)));
+    assert_eq!(eval("0x7fff_ffff_ffff_ffff"), Ok(Value::Int(IntType::MAX)));
+    assert_eq!(eval("1e3"), Ok(Value::Float(1000.0)));
+
+    // Literals that are not valid in their radix are identifiers.
+    assert_eq!(
+        eval("0b102"),
+        Err(variable_not_found_at("0b102", Span::new(0, 5)))
+    );
+    assert_eq!(
+        eval("_1"),
+        Err(variable_not_found_at("_1", Span::new(0, 2)))
+    );
+    assert_eq!(
+        eval_with_context("_1", &context_map! {"_1" => 2}.unwrap()),
+        Ok(Value::Int(2))
+    );
+
+    // Integer literals that do not fit into an integer are errors, unless the `bigint` feature is enabled.
+    #[cfg(not(feature = "bigint"))]
+    {
+        assert_eq!(
+            eval("1 + 0x8000_0000_0000_0000"),
+            Err(EvalexprError::IntLiteralOutOfRange {
+                literal: "0x8000_0000_0000_0000".into(),
+                span: Span::new(4, 25)
+            })
+        );
+        assert_eq!(
+            eval("99999999999999999999"),
+            Err(EvalexprError::IntLiteralOutOfRange {
+                literal: "99999999999999999999".into(),
+                span: Span::new(0, 20)
+            })
+        );
+    }
+}
+
+#[test]
+fn test_lazy_if() {
+    assert_eq!(eval("if(true, 1, 1 / 0)"), Ok(Value::Int(1)));
+    assert_eq!(eval("if(false, 1 / 0, 2)"), Ok(Value::Int(2)));
+    assert_eq!(
+        eval("if(true, 1 / 0, 2)"),
+        Err(EvalexprError::DivisionByZero { dividend: 1.into() })
+    );
+    assert_eq!(
+        eval("if(1, 2, 3)"),
+        Err(EvalexprError::expected_boolean(1.into()))
+    );
+
+    // Only the selected branch is evaluated, so only its assignment takes place.
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_int_with_context_mut("a = 0; if(a == 0, a = 1, a = 2); a", &mut context),
+        Ok(1)
+    );
+
+    // A function in the context shadows the builtin `if` and gets all arguments.
+    let context = context_map! {
+        "if" => Function::new(|argument| Ok(Value::from(argument.as_tuple()?.len() as IntType))),
+    }
+    .unwrap();
+    assert_eq!(eval_int_with_context("if(true, 1, 2)", &context), Ok(3));
+
+    // Custom contexts decide with `has_function` whether their own `if` shadows the builtin one.
+    struct MinimalContext {
+        custom_if: bool,
+    }
+
+    impl Context for MinimalContext {
+        fn get_value(&self, _identifier: &str) -> Option<&Value> {
+            None
+        }
+
+        fn call_function(&self, identifier: &str, _argument: &Value) -> EvalexprResult<Value> {
+            if self.has_function(identifier) {
+                Ok(Value::from("custom"))
+            } else {
+                Err(EvalexprError::function_identifier_not_found(
+                    identifier.to_string(),
+                ))
+            }
+        }
+
+        fn has_function(&self, identifier: &str) -> bool {
+            self.custom_if && identifier == "if"
+        }
+
+        fn are_builtin_functions_disabled(&self) -> bool {
+            false
+        }
+
+        fn set_builtin_functions_disabled(&mut self, _disabled: bool) -> EvalexprResult<()> {
+            Ok(())
+        }
+    }
+
+    let context = MinimalContext { custom_if: false };
+    assert_eq!(eval_int_with_context("if(true, 1, 1 / 0)", &context), Ok(1));
+    assert_eq!(
+        eval_int_with_context("if(false, missing, 2)", &context),
+        Ok(2)
+    );
+    let context = MinimalContext { custom_if: true };
+    assert_eq!(
+        eval_with_context("if(true, 1, 2)", &context),
+        Ok(Value::from("custom"))
+    );
+}
+
+#[test]
+fn test_display_round_trip() {
+    let mut context = context_map! {
+        "a" => 3,
+        "b" => 4.5,
+        "c" => false,
+        "s" => "abc",
+        "t" => Value::Tuple(vec![Value::from(1), Value::from(2)]),
+    }
+    .unwrap();
+    let expressions = [
+        "1 + 2 * 3",
+        "(1 + 2) * 3",
+        "1 - (2 - 3)",
+        "(1 - 2) - 3",
+        "2 ^ 3 ^ 2",
+        "(2 ^ 3) ^ 2",
+        "-2 ^ 2",
+        "(-2) ^ 2",
+        "2 ^ -3",
+        "+a - +b",
+        "-+a ^ (+2)",
+        "--a",
+        "!!c",
+        "!(c || true) && c",
+        "a < b == (b < a)",
+        "1 < a <= 3",
+        "(1 < a) < true",
+        "1.5e-7 * 1e300 + 0.5",
+        "min(a, b, 2) + max 1",
+        "-min(a, 1)",
+        "str::len(s) + str::len \"a\\\"b\\\\c\"",
+        "1, (), (2, 3), t",
+        "d = e = a; d += 1; d ^= 2; d",
+        "x = (a, b); x",
+        "s = s + \"def\"; s;",
+        "if(c, 1, 2); (a; b)",
+    ];
+
+    for expression in expressions.iter() {
+        let tree = build_operator_tree(expression).unwrap();
+        let displayed = tree.to_string();
+        let reparsed = build_operator_tree(&displayed).unwrap();
+        assert_eq!(
+            reparsed.eval_with_context_mut(&mut context.clone()),
+            tree.eval_with_context_mut(&mut context),
+            "{} was displayed as {}",
+            expression,
+            displayed
+        );
+        // The displayed form is canonical.
+        assert_eq!(reparsed.to_string(), displayed);
+    }
+
+    assert_eq!(
+        build_operator_tree("((1+2)) *(3)").unwrap().to_string(),
+        "(1 + 2) * 3"
+    );
+    assert_eq!(
+        build_operator_tree("f 1; g(2, 3);").unwrap().to_string(),
+        "f(1); g(2, 3);"
+    );
+
+    // Constant tuples with less than two elements have no literal and are displayed as slices.
+    let tuples = [
+        Value::Tuple(vec![]),
+        Value::Tuple(vec![Value::from(1)]),
+        Value::Tuple(vec![Value::Empty]),
+        Value::Tuple(vec![Value::Tuple(vec![Value::from(1), Value::from(2)])]),
+        Value::Tuple(vec![Value::Tuple(vec![Value::Tuple(vec![])])]),
+        Value::Tuple(vec![Value::Tuple(vec![Value::from(1)]), Value::from(2)]),
+    ];
+    for tuple in tuples {
+        let tree = Node::constant(tuple.clone());
+        let displayed = tree.to_string();
+        let reparsed = build_operator_tree(&displayed).unwrap();
+        assert_eq!(reparsed.eval(), Ok(tuple), "displayed as {}", displayed);
+    }
+    assert_eq!(
+        Node::constant(Value::Tuple(vec![Value::from(1)])).to_string(),
+        "(1, ())[0..1]"
+    );
+    assert_eq!(
+        Node::constant(Value::Tuple(vec![])).to_string(),
+        "((), ())[0..0]"
     );
 }
+
+#[test]
+fn test_to_dot() {
+    let tree = build_operator_tree("let s = \"a\\\"b\"; f = |x| [x, -x]").unwrap();
+    assert_eq!(
+        tree.to_dot(),
+        r#"digraph {
+    n0 [label="()"];
+    n0 -> n1;
+    n1 [label=";"];
+    n1 -> n2;
+    n1 -> n3;
+    n2 [label="()"];
+    n2 -> n4;
+    n4 [label="let"];
+    n4 -> n5;
+    n4 -> n6;
+    n5 [label="s"];
+    n6 [label="\"a\"b\""];
+    n3 [label="()"];
+    n3 -> n7;
+    n7 [label="="];
+    n7 -> n8;
+    n7 -> n9;
+    n8 [label="f"];
+    n9 [label="|x|"];
+    n9 -> n10;
+    n10 [label="array"];
+    n10 -> n11;
+    n10 -> n12;
+    n11 [label="()"];
+    n11 -> n13;
+    n13 [label="x"];
+    n12 [label="()"];
+    n12 -> n14;
+    n14 [label="-"];
+    n14 -> n15;
+    n15 [label="x"];
+}
+"#
+    );
+    assert_eq!(
+        build_operator_tree("").unwrap().to_dot(),
+        "digraph {\n    n0 [label=\"()\"];\n}\n"
+    );
+}
+
+#[test]
+fn test_context_map() {
+    let context = context_map! {}.unwrap();
+    assert_eq!(context.iter_variable_names().count(), 0);
+
+    let context = context_map! {
+        "a" => 2,
+        "b" => "abc",
+        "f" => Function::new(|argument| Ok(Value::from(argument.as_int()? * 3))),
+        "c" => Value::Tuple(vec![Value::from(1), Value::from(true)]),
+        "g" => Function::new(|_| Ok(Value::Empty)),
+    }
+    .unwrap();
+    assert_eq!(eval_int_with_context("f(a)", &context), Ok(6));
+    assert_eq!(
+        eval_string_with_context("b", &context),
+        Ok("abc".to_string())
+    );
+    assert_eq!(
+        eval_with_context("c", &context),
+        Ok(Value::Tuple(vec![Value::from(1), Value::from(true)]))
+    );
+    assert_eq!(eval_empty_with_context("g()", &context), Ok(()));
+
+    // Assigning a value of a different type to the same identifier fails like `set_value`.
+    assert_eq!(
+        context_map! {
+            "a" => 2,
+            "a" => 2.0
+        }
+        .map(|_| ()),
+        Err(EvalexprError::ExpectedInt {
+            actual: Value::from(2.0)
+        })
+    );
+}
+
+#[test]
+fn test_error_spans() {
+    assert_eq!(
+        build_operator_tree("(1 + 2)) * 3"),
+        Err(EvalexprError::UnmatchedRBrace {
+            span: Span::new(7, 8)
+        })
+    );
+    assert_eq!(
+        build_operator_tree("(1, (2, 3), (4"),
+        Err(EvalexprError::UnmatchedLBrace {
+            span: Span::new(12, 13)
+        })
+    );
+    assert_eq!(
+        build_operator_tree("a && b & c"),
+        Err(EvalexprError::UnmatchedPartialToken {
+            first: PartialToken::Ampersand,
+            second: Some(PartialToken::Whitespace),
+            span: Span::new(7, 8)
+        })
+    );
+    // Spans are byte offsets, so multibyte characters advance them by more than one.
+    assert_eq!(
+        build_operator_tree("\"äöü\" + ä)"),
+        Err(EvalexprError::UnmatchedRBrace {
+            span: Span::new(13, 14)
+        })
+    );
+
+    // Variables that do not exist are reported with the span of the node reading them.
+    let context = context_map! { "a" => 1 }.unwrap();
+    let tree = build_operator_tree("a + max(a, (b))").unwrap();
+    let error = tree.eval_with_context(&context).unwrap_err();
+    assert_eq!(
+        error,
+        EvalexprError::VariableIdentifierNotFound {
+            identifier: "b".into(),
+            suggestions: Vec::new(),
+            span: Some(Span::new(12, 13)),
+        }
+    );
+    assert_eq!(tree.compile().eval_with_context(&context), Err(error));
+    assert_eq!(
+        eval_with_context("a +\n  c", &context)
+            .unwrap_err()
+            .to_string(),
+        "Variable identifier is not bound to anything by context: \"c\" at position 6."
+    );
+    // Nodes that were not parsed have no span.
+    assert_eq!(
+        Node::variable("b").eval(),
+        Err(EvalexprError::variable_identifier_not_found("b".into()))
+    );
+}
+
+#[test]
+fn test_error_recovery() {
+    let recover = |string: &str| {
+        let (tree, errors) = build_operator_tree_with_recovery(string, &ParserConfig::new());
+        (tree.to_string(), errors)
+    };
+
+    // Without errors, the tree is the same as without recovery.
+    assert_eq!(recover("(1 + 2) * a"), ("(1 + 2) * a".into(), Vec::new()));
+
+    // Tokens that cannot be parsed are skipped.
+    assert_eq!(
+        recover("1 2 + 3"),
+        (
+            "1 + 3".into(),
+            vec![(EvalexprError::AppendedToLeafNode, Span::new(2, 3))]
+        )
+    );
+    assert_eq!(
+        recover(")a + b) * c"),
+        (
+            "a + b * c".into(),
+            vec![
+                (
+                    EvalexprError::UnmatchedRBrace {
+                        span: Span::new(0, 1)
+                    },
+                    Span::new(0, 1)
+                ),
+                (
+                    EvalexprError::UnmatchedRBrace {
+                        span: Span::new(6, 7)
+                    },
+                    Span::new(6, 7)
+                ),
+            ]
+        )
+    );
+    // A group that cannot be closed is skipped with its contents.
+    assert_eq!(
+        recover("4(5) + x"),
+        (
+            "4 + x".into(),
+            vec![(
+                EvalexprError::MissingOperatorOutsideOfBrace,
+                Span::new(3, 4)
+            )]
+        )
+    );
+
+    // Unclosed groups are closed at the end.
+    assert_eq!(
+        recover("a[1 + (2"),
+        (
+            "a[1 + 2]".into(),
+            vec![
+                (
+                    EvalexprError::UnmatchedLBracket {
+                        span: Span::new(1, 2)
+                    },
+                    Span::new(1, 2)
+                ),
+                (
+                    EvalexprError::UnmatchedLBrace {
+                        span: Span::new(6, 7)
+                    },
+                    Span::new(6, 7)
+                ),
+            ]
+        )
+    );
+
+    // Errors of the tokenizer are recovered from as well.
+    let (tree, errors) = recover("x + \"\\q\" & y /* z");
+    assert_eq!(tree, "x + y");
+    assert_eq!(
+        errors
+            .iter()
+            .map(|(_, span)| (span.start, span.end))
+            .collect::<Vec<_>>(),
+        vec![(4, 8), (9, 10), (13, 17)]
+    );
+
+    // A policy violation is reported for the whole expression.
+    let config = ParserConfig::new().with_policy(Some(
+        EvaluationPolicy::new().with_forbidden_operator(Operator::Mul),
+    ));
+    let (tree, errors) = build_operator_tree_with_recovery("a * (b", &config);
+    assert_eq!(tree.to_string(), "a * b");
+    assert_eq!(
+        errors,
+        vec![
+            (
+                EvalexprError::ForbiddenOperator {
+                    operator: Operator::Mul
+                },
+                Span::new(0, 6)
+            ),
+            (
+                EvalexprError::UnmatchedLBrace {
+                    span: Span::new(4, 5)
+                },
+                Span::new(4, 5)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_complete() {
+    let mut context = context_map! {
+        "width" => 3,
+        "weight" => 70,
+        "scale" => Function::new(|argument| Ok(argument.clone())),
+    }
+    .unwrap();
+    let texts = |expression: &str, cursor: usize, context: &HashMapContext| {
+        complete(expression, cursor, context)
+            .into_iter()
+            .map(|completion| completion.text)
+            .collect::<Vec<_>>()
+    };
+
+    // Identifiers are completed from the typed prefix.
+    assert_eq!(
+        complete("1 + wi", 6, &context),
+        vec![Completion {
+            text: "width".into(),
+            kind: CompletionKind::Variable,
+            span: Span::new(4, 6),
+        }]
+    );
+    assert_eq!(texts("w", 1, &context), ["weight", "width"]);
+    assert_eq!(texts("sc", 2, &context), ["scale"]);
+    assert_eq!(
+        texts("math::l", 7, &context),
+        ["math::ln", "math::log", "math::log10", "math::log2"]
+    );
+    assert_eq!(texts("tr", 2, &context), ["true"]);
+    // Only the part of the expression before the cursor is considered.
+    assert_eq!(texts("x + we * 2", 6, &context), ["weight"]);
+    assert_eq!(texts("(1 /* w */ + we", 15, &context), ["weight"]);
+
+    // After a value, operators are expected.
+    let operators = texts("(a + 1", 6, &context);
+    assert!(operators.contains(&"*".to_string()));
+    assert!(operators.contains(&")".to_string()));
+    assert!(!operators.contains(&"]".to_string()));
+    assert!(!operators.contains(&"=".to_string()));
+    assert!(!operators.contains(&"width".to_string()));
+    let operators = texts("a", 1, &context);
+    assert!(!operators.contains(&"+=".to_string()));
+    let operators = texts("a ", 2, &context);
+    assert!(operators.contains(&"+=".to_string()));
+    assert!(operators.contains(&"(".to_string()));
+    assert_eq!(texts("a[b i", 5, &context), ["in"]);
+
+    // Without a value before the cursor, values are expected.
+    let values = texts("1 + ", 4, &context);
+    assert!(values.contains(&"width".to_string()));
+    assert!(values.contains(&"scale".to_string()));
+    assert!(values.contains(&"max".to_string()));
+    assert!(values.contains(&"(".to_string()));
+    assert!(!values.contains(&"*".to_string()));
+    context.set_builtin_functions_disabled(true).unwrap();
+    assert_eq!(texts("m", 1, &context), Vec::<String>::new());
+
+    // No completions within string literals and comments, or at invalid positions.
+    assert_eq!(texts("\"w", 2, &context), Vec::<String>::new());
+    assert_eq!(texts("1 // w", 6, &context), Vec::<String>::new());
+    assert_eq!(texts("1 /* w", 6, &context), Vec::<String>::new());
+    assert_eq!(texts("w", 2, &context), Vec::<String>::new());
+    assert_eq!(texts("\u{e4}", 1, &context), Vec::<String>::new());
+}
+
+#[test]
+fn test_tokenize() {
+    fn kinds(expression: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(expression)
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.kind, &expression[token.span.start..token.span.end]))
+            .collect()
+    }
+
+    assert_eq!(
+        kinds("let x = [1.5, \"a b\"]; // comment\nx[0] >= 2e-3 && !true"),
+        vec![
+            (TokenKind::Keyword, "let"),
+            (TokenKind::Identifier, "x"),
+            (TokenKind::Operator, "="),
+            (TokenKind::Parenthesis, "["),
+            (TokenKind::Number, "1.5"),
+            (TokenKind::Operator, ","),
+            (TokenKind::String, "\"a b\""),
+            (TokenKind::Parenthesis, "]"),
+            (TokenKind::Operator, ";"),
+            (TokenKind::Identifier, "x"),
+            (TokenKind::Parenthesis, "["),
+            (TokenKind::Number, "0"),
+            (TokenKind::Parenthesis, "]"),
+            (TokenKind::Operator, ">="),
+            (TokenKind::Number, "2e-3"),
+            (TokenKind::Operator, "&&"),
+            (TokenKind::Operator, "!"),
+            (TokenKind::Boolean, "true"),
+        ]
+    );
+    assert_eq!(
+        kinds("map(|x| x in a, math::abs)"),
+        vec![
+            (TokenKind::Identifier, "map"),
+            (TokenKind::Parenthesis, "("),
+            (TokenKind::LambdaParameters, "|x|"),
+            (TokenKind::Identifier, "x"),
+            (TokenKind::Operator, "in"),
+            (TokenKind::Identifier, "a"),
+            (TokenKind::Operator, ","),
+            (TokenKind::Identifier, "math::abs"),
+            (TokenKind::Parenthesis, ")"),
+        ]
+    );
+
+    // Keyword operators are only operators if enabled in the configuration.
+    let config = ParserConfig::new().with_keyword_operators(true);
+    assert_eq!(tokenize("a and b").unwrap()[1].kind, TokenKind::Identifier);
+    assert_eq!(
+        tokenize_with_config("a and b", &config).unwrap()[1],
+        SpannedToken {
+            kind: TokenKind::Operator,
+            span: Span::new(2, 5)
+        }
+    );
+
+    assert_eq!(
+        tokenize("a & b"),
+        Err(EvalexprError::UnmatchedPartialToken {
+            first: PartialToken::Ampersand,
+            second: Some(PartialToken::Whitespace),
+            span: Span::new(2, 3),
+        })
+    );
+}
+
+#[test]
+fn test_node_spans() {
+    let expression = "let t = [1, (2)]; f(t[0..1]) + -a^2";
+    let tree = build_operator_tree(expression).unwrap();
+    let sources: Vec<_> = tree
+        .iter()
+        .filter(|node| node.operator() != &Operator::RootNode)
+        .map(|node| tree.source_of(node).unwrap())
+        .collect();
+    assert_eq!(
+        sources,
+        [
+            "let t = [1, (2)]; f(t[0..1]) + -a^2",
+            "let t = [1, (2)]",
+            "t",
+            "[1, (2)]",
+            "1",
+            "2",
+            "f(t[0..1]) + -a^2",
+            "f(t[0..1])",
+            "t[0..1]",
+            "t",
+            "0",
+            "1",
+            "-a^2",
+            "a^2",
+            "a",
+            "2",
+        ]
+    );
+    assert_eq!(tree.span(), Some(Span::new(0, expression.len())));
+    assert!(tree
+        .iter()
+        .all(|node| node.span().is_some() && tree.source_of(node).is_some()));
+    // Only the root stores the expression string.
+    assert_eq!(tree.source(), Some(expression));
+    assert!(tree.iter().all(|node| node.source().is_none()));
+    assert_eq!(tree.children()[0].source_of(&tree.children()[0]), None);
+
+    // Spans do not influence equality.
+    assert_eq!(
+        build_operator_tree("(1 + 2)").unwrap().children()[0],
+        build_operator_tree("  (1+2)").unwrap().children()[0]
+    );
+
+    // Nodes that were not parsed have no span.
+    let node = Node::add(Node::variable("a"), Node::constant(1));
+    assert_eq!(node.span(), None);
+    assert_eq!(node.source(), None);
+    assert_eq!(node.source_of(&node), None);
+    assert_eq!(build_operator_tree("").unwrap().span(), None);
+}
+
+#[test]
+fn test_eval_observer() {
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl EvalObserver for Trace {
+        fn enter_node(&mut self, node: &Node) {
+            if node.operator() != &Operator::RootNode {
+                self.0.push(format!("enter {}", node));
+            }
+        }
+
+        fn exit_node(
+            &mut self,
+            node: &Node,
+            operands: &[Value],
+            result: Result<&Value, &EvalexprError>,
+        ) {
+            if node.operator() != &Operator::RootNode {
+                let operands: Vec<_> = operands.iter().map(ToString::to_string).collect();
+                let result = match result {
+                    Ok(value) => value.to_string(),
+                    Err(error) => format!("error {:?}", error),
+                };
+                self.0.push(format!(
+                    "exit {} [{}] {}",
+                    node,
+                    operands.join(", "),
+                    result
+                ));
+            }
+        }
+    }
+
+    let context = context_map! { "a" => 4, "b" => false }.unwrap();
+    let mut trace = Trace::default();
+    assert_eq!(
+        eval_with_context_and_observer("b && x || a > 3", &context, &mut trace),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        trace.0,
+        [
+            "enter b && x || a > 3",
+            "enter b && x",
+            "enter b",
+            "exit b [] false",
+            "exit b && x [false] false",
+            "enter a > 3",
+            "enter a",
+            "exit a [] 4",
+            "enter 3",
+            "exit 3 [] 3",
+            "exit a > 3 [4, 3] true",
+            "exit b && x || a > 3 [false, true] true",
+        ]
+    );
+
+    // The body of a called lambda is observed, but its value is no operand of the call.
+    let mut context = context.clone();
+    eval_with_context_mut("f = |x| x * 2", &mut context).unwrap();
+    let mut trace = Trace::default();
+    assert_eq!(
+        eval_with_context_and_observer("f(a)", &context, &mut trace),
+        Ok(Value::from(8))
+    );
+    assert_eq!(
+        trace.0,
+        [
+            "enter f(a)",
+            "enter a",
+            "exit a [] 4",
+            // The parameters of a lambda are substituted into its body before it is evaluated.
+            "enter 4 * 2",
+            "enter 4",
+            "exit 4 [] 4",
+            "enter 2",
+            "exit 2 [] 2",
+            "exit 4 * 2 [4, 2] 8",
+            "exit f(a) [4] 8",
+        ]
+    );
+
+    // Failing nodes are reported with their errors.
+    let mut trace = Trace::default();
+    assert_eq!(
+        eval_with_context_and_observer("1 + 2 / (a - 4)", &context, &mut trace),
+        Err(EvalexprError::DivisionByZero {
+            dividend: Value::from(2)
+        })
+    );
+    assert_eq!(
+        trace.0[trace.0.len() - 2..],
+        [
+            "exit 2 / (a - 4) [2, 0] error DivisionByZero { dividend: Int(2) }",
+            "exit 1 + 2 / (a - 4) [1] error DivisionByZero { dividend: Int(2) }",
+        ]
+    );
+}
+
+#[test]
+fn test_eval_with_explanation() {
+    let context = context_map! {
+        "price" => 12.0,
+        "qty" => 3,
+        "a" => 0,
+        "s" => "hi",
+    }
+    .unwrap();
+    let explain = |expression: &str| {
+        let (result, explanation) = build_operator_tree(expression)
+            .unwrap()
+            .eval_with_explanation(&context);
+        (result, explanation.to_string())
+    };
+
+    assert_eq!(
+        explain("price * qty > 30"),
+        (
+            Ok(Value::from(true)),
+            "price (12) * qty (3) = 36 > 30 → true".to_string()
+        )
+    );
+    assert_eq!(
+        explain("-qty * (qty - 1)"),
+        (
+            Ok(Value::from(-6)),
+            "-qty (3) = -3 * (qty (3) - 1 = 2) → -6".to_string()
+        )
+    );
+    assert_eq!(
+        explain("max(qty, 2) + len(s)"),
+        (
+            Ok(Value::from(5)),
+            "max(qty (3), 2) = 3 + (len(s (\"hi\")) = 2) → 5".to_string()
+        )
+    );
+    assert_eq!(
+        explain("1 < qty < 5"),
+        (Ok(Value::from(true)), "1 < qty (3) < 5 → true".to_string())
+    );
+    assert_eq!(
+        explain("a > 1 && b"),
+        (
+            Ok(Value::from(false)),
+            "a (0) > 1 = false && … → false".to_string()
+        )
+    );
+    assert_eq!(explain("qty"), (Ok(Value::from(3)), "qty (3)".to_string()));
+
+    let (result, explanation) = build_operator_tree("qty + b")
+        .unwrap()
+        .eval_with_explanation(&context);
+    assert_eq!(
+        result,
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "b".to_string(),
+            suggestions: vec![],
+            span: Some(Span::new(6, 7)),
+        })
+    );
+    assert_eq!(explanation.to_string(), "qty (3) + b (error) → error");
+    assert_eq!(explanation.operator(), &Operator::Add);
+    assert_eq!(explanation.expression(), "qty + b");
+    assert_eq!(explanation.value(), None);
+    assert_eq!(explanation.operands().len(), 2);
+    assert_eq!(explanation.operands()[0].value(), Some(&Value::from(3)));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_profiler() {
+    let mut context = HashMapContext::new();
+    context
+        .set_function(
+            "expensive".into(),
+            Function::new(|argument| Ok(Value::from(argument.as_int()? * 2))),
+        )
+        .unwrap();
+    let tree = build_operator_tree("x > 5 && expensive(x) > 15").unwrap();
+
+    let mut profiler = Profiler::new();
+    for x in 0..10 {
+        context.set_value("x".into(), Value::from(x)).unwrap();
+        tree.eval_with_context_and_observer(&context, &mut profiler)
+            .unwrap();
+    }
+
+    let profile = profiler.profile(&tree);
+    let evaluations = |expression: &str| {
+        profile
+            .iter()
+            .find(|entry| entry.node.to_string() == expression)
+            .map(|entry| entry.evaluations)
+    };
+    assert_eq!(profile[0].node, &tree);
+    assert_eq!(evaluations("x > 5 && expensive(x) > 15"), Some(10));
+    assert_eq!(evaluations("x > 5"), Some(10));
+    assert_eq!(evaluations("expensive(x) > 15"), Some(4));
+    assert_eq!(evaluations("expensive(x)"), Some(4));
+    assert_eq!(profile.len(), tree.iter().count() + 1);
+    assert!(profile
+        .windows(2)
+        .all(|entries| entries[0].total_time >= entries[1].total_time));
+    assert!(profile[0].average_time() <= profile[0].total_time);
+
+    // Nodes that were never evaluated are not profiled.
+    let other = build_operator_tree("x > 5 && expensive(x) > 15").unwrap();
+    assert_eq!(profiler.profile(&other), vec![]);
+    profiler.clear();
+    assert_eq!(profiler.profile(&tree), vec![]);
+}
+#[test]
+fn test_comments() {
+    assert_eq!(eval("1 + 2 // three"), Ok(Value::Int(3)));
+    assert_eq!(eval("// nothing"), Ok(Value::Empty));
+    assert_eq!(
+        eval("a = 2; // assign\n a * 3 // multiply"),
+        Ok(Value::Int(6))
+    );
+    // Comments separate tokens.
+    assert_eq!(eval("1/**/2"), Err(EvalexprError::AppendedToLeafNode));
+    assert_eq!(eval("4 /* / 2 */ / 2"), Ok(Value::Int(2)));
+    assert_eq!(eval("4 /*/ 2 **/ - 1"), Ok(Value::Int(3)));
+    assert_eq!(
+        eval("\"// not a comment\""),
+        Ok(Value::from("// not a comment"))
+    );
+    assert_eq!(
+        eval("1 + /* 2\n + 3"),
+        Err(EvalexprError::UnterminatedComment {
+            span: Span::new(4, 13)
+        })
+    );
+}
+
+#[test]
+fn test_chained_comparisons() {
+    let context = context_map! {"x" => 5, "y" => 2.5}.unwrap();
+    assert_eq!(eval_boolean_with_context("1 < x < 10", &context), Ok(true));
+    assert_eq!(eval_boolean_with_context("1 < x < 5", &context), Ok(false));
+    assert_eq!(eval_boolean_with_context("1 < x <= 5", &context), Ok(true));
+    assert_eq!(
+        eval_boolean_with_context("10 > x >= y > 2", &context),
+        Ok(true)
+    );
+    assert_eq!(
+        eval_boolean_with_context("0 < y < x < 1", &context),
+        Ok(false)
+    );
+    assert_eq!(
+        eval_boolean_with_context("\"a\" < \"b\" < \"c\"", &context),
+        Ok(true)
+    );
+    assert_eq!(
+        eval_boolean_with_context("x - 4 < x < x + 1", &context),
+        Ok(true)
+    );
+    // Operands after the first false comparison are not evaluated.
+    assert_eq!(eval_boolean_with_context("x < 1 < z", &context), Ok(false));
+    assert_eq!(
+        eval_boolean_with_context("1 < x < z", &context),
+        Err(variable_not_found_at("z", Span::new(8, 9)))
+    );
+    // Each operand is evaluated only once.
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_boolean_with_context_mut("a = 0; 0 < (a += 1; a) < 2; a == 1", &mut context),
+        Ok(true)
+    );
+
+    // Parentheses and equality comparisons end the chain.
+    assert_eq!(
+        eval("(1 < 2) < 3"),
+        Err(EvalexprError::ExpectedNumberOrString {
+            actual: Value::Boolean(true)
+        })
+    );
+    assert_eq!(eval("1 < 2 == true"), Ok(Value::Boolean(true)));
+    assert_eq!(eval("1 < 2 == 2"), Ok(Value::Boolean(false)));
+}
+
+#[test]
+fn test_equality() {
+    assert_eq!(eval("1 == 1.0"), Ok(Value::from(true)));
+    assert_eq!(eval("1 != 1.5"), Ok(Value::from(true)));
+    assert_eq!(eval("(1, 2.0) == (1.0, 2)"), Ok(Value::from(true)));
+    assert_eq!(eval("(1, 2) == (1, 2, 3)"), Ok(Value::from(false)));
+    assert_eq!(eval("(1, (2, 3.0)) == (1, (2, 3))"), Ok(Value::from(true)));
+    assert_eq!(eval("[1, 2] == [1.0, 2.0]"), Ok(Value::from(true)));
+    assert_eq!(eval("[1, 2] == [2, 1]"), Ok(Value::from(false)));
+    assert_eq!(eval("\"abc\" == \"abc\""), Ok(Value::from(true)));
+    assert_eq!(eval("\"abc\" != \"abd\""), Ok(Value::from(true)));
+    assert_eq!(eval("0.0 / 0.0 == 0.0 / 0.0"), Ok(Value::from(false)));
+    assert_eq!(eval("0.0 / 0.0 != 0.0 / 0.0"), Ok(Value::from(true)));
+
+    // Values of mismatched types are never equal.
+    assert_eq!(eval("\"1\" == 1"), Ok(Value::from(false)));
+    assert_eq!(eval("true != 1"), Ok(Value::from(true)));
+    assert_eq!(eval("(1, 2) == [1, 2]"), Ok(Value::from(false)));
+    assert_eq!(eval("() == 0"), Ok(Value::from(false)));
+    assert_eq!(eval("() == ()"), Ok(Value::from(true)));
+
+    let mut a = MapType::new();
+    a.insert("x".into(), Value::from(1));
+    a.insert(
+        "y".into(),
+        Value::from(vec![Value::from(2), Value::from(3)]),
+    );
+    let mut b = MapType::new();
+    b.insert("x".into(), Value::from(1.0));
+    b.insert(
+        "y".into(),
+        Value::from(vec![Value::from(2.0), Value::from(3.0)]),
+    );
+    let mut c = b.clone();
+    c.insert("z".into(), Value::from(4));
+    let context =
+        context_map! { "a" => Value::from(a), "b" => Value::from(b), "c" => Value::from(c) }
+            .unwrap();
+    assert_eq!(eval_with_context("a == b", &context), Ok(Value::from(true)));
+    assert_eq!(eval_with_context("b != c", &context), Ok(Value::from(true)));
+
+    // Membership uses the same equality.
+    assert_eq!(eval("1.0 in (1, 2)"), Ok(Value::from(true)));
+    assert_eq!(eval("contains((1, 2), 2.0)"), Ok(Value::from(true)));
+    assert_eq!(eval("\"1\" in (1, 2)"), Ok(Value::from(false)));
+}
+
+#[test]
+fn test_in_operator() {
+    assert_eq!(eval("2 in (1, 2, 3)"), Ok(Value::from(true)));
+    assert_eq!(eval("4 in (1, 2, 3)"), Ok(Value::from(false)));
+    assert_eq!(eval("2 in [1, 2]"), Ok(Value::from(true)));
+    assert_eq!(eval("2 in ()"), Ok(Value::from(false)));
+    assert_eq!(eval("(1, 2) in ((1, 2), 3)"), Ok(Value::from(true)));
+    assert_eq!(eval("\"sub\" in \"substring\""), Ok(Value::from(true)));
+    assert_eq!(eval("\"bus\" in \"substring\""), Ok(Value::from(false)));
+    assert_eq!(eval("\"\" in \"\""), Ok(Value::from(true)));
+
+    let mut map = MapType::new();
+    map.insert("key".into(), Value::from(1));
+    let context = context_map! { "m" => Value::from(map), "x" => 3 }.unwrap();
+    assert_eq!(
+        eval_with_context("\"key\" in m && !(\"other\" in m)", &context),
+        Ok(Value::from(true))
+    );
+
+    // The operator has the same precedence as the comparison operators.
+    assert_eq!(
+        eval_with_context("x + 1 in (2, 4) && x * 2 in (5, 6)", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("x in (1, 2) == false", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("x in (1, 2) || x in (3, 4)", &context),
+        Ok(Value::from(true))
+    );
+
+    assert_eq!(
+        eval("1 in 2"),
+        Err(EvalexprError::type_error(
+            Value::from(2),
+            vec![
+                ValueType::Tuple,
+                ValueType::Array,
+                ValueType::String,
+                ValueType::Map
+            ]
+        ))
+    );
+    assert_eq!(
+        eval("1 in \"1\""),
+        Err(EvalexprError::expected_string(Value::from(1)))
+    );
+
+    for expression in ["a in (1, 2)", "a + 1 in t && b", "b == (a in t)"] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(tree.to_string(), expression);
+    }
+}
+
+#[test]
+fn test_null_coalescing() {
+    let mut context = context_map! { "a" => 1, "e" => () }.unwrap();
+    assert_eq!(eval_with_context("a ?? 0", &context), Ok(Value::from(1)));
+    assert_eq!(eval_with_context("e ?? 0", &context), Ok(Value::from(0)));
+    assert_eq!(
+        eval_with_context("missing ?? 0", &context),
+        Ok(Value::from(0))
+    );
+    assert_eq!(
+        eval_with_context("missing ?? e ?? a", &context),
+        Ok(Value::from(1))
+    );
+    assert_eq!(eval("() ?? \"default\""), Ok(Value::from("default")));
+    assert_eq!(eval("false ?? true"), Ok(Value::from(false)));
+
+    // The right operand is only evaluated if it is needed, and its errors are not suppressed.
+    assert_eq!(
+        eval_with_context("a ?? 1 / 0", &context),
+        Ok(Value::from(1))
+    );
+    assert_eq!(
+        eval_with_context("missing ?? other", &context),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "other".into(),
+            suggestions: Vec::new(),
+            span: Some(Span::new(11, 16)),
+        })
+    );
+    // Other errors of the left operand are not suppressed.
+    assert_eq!(
+        eval_with_context("1 / 0 ?? 2", &context),
+        Err(EvalexprError::DivisionByZero {
+            dividend: Value::from(1)
+        })
+    );
+
+    // The operator binds weaker than `||` and stronger than assignments.
+    assert_eq!(
+        eval_with_context("missing || true ?? 5", &context),
+        Ok(Value::from(5))
+    );
+    assert_eq!(
+        eval_with_context_mut("b = missing ?? a + 1; b", &mut context),
+        Ok(Value::from(2))
+    );
+
+    let compiled = build_operator_tree("x ?? a * 10").unwrap().compile();
+    assert_eq!(compiled.eval_with_context(&context), Ok(Value::from(10)));
+
+    assert_eq!(
+        eval("1 ?"),
+        Err(EvalexprError::UnmatchedPartialToken {
+            first: PartialToken::QuestionMark,
+            second: None,
+            span: Span::new(2, 3)
+        })
+    );
+
+    for expression in ["a ?? 0", "a ?? b ?? c", "(a ?? b) + 1"] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(tree.to_string(), expression);
+    }
+}
+
+#[test]
+fn test_short_circuit_evaluation() {
+    let context = context_map! {"x" => 0}.unwrap();
+    assert_eq!(
+        eval_boolean_with_context("x != 0 && 10 / x > 2", &context),
+        Ok(false)
+    );
+    assert_eq!(
+        eval_boolean_with_context("x == 0 || 10 / x > 2", &context),
+        Ok(true)
+    );
+    assert_eq!(eval_boolean("false && undefined()"), Ok(false));
+    assert_eq!(eval_boolean("true || undefined"), Ok(true));
+    assert_eq!(eval_boolean("false || true && false"), Ok(false));
+
+    // The right operand is evaluated if the left one does not determine the result.
+    assert_eq!(
+        eval_boolean("true && undefined"),
+        Err(variable_not_found_at("undefined", Span::new(8, 17)))
+    );
+    assert_eq!(
+        eval_boolean("false || 1"),
+        Err(EvalexprError::ExpectedBoolean {
+            actual: Value::from(1)
+        })
+    );
+    // The left operand still needs to be a boolean.
+    assert_eq!(
+        eval_boolean("1 && false"),
+        Err(EvalexprError::ExpectedBoolean {
+            actual: Value::from(1)
+        })
+    );
+
+    // Skipped operands do not modify the context.
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_int_with_context_mut("a = 1; false && (a = 2; true); a", &mut context),
+        Ok(1)
+    );
+    assert_eq!(
+        eval_int_with_context_mut("true && (a = 2; true); a", &mut context),
+        Ok(2)
+    );
+}
+
+#[test]
+fn test_recursion_limit() {
+    let max_depth = ParserConfig::DEFAULT_MAX_DEPTH;
+    let error = Err(EvalexprError::RecursionLimitExceeded { max_depth });
+
+    // Deeply nested input must not overflow the stack.
+    for depth in [max_depth, 100_000] {
+        let nested_braces = format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&nested_braces), error);
+        assert_eq!(eval(&format!("{}1", "-".repeat(depth))), error);
+        assert_eq!(eval(&format!("{}1", "2 ^ ".repeat(depth))), error);
+        assert_eq!(eval(&format!("{}1", "a = ".repeat(depth))), error);
+        assert_eq!(
+            eval(&format!("{}1{}", "f(".repeat(depth), ")".repeat(depth))),
+            error
+        );
+    }
+
+    // Expressions within the limit are fine.
+    let depth = max_depth - 2;
+    assert_eq!(eval(&format!("{}1", "-".repeat(depth))), Ok(Value::Int(1)));
+    assert_eq!(
+        eval(&format!("{}1", "1 + ".repeat(depth))),
+        Ok(Value::Int(depth as IntType + 1))
+    );
+    // Long sequences and chains of left-associative operators do not increase the depth.
+    assert_eq!(
+        eval_tuple(&format!("{}1", "1, ".repeat(100_000))).map(|tuple| tuple.len()),
+        Ok(100_001)
+    );
+    // Evaluation recurses into the whole operator tree, which needs more than the stack of a test thread in debug builds.
+    std::thread::Builder::new()
+        .stack_size(64 << 20)
+        .spawn(|| {
+            assert_eq!(eval(&vec!["1"; 1000].join(" + ")), Ok(Value::Int(1000)));
+            assert_eq!(eval(&vec!["1"; 1000].join(" - ")), Ok(Value::Int(-998)));
+            assert_eq!(
+                eval(&format!("{}1", "-1 - ".repeat(1000))),
+                Ok(Value::Int(997))
+            );
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    let config = ParserConfig::new().with_max_depth(4);
+    assert_eq!(config.max_depth(), 4);
+    assert!(build_operator_tree_with_config("-(1)", &config).is_ok());
+    assert!(build_operator_tree_with_config("1 * 2 + 3", &config).is_ok());
+    assert_eq!(
+        build_operator_tree_with_config("---(1)", &config),
+        Err(EvalexprError::RecursionLimitExceeded { max_depth: 4 })
+    );
+    assert!(build_operator_tree_with_config("1 + 2 + 3 + 4 + 5 + 6", &config).is_ok());
+    assert_eq!(
+        build_operator_tree_with_config("(1 + (2 + (3 + 4)))", &config),
+        Err(EvalexprError::RecursionLimitExceeded { max_depth: 4 })
+    );
+
+    // The depth of the whole operator tree is limited by an evaluation policy.
+    let policy = EvaluationPolicy::new().with_max_depth(Some(4));
+    let config = ParserConfig::new().with_policy(Some(policy));
+    assert_eq!(
+        build_operator_tree_with_config("1 + 2 + 3 + 4", &config),
+        Err(EvalexprError::RecursionLimitExceeded { max_depth: 4 })
+    );
+}
+
+#[test]
+fn test_evaluation_budget() {
+    let options = EvalOptions::new().with_max_node_evaluations(Some(10));
+    assert_eq!(options.max_node_evaluations(), Some(10));
+    assert_eq!(EvalOptions::default().max_node_evaluations(), None);
+    let error = Err(EvalexprError::BudgetExceeded {
+        max_node_evaluations: 10,
+    });
+
+    let context = HashMapContext::new();
+    // The root node, four additions and five values.
+    let tree = build_operator_tree("1 + 2 + 3 + 4 + 5").unwrap();
+    assert_eq!(
+        tree.eval_with_context_and_options(&context, &options),
+        Ok(Value::from(15))
+    );
+    let tree = build_operator_tree("1 + 2 + 3 + 4 + 5 + 6").unwrap();
+    assert_eq!(
+        tree.eval_with_context_and_options(&context, &options),
+        error
+    );
+    assert_eq!(tree.eval_with_context(&context), Ok(Value::from(21)));
+
+    // Nodes that are skipped by lazy evaluation are not counted.
+    assert_eq!(
+        eval_with_context_and_options(
+            "if(true, 1, 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1)",
+            &context,
+            &options
+        ),
+        Ok(Value::from(1))
+    );
+
+    // Each evaluation of a tree has its own budget.
+    // Every element of the chain is wrapped into an extra root node, so this tree has twelve nodes.
+    let options = EvalOptions::new().with_max_node_evaluations(Some(12));
+    let error = Err(EvalexprError::BudgetExceeded {
+        max_node_evaluations: 12,
+    });
+    let mut context = HashMapContext::new();
+    let tree = build_operator_tree("a = 1; a += 1; a").unwrap();
+    for _ in 0..3 {
+        assert_eq!(
+            tree.eval_with_context_mut_and_options(&mut context, &options),
+            Ok(Value::from(2))
+        );
+    }
+    assert_eq!(
+        eval_with_context_mut_and_options("a = 1; a += 1; a += 1; a", &mut context, &options),
+        error
+    );
+    // The evaluation stops at the point where the budget is exceeded.
+    assert_eq!(eval_int_with_context("a", &context), Ok(2));
+}
+
+#[test]
+fn test_arrays_and_maps() {
+    let array = |values: Vec<Value>| Value::Array(values);
+    assert_eq!(eval("[]"), Ok(array(vec![])));
+    assert_eq!(eval("[1]"), Ok(array(vec![Value::from(1)])));
+    assert_eq!(
+        eval("[1, 2 + 3, \"a\"]"),
+        Ok(array(vec![
+            Value::from(1),
+            Value::from(5),
+            Value::from("a")
+        ]))
+    );
+    // Unlike tuples, arrays do not flatten when they are nested.
+    assert_eq!(
+        eval("[[1, 2], [3], (4, 5)]"),
+        Ok(array(vec![
+            array(vec![Value::from(1), Value::from(2)]),
+            array(vec![Value::from(3)]),
+            Value::Tuple(vec![Value::from(4), Value::from(5)]),
+        ]))
+    );
+    assert_eq!(eval("typeof([1])"), Ok(Value::from("array")));
+    assert_eq!(eval("[1, 2] == [1, 2]"), Ok(Value::from(true)));
+
+    assert_eq!(eval("[1, 2, 3][0]"), Ok(Value::from(1)));
+    assert_eq!(eval("[1, 2, 3][1 + 1] * 2"), Ok(Value::from(6)));
+    assert_eq!(eval("-[1, 2][1]"), Ok(Value::from(-2)));
+    assert_eq!(eval("[[1, 2], [3, 4]][1][0]"), Ok(Value::from(3)));
+    assert_eq!(
+        eval("[1, 2][2]"),
+        Err(EvalexprError::index_out_of_bounds(2, 2))
+    );
+    assert_eq!(
+        eval("[1, 2][-1]"),
+        Err(EvalexprError::index_out_of_bounds(-1, 2))
+    );
+    assert_eq!(
+        eval("[1, 2][0.5]"),
+        Err(EvalexprError::expected_int(Value::from(0.5)))
+    );
+    assert_eq!(
+        eval("true[0]"),
+        Err(EvalexprError::type_error(
+            Value::from(true),
+            vec![
+                ValueType::Tuple,
+                ValueType::Array,
+                ValueType::String,
+                ValueType::Map
+            ]
+        ))
+    );
+
+    let mut map = MapType::new();
+    map.insert("name".into(), Value::from("evalexpr"));
+    map.insert(
+        "versions".into(),
+        array(vec![Value::from(11), Value::from(12)]),
+    );
+    let mut context = context_map! {
+        "m" => Value::from(map),
+        "f" => Function::new(|argument| Ok(Value::Array(vec![argument.clone()]))),
+    }
+    .unwrap();
+    assert_eq!(
+        eval_with_context("m[\"name\"]", &context),
+        Ok(Value::from("evalexpr"))
+    );
+    assert_eq!(
+        eval_with_context("m[\"versions\"][1] + 1", &context),
+        Ok(Value::from(13))
+    );
+    assert_eq!(
+        eval_with_context("m[\"missing\"]", &context),
+        Err(EvalexprError::key_not_found("missing".into()))
+    );
+    assert_eq!(
+        eval_with_context("m[0]", &context),
+        Err(EvalexprError::expected_string(Value::from(0)))
+    );
+    assert_eq!(eval_with_context("f(5)[0]", &context), Ok(Value::from(5)));
+    assert_eq!(
+        eval_with_context("typeof(m)", &context),
+        Ok(Value::from("map"))
+    );
+    assert_eq!(
+        eval_with_context_mut("a = [1, 2]; a[1]", &mut context),
+        Ok(Value::from(2))
+    );
+
+    assert_eq!(
+        build_operator_tree("[1, 2"),
+        Err(EvalexprError::UnmatchedLBracket {
+            span: Span::new(0, 1)
+        })
+    );
+    assert_eq!(
+        build_operator_tree("a[(1]"),
+        Err(EvalexprError::UnmatchedRBracket {
+            span: Span::new(4, 5)
+        })
+    );
+    assert_eq!(
+        build_operator_tree("[1)"),
+        Err(EvalexprError::UnmatchedRBrace {
+            span: Span::new(2, 3)
+        })
+    );
+    assert_eq!(
+        build_operator_tree("1]"),
+        Err(EvalexprError::UnmatchedRBracket {
+            span: Span::new(1, 2)
+        })
+    );
+
+    for expression in [
+        "[]",
+        "[1, (2, 3), [a = 4]]",
+        "[(1, 2)]",
+        "a[0][1]",
+        "(a + b)[0]",
+        "f(x)[i + 1]",
+        "-a[0]",
+        "-[1][0]",
+    ] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(tree.to_string(), expression);
+    }
+}
+
+#[test]
+fn test_tuple_and_string_indexing() {
+    let tuple =
+        |values: &[IntType]| Value::Tuple(values.iter().copied().map(Value::from).collect());
+    assert_eq!(eval("(1, 2, 3)[0]"), Ok(Value::from(1)));
+    assert_eq!(eval("(1, 2, 3)[2] * 2"), Ok(Value::from(6)));
+    assert_eq!(eval("\"häll\"[1]"), Ok(Value::from("ä")));
+    assert_eq!(
+        eval("(1, 2)[2]"),
+        Err(EvalexprError::index_out_of_bounds(2, 2))
+    );
+    assert_eq!(
+        eval("\"häll\"[-1]"),
+        Err(EvalexprError::index_out_of_bounds(-1, 4))
+    );
+
+    assert_eq!(eval("(1, 2, 3, 4)[1..3]"), Ok(tuple(&[2, 3])));
+    assert_eq!(eval("(1, 2, 3, 4)[1..=3]"), Ok(tuple(&[2, 3, 4])));
+    assert_eq!(eval("(1, 2, 3, 4)[0..4]"), Ok(tuple(&[1, 2, 3, 4])));
+    assert_eq!(eval("(1, 2, 3)[2..2]"), Ok(tuple(&[])));
+    assert_eq!(eval("(1, 2, 3)[2..1]"), Ok(tuple(&[])));
+    assert_eq!(eval("(1, 2, 3)[1 + 1 - 1..1 * 3]"), Ok(tuple(&[2, 3])));
+    assert_eq!(
+        eval("[1, 2, 3][0..=1]"),
+        Ok(Value::Array(vec![Value::from(1), Value::from(2)]))
+    );
+    assert_eq!(eval("\"häll\"[1..3]"), Ok(Value::from("äl")));
+    assert_eq!(eval("\"abc\"[0..=2]"), Ok(Value::from("abc")));
+    assert_eq!(
+        eval("(1, 2)[0..=2]"),
+        Err(EvalexprError::index_out_of_bounds(2, 2))
+    );
+    assert_eq!(
+        eval("(1, 2)[-1..1]"),
+        Err(EvalexprError::index_out_of_bounds(-1, 2))
+    );
+    assert_eq!(
+        eval("\"abc\"[1..4]"),
+        Err(EvalexprError::index_out_of_bounds(4, 3))
+    );
+    assert_eq!(
+        eval("1[0..1]"),
+        Err(EvalexprError::type_error(
+            Value::from(1),
+            vec![ValueType::Tuple, ValueType::Array, ValueType::String]
+        ))
+    );
+    // Only ranges directly inside of the brackets select a slice.
+    assert_eq!(
+        eval("(1, 2, 3)[(0..1)]"),
+        Err(EvalexprError::expected_int(tuple(&[0])))
+    );
+
+    // Tuples built inside of the expression can be consumed right away.
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_with_context_mut("t = (1, 2, 3); t[0] + t[2]", &mut context),
+        Ok(Value::from(4))
+    );
+    assert_eq!(
+        eval_with_context_mut("t[1..3]", &mut context),
+        Ok(tuple(&[2, 3]))
+    );
+
+    for expression in [
+        "t[1..3]",
+        "t[1..=3]",
+        "(a, b)[i + 1..len(t) - 1]",
+        "s[(a == b)..2]",
+        "-t[0..1]",
+    ] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(tree.to_string(), expression);
+    }
+}
+
+#[test]
+fn test_ranges() {
+    let tuple =
+        |values: &[IntType]| Value::Tuple(values.iter().copied().map(Value::from).collect());
+    assert_eq!(eval("1..5"), Ok(tuple(&[1, 2, 3, 4])));
+    assert_eq!(eval("1..=5"), Ok(tuple(&[1, 2, 3, 4, 5])));
+    assert_eq!(eval("-2..1"), Ok(tuple(&[-2, -1, 0])));
+    assert_eq!(eval("3..3"), Ok(tuple(&[])));
+    assert_eq!(eval("3..=3"), Ok(tuple(&[3])));
+    assert_eq!(eval("5..1"), Ok(tuple(&[])));
+    assert_eq!(eval("1 + 1..2 * 2"), Ok(tuple(&[2, 3])));
+    assert_eq!(eval("sum(1..=10)"), Ok(Value::from(55)));
+    assert_eq!(eval("len(0..1000)"), Ok(Value::from(1000)));
+    assert_eq!(eval("map(0..3, |i| i * i)"), Ok(tuple(&[0, 1, 4])));
+
+    let context = context_map! { "x" => 42 }.unwrap();
+    assert_eq!(eval_boolean_with_context("x in 1..100", &context), Ok(true));
+    assert_eq!(eval_boolean_with_context("x in 1..42", &context), Ok(false));
+    assert_eq!(eval_boolean_with_context("x in 1..=42", &context), Ok(true));
+
+    assert_eq!(
+        eval("1.5..3"),
+        Err(EvalexprError::expected_int(Value::from(1.5)))
+    );
+    assert_eq!(
+        eval("1..\"3\""),
+        Err(EvalexprError::expected_int(Value::from("3")))
+    );
+
+    // Long ranges fail before they are created if the policy limits the length of tuples.
+    let options = EvalOptions::new()
+        .with_policy(Some(EvaluationPolicy::new().with_max_tuple_length(Some(3))));
+    assert_eq!(
+        eval_with_context_and_options("1..=3", &context, &options),
+        Ok(tuple(&[1, 2, 3]))
+    );
+    assert_eq!(
+        eval_with_context_and_options("0..1000000000000000", &context, &options),
+        Err(EvalexprError::TupleLengthLimitExceeded {
+            max_tuple_length: 3,
+            actual: 1000000000000000
+        })
+    );
+
+    for expression in [
+        "1..5",
+        "a..=b + 1",
+        "-1..len(t)",
+        "(1..3)..5",
+        "x in 1..100",
+        "t[(0..2)]",
+    ] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(tree.to_string(), expression);
+    }
+}
+
+#[test]
+fn test_dot_path_access() {
+    let mut address = MapType::new();
+    address.insert("city".into(), Value::from("Berlin"));
+    let mut user = MapType::new();
+    user.insert("name".into(), Value::from("Ada"));
+    user.insert("age".into(), Value::from(36));
+    user.insert("address".into(), Value::from(address));
+    let mut context = context_map! {
+        "user" => Value::from(user),
+        "config.limits" => Value::from(MapType::from([("max".to_string(), Value::from(10))])),
+        "config.limits.max" => 5,
+    }
+    .unwrap();
+
+    assert_eq!(
+        eval_with_context("user.name", &context),
+        Ok(Value::from("Ada"))
+    );
+    assert_eq!(
+        eval_with_context("user.address.city + \"!\"", &context),
+        Ok(Value::from("Berlin!"))
+    );
+    assert_eq!(
+        eval_with_context(
+            "user.age >= 18 && user.address.city == \"Berlin\"",
+            &context
+        ),
+        Ok(Value::from(true))
+    );
+    // Identifiers that are stored in the context with dots take precedence over nested maps.
+    assert_eq!(
+        eval_with_context("config.limits.max", &context),
+        Ok(Value::from(5))
+    );
+    assert_eq!(
+        eval_with_context("user.email", &context),
+        Err(variable_not_found_at("user.email", Span::new(0, 10)))
+    );
+    assert_eq!(
+        eval_with_context("user.name.first", &context),
+        Err(variable_not_found_at("user.name.first", Span::new(0, 15)))
+    );
+
+    // Assignments store the path as an identifier of its own.
+    assert_eq!(
+        eval_with_context_mut("user.age += 1; user.age", &mut context),
+        Ok(Value::from(37))
+    );
+    assert_eq!(
+        eval_with_context("user[\"age\"]", &context),
+        Ok(Value::from(36))
+    );
+}
+
+#[test]
+fn test_functions_with_context() {
+    let mut context = context_map! {
+        "a" => 3,
+        "lookup" => Function::new_with_context(|context, argument| {
+            let identifier = argument.as_string()?;
+            context
+                .get_value(&identifier)
+                .cloned()
+                .ok_or(EvalexprError::variable_identifier_not_found(identifier))
+        }),
+        "call" => Function::new_with_context(|context, argument| {
+            let arguments = argument.as_fixed_len_tuple(2)?;
+            context.call_function(&arguments[0].as_string()?, &arguments[1])
+        }),
+        "inc" => Function::new(|argument| Ok(Value::from(argument.as_int()? + 1))),
+    }
+    .unwrap();
+
+    assert_eq!(
+        eval_with_context("lookup(\"a\") * 2", &context),
+        Ok(Value::from(6))
+    );
+    assert_eq!(
+        eval_with_context("lookup(\"b\")", &context),
+        Err(variable_not_found_at("b", Span::new(0, 11)))
+    );
+    assert_eq!(
+        eval_with_context("call(\"inc\", lookup(\"a\"))", &context),
+        Ok(Value::from(4))
+    );
+    // The function sees the current state of the context.
+    assert_eq!(
+        eval_with_context_mut("a = 10; b = 1; lookup(\"a\") + lookup(\"b\")", &mut context),
+        Ok(Value::from(11))
+    );
+    assert_eq!(
+        eval_with_context("lookup(\"lookup\")", &context),
+        Err(variable_not_found_at("lookup", Span::new(0, 16)))
+    );
+}
+
+#[test]
+fn test_aggregation_functions() {
+    assert_eq!(eval("sum(1, 2, 3)"), Ok(Value::from(6)));
+    assert_eq!(eval("sum(1, 2.5)"), Ok(Value::from(3.5)));
+    assert_eq!(eval("sum(5)"), Ok(Value::from(5)));
+    assert_eq!(eval("sum()"), Ok(Value::from(0)));
+    assert_eq!(eval("sum([1, 2, 3])"), Ok(Value::from(6)));
+    assert_eq!(eval("product(2, 3, 4)"), Ok(Value::from(24)));
+    assert_eq!(eval("product(2, 0.5)"), Ok(Value::from(1.0)));
+    assert_eq!(eval("product()"), Ok(Value::from(1)));
+    assert_eq!(eval("avg(1, 2)"), Ok(Value::from(1.5)));
+    assert_eq!(eval("avg(2.5)"), Ok(Value::from(2.5)));
+    assert_eq!(
+        eval("avg()"),
+        Err(EvalexprError::WrongFunctionArgumentAmountRange {
+            actual: 0,
+            expected: 1..=usize::MAX
+        })
+    );
+    assert_eq!(
+        eval("avg([])"),
+        Err(EvalexprError::WrongFunctionArgumentAmountRange {
+            actual: 0,
+            expected: 1..=usize::MAX
+        })
+    );
+    assert_eq!(eval("count(1, \"a\", ())"), Ok(Value::from(3)));
+    assert_eq!(eval("count(\"a\")"), Ok(Value::from(1)));
+    assert_eq!(eval("count()"), Ok(Value::from(0)));
+    assert_eq!(eval("count([(1, 2), 3])"), Ok(Value::from(2)));
+    assert_eq!(eval("len([1, 2, 3])"), Ok(Value::from(3)));
+
+    let context = context_map! {
+        "scores" => Value::from(vec![Value::from(2), Value::from(4), Value::from(9)]),
+    }
+    .unwrap();
+    assert_eq!(
+        eval_with_context("sum(scores)", &context),
+        Ok(Value::from(15))
+    );
+    assert_eq!(
+        eval_with_context("avg(scores)", &context),
+        Ok(Value::from(5.0))
+    );
+    assert_eq!(
+        eval_with_context("count(scores) == len(scores)", &context),
+        Ok(Value::from(true))
+    );
+
+    assert_eq!(
+        eval("sum(1, \"a\")"),
+        Err(EvalexprError::expected_number(Value::from("a")))
+    );
+    assert_eq!(
+        eval("avg(true)"),
+        Err(EvalexprError::expected_number(Value::from(true)))
+    );
+    #[cfg(not(feature = "bigint"))]
+    assert_eq!(
+        eval("product(9223372036854775807, 2)"),
+        Err(EvalexprError::MultiplicationError {
+            multiplicand: Value::from(IntType::MAX),
+            multiplier: Value::from(2)
+        })
+    );
+}
+
+#[test]
+fn test_conversion_functions() {
+    assert_eq!(eval("int(3)"), Ok(Value::from(3)));
+    assert_eq!(eval("int(3.9)"), Ok(Value::from(3)));
+    assert_eq!(eval("int(-3.9)"), Ok(Value::from(-3)));
+    assert_eq!(eval("int(true)"), Ok(Value::from(1)));
+    assert_eq!(eval("int(\" 42 \")"), Ok(Value::from(42)));
+    assert_eq!(eval("int(\"-2.5\")"), Ok(Value::from(-2)));
+    assert_eq!(eval("int(\"1e3\")"), Ok(Value::from(1000)));
+    assert_eq!(eval("float(2)"), Ok(Value::from(2.0)));
+    assert_eq!(eval("float(false)"), Ok(Value::from(0.0)));
+    assert_eq!(eval("float(\"2.5\")"), Ok(Value::from(2.5)));
+    assert_eq!(eval("bool(0)"), Ok(Value::from(false)));
+    assert_eq!(eval("bool(-0.5)"), Ok(Value::from(true)));
+    assert_eq!(eval("bool(\"true\")"), Ok(Value::from(true)));
+    assert_eq!(eval("str(\"abc\")"), Ok(Value::from("abc")));
+    assert_eq!(eval("str(12)"), Ok(Value::from("12")));
+    assert_eq!(eval("str(true) + str(1.5)"), Ok(Value::from("true1.5")));
+    assert_eq!(eval("int(str(-12))"), Ok(Value::from(-12)));
+
+    assert_eq!(
+        eval("int(\"abc\")"),
+        Err(EvalexprError::conversion_error(
+            Value::from("abc"),
+            ValueType::Int
+        ))
+    );
+    assert_eq!(
+        eval("int(math::sqrt(-1))").map_err(|error| matches!(
+            error,
+            EvalexprError::ConversionError {
+                target: ValueType::Int,
+                ..
+            }
+        )),
+        Err(true)
+    );
+    #[cfg(not(feature = "bigint"))]
+    assert_eq!(
+        eval("int(1e19)"),
+        Err(EvalexprError::conversion_error(
+            Value::from(1e19),
+            ValueType::Int
+        ))
+    );
+    assert_eq!(
+        eval("float(\"1,5\")"),
+        Err(EvalexprError::conversion_error(
+            Value::from("1,5"),
+            ValueType::Float
+        ))
+    );
+    assert_eq!(
+        eval("bool(\"yes\")"),
+        Err(EvalexprError::conversion_error(
+            Value::from("yes"),
+            ValueType::Boolean
+        ))
+    );
+    assert_eq!(
+        eval("int(())"),
+        Err(EvalexprError::conversion_error(
+            Value::Empty,
+            ValueType::Int
+        ))
+    );
+    assert_eq!(
+        eval("int(\"abc\")").unwrap_err().to_string(),
+        "Cannot convert \"abc\" into a value of type Int"
+    );
+}
+
+#[test]
+fn test_simplify() {
+    let simplify = |expression: &str, context: &HashMapContext| {
+        build_operator_tree(expression)
+            .unwrap()
+            .simplify(context)
+            .to_string()
+    };
+    let context = context_map! {
+        "y" => 4,
+        "s" => "abc",
+        "f" => Function::new(|argument| Ok(argument.clone())),
+    }
+    .unwrap();
+    let empty = HashMapContext::new();
+
+    assert_eq!(simplify("2 * 3 + x", &empty), "6 + x");
+    assert_eq!(simplify("x + 2 * 3", &empty), "x + 6");
+    assert_eq!(simplify("x * y + y ^ 2", &context), "x * 4 + 16.0");
+    assert_eq!(
+        simplify("str::len(s) + 1", &context),
+        "str::len(\"abc\") + 1"
+    );
+    assert_eq!(
+        simplify("(1, 2 + 3), [y, -y]", &context),
+        "((1, 5), [4, -4])"
+    );
+    assert_eq!(simplify("1 < 2 < 3", &empty), "true");
+    assert_eq!(simplify("1 < 2 < x", &empty), "1 < 2 < x");
+    assert_eq!(simplify("y < x < 5 * 2", &context), "4 < x < 10");
+    assert_eq!(simplify("true && x || !false", &empty), "true && x || true");
+    assert_eq!(simplify("f(1 + 1)", &context), "f(2)");
+    assert_eq!(simplify("", &empty), "()");
+
+    // Errors are kept so they occur during evaluation.
+    assert_eq!(simplify("x + 1 / 0", &empty), "x + 1 / 0");
+    assert_eq!(simplify("false && 1 / 0", &empty), "false && 1 / 0");
+
+    // Assigned variables are not replaced.
+    assert_eq!(simplify("y = y + 1; y * 2", &context), "y = y + 1; y * 2");
+    assert_eq!(simplify("z = y + 1; z * 2", &context), "z = 5; z * 2");
+
+    let tree = build_operator_tree("a = x * y; a + y").unwrap();
+    let simplified = tree.simplify(&context);
+    let mut evaluation_context = context_map! { "x" => 3 }.unwrap();
+    assert_eq!(
+        simplified.eval_with_context_mut(&mut evaluation_context),
+        Ok(Value::from(16))
+    );
+}
+
+#[test]
+fn test_compiled_expression() {
+    let mut context = context_map! {
+        "a" => 3,
+        "b" => 2.5,
+        "s" => "text",
+        "t" => Value::from(vec![Value::from(1), Value::from(2)]),
+        "f" => Function::new(|argument| Ok(Value::from(argument.as_int()? * 2))),
+    }
+    .unwrap();
+    let expressions = [
+        "",
+        "()",
+        "1 + 2 * 3",
+        "a * b - 4 / 2",
+        "-a ^ 2 % 5",
+        "(1, (2, 3)), a",
+        "[a, [b], ()][1][0]",
+        "s + \"!\" == \"text!\"",
+        "f(a) + f 1",
+        "max(a, b, 4) + min(t)",
+        "1 < a <= 3 < b",
+        "1 < a < 2 < x",
+        "a > 1 && b < 1 || !false",
+        "false && x || true",
+        "true || x",
+        "1 && true",
+        "if(a > 2, \"big\", x)",
+        "if(a > 5, x, if(b > 2, 1, 2))",
+        "if(a, 1, 2)",
+        "if(true, 1)",
+        "x + 1",
+        "1 / 0",
+        "a = 1",
+        "1; 2; a",
+        "1; 2;",
+        "a.b",
+        "typeof(t)",
+        "|x|",
+        "||",
+        "|a, b|",
+    ];
+    let check = |context: &HashMapContext| {
+        for expression in expressions {
+            let tree = build_operator_tree(expression).unwrap();
+            assert_eq!(
+                tree.compile().eval_with_context(context),
+                tree.eval_with_context(context),
+                "{}",
+                expression
+            );
+        }
+    };
+
+    check(&context);
+    // The builtin `if` is only evaluated lazily if it is not overridden.
+    context
+        .set_function(
+            "if".into(),
+            Function::new(|argument| Ok(argument.as_fixed_len_tuple(3)?[2].clone())),
+        )
+        .unwrap();
+    check(&context);
+    context.set_builtin_functions_disabled(true).unwrap();
+    check(&context);
+
+    // Lambdas without a body fail instead of panicking.
+    for expression in ["|x|", "||", "|a, b|"] {
+        let compiled = build_operator_tree(expression).unwrap().compile();
+        let error = EvalexprError::WrongOperatorArgumentAmount {
+            expected: 1,
+            actual: 0,
+        };
+        assert_eq!(compiled.eval_with_context(&context), Err(error.clone()));
+        assert_eq!(
+            compiled.eval_columns(&[("x", Column::Int(&[1, 2]))], &context),
+            Err(error)
+        );
+    }
+
+    let compiled = build_operator_tree("if(x > 0, x, -x)").unwrap().compile();
+    for x in -3..3 {
+        let context = context_map! { "x" => x }.unwrap();
+        assert_eq!(
+            compiled.eval_with_context(&context),
+            Ok(Value::from(x.abs()))
+        );
+    }
+}
+
+#[test]
+fn test_node_builder() {
+    let tree = Node::chain(vec![
+        Node::assign(
+            "a",
+            Node::neg(Node::exp(Node::variable("x"), Node::constant(2))),
+        ),
+        Node::call(
+            "if",
+            vec![
+                Node::and(
+                    Node::not(Node::constant(false)),
+                    Node::or(Node::variable("flag"), Node::variable("missing")),
+                ),
+                Node::tuple(vec![
+                    Node::sub(Node::variable("a"), Node::constant(1)),
+                    Node::index(
+                        Node::array(vec![Node::constant("s"), Node::constant(1.5)]),
+                        Node::modulo(Node::constant(3), Node::constant(2)),
+                    ),
+                ]),
+                Node::variable("missing"),
+            ],
+        ),
+    ]);
+    assert_eq!(
+        tree.to_string(),
+        "a = -(x ^ 2); if(!false && (flag || missing), (a - 1, [\"s\", 1.5][3 % 2]), missing)"
+    );
+    let mut context = context_map! { "x" => 3, "flag" => true }.unwrap();
+    let expected = Value::Tuple(vec![Value::from(-10.0), Value::from(1.5)]);
+    assert_eq!(
+        tree.eval_with_context_mut(&mut context),
+        Ok(expected.clone())
+    );
+    assert_eq!(
+        build_operator_tree(&tree.to_string())
+            .unwrap()
+            .eval_with_context_mut(&mut context),
+        Ok(expected)
+    );
+
+    assert_eq!(Node::call("f", vec![]).to_string(), "f()");
+
+    // Displayed trees evaluate to the same values when parsed again, including tuples that have no literal.
+    let trees = [
+        Node::constant(Value::Tuple(vec![])),
+        Node::constant(Value::Tuple(vec![Value::from(1)])),
+        Node::constant(Value::Tuple(vec![Value::Tuple(vec![Value::Empty])])),
+        Node::tuple(vec![]),
+        Node::tuple(vec![Node::constant(1)]),
+        Node::tuple(vec![Node::tuple(vec![
+            Node::constant(1),
+            Node::constant(2),
+        ])]),
+        Node::call("len", vec![Node::tuple(vec![Node::constant("a")])]),
+        Node::neg(Node::index(
+            Node::tuple(vec![Node::constant(1)]),
+            Node::constant(0),
+        )),
+        Node::add(
+            Node::constant(Value::Tuple(vec![Value::from(1)])),
+            Node::tuple(vec![]),
+        ),
+    ];
+    for tree in trees {
+        let displayed = tree.to_string();
+        let reparsed = build_operator_tree(&displayed).unwrap();
+        assert_eq!(reparsed.eval(), tree.eval(), "displayed as {}", displayed);
+        assert_eq!(
+            reparsed.to_string(),
+            build_operator_tree(&reparsed.to_string())
+                .unwrap()
+                .to_string()
+        );
+    }
+    assert_eq!(
+        Node::tuple(vec![Node::variable("x")]).to_string(),
+        "(x, ())[0..1]"
+    );
+    assert_eq!(
+        Node::call("typeof", vec![Node::constant(1)]).eval(),
+        Ok(Value::from("int"))
+    );
+    assert_eq!(
+        Node::div(
+            Node::mul(Node::constant(6), Node::constant(2)),
+            Node::add(Node::constant(1), Node::constant(2))
+        )
+        .eval(),
+        Ok(Value::from(4))
+    );
+
+    // Operations with arbitrary operators check the amount of children.
+    let comparison = Node::operation(
+        Operator::Lt,
+        vec![
+            Node::operation(Operator::Lt, vec![Node::constant(1), Node::constant(2)]).unwrap(),
+            Node::constant(true),
+        ],
+    )
+    .unwrap();
+    assert_eq!(comparison.to_string(), "(1 < 2) < true");
+    assert_eq!(
+        comparison.eval(),
+        Err(EvalexprError::expected_number_or_string(Value::from(true)))
+    );
+    assert_eq!(
+        Node::operation(Operator::RootNode, vec![]).unwrap().eval(),
+        Ok(Value::Empty)
+    );
+    assert_eq!(
+        Node::operation(Operator::Add, vec![Node::constant(1)]),
+        Err(EvalexprError::WrongOperatorArgumentAmount {
+            expected: 2,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        Node::operation(
+            Operator::Const {
+                value: Value::from(1)
+            },
+            vec![Node::constant(2)]
+        ),
+        Err(EvalexprError::WrongOperatorArgumentAmount {
+            expected: 0,
+            actual: 1
+        })
+    );
+}
+
+#[test]
+fn test_node_walk() {
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Visitor for Recorder {
+        fn enter(&mut self, node: &Node) -> bool {
+            self.events.push(format!("enter {}", node));
+            // Skip the arguments of function calls.
+            !matches!(node.operator(), Operator::FunctionIdentifier { .. })
+        }
+
+        fn leave(&mut self, node: &Node) {
+            self.events.push(format!("leave {}", node));
+        }
+    }
+
+    let tree = Node::add(
+        Node::mul(Node::variable("a"), Node::constant(2)),
+        Node::call("f", vec![Node::variable("b")]),
+    );
+    let mut recorder = Recorder { events: Vec::new() };
+    tree.walk(&mut recorder);
+    assert_eq!(
+        recorder.events,
+        [
+            "enter a * 2 + f(b)",
+            "enter a * 2",
+            "enter a",
+            "leave a",
+            "enter 2",
+            "leave 2",
+            "leave a * 2",
+            "enter f(b)",
+            "leave f(b)",
+            "leave a * 2 + f(b)",
+        ]
+    );
+
+    // Skipping the root node skips the whole tree.
+    let mut visited = 0;
+    tree.walk(&mut |_: &Node| {
+        visited += 1;
+        false
+    });
+    assert_eq!(visited, 1);
+
+    // Reject expressions that use assignments.
+    let uses_assignment = |expression: &str| {
+        let mut found = false;
+        build_operator_tree(expression)
+            .unwrap()
+            .walk(&mut |node: &Node| {
+                found |= matches!(node.operator(), Operator::Assign);
+                !found
+            });
+        found
+    };
+    assert!(uses_assignment("a = 1; a"));
+    assert!(uses_assignment("f(1, (b = 2))"));
+    assert!(!uses_assignment("a == 1"));
+
+    // Deep trees do not overflow the stack.
+    let mut tree = Node::constant(1);
+    for _ in 0..100_000 {
+        tree = Node::neg(tree);
+    }
+    let mut negations = 0;
+    tree.walk(&mut |node: &Node| {
+        negations += usize::from(node.operator() == &Operator::Neg);
+        true
+    });
+    assert_eq!(negations, 100_000);
+}
+
+#[test]
+fn test_substitute_and_rename_variables() {
+    let tree = build_operator_tree("x * (y + x) > limit.max").unwrap();
+    let substituted = tree.substitute_variable("x", Value::from(-2));
+    assert_eq!(substituted.to_string(), "-2 * (y + -2) > limit.max");
+    // The original tree is unchanged.
+    assert_eq!(tree.to_string(), "x * (y + x) > limit.max");
+    let context = context_map! { "y" => 3, "limit.max" => 0 }.unwrap();
+    assert_eq!(
+        substituted.eval_with_context(&context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(
+        substituted
+            .substitute_variable("limit.max", Value::from(-3))
+            .substitute_variable("y", Value::from(1))
+            .eval(),
+        Ok(Value::from(true))
+    );
+    // Dot paths into the substituted variable are not replaced.
+    assert_eq!(
+        build_operator_tree("limit + limit.max")
+            .unwrap()
+            .substitute_variable("limit", Value::from(1))
+            .to_string(),
+        "1 + limit.max"
+    );
+
+    // Variables at the root of a tree are replaced as well.
+    assert_eq!(
+        Node::variable("x")
+            .substitute_variable("x", Value::from("s"))
+            .eval(),
+        Ok(Value::from("s"))
+    );
+    assert_eq!(
+        Node::variable("x").rename_variable("x", "y").to_string(),
+        "y"
+    );
+
+    let tree = build_operator_tree("count += step; count(step)").unwrap();
+    let renamed = tree
+        .rename_variable("count", "total")
+        .rename_variable("missing", "other");
+    assert_eq!(renamed.to_string(), "total += step; count(step)");
+    let mut context = context_map! {
+        "total" => 1,
+        "step" => 2,
+        "count" => Function::new(|argument| Ok(Value::from(argument.as_int()? * 10))),
+    }
+    .unwrap();
+    assert_eq!(
+        renamed.eval_with_context_mut(&mut context),
+        Ok(Value::from(20))
+    );
+    assert_eq!(context.get_value("total"), Some(&Value::from(3)));
+
+    // Parameters of lambdas and let-bound variables shadow the renamed variable.
+    let tree = build_operator_tree("f = |x| x + 1; f(x)").unwrap();
+    assert_eq!(
+        tree.rename_variable("x", "q").to_string(),
+        "f = |x| x + 1; f(q)"
+    );
+    let tree = build_operator_tree("map((5, 6), |x| x + y)").unwrap();
+    assert_eq!(
+        tree.rename_variable("x", "q").to_string(),
+        "map((5, 6), |x| x + y)"
+    );
+    assert_eq!(
+        tree.rename_variable("y", "q").to_string(),
+        "map((5, 6), |x| x + q)"
+    );
+    let tree = build_operator_tree("x; let x = x + 1; x").unwrap();
+    assert_eq!(
+        tree.rename_variable("x", "q").to_string(),
+        "q; let x = q + 1; x"
+    );
+}
+
+#[test]
+fn test_node_equality_and_hash() {
+    use std::collections::{hash_map::DefaultHasher, HashSet};
+    use std::hash::{Hash, Hasher};
+
+    fn hash(node: &Node) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        node.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let tree = build_operator_tree("a + f(1, 2.5) * [\"s\"][0]").unwrap();
+    let same = build_operator_tree("a+f(1,2.5)*[\"s\"][0] // comment").unwrap();
+    assert_eq!(tree, same);
+    assert_eq!(hash(&tree), hash(&same));
+
+    for other in [
+        "b + f(1, 2.5) * [\"s\"][0]",
+        "a + g(1, 2.5) * [\"s\"][0]",
+        "a + f(1, 2.4) * [\"s\"][0]",
+        "a + f(1, 2.5) / [\"s\"][0]",
+        "a + f(1, 2.5, 3) * [\"s\"][0]",
+        "a + (f(1, 2.5) * [\"s\"][0])",
+        "a + f(1, 2.5) * (\"s\", 0)",
+    ] {
+        assert_ne!(tree, build_operator_tree(other).unwrap(), "{}", other);
+    }
+
+    // Constants are compared by their representation.
+    assert_eq!(Node::constant(f64::NAN), Node::constant(f64::NAN),);
+    assert_ne!(Node::constant(0.0), Node::constant(-0.0));
+    assert_ne!(Node::constant(1), Node::constant(1.0));
+    assert_ne!(Node::variable("a"), Node::call("a", vec![]));
+    // Values keep their usual equality.
+    assert_ne!(Value::Float(f64::NAN), Value::Float(f64::NAN));
+    assert_eq!(Value::Float(0.0), Value::Float(-0.0));
+
+    let mut cache = HashSet::new();
+    for expression in ["1 + x", "1+x", "(1 + x)", "x + 1", "0.0 / 0.0", "0.0/0.0"] {
+        cache.insert(
+            build_operator_tree(expression)
+                .unwrap()
+                .simplify(&EmptyContext),
+        );
+    }
+    assert_eq!(cache.len(), 4);
+    assert!(cache.contains(&Node::constant(f64::NAN)));
+
+    // Deep trees do not overflow the stack.
+    let deep_tree = |depth| {
+        let mut tree = Node::constant(1);
+        for _ in 0..depth {
+            tree = Node::not(tree);
+        }
+        tree
+    };
+    assert!(deep_tree(100_000) == deep_tree(100_000));
+    assert_eq!(hash(&deep_tree(100_000)), hash(&deep_tree(100_000)));
+    assert!(deep_tree(100_000) != deep_tree(100_001));
+}
+
+#[test]
+fn test_division_by_zero() {
+    let mut context = context_map! { "a" => 7 }.unwrap();
+    assert_eq!(
+        eval_with_context("a / 0", &context),
+        Err(EvalexprError::DivisionByZero {
+            dividend: Value::from(7)
+        })
+    );
+    assert_eq!(
+        eval_with_context("a % 0", &context),
+        Err(EvalexprError::DivisionByZero {
+            dividend: Value::from(7)
+        })
+    );
+    // Floats are divided as usual.
+    assert_eq!(eval("1.0 / 0"), Ok(Value::from(f64::INFINITY)));
+    assert!(eval_float("1 % 0.0").unwrap().is_nan());
+
+    let options = EvalOptions::new().with_division_by_zero(DivisionByZeroMode::Empty);
+    assert_eq!(options.division_by_zero(), DivisionByZeroMode::Empty);
+    assert_eq!(
+        eval_with_context_and_options("(a / 0, a % 0, a / 2)", &context, &options),
+        Ok(Value::Tuple(vec![
+            Value::Empty,
+            Value::Empty,
+            Value::from(3)
+        ]))
+    );
+    assert_eq!(
+        eval_with_context_and_options("typeof(1 / 0)", &context, &options),
+        Ok(Value::from("empty"))
+    );
+
+    let options = EvalOptions::new().with_division_by_zero(DivisionByZeroMode::Float);
+    assert_eq!(
+        eval_with_context_and_options("(a / 0, -a / 0)", &context, &options),
+        Ok(Value::Tuple(vec![
+            Value::from(f64::INFINITY),
+            Value::from(f64::NEG_INFINITY)
+        ]))
+    );
+    assert!(eval_with_context_and_options("0 / 0", &context, &options)
+        .unwrap()
+        .as_float()
+        .unwrap()
+        .is_nan());
+    assert!(eval_with_context_and_options("a % 0", &context, &options)
+        .unwrap()
+        .as_float()
+        .unwrap()
+        .is_nan());
+    // Other errors are not affected.
+    assert_eq!(
+        eval_with_context_and_options("true / 0", &context, &options),
+        Err(EvalexprError::expected_number(Value::from(true)))
+    );
+    // Assignments cannot change the type of a variable, so they still fail.
+    assert_eq!(
+        eval_with_context_mut_and_options("a /= 0", &mut context, &options),
+        Err(EvalexprError::DivisionByZero {
+            dividend: Value::from(7)
+        })
+    );
+}
+
+#[test]
+fn test_strict_number_types() {
+    let mut context = context_map! { "a" => 2, "b" => 0.5 }.unwrap();
+    let options = EvalOptions::new().with_strict_number_types(true);
+    assert!(!EvalOptions::new().strict_number_types());
+    assert!(options.strict_number_types());
+
+    assert_eq!(eval_with_context("a + b", &context), Ok(Value::from(2.5)));
+    assert_eq!(
+        eval_with_context_and_options("a + b", &context, &options),
+        Err(EvalexprError::wrong_type_combination(
+            Operator::Add,
+            vec![ValueType::Int, ValueType::Float]
+        ))
+    );
+    for expression in [
+        "a - b", "b * a", "a / b", "a % b", "b ^ a", "a < b", "b >= a",
+    ] {
+        assert!(
+            matches!(
+                eval_with_context_and_options(expression, &context, &options),
+                Err(EvalexprError::WrongTypeCombination { .. })
+            ),
+            "{}",
+            expression
+        );
+    }
+    // Every comparison of a chain is checked.
+    assert_eq!(
+        eval_with_context_and_options("0 < a < b", &context, &options),
+        Err(EvalexprError::wrong_type_combination(
+            Operator::Lt,
+            vec![ValueType::Int, ValueType::Float]
+        ))
+    );
+
+    // Numbers of the same type and explicit conversions are allowed.
+    assert_eq!(
+        eval_with_context_and_options(
+            "(a + 3, b * 2.0, float(a) + b, a + int(b))",
+            &context,
+            &options
+        ),
+        Ok(Value::from(vec![
+            Value::from(5),
+            Value::from(1.0),
+            Value::from(2.5),
+            Value::from(2)
+        ]))
+    );
+    assert_eq!(
+        eval_with_context_and_options("1 < a <= 2", &context, &options),
+        Ok(Value::from(true))
+    );
+    // Equality and string concatenation are not affected.
+    assert_eq!(
+        eval_with_context_and_options("(a == b, \"a\" + \"b\")", &context, &options),
+        Ok(Value::from(vec![Value::from(false), Value::from("ab")]))
+    );
+    assert_eq!(
+        eval_with_context_mut_and_options("c = a * b", &mut context, &options),
+        Err(EvalexprError::wrong_type_combination(
+            Operator::Mul,
+            vec![ValueType::Int, ValueType::Float]
+        ))
+    );
+}
+
+#[test]
+fn test_integer_exponentiation() {
+    let context = context_map! { "a" => 3, "f" => 2.0 }.unwrap();
+    let options = EvalOptions::new().with_integer_exponentiation(true);
+    assert!(!EvalOptions::new().integer_exponentiation());
+    assert!(options.integer_exponentiation());
+
+    assert_eq!(
+        eval_with_context("2 ^ 10", &context),
+        Ok(Value::from(1024.0))
+    );
+    assert_eq!(
+        eval_with_context_and_options("2 ^ 10", &context, &options),
+        Ok(Value::from(1024))
+    );
+    assert_eq!(
+        eval_with_context_and_options("a ^ 0 + (-a) ^ 3", &context, &options),
+        Ok(Value::from(-26))
+    );
+    assert_eq!(
+        eval_with_context_and_options("2 ^ 3 ^ 2", &context, &options),
+        Ok(Value::from(512))
+    );
+    // Negative exponents and floats still result in floats.
+    assert_eq!(
+        eval_with_context_and_options("2 ^ -2", &context, &options),
+        Ok(Value::from(0.25))
+    );
+    assert_eq!(
+        eval_with_context_and_options("f ^ 2", &context, &options),
+        Ok(Value::from(4.0))
+    );
+    assert_eq!(
+        eval_with_context_and_options("a ^ 2.0", &context, &options),
+        Ok(Value::from(9.0))
+    );
+    assert_eq!(
+        eval_with_context_and_options("0 ^ 0", &context, &options),
+        Ok(Value::from(1))
+    );
+
+    assert_eq!(
+        eval_with_context_and_options("2 ^ 63", &context, &options),
+        Err(EvalexprError::ExponentiationError {
+            base: Value::from(2),
+            exponent: Value::from(63)
+        })
+    );
+    assert_eq!(
+        eval_with_context_and_options("(-2) ^ 63", &context, &options),
+        Ok(Value::from(IntType::MIN))
+    );
+    assert_eq!(
+        eval_with_context_and_options("(1 ^ 5000000000, (-1) ^ 5000000001)", &context, &options),
+        Ok(Value::from(vec![Value::from(1), Value::from(-1)]))
+    );
+    assert_eq!(
+        eval_with_context_and_options("2 ^ 5000000000", &context, &options),
+        Err(EvalexprError::ExponentiationError {
+            base: Value::from(2),
+            exponent: Value::from(5_000_000_000_i64)
+        })
+    );
+    assert_eq!(
+        EvalexprError::ExponentiationError {
+            base: Value::from(2),
+            exponent: Value::from(63)
+        }
+        .to_string(),
+        "Error exponentiating 2 ^ 63"
+    );
+
+    let tree = build_operator_tree("a ^ 2").unwrap();
+    assert_eq!(
+        tree.eval_with_context_and_options(&context, &options)
+            .and_then(|value| value.as_int()),
+        Ok(9)
+    );
+}
+
+#[test]
+fn test_lenient_evaluation() {
+    let mut context = context_map! { "a" => 2, "e" => () }.unwrap();
+    let options = EvalOptions::new().with_lenient(true);
+    assert!(!EvalOptions::new().lenient());
+    assert!(options.lenient());
+
+    assert_eq!(
+        eval_with_context_and_options("missing", &context, &options),
+        Ok(Value::Empty)
+    );
+    assert_eq!(
+        eval_with_context_and_options("(missing, a)", &context, &options),
+        Ok(Value::from(vec![Value::Empty, Value::from(2)]))
+    );
+    assert_eq!(
+        eval_with_context_and_options("len(missing)", &context, &options),
+        eval_with_context("len(())", &context)
+    );
+    assert_eq!(
+        eval_with_context("missing", &context),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "missing".into(),
+            suggestions: Vec::new(),
+            span: Some(Span::new(0, 7)),
+        })
+    );
+
+    // Ordering comparisons with empty operands are false, also in chains.
+    for expression in [
+        "missing > 1",
+        "missing < 1",
+        "1 >= e",
+        "e <= e",
+        "0 < missing < 3",
+        "a < 3 <= missing",
+    ] {
+        assert_eq!(
+            eval_with_context_and_options(expression, &context, &options),
+            Ok(Value::from(false)),
+            "{}",
+            expression
+        );
+    }
+    assert_eq!(
+        eval_with_context_and_options("missing == () && missing != 1", &context, &options),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context_and_options("a > 1 && !(missing > 1)", &context, &options),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context_and_options("missing ?? a", &context, &options),
+        Ok(Value::from(2))
+    );
+    assert!(eval_with_context("e > 1", &context).is_err());
+
+    // Other errors, like arithmetic with empty values or unknown functions, are not suppressed.
+    assert!(eval_with_context_and_options("missing + 1", &context, &options).is_err());
+    assert!(matches!(
+        eval_with_context_and_options("unknown(1)", &context, &options),
+        Err(EvalexprError::FunctionIdentifierNotFound { .. })
+    ));
+
+    assert_eq!(
+        eval_with_context_mut_and_options("b = missing; b == ()", &mut context, &options),
+        Ok(Value::from(true))
+    );
+}
+
+#[test]
+fn test_keyword_operators() {
+    let context = context_map! { "a" => true, "b" => false, "x" => 3 }.unwrap();
+    let keywords = ParserConfig::new().with_keyword_operators(true);
+    let eval_keywords = |expression: &str| {
+        build_operator_tree_with_config(expression, &keywords)
+            .and_then(|tree| tree.eval_with_context(&context))
+    };
+
+    assert_eq!(eval_keywords("a and b"), Ok(Value::from(false)));
+    assert_eq!(eval_keywords("a or b"), Ok(Value::from(true)));
+    assert_eq!(eval_keywords("not b"), Ok(Value::from(true)));
+    assert_eq!(eval_keywords("not(b)"), Ok(Value::from(true)));
+    assert_eq!(
+        eval_keywords("x > 1 and not (x > 5) or b"),
+        eval_with_context("x > 1 && !(x > 5) || b", &context)
+    );
+    assert_eq!(
+        build_operator_tree_with_config("a and b or not a", &keywords),
+        build_operator_tree("a && b || !a")
+    );
+    // Only whole words are keywords.
+    assert_eq!(
+        build_operator_tree_with_config("android + orange + nothing + AND", &keywords)
+            .unwrap()
+            .iter_read_variable_identifiers()
+            .collect::<Vec<_>>(),
+        vec!["android", "orange", "nothing", "AND"]
+    );
+
+    // By default, keywords are identifiers.
+    assert!(eval_with_context("a and b", &context).is_err());
+    let context_with_keyword = context_map! { "and" => 1 }.unwrap();
+    assert_eq!(
+        eval_with_context("and + 1", &context_with_keyword),
+        Ok(Value::from(2))
+    );
+
+    let neq = ParserConfig::new().with_angle_bracket_neq(true);
+    assert!(!ParserConfig::new().angle_bracket_neq());
+    assert!(neq.angle_bracket_neq());
+    assert_eq!(
+        build_operator_tree_with_config("x <> 3", &neq),
+        build_operator_tree("x != 3")
+    );
+    assert_eq!(
+        build_operator_tree_with_config("x <> 2", &neq)
+            .unwrap()
+            .eval_with_context(&context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        build_operator_tree_with_config("x <= 2", &neq)
+            .unwrap()
+            .eval_with_context(&context),
+        Ok(Value::from(false))
+    );
+    assert_ne!(build_operator_tree("x <> 3"), build_operator_tree("x != 3"));
+}
+
+#[test]
+fn test_lambdas() {
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_with_context_mut("f = |x| x * 2; f(21)", &mut context),
+        Ok(Value::from(42))
+    );
+    assert_eq!(
+        eval_with_context("f(f(1)) + f(2)", &context),
+        Ok(Value::from(8))
+    );
+    assert_eq!(
+        eval_with_context_mut("g = |a, b| a - b; g(5, 2)", &mut context),
+        Ok(Value::from(3))
+    );
+    assert_eq!(
+        eval_with_context_mut("h = || 7; h()", &mut context),
+        Ok(Value::from(7))
+    );
+    assert_eq!(
+        eval_with_context("typeof(f)", &context),
+        Ok(Value::from("lambda"))
+    );
+    assert_eq!(
+        eval_with_context("g(1)", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 2,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        eval_with_context("f()", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 1,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        eval_with_context("f(1, 2)", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 1,
+            actual: 2
+        })
+    );
+    assert_eq!(
+        eval_with_context("h(1)", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 0,
+            actual: 1
+        })
+    );
+    // Lambda expressions cannot be called directly.
+    assert_eq!(
+        eval("(|x| x + 1)(2)"),
+        Err(EvalexprError::MissingOperatorOutsideOfBrace)
+    );
+
+    // The body extends up to the next comma or semicolon.
+    assert_eq!(
+        eval_with_context("(|x| x + 1, 2)", &context),
+        Ok(Value::from(vec![
+            Lambda::new(
+                vec!["x".into()],
+                build_operator_tree("x + 1").unwrap().children()[0].clone()
+            )
+            .into(),
+            Value::from(2)
+        ]))
+    );
+    for expression in [
+        "f = |x| x * 2; f(21)",
+        "|a, b| (a, b)",
+        "a + (|x| x) + -(|x| x)",
+        "|| |x| x ^ 2",
+    ] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(tree.to_string(), expression);
+        assert_eq!(build_operator_tree(&tree.to_string()), Ok(tree));
+    }
+
+    // Lambdas read variables from the context when they are called, but cannot assign to them.
+    let mut context = context_map! { "offset" => 10 }.unwrap();
+    eval_empty_with_context_mut("shift = |x| x + offset", &mut context).unwrap();
+    assert_eq!(eval_with_context("shift(1)", &context), Ok(Value::from(11)));
+    context.set_value("offset".into(), 20.into()).unwrap();
+    assert_eq!(eval_with_context("shift(1)", &context), Ok(Value::from(21)));
+    assert_eq!(
+        eval_with_context_mut("a = 1; f = |x| x + a; a = 2; f(0)", &mut context),
+        Ok(Value::from(2))
+    );
+    assert_eq!(
+        eval_with_context_mut("set = |x| offset = x; set(1)", &mut context),
+        Err(EvalexprError::ContextNotMutable)
+    );
+
+    // Parameters shadow variables, and nested lambdas capture the arguments of the outer lambda.
+    assert_eq!(
+        eval_with_context_mut(
+            "add = |a| |b| a + b; add_two = add(2); add_two(3) + offset",
+            &mut context
+        ),
+        Ok(Value::from(25))
+    );
+    assert_eq!(
+        eval_with_context_mut("double = |offset| offset * 2; double(3)", &mut context),
+        Ok(Value::from(6))
+    );
+
+    // Lambdas can be passed as arguments and called through parameters.
+    assert_eq!(
+        eval_with_context_mut("apply = |f, x| f(x); apply(|y| y * 3, 4)", &mut context),
+        Ok(Value::from(12))
+    );
+
+    // Lambdas take precedence over builtin functions, but not over functions of the context.
+    assert_eq!(
+        eval_with_context_mut("max = |a, b| a; max(1, 2)", &mut context),
+        Ok(Value::from(1))
+    );
+    context
+        .set_function("min".into(), Function::new(|_| Ok(Value::from("min"))))
+        .unwrap();
+    assert_eq!(
+        eval_with_context_mut("min = |a, b| a; min(1, 2)", &mut context),
+        Ok(Value::from("min"))
+    );
+
+    // Recursion works, but is limited in depth.
+    assert_eq!(
+        eval_with_context_mut(
+            "fact = |n| if(n <= 1, 1, n * fact(n - 1)); fact(10)",
+            &mut context
+        ),
+        Ok(Value::from(3628800))
+    );
+    eval_empty_with_context_mut("sum = |n| if(n == 0, 0, n + sum(n - 1))", &mut context).unwrap();
+    let max_call_depth = Lambda::MAX_CALL_DEPTH as i64;
+    assert_eq!(
+        eval_with_context(&format!("sum({})", max_call_depth - 1), &context),
+        Ok(Value::from(max_call_depth * (max_call_depth - 1) / 2))
+    );
+    assert_eq!(
+        eval_with_context(&format!("sum({})", max_call_depth), &context),
+        Err(EvalexprError::RecursionLimitExceeded {
+            max_depth: Lambda::MAX_CALL_DEPTH
+        })
+    );
+
+    // Compiled expressions and the Rust API.
+    let compiled = build_operator_tree("f = |x| x * 2; f(21)")
+        .unwrap()
+        .compile();
+    assert_eq!(
+        compiled.eval_with_context(&HashMapContext::new()),
+        Err(EvalexprError::ContextNotMutable)
+    );
+    assert_eq!(
+        build_operator_tree("fact(5)")
+            .unwrap()
+            .compile()
+            .eval_with_context(&context),
+        Ok(Value::from(120))
+    );
+    let lambda = Lambda::new(
+        vec!["x".into()],
+        Node::mul(Node::variable("x"), Node::variable("offset")),
+    );
+    assert_eq!(lambda.to_string(), "|x| x * offset");
+    assert_eq!(lambda.call(&context, &Value::from(2)), Ok(Value::from(40)));
+    context.set_value("triple".into(), lambda.into()).unwrap();
+    assert_eq!(
+        eval_with_context("triple(3)", &context),
+        Ok(Value::from(60))
+    );
+
+    // Parameters are not replaced when simplifying.
+    let tree = build_operator_tree("|offset| offset + offset * 2").unwrap();
+    assert_eq!(
+        tree.simplify(&context).to_string(),
+        "|offset| offset + offset * 2"
+    );
+    assert_eq!(
+        build_operator_tree("|x| x + offset * 2")
+            .unwrap()
+            .simplify(&context)
+            .to_string(),
+        "|x| x + 40"
+    );
+}
+
+#[test]
+fn test_higher_order_builtin_functions() {
+    let tuple =
+        |values: &[IntType]| Value::Tuple(values.iter().copied().map(Value::from).collect());
+    assert_eq!(eval("map((1, 2, 3), |x| x * 2)"), Ok(tuple(&[2, 4, 6])));
+    assert_eq!(
+        eval("map([1, 2], |x| x + 1)"),
+        Ok(Value::Array(vec![Value::from(2), Value::from(3)]))
+    );
+    assert_eq!(eval("map((), |x| x)"), Ok(tuple(&[])));
+    // Elements that are tuples are passed as multiple arguments.
+    assert_eq!(
+        eval("map(((1, 2), (3, 4)), |a, b| a * b)"),
+        Ok(tuple(&[2, 12]))
+    );
+    assert_eq!(
+        eval("filter((1, 2, 3, 4), |x| x % 2 == 0)"),
+        Ok(tuple(&[2, 4]))
+    );
+    assert_eq!(
+        eval("filter([\"a\", \"bc\"], |s| len(s) > 1)"),
+        Ok(Value::Array(vec![Value::from("bc")]))
+    );
+    assert_eq!(
+        eval("reduce((1, 2, 3, 4), |sum, x| sum + x, 0)"),
+        Ok(Value::from(10))
+    );
+    assert_eq!(
+        eval("reduce((\"a\", \"b\"), |s, x| s + x, \"\")"),
+        Ok(Value::from("ab"))
+    );
+    assert_eq!(eval("reduce((), |a, x| a + x, 5)"), Ok(Value::from(5)));
+    assert_eq!(eval("any((1, 5), |x| x > 4)"), Ok(Value::from(true)));
+    assert_eq!(eval("any((1, 2), |x| x > 4)"), Ok(Value::from(false)));
+    assert_eq!(eval("all((5, 6), |x| x > 4)"), Ok(Value::from(true)));
+    assert_eq!(eval("all((5, 1), |x| x > 4)"), Ok(Value::from(false)));
+    assert_eq!(eval("any((), |x| x)"), Ok(Value::from(false)));
+    assert_eq!(eval("all((), |x| x)"), Ok(Value::from(true)));
+    // The remaining elements are skipped once the result is decided.
+    assert_eq!(eval("any((1, \"a\"), |x| x == 1)"), Ok(Value::from(true)));
+    assert_eq!(eval("all((1, \"a\"), |x| x > 1)"), Ok(Value::from(false)));
+
+    let mut context = HashMapContext::new();
+    assert_eq!(
+        eval_with_context_mut(
+            "double = |x| x * 2; is_big = |x| x > 2; len(filter(map((1, 2, 3), double), is_big))",
+            &mut context
+        ),
+        Ok(Value::from(2))
+    );
+    assert_eq!(
+        eval_with_context("map((1, 2, 3), double)[1..3]", &context),
+        Ok(tuple(&[4, 6]))
+    );
+
+    assert_eq!(
+        eval("map(1, |x| x)"),
+        Err(EvalexprError::type_error(
+            Value::from(1),
+            vec![ValueType::Tuple, ValueType::Array]
+        ))
+    );
+    assert_eq!(
+        eval("map((1, 2), 3)"),
+        Err(EvalexprError::type_error(
+            Value::from(3),
+            vec![ValueType::Lambda]
+        ))
+    );
+    assert_eq!(
+        eval("filter((1, 2), |x| x)"),
+        Err(EvalexprError::expected_boolean(Value::from(1)))
+    );
+    assert!(matches!(
+        eval("reduce((1, 2), |a, x| a + x)"),
+        Err(EvalexprError::ExpectedFixedLenTuple {
+            expected_len: 3,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_let_bindings() {
+    let mut context = context_map! { "a" => 1, "b" => 5, "c" => 4 }.unwrap();
+    assert_eq!(
+        eval_with_context("let d = b*b - 4*a*c; d > 0", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context_mut("let d = b*b - 4*a*c; d", &mut context),
+        Ok(Value::from(9))
+    );
+    // The bound variables do not reach the context.
+    assert_eq!(context.get_value("d"), None);
+    assert_eq!(
+        eval_with_context_mut("let x = 2; let x = x * a + b; x", &mut context),
+        Ok(Value::from(7))
+    );
+    assert_eq!(context.get_value("x"), None);
+
+    // Let-bindings shadow variables of the context, and assignments to them stay in the chain.
+    assert_eq!(
+        eval_with_context_mut("let a = 10; a += 1; b = a; a", &mut context),
+        Ok(Value::from(11))
+    );
+    assert_eq!(context.get_value("a"), Some(&Value::from(1)));
+    assert_eq!(context.get_value("b"), Some(&Value::from(11)));
+    assert_eq!(
+        eval_with_context_mut("let a = 10; a = 1.5", &mut context),
+        Err(EvalexprError::expected_int(Value::from(1.5)))
+    );
+    // Bindings are only visible to the following expressions of the chain.
+    assert_eq!(
+        eval_with_context("(let y = 1; y); y", &context),
+        Err(variable_not_found_at("y", Span::new(16, 17)))
+    );
+    assert_eq!(eval_with_context("let y = 1", &context), Ok(Value::Empty));
+
+    // Let-bound lambdas can be called, also recursively and from the body of other lambdas.
+    assert_eq!(
+        eval_with_context(
+            "let square = |x| x * x; let sum = |n| if(n == 0, 0, square(n) + sum(n - 1)); sum(3)",
+            &context
+        ),
+        Ok(Value::from(14))
+    );
+    assert_eq!(
+        eval_with_context_mut("f = |x| (let y = x + 1; y * y); f(2)", &mut context),
+        Ok(Value::from(9))
+    );
+    assert_eq!(
+        eval_with_context_mut("let x = 3; g = |y| x + y; g(1)", &mut context),
+        Ok(Value::from(4))
+    );
+
+    // Without a following assignment, `let` is an ordinary identifier.
+    let context = context_map! { "let" => 1 }.unwrap();
+    assert_eq!(eval_with_context("let + 1", &context), Ok(Value::from(2)));
+    assert_eq!(
+        eval_with_context("let == 1", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("let let = 2; let", &context),
+        Ok(Value::from(2))
+    );
+
+    for expression in [
+        "let d = b * b - 4 * a * c; d > 0",
+        "let f = |x| x; let x = a = 1",
+    ] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(tree.to_string(), expression);
+        assert_eq!(build_operator_tree(&tree.to_string()), Ok(tree));
+    }
+    let tree = build_operator_tree("let x = x + 1; x * y").unwrap();
+    assert_eq!(
+        tree.substitute_variable("x", Value::from(2)).to_string(),
+        "let x = 2 + 1; x * y"
+    );
+    let context = context_map! { "x" => 4, "y" => 2 }.unwrap();
+    assert_eq!(
+        tree.compile().eval_with_context(&context),
+        Ok(Value::from(10))
+    );
+    assert_eq!(
+        tree.simplify(&context).eval_with_context(&context),
+        Ok(Value::from(10))
+    );
+}
+
+#[test]
+fn test_function_argument_amounts() {
+    let count = |argument: &Value| {
+        Ok(Value::from(match argument {
+            Value::Tuple(tuple) => tuple.len() as IntType,
+            Value::Empty => 0,
+            _ => 1,
+        }))
+    };
+    let context = context_map! {
+        "range" => Function::new(count).with_argument_range(1, 3),
+        "at_least_two" => Function::new(count).with_argument_range(2, usize::MAX),
+        "choice" => Function::new(count).with_argument_counts(&[0, 2, 4]),
+        "any" => Function::new(count),
+    }
+    .unwrap();
+
+    assert_eq!(eval_with_context("range(1)", &context), Ok(Value::from(1)));
+    assert_eq!(
+        eval_with_context("range(1, 2, 3)", &context),
+        Ok(Value::from(3))
+    );
+    assert_eq!(
+        eval_with_context("range()", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmountRange {
+            expected: 1..=3,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        eval_with_context("range(1, 2, 3, 4)", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmountRange {
+            expected: 1..=3,
+            actual: 4
+        })
+    );
+    assert_eq!(
+        eval_with_context("at_least_two(1, 2, 3, 4, 5)", &context),
+        Ok(Value::from(5))
+    );
+    assert_eq!(eval_with_context("choice()", &context), Ok(Value::from(0)));
+    assert_eq!(
+        eval_with_context("choice(1, 2)", &context),
+        Ok(Value::from(2))
+    );
+    assert_eq!(
+        eval_with_context("choice(1, 2, 3)", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmountChoice {
+            expected: vec![0, 2, 4],
+            actual: 3
+        })
+    );
+    assert_eq!(
+        eval_with_context("any(1, 2, 3, 4, 5, 6)", &context),
+        Ok(Value::from(6))
+    );
+
+    assert_eq!(
+        eval_with_context("range(1, 2, 3, 4)", &context)
+            .unwrap_err()
+            .to_string(),
+        "A function expected between 1 and 3 arguments, but got 4."
+    );
+    assert_eq!(
+        eval_with_context("at_least_two(1)", &context)
+            .unwrap_err()
+            .to_string(),
+        "A function expected at least 2 arguments, but got 1."
+    );
+    assert_eq!(
+        eval_with_context("choice(1)", &context)
+            .unwrap_err()
+            .to_string(),
+        "A function expected 0, 2 or 4 arguments, but got 1."
+    );
+}
+
+#[test]
+fn test_typed_functions() {
+    let context = context_map! {
+        "repeat" => Function::typed(&[ValueType::String, ValueType::Int], |arguments| {
+            Ok(Value::from(arguments[0].as_string()?.repeat(arguments[1].as_int()? as usize)))
+        }),
+        "negate" => Function::typed(&[ValueType::Boolean], |arguments| {
+            Ok(Value::from(!arguments[0].as_boolean()?))
+        }),
+        "length" => Function::typed(&[ValueType::Tuple], |arguments| {
+            Ok(Value::from(arguments[0].as_tuple()?.len() as IntType))
+        }),
+        "zero" => Function::typed(&[], |arguments| {
+            assert!(arguments.is_empty());
+            Ok(Value::from(0))
+        }),
+    }
+    .unwrap();
+
+    assert_eq!(
+        eval_with_context("repeat(\"ab\", 2)", &context),
+        Ok(Value::from("abab"))
+    );
+    assert_eq!(
+        eval_with_context("repeat(2, 2)", &context),
+        Err(EvalexprError::expected_string(Value::from(2)))
+    );
+    assert_eq!(
+        eval_with_context("repeat(\"ab\", \"2\")", &context),
+        Err(EvalexprError::expected_int(Value::from("2")))
+    );
+    assert_eq!(
+        eval_with_context("repeat(\"ab\")", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 2,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        eval_with_context("negate(true)", &context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(
+        eval_with_context("negate(1)", &context),
+        Err(EvalexprError::expected_boolean(Value::from(1)))
+    );
+    // The amount of arguments is checked before their types.
+    assert_eq!(
+        eval_with_context("negate()", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 1,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        eval_with_context("negate(true, false)", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 1,
+            actual: 2
+        })
+    );
+    // A single tuple parameter receives the whole tuple.
+    assert_eq!(
+        eval_with_context("length(1, 2, 3)", &context),
+        Ok(Value::from(3))
+    );
+    assert_eq!(
+        eval_with_context("length(1)", &context),
+        Err(EvalexprError::expected_tuple(Value::from(1)))
+    );
+    assert_eq!(
+        eval_with_context("length()", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 1,
+            actual: 0
+        })
+    );
+    assert_eq!(eval_with_context("zero()", &context), Ok(Value::from(0)));
+    assert_eq!(
+        eval_with_context("zero(1)", &context),
+        Err(EvalexprError::WrongFunctionArgumentAmount {
+            expected: 0,
+            actual: 1
+        })
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_function_errors() {
+    use std::error::Error;
+
+    #[derive(Debug, PartialEq)]
+    struct Unavailable(&'static str);
+
+    impl std::fmt::Display for Unavailable {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{} is unavailable", self.0)
+        }
+    }
+
+    impl Error for Unavailable {}
+
+    let mut context = context_map! {
+        "fetch" => Function::new(|_| Err(EvalexprError::function_error(Unavailable("database")))),
+        "twice" => Function::new(|argument| Ok(Value::from(argument.as_int()? * 2))),
+    }
+    .unwrap();
+
+    let error = eval_with_context("1 + twice(fetch(1))", &context).unwrap_err();
+    match &error {
+        EvalexprError::FunctionError { name, source } => {
+            assert_eq!(name, "fetch");
+            assert_eq!(
+                source.downcast_ref::<Unavailable>(),
+                Some(&Unavailable("database"))
+            );
+        },
+        error => panic!("unexpected error {:?}", error),
+    }
+    assert_eq!(
+        error.to_string(),
+        "Function \"fetch\" failed: database is unavailable"
+    );
+    assert_eq!(
+        error.source().map(ToString::to_string),
+        Some("database is unavailable".into())
+    );
+    assert_eq!(error.clone(), error);
+    assert_ne!(
+        EvalexprError::function_error(Unavailable("database")),
+        EvalexprError::function_error(Unavailable("database"))
+    );
+
+    // The error keeps the identifier of the function that returned it.
+    eval_empty_with_context_mut("f = |x| fetch(x)", &mut context).unwrap();
+    assert!(matches!(
+        eval_with_context("f(1)", &context),
+        Err(EvalexprError::FunctionError { name, .. }) if name == "fetch"
+    ));
+}
+
+#[test]
+fn test_sync_hash_map_context() {
+    fn assert_send_and_sync<T: Send + Sync>() {}
+    assert_send_and_sync::<Function>();
+    assert_send_and_sync::<Node>();
+    assert_send_and_sync::<CompiledExpression>();
+    assert_send_and_sync::<HashMapContext>();
+    assert_send_and_sync::<SyncHashMapContext>();
+
+    let mut context = SyncHashMapContext::from(
+        context_map! {
+            "a" => 1,
+            "double" => Function::new(|argument| Ok(Value::from(argument.as_int()? * 2))),
+        }
+        .unwrap(),
+    );
+    let copy = context.clone();
+    assert_eq!(
+        eval_with_context_mut("a = double(a); b = 5; a + b", &mut context),
+        Ok(Value::from(7))
+    );
+    // Assignments to one clone do not affect the others.
+    assert_eq!(copy.get_value("a"), Some(&Value::from(1)));
+    assert_eq!(copy.get_value("b"), None);
+    assert_eq!(
+        eval_with_context_mut("a = 2.0", &mut context),
+        Err(EvalexprError::expected_int(Value::from(2.0)))
+    );
+    let mut names: Vec<String> = context.iter_variable_names().collect();
+    names.sort();
+    assert_eq!(names, vec!["a".to_string(), "b".to_string()]);
+
+    let tree = build_operator_tree("double(a) + offset").unwrap();
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|offset| {
+                let mut context = copy.clone();
+                let tree = &tree;
+                scope.spawn(move || {
+                    context
+                        .set_value("offset".into(), Value::from(offset))
+                        .unwrap();
+                    tree.eval_with_context(&context)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    assert_eq!(
+        results,
+        (2..6)
+            .map(|result| Ok(Value::from(result)))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(copy.get_value("offset"), None);
+}
+
+#[test]
+fn test_eval_columns() {
+    let ints: [IntType; 4] = [1, 2, 3, 4];
+    let floats = [0.5, 1.5, 2.5, 3.5];
+    let flags = [true, false, true, false];
+    let names = [
+        Value::from("a"),
+        Value::from("b"),
+        Value::from("c"),
+        Value::from("d"),
+    ];
+    let columns = [
+        ("i", Column::from(&ints[..])),
+        ("f", Column::from(&floats[..])),
+        ("flag", Column::from(&flags[..])),
+        ("name", Column::from(&names[..])),
+    ];
+    let context = context_map! {
+        "i" => 100,
+        "scale" => 10,
+        "label" => Function::new(|argument| Ok(Value::from(format!("<{}>", argument.as_string()?)))),
+    }
+    .unwrap();
+
+    let tree = build_operator_tree("if(flag, i * scale, f)").unwrap();
+    assert_eq!(
+        tree.eval_columns(&columns, &context),
+        Ok(vec![
+            Value::from(10),
+            Value::from(1.5),
+            Value::from(30),
+            Value::from(3.5)
+        ])
+    );
+    // The results equal the results of evaluating each row with a context.
+    let compiled = build_operator_tree("label(name) + str::from(i + f)")
+        .unwrap()
+        .compile();
+    let results = compiled.eval_columns(&columns, &context).unwrap();
+    for (row, result) in results.iter().enumerate() {
+        let mut row_context = context.clone();
+        row_context.set_value("i".into(), ints[row].into()).unwrap();
+        row_context
+            .set_value("f".into(), floats[row].into())
+            .unwrap();
+        row_context
+            .set_value("name".into(), names[row].clone())
+            .unwrap();
+        assert_eq!(
+            compiled.eval_with_context(&row_context).as_ref(),
+            Ok(result)
+        );
+    }
+    assert_eq!(results[0], Value::from("<a>1.5"));
+
+    assert_eq!(
+        compiled.eval_columns(&[("i", Column::from(&ints[..2]))], &context),
+        Err(variable_not_found_at("name", Span::new(6, 10)))
+    );
+    assert_eq!(
+        tree.eval_columns(
+            &[
+                ("i", Column::Int(&ints)),
+                ("f", Column::Float(&floats[..3]))
+            ],
+            &context
+        ),
+        Err(EvalexprError::ColumnLengthMismatch {
+            identifier: "f".into(),
+            expected: 4,
+            actual: 3
+        })
+    );
+    assert_eq!(tree.eval_columns(&[], &context), Ok(vec![]));
+    assert!(Column::Int(&[]).is_empty());
+    assert_eq!(Column::Boolean(&flags).len(), 4);
+}
+
+#[test]
+fn test_eval_cached() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted_calls = calls.clone();
+    let mut context = context_map! {
+        "a" => 2,
+        "b" => 3,
+        "c" => 1,
+        "f" => Function::new(move |argument| {
+            counted_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Value::Int(argument.as_int()? * 10))
+        })
+        .pure(),
+    }
+    .unwrap();
+    let tree = build_operator_tree("f(a + b) + f(a + b) * c").unwrap();
+    let mut cache = EvalCache::new();
+    assert!(cache.is_empty());
+
+    // Identical subexpressions are only evaluated once.
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(100)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(!cache.is_empty());
+
+    // Subexpressions that do not depend on changed variables are not evaluated again.
+    context.set_value("c".into(), 2.into()).unwrap();
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(150)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    context.set_value("a".into(), 4.into()).unwrap();
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(210)));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // The cache can be shared between different trees.
+    let other_tree = build_operator_tree("f(a + b) - 1").unwrap();
+    assert_eq!(
+        other_tree.eval_cached(&context, &mut cache),
+        Ok(Value::Int(69))
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Cached evaluation yields the same results as uncached evaluation.
+    for expression in [
+        "if(a > b, a, f(b))",
+        "let x = a * 2; x + f(x)",
+        "g = |x| x + a; g(b) + g(b)",
+        "a > 3 && f(b) == 30",
+        "1 < a < b",
+    ] {
+        let tree = build_operator_tree(expression).unwrap();
+        assert_eq!(
+            tree.eval_cached(&context, &mut cache),
+            tree.eval_with_context(&context),
+            "{}",
+            expression
+        );
+    }
+
+    calls.store(0, Ordering::SeqCst);
+    cache.clear();
+    assert!(cache.is_empty());
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(210)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    // Calls of functions that are not marked as pure and of lambdas are not cached.
+    let impure_calls = Arc::new(AtomicUsize::new(0));
+    let counted_impure_calls = impure_calls.clone();
+    context
+        .set_function(
+            "g".into(),
+            Function::new(move |_| {
+                Ok(Value::from(
+                    counted_impure_calls.fetch_add(1, Ordering::SeqCst) as IntType,
+                ))
+            }),
+        )
+        .unwrap();
+    let tree = build_operator_tree("g() + f(a)").unwrap();
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(40)));
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(41)));
+    assert_eq!(impure_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    context
+        .set_value(
+            "h".into(),
+            build_operator_tree("|| g()").unwrap().eval().unwrap(),
+        )
+        .unwrap();
+    let tree = build_operator_tree("h() * 2").unwrap();
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(4)));
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(6)));
+
+    // Cached values are only reused for identical subexpressions with identical variables.
+    let tree = build_operator_tree("f(a) + f(-a) + f(a)").unwrap();
+    context.set_value("a".into(), (-1).into()).unwrap();
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(-10)));
+    context.set_value("a".into(), 1.into()).unwrap();
+    assert_eq!(tree.eval_cached(&context, &mut cache), Ok(Value::Int(10)));
+
+    // All variables read by the expression are resolved before it is evaluated,
+    // so lazy variables are computed even if they are only read in a branch that is not taken.
+    let computations = Arc::new(AtomicUsize::new(0));
+    let mut lazy_context = LazyContext::new(context_map! { "a" => 1 }.unwrap());
+    let counter = computations.clone();
+    lazy_context.set_lazy_value("expensive".into(), move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(Value::from(5))
+    });
+    let tree = build_operator_tree("if(a > 0, a, expensive)").unwrap();
+    assert_eq!(tree.eval_with_context(&lazy_context), Ok(Value::from(1)));
+    assert_eq!(computations.load(Ordering::SeqCst), 0);
+    assert_eq!(
+        tree.eval_cached(&lazy_context, &mut cache),
+        Ok(Value::from(1))
+    );
+    assert_eq!(computations.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_dependencies() {
+    let dependencies = |expression: &str| -> Vec<String> {
+        build_operator_tree(expression)
+            .unwrap()
+            .dependencies()
+            .into_iter()
+            .map(String::from)
+            .collect()
+    };
+
+    assert_eq!(dependencies("a + b * a"), vec!["a", "b"]);
+    assert_eq!(dependencies("f(x, 2)"), vec!["x"]);
+    assert_eq!(dependencies("y = x; y"), vec!["x", "y"]);
+    assert_eq!(dependencies("1 + 2"), Vec::<String>::new());
+    // Lambda parameters are not taken from the context.
+    assert_eq!(
+        dependencies("g = |x, y| x + y + z; g(a, b)"),
+        vec!["a", "b", "z"]
+    );
+    // Let-bound variables are only taken from the context before their binding.
+    assert_eq!(dependencies("let x = x + 1; x * y"), vec!["x", "y"]);
+    assert_eq!(dependencies("let x = 1; x * y"), vec!["y"]);
+    assert_eq!(
+        dependencies("if(c, obj.field, d)"),
+        vec!["c", "d", "obj.field"]
+    );
+
+    let tree = build_operator_tree("if(c, obj.field, d) + let_x").unwrap();
+    assert!(tree.depends_on("c"));
+    assert!(tree.depends_on("obj"));
+    assert!(tree.depends_on("obj.field"));
+    assert!(tree.depends_on("let_x"));
+    assert!(!tree.depends_on("ob"));
+    assert!(!tree.depends_on("obj.other"));
+    assert!(!tree.depends_on("if"));
+}
+
+#[test]
+fn test_validate() {
+    let validate = |expression: &str| {
+        build_operator_tree(expression)
+            .unwrap()
+            .validate(&["price", "quantity", "obj", "apply"], &["tax"])
+    };
+
+    assert_eq!(validate("price * quantity + tax(price)"), Ok(()));
+    assert_eq!(validate("obj.field + max(price, 1)"), Ok(()));
+    assert_eq!(validate("total = price * 2; total + 1"), Ok(()));
+    assert_eq!(validate("let double = |x| x * 2; double(price)"), Ok(()));
+    assert_eq!(validate("apply(quantity)"), Ok(()));
+    assert_eq!(validate("map((1, 2), |f| f(price))"), Ok(()));
+
+    assert_eq!(
+        validate("prices * quantity + discount(pricee) - prices"),
+        Err(vec![
+            EvalexprError::VariableIdentifierNotFound {
+                identifier: "prices".into(),
+                suggestions: vec!["price".into()],
+                span: Some(Span::new(0, 6)),
+            },
+            EvalexprError::FunctionIdentifierNotFound {
+                identifier: "discount".into(),
+                suggestions: vec!["count".into()]
+            },
+            EvalexprError::VariableIdentifierNotFound {
+                identifier: "pricee".into(),
+                suggestions: vec!["price".into()],
+                span: Some(Span::new(29, 35)),
+            },
+        ])
+    );
+    assert_eq!(
+        validate("taxx(1) + ob.field"),
+        Err(vec![
+            EvalexprError::FunctionIdentifierNotFound {
+                identifier: "taxx".into(),
+                suggestions: vec!["tax".into()]
+            },
+            EvalexprError::VariableIdentifierNotFound {
+                identifier: "ob.field".into(),
+                suggestions: Vec::new(),
+                span: Some(Span::new(10, 18)),
+            },
+        ])
+    );
+    // Let-bound variables are only bound after their binding.
+    assert_eq!(
+        validate("let x = x + 1; x"),
+        Err(vec![EvalexprError::VariableIdentifierNotFound {
+            identifier: "x".into(),
+            suggestions: Vec::new(),
+            span: Some(Span::new(8, 9)),
+        }])
+    );
+}
+
+#[test]
+fn test_infer_return_type() {
+    let hints = TypeHints::new()
+        .with_variable_type("n", ValueType::Int)
+        .with_variable_type("x", ValueType::Float)
+        .with_variable_type("s", ValueType::String)
+        .with_variable_type("b", ValueType::Boolean)
+        .with_variable_type("list", ValueType::Array)
+        .with_function_return_type("score", ValueType::Float)
+        .with_function_return_type("floor", ValueType::String);
+    let infer = |expression: &str| {
+        build_operator_tree(expression)
+            .unwrap()
+            .infer_return_type(&hints)
+    };
+
+    // Constants and variables
+    assert_eq!(infer("1"), Some(ValueType::Int));
+    assert_eq!(infer("\"a\""), Some(ValueType::String));
+    assert_eq!(infer(""), Some(ValueType::Empty));
+    assert_eq!(infer("x"), Some(ValueType::Float));
+    assert_eq!(infer("unknown"), None);
+
+    // Operators
+    assert_eq!(infer("n * 2 - 1"), Some(ValueType::Int));
+    assert_eq!(infer("n / 2 + x"), Some(ValueType::Float));
+    assert_eq!(infer("n ^ 2"), Some(ValueType::Float));
+    assert_eq!(infer("-n"), Some(ValueType::Int));
+    assert_eq!(infer("s + \"!\""), Some(ValueType::String));
+    assert_eq!(infer("s - 1"), None);
+    assert_eq!(infer("-s"), None);
+    assert_eq!(infer("n + unknown"), None);
+    assert_eq!(infer("n == s"), Some(ValueType::Boolean));
+    assert_eq!(infer("unknown == 1"), Some(ValueType::Boolean));
+    assert_eq!(infer("n > x"), Some(ValueType::Boolean));
+    assert_eq!(infer("0 < x <= n"), Some(ValueType::Boolean));
+    assert_eq!(infer("n > s"), None);
+    assert_eq!(infer("0 < s <= n"), None);
+    assert_eq!(infer("b && !(n in list)"), Some(ValueType::Boolean));
+    assert_eq!(infer("b || n"), None);
+    assert_eq!(infer("unknown ?? 1"), None);
+    assert_eq!(infer("() ?? 1"), Some(ValueType::Int));
+    assert_eq!(infer("n, s"), Some(ValueType::Tuple));
+    assert_eq!(infer("[n, s]"), Some(ValueType::Array));
+    assert_eq!(infer("1..n"), Some(ValueType::Tuple));
+    assert_eq!(infer("s[0]"), Some(ValueType::String));
+    assert_eq!(infer("list[0]"), None);
+    assert_eq!(infer("list[0..2]"), Some(ValueType::Array));
+    assert_eq!(infer("|a| a + 1"), Some(ValueType::Lambda));
+
+    // Assignments and chains
+    assert_eq!(infer("n = 2"), Some(ValueType::Empty));
+    assert_eq!(infer("y = 2; y * 1.5"), Some(ValueType::Float));
+    assert_eq!(infer("y = 2; y += 1; y"), Some(ValueType::Int));
+    assert_eq!(infer("y = 2; y /= 0.5; y"), Some(ValueType::Float));
+    assert_eq!(infer("let y = s; y"), Some(ValueType::String));
+    assert_eq!(infer("n = unknown; n"), None);
+
+    // Functions
+    assert_eq!(infer("score(n) * 2"), Some(ValueType::Float));
+    assert_eq!(infer("floor(x)"), Some(ValueType::String));
+    assert_eq!(infer("round(x)"), Some(ValueType::Float));
+    assert_eq!(infer("round(n)"), Some(ValueType::Int));
+    assert_eq!(infer("math::sqrt(n)"), Some(ValueType::Float));
+    assert_eq!(infer("math::rem_euclid(n, 3)"), Some(ValueType::Int));
+    assert_eq!(infer("str::len(s) > 3"), Some(ValueType::Boolean));
+    assert_eq!(infer("str::to_uppercase(s)"), Some(ValueType::String));
+    assert_eq!(infer("is_empty(unknown)"), Some(ValueType::Boolean));
+    assert_eq!(infer("max(n, 3)"), Some(ValueType::Int));
+    assert_eq!(infer("max(n, x)"), None);
+    assert_eq!(infer("sum(n, x)"), Some(ValueType::Float));
+    assert_eq!(infer("sum(list)"), None);
+    assert_eq!(infer("if(b, n, 0)"), Some(ValueType::Int));
+    assert_eq!(infer("if(b, n, s)"), None);
+    assert_eq!(infer("filter(list, |a| a > 1)"), Some(ValueType::Array));
+    assert_eq!(infer("unknown_function(1)"), None);
+
+    // Hints can be taken from a context.
+    let context = context_map! { "a" => 1, "t" => "text" }.unwrap();
+    let hints = TypeHints::from_context(&context);
+    assert_eq!(hints.variable_type("a"), Some(ValueType::Int));
+    assert_eq!(hints.variable_type("t"), Some(ValueType::String));
+    assert_eq!(
+        build_operator_tree("a > 0 && t != \"\"")
+            .unwrap()
+            .infer_return_type(&hints),
+        Some(ValueType::Boolean)
+    );
+}
+
+#[test]
+fn test_incremental_evaluator() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted_calls = calls.clone();
+    let context = context_map! {
+        "a" => 1,
+        "b" => 2,
+        "unused" => 0,
+        "expensive" => Function::new(move |argument| {
+            counted_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Value::Int(argument.as_int()? * 100))
+        })
+        .pure(),
+    }
+    .unwrap();
+    let tree = build_operator_tree("expensive(a) + b").unwrap();
+    let mut evaluator = IncrementalEvaluator::new(tree, context);
+
+    assert_eq!(evaluator.eval(), Ok(Value::Int(102)));
+    assert_eq!(evaluator.eval(), Ok(Value::Int(102)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    evaluator.set_value("unused".into(), 5.into()).unwrap();
+    evaluator.set_value("a".into(), 1.into()).unwrap();
+    assert_eq!(evaluator.eval(), Ok(Value::Int(102)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    evaluator.set_value("b".into(), 3.into()).unwrap();
+    assert_eq!(evaluator.eval(), Ok(Value::Int(103)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    evaluator.set_value("a".into(), 2.into()).unwrap();
+    assert_eq!(evaluator.eval(), Ok(Value::Int(203)));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Changes through the context are picked up as well.
+    evaluator
+        .context_mut()
+        .set_value("b".into(), 4.into())
+        .unwrap();
+    assert_eq!(evaluator.eval(), Ok(Value::Int(204)));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    assert_eq!(
+        evaluator.into_context().get_value("b"),
+        Some(&Value::Int(4))
+    );
+}
+
+#[test]
+fn test_evaluation_policy() {
+    let policy = EvaluationPolicy::new()
+        .with_forbidden_operator(Operator::Exp)
+        .with_forbidden_operator(Operator::Lambda {
+            parameters: Vec::new(),
+        })
+        .with_forbidden_function("math::pow")
+        .with_max_tuple_length(Some(3))
+        .with_max_string_length(Some(5))
+        .with_max_depth(Some(6));
+    assert_eq!(policy.forbidden_functions(), &["math::pow".to_string()]);
+    assert_eq!(policy.max_depth(), Some(6));
+
+    // Parse time
+    let config = ParserConfig::new().with_policy(Some(policy.clone()));
+    assert_eq!(config.policy(), Some(&policy));
+    assert!(build_operator_tree_with_config("1 + 2 * 3", &config).is_ok());
+    assert_eq!(
+        build_operator_tree_with_config("2 ^ 3", &config),
+        Err(EvalexprError::ForbiddenOperator {
+            operator: Operator::Exp
+        })
+    );
+    assert_eq!(
+        build_operator_tree_with_config("f = |x| x", &config),
+        Err(EvalexprError::ForbiddenOperator {
+            operator: Operator::Lambda {
+                parameters: vec!["x".into()]
+            }
+        })
+    );
+    assert_eq!(
+        build_operator_tree_with_config("math::pow(2, 3)", &config),
+        Err(EvalexprError::ForbiddenFunction {
+            identifier: "math::pow".into()
+        })
+    );
+    assert_eq!(
+        build_operator_tree_with_config("(1, 2, 3, 4)", &config),
+        Err(EvalexprError::TupleLengthLimitExceeded {
+            max_tuple_length: 3,
+            actual: 4
+        })
+    );
+    assert_eq!(
+        build_operator_tree_with_config("\"abcdef\"", &config),
+        Err(EvalexprError::StringLengthLimitExceeded {
+            max_string_length: 5,
+            actual: 6
+        })
+    );
+    assert_eq!(
+        build_operator_tree_with_config("1 + 2 * (3 - -4)", &config),
+        Err(EvalexprError::RecursionLimitExceeded { max_depth: 6 })
+    );
+
+    // Evaluation time
+    let options = EvalOptions::new().with_policy(Some(policy));
+    let mut context = context_map! {
+        "s" => "abc",
+        "f" => Function::new(|_| Ok(Value::from((1..=4).map(Value::from).collect::<Vec<_>>()))),
+        "square" => Value::from(Lambda::new(vec!["x".into()], build_operator_tree("x * x").unwrap())),
+    }
+    .unwrap();
+    assert_eq!(
+        eval_with_context_and_options("s + \"de\"", &context, &options),
+        Ok(Value::from("abcde"))
+    );
+    assert_eq!(
+        eval_with_context_and_options("s + s", &context, &options),
+        Err(EvalexprError::StringLengthLimitExceeded {
+            max_string_length: 5,
+            actual: 6
+        })
+    );
+    assert_eq!(
+        eval_with_context_and_options("f()", &context, &options),
+        Err(EvalexprError::TupleLengthLimitExceeded {
+            max_tuple_length: 3,
+            actual: 4
+        })
+    );
+    assert_eq!(
+        eval_with_context_and_options("square(3)", &context, &options),
+        Ok(Value::from(9))
+    );
+    assert_eq!(
+        eval_with_context_mut_and_options("x = 2 ^ 2", &mut context, &options),
+        Err(EvalexprError::ForbiddenOperator {
+            operator: Operator::Exp
+        })
+    );
+    assert_eq!(context.get_value("x"), None);
+    let tree = Node::call("math::pow", vec![Node::constant(2), Node::constant(3)]);
+    assert_eq!(
+        tree.eval_with_context_and_options(&context, &options),
+        Err(EvalexprError::ForbiddenFunction {
+            identifier: "math::pow".into()
+        })
+    );
+    assert_eq!(tree.eval_with_context(&context), Ok(Value::from(8.0)));
+}
+
+#[test]
+fn test_identifier_suggestions() {
+    let mut context = context_map! {
+        "length" => 3,
+        "width" => 4,
+        "weight" => 5.0,
+        "area" => Function::new(|argument| Ok(argument.clone())),
+        "double" => Value::from(Lambda::new(vec!["x".into()], build_operator_tree("x * 2").unwrap())),
+    }
+    .unwrap();
+
+    assert_eq!(
+        eval_with_context("lenght", &context),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "lenght".into(),
+            suggestions: vec!["length".into()],
+            span: Some(Span::new(0, 6)),
+        })
+    );
+    assert_eq!(
+        eval_with_context("wieght + widht", &context),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "wieght".into(),
+            suggestions: vec!["weight".into()],
+            span: Some(Span::new(0, 6)),
+        })
+    );
+    // Suggestions include the context's functions, lambdas and builtin functions.
+    assert_eq!(
+        eval_with_context("aera(1)", &context),
+        Err(EvalexprError::FunctionIdentifierNotFound {
+            identifier: "aera".into(),
+            suggestions: vec!["area".into()],
+        })
+    );
+    assert_eq!(
+        eval_with_context("doubel(1)", &context),
+        Err(EvalexprError::FunctionIdentifierNotFound {
+            identifier: "doubel".into(),
+            suggestions: vec!["double".into()],
+        })
+    );
+    assert_eq!(
+        eval_with_context("math::sqr(4)", &context),
+        Err(EvalexprError::FunctionIdentifierNotFound {
+            identifier: "math::sqr".into(),
+            suggestions: vec!["math::sqrt".into()],
+        })
+    );
+    // Let-bound variables are suggested as well.
+    assert_eq!(
+        eval_with_context("let total = 1; totl", &context),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "totl".into(),
+            suggestions: vec!["total".into()],
+            span: Some(Span::new(15, 19)),
+        })
+    );
+    assert_eq!(
+        eval_with_context("unrelated", &context),
+        Err(variable_not_found_at("unrelated", Span::new(0, 9)))
+    );
+
+    context.set_builtin_functions_disabled(true).unwrap();
+    assert_eq!(
+        eval_with_context("math::sqr(4)", &context),
+        Err(EvalexprError::function_identifier_not_found(
+            "math::sqr".into()
+        ))
+    );
+    assert_eq!(
+        EvalexprError::FunctionIdentifierNotFound {
+            identifier: "mx".into(),
+            suggestions: vec!["max".into(), "min".into()],
+        }
+        .to_string(),
+        "Function identifier is not bound to anything by context: \"mx\". Did you mean \"max\" or \"min\"?"
+    );
+}
+
+#[test]
+fn test_hash_map_context_from_iterator() {
+    let rows = vec![
+        ("price".to_string(), Value::from(10.0)),
+        ("amount".to_string(), Value::from(3)),
+    ];
+    let mut context: HashMapContext = rows.into_iter().collect();
+    assert_eq!(
+        eval_with_context("price * amount", &context),
+        Ok(Value::from(30.0))
+    );
+
+    context.extend(vec![("amount", 4), ("discount", 5)]);
+    assert_eq!(
+        eval_with_context("price * amount - discount", &context),
+        Ok(Value::from(35.0))
+    );
+    // Extending replaces values regardless of their type.
+    context.extend(std::iter::once(("amount", 4.5)));
+    assert_eq!(context.get_value("amount"), Some(&Value::from(4.5)));
+
+    let mut context = HashMapContext::with_capacity(16);
+    assert_eq!(context.iter_variables().count(), 0);
+    context.set_value("a".into(), 1.into()).unwrap();
+    assert_eq!(eval_with_context("a", &context), Ok(Value::from(1)));
+}
+
+#[test]
+fn test_lazy_context() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let computations = Arc::new(AtomicUsize::new(0));
+    let mut context = LazyContext::new(context_map! { "a" => 2, "b" => 5 }.unwrap());
+    let counter = computations.clone();
+    context.set_lazy_value("b".into(), move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(Value::from(3))
+    });
+    context.set_lazy_value("broken".into(), || {
+        Err(EvalexprError::CustomMessage("unavailable".into()))
+    });
+
+    // Lazy variables that are not read are not computed.
+    assert_eq!(eval_with_context("a", &context), Ok(Value::from(2)));
+    assert_eq!(
+        eval_with_context("a < 0 && b > 0", &context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(computations.load(Ordering::SeqCst), 0);
+    assert!(!context.is_computed("b"));
+
+    // Lazy variables shadow the inner context and are computed at most once.
+    assert_eq!(eval_with_context("a * b + b", &context), Ok(Value::from(9)));
+    assert_eq!(eval_with_context("b", &context), Ok(Value::from(3)));
+    assert_eq!(computations.load(Ordering::SeqCst), 1);
+    assert!(context.is_computed("b"));
+    context.reset();
+    assert!(!context.is_computed("b"));
+    assert_eq!(eval_with_context("b", &context), Ok(Value::from(3)));
+    assert_eq!(computations.load(Ordering::SeqCst), 2);
+
+    assert_eq!(
+        eval_with_context("broken", &context),
+        Err(EvalexprError::CustomMessage("unavailable".into()))
+    );
+    assert_eq!(context.get_value("broken"), None);
+    let mut identifiers: Vec<_> = context.iter_variable_identifiers().collect();
+    identifiers.sort_unstable();
+    assert_eq!(identifiers, vec!["a", "b", "b", "broken"]);
+
+    // Assignments replace lazy variables.
+    assert_eq!(
+        eval_with_context_mut("b = 4; a * b", &mut context),
+        Ok(Value::from(8))
+    );
+    assert_eq!(context.remove_value("broken"), Ok(None));
+    assert_eq!(context.remove_value("b"), Ok(Some(Value::from(4))));
+    assert_eq!(context.into_context().get_value("a"), Some(&Value::from(2)));
+}
+
+#[test]
+fn test_fallible_variable_lookup() {
+    use std::borrow::Cow;
+
+    struct RemoteContext;
+
+    impl Context for RemoteContext {
+        fn get_value(&self, _identifier: &str) -> Option<&Value> {
+            None
+        }
+
+        fn try_get_value(&self, identifier: &str) -> EvalexprResult<Option<Cow<'_, Value>>> {
+            match identifier {
+                "offline" => Err(EvalexprError::CustomMessage("connection lost".into())),
+                "remote" => {
+                    let mut map = MapType::new();
+                    map.insert("answer".into(), Value::from(42));
+                    Ok(Some(Cow::Owned(Value::Map(map))))
+                },
+                _ => Ok(None),
+            }
+        }
+
+        fn call_function(&self, identifier: &str, _argument: &Value) -> EvalexprResult<Value> {
+            Err(EvalexprError::function_identifier_not_found(
+                identifier.to_string(),
+            ))
+        }
+
+        fn has_function(&self, _identifier: &str) -> bool {
+            false
+        }
+
+        fn are_builtin_functions_disabled(&self) -> bool {
+            false
+        }
+
+        fn set_builtin_functions_disabled(&mut self, _disabled: bool) -> EvalexprResult<()> {
+            Err(EvalexprError::CustomMessage("unsupported".into()))
+        }
+    }
+
+    let lookup_error = Err(EvalexprError::CustomMessage("connection lost".into()));
+    assert_eq!(
+        eval_with_context("offline + 1", &RemoteContext),
+        lookup_error
+    );
+    assert_eq!(
+        eval_with_context("offline.field", &RemoteContext),
+        lookup_error
+    );
+    assert_eq!(
+        eval_with_context("offline(1)", &RemoteContext),
+        lookup_error
+    );
+    // Owned values are supported as well.
+    assert_eq!(
+        eval_with_context("remote.answer + 1", &RemoteContext),
+        Ok(Value::from(43))
+    );
+    assert_eq!(
+        eval_with_context("len(remote)", &RemoteContext),
+        Ok(Value::from(1))
+    );
+    assert_eq!(
+        eval_with_context("missing", &RemoteContext),
+        Err(variable_not_found_at("missing", Span::new(0, 7)))
+    );
+
+    // Wrapping contexts pass lookup errors on.
+    let local = context_map! { "a" => 1 }.unwrap();
+    let context = ChainedContext::new()
+        .with_layer(&local)
+        .with_layer(&RemoteContext);
+    assert_eq!(eval_with_context("a", &context), Ok(Value::from(1)));
+    assert_eq!(eval_with_context("a + offline", &context), lookup_error);
+    assert_eq!(
+        eval_with_context("let x = 1; x + offline", &context),
+        lookup_error
+    );
+    let tree = build_operator_tree("a + offline").unwrap();
+    assert_eq!(
+        tree.eval_cached(&context, &mut EvalCache::new()),
+        lookup_error
+    );
+    // Simplification keeps variables whose lookup fails.
+    assert_eq!(
+        build_operator_tree("offline * 1")
+            .unwrap()
+            .simplify(&context)
+            .to_string(),
+        "offline * 1"
+    );
+}
+
+#[test]
+fn test_variables_are_borrowed() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let mut context = context_map! {
+        "text" => "a long string",
+        "names" => Value::Array(vec!["first".into(), "second".into()]),
+    }
+    .unwrap();
+    let text_address = match context.get_value("text") {
+        Some(Value::String(text)) => text.as_ptr() as usize,
+        _ => unreachable!(),
+    };
+    let borrowed_calls = Arc::new(AtomicUsize::new(0));
+    let counter = borrowed_calls.clone();
+    context
+        .set_function(
+            "is_borrowed".into(),
+            Function::new(move |argument| {
+                let is_borrowed = matches!(argument, Value::String(text) if text.as_ptr() as usize == text_address);
+                counter.fetch_add(usize::from(is_borrowed), Ordering::SeqCst);
+                Ok(Value::from(is_borrowed))
+            }),
+        )
+        .unwrap();
+
+    // Function arguments read from variables are passed without cloning them.
+    assert_eq!(
+        eval_with_context("is_borrowed(text)", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("is_borrowed text", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context_mut("is_borrowed(text)", &mut context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("is_borrowed(text + \"\")", &context),
+        Ok(Value::from(false))
+    );
+    assert_eq!(borrowed_calls.load(Ordering::SeqCst), 3);
+
+    // Borrowed reads behave like other reads.
+    assert_eq!(
+        eval_with_context("str::len(text)", &context),
+        Ok(Value::from(13))
+    );
+    assert_eq!(
+        eval_with_context("names[1]", &context),
+        Ok(Value::from("second"))
+    );
+    assert_eq!(
+        eval_with_context("names[2]", &context),
+        Err(EvalexprError::IndexOutOfBounds { index: 2, len: 2 })
+    );
+    assert_eq!(
+        eval_with_context("is_borrowed(txt)", &context),
+        Err(EvalexprError::VariableIdentifierNotFound {
+            identifier: "txt".to_string(),
+            suggestions: vec!["text".to_string()],
+            span: Some(Span::new(12, 15)),
+        })
+    );
+    let options = EvalOptions::new().with_max_node_evaluations(Some(2));
+    assert_eq!(
+        eval_with_context_and_options("is_borrowed(text)", &context, &options),
+        Err(EvalexprError::BudgetExceeded {
+            max_node_evaluations: 2
+        })
+    );
+    let options = EvalOptions::new().with_policy(Some(
+        EvaluationPolicy::new().with_max_string_length(Some(4)),
+    ));
+    assert_eq!(
+        eval_with_context_and_options("str::len(text)", &context, &options),
+        Err(EvalexprError::StringLengthLimitExceeded {
+            max_string_length: 4,
+            actual: 13
+        })
+    );
+}
+
+#[test]
+fn test_env_context() {
+    std::env::set_var("EVALEXPR_TEST_ENV_INT", "-12");
+    std::env::set_var("EVALEXPR_TEST_ENV_FLOAT", "2.5");
+    std::env::set_var("EVALEXPR_TEST_ENV_BOOL", "true");
+    std::env::set_var("EVALEXPR_TEST_ENV_STRING", "nan");
+    std::env::set_var("EVALEXPR_TEST_ENV_EMPTY_set", "no");
+
+    let context = EnvContext::new();
+    assert_eq!(
+        eval_with_context("EVALEXPR_TEST_ENV_INT", &context),
+        Ok(Value::from(-12))
+    );
+    let context = context.with_prefix("EVALEXPR_TEST_ENV_");
+    assert_eq!(context.prefix(), "EVALEXPR_TEST_ENV_");
+    assert_eq!(
+        eval_with_context("INT * FLOAT", &context),
+        Ok(Value::from(-30.0))
+    );
+    assert_eq!(
+        eval_with_context("BOOL && STRING == \"nan\"", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("INT_set && !MISSING_set", &context),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("EMPTY_set", &context),
+        Ok(Value::from("no"))
+    );
+    assert_eq!(
+        eval_with_context("MISSING", &context),
+        Err(variable_not_found_at("MISSING", Span::new(0, 7)))
+    );
+    assert_eq!(
+        eval_with_context("EVALEXPR_TEST_ENV_INT", &context),
+        Err(variable_not_found_at(
+            "EVALEXPR_TEST_ENV_INT",
+            Span::new(0, 21)
+        ))
+    );
+    assert_eq!(
+        eval_with_context("max(INT, 0)", &context),
+        Ok(Value::from(0))
+    );
+
+    let context = context.with_type_coercion(false);
+    assert!(!context.type_coercion());
+    assert_eq!(
+        eval_with_context("INT + BOOL", &context),
+        Ok(Value::from("-12true"))
+    );
+}
+
+#[test]
+fn test_callback_context() {
+    use std::{cell::Cell, collections::HashMap};
+
+    let position = Cell::new(3.0);
+    let functions = HashMap::from([(
+        "double".to_string(),
+        Function::new(|argument| Ok(Value::from(argument.as_float()? * 2.0))),
+    )]);
+    let mut context = CallbackContext::new(
+        |identifier| match identifier {
+            "position" => Some(Value::from(position.get())),
+            "name" => Some(Value::from("crate")),
+            _ => None,
+        },
+        |identifier| functions.get(identifier),
+    );
+
+    assert_eq!(
+        eval_with_context("double(position)", &context),
+        Ok(Value::from(6.0))
+    );
+    // The closures see the current state of the storage.
+    position.set(5.0);
+    assert_eq!(
+        eval_with_context("double(position) + 1", &context),
+        Ok(Value::from(11.0))
+    );
+    assert_eq!(
+        eval_with_context("if(len(name) > 3, position, 0.0)", &context),
+        Ok(Value::from(5.0))
+    );
+    assert_eq!(
+        eval_with_context("velocity", &context),
+        Err(variable_not_found_at("velocity", Span::new(0, 8)))
+    );
+    assert!(matches!(
+        eval_with_context("halve(position)", &context),
+        Err(EvalexprError::FunctionIdentifierNotFound { .. })
+    ));
+    assert!(context.has_function("double"));
+    assert!(!context.has_function("halve"));
+    assert_eq!(context.get_value("position"), None);
+
+    assert!(!context.are_builtin_functions_disabled());
+    context.set_builtin_functions_disabled(true).unwrap();
+    assert!(matches!(
+        eval_with_context("len(name)", &context),
+        Err(EvalexprError::FunctionIdentifierNotFound { .. })
+    ));
+}
+
+#[test]
+fn test_format_builtin() {
+    let context = context_map! { "name" => "apple", "count" => 2, "items" => Value::Array(vec![1.into(), "a".into()]) }.unwrap();
+    assert_eq!(
+        eval_with_context("format(\"{} {}s\", count, name)", &context),
+        Ok(Value::from("2 apples"))
+    );
+    assert_eq!(
+        eval_with_context("format(\"{1}-{0}-{1}\", 1, 2.5)", &context),
+        Ok(Value::from("2.5-1-2.5"))
+    );
+    assert_eq!(
+        eval_with_context("format(\"{}, {}, {}\", items, (true, \"x\"), ())", &context),
+        Ok(Value::from("[1, \"a\"], (true, \"x\"), ()"))
+    );
+    assert_eq!(
+        eval_with_context("format(\"{{}} {}\", name)", &context),
+        Ok(Value::from("{} apple"))
+    );
+    assert_eq!(eval("format(\"plain\")"), Ok(Value::from("plain")));
+
+    let invalid = |message: &str| {
+        Err(EvalexprError::InvalidFormatString {
+            format_string: "{} {".into(),
+            message: message.into(),
+        })
+    };
+    assert_eq!(eval("format(\"{} {\", 1)"), invalid("unclosed `{`"));
+    assert_eq!(
+        eval("format(\"{} {}\", 1)"),
+        Err(EvalexprError::InvalidFormatString {
+            format_string: "{} {}".into(),
+            message: "argument 1 is missing".into(),
+        })
+    );
+    assert_eq!(
+        eval("format(\"{}\", 1, 2)"),
+        Err(EvalexprError::InvalidFormatString {
+            format_string: "{}".into(),
+            message: "argument 1 is not used".into(),
+        })
+    );
+    assert_eq!(
+        eval("format(\"{x}\", 1)"),
+        Err(EvalexprError::InvalidFormatString {
+            format_string: "{x}".into(),
+            message: "invalid placeholder `{x}`".into(),
+        })
+    );
+    assert_eq!(
+        eval("format(\"a}\")"),
+        Err(EvalexprError::InvalidFormatString {
+            format_string: "a}".into(),
+            message: "unmatched `}`".into(),
+        })
+    );
+    assert_eq!(
+        eval("format(1, 2)"),
+        Err(EvalexprError::expected_string(Value::from(1)))
+    );
+    assert_eq!(
+        eval("format()"),
+        Err(EvalexprError::expected_string(Value::Empty))
+    );
+    assert_eq!(
+        eval("format(\"{} {}\", 1)").unwrap_err().to_string(),
+        "Format string \"{} {}\" is invalid: argument 1 is missing"
+    );
+}
+
+#[test]
+fn test_duration_and_timestamp_parse_errors() {
+    let invalid_duration = |duration: &str, message: &str| {
+        Err(EvalexprError::InvalidDuration {
+            duration: duration.into(),
+            message: message.into(),
+        })
+    };
+    assert_eq!(
+        eval(r#"duration("")"#),
+        invalid_duration("", "expected an amount followed by a unit")
+    );
+    assert_eq!(
+        eval(r#"duration("5")"#),
+        invalid_duration("5", "expected a unit after `5`")
+    );
+    assert_eq!(
+        eval(r#"duration("5 parsecs")"#),
+        invalid_duration("5 parsecs", "unknown unit `parsecs`")
+    );
+    assert_eq!(
+        eval(r#"duration("1h m")"#),
+        invalid_duration("1h m", "expected an amount before `m`")
+    );
+    assert_eq!(
+        eval(r#"duration("1.2.3s")"#),
+        invalid_duration("1.2.3s", "invalid amount `1.2.3`")
+    );
+    assert_eq!(
+        eval(r#"duration("99999999999999999999w")"#),
+        invalid_duration("99999999999999999999w", "out of range")
+    );
+    assert_eq!(
+        eval("duration(5)"),
+        Err(EvalexprError::expected_string(Value::from(5)))
+    );
+
+    let invalid_datetime = |datetime: &str, message: &str| {
+        Err(EvalexprError::InvalidDateTime {
+            datetime: datetime.into(),
+            message: message.into(),
+        })
+    };
+    let format_message = "expected a date and time like `2024-01-01T12:00:00Z`";
+    for datetime in [
+        "2024-01-01",
+        "2024-01-01T12:00:00",
+        "2024-01-01T12:00:00.Z",
+        "2024/01/01T12:00:00Z",
+        "2024-01-01T12:00:00+0100",
+        "2024-01-01T12:00:00Zx",
+    ] {
+        assert_eq!(
+            eval(&format!("timestamp(\"{}\")", datetime)),
+            invalid_datetime(datetime, format_message)
+        );
+    }
+    assert_eq!(
+        eval(r#"timestamp("2023-02-29T00:00:00Z")"#),
+        invalid_datetime("2023-02-29T00:00:00Z", "the date does not exist")
+    );
+    assert_eq!(
+        eval(r#"timestamp("2024-01-01T24:00:00Z")"#),
+        invalid_datetime("2024-01-01T24:00:00Z", "the time does not exist")
+    );
+    assert_eq!(
+        eval(r#"timestamp("2024-01-01T12:00:00+24:00")"#),
+        invalid_datetime(
+            "2024-01-01T12:00:00+24:00",
+            "the timezone offset is out of range"
+        )
+    );
+}
+
+#[test]
+#[cfg(not(feature = "chrono"))]
+fn test_duration_and_timestamp_as_numbers() {
+    assert_eq!(eval(r#"duration("5m 30s")"#), Ok(Value::Int(330)));
+    assert_eq!(
+        eval(r#"duration("1 week 2days 3 hours 4min")"#),
+        Ok(Value::Int(788_640))
+    );
+    assert_eq!(eval(r#"duration(" -1.5h ")"#), Ok(Value::Int(-5400)));
+    assert_eq!(eval(r#"duration("1s 500ms")"#), Ok(Value::Float(1.5)));
+    assert_eq!(eval(r#"duration("-250us")"#), Ok(Value::Float(-0.00025)));
+    assert_eq!(eval(r#"duration("0s")"#), Ok(Value::Int(0)));
+
+    assert_eq!(
+        eval(r#"timestamp("1970-01-01T00:00:00Z")"#),
+        Ok(Value::Int(0))
+    );
+    assert_eq!(
+        eval(r#"timestamp("2024-02-29T12:30:00Z")"#),
+        Ok(Value::Int(1_709_209_800))
+    );
+    assert_eq!(
+        eval(r#"timestamp("2024-02-29 14:30:00+02:00")"#),
+        Ok(Value::Int(1_709_209_800))
+    );
+    assert_eq!(
+        eval(r#"timestamp("1969-12-31t23:59:59.25z")"#),
+        Ok(Value::Float(-0.75))
+    );
+
+    let context = context_map! { "age" => 1200, "created" => 1_704_067_200 }.unwrap();
+    assert_eq!(
+        eval_with_context(
+            r#"age < duration("15m") || created + duration("1d") < timestamp("2024-01-01T12:00:00Z")"#,
+            &context
+        ),
+        Ok(Value::from(false))
+    );
+}
+
+#[test]
+fn test_interpreter() {
+    let mut interpreter = Interpreter::new();
+    assert_eq!(interpreter.eval_line(""), Ok(Value::Empty));
+    assert_eq!(interpreter.eval_line("// nothing"), Ok(Value::Empty));
+    assert_eq!(interpreter.eval_line("x = 2; y = x + 1"), Ok(Value::Empty));
+    assert_eq!(
+        interpreter.eval_line("square = |n| n * n"),
+        Ok(Value::Empty)
+    );
+    assert_eq!(interpreter.eval_line("square(y) + x"), Ok(Value::from(11)));
+
+    // Let-bindings only last for their line.
+    assert_eq!(interpreter.eval_line("let z = 4; z"), Ok(Value::from(4)));
+    assert!(interpreter.eval_line("z").is_err());
+
+    // Assignments before an error are kept.
+    assert!(interpreter.eval_line("x = 5; x + \"a\" * 2").is_err());
+    assert!(interpreter.eval_line("x +").is_err());
+    assert_eq!(interpreter.eval_line("x"), Ok(Value::from(5)));
+
+    let identifiers: Vec<_> = interpreter
+        .bindings()
+        .into_iter()
+        .map(|(identifier, _)| identifier)
+        .collect();
+    assert_eq!(identifiers, ["square", "x", "y"]);
+
+    let mut context = HashMapContext::new();
+    context
+        .set_function(
+            "double".into(),
+            Function::new(|argument| Ok(Value::from(argument.as_int()? * 2))),
+        )
+        .unwrap();
+    let mut interpreter = Interpreter::with_context(context);
+    interpreter
+        .context_mut()
+        .set_value("a".into(), Value::from(3))
+        .unwrap();
+    assert_eq!(
+        interpreter.eval_line("b = double(a); b"),
+        Ok(Value::from(6))
+    );
+    assert_eq!(
+        interpreter.into_context().get_value("b"),
+        Some(&Value::from(6))
+    );
+}
+
+#[test]
+fn test_eval_template() {
+    let context = context_map! {
+        "name" => "Bob",
+        "price" => 2.5,
+        "qty" => 4,
+        "items" => Value::from(vec![Value::from(1), Value::from(2)]),
+    }
+    .unwrap();
+
+    assert_eq!(
+        eval_template("Hello {name}, you owe {price * qty} EUR", &context),
+        Ok("Hello Bob, you owe 10 EUR".to_string())
+    );
+    assert_eq!(eval_template("", &context), Ok("".to_string()));
+    assert_eq!(
+        eval_template("no expressions", &context),
+        Ok("no expressions".to_string())
+    );
+    assert_eq!(eval_template("{qty}{qty}", &context), Ok("44".to_string()));
+    assert_eq!(eval_template("{items}", &context), Ok("(1, 2)".to_string()));
+    assert_eq!(eval_template("{\"a}b\"}", &context), Ok("a}b".to_string()));
+    assert_eq!(
+        eval_template("{\"\\\"}\"}", &context),
+        Ok("\"}".to_string())
+    );
+    assert_eq!(eval_template("ä{name}ö", &context), Ok("äBobö".to_string()));
+    assert_eq!(
+        eval_template("{{name}} }} } {{", &context),
+        Ok("{name} } } {".to_string())
+    );
+
+    assert_eq!(
+        eval_template_with_delimiters("${qty} {qty} ${${qty}", "${", "}", &context),
+        Ok("4 {qty} ${qty}".to_string())
+    );
+    assert_eq!(
+        eval_template_with_delimiters("<<qty + 1>> >>>> <<<<", "<<", ">>", &context),
+        Ok("5 >> <<".to_string())
+    );
+    assert_eq!(
+        eval_template_with_delimiters("|qty| and |name| ||", "|", "|", &context),
+        Ok("4 and Bob |".to_string())
+    );
+
+    assert_eq!(
+        eval_template("Hello {name}, you owe {price * qty EUR", &context),
+        Err(EvalexprError::UnterminatedTemplateExpression {
+            span: Span::new(22, 38)
+        })
+    );
+    match eval_template("Hello {name}, you owe {price * (qty} EUR", &context) {
+        Err(EvalexprError::TemplateExpressionFailed { span, error }) => {
+            assert_eq!(span, Span::new(23, 35));
+            assert_eq!(
+                *error,
+                EvalexprError::UnmatchedLBrace {
+                    span: Span::new(31, 32)
+                }
+            );
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+    match eval_template("Total: {unknown}", &context) {
+        Err(EvalexprError::TemplateExpressionFailed { span, error }) => {
+            assert_eq!(span, Span::new(8, 15));
+            assert!(matches!(
+                *error,
+                EvalexprError::VariableIdentifierNotFound { .. }
+            ));
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
@@ -21,3 +21,79 @@ fn test_random_errors() {
     assert!(eval("random(9)").is_err());
     assert!(eval("random(\"a\", \"b\")").is_err());
 }
+
+#[test]
+fn test_random_int_and_choice() {
+    for _ in 0..100 {
+        assert_expr("random_int(1, 6) >= 1 && random_int(1, 6) <= 6");
+        assert_expr("random_int(3, -3) >= -3 && random_int(3, -3) <= 3");
+        assert_expr("random_int(7, 7) == 7");
+        assert_expr("contains((1, 2, 3), random_choice(1, 2, 3))");
+        assert_expr("contains((\"a\", \"b\"), random_choice([\"a\", \"b\"]))");
+        assert_expr("random_choice(5) == 5");
+    }
+
+    assert_eq!(
+        eval("random_int(1)"),
+        Err(EvalexprError::expected_tuple(Value::from(1)))
+    );
+    assert_eq!(
+        eval("random_int(1, 2.5)"),
+        Err(EvalexprError::expected_int(Value::from(2.5)))
+    );
+    assert_eq!(
+        eval("random_choice()"),
+        Err(EvalexprError::WrongFunctionArgumentAmountRange {
+            actual: 0,
+            expected: 1..=usize::MAX
+        })
+    );
+    assert_eq!(
+        eval("random_choice([])"),
+        Err(EvalexprError::WrongFunctionArgumentAmountRange {
+            actual: 0,
+            expected: 1..=usize::MAX
+        })
+    );
+}
+
+#[test]
+fn test_random_seed() {
+    let expression = "(random(), random_int(0, 1000000), random_choice(1, 2, 3, 4, 5, 6, 7, 8, 9))";
+    let context = HashMapContext::new();
+    let seeded = |seed| {
+        let options = EvalOptions::new().with_random_seed(Some(seed));
+        eval_with_context_and_options(expression, &context, &options).unwrap()
+    };
+
+    assert_eq!(seeded(42), seeded(42));
+    assert_ne!(seeded(42), seeded(43));
+    assert_eq!(EvalOptions::new().random_seed(), None);
+    assert_eq!(
+        EvalOptions::new().with_random_seed(Some(42)).random_seed(),
+        Some(42)
+    );
+
+    // The seed is used by all evaluation functions that take options.
+    let options = EvalOptions::new().with_random_seed(Some(42));
+    let mut mutable_context = HashMapContext::new();
+    let tree = build_operator_tree(expression).unwrap();
+    assert_eq!(
+        tree.eval_with_context_mut_and_options(&mut mutable_context, &options),
+        Ok(seeded(42))
+    );
+    assert_eq!(
+        tree.eval_with_context_and_options(&context, &options),
+        Ok(seeded(42))
+    );
+
+    // Functions of the context take precedence over the seeded builtin functions.
+    let context = context_map! {
+        "random" => Function::new(|_| Ok(Value::from(0.5))),
+    }
+    .unwrap();
+    assert_eq!(
+        eval_with_context_and_options("random()", &context, &options),
+        Ok(Value::from(0.5))
+    );
+}
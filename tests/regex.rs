@@ -29,3 +29,22 @@ fn test_regex_functions() {
         Ok(Value::String("foobar".to_owned()))
     );
 }
+
+#[test]
+fn test_regex_function_argument_errors() {
+    assert_eq!(
+        eval("str::regex_matches(\"foobar\")"),
+        Err(EvalexprError::expected_tuple(Value::from("foobar")))
+    );
+    assert_eq!(
+        eval("str::regex_replace(\"foobar\", \"o\")"),
+        Err(EvalexprError::expected_fixed_len_tuple(
+            3,
+            Value::from(vec![Value::from("foobar"), Value::from("o")])
+        ))
+    );
+    assert_eq!(
+        eval("str::regex_matches(\"foobar\", 1)"),
+        Err(EvalexprError::expected_string(Value::from(1)))
+    );
+}
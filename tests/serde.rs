@@ -1,7 +1,7 @@
 #![cfg(not(tarpaulin_include))]
 #![cfg(feature = "serde")]
 
-use evalexpr::{build_operator_tree, Node};
+use evalexpr::*;
 
 #[test]
 fn test_serde() {
@@ -14,6 +14,16 @@ fn test_serde() {
     }
 }
 
+#[test]
+fn test_serde_serialize() {
+    let tree = build_operator_tree("21^(2*2)--3>5||!true").unwrap();
+    let serialized = ron::ser::to_string(&tree).unwrap();
+    assert_eq!(serialized, "\"21 ^ (2 * 2) - -3 > 5 || !true\"");
+
+    let deserialized: Node = ron::de::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.eval(), tree.eval());
+}
+
 #[test]
 fn test_serde_errors() {
     assert_eq!(
@@ -27,7 +37,8 @@ fn test_serde_errors() {
         ron::de::from_str::<Node>("\"&\""),
         Err(ron::Error {
             code: ron::de::ErrorCode::Message(
-                "Found a partial token '&' that should be followed by another partial token."
+                "Found a partial token '&' at position 0 that should be followed by another \
+                 partial token."
                     .to_owned()
             ),
             position: ron::de::Position { line: 0, col: 0 }
@@ -41,3 +52,135 @@ fn test_serde_errors() {
         ""
     );
 }
+
+#[derive(Debug, serde_derive::Serialize, serde_derive::Deserialize)]
+struct Structured {
+    #[serde(with = "evalexpr::structured")]
+    node: Node,
+}
+
+#[test]
+fn test_serde_structured() {
+    let tree = build_operator_tree("a = 1 < b <= 3; if(a, [1.5, ()], -c)").unwrap();
+    let serialized = ron::ser::to_string(&Structured { node: tree.clone() }).unwrap();
+    let deserialized: Structured = ron::de::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.node, tree);
+
+    // Constants that have no literal are kept as well.
+    let mut map = MapType::new();
+    map.insert("key".into(), Value::from(f64::NAN));
+    let tree = Node::index(Node::constant(Value::Map(map)), Node::constant("key"));
+    let serialized = ron::ser::to_string(&Structured { node: tree.clone() }).unwrap();
+    let deserialized: Structured = ron::de::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.node, tree);
+    assert!(deserialized
+        .node
+        .eval()
+        .unwrap()
+        .as_float()
+        .unwrap()
+        .is_nan());
+
+    // Children can be omitted for leaves.
+    let deserialized: Structured = ron::de::from_str(
+        "(node: (operator: Add, children: [(operator: Const(value: Int(1))), (operator: \
+         VariableIdentifierRead(identifier: \"x\"))]))",
+    )
+    .unwrap();
+    assert_eq!(deserialized.node.to_string(), "1 + x");
+
+    let error = ron::de::from_str::<Structured>(
+        "(node: (operator: Add, children: [(operator: Const(value: Int(1)))]))",
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.code,
+        ron::de::ErrorCode::Message(
+            EvalexprError::WrongOperatorArgumentAmount {
+                expected: 2,
+                actual: 1
+            }
+            .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_serde_values_and_context() {
+    let values = [
+        Value::from("s"),
+        Value::from(1),
+        Value::from(1.5),
+        Value::from(true),
+        Value::from(()),
+        Value::Tuple(vec![Value::from(1), Value::from("t")]),
+        Value::Array(vec![Value::Array(vec![]), Value::from(2)]),
+        Value::Map(MapType::from([("key".to_owned(), Value::from(false))])),
+    ];
+    for value in values {
+        let serialized = ron::ser::to_string(&value).unwrap();
+        assert_eq!(ron::de::from_str::<Value>(&serialized), Ok(value));
+    }
+
+    let mut context = context_map! {
+        "a" => 2,
+        "b" => Value::Array(vec![Value::from(3)]),
+        "f" => Function::new(|argument| Ok(argument.clone())),
+    }
+    .unwrap();
+    context.set_builtin_functions_disabled(true).unwrap();
+    let serialized = ron::ser::to_string(&context).unwrap();
+    let mut deserialized: HashMapContext = ron::de::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.get_value("a"), Some(&Value::from(2)));
+    assert!(deserialized.are_builtin_functions_disabled());
+    // Functions are not serialized.
+    assert!(!deserialized.has_function("f"));
+
+    // Together with expressions, the whole environment can be stored.
+    let expression: Node = ron::de::from_str("\"a = a + b[0]; a\"").unwrap();
+    assert_eq!(
+        expression.eval_with_context_mut(&mut deserialized),
+        Ok(Value::from(5))
+    );
+
+    // Missing fields are set to their defaults.
+    let context: HashMapContext = ron::de::from_str("(variables: {\"x\": Float(0.5)})").unwrap();
+    assert_eq!(context.get_value("x"), Some(&Value::from(0.5)));
+    assert!(!context.are_builtin_functions_disabled());
+}
+
+#[test]
+#[cfg(feature = "bincode")]
+fn test_binary() {
+    let tree =
+        build_operator_tree("a = 1 < b <= 3; f = |x| x * 2; if(a, [1.5, ()], f(\"c\"))").unwrap();
+    let bytes = tree.to_binary().unwrap();
+    assert_eq!(&bytes[..4], b"EVXB");
+    assert_eq!(Node::from_binary(&bytes), Ok(tree));
+
+    // Constants that have no literal are kept as well.
+    let mut map = MapType::new();
+    map.insert("key".into(), Value::from(i64::MAX));
+    let tree = Node::array(vec![
+        Node::constant(Value::Map(map)),
+        #[cfg(feature = "decimal")]
+        Node::constant(rust_decimal::Decimal::new(15, 1)),
+        #[cfg(feature = "chrono")]
+        Node::constant(chrono::Duration::minutes(90)),
+    ]);
+    assert_eq!(Node::from_binary(&tree.to_binary().unwrap()), Ok(tree));
+
+    let mut other_version = bytes.clone();
+    other_version[4] += 1;
+    for invalid in [&bytes[..3], &bytes[..bytes.len() - 1], &other_version] {
+        assert!(matches!(
+            Node::from_binary(invalid),
+            Err(EvalexprError::InvalidBinaryFormat { .. })
+        ));
+    }
+    assert_eq!(
+        Node::from_binary(&other_version).unwrap_err().to_string(),
+        "Invalid binary operator tree: the version of the format is 2, but only version 1 is \
+         supported"
+    );
+}
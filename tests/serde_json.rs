@@ -0,0 +1,96 @@
+#![cfg(feature = "serde_json")]
+
+use evalexpr::*;
+use serde_json::json;
+use std::convert::TryFrom;
+
+#[test]
+fn test_json_to_value() {
+    assert_eq!(Value::from(json!(null)), Value::Empty);
+    assert_eq!(Value::from(json!(true)), Value::from(true));
+    assert_eq!(Value::from(json!(-3)), Value::from(-3));
+    assert_eq!(Value::from(json!(2.5)), Value::from(2.5));
+    assert_eq!(Value::from(json!("s")), Value::from("s"));
+    assert_eq!(
+        Value::from(json!([1, [], "a"])),
+        Value::Array(vec![Value::from(1), Value::Array(vec![]), Value::from("a")])
+    );
+    assert_eq!(
+        Value::from(json!({ "a": { "b": null } })),
+        Value::Map(MapType::from([(
+            "a".to_owned(),
+            Value::Map(MapType::from([("b".to_owned(), Value::Empty)]))
+        )]))
+    );
+    #[cfg(not(feature = "bigint"))]
+    assert_eq!(Value::from(json!(u64::MAX)), Value::from(u64::MAX as f64));
+    #[cfg(feature = "bigint")]
+    assert_eq!(
+        Value::from(json!(u64::MAX)),
+        Value::from(BigIntType::from(u64::MAX))
+    );
+}
+
+#[test]
+fn test_value_to_json() {
+    let value = Value::Tuple(vec![
+        Value::Empty,
+        Value::from(1),
+        Value::from(-0.5),
+        Value::from("s"),
+        Value::Array(vec![Value::from(false)]),
+        Value::Map(MapType::from([("key".to_owned(), Value::from(2))])),
+    ]);
+    assert_eq!(
+        serde_json::Value::try_from(value),
+        Ok(json!([null, 1, -0.5, "s", [false], { "key": 2 }]))
+    );
+    assert_eq!(
+        serde_json::Value::try_from(Value::Array(vec![Value::from(f64::INFINITY)])),
+        Err(EvalexprError::not_json_representable(Value::from(
+            f64::INFINITY
+        )))
+    );
+
+    #[cfg(feature = "decimal")]
+    assert_eq!(
+        serde_json::Value::try_from(Value::Decimal("19.99".parse().unwrap())),
+        Ok(json!(19.99))
+    );
+
+    let json = json!({ "a": [1, 2.5, "x", { "b": true }], "c": null });
+    assert_eq!(
+        serde_json::Value::try_from(Value::from(json.clone())),
+        Ok(json)
+    );
+}
+
+#[test]
+fn test_context_from_json() {
+    let json = json!({
+        "user": { "name": "Ann", "age": 31 },
+        "limits": [18, 65],
+        "verbose": false,
+    });
+    let mut context = HashMapContext::from_json(json).unwrap();
+    assert_eq!(
+        eval_with_context(
+            "user.age >= limits[0] && user.age < limits[1] && !verbose",
+            &context
+        ),
+        Ok(Value::from(true))
+    );
+    assert_eq!(
+        eval_with_context("str::len(user.name)", &context),
+        Ok(Value::from(3))
+    );
+    // The context is type-safe as usual.
+    assert!(eval_with_context_mut("verbose = 1", &mut context).is_err());
+
+    assert_eq!(
+        HashMapContext::from_json(json!([1])).map(|_| ()),
+        Err(EvalexprError::expected_map(Value::Array(vec![
+            Value::from(1)
+        ])))
+    );
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "wasm")]
+
+use evalexpr::wasm::{compile, evaluate};
+
+// Errors are JavaScript values, which cannot be constructed outside of WebAssembly, so only successes are tested.
+
+#[test]
+fn test_wasm_evaluate() {
+    assert_eq!(
+        evaluate("price * qty", r#"{ "price": 2.5, "qty": 4 }"#).ok(),
+        Some("10.0".to_string())
+    );
+    assert_eq!(
+        evaluate("(item.name, [1, 2], ())", r#"{ "item": { "name": "a" } }"#).ok(),
+        Some(r#"["a",[1,2],null]"#.to_string())
+    );
+}
+
+#[test]
+fn test_wasm_compile() {
+    let expression = compile("price*qty > limit && price > 1").ok().unwrap();
+    assert_eq!(expression.variables(), ["limit", "price", "qty"]);
+    assert_eq!(expression.to_string(), "price * qty > limit && price > 1");
+    assert_eq!(
+        expression
+            .evaluate(r#"{ "price": 2.5, "qty": 4, "limit": 5 }"#)
+            .ok(),
+        Some("true".to_string())
+    );
+    assert_eq!(
+        expression
+            .evaluate(r#"{ "price": 2.5, "qty": 1, "limit": 5 }"#)
+            .ok(),
+        Some("false".to_string())
+    );
+}